@@ -0,0 +1,3 @@
+"""Aleo Poseidon hashes, Schnorr proofs, range proofs and KZG commitments implemented in Rust"""
+from .aleo_python import *
+from .envelopes import *
@@ -0,0 +1,90 @@
+# Type stubs of the aleo_python extension module, generated from the Rust bindings by
+# src/stubs.rs
+from typing import Any, Dict, List, Tuple, Union
+
+StructValue = Union[
+    None,
+    bool,
+    int,
+    bytes,
+    str,
+    List["StructValue"],
+    Tuple["StructValue", ...],
+    Dict[str, "StructValue"],
+]
+
+def hash_int(a: int) -> str:
+    """Takes a poseiden hash of an integer and returns the hash as a string"""
+    ...
+
+def hash_bytes(data: bytes) -> str:
+    """Takes a poseiden hash of a byte string and returns the hash as a string"""
+    ...
+
+def hash_fields(fields: List[str]) -> str:
+    """Takes a poseiden hash of field elements written like "5field", e.g. earlier hashes, and returns
+    the hash as a string"""
+    ...
+
+def hash_field_list(integers: List[int]) -> str:
+    """Takes a poseiden hash of a list of non-negative integers below the field modulus and returns
+    the hash as a string"""
+    ...
+
+def hash_struct(value: StructValue) -> str:
+    """Takes a poseiden hash of the canonical encoding of a nested structure of dicts with str keys,
+    lists, tuples, ints, bools, bytes, strs and None, and returns the hash as a string"""
+    ...
+
+def generate_schnorr_key() -> bytes:
+    """Generate a random Schnorr private key"""
+    ...
+
+def schnorr_prove(private_key: bytes) -> Tuple[bytes, bytes]:
+    """Prove knowledge of a private key, returning the public key and the proof"""
+    ...
+
+def schnorr_verify(public_key: bytes, proof: bytes) -> bool:
+    """Verify a proof of knowledge of the private key of a public key"""
+    ...
+
+def prove_range(value: int, bits: int, context: bytes) -> Tuple[bytes, RangeProof]:
+    """Commit to a value and prove it lies within [0, 2^bits), returning the commitment and the
+    proof. The proof is bound to the context, which the verifier must pass in too."""
+    ...
+
+def verify_range(commitment: bytes, bits: int, proof: RangeProof, context: bytes) -> bool:
+    """Verify that a commitment commits to a value within [0, 2^bits)"""
+    ...
+
+def describe_schnorr(public_key: bytes, proof: bytes) -> Dict[str, Any]:
+    """Describe a Schnorr proof for display in a notebook: its components, the transcript the
+    challenge is derived from and both sides of the verification equation as hex"""
+    ...
+
+def describe_range_proof(commitment: bytes, bits: int, proof: RangeProof, context: bytes) -> Dict[str, Any]:
+    """Describe a range proof for display in a notebook: the sizes of the bit commitments and their
+    proofs, and the recomposition of the bit commitments as hex"""
+    ...
+
+class RangeProof:
+    """Proof that a Pedersen commitment commits to a value within [0, 2^bits)"""
+
+class KzgSetup:
+    """KZG reference string for committing to polynomials and proving their evaluations"""
+
+    def __init__(self, max_degree: int) -> None:
+        """Generate a reference string for polynomials of up to `max_degree`"""
+        ...
+
+    def commit(self, coefficients: List[int]) -> bytes:
+        """Commit to a polynomial given by coefficients in ascending order of degree"""
+        ...
+
+    def prove_evaluation(self, coefficients: List[int], point: int) -> Tuple[bytes, bytes]:
+        """Evaluate a polynomial at a point, returning the value and the proof of the evaluation"""
+        ...
+
+    def verify_evaluation(self, commitment: bytes, point: int, value: bytes, proof: bytes) -> bool:
+        """Verify that a committed polynomial evaluates to `value` at `point`"""
+        ...
@@ -0,0 +1,81 @@
+"""Statements of the aleo_python verifiers bundled with their proofs
+
+Generated from the Rust bindings by src/stubs.rs.
+"""
+from dataclasses import dataclass
+
+from .aleo_python import KzgSetup, RangeProof, schnorr_verify, verify_range
+
+__all__ = [
+    "SchnorrStatement",
+    "SchnorrEnvelope",
+    "RangeStatement",
+    "RangeEnvelope",
+    "KzgEvaluationStatement",
+    "KzgEvaluationEnvelope",
+]
+
+
+@dataclass(frozen=True)
+class SchnorrStatement:
+    """Public inputs of schnorr_verify"""
+    public_key: bytes
+
+
+@dataclass(frozen=True)
+class SchnorrEnvelope:
+    """Proof checked by schnorr_verify along with its statement"""
+    statement: SchnorrStatement
+    proof: bytes
+
+    def verify(self) -> bool:
+        """Verify a proof of knowledge of the private key of a public key"""
+        return schnorr_verify(self.statement.public_key, self.proof)
+
+
+@dataclass(frozen=True)
+class RangeStatement:
+    """Public inputs of verify_range"""
+    commitment: bytes
+    bits: int
+    context: bytes
+
+
+@dataclass(frozen=True)
+class RangeEnvelope:
+    """Proof checked by verify_range along with its statement"""
+    statement: RangeStatement
+    proof: RangeProof
+
+    def verify(self) -> bool:
+        """Verify that a commitment commits to a value within [0, 2^bits)"""
+        return verify_range(
+            self.statement.commitment,
+            self.statement.bits,
+            self.proof,
+            self.statement.context,
+        )
+
+
+@dataclass(frozen=True)
+class KzgEvaluationStatement:
+    """Public inputs of KzgSetup.verify_evaluation"""
+    commitment: bytes
+    point: int
+    value: bytes
+
+
+@dataclass(frozen=True)
+class KzgEvaluationEnvelope:
+    """Proof checked by KzgSetup.verify_evaluation along with its statement"""
+    statement: KzgEvaluationStatement
+    proof: bytes
+
+    def verify(self, setup: KzgSetup) -> bool:
+        """Verify that a committed polynomial evaluates to `value` at `point`"""
+        return setup.verify_evaluation(
+            self.statement.commitment,
+            self.statement.point,
+            self.statement.value,
+            self.proof,
+        )
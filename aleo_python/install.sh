@@ -5,6 +5,9 @@
 ## the Aleo implementation of the Poseidon hash function in Python.
 python -m venv .env
 source .env/bin/activate
-pip install maturin
+pip install maturin pytest
 maturin develop
+
+## Check the bindings against the golden vectors generated from the Rust crates
+pytest tests
 python hash_integer.py 5
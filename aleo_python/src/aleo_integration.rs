@@ -0,0 +1,374 @@
+//! Proving quantized inferences with the Aleo proving system
+
+use encodings::{FixedPoint, QuantizedVector};
+use snarkvm::circuit::AleoV0;
+use snarkvm::prelude::{
+    bail, ensure, Execution, Identifier, Literal, Plaintext, PrivateKey, Process, Program, Result,
+    Testnet3, Value,
+};
+use std::str::FromStr;
+use zksnarks_example::ArithmeticBackend;
+
+// A quantized linear model y = w_0*x_0 + .. + w_(n-1)*x_(n-1) + b is turned into a snarkVM program
+// whose single function takes the features as private i64 inputs and returns y as a public
+// output. The weights and the bias are constants of the program, so the program ID identifies the
+// model the inference was run with.
+//
+// snarkVM's signed integer instructions fail on overflow, so an inference whose products or sums
+// don't fit into an i64 can't be proven rather than silently wrapping around. The product of two
+// fixed-point values carries the product of their scales, so the output is on the scale of the
+// weights times the scale of the features, and the bias must be quantized on that scale too.
+//
+// Executing the function synthesizes its proving key on first use and proves the execution. The
+// process also needs the proving keys of the built-in credits program, which snarkVM downloads
+// from the Aleo parameter server on first use and caches in ~/.aleo.
+
+// Name of the function running the inference
+const INFER_FUNCTION: &str = "infer";
+
+/// snarkVM program evaluating a quantized linear model on private features
+#[derive(Clone, Debug)]
+pub struct InferenceProgram {
+    // Parsed program
+    program: Program<Testnet3>,
+    // Scale the features must be quantized with
+    feature_scale: u64,
+    // Scale of the output, i.e. the weight scale times the feature scale
+    output_scale: u64,
+}
+
+impl InferenceProgram {
+    /// Generate a program named `name`.aleo evaluating a linear model on features quantized with
+    /// `feature_format`. The bias must be quantized on the scale of the weights times the scale
+    /// of the features.
+    pub fn linear(
+        name: &str,
+        weights: &QuantizedVector,
+        bias: i64,
+        feature_format: &FixedPoint,
+    ) -> Result<Self> {
+        let Some(output_scale) = weights
+            .get_format()
+            .get_scale()
+            .checked_mul(feature_format.get_scale())
+        else {
+            bail!("The output scale doesn't fit into a u64");
+        };
+        let weights = weights.get_values();
+        ensure!(
+            !weights.is_empty(),
+            "A linear model needs at least one weight"
+        );
+
+        // Features are in registers r0..r(n-1), the products in rn..r(2n-1), the running sums in
+        // r(2n)..r(3n-2) and the output adding the bias in r(3n-1)
+        let n = weights.len();
+        let mut source = format!("program {name}.aleo;\n\nfunction {INFER_FUNCTION}:\n");
+        for i in 0..n {
+            source += &format!("    input r{i} as i64.private;\n");
+        }
+        for (i, weight) in weights.iter().enumerate() {
+            source += &format!("    mul r{i} {weight}i64 into r{};\n", n + i);
+        }
+        let mut sum = n;
+        for i in 1..n {
+            source += &format!("    add r{sum} r{} into r{};\n", n + i, 2 * n + i - 1);
+            sum = 2 * n + i - 1;
+        }
+        source += &format!("    add r{sum} {bias}i64 into r{};\n", 3 * n - 1);
+        source += &format!("    output r{} as i64.public;\n", 3 * n - 1);
+
+        Ok(Self {
+            program: Program::from_str(&source)?,
+            feature_scale: feature_format.get_scale(),
+            output_scale,
+        })
+    }
+
+    /// Get the snarkVM program
+    pub fn get_program(&self) -> &Program<Testnet3> {
+        &self.program
+    }
+
+    /// Get the scale of the output
+    pub fn get_output_scale(&self) -> u64 {
+        self.output_scale
+    }
+
+    /// Get the number of features the model takes
+    pub fn num_features(&self) -> Result<usize> {
+        Ok(self
+            .program
+            .get_function(&Identifier::from_str(INFER_FUNCTION)?)?
+            .inputs()
+            .len())
+    }
+}
+
+// Models beyond a single linear layer are built with the fixed-point gadgets of the zksnarks
+// crate, which emit their arithmetic into a function builder whose values are register or literal
+// operands. snarkVM's i64 instructions already range check every result, so products are rescaled
+// with a plain division. Division truncates towards zero, so negative quotients with a remainder
+// are decremented to round towards negative infinity like the constraint system backend does.
+
+/// Builder of an inference function from the fixed-point gadgets, whose values are the operands
+/// of snarkVM instructions
+#[derive(Clone, Debug)]
+pub struct AleoFunctionBuilder {
+    // Number of private i64 inputs
+    num_inputs: usize,
+    // Instructions computing the output
+    instructions: Vec<String>,
+    // Number of registers written so far, including the inputs
+    num_registers: usize,
+}
+
+impl AleoFunctionBuilder {
+    /// Start a function taking `num_inputs` private i64 inputs, returning the registers holding
+    /// them
+    pub fn new(num_inputs: usize) -> (Self, Vec<String>) {
+        let builder = Self {
+            num_inputs,
+            instructions: vec![],
+            num_registers: num_inputs,
+        };
+        (builder, (0..num_inputs).map(|i| format!("r{i}")).collect())
+    }
+
+    // Append an instruction writing into a new register, returning the register
+    fn emit(&mut self, instruction: String) -> String {
+        let register = format!("r{}", self.num_registers);
+        self.instructions
+            .push(format!("{instruction} into {register};"));
+        self.num_registers += 1;
+        register
+    }
+
+    /// Generate a program named `name`.aleo returning `output` as its public output, where the
+    /// inputs and the output are quantized with `format`
+    pub fn build(&self, name: &str, output: &str, format: &FixedPoint) -> Result<InferenceProgram> {
+        ensure!(self.num_inputs > 0, "An inference needs at least one input");
+        let mut source = format!("program {name}.aleo;\n\nfunction {INFER_FUNCTION}:\n");
+        for i in 0..self.num_inputs {
+            source += &format!("    input r{i} as i64.private;\n");
+        }
+        for instruction in &self.instructions {
+            source += &format!("    {instruction}\n");
+        }
+        source += &format!("    output {output} as i64.public;\n");
+        Ok(InferenceProgram {
+            program: Program::from_str(&source)?,
+            feature_scale: format.get_scale(),
+            output_scale: format.get_scale(),
+        })
+    }
+}
+
+impl ArithmeticBackend for AleoFunctionBuilder {
+    type Value = String;
+
+    fn constant(&mut self, value: i64) -> String {
+        format!("{value}i64")
+    }
+
+    fn add(&mut self, a: &String, b: &String) -> String {
+        self.emit(format!("add {a} {b}"))
+    }
+
+    fn sub(&mut self, a: &String, b: &String) -> String {
+        self.emit(format!("sub {a} {b}"))
+    }
+
+    fn mul(&mut self, a: &String, b: &String) -> String {
+        self.emit(format!("mul {a} {b}"))
+    }
+
+    fn div_floor(&mut self, a: &String, divisor: u64, _bits: u32) -> String {
+        let quotient = self.emit(format!("div {a} {divisor}i64"));
+        let remainder = self.emit(format!("rem {a} {divisor}i64"));
+        let negative = self.emit(format!("lt {remainder} 0i64"));
+        let correction = self.emit(format!("ternary {negative} 1i64 0i64"));
+        self.emit(format!("sub {quotient} {correction}"))
+    }
+
+    fn less_than(&mut self, a: &String, b: &String, _bits: u32) -> String {
+        let less = self.emit(format!("lt {a} {b}"));
+        self.emit(format!("ternary {less} 1i64 0i64"))
+    }
+
+    fn select(&mut self, bit: &String, a: &String, b: &String) -> String {
+        let set = self.emit(format!("is.eq {bit} 1i64"));
+        self.emit(format!("ternary {set} {a} {b}"))
+    }
+}
+
+/// Proven inference along with its public output
+#[derive(Clone, Debug)]
+pub struct InferenceExecution {
+    // Transitions of the execution including their proofs
+    execution: Execution<Testnet3>,
+    // Quantized output of the model
+    output: i64,
+    // Scale of the output
+    output_scale: u64,
+}
+
+impl InferenceExecution {
+    /// Get the execution holding the proof
+    pub fn get_execution(&self) -> &Execution<Testnet3> {
+        &self.execution
+    }
+
+    /// Get the quantized output
+    pub fn get_output(&self) -> i64 {
+        self.output
+    }
+
+    /// Get the output as a real number
+    pub fn dequantize(&self) -> f64 {
+        self.output as f64 / self.output_scale as f64
+    }
+}
+
+/// Prover and verifier of inference programs backed by a snarkVM process
+pub struct AleoInferenceProver {
+    process: Process<Testnet3>,
+}
+
+impl AleoInferenceProver {
+    /// Load the snarkVM process, downloading the credits program parameters on first use
+    pub fn load() -> Result<Self> {
+        Ok(Self {
+            process: Process::load()?,
+        })
+    }
+
+    /// Execute an inference program on quantized features, returning the execution proof and
+    /// the public output
+    pub fn prove(
+        &mut self,
+        program: &InferenceProgram,
+        private_key: &PrivateKey<Testnet3>,
+        features: &QuantizedVector,
+    ) -> Result<InferenceExecution> {
+        ensure!(
+            features.get_format().get_scale() == program.feature_scale,
+            "Features must be quantized with a scale of {}",
+            program.feature_scale
+        );
+        let features = features.get_values();
+        ensure!(
+            features.len() == program.num_features()?,
+            "Expected {} features but got {}",
+            program.num_features()?,
+            features.len()
+        );
+        if !self.process.contains_program(program.program.id()) {
+            self.process.add_program(&program.program)?;
+        }
+
+        let rng = &mut rand::thread_rng();
+        let inputs: Vec<Value<Testnet3>> = features
+            .iter()
+            .map(|feature| Value::from_str(&format!("{feature}i64")))
+            .collect::<Result<_>>()?;
+        let authorization = self.process.authorize::<AleoV0, _>(
+            private_key,
+            program.program.id(),
+            INFER_FUNCTION,
+            inputs.into_iter(),
+            rng,
+        )?;
+        let (response, execution, _, _) = self.process.execute::<AleoV0, _>(authorization, rng)?;
+        let output = match response.outputs() {
+            [Value::Plaintext(Plaintext::Literal(Literal::I64(output), _))] => **output,
+            outputs => bail!("Unexpected inference outputs {outputs:?}"),
+        };
+        Ok(InferenceExecution {
+            execution,
+            output,
+            output_scale: program.output_scale,
+        })
+    }
+
+    /// Verify the proofs of an inference execution
+    pub fn verify(&self, execution: &InferenceExecution) -> Result<()> {
+        self.process.verify_execution::<false>(&execution.execution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksnarks_example::FixedPointGadgets;
+
+    fn linear_program() -> InferenceProgram {
+        let format = FixedPoint::with_bits(8, 16).unwrap();
+        let weights = format.quantize_vector(&[0.5, -1.25, 2.0]).unwrap();
+        InferenceProgram::linear("linear_model", &weights, -300, &format).unwrap()
+    }
+
+    #[test]
+    fn test_linear_models_become_snarkvm_programs() {
+        let program = linear_program();
+        assert_eq!(program.get_program().id().to_string(), "linear_model.aleo");
+        assert_eq!(program.num_features().unwrap(), 3);
+        assert_eq!(program.get_output_scale(), 1 << 16);
+
+        // The weights and bias are constants of the generated instructions
+        let source = program.get_program().to_string();
+        for instruction in [
+            "mul r1 -320i64 into r4",
+            "add r7 -300i64 into r8",
+            "output r8",
+        ] {
+            assert!(source.contains(instruction), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_fixed_point_gadgets_become_snarkvm_programs() {
+        // The largest of relu(w * x_i) over three features
+        let format = FixedPoint::with_bits(8, 16).unwrap();
+        let gadgets = FixedPointGadgets::new(format);
+        let (mut builder, features) = AleoFunctionBuilder::new(3);
+        let weight = builder.constant(format.quantize(-1.5).unwrap());
+        let activations: Vec<String> = features
+            .iter()
+            .map(|feature| {
+                let product = gadgets.mul(&mut builder, &weight, feature);
+                gadgets.relu(&mut builder, &product)
+            })
+            .collect();
+        let (_, max) = gadgets.argmax(&mut builder, &activations).unwrap();
+        let program = builder.build("gadget_model", &max, &format).unwrap();
+        assert_eq!(program.num_features().unwrap(), 3);
+        assert_eq!(program.get_output_scale(), 256);
+
+        let source = program.get_program().to_string();
+        for instruction in [
+            "mul -384i64 r0 into r3",
+            "div r3 256i64 into r4",
+            "rem r3 256i64 into r5",
+            "lt r5 0i64 into r6",
+            "lt r8 0i64 into r9",
+        ] {
+            assert!(source.contains(instruction), "{source}");
+        }
+        assert!(AleoFunctionBuilder::new(0)
+            .0
+            .build("no_inputs", "0i64", &format)
+            .is_err());
+    }
+
+    #[test]
+    fn test_invalid_models_and_features_are_rejected() {
+        let format = FixedPoint::with_bits(8, 16).unwrap();
+        let no_weights = format.quantize_vector(&[]).unwrap();
+        assert!(InferenceProgram::linear("empty_model", &no_weights, 0, &format).is_err());
+        let weights = format.quantize_vector(&[1.0]).unwrap();
+        assert!(InferenceProgram::linear("not a name", &weights, 0, &format).is_err());
+        let huge_scale = FixedPoint::new(u64::MAX, 1).unwrap();
+        assert!(InferenceProgram::linear("huge_scale", &weights, 0, &huge_scale).is_err());
+    }
+}
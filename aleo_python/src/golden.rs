@@ -0,0 +1,217 @@
+//! Golden vectors pinning the outputs of the Python bindings to those of the Rust implementations
+
+use super::*;
+use curve25519_dalek::scalar::Scalar;
+use encodings::{Endianness, ScalarEncoding};
+use num_bigint::{BigInt, BigUint};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// The conformance suite in tests/test_golden_vectors.py checks the built Python module against
+// tests/golden_vectors.json byte for byte, and the test below checks the same file against the
+// Rust functions the bindings wrap, so any change in behaviour on either side of the boundary
+// fails one of the two. Byte strings are hex encoded and integers which may not fit into a JSON
+// number are decimal strings.
+//
+// Hashes are deterministic and pinned exactly. Proofs use random nonces, so a vector pins a proof
+// created once which must keep verifying, along with the deterministic parts of the statement
+// such as the public key of a private key. Range proofs and KZG commitments depend on fresh
+// blinding and a fresh setup, so only the evaluation value of a KZG proof is pinned.
+//
+// After an intended change, regenerate the file with
+//
+//     UPDATE_GOLDEN_VECTORS=1 cargo test -p aleo_python golden
+//
+// and review the diff.
+
+// Environment variable requesting the golden vectors to be regenerated
+const UPDATE_VAR: &str = "UPDATE_GOLDEN_VECTORS";
+
+// Location of the golden vectors relative to the crate
+fn golden_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden_vectors.json")
+}
+
+// Convert a JSON value into the structured value Python passes in after parsing the same JSON
+fn to_struct_value(value: &Value) -> StructValue {
+    match value {
+        Value::Null => StructValue::None,
+        Value::Bool(value) => StructValue::Bool(*value),
+        Value::Number(value) => StructValue::Int(BigInt::from(
+            value.as_i64().expect("golden integers fit into an i64"),
+        )),
+        Value::String(value) => StructValue::Str(value.clone()),
+        Value::Array(items) => StructValue::List(items.iter().map(to_struct_value).collect()),
+        Value::Object(entries) => StructValue::Dict(
+            entries
+                .iter()
+                .map(|(key, value)| (key.clone(), to_struct_value(value)))
+                .collect::<BTreeMap<_, _>>(),
+        ),
+    }
+}
+
+// Parse integers written as decimal strings
+fn to_integers(value: &Value) -> Vec<BigUint> {
+    serde_json::from_value::<Vec<String>>(value.clone())
+        .unwrap()
+        .iter()
+        .map(|integer| integer.parse().unwrap())
+        .collect()
+}
+
+// Compute the golden vectors from the Rust implementations
+fn generate_vectors() -> Value {
+    let hash_int_vectors: Vec<Value> = [0, 1, 5, u64::MAX]
+        .into_iter()
+        .map(|input| json!({"input": input, "output": hash_of_int(input).unwrap()}))
+        .collect();
+    let hash_bytes_vectors: Vec<Value> = [&b""[..], &[0], b"model", &[0xff; 100]]
+        .into_iter()
+        .map(|input| json!({"input": hex::encode(input), "output": hash_of_bytes(input).unwrap()}))
+        .collect();
+    let chained = hash_of_int(5).unwrap();
+    let hash_fields_vectors: Vec<Value> = [vec!["5field"], vec![&chained, "7field"]]
+        .into_iter()
+        .map(|input| json!({"input": input, "output": hash_of_fields(&input).unwrap()}))
+        .collect();
+    let hash_field_list_vectors: Vec<Value> = [
+        vec!["0"],
+        vec!["1", "2", "3"],
+        vec!["340282366920938463463374607431768211456"],
+    ]
+    .into_iter()
+    .map(|input| {
+        let output = hash_of_field_list(&to_integers(&json!(input))).unwrap();
+        json!({"input": input, "output": output})
+    })
+    .collect();
+    let hash_struct_vectors: Vec<Value> = [
+        json!(null),
+        json!({"features": [1, -2, 3], "model": "linear", "quantized": true}),
+        json!([{"a": 1}, {"b": [null, false, "x"]}, -9_007_199_254_740_993i64]),
+    ]
+    .into_iter()
+    .map(|input| json!({"input": input, "output": hash_of_struct(&to_struct_value(&input)).unwrap()}))
+    .collect();
+
+    let schnorr_vectors: Vec<Value> = [Scalar::ONE, Scalar::from(0x5eed_u64)]
+        .into_iter()
+        .map(|private_key| {
+            let private_key = private_key.encode(Endianness::Little);
+            let (public_key, proof) = create_schnorr_proof(&private_key).unwrap();
+            json!({
+                "private_key": hex::encode(private_key),
+                "public_key": hex::encode(public_key),
+                "proof": hex::encode(proof),
+            })
+        })
+        .collect();
+    let setup = PyKzgSetup::new(4);
+    let kzg_vectors: Vec<Value> = [(vec![3, 2, 1], 5), (vec![7], 0), (vec![0, 0, 0, 0, 1], 3)]
+        .into_iter()
+        .map(|(coefficients, point)| {
+            let (value, _) = setup
+                .create_evaluation_proof(&to_coefficients(coefficients.clone()), point)
+                .unwrap();
+            json!({"coefficients": coefficients, "point": point, "value": hex::encode(value)})
+        })
+        .collect();
+
+    json!({
+        "hash_int": hash_int_vectors,
+        "hash_bytes": hash_bytes_vectors,
+        "hash_fields": hash_fields_vectors,
+        "hash_field_list": hash_field_list_vectors,
+        "hash_struct": hash_struct_vectors,
+        "schnorr": schnorr_vectors,
+        "kzg_evaluation": kzg_vectors,
+    })
+}
+
+// Check every golden vector against the Rust implementations
+fn check_vectors(vectors: &Value) {
+    let section = |name: &str| vectors[name].as_array().unwrap().iter();
+    for vector in section("hash_int") {
+        let output = hash_of_int(vector["input"].as_u64().unwrap()).unwrap();
+        assert_eq!(output, vector["output"], "hash_int({})", vector["input"]);
+    }
+    for vector in section("hash_bytes") {
+        let input = hex::decode(vector["input"].as_str().unwrap()).unwrap();
+        let output = hash_of_bytes(&input).unwrap();
+        assert_eq!(output, vector["output"], "hash_bytes({})", vector["input"]);
+    }
+    for vector in section("hash_fields") {
+        let input: Vec<String> = serde_json::from_value(vector["input"].clone()).unwrap();
+        let output = hash_of_fields(&input.iter().map(String::as_str).collect::<Vec<_>>()).unwrap();
+        assert_eq!(output, vector["output"], "hash_fields({})", vector["input"]);
+    }
+    for vector in section("hash_field_list") {
+        let output = hash_of_field_list(&to_integers(&vector["input"])).unwrap();
+        assert_eq!(
+            output, vector["output"],
+            "hash_field_list({})",
+            vector["input"]
+        );
+    }
+    for vector in section("hash_struct") {
+        let output = hash_of_struct(&to_struct_value(&vector["input"])).unwrap();
+        assert_eq!(output, vector["output"], "hash_struct({})", vector["input"]);
+    }
+    for vector in section("schnorr") {
+        let decode = |name: &str| hex::decode(vector[name].as_str().unwrap()).unwrap();
+        let (public_key, _) = create_schnorr_proof(&decode("private_key")).unwrap();
+        assert_eq!(public_key, decode("public_key"));
+        assert!(check_schnorr_proof(&decode("public_key"), &decode("proof")).unwrap());
+    }
+    let setup = PyKzgSetup::new(4);
+    for vector in section("kzg_evaluation") {
+        let coefficients = serde_json::from_value(vector["coefficients"].clone()).unwrap();
+        let point = vector["point"].as_u64().unwrap();
+        let (value, _) = setup
+            .create_evaluation_proof(&to_coefficients(coefficients), point)
+            .unwrap();
+        assert_eq!(hex::encode(value), vector["value"], "kzg {vector}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_vectors_match_the_rust_implementations() {
+        if std::env::var_os(UPDATE_VAR).is_some() {
+            let vectors = serde_json::to_string_pretty(&generate_vectors()).unwrap();
+            std::fs::write(golden_path(), vectors + "\n").unwrap();
+        }
+        let vectors = std::fs::read_to_string(golden_path()).unwrap_or_else(|_| {
+            panic!("missing golden vectors, generate them by setting {UPDATE_VAR}")
+        });
+        check_vectors(&serde_json::from_str(&vectors).unwrap());
+    }
+
+    #[test]
+    fn test_regenerated_vectors_agree_with_the_checked_in_vectors() {
+        // Freshly generated vectors pass the same checks, and their deterministic sections are
+        // identical to the checked in ones
+        let generated = generate_vectors();
+        check_vectors(&generated);
+        let checked_in: Value =
+            serde_json::from_str(&std::fs::read_to_string(golden_path()).unwrap()).unwrap();
+        for name in [
+            "hash_int",
+            "hash_bytes",
+            "hash_fields",
+            "hash_field_list",
+            "hash_struct",
+            "kzg_evaluation",
+        ] {
+            assert_eq!(
+                generated[name], checked_in[name],
+                "{name} vectors are stale"
+            );
+        }
+    }
+}
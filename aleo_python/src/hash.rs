@@ -1,13 +1,365 @@
 use super::*;
+use num_bigint::{BigInt, BigUint, Sign};
+use pyo3::types::{PyBool, PyBytes, PyDict, PyList, PyLong, PyString, PyTuple};
 use snarkvm::console::algorithms::Poseidon2;
-use snarkvm::prelude::{Hash, Testnet3, Field};
+use snarkvm::console::prelude::FromBits;
+use snarkvm::prelude::{bail, Field, Hash, Result, Testnet3};
+use std::collections::BTreeMap;
+use std::str::FromStr;
 use ToString;
 
-// Takes a poseiden hash of an integer and returns the hash as a string
+// Bytes are packed into field elements 31 bytes at a time, which always fits below the field
+// modulus, and the byte length is hashed first so inputs differing only in trailing zero bytes
+// don't collide. As with the proof bindings, hashing is done by plain Rust functions the Python
+// functions wrap.
+//
+// hash_int and hash_fields hash their field elements as they are, so they match Aleo's hash.psd2
+// and chain with earlier hashes. The byte, integer list and struct hash functions instead start
+// their input with a field element packing their own domain separator, so e.g. hash_bytes(b"")
+// and hash_field_list([0]) never hash like hash_int(0), although each of them encodes to the
+// single field element 0.
+
+// Number of bytes packed into each field element
+const BYTES_PER_FIELD: usize = 31;
+
+// Domain separators of the hash functions, each packed into the first field element hashed
+const HASH_BYTES_DOMAIN_SEP: &[u8] = b"HASH_BYTES";
+const HASH_FIELD_LIST_DOMAIN_SEP: &[u8] = b"HASH_FIELD_LIST";
+const HASH_STRUCT_DOMAIN_SEP: &[u8] = b"HASH_STRUCT";
+
+// Structured inputs are hashed by serializing them into canonical bytes which are then packed
+// like a byte string, under their own domain separator so a struct never hashes like its bytes.
+// Every value is written as a type tag followed by its contents, with lengths and counts as 8 byte
+// little endian integers, and dict entries sorted by key, so equal values always encode the same
+// way and values of different shapes never do:
+//
+// - None: tag 0
+// - bool: tag 1 and one byte
+// - int: tag 2, a sign byte and the length prefixed big endian magnitude
+// - bytes: tag 3 and the length prefixed bytes
+// - str: tag 4 and the length prefixed UTF-8 bytes
+// - list or tuple: tag 5, the item count and the items
+// - dict: tag 6, the entry count and the length prefixed UTF-8 key followed by the value of every
+// entry
+//
+// Floats are rejected since pipelines rarely agree on their exact bits and should be quantized
+// to integers first.
+
+// Type tags of structured values
+const NONE_TAG: u8 = 0;
+const BOOL_TAG: u8 = 1;
+const INT_TAG: u8 = 2;
+const BYTES_TAG: u8 = 3;
+const STR_TAG: u8 = 4;
+const LIST_TAG: u8 = 5;
+const DICT_TAG: u8 = 6;
+
+/// Structured value passed in from Python, e.g. a dict describing an ML input
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StructValue {
+    /// Python None
+    None,
+    /// Python bool
+    Bool(bool),
+    /// Python int of any size
+    Int(BigInt),
+    /// Python bytes
+    Bytes(Vec<u8>),
+    /// Python str
+    Str(String),
+    /// Python list or tuple
+    List(Vec<StructValue>),
+    /// Python dict with str keys
+    Dict(BTreeMap<String, StructValue>),
+}
+
+impl StructValue {
+    /// Serialize the value into its canonical bytes
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write_canonical(&mut bytes);
+        bytes
+    }
+
+    // Append the canonical encoding of the value
+    fn write_canonical(&self, bytes: &mut Vec<u8>) {
+        let write_prefixed = |bytes: &mut Vec<u8>, data: &[u8]| {
+            bytes.extend((data.len() as u64).to_le_bytes());
+            bytes.extend(data);
+        };
+        match self {
+            Self::None => bytes.push(NONE_TAG),
+            Self::Bool(value) => bytes.extend([BOOL_TAG, *value as u8]),
+            Self::Int(value) => {
+                let (sign, magnitude) = value.to_bytes_be();
+                bytes.extend([INT_TAG, (sign == Sign::Minus) as u8]);
+                // Zero has an empty magnitude so it has a single encoding
+                let magnitude: &[u8] = if sign == Sign::NoSign {
+                    &[]
+                } else {
+                    &magnitude
+                };
+                write_prefixed(bytes, magnitude);
+            }
+            Self::Bytes(data) => {
+                bytes.push(BYTES_TAG);
+                write_prefixed(bytes, data);
+            }
+            Self::Str(data) => {
+                bytes.push(STR_TAG);
+                write_prefixed(bytes, data.as_bytes());
+            }
+            Self::List(items) => {
+                bytes.push(LIST_TAG);
+                bytes.extend((items.len() as u64).to_le_bytes());
+                items.iter().for_each(|item| item.write_canonical(bytes));
+            }
+            Self::Dict(entries) => {
+                bytes.push(DICT_TAG);
+                bytes.extend((entries.len() as u64).to_le_bytes());
+                for (key, value) in entries {
+                    write_prefixed(bytes, key.as_bytes());
+                    value.write_canonical(bytes);
+                }
+            }
+        }
+    }
+}
+
+impl<'source> FromPyObject<'source> for StructValue {
+    fn extract(object: &'source PyAny) -> PyResult<Self> {
+        // bool is checked before int since Python bools are ints
+        if object.is_none() {
+            Ok(Self::None)
+        } else if let Ok(value) = object.downcast::<PyBool>() {
+            Ok(Self::Bool(value.is_true()))
+        } else if object.is_instance_of::<PyLong>()? {
+            Ok(Self::Int(object.extract()?))
+        } else if let Ok(value) = object.downcast::<PyBytes>() {
+            Ok(Self::Bytes(value.as_bytes().to_vec()))
+        } else if let Ok(value) = object.downcast::<PyString>() {
+            Ok(Self::Str(value.to_str()?.to_string()))
+        } else if object.is_instance_of::<PyList>()? || object.is_instance_of::<PyTuple>()? {
+            Ok(Self::List(object.extract()?))
+        } else if let Ok(value) = object.downcast::<PyDict>() {
+            Ok(Self::Dict(value.extract()?))
+        } else {
+            Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                "Can't hash values of type {}, floats must be quantized first",
+                object.get_type().name()?
+            )))
+        }
+    }
+}
+
+// Convert an error into a Python ValueError
+fn value_error(error: snarkvm::console::prelude::Error) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(error.to_string())
+}
+
+// Hash field elements with Poseidon2
+fn poseidon_hash(fields: &[Field<Testnet3>]) -> Result<Field<Testnet3>> {
+    Poseidon2::setup("Poseidon2")?.hash(fields)
+}
+
+// Hash field elements after the field element packing a domain separator
+fn domain_separated_hash(domain_sep: &[u8], fields: &[Field<Testnet3>]) -> Result<Field<Testnet3>> {
+    let mut input = vec![pack_chunk(domain_sep)?];
+    input.extend(fields);
+    poseidon_hash(&input)
+}
+
+// Pack at most 31 bytes into a field element
+fn pack_chunk(chunk: &[u8]) -> Result<Field<Testnet3>> {
+    let bits: Vec<bool> = chunk
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .collect();
+    Field::from_bits_le(&bits)
+}
+
+// Pack a byte string into field elements prefixed with its length
+fn pack_bytes(data: &[u8]) -> Result<Vec<Field<Testnet3>>> {
+    let mut fields = vec![Field::from_u64(data.len() as u64)];
+    for chunk in data.chunks(BYTES_PER_FIELD) {
+        fields.push(pack_chunk(chunk)?);
+    }
+    Ok(fields)
+}
+
+// Parse field elements written like "5field"
+fn parse_fields(fields: &[&str]) -> Result<Vec<Field<Testnet3>>> {
+    fields.iter().map(|field| Field::from_str(field)).collect()
+}
+
+// Convert integers to field elements, rejecting integers at or above the field modulus rather
+// than reducing them so distinct integers never hash the same
+fn integers_to_fields(integers: &[BigUint]) -> Result<Vec<Field<Testnet3>>> {
+    integers
+        .iter()
+        .map(|integer| {
+            // Parsing reduces integers modulo the field modulus, which changes their digits
+            let literal = format!("{integer}field");
+            match Field::<Testnet3>::from_str(&literal) {
+                Ok(field) if field.to_string() == literal => Ok(field),
+                _ => bail!("{integer} doesn't fit into a field element"),
+            }
+        })
+        .collect()
+}
+
+// Hash an integer the way hash_int does
+pub(crate) fn hash_of_int(a: u64) -> Result<String> {
+    Ok(poseidon_hash(&[Field::from_u64(a)])?.to_string())
+}
+
+// Hash a byte string the way hash_bytes does
+pub(crate) fn hash_of_bytes(data: &[u8]) -> Result<String> {
+    Ok(domain_separated_hash(HASH_BYTES_DOMAIN_SEP, &pack_bytes(data)?)?.to_string())
+}
+
+// Hash field elements the way hash_fields does
+pub(crate) fn hash_of_fields(fields: &[&str]) -> Result<String> {
+    Ok(poseidon_hash(&parse_fields(fields)?)?.to_string())
+}
+
+// Hash a list of integers the way hash_field_list does
+pub(crate) fn hash_of_field_list(integers: &[BigUint]) -> Result<String> {
+    let fields = integers_to_fields(integers)?;
+    Ok(domain_separated_hash(HASH_FIELD_LIST_DOMAIN_SEP, &fields)?.to_string())
+}
+
+// Hash a structured value the way hash_struct does
+pub(crate) fn hash_of_struct(value: &StructValue) -> Result<String> {
+    let fields = pack_bytes(&value.canonical_bytes())?;
+    Ok(domain_separated_hash(HASH_STRUCT_DOMAIN_SEP, &fields)?.to_string())
+}
+
+/// Takes a poseiden hash of an integer and returns the hash as a string
 #[pyfunction]
 pub fn hash_int(a: u64) -> PyResult<String> {
-    let field = Field::from_u64(a);
-    let hasher = Poseidon2::setup("Poseidon2").unwrap();
-    let hash: Field<Testnet3> = hasher.hash(&[field]).unwrap();
-    Ok(hash.to_string())
+    hash_of_int(a).map_err(value_error)
+}
+
+/// Takes a poseiden hash of a byte string and returns the hash as a string
+#[pyfunction]
+pub fn hash_bytes(data: &[u8]) -> PyResult<String> {
+    hash_of_bytes(data).map_err(value_error)
+}
+
+/// Takes a poseiden hash of field elements written like "5field", e.g. earlier hashes, and returns
+/// the hash as a string
+#[pyfunction]
+pub fn hash_fields(fields: Vec<&str>) -> PyResult<String> {
+    hash_of_fields(&fields).map_err(value_error)
+}
+
+/// Takes a poseiden hash of a list of non-negative integers below the field modulus and returns
+/// the hash as a string
+#[pyfunction]
+pub fn hash_field_list(integers: Vec<BigUint>) -> PyResult<String> {
+    hash_of_field_list(&integers).map_err(value_error)
+}
+
+/// Takes a poseiden hash of the canonical encoding of a nested structure of dicts with str keys,
+/// lists, tuples, ints, bools, bytes, strs and None, and returns the hash as a string
+#[pyfunction]
+pub fn hash_struct(value: StructValue) -> PyResult<String> {
+    hash_of_struct(&value).map_err(value_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_strings_are_hashed_with_their_length() {
+        let hash = |data: &[u8]| hash_of_bytes(data).unwrap();
+        assert_eq!(hash(b"model"), hash(b"model"));
+        assert_ne!(hash(b"model"), hash(b"model\0"));
+        assert_ne!(hash(&[]), hash(&[0]));
+        assert_eq!(pack_bytes(&[0xff; 100]).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_hash_functions_are_domain_separated() {
+        // Integers and field elements are hashed as plain Poseidon2, like Aleo's hash.psd2
+        let plain = poseidon_hash(&[Field::from_u64(0)]).unwrap().to_string();
+        assert_eq!(hash_of_int(0).unwrap(), plain);
+        assert_eq!(hash_of_fields(&["0field"]).unwrap(), plain);
+
+        // Each of these encodes to the field elements [0] or [1, 0]
+        let zeros = [
+            plain,
+            hash_of_bytes(b"").unwrap(),
+            hash_of_field_list(&[0u64.into()]).unwrap(),
+        ];
+        let packed = [
+            hash_of_bytes(&[0]).unwrap(),
+            hash_of_fields(&["1field", "0field"]).unwrap(),
+            hash_of_field_list(&[1u64.into(), 0u64.into()]).unwrap(),
+        ];
+        // A struct commitment can't be opened as its canonical bytes
+        let none = StructValue::None;
+        assert_eq!(none.canonical_bytes(), [NONE_TAG]);
+        assert_ne!(hash_of_struct(&none).unwrap(), packed[0]);
+        for hashes in [&zeros[..], &packed[..]] {
+            for (i, hash) in hashes.iter().enumerate() {
+                assert!(hashes[i + 1..].iter().all(|other| other != hash));
+            }
+        }
+    }
+
+    #[test]
+    fn test_field_arrays_chain_with_earlier_hashes() {
+        let first = poseidon_hash(&[Field::from_u64(5)]).unwrap().to_string();
+        let fields = parse_fields(&[&first, "7field"]).unwrap();
+        assert_eq!(fields[0].to_string(), first);
+        let chained = poseidon_hash(&fields).unwrap();
+        assert_ne!(
+            chained,
+            poseidon_hash(&parse_fields(&["7field", &first]).unwrap()).unwrap()
+        );
+        assert!(parse_fields(&["5"]).is_err());
+    }
+
+    #[test]
+    fn test_structs_and_integer_lists_are_encoded_canonically() {
+        let input = |label: &str, pixels: &[i64]| {
+            StructValue::Dict(BTreeMap::from([
+                ("label".to_string(), StructValue::Str(label.to_string())),
+                (
+                    "pixels".to_string(),
+                    StructValue::List(
+                        pixels
+                            .iter()
+                            .map(|pixel| StructValue::Int((*pixel).into()))
+                            .collect(),
+                    ),
+                ),
+            ]))
+        };
+        assert_eq!(
+            input("cat", &[0, -1]).canonical_bytes(),
+            input("cat", &[0, -1]).canonical_bytes()
+        );
+        assert_ne!(
+            input("cat", &[0, -1]).canonical_bytes(),
+            input("cat", &[0, 1]).canonical_bytes()
+        );
+        // Values of different shapes never share an encoding
+        assert_ne!(
+            StructValue::Str("1".to_string()).canonical_bytes(),
+            StructValue::Bytes(b"1".to_vec()).canonical_bytes()
+        );
+        assert_ne!(
+            StructValue::List(vec![StructValue::None]).canonical_bytes(),
+            StructValue::List(vec![]).canonical_bytes()
+        );
+
+        let fields = integers_to_fields(&[5u64.into(), 7u64.into()]).unwrap();
+        assert_eq!(fields, parse_fields(&["5field", "7field"]).unwrap());
+        let too_large = BigUint::from(1u8) << 253;
+        assert!(integers_to_fields(&[too_large]).is_err());
+    }
 }
@@ -1,12 +1,35 @@
 use pyo3::prelude::*;
 
+pub mod aleo_integration;
+#[cfg(test)]
+mod golden;
 pub mod hash;
+pub mod notebook;
+pub mod proofs;
+#[cfg(test)]
+mod stubs;
+pub use aleo_integration::*;
 pub use hash::*;
+pub use notebook::*;
+pub use proofs::*;
 
 /// A Python module implemented in Rust.
 #[pymodule]
 fn aleo_python(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(hash_int, m)?)?;
+    m.add_function(wrap_pyfunction!(hash_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(hash_fields, m)?)?;
+    m.add_function(wrap_pyfunction!(hash_field_list, m)?)?;
+    m.add_function(wrap_pyfunction!(hash_struct, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_schnorr_key, m)?)?;
+    m.add_function(wrap_pyfunction!(schnorr_prove, m)?)?;
+    m.add_function(wrap_pyfunction!(schnorr_verify, m)?)?;
+    m.add_function(wrap_pyfunction!(prove_range, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_range, m)?)?;
+    m.add_function(wrap_pyfunction!(describe_schnorr, m)?)?;
+    m.add_function(wrap_pyfunction!(describe_range_proof, m)?)?;
+    m.add_class::<PyRangeProof>()?;
+    m.add_class::<PyKzgSetup>()?;
 
     Ok(())
 }
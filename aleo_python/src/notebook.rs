@@ -0,0 +1,313 @@
+//! Python helpers describing proofs as plain dicts for display in notebooks
+
+use super::*;
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use encodings::{Endianness, PointEncoding, ScalarEncoding};
+use merlin_example::{SchnorrProof, TracedOracle, TranscriptEvent};
+use pyo3::types::PyDict;
+use snarkvm::prelude::{ensure, Result};
+
+// The bindings in proofs.rs hand out opaque bytes, which is what applications need but hides how
+// a proof works. The describe functions take the same bytes apart for teaching, returning a dict
+// of only str, int, bool, list and dict values which notebooks and pandas display as is:
+//
+// - scheme: name of the proof system
+// - components: the name, size in bytes and hex encoding of every part of the encoded proof,
+// where parts a proof doesn't expose have no encoding
+// - size: total size of the proof in bytes
+// - transcript: the prover messages and challenges in the order they pass through the Fiat-Shamir
+// transcript, or None for proofs whose transcript can't be observed
+// - equation: the verification equation along with the hex encodings of both of its sides and
+// whether they are equal
+// - verified: whether the proof verifies as a whole, which takes more than the equation shown
+//
+// Describing a proof never raises for a proof which merely doesn't verify, only for bytes which
+// don't decode.
+
+/// Part of an encoded proof
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofComponent {
+    /// Name of the component
+    pub name: &'static str,
+    /// Size of the component in bytes
+    pub size: usize,
+    /// Hex encoding of the component, if its bytes are exposed
+    pub hex: Option<String>,
+}
+
+/// Message passing through the transcript of a proof
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TranscriptStep {
+    /// Either "prover_message" or "challenge"
+    pub step: &'static str,
+    /// Hex encoding of the message
+    pub value: String,
+}
+
+/// Verification equation of a proof along with both of its sides
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationEquation {
+    /// Equation in terms of the proof values
+    pub equation: &'static str,
+    /// Hex encoding of the left side
+    pub lhs: String,
+    /// Hex encoding of the right side
+    pub rhs: String,
+}
+
+/// Description of a proof for display
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofDescription {
+    /// Name of the proof system
+    pub scheme: &'static str,
+    /// Parts of the encoded proof
+    pub components: Vec<ProofComponent>,
+    /// Messages of the transcript, if it can be observed
+    pub transcript: Option<Vec<TranscriptStep>>,
+    /// Verification equation
+    pub equation: VerificationEquation,
+    /// Whether the proof verifies
+    pub verified: bool,
+}
+
+impl IntoPy<PyObject> for ProofDescription {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        let size = self.size();
+        let dict = |entries: &[(&str, PyObject)]| {
+            let dict = PyDict::new(py);
+            for (key, value) in entries {
+                dict.set_item(key, value).expect("str keys can be set");
+            }
+            dict.to_object(py)
+        };
+        let components: Vec<PyObject> = self
+            .components
+            .iter()
+            .map(|component| {
+                dict(&[
+                    ("name", component.name.into_py(py)),
+                    ("size", component.size.into_py(py)),
+                    ("hex", component.hex.clone().into_py(py)),
+                ])
+            })
+            .collect();
+        let transcript = self.transcript.map(|transcript| {
+            transcript
+                .into_iter()
+                .map(|step| {
+                    dict(&[
+                        ("step", step.step.into_py(py)),
+                        ("value", step.value.into_py(py)),
+                    ])
+                })
+                .collect::<Vec<_>>()
+        });
+        let holds = self.equation.lhs == self.equation.rhs;
+        let equation = dict(&[
+            ("equation", self.equation.equation.into_py(py)),
+            ("lhs", self.equation.lhs.into_py(py)),
+            ("rhs", self.equation.rhs.into_py(py)),
+            ("holds", holds.into_py(py)),
+        ]);
+        dict(&[
+            ("scheme", self.scheme.into_py(py)),
+            ("size", size.into_py(py)),
+            ("components", components.into_py(py)),
+            ("transcript", transcript.into_py(py)),
+            ("equation", equation),
+            ("verified", self.verified.into_py(py)),
+        ])
+    }
+}
+
+impl ProofDescription {
+    /// Get the total size of the proof in bytes
+    pub fn size(&self) -> usize {
+        self.components.iter().map(|component| component.size).sum()
+    }
+}
+
+// Describe an encoded Schnorr proof of knowledge of the private key of a public key
+pub(crate) fn describe_schnorr_proof(public_key: &[u8], proof: &[u8]) -> Result<ProofDescription> {
+    ensure!(
+        proof.len() == SCHNORR_PROOF_LEN,
+        "Schnorr proofs are {SCHNORR_PROOF_LEN} bytes long"
+    );
+    let decoded_key = RistrettoPoint::decode(public_key).map_err(proof_error)?;
+    let (response, nonce_commitment) = proof.split_at(32);
+    let response = Scalar::decode(response, Endianness::Little).map_err(proof_error)?;
+    let nonce_commitment = RistrettoPoint::decode(nonce_commitment).map_err(proof_error)?;
+
+    // Replay the verifier's side of the transcript, which derives the challenge
+    let mut oracle = TracedOracle::new(SchnorrProof::create_new_transcript());
+    let verified = SchnorrProof::from((response, nonce_commitment))
+        .verify_proof(&decoded_key, &mut oracle)
+        .is_ok();
+    let events = oracle.into_parts().1;
+    let challenge = events
+        .iter()
+        .find_map(|event| match event {
+            TranscriptEvent::Challenge(challenge) => Some(*challenge),
+            TranscriptEvent::ProverMessage(_) => None,
+        })
+        // Proofs with an invalid point are rejected before a challenge is derived
+        .unwrap_or(Scalar::ZERO);
+
+    Ok(ProofDescription {
+        scheme: "schnorr",
+        components: vec![
+            ProofComponent {
+                name: "response",
+                size: 32,
+                hex: Some(hex::encode(response.encode(Endianness::Little))),
+            },
+            ProofComponent {
+                name: "nonce_commitment",
+                size: 32,
+                hex: Some(hex::encode(nonce_commitment.encode())),
+            },
+        ],
+        transcript: Some(events.iter().map(transcript_step).collect()),
+        equation: VerificationEquation {
+            equation: "response*G == nonce_commitment + challenge*public_key",
+            lhs: hex::encode((response * RISTRETTO_BASEPOINT_POINT).encode()),
+            rhs: hex::encode((nonce_commitment + challenge * decoded_key).encode()),
+        },
+        verified,
+    })
+}
+
+// Describe a range proof of a committed value
+pub(crate) fn describe_range(
+    commitment: &[u8],
+    bits: usize,
+    proof: &PyRangeProof,
+    context: &[u8],
+) -> Result<ProofDescription> {
+    let decoded_commitment = RistrettoPoint::decode(commitment).map_err(proof_error)?;
+    let bit_commitments = proof.proof.get_bit_commitments();
+    let recomposed: RistrettoPoint = bit_commitments
+        .iter()
+        .rev()
+        .fold(RistrettoPoint::default(), |sum, bit_commitment| {
+            sum + sum + bit_commitment
+        });
+    let bit_commitment_bytes: Vec<u8> = bit_commitments
+        .iter()
+        .flat_map(|bit_commitment| bit_commitment.encode())
+        .collect();
+
+    // Each bit proof holds a challenge and a response for each of the two possible bits, which
+    // range proofs don't expose
+    let bit_proofs = ProofComponent {
+        name: "bit_proofs",
+        size: bit_commitments.len() * 4 * 32,
+        hex: None,
+    };
+    Ok(ProofDescription {
+        scheme: "range",
+        components: vec![
+            ProofComponent {
+                name: "bit_commitments",
+                size: bit_commitment_bytes.len(),
+                hex: Some(hex::encode(bit_commitment_bytes)),
+            },
+            bit_proofs,
+        ],
+        transcript: None,
+        equation: VerificationEquation {
+            equation: "sum(2^i * bit_commitment_i) == commitment",
+            lhs: hex::encode(recomposed.encode()),
+            rhs: hex::encode(decoded_commitment.encode()),
+        },
+        verified: check_range_proof(commitment, bits, proof, context)?,
+    })
+}
+
+// Describe a message passing through a transcript
+fn transcript_step(event: &TranscriptEvent) -> TranscriptStep {
+    match event {
+        TranscriptEvent::ProverMessage(point) => TranscriptStep {
+            step: "prover_message",
+            value: hex::encode(point.encode()),
+        },
+        TranscriptEvent::Challenge(challenge) => TranscriptStep {
+            step: "challenge",
+            value: hex::encode(challenge.encode(Endianness::Little)),
+        },
+    }
+}
+
+/// Describe a Schnorr proof for display in a notebook: its components, the transcript the
+/// challenge is derived from and both sides of the verification equation as hex
+#[pyfunction]
+pub fn describe_schnorr(public_key: &[u8], proof: &[u8]) -> PyResult<ProofDescription> {
+    describe_schnorr_proof(public_key, proof).map_err(value_error)
+}
+
+/// Describe a range proof for display in a notebook: the sizes of the bit commitments and their
+/// proofs, and the recomposition of the bit commitments as hex
+#[pyfunction]
+pub fn describe_range_proof(
+    commitment: &[u8],
+    bits: usize,
+    proof: PyRef<'_, PyRangeProof>,
+    context: &[u8],
+) -> PyResult<ProofDescription> {
+    describe_range(commitment, bits, &proof, context).map_err(value_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schnorr_descriptions_show_the_transcript_and_equation() {
+        let private_key = Scalar::from(7u64).encode(Endianness::Little);
+        let (public_key, proof) = create_schnorr_proof(&private_key).unwrap();
+        let description = describe_schnorr_proof(&public_key, &proof).unwrap();
+        assert!(description.verified);
+        assert_eq!(description.size(), SCHNORR_PROOF_LEN);
+        assert_eq!(
+            description.components[1].hex,
+            Some(hex::encode(&proof[32..]))
+        );
+        assert_eq!(description.equation.lhs, description.equation.rhs);
+        let transcript = description.transcript.unwrap();
+        let steps: Vec<&str> = transcript.iter().map(|step| step.step).collect();
+        // The generator and public key are absorbed before the nonce commitment
+        assert_eq!(
+            steps,
+            [
+                "prover_message",
+                "prover_message",
+                "prover_message",
+                "challenge"
+            ]
+        );
+
+        // A tampered response shows up as differing sides of the equation
+        let mut tampered = proof.clone();
+        tampered[0] ^= 1;
+        let description = describe_schnorr_proof(&public_key, &tampered).unwrap();
+        assert!(!description.verified);
+        assert_ne!(description.equation.lhs, description.equation.rhs);
+        assert!(describe_schnorr_proof(&public_key, &proof[1..]).is_err());
+    }
+
+    #[test]
+    fn test_range_descriptions_recompose_the_bit_commitments() {
+        let (commitment, proof) = create_range_proof(5, 8, b"context").unwrap();
+        let description = describe_range(&commitment, 8, &proof, b"context").unwrap();
+        assert!(description.verified);
+        assert_eq!(description.size(), 8 * 32 + 8 * 128);
+        assert_eq!(description.transcript, None);
+        assert_eq!(description.equation.lhs, description.equation.rhs);
+        assert_eq!(description.equation.rhs, hex::encode(&commitment));
+
+        let description = describe_range(&commitment, 8, &proof, b"other").unwrap();
+        assert!(!description.verified);
+    }
+}
@@ -0,0 +1,279 @@
+//! Python bindings for creating and verifying Schnorr proofs, range proofs and polynomial commitments
+
+use super::*;
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use encodings::{CommitAndProve, Endianness, PointEncoding, ScalarEncoding};
+use merlin_example::{RangeBackend, SchnorrProof};
+use pyo3::exceptions::PyValueError;
+use snarkvm::prelude::{anyhow, ensure, Result};
+use std::borrow::Cow;
+use std::fmt::Debug;
+use zksnarks_example::{EvaluationProof, KzgCommitment, KZG_POINT_LEN};
+
+// Values cross the Python boundary as bytes: scalars are 32 little endian bytes, Ristretto points
+// are 32 byte compressed points and BLS12-381 G1 points are 48 byte compressed points. Invalid
+// encodings and failed proof generation raise a ValueError, while a proof which decodes but
+// doesn't verify makes the verify functions return False.
+//
+// Each binding is a thin wrapper converting the errors of a plain Rust function, since PyO3 error
+// types can't be used without a Python interpreter, e.g. in tests.
+
+// Bytes returned to Python
+type Bytes = Cow<'static, [u8]>;
+
+// Length of an encoded Schnorr proof, i.e. the response followed by the nonce commitment
+pub(crate) const SCHNORR_PROOF_LEN: usize = 64;
+
+// Convert an error of the proof crates
+pub(crate) fn proof_error<E: Debug>(error: E) -> snarkvm::console::prelude::Error {
+    anyhow!("{error:?}")
+}
+
+// Convert an error into a Python ValueError
+pub(crate) fn value_error(error: snarkvm::console::prelude::Error) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+// Decode a compressed BLS12-381 G1 point
+fn decode_g1(bytes: &[u8]) -> Result<[u8; KZG_POINT_LEN]> {
+    bytes.try_into().map_err(proof_error)
+}
+
+// Prove knowledge of a private key, returning the public key and the encoded proof
+pub(crate) fn create_schnorr_proof(private_key: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let private_key = Scalar::decode(private_key, Endianness::Little).map_err(proof_error)?;
+    let public_key = private_key * RISTRETTO_BASEPOINT_POINT;
+    let proof =
+        SchnorrProof::generate_proof(&private_key, &mut SchnorrProof::create_new_transcript());
+    let (response, nonce_commitment) = proof.get_proof_pair();
+    let mut proof = response.encode(Endianness::Little);
+    proof.extend(nonce_commitment.encode());
+    Ok((public_key.encode(), proof))
+}
+
+// Verify an encoded proof of knowledge of the private key of a public key
+pub(crate) fn check_schnorr_proof(public_key: &[u8], proof: &[u8]) -> Result<bool> {
+    ensure!(
+        proof.len() == SCHNORR_PROOF_LEN,
+        "Schnorr proofs are {SCHNORR_PROOF_LEN} bytes long"
+    );
+    let public_key = RistrettoPoint::decode(public_key).map_err(proof_error)?;
+    let (response, nonce_commitment) = proof.split_at(32);
+    let proof = SchnorrProof::from((
+        Scalar::decode(response, Endianness::Little).map_err(proof_error)?,
+        RistrettoPoint::decode(nonce_commitment).map_err(proof_error)?,
+    ));
+    Ok(proof
+        .verify_proof(&public_key, &mut SchnorrProof::create_new_transcript())
+        .is_ok())
+}
+
+/// Generate a random Schnorr private key
+#[pyfunction]
+pub fn generate_schnorr_key() -> Bytes {
+    Scalar::random(&mut rand::rngs::OsRng)
+        .encode(Endianness::Little)
+        .into()
+}
+
+/// Prove knowledge of a private key, returning the public key and the proof
+#[pyfunction]
+pub fn schnorr_prove(private_key: &[u8]) -> PyResult<(Bytes, Bytes)> {
+    let (public_key, proof) = create_schnorr_proof(private_key).map_err(value_error)?;
+    Ok((public_key.into(), proof.into()))
+}
+
+/// Verify a proof of knowledge of the private key of a public key
+#[pyfunction]
+pub fn schnorr_verify(public_key: &[u8], proof: &[u8]) -> PyResult<bool> {
+    check_schnorr_proof(public_key, proof).map_err(value_error)
+}
+
+/// Proof that a Pedersen commitment commits to a value within [0, 2^bits)
+#[pyclass(name = "RangeProof")]
+#[derive(Clone, Debug)]
+pub struct PyRangeProof {
+    pub(crate) proof: merlin_example::RangeProof,
+}
+
+// Commit to a value and prove it lies within [0, 2^bits)
+pub(crate) fn create_range_proof(
+    value: u64,
+    bits: usize,
+    context: &[u8],
+) -> Result<(Vec<u8>, PyRangeProof)> {
+    let backend = RangeBackend::default();
+    let (commitment, opening) = backend.commit(&Scalar::from(value)).map_err(proof_error)?;
+    let proof = backend
+        .prove(&commitment, &opening, &bits, context)
+        .map_err(proof_error)?;
+    Ok((commitment.encode(), PyRangeProof { proof }))
+}
+
+// Verify that an encoded commitment commits to a value within [0, 2^bits)
+pub(crate) fn check_range_proof(
+    commitment: &[u8],
+    bits: usize,
+    proof: &PyRangeProof,
+    context: &[u8],
+) -> Result<bool> {
+    let commitment = RistrettoPoint::decode(commitment).map_err(proof_error)?;
+    Ok(RangeBackend::default()
+        .verify(&commitment, &bits, &proof.proof, context)
+        .is_ok())
+}
+
+/// Commit to a value and prove it lies within [0, 2^bits), returning the commitment and the
+/// proof. The proof is bound to the context, which the verifier must pass in too.
+#[pyfunction]
+pub fn prove_range(value: u64, bits: usize, context: &[u8]) -> PyResult<(Bytes, PyRangeProof)> {
+    let (commitment, proof) = create_range_proof(value, bits, context).map_err(value_error)?;
+    Ok((commitment.into(), proof))
+}
+
+/// Verify that a commitment commits to a value within [0, 2^bits)
+#[pyfunction]
+pub fn verify_range(
+    commitment: &[u8],
+    bits: usize,
+    proof: PyRef<'_, PyRangeProof>,
+    context: &[u8],
+) -> PyResult<bool> {
+    check_range_proof(commitment, bits, &proof, context).map_err(value_error)
+}
+
+// Convert coefficients passed in from Python
+pub(crate) fn to_coefficients(coefficients: Vec<u64>) -> Vec<bls12_381::Scalar> {
+    coefficients.into_iter().map(Into::into).collect()
+}
+
+/// KZG reference string for committing to polynomials and proving their evaluations
+#[pyclass(name = "KzgSetup")]
+#[derive(Clone, Debug)]
+pub struct PyKzgSetup {
+    setup: zksnarks_example::KzgSetup,
+}
+
+impl PyKzgSetup {
+    // Commit to a polynomial, returning the encoded commitment
+    fn create_commitment(&self, coefficients: &[bls12_381::Scalar]) -> Result<Vec<u8>> {
+        let commitment = self.setup.commit(coefficients).map_err(proof_error)?;
+        Ok(commitment.to_bytes().to_vec())
+    }
+
+    // Evaluate a polynomial at a point, returning the encoded value and proof
+    pub(crate) fn create_evaluation_proof(
+        &self,
+        coefficients: &[bls12_381::Scalar],
+        point: u64,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let point = bls12_381::Scalar::from(point);
+        let commitment = self.setup.commit(coefficients).map_err(proof_error)?;
+        let opening = self.setup.open(coefficients, &point).map_err(proof_error)?;
+        let (statement, proof) = opening.into_evaluation(commitment, point);
+        Ok((
+            statement.value.encode(Endianness::Little),
+            proof.to_bytes().to_vec(),
+        ))
+    }
+
+    // Verify an encoded evaluation proof against an encoded commitment
+    fn check_evaluation_proof(
+        &self,
+        commitment: &[u8],
+        point: u64,
+        value: &[u8],
+        proof: &[u8],
+    ) -> Result<bool> {
+        let commitment = KzgCommitment::from_bytes(&decode_g1(commitment)?).map_err(proof_error)?;
+        let proof = EvaluationProof::from_bytes(&decode_g1(proof)?).map_err(proof_error)?;
+        let value = bls12_381::Scalar::decode(value, Endianness::Little).map_err(proof_error)?;
+        Ok(self
+            .setup
+            .verify(&commitment, &(point.into(), value), &proof, &[])
+            .is_ok())
+    }
+}
+
+#[pymethods]
+impl PyKzgSetup {
+    /// Generate a reference string for polynomials of up to `max_degree`
+    #[new]
+    pub fn new(max_degree: usize) -> Self {
+        Self {
+            setup: zksnarks_example::KzgSetup::new(max_degree),
+        }
+    }
+
+    /// Commit to a polynomial given by coefficients in ascending order of degree
+    pub fn commit(&self, coefficients: Vec<u64>) -> PyResult<Bytes> {
+        let commitment = self
+            .create_commitment(&to_coefficients(coefficients))
+            .map_err(value_error)?;
+        Ok(commitment.into())
+    }
+
+    /// Evaluate a polynomial at a point, returning the value and the proof of the evaluation
+    pub fn prove_evaluation(&self, coefficients: Vec<u64>, point: u64) -> PyResult<(Bytes, Bytes)> {
+        let (value, proof) = self
+            .create_evaluation_proof(&to_coefficients(coefficients), point)
+            .map_err(value_error)?;
+        Ok((value.into(), proof.into()))
+    }
+
+    /// Verify that a committed polynomial evaluates to `value` at `point`
+    pub fn verify_evaluation(
+        &self,
+        commitment: &[u8],
+        point: u64,
+        value: &[u8],
+        proof: &[u8],
+    ) -> PyResult<bool> {
+        self.check_evaluation_proof(commitment, point, value, proof)
+            .map_err(value_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schnorr_and_range_proofs_round_trip_through_bytes() {
+        let key = Scalar::random(&mut rand::rngs::OsRng).encode(Endianness::Little);
+        let (public_key, proof) = create_schnorr_proof(&key).unwrap();
+        assert!(check_schnorr_proof(&public_key, &proof).unwrap());
+        let (other_key, _) = create_schnorr_proof(&[1; 32]).unwrap();
+        assert!(!check_schnorr_proof(&other_key, &proof).unwrap());
+        assert!(check_schnorr_proof(&public_key, &proof[1..]).is_err());
+
+        let (commitment, proof) = create_range_proof(200, 8, b"features").unwrap();
+        assert!(check_range_proof(&commitment, 8, &proof, b"features").unwrap());
+        assert!(!check_range_proof(&commitment, 8, &proof, b"labels").unwrap());
+        assert!(create_range_proof(300, 8, b"features").is_err());
+    }
+
+    #[test]
+    fn test_polynomial_evaluations_round_trip_through_bytes() {
+        let setup = PyKzgSetup::new(4);
+        // f(x) = 3 + 2x + x^2
+        let coefficients = to_coefficients(vec![3, 2, 1]);
+        let commitment = setup.create_commitment(&coefficients).unwrap();
+        let (value, proof) = setup.create_evaluation_proof(&coefficients, 5).unwrap();
+        assert_eq!(
+            value,
+            bls12_381::Scalar::from(38u64).encode(Endianness::Little)
+        );
+        assert!(setup
+            .check_evaluation_proof(&commitment, 5, &value, &proof)
+            .unwrap());
+        assert!(!setup
+            .check_evaluation_proof(&commitment, 6, &value, &proof)
+            .unwrap());
+        assert!(setup
+            .check_evaluation_proof(&commitment[1..], 5, &value, &proof)
+            .is_err());
+    }
+}
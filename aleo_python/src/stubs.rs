@@ -0,0 +1,534 @@
+//! Python type stubs and dataclasses generated from the Rust bindings
+
+use quote::ToTokens;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+use syn::{Attribute, Expr, FnArg, GenericArgument, ImplItem, Item, Lit, LitStr, Meta, Pat};
+use syn::{PathArguments, ReturnType, Signature, Type};
+
+// The extension module is shipped inside the aleo_python package, whose __init__.py re-exports
+// it. Python sees the bindings as untyped builtins, so the package also ships
+//
+// - aleo_python.pyi: type stubs of every function and class registered in lib.rs, with the
+// parameter names, types and doc comments of the Rust signatures
+// - envelopes.py: frozen dataclasses bundling the statement of a proof, i.e. the public inputs of
+// its verifier, with the proof itself, so clients pass typed values around instead of loose
+// bytes
+//
+// Both are generated by parsing the binding sources and the test below fails whenever the
+// checked in files drift from them, e.g. after adding a binding or changing a signature. Rust
+// types map onto Python types as the PyO3 conversions do, and a type without a mapping fails the
+// generation so that new conversions are typed deliberately. After an intended change, regenerate
+// the files with
+//
+//     UPDATE_PYTHON_STUBS=1 cargo test -p aleo_python stubs
+//
+// and review the diff.
+
+// Environment variable requesting the stubs to be regenerated
+const UPDATE_VAR: &str = "UPDATE_PYTHON_STUBS";
+
+// Sources of the bindings
+const BINDING_SOURCES: [&str; 3] = [
+    include_str!("hash.rs"),
+    include_str!("proofs.rs"),
+    include_str!("notebook.rs"),
+];
+
+// Source registering the bindings with the module
+const MODULE_SOURCE: &str = include_str!("lib.rs");
+
+// Class of a verifier which is a method, and the name of the parameter passing an instance of it
+type Receiver = Option<(&'static str, &'static str)>;
+
+// Proofs wrapped into envelopes, as the name of the envelope, the class and parameter name of the
+// verifier if it's a method, and the name of the verifier
+const ENVELOPES: [(&str, Receiver, &str); 3] = [
+    ("Schnorr", None, "schnorr_verify"),
+    ("Range", None, "verify_range"),
+    (
+        "KzgEvaluation",
+        Some(("KzgSetup", "setup")),
+        "verify_evaluation",
+    ),
+];
+
+// Parameter of a verifier holding the proof, every other parameter is part of the statement
+const PROOF_PARAM: &str = "proof";
+
+// Length beyond which generated Python calls are split over several lines
+const MAX_LINE_LEN: usize = 100;
+
+// Python type of the values hash_struct accepts
+const STRUCT_VALUE_ALIAS: &str = r#"StructValue = Union[
+    None,
+    bool,
+    int,
+    bytes,
+    str,
+    List["StructValue"],
+    Tuple["StructValue", ...],
+    Dict[str, "StructValue"],
+]
+"#;
+
+// Function or method exposed to Python
+#[derive(Clone, Debug)]
+struct Binding {
+    name: String,
+    doc: String,
+    params: Vec<(String, String)>,
+    returns: String,
+    constructor: bool,
+}
+
+// Class exposed to Python
+#[derive(Clone, Debug)]
+struct Class {
+    rust_name: String,
+    name: String,
+    doc: String,
+    methods: Vec<Binding>,
+}
+
+// Every binding of the module, in the order they are registered
+#[derive(Clone, Debug)]
+struct Bindings {
+    functions: Vec<Binding>,
+    classes: Vec<Class>,
+}
+
+impl Bindings {
+    // Parse the bindings out of their sources
+    fn parse() -> Self {
+        let files: Vec<syn::File> = BINDING_SOURCES
+            .iter()
+            .map(|source| syn::parse_file(source).expect("binding sources parse"))
+            .collect();
+        let items: Vec<&Item> = files.iter().flat_map(|file| &file.items).collect();
+
+        // Python names of the classes are needed to type the functions, so classes come first
+        let mut classes: Vec<Class> = items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Struct(item) => Some(item),
+                _ => None,
+            })
+            .filter_map(|item| {
+                let attribute = find_attribute(&item.attrs, "pyclass")?;
+                let rust_name = item.ident.to_string();
+                Some(Class {
+                    name: class_name(attribute).unwrap_or_else(|| rust_name.clone()),
+                    rust_name,
+                    doc: doc_comment(&item.attrs),
+                    methods: vec![],
+                })
+            })
+            .collect();
+        let class_names: BTreeMap<String, String> = classes
+            .iter()
+            .map(|class| (class.rust_name.clone(), class.name.clone()))
+            .collect();
+
+        let mut functions = vec![];
+        for item in items {
+            match item {
+                Item::Fn(item) if find_attribute(&item.attrs, "pyfunction").is_some() => {
+                    functions.push(binding(&item.attrs, &item.sig, None, &class_names));
+                }
+                Item::Impl(item) if find_attribute(&item.attrs, "pymethods").is_some() => {
+                    let rust_name = item.self_ty.to_token_stream().to_string();
+                    let class = classes
+                        .iter_mut()
+                        .find(|class| class.rust_name == rust_name)
+                        .expect("methods belong to a class");
+                    for method in &item.items {
+                        if let ImplItem::Fn(method) = method {
+                            let binding =
+                                binding(&method.attrs, &method.sig, Some(&rust_name), &class_names);
+                            class.methods.push(binding);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Order the bindings like lib.rs registers them, which also checks they are all registered
+        functions
+            .sort_by_key(|function| registration(&format!("wrap_pyfunction!({},", function.name)));
+        classes.sort_by_key(|class| registration(&format!("add_class::<{}>", class.rust_name)));
+        Self { functions, classes }
+    }
+
+    // Find a function, or a method of a class
+    fn find(&self, class: Option<&str>, name: &str) -> &Binding {
+        let functions = match class {
+            Some(class) => {
+                &self
+                    .classes
+                    .iter()
+                    .find(|candidate| candidate.name == class)
+                    .expect("known class")
+                    .methods
+            }
+            None => &self.functions,
+        };
+        functions
+            .iter()
+            .find(|function| function.name == name)
+            .unwrap_or_else(|| panic!("{name} is a binding"))
+    }
+}
+
+// Find an attribute by name
+fn find_attribute<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.path().is_ident(name))
+}
+
+// Python name given to a class by `#[pyclass(name = "...")]`
+fn class_name(attribute: &Attribute) -> Option<String> {
+    let mut name = None;
+    if matches!(attribute.meta, Meta::List(_)) {
+        attribute
+            .parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse::<LitStr>()?.value());
+                }
+                Ok(())
+            })
+            .expect("pyclass arguments parse");
+    }
+    name
+}
+
+// Doc comment of an item with the leading space of every line removed
+fn doc_comment(attributes: &[Attribute]) -> String {
+    attributes
+        .iter()
+        .filter_map(|attribute| match &attribute.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(line) => Some(line.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Position of a registration within lib.rs
+fn registration(pattern: &str) -> usize {
+    MODULE_SOURCE
+        .find(pattern)
+        .unwrap_or_else(|| panic!("{pattern} is missing from lib.rs"))
+}
+
+// Describe a function or method from its Rust signature
+fn binding(
+    attributes: &[Attribute],
+    signature: &Signature,
+    class: Option<&str>,
+    class_names: &BTreeMap<String, String>,
+) -> Binding {
+    let to_python = |ty: &Type| python_type(ty, class, class_names);
+    let params = signature
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(input) => match &*input.pat {
+                Pat::Ident(pat) => Some((pat.ident.to_string(), to_python(&input.ty))),
+                _ => panic!("bindings take named parameters"),
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    Binding {
+        name: signature.ident.to_string(),
+        doc: doc_comment(attributes),
+        params,
+        returns: match &signature.output {
+            ReturnType::Default => "None".to_string(),
+            ReturnType::Type(_, ty) => to_python(ty),
+        },
+        constructor: find_attribute(attributes, "new").is_some(),
+    }
+}
+
+// Python type a Rust type converts to or from
+fn python_type(ty: &Type, class: Option<&str>, class_names: &BTreeMap<String, String>) -> String {
+    let to_python = |ty: &Type| python_type(ty, class, class_names);
+    let is_byte = |ty: &Type| ty.to_token_stream().to_string() == "u8";
+    match ty {
+        Type::Reference(reference) => to_python(&reference.elem),
+        Type::Slice(slice) if is_byte(&slice.elem) => "bytes".to_string(),
+        Type::Tuple(tuple) => {
+            let items: Vec<String> = tuple.elems.iter().map(to_python).collect();
+            format!("Tuple[{}]", items.join(", "))
+        }
+        Type::Path(path) => {
+            let segment = path.path.segments.last().expect("paths aren't empty");
+            let arguments: Vec<&Type> = match &segment.arguments {
+                PathArguments::AngleBracketed(arguments) => arguments
+                    .args
+                    .iter()
+                    .filter_map(|argument| match argument {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+            match (segment.ident.to_string().as_str(), arguments.as_slice()) {
+                ("PyResult" | "PyRef", [inner]) => to_python(inner),
+                ("Vec", [inner]) if is_byte(inner) => "bytes".to_string(),
+                ("Vec", [inner]) => format!("List[{}]", to_python(inner)),
+                ("u8" | "u64" | "usize" | "i64" | "BigUint", []) => "int".to_string(),
+                ("bool", []) => "bool".to_string(),
+                ("str" | "String", []) => "str".to_string(),
+                ("Bytes", []) => "bytes".to_string(),
+                ("StructValue", []) => "StructValue".to_string(),
+                // Descriptions of proofs are converted into plain dicts
+                ("ProofDescription", []) => "Dict[str, Any]".to_string(),
+                ("Self", []) => class_names[class.expect("Self is used in methods")].clone(),
+                (name, []) if class_names.contains_key(name) => class_names[name].clone(),
+                _ => panic!("no Python type for {}", ty.to_token_stream()),
+            }
+        }
+        _ => panic!("no Python type for {}", ty.to_token_stream()),
+    }
+}
+
+// Write a docstring indented to the given depth
+fn write_docstring(output: &mut String, doc: &str, indent: &str) {
+    let doc = doc.replace('\n', &format!("\n{indent}"));
+    writeln!(output, "{indent}\"\"\"{doc}\"\"\"").unwrap();
+}
+
+// Write the stub of a function or method
+fn write_function_stub(output: &mut String, function: &Binding, indent: &str) {
+    let mut params: Vec<String> = function
+        .params
+        .iter()
+        .map(|(name, ty)| format!("{name}: {ty}"))
+        .collect();
+    let (name, returns) = match indent.is_empty() {
+        true => (function.name.as_str(), function.returns.as_str()),
+        false => {
+            params.insert(0, "self".to_string());
+            match function.constructor {
+                true => ("__init__", "None"),
+                false => (function.name.as_str(), function.returns.as_str()),
+            }
+        }
+    };
+    writeln!(
+        output,
+        "{indent}def {name}({}) -> {returns}:",
+        params.join(", ")
+    )
+    .unwrap();
+    write_docstring(output, &function.doc, &format!("{indent}    "));
+    writeln!(output, "{indent}    ...").unwrap();
+}
+
+// Render the type stubs of the extension module
+fn render_stubs(bindings: &Bindings) -> String {
+    let mut output = String::new();
+    output.push_str(
+        "# Type stubs of the aleo_python extension module, generated from the Rust bindings by\n",
+    );
+    output.push_str("# src/stubs.rs\n");
+    output.push_str("from typing import Any, Dict, List, Tuple, Union\n\n");
+    output.push_str(STRUCT_VALUE_ALIAS);
+    for function in &bindings.functions {
+        output.push('\n');
+        write_function_stub(&mut output, function, "");
+    }
+    for class in &bindings.classes {
+        writeln!(output, "\nclass {}:", class.name).unwrap();
+        write_docstring(&mut output, &class.doc, "    ");
+        for method in &class.methods {
+            output.push('\n');
+            write_function_stub(&mut output, method, "    ");
+        }
+    }
+    output
+}
+
+// Render the dataclasses bundling statements with their proofs
+fn render_envelopes(bindings: &Bindings) -> String {
+    let class_names: BTreeSet<&str> = bindings
+        .classes
+        .iter()
+        .map(|class| class.name.as_str())
+        .collect();
+    let mut imports = BTreeSet::new();
+    let mut exports = vec![];
+    let mut body = String::new();
+    for (name, class, verifier_name) in ENVELOPES {
+        let verifier = bindings.find(class.map(|(class, _)| class), verifier_name);
+        let qualified_name = match class {
+            Some((class, _)) => format!("{class}.{verifier_name}"),
+            None => verifier_name.to_string(),
+        };
+        assert_eq!(
+            verifier.returns, "bool",
+            "{verifier_name} returns whether the proof verifies"
+        );
+        let (statement, proof): (Vec<_>, Vec<_>) = verifier
+            .params
+            .iter()
+            .partition(|(param, _)| param != PROOF_PARAM);
+        let [(_, proof_type)] = proof.as_slice() else {
+            panic!("{verifier_name} takes a {PROOF_PARAM} parameter")
+        };
+        let types = statement.iter().map(|(_, ty)| ty).chain([proof_type]);
+        imports.extend(
+            types
+                .filter(|ty| class_names.contains(ty.as_str()))
+                .cloned(),
+        );
+
+        writeln!(body, "\n\n@dataclass(frozen=True)\nclass {name}Statement:").unwrap();
+        write_docstring(
+            &mut body,
+            &format!("Public inputs of {qualified_name}"),
+            "    ",
+        );
+        for (param, ty) in &statement {
+            writeln!(body, "    {param}: {ty}").unwrap();
+        }
+
+        writeln!(body, "\n\n@dataclass(frozen=True)\nclass {name}Envelope:").unwrap();
+        write_docstring(
+            &mut body,
+            &format!("Proof checked by {qualified_name} along with its statement"),
+            "    ",
+        );
+        writeln!(
+            body,
+            "    statement: {name}Statement\n    {PROOF_PARAM}: {proof_type}\n"
+        )
+        .unwrap();
+        let arguments: Vec<String> = verifier
+            .params
+            .iter()
+            .map(|(param, _)| match param == PROOF_PARAM {
+                true => format!("self.{param}"),
+                false => format!("self.statement.{param}"),
+            })
+            .collect();
+        let (params, call) = match class {
+            Some((class, receiver)) => {
+                imports.insert(class.to_string());
+                (
+                    format!(", {receiver}: {class}"),
+                    format!("{receiver}.{verifier_name}"),
+                )
+            }
+            None => {
+                imports.insert(verifier_name.to_string());
+                (String::new(), verifier_name.to_string())
+            }
+        };
+        writeln!(body, "    def verify(self{params}) -> bool:").unwrap();
+        write_docstring(&mut body, &verifier.doc, "        ");
+        let line = format!("        return {call}({})", arguments.join(", "));
+        match line.len() < MAX_LINE_LEN {
+            true => writeln!(body, "{line}").unwrap(),
+            false => {
+                let arguments = arguments.join(",\n            ");
+                writeln!(
+                    body,
+                    "        return {call}(\n            {arguments},\n        )"
+                )
+                .unwrap()
+            }
+        }
+        exports.extend([format!("{name}Statement"), format!("{name}Envelope")]);
+    }
+
+    let mut output = String::new();
+    output.push_str("\"\"\"Statements of the aleo_python verifiers bundled with their proofs\n\n");
+    output.push_str("Generated from the Rust bindings by src/stubs.rs.\n\"\"\"\n");
+    output.push_str("from dataclasses import dataclass\n\n");
+    let imports: Vec<String> = imports.into_iter().collect();
+    writeln!(output, "from .aleo_python import {}\n", imports.join(", ")).unwrap();
+    output.push_str("__all__ = [\n");
+    for export in exports {
+        writeln!(output, "    \"{export}\",").unwrap();
+    }
+    output.push_str("]\n");
+    output.push_str(&body);
+    output
+}
+
+// Location of a generated file relative to the crate
+fn package_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("aleo_python")
+        .join(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_python_stubs_match_the_bindings() {
+        let bindings = Bindings::parse();
+        let files = [
+            ("aleo_python.pyi", render_stubs(&bindings)),
+            ("envelopes.py", render_envelopes(&bindings)),
+        ];
+        for (name, generated) in files {
+            if std::env::var_os(UPDATE_VAR).is_some() {
+                std::fs::write(package_path(name), &generated).unwrap();
+            }
+            let checked_in = std::fs::read_to_string(package_path(name)).unwrap_or_default();
+            assert!(
+                checked_in == generated,
+                "{name} is stale, regenerate it by setting {UPDATE_VAR}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_bindings_are_parsed_with_their_python_types() {
+        let bindings = Bindings::parse();
+        let registered = MODULE_SOURCE.matches("wrap_pyfunction!(").count();
+        assert_eq!(bindings.functions.len(), registered);
+        assert_eq!(bindings.functions[0].name, "hash_int");
+
+        let verify_range = bindings.find(None, "verify_range");
+        let params: Vec<(&str, &str)> = verify_range
+            .params
+            .iter()
+            .map(|(name, ty)| (name.as_str(), ty.as_str()))
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("commitment", "bytes"),
+                ("bits", "int"),
+                ("proof", "RangeProof"),
+                ("context", "bytes")
+            ]
+        );
+        let new = bindings.find(Some("KzgSetup"), "new");
+        assert!(new.constructor);
+        assert_eq!(new.returns, "KzgSetup");
+        assert_eq!(
+            bindings.find(Some("KzgSetup"), "prove_evaluation").returns,
+            "Tuple[bytes, bytes]"
+        );
+    }
+}
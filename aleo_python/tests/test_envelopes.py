@@ -0,0 +1,38 @@
+"""Checks of the dataclasses bundling the statements of the verifiers with their proofs"""
+import dataclasses
+
+import pytest
+
+import aleo_python
+from aleo_python import (
+    KzgEvaluationEnvelope,
+    KzgEvaluationStatement,
+    RangeEnvelope,
+    RangeStatement,
+    SchnorrEnvelope,
+    SchnorrStatement,
+)
+
+
+def test_envelopes_verify_their_proofs():
+    public_key, proof = aleo_python.schnorr_prove(aleo_python.generate_schnorr_key())
+    assert SchnorrEnvelope(SchnorrStatement(public_key), proof).verify()
+
+    commitment, range_proof = aleo_python.prove_range(5, 8, b"context")
+    statement = RangeStatement(commitment=commitment, bits=8, context=b"context")
+    assert RangeEnvelope(statement, range_proof).verify()
+    assert not RangeEnvelope(dataclasses.replace(statement, context=b"other"), range_proof).verify()
+
+    setup = aleo_python.KzgSetup(4)
+    commitment = setup.commit([3, 2, 1])
+    value, evaluation_proof = setup.prove_evaluation([3, 2, 1], 5)
+    envelope = KzgEvaluationEnvelope(KzgEvaluationStatement(commitment, 5, value), evaluation_proof)
+    assert envelope.verify(setup)
+
+
+def test_envelopes_are_immutable():
+    envelope = SchnorrEnvelope(SchnorrStatement(b"\x00" * 32), b"\x00" * 64)
+    with pytest.raises(dataclasses.FrozenInstanceError):
+        envelope.proof = b""
+    fields = [field.name for field in dataclasses.fields(RangeStatement)]
+    assert fields == ["commitment", "bits", "context"]
@@ -0,0 +1,63 @@
+"""Conformance suite checking the aleo_python module against golden vectors from the Rust crates.
+
+The vectors in golden_vectors.json are generated and checked by the golden module of the crate,
+see src/golden.rs for how to regenerate them after an intended change.
+"""
+import json
+from pathlib import Path
+
+import pytest
+
+import aleo_python
+
+VECTORS = json.loads((Path(__file__).parent / "golden_vectors.json").read_text())
+
+
+def vectors(section):
+    return pytest.mark.parametrize("vector", VECTORS[section])
+
+
+@vectors("hash_int")
+def test_hash_int(vector):
+    assert aleo_python.hash_int(vector["input"]) == vector["output"]
+
+
+@vectors("hash_bytes")
+def test_hash_bytes(vector):
+    assert aleo_python.hash_bytes(bytes.fromhex(vector["input"])) == vector["output"]
+
+
+@vectors("hash_fields")
+def test_hash_fields(vector):
+    assert aleo_python.hash_fields(vector["input"]) == vector["output"]
+
+
+@vectors("hash_field_list")
+def test_hash_field_list(vector):
+    integers = [int(integer) for integer in vector["input"]]
+    assert aleo_python.hash_field_list(integers) == vector["output"]
+
+
+@vectors("hash_struct")
+def test_hash_struct(vector):
+    assert aleo_python.hash_struct(vector["input"]) == vector["output"]
+
+
+@vectors("schnorr")
+def test_schnorr(vector):
+    private_key = bytes.fromhex(vector["private_key"])
+    public_key = bytes.fromhex(vector["public_key"])
+    proof = bytes.fromhex(vector["proof"])
+    assert aleo_python.schnorr_prove(private_key)[0] == public_key
+    assert aleo_python.schnorr_verify(public_key, proof)
+
+    # Flipping a bit of the response makes the proof fail
+    tampered = bytes([proof[0] ^ 1]) + proof[1:]
+    assert not aleo_python.schnorr_verify(public_key, tampered)
+
+
+@vectors("kzg_evaluation")
+def test_kzg_evaluation(vector):
+    setup = aleo_python.KzgSetup(4)
+    value, _ = setup.prove_evaluation(vector["coefficients"], vector["point"])
+    assert value == bytes.fromhex(vector["value"])
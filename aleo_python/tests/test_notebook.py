@@ -0,0 +1,31 @@
+"""Checks of the proof descriptions meant for display in notebooks"""
+import json
+
+import aleo_python
+
+
+def test_schnorr_descriptions_are_plain_data():
+    public_key, proof = aleo_python.schnorr_prove(aleo_python.generate_schnorr_key())
+    description = aleo_python.describe_schnorr(public_key, proof)
+    # Only JSON compatible values, so notebooks and pandas display them as is
+    json.dumps(description)
+    assert description["verified"]
+    assert description["size"] == 64
+    assert [step["step"] for step in description["transcript"]] == [
+        "prover_message",
+        "prover_message",
+        "prover_message",
+        "challenge",
+    ]
+    assert description["equation"]["holds"]
+
+
+def test_range_proof_descriptions_hide_the_transcript():
+    commitment, proof = aleo_python.prove_range(5, 8, b"context")
+    description = aleo_python.describe_range_proof(commitment, 8, proof, b"context")
+    assert description["transcript"] is None
+    assert description["equation"]["rhs"] == commitment.hex()
+    assert [component["name"] for component in description["components"]] == [
+        "bit_commitments",
+        "bit_proofs",
+    ]
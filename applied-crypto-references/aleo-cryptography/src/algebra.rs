@@ -1,4 +1,4 @@
-///! This module explores Aleo's basic algebraic structures and their properties
+//! This module explores Aleo's basic algebraic structures and their properties
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,15 @@
+//! Errors in aleo-cryptography
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Integer (in decimal) is not smaller than the modulus of the target field
+    ValueExceedsModulus(String),
+    /// Digit decomposition contained a digit that isn't valid in the radix
+    InvalidDigits(u32),
+    /// Radix must be between 2 and 256
+    InvalidRadix(u32),
+    /// Modulus (in decimal) isn't an odd integer between 3 and 2^384
+    InvalidModulus(String),
+    /// Zero has no inverse
+    NotInvertible,
+}
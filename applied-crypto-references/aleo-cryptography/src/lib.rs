@@ -1 +1,10 @@
 mod algebra;
+mod error;
+mod montgomery;
+mod scalar_bridge;
+
+pub use crate::{
+    error::Error,
+    montgomery::{MontgomeryElement, MontgomeryField, MONTGOMERY_LIMBS},
+    scalar_bridge::{convert, from_digits, to_digits, FieldElement, WrapMode},
+};
@@ -0,0 +1,387 @@
+//! Reference implementation of Montgomery-form arithmetic modulo primes of up to 384 bits
+
+use crate::Error;
+use num_bigint::BigUint;
+
+// Libraries such as snarkVM store field elements as arrays of 64-bit limbs, e.g. the
+// `BigInteger384` of BLS12-377's base field, and never reduce a product with a division. They
+// keep every element `a` in Montgomery form `a*R mod p` instead, with `R = 2^384`, one bit past
+// the limbs:
+//
+// 1. Addition and subtraction work on Montgomery forms unchanged, since `aR + bR = (a + b)R`. A
+// sum of two values below `p` is below `2p`, so a single conditional subtraction of `p` reduces
+// it, and a subtraction that borrows is fixed by adding `p` back.
+// 2. Multiplying two forms gives `abR^2`, one factor of `R` too many. Montgomery reduction divides
+// by `R` instead of reducing modulo `p`: for a product `T`, it picks `m` with `T + m*p` divisible
+// by `R`, and `(T + m*p) / R` is congruent to `T/R` modulo `p` and smaller than `2p`. Dividing by
+// `R` is free, it only drops the low limbs.
+// 3. The multiple `m` is found one limb at a time. Adding `m_i*p` with
+// `m_i = t_0 * (-p^-1 mod 2^64)` clears the lowest limb `t_0` of the running sum, which is then
+// shifted away. After one round per limb all of `R` has been divided out. This implementation
+// interleaves those rounds with the schoolbook multiplication ("coarsely integrated operand
+// scanning"), so the running sum never grows past 8 limbs.
+// 4. Converting into Montgomery form is a Montgomery multiplication by `R^2 mod p`, since
+// `a * R^2 / R = aR`, and converting out of it is one by 1. Both constants are derived from the
+// modulus: `R^2 mod p` by doubling 1 modulo `p` 768 times, and `-p^-1 mod 2^64` with Newton's
+// iteration, which doubles the number of correct low bits of an inverse at every step.
+// 5. Exponentiation squares and multiplies by the bits of the exponent, and inversion is the
+// exponentiation `a^(p-2)`, which is `a^-1` by Fermat's little theorem when `p` is prime.
+//
+// The code branches on the final subtractions and on the bits of exponents, so unlike snarkVM's
+// fields it leaks timing and must not be used with secret values.
+
+/// Number of 64-bit limbs of an element
+pub const MONTGOMERY_LIMBS: usize = 6;
+
+// Modulus of BLS12-377's base field, the field of the coordinates of Aleo's curve points
+const BLS12_377_BASE_MODULUS: &str = "01ae3a4617c510eac63b05c06ca1493b1a22d9f300f5138f1ef3622fba094800170b5d44300000008508c00000000001";
+
+// Limbs of an integer, least significant first
+type Limbs = [u64; MONTGOMERY_LIMBS];
+
+/// Element of a [`MontgomeryField`], stored in Montgomery form
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MontgomeryElement {
+    // a*R mod p
+    limbs: Limbs,
+}
+
+/// Prime field of at most 384 bits with the constants of Montgomery arithmetic
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MontgomeryField {
+    // Modulus p
+    modulus: Limbs,
+    // -p^-1 mod 2^64
+    inv: u64,
+    // R^2 mod p
+    r2: Limbs,
+    // R mod p, the Montgomery form of 1
+    one: Limbs,
+}
+
+// a + b + carry, returning the low limb and the carry
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let sum = a as u128 + b as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+// a - b - borrow, returning the low limb and the borrow as 0 or 1
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let difference = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (difference as u64, (difference >> 127) as u64)
+}
+
+// a + b*c + carry, returning the low limb and the carry
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let sum = a as u128 + b as u128 * c as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+// Subtract two integers of the same number of limbs, returning the difference and the borrow
+fn sub_limbs<const N: usize>(a: &[u64; N], b: &[u64; N]) -> ([u64; N], u64) {
+    let mut difference = [0; N];
+    let mut borrow = 0;
+    for i in 0..N {
+        (difference[i], borrow) = sbb(a[i], b[i], borrow);
+    }
+    (difference, borrow)
+}
+
+impl MontgomeryField {
+    /// Set up arithmetic modulo an odd prime of at most 384 bits. Primality isn't checked, and
+    /// inversion is only correct for primes.
+    pub fn new(modulus: &BigUint) -> Result<Self, Error> {
+        if modulus.bits() > 64 * MONTGOMERY_LIMBS as u64
+            || *modulus < BigUint::from(3u32)
+            || !modulus.bit(0)
+        {
+            return Err(Error::InvalidModulus(modulus.to_string()));
+        }
+        let mut field = Self {
+            modulus: to_limbs(modulus),
+            inv: 0,
+            r2: [0; MONTGOMERY_LIMBS],
+            one: [0; MONTGOMERY_LIMBS],
+        };
+
+        // Newton's iteration x <- x * (2 - p*x) doubles the number of correct low bits of p^-1,
+        // and x = 1 is correct in the lowest bit because p is odd
+        let p_0 = field.modulus[0];
+        let inverse = (0..6).fold(1u64, |x, _| {
+            x.wrapping_mul(2u64.wrapping_sub(p_0.wrapping_mul(x)))
+        });
+        field.inv = inverse.wrapping_neg();
+
+        // R^2 = 2^768 by doubling 1 modulo p, then R = 1 * R^2 / R
+        let mut r2 = [0; MONTGOMERY_LIMBS];
+        r2[0] = 1;
+        for _ in 0..2 * 64 * MONTGOMERY_LIMBS {
+            r2 = field.add_limbs(&r2, &r2);
+        }
+        field.r2 = r2;
+        let mut one = [0; MONTGOMERY_LIMBS];
+        one[0] = 1;
+        field.one = field.montgomery_mul(&one, &r2);
+        Ok(field)
+    }
+
+    /// Set up arithmetic in BLS12-377's base field, the field snarkVM stores in a
+    /// `BigInteger384`
+    pub fn bls12_377_base_field() -> Self {
+        let modulus = BigUint::parse_bytes(BLS12_377_BASE_MODULUS.as_bytes(), 16)
+            .expect("modulus is hexadecimal");
+        Self::new(&modulus).expect("modulus is an odd prime of 377 bits")
+    }
+
+    /// Get the modulus
+    pub fn modulus(&self) -> BigUint {
+        from_limbs(&self.modulus)
+    }
+
+    /// Get the zero element
+    pub fn zero(&self) -> MontgomeryElement {
+        MontgomeryElement {
+            limbs: [0; MONTGOMERY_LIMBS],
+        }
+    }
+
+    /// Get the one element
+    pub fn one(&self) -> MontgomeryElement {
+        MontgomeryElement { limbs: self.one }
+    }
+
+    /// Convert an integer smaller than the modulus into Montgomery form
+    pub fn element(&self, value: &BigUint) -> Result<MontgomeryElement, Error> {
+        if *value >= self.modulus() {
+            return Err(Error::ValueExceedsModulus(value.to_string()));
+        }
+        Ok(MontgomeryElement {
+            limbs: self.montgomery_mul(&to_limbs(value), &self.r2),
+        })
+    }
+
+    /// Convert a small integer into Montgomery form, reducing it modulo the modulus
+    pub fn from_u64(&self, value: u64) -> MontgomeryElement {
+        self.element(&(BigUint::from(value) % self.modulus()))
+            .expect("reduced values are smaller than the modulus")
+    }
+
+    /// Convert an element out of Montgomery form into its canonical integer in [0, modulus)
+    pub fn to_biguint(&self, element: &MontgomeryElement) -> BigUint {
+        let mut one = [0; MONTGOMERY_LIMBS];
+        one[0] = 1;
+        from_limbs(&self.montgomery_mul(&element.limbs, &one))
+    }
+
+    /// Add two elements
+    pub fn add(&self, a: &MontgomeryElement, b: &MontgomeryElement) -> MontgomeryElement {
+        MontgomeryElement {
+            limbs: self.add_limbs(&a.limbs, &b.limbs),
+        }
+    }
+
+    /// Subtract an element from another
+    pub fn sub(&self, a: &MontgomeryElement, b: &MontgomeryElement) -> MontgomeryElement {
+        let (difference, borrow) = sub_limbs(&a.limbs, &b.limbs);
+        if borrow == 0 {
+            return MontgomeryElement { limbs: difference };
+        }
+        // The difference wrapped around 2^384, and adding p wraps it back into [0, p)
+        let mut limbs = [0; MONTGOMERY_LIMBS];
+        let mut carry = 0;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            (*limb, carry) = adc(difference[i], self.modulus[i], carry);
+        }
+        MontgomeryElement { limbs }
+    }
+
+    /// Negate an element
+    pub fn neg(&self, a: &MontgomeryElement) -> MontgomeryElement {
+        self.sub(&self.zero(), a)
+    }
+
+    /// Multiply two elements
+    pub fn mul(&self, a: &MontgomeryElement, b: &MontgomeryElement) -> MontgomeryElement {
+        MontgomeryElement {
+            limbs: self.montgomery_mul(&a.limbs, &b.limbs),
+        }
+    }
+
+    /// Square an element
+    pub fn square(&self, a: &MontgomeryElement) -> MontgomeryElement {
+        self.mul(a, a)
+    }
+
+    /// Raise an element to a power by squaring and multiplying, from the most significant bit of
+    /// the exponent down
+    pub fn pow(&self, base: &MontgomeryElement, exponent: &BigUint) -> MontgomeryElement {
+        (0..exponent.bits()).rev().fold(self.one(), |power, bit| {
+            let power = self.square(&power);
+            match exponent.bit(bit) {
+                true => self.mul(&power, base),
+                false => power,
+            }
+        })
+    }
+
+    /// Invert a nonzero element as `a^(p-2)`
+    pub fn invert(&self, a: &MontgomeryElement) -> Result<MontgomeryElement, Error> {
+        if *a == self.zero() {
+            return Err(Error::NotInvertible);
+        }
+        Ok(self.pow(a, &(self.modulus() - 2u32)))
+    }
+
+    // Add two integers below p modulo p
+    fn add_limbs(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        // The sum can need a seventh limb when p has 384 bits
+        let mut sum = [0; MONTGOMERY_LIMBS + 1];
+        let mut carry = 0;
+        for i in 0..MONTGOMERY_LIMBS {
+            (sum[i], carry) = adc(a[i], b[i], carry);
+        }
+        sum[MONTGOMERY_LIMBS] = carry;
+        self.reduce_once(&sum)
+    }
+
+    // Montgomery multiplication a * b / R mod p of two integers below p
+    fn montgomery_mul(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        // Running sum of partial products with two limbs of headroom
+        let mut t = [0u64; MONTGOMERY_LIMBS + 2];
+        for b_i in b {
+            // t += a * b_i
+            let mut carry = 0;
+            for j in 0..MONTGOMERY_LIMBS {
+                (t[j], carry) = mac(t[j], a[j], *b_i, carry);
+            }
+            (t[MONTGOMERY_LIMBS], carry) = adc(t[MONTGOMERY_LIMBS], carry, 0);
+            t[MONTGOMERY_LIMBS + 1] = carry;
+
+            // t = (t + m*p) / 2^64, where m clears the lowest limb
+            let m = t[0].wrapping_mul(self.inv);
+            let (_, mut carry) = mac(t[0], m, self.modulus[0], 0);
+            for j in 1..MONTGOMERY_LIMBS {
+                (t[j - 1], carry) = mac(t[j], m, self.modulus[j], carry);
+            }
+            (t[MONTGOMERY_LIMBS - 1], carry) = adc(t[MONTGOMERY_LIMBS], carry, 0);
+            t[MONTGOMERY_LIMBS] = t[MONTGOMERY_LIMBS + 1] + carry;
+        }
+        let mut result = [0; MONTGOMERY_LIMBS + 1];
+        result.copy_from_slice(&t[..=MONTGOMERY_LIMBS]);
+        self.reduce_once(&result)
+    }
+
+    // Reduce an integer below 2p into [0, p) by subtracting p if it isn't smaller
+    fn reduce_once(&self, value: &[u64; MONTGOMERY_LIMBS + 1]) -> Limbs {
+        let mut modulus = [0; MONTGOMERY_LIMBS + 1];
+        modulus[..MONTGOMERY_LIMBS].copy_from_slice(&self.modulus);
+        let (difference, borrow) = sub_limbs(value, &modulus);
+        let reduced = match borrow {
+            0 => difference,
+            _ => *value,
+        };
+        let mut limbs = [0; MONTGOMERY_LIMBS];
+        limbs.copy_from_slice(&reduced[..MONTGOMERY_LIMBS]);
+        limbs
+    }
+}
+
+// Split an integer of at most 384 bits into limbs
+fn to_limbs(value: &BigUint) -> Limbs {
+    let mut limbs = [0; MONTGOMERY_LIMBS];
+    for (limb, digit) in limbs.iter_mut().zip(value.iter_u64_digits()) {
+        *limb = digit;
+    }
+    limbs
+}
+
+// Join limbs into an integer
+fn from_limbs(limbs: &Limbs) -> BigUint {
+    limbs
+        .iter()
+        .rev()
+        .fold(BigUint::default(), |value, limb| (value << 64u32) + *limb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{curves::bls12_377::Fq, utilities::ToBytes};
+
+    // Deterministic values spread over the whole field
+    fn samples(field: &MontgomeryField) -> Vec<BigUint> {
+        let modulus = field.modulus();
+        (1u32..8)
+            .map(|i| (&modulus - 1u32) / BigUint::from(i * i + 1) + BigUint::from(i).pow(40))
+            .chain([BigUint::from(0u32), BigUint::from(1u32), &modulus - 1u32])
+            .collect()
+    }
+
+    // Check an element times its inverse is one unless it is zero, and that it cancels its negation
+    fn check_inversion(field: &MontgomeryField, element: &MontgomeryElement, value: &BigUint) {
+        match field.invert(element) {
+            Ok(inverse) => assert_eq!(field.mul(element, &inverse), field.one()),
+            Err(error) => assert_eq!((error, value), (Error::NotInvertible, &BigUint::default())),
+        }
+        assert_eq!(field.add(element, &field.neg(element)), field.zero());
+    }
+
+    #[test]
+    fn test_montgomery_arithmetic_matches_integer_arithmetic() {
+        let bls12_381_base_modulus = BigUint::parse_bytes(b"1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab", 16).unwrap();
+        for field in [
+            MontgomeryField::bls12_377_base_field(),
+            MontgomeryField::new(&bls12_381_base_modulus).unwrap(),
+        ] {
+            let p = field.modulus();
+            for a in samples(&field) {
+                let a_element = field.element(&a).unwrap();
+                assert_eq!(field.to_biguint(&a_element), a);
+                for b in samples(&field) {
+                    let b_element = field.element(&b).unwrap();
+                    let check = |element: MontgomeryElement, expected: BigUint| {
+                        assert_eq!(field.to_biguint(&element), expected % &p)
+                    };
+                    check(field.add(&a_element, &b_element), &a + &b);
+                    check(field.sub(&a_element, &b_element), &a + &p - &b);
+                    check(field.mul(&a_element, &b_element), &a * &b);
+                }
+                check_inversion(&field, &a_element, &a);
+                let exponent = BigUint::from(0xdead_beefu32).pow(5);
+                assert_eq!(
+                    field.to_biguint(&field.pow(&a_element, &exponent)),
+                    a.modpow(&exponent, &p)
+                );
+            }
+        }
+        // Moduli must be odd and fit into the limbs
+        let too_wide = BigUint::from(1u32) << 384u32;
+        assert_eq!(
+            MontgomeryField::new(&too_wide),
+            Err(Error::InvalidModulus(too_wide.to_string()))
+        );
+        assert!(MontgomeryField::new(&BigUint::from(10u32)).is_err());
+        assert!(MontgomeryField::new(&BigUint::from(1u32)).is_err());
+    }
+
+    #[test]
+    fn test_montgomery_arithmetic_matches_snarkvm() {
+        let field = MontgomeryField::bls12_377_base_field();
+        let to_biguint = |value: Fq| BigUint::from_bytes_le(&value.to_bytes_le().unwrap());
+        assert_eq!(field.modulus(), to_biguint(-Fq::from(1u64)) + 1u32);
+
+        // Grow elements past 64 bits by repeated multiplication in both implementations
+        let (mut expected, mut element) = (Fq::from(7u64), field.from_u64(7));
+        for i in 1..50u64 {
+            let factor = Fq::from(u64::MAX - i);
+            let factor_element = field.from_u64(u64::MAX - i);
+            expected = expected * factor + factor;
+            element = field.add(&field.mul(&element, &factor_element), &factor_element);
+            assert_eq!(field.to_biguint(&element), to_biguint(expected));
+        }
+        assert_eq!(
+            field.element(&field.modulus()),
+            Err(Error::ValueExceedsModulus(field.modulus().to_string()))
+        );
+    }
+}
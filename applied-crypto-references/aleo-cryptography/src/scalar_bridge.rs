@@ -0,0 +1,221 @@
+//! Conversions between the scalar fields of Ristretto, BLS12-381 and snarkVM (BLS12-377)
+
+use crate::Error;
+use bls12_381::Scalar as Bls12_381Scalar;
+use curve25519_dalek::scalar::Scalar as RistrettoScalar;
+use encodings::{Endianness, ScalarEncoding};
+use num_bigint::BigUint;
+use snarkvm::{
+    curves::bls12_377::Fr as AleoField,
+    utilities::{FromBytes, ToBytes},
+};
+
+// Each proving system in this repository works over a different prime field:
+//
+// | Field                    | Used by                      | Modulus size |
+// | ------------------------ | ---------------------------- | ------------ |
+// | Ristretto scalar field   | Merlin/Schnorr examples      | ~2^252       |
+// | BLS12-377 scalar field   | Aleo programs (snarkVM)      | ~2^252.9     |
+// | BLS12-381 scalar field   | zksnarks examples            | ~2^254.9     |
+//
+// All three libraries represent elements as 32 bytes, which makes it tempting to copy bytes from
+// one type into another. Doing so is only correct when the integer represented is smaller than the
+// modulus of the target field. Otherwise the bytes are either rejected or, worse, silently reduced
+// to a different number. Going through an explicit integer representation and choosing what
+// should happen when a value doesn't fit makes these conversions auditable.
+
+/// Element of a prime field that can be converted to and from its canonical integer
+/// representation in [0, modulus)
+pub trait FieldElement: Sized {
+    /// Human readable name of the field
+    const NAME: &'static str;
+
+    /// Modulus of the field
+    fn field_modulus() -> BigUint;
+
+    /// Canonical integer representation of the element
+    fn to_biguint(&self) -> BigUint;
+
+    /// Create an element from an integer, rejecting integers which aren't smaller than the modulus
+    fn from_biguint(value: &BigUint) -> Result<Self, Error>;
+
+    /// Number of bits needed to represent the modulus
+    fn modulus_bits() -> u64 {
+        Self::field_modulus().bits()
+    }
+}
+
+// Write an integer into 32 little endian bytes, rejecting it if it doesn't fit
+fn to_le_bytes<F: FieldElement>(value: &BigUint) -> Result<[u8; 32], Error> {
+    if value >= &F::field_modulus() {
+        return Err(Error::ValueExceedsModulus(value.to_string()));
+    }
+    let mut bytes = [0; 32];
+    let le_bytes = value.to_bytes_le();
+    bytes[..le_bytes.len()].copy_from_slice(&le_bytes);
+    Ok(bytes)
+}
+
+impl FieldElement for RistrettoScalar {
+    const NAME: &'static str = "Ristretto scalar field";
+
+    fn field_modulus() -> BigUint {
+        (-RistrettoScalar::ONE).to_biguint() + 1u32
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_le(&self.encode(Endianness::Little))
+    }
+
+    fn from_biguint(value: &BigUint) -> Result<Self, Error> {
+        let bytes = to_le_bytes::<Self>(value)?;
+        RistrettoScalar::decode(&bytes, Endianness::Little)
+            .map_err(|_| Error::ValueExceedsModulus(value.to_string()))
+    }
+}
+
+impl FieldElement for Bls12_381Scalar {
+    const NAME: &'static str = "BLS12-381 scalar field";
+
+    fn field_modulus() -> BigUint {
+        (-Bls12_381Scalar::one()).to_biguint() + 1u32
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_le(&self.encode(Endianness::Little))
+    }
+
+    fn from_biguint(value: &BigUint) -> Result<Self, Error> {
+        let bytes = to_le_bytes::<Self>(value)?;
+        Bls12_381Scalar::decode(&bytes, Endianness::Little)
+            .map_err(|_| Error::ValueExceedsModulus(value.to_string()))
+    }
+}
+
+impl FieldElement for AleoField {
+    const NAME: &'static str = "BLS12-377 scalar field (Aleo)";
+
+    fn field_modulus() -> BigUint {
+        (-AleoField::from(1u64)).to_biguint() + 1u32
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        // snarkVM writes field elements as their canonical integer in little endian bytes
+        BigUint::from_bytes_le(&self.to_bytes_le().unwrap())
+    }
+
+    fn from_biguint(value: &BigUint) -> Result<Self, Error> {
+        let bytes = to_le_bytes::<Self>(value)?;
+        AleoField::from_bytes_le(&bytes).map_err(|_| Error::ValueExceedsModulus(value.to_string()))
+    }
+}
+
+/// How a conversion handles integers which don't fit in the target field
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WrapMode {
+    /// Refuse to convert integers that are not smaller than the target modulus
+    Reject,
+    /// Reduce integers modulo the target modulus. The result is a different integer whenever the
+    /// source value is not smaller than the target modulus.
+    Reduce,
+}
+
+// Apply the wrap mode to an integer before converting it into the target field
+fn wrap<T: FieldElement>(value: BigUint, wrap_mode: WrapMode) -> Result<T, Error> {
+    match wrap_mode {
+        WrapMode::Reject => T::from_biguint(&value),
+        WrapMode::Reduce => T::from_biguint(&(value % T::field_modulus())),
+    }
+}
+
+/// Convert an element of one field into another by way of its canonical integer representation
+pub fn convert<F: FieldElement, T: FieldElement>(
+    value: &F,
+    wrap_mode: WrapMode,
+) -> Result<T, Error> {
+    wrap(value.to_biguint(), wrap_mode)
+}
+
+/// Decompose a field element into digits of the given radix, least significant digit first
+pub fn to_digits<F: FieldElement>(value: &F, radix: u32) -> Result<Vec<u8>, Error> {
+    if !(2..=256).contains(&radix) {
+        return Err(Error::InvalidRadix(radix));
+    }
+    Ok(value.to_biguint().to_radix_le(radix))
+}
+
+/// Recompose a field element from digits of the given radix, least significant digit first
+pub fn from_digits<F: FieldElement>(
+    digits: &[u8],
+    radix: u32,
+    wrap_mode: WrapMode,
+) -> Result<F, Error> {
+    if !(2..=256).contains(&radix) {
+        return Err(Error::InvalidRadix(radix));
+    }
+    let value = BigUint::from_radix_le(digits, radix).ok_or(Error::InvalidDigits(radix))?;
+    wrap(value, wrap_mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_moduli_are_ordered_as_documented() {
+        assert!(RistrettoScalar::field_modulus() < AleoField::field_modulus());
+        assert!(AleoField::field_modulus() < Bls12_381Scalar::field_modulus());
+        assert_eq!(RistrettoScalar::modulus_bits(), 253);
+        assert_eq!(AleoField::modulus_bits(), 253);
+        assert_eq!(Bls12_381Scalar::modulus_bits(), 255);
+    }
+
+    #[test]
+    fn test_conversions_preserve_small_values_and_wrap_explicitly() {
+        // Values that fit in every field convert losslessly
+        let value = RistrettoScalar::from(123456789u64);
+        let bls: Bls12_381Scalar = convert(&value, WrapMode::Reject).unwrap();
+        let aleo: AleoField = convert(&bls, WrapMode::Reject).unwrap();
+        assert_eq!(bls, Bls12_381Scalar::from(123456789u64));
+        assert_eq!(aleo, AleoField::from(123456789u64));
+        assert_eq!(
+            convert::<AleoField, RistrettoScalar>(&aleo, WrapMode::Reject).unwrap(),
+            value
+        );
+
+        // The largest BLS12-381 scalar doesn't fit in the smaller fields
+        let largest = -Bls12_381Scalar::one();
+        assert!(matches!(
+            convert::<Bls12_381Scalar, RistrettoScalar>(&largest, WrapMode::Reject),
+            Err(Error::ValueExceedsModulus(_))
+        ));
+        let wrapped: RistrettoScalar = convert(&largest, WrapMode::Reduce).unwrap();
+        assert_eq!(
+            wrapped.to_biguint(),
+            largest.to_biguint() % RistrettoScalar::field_modulus()
+        );
+    }
+
+    #[test]
+    fn test_digit_decomposition_round_trips() {
+        let value = -AleoField::from(1u64);
+        let bits = to_digits(&value, 2).unwrap();
+        assert_eq!(bits.len() as u64, AleoField::modulus_bits());
+        assert_eq!(
+            from_digits::<AleoField>(&bits, 2, WrapMode::Reject).unwrap(),
+            value
+        );
+        let bytes = to_digits(&value, 256).unwrap();
+        assert_eq!(
+            from_digits::<AleoField>(&bytes, 256, WrapMode::Reject).unwrap(),
+            value
+        );
+
+        // Digits larger than the radix and unsupported radixes are rejected
+        assert_eq!(
+            from_digits::<AleoField>(&[2], 2, WrapMode::Reject),
+            Err(Error::InvalidDigits(2))
+        );
+        assert_eq!(to_digits(&value, 1), Err(Error::InvalidRadix(1)));
+    }
+}
@@ -1,80 +1,231 @@
-#![feature(test)]
-
-extern crate test;
+//! Benchmarks of atomic curve operations over scalar sizes, multiscalar multiplications of
+//! increasing length, pairings and target group exponentiations
+
+use criterion::{
+    criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, BenchmarkId, Criterion,
+};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT as G, ristretto::RistrettoPoint,
+    scalar::Scalar as Ristretto_Scalar, traits::VartimeMultiscalarMul,
+};
 use curve_operations::CurveTests;
-use lazy_static::lazy_static;
-use test::Bencher;
-
-lazy_static! {
-    static ref CURVE_TESTS: CurveTests = CurveTests::new(4000);
-}
-
-#[bench]
-fn bench_ristretto_scalar_inversion(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.ristretto_scalar_inversion());
-}
-
-#[bench]
-fn bench_bls_scalar_inversion(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.bls_scalar_inversion());
-}
-
-#[bench]
-fn bench_small_ristretto_scalar_addition(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.small_ristretto_scalar_addition());
-}
 
-#[bench]
-fn bench_large_ristretto_scalar_addition(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.large_ristretto_scalar_addition());
+// Bit lengths of the small scalars operations are benchmarked with. Large scalars are inverses of
+// small ones and take up the whole field whatever the size of the small scalar.
+const SCALAR_BITS: [u32; 4] = [8, 16, 32, 64];
+
+// Numbers of terms of the benchmarked multiscalar multiplications
+const MSM_LENGTHS: [usize; 4] = [16, 64, 256, 1024];
+
+// Numbers of pairings multiplied together, as verifiers of pairing based proofs do
+const PAIRING_COUNTS: [usize; 3] = [1, 2, 4];
+
+// Benchmark an operation on small scalars of every benchmarked bit length
+fn bench_scalar_sizes<T>(
+    group: &mut BenchmarkGroup<WallTime>,
+    curve: &str,
+    operation: fn(&CurveTests) -> T,
+) {
+    for bits in SCALAR_BITS {
+        let curve_tests = CurveTests::new(u64::MAX >> (64 - bits));
+        group.bench_with_input(
+            BenchmarkId::new(curve, bits),
+            &curve_tests,
+            |b, curve_tests| b.iter(|| operation(curve_tests)),
+        );
+    }
 }
 
-#[bench]
-fn bench_small_bls_scalar_addition(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.small_bls_scalar_addition());
+// Benchmark an operation on large scalars
+fn bench_full_scalars<T>(
+    group: &mut BenchmarkGroup<WallTime>,
+    curve: &str,
+    operation: fn(&CurveTests) -> T,
+) {
+    let curve_tests = CurveTests::new(4000);
+    group.bench_function(BenchmarkId::new(curve, "full"), |b| {
+        b.iter(|| operation(&curve_tests))
+    });
 }
 
-#[bench]
-fn bench_large_bls_scalar_addition(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.large_bls_scalar_addition());
+fn bench_scalar_inversion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scalar_inversion");
+    bench_scalar_sizes(
+        &mut group,
+        "ristretto",
+        CurveTests::ristretto_scalar_inversion,
+    );
+    bench_scalar_sizes(&mut group, "bls12_381", CurveTests::bls_scalar_inversion);
+    group.finish();
 }
 
-#[bench]
-fn bench_small_ristretto_scalar_multiplication_with_generator(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.small_ristretto_scalar_multiplication_with_generator());
+fn bench_scalar_addition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scalar_addition");
+    bench_scalar_sizes(
+        &mut group,
+        "ristretto",
+        CurveTests::small_ristretto_scalar_addition,
+    );
+    bench_full_scalars(
+        &mut group,
+        "ristretto",
+        CurveTests::large_ristretto_scalar_addition,
+    );
+    bench_scalar_sizes(
+        &mut group,
+        "bls12_381",
+        CurveTests::small_bls_scalar_addition,
+    );
+    bench_full_scalars(
+        &mut group,
+        "bls12_381",
+        CurveTests::large_bls_scalar_addition,
+    );
+    group.finish();
 }
 
-#[bench]
-fn bench_large_ristretto_scalar_multiplication_with_generator(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.large_ristretto_scalar_multiplication_with_generator());
+fn bench_scalar_multiplication(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scalar_multiplication_with_generator");
+    bench_scalar_sizes(
+        &mut group,
+        "ristretto",
+        CurveTests::small_ristretto_scalar_multiplication_with_generator,
+    );
+    bench_full_scalars(
+        &mut group,
+        "ristretto",
+        CurveTests::large_ristretto_scalar_multiplication_with_generator,
+    );
+    bench_scalar_sizes(
+        &mut group,
+        "bls12_381",
+        CurveTests::small_bls_scalar_multiplication_with_prime_generator,
+    );
+    bench_full_scalars(
+        &mut group,
+        "bls12_381",
+        CurveTests::large_bls_scalar_multiplication_with_prime_generator,
+    );
+    bench_scalar_sizes(
+        &mut group,
+        "bls12_381_g2",
+        CurveTests::small_bls_scalar_multiplication_with_g2_generator,
+    );
+    bench_full_scalars(
+        &mut group,
+        "bls12_381_g2",
+        CurveTests::large_bls_scalar_multiplication_with_g2_generator,
+    );
+    group.finish();
 }
 
-#[bench]
-fn bench_small_bls_scalar_multiplication_with_prime_generator(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.small_bls_scalar_multiplication_with_prime_generator());
+fn bench_point_addition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("point_addition");
+    bench_scalar_sizes(
+        &mut group,
+        "ristretto",
+        CurveTests::small_ristretto_point_addition,
+    );
+    bench_full_scalars(
+        &mut group,
+        "ristretto",
+        CurveTests::large_ristretto_point_addition,
+    );
+    bench_scalar_sizes(
+        &mut group,
+        "bls12_381",
+        CurveTests::small_bls_point_addition,
+    );
+    bench_full_scalars(
+        &mut group,
+        "bls12_381",
+        CurveTests::large_bls_point_addition,
+    );
+    bench_scalar_sizes(
+        &mut group,
+        "bls12_381_g2",
+        CurveTests::small_bls_g2_point_addition,
+    );
+    bench_full_scalars(
+        &mut group,
+        "bls12_381_g2",
+        CurveTests::large_bls_g2_point_addition,
+    );
+    group.finish();
 }
 
-#[bench]
-fn bench_large_bls_scalar_multiplication_with_prime_generator(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.large_bls_scalar_multiplication_with_prime_generator());
+fn bench_multiscalar_mul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ristretto_multiscalar_mul");
+    group.sample_size(10);
+    for length in MSM_LENGTHS {
+        // Full size scalars and points from the inverses of 1..=length
+        let scalars: Vec<Ristretto_Scalar> = (1..=length as u64)
+            .map(|i| Ristretto_Scalar::from(i).invert())
+            .collect();
+        let points: Vec<RistrettoPoint> = scalars.iter().rev().map(|scalar| G * scalar).collect();
+
+        group.bench_with_input(BenchmarkId::new("naive", length), &length, |b, _| {
+            b.iter(|| {
+                scalars
+                    .iter()
+                    .zip(&points)
+                    .map(|(scalar, point)| scalar * point)
+                    .sum::<RistrettoPoint>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("vartime", length), &length, |b, _| {
+            b.iter(|| RistrettoPoint::vartime_multiscalar_mul(&scalars, &points))
+        });
+    }
+    group.finish();
 }
 
-#[bench]
-fn bench_small_ristretto_point_addition(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.small_ristretto_point_addition());
+fn bench_pairing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bls12_381_pairing");
+    group.sample_size(10);
+    let curve_tests = CurveTests::new(4000);
+    group.bench_function("pairing", |b| b.iter(|| curve_tests.bls_pairing()));
+    for count in PAIRING_COUNTS {
+        group.bench_with_input(BenchmarkId::new("separate", count), &count, |b, count| {
+            b.iter(|| curve_tests.bls_separate_pairings(*count))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("multi_pairing", count),
+            &count,
+            |b, count| b.iter(|| curve_tests.bls_multi_pairing(*count)),
+        );
+    }
+    group.finish();
 }
 
-#[bench]
-fn bench_large_ristretto_point_addition(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.large_ristretto_point_addition());
+fn bench_gt_exponentiation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bls12_381_gt_exponentiation");
+    group.sample_size(10);
+    let curve_tests = CurveTests::new(4000);
+    group.bench_function("multiplication", |b| {
+        b.iter(|| curve_tests.bls_gt_multiplication())
+    });
+    bench_scalar_sizes(
+        &mut group,
+        "bls12_381",
+        CurveTests::small_bls_gt_exponentiation,
+    );
+    bench_full_scalars(
+        &mut group,
+        "bls12_381",
+        CurveTests::large_bls_gt_exponentiation,
+    );
+    group.finish();
 }
 
-#[bench]
-fn bench_small_bls_point_addition(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.small_bls_point_addition());
-}
-
-#[bench]
-fn bench_large_bls_point_addition(b: &mut Bencher) {
-    b.iter(|| CURVE_TESTS.large_bls_point_addition());
-}
+criterion_group!(
+    benches,
+    bench_scalar_inversion,
+    bench_scalar_addition,
+    bench_scalar_multiplication,
+    bench_point_addition,
+    bench_multiscalar_mul,
+    bench_pairing,
+    bench_gt_exponentiation
+);
+criterion_main!(benches);
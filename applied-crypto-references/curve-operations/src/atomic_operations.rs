@@ -1,6 +1,9 @@
 //! Collection of atomic curve operations for use in benchmarking
 
-use bls12_381::{G1Projective, Scalar as BLS_Scalar};
+use bls12_381::{
+    multi_miller_loop, pairing, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt,
+    Scalar as BLS_Scalar,
+};
 use curve25519_dalek::{
     constants::RISTRETTO_BASEPOINT_POINT as G, ristretto::RistrettoPoint,
     scalar::Scalar as Ristretto_Scalar,
@@ -9,6 +12,8 @@ use lazy_static::lazy_static;
 
 lazy_static! {
     static ref G_BLS: G1Projective = G1Projective::generator();
+    static ref G2_BLS: G2Projective = G2Projective::generator();
+    static ref GT_BLS: Gt = pairing(&G1Affine::generator(), &G2Affine::generator());
 }
 
 /// Curve test objects containing pre-computed scalars and curve points
@@ -22,6 +27,12 @@ pub struct CurveTests {
     bls_point: G1Projective,
     inverse_ristretto_point: RistrettoPoint,
     inverse_bls_point: G1Projective,
+    bls_g2_point: G2Projective,
+    inverse_bls_g2_point: G2Projective,
+    bls_gt_element: Gt,
+    // Affine form of the G1 point and prepared G2 Generator, as pairing verifiers precompute them
+    bls_affine_point: G1Affine,
+    prepared_g2_generator: G2Prepared,
 }
 
 impl CurveTests {
@@ -35,6 +46,9 @@ impl CurveTests {
         let bls_point = *G_BLS * base_bls;
         let inverse_ristretto_point = G * inverse_ristretto;
         let inverse_bls_point = *G_BLS * inverse_bls;
+        let bls_g2_point = *G2_BLS * base_bls;
+        let inverse_bls_g2_point = *G2_BLS * inverse_bls;
+        let bls_gt_element = *GT_BLS * base_bls;
         CurveTests {
             ristretto_scalar: base_ristretto,
             inverse_ristretto_scalar: inverse_ristretto,
@@ -44,6 +58,11 @@ impl CurveTests {
             bls_point,
             inverse_ristretto_point,
             inverse_bls_point,
+            bls_g2_point,
+            inverse_bls_g2_point,
+            bls_gt_element,
+            bls_affine_point: G1Affine::from(bls_point),
+            prepared_g2_generator: G2Prepared::from(G2Affine::generator()),
         }
     }
 
@@ -116,6 +135,63 @@ impl CurveTests {
     pub fn large_bls_point_addition(&self) -> G1Projective {
         self.inverse_bls_point + self.inverse_bls_point
     }
+
+    /// Pair the BLS point found by multiplying the small BLS scalar by the prime field Generator
+    /// with the Generator of the BLS twisted curve
+    pub fn bls_pairing(&self) -> Gt {
+        pairing(&G1Affine::from(self.bls_point), &G2Affine::generator())
+    }
+
+    /// Multiply small BLS scalar by the Generator of the BLS twisted curve
+    pub fn small_bls_scalar_multiplication_with_g2_generator(&self) -> G2Projective {
+        G2Projective::generator() * self.bls_scalar
+    }
+
+    /// Multiply large BLS scalar by the Generator of the BLS twisted curve
+    pub fn large_bls_scalar_multiplication_with_g2_generator(&self) -> G2Projective {
+        G2Projective::generator() * self.inverse_bls_scalar
+    }
+
+    /// Add two BLS twisted curve points found by multiplying small BLS scalars by its Generator
+    pub fn small_bls_g2_point_addition(&self) -> G2Projective {
+        self.bls_g2_point + self.bls_g2_point
+    }
+
+    /// Add two BLS twisted curve points found by multiplying large BLS scalars by its Generator
+    pub fn large_bls_g2_point_addition(&self) -> G2Projective {
+        self.inverse_bls_g2_point + self.inverse_bls_g2_point
+    }
+
+    /// Multiply `count` pairings of the small BLS point with the twisted curve Generator by
+    /// computing each pairing on its own
+    pub fn bls_separate_pairings(&self, count: usize) -> Gt {
+        (0..count)
+            .map(|_| pairing(&self.bls_affine_point, &G2Affine::generator()))
+            .sum()
+    }
+
+    /// Multiply `count` pairings of the small BLS point with the twisted curve Generator by
+    /// batching their Miller loops and sharing a single final exponentiation
+    pub fn bls_multi_pairing(&self, count: usize) -> Gt {
+        let terms = vec![(&self.bls_affine_point, &self.prepared_g2_generator); count];
+        multi_miller_loop(&terms).final_exponentiation()
+    }
+
+    /// Multiply two target group elements found by exponentiating the pairing of the Generators
+    /// by the small BLS scalar, written as an addition like the other groups
+    pub fn bls_gt_multiplication(&self) -> Gt {
+        self.bls_gt_element + self.bls_gt_element
+    }
+
+    /// Exponentiate the pairing of the Generators by a small BLS scalar
+    pub fn small_bls_gt_exponentiation(&self) -> Gt {
+        *GT_BLS * self.bls_scalar
+    }
+
+    /// Exponentiate the pairing of the Generators by a large BLS scalar
+    pub fn large_bls_gt_exponentiation(&self) -> Gt {
+        *GT_BLS * self.inverse_bls_scalar
+    }
 }
 
 #[cfg(test)]
@@ -184,5 +260,54 @@ mod tests {
             *G_BLS * BLS_Scalar::from(base).invert().unwrap()
                 + *G_BLS * BLS_Scalar::from(base).invert().unwrap()
         );
+        assert_eq!(
+            curve_tests.bls_pairing(),
+            pairing(&G1Affine::generator(), &G2Affine::generator()) * BLS_Scalar::from(base)
+        );
+    }
+
+    #[test]
+    fn test_twisted_curve_and_target_group_operations_give_expected_outputs() {
+        let base = 4000u64;
+        let curve_tests = CurveTests::new(base);
+        let (scalar, inverse) = (
+            BLS_Scalar::from(base),
+            BLS_Scalar::from(base).invert().unwrap(),
+        );
+        assert_eq!(
+            curve_tests.small_bls_scalar_multiplication_with_g2_generator(),
+            *G2_BLS * scalar
+        );
+        assert_eq!(
+            curve_tests.large_bls_scalar_multiplication_with_g2_generator(),
+            *G2_BLS * inverse
+        );
+        assert_eq!(
+            curve_tests.small_bls_g2_point_addition(),
+            *G2_BLS * (scalar + scalar)
+        );
+        assert_eq!(
+            curve_tests.large_bls_g2_point_addition(),
+            *G2_BLS * (inverse + inverse)
+        );
+        assert_eq!(
+            curve_tests.bls_gt_multiplication(),
+            *GT_BLS * (scalar + scalar)
+        );
+        assert_eq!(curve_tests.small_bls_gt_exponentiation(), *GT_BLS * scalar);
+        assert_eq!(curve_tests.large_bls_gt_exponentiation(), *GT_BLS * inverse);
+
+        // Batched Miller loops give the same product of pairings as separate pairings
+        for count in [1, 3] {
+            assert_eq!(
+                curve_tests.bls_multi_pairing(count),
+                curve_tests.bls_separate_pairings(count)
+            );
+            assert_eq!(
+                curve_tests.bls_multi_pairing(count),
+                *GT_BLS * (scalar * BLS_Scalar::from(count as u64))
+            );
+        }
+        assert_eq!(curve_tests.bls_separate_pairings(0), Gt::identity());
     }
 }
@@ -0,0 +1,56 @@
+//! Commit-and-prove interface shared by the proof backends of all proof crates
+
+// An inference pipeline often proves several statements about the same inputs, e.g. that a
+// feature equals a disclosed value, that it lies in a range, and that a polynomial encoding it
+// evaluates to some output. Committing to the inputs separately for every proof is wasteful and,
+// worse, leaves nothing tying the proofs to the same inputs. With commit-and-prove, a witness is
+// committed to once and every proof is created against that commitment:
+//
+// 1. Commit: the prover commits to a witness and keeps the opening, e.g. the value and blinding of
+// a Pedersen commitment, to itself.
+// 2. Prove: any number of statements are proven about the commitment using the opening.
+// 3. Verify: the verifier checks each statement against the same public commitment.
+//
+// Backends proving over the same commitment scheme share their commitment and opening types, so
+// one commitment can back proofs from all of them. The context is bound into the proof transcript
+// of backends which use one, so a proof created for one purpose can't be replayed for another.
+
+/// Backend proving statements about committed witnesses
+pub trait CommitAndProve {
+    /// Witness a commitment is created to
+    type Witness;
+    /// Public commitment to a witness
+    type Commitment;
+    /// Secret information opening a commitment, kept by the prover
+    type Opening;
+    /// Public statement about a committed witness
+    type Statement;
+    /// Proof of a statement
+    type Proof;
+    /// Error creating or verifying proofs
+    type Error;
+
+    /// Commit to a witness, returning the commitment along with its opening
+    fn commit(
+        &self,
+        witness: &Self::Witness,
+    ) -> Result<(Self::Commitment, Self::Opening), Self::Error>;
+
+    /// Prove a statement about a committed witness
+    fn prove(
+        &self,
+        commitment: &Self::Commitment,
+        opening: &Self::Opening,
+        statement: &Self::Statement,
+        context: &[u8],
+    ) -> Result<Self::Proof, Self::Error>;
+
+    /// Verify a statement about a committed witness
+    fn verify(
+        &self,
+        commitment: &Self::Commitment,
+        statement: &Self::Statement,
+        proof: &Self::Proof,
+        context: &[u8],
+    ) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,238 @@
+//! Pedersen commitments over the Ristretto and BLS12-381 G1 groups
+
+use crate::{Error, PointEncoding, ScalarEncoding};
+use bls12_381::{G1Affine, G1Projective};
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint};
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha384, Sha512};
+use std::{
+    fmt::Debug,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+// A Pedersen commitment to a value v is C = v*G + r*H for a random blinding scalar r and two
+// generators G and H:
+//
+// * Hiding: for a uniformly random r, C is a uniformly random group element whatever v is.
+// * Binding: opening C to two different values would reveal the discrete log of H with respect to
+// G. H must therefore be derived by hashing so that nobody knows this discrete log. Deriving H as
+// a known multiple of G would let its creator open commitments to any value.
+// * Homomorphic: C1 + C2 = (v1 + v2)*G + (r1 + r2)*H commits to the sum of the values, so sums of
+// committed values can be checked without opening the summands.
+//
+// For Ristretto, H is the Elligator map of a hash. BLS12-381 has no hash to curve in the version
+// of the library used here, so H is found by try-and-increment: hashes are interpreted as
+// compressed x coordinates until one lies on the curve, and the cofactor is cleared to move the
+// point into the prime order subgroup.
+
+// Domain separator for deriving the blinding generator
+const BLINDING_GENERATOR_DOMAIN_SEP: &[u8] = b"PEDERSEN_BLINDING_GENERATOR";
+
+/// Prime order group Pedersen commitments can be created in
+pub trait CommitmentGroup: Clone + Copy + Debug + Eq {
+    /// Human readable name of the group
+    const NAME: &'static str;
+
+    /// Element of the group's scalar field
+    type Scalar: Copy
+        + Debug
+        + Eq
+        + From<u64>
+        + ScalarEncoding
+        + Add<Output = Self::Scalar>
+        + Neg<Output = Self::Scalar>;
+
+    /// Element of the group
+    type Point: Copy
+        + Debug
+        + Eq
+        + PointEncoding
+        + Add<Output = Self::Point>
+        + Sub<Output = Self::Point>
+        + Mul<Self::Scalar, Output = Self::Point>;
+
+    /// Generator G the committed value is multiplied by
+    fn value_generator() -> Self::Point;
+
+    /// Generator H the blinding scalar is multiplied by, with an unknown discrete log base G
+    fn blinding_generator() -> Self::Point;
+
+    /// Sample a uniformly random scalar, e.g. a blinding
+    fn random_scalar<R: CryptoRng + RngCore>(rng: &mut R) -> Self::Scalar;
+}
+
+/// Ristretto group over Curve25519
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ristretto;
+
+impl CommitmentGroup for Ristretto {
+    const NAME: &'static str = "ristretto255";
+    type Scalar = curve25519_dalek::scalar::Scalar;
+    type Point = RistrettoPoint;
+
+    fn value_generator() -> Self::Point {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn blinding_generator() -> Self::Point {
+        let mut hash = [0; 64];
+        hash.copy_from_slice(&Sha512::digest(BLINDING_GENERATOR_DOMAIN_SEP));
+        RistrettoPoint::from_uniform_bytes(&hash)
+    }
+
+    fn random_scalar<R: CryptoRng + RngCore>(rng: &mut R) -> Self::Scalar {
+        curve25519_dalek::scalar::Scalar::random(rng)
+    }
+}
+
+/// G1 group of the BLS12-381 curve
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bls12G1;
+
+impl CommitmentGroup for Bls12G1 {
+    const NAME: &'static str = "bls12_381_g1";
+    type Scalar = bls12_381::Scalar;
+    type Point = G1Projective;
+
+    fn value_generator() -> Self::Point {
+        G1Projective::generator()
+    }
+
+    fn blinding_generator() -> Self::Point {
+        for counter in 0u32.. {
+            let mut hasher = Sha384::new();
+            hasher.update(BLINDING_GENERATOR_DOMAIN_SEP);
+            hasher.update(counter.to_le_bytes());
+            let mut x = [0; 48];
+            x.copy_from_slice(&hasher.finalize());
+            // Set the compression flag and clear the infinity and sign flags
+            x[0] = (x[0] & 0x1f) | 0x80;
+            let candidate: Option<G1Affine> = G1Affine::from_compressed_unchecked(&x).into();
+            if let Some(point) = candidate {
+                let point = G1Projective::from(point).clear_cofactor();
+                if !bool::from(point.is_identity()) {
+                    return point;
+                }
+            }
+        }
+        unreachable!("about half of all x coordinates lie on the curve")
+    }
+
+    fn random_scalar<R: CryptoRng + RngCore>(rng: &mut R) -> Self::Scalar {
+        <bls12_381::Scalar as ff::Field>::random(rng)
+    }
+}
+
+/// Pedersen commitment `C = v*G + r*H` in a [`CommitmentGroup`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PedersenCommitment<C: CommitmentGroup> {
+    point: C::Point,
+}
+
+impl<C: CommitmentGroup> PedersenCommitment<C> {
+    /// Commit to a value using a blinding scalar
+    pub fn commit(value: &C::Scalar, blinding: &C::Scalar) -> Self {
+        Self {
+            point: C::value_generator() * *value + C::blinding_generator() * *blinding,
+        }
+    }
+
+    /// Check the commitment opens to a value with a blinding scalar
+    pub fn verify_opening(&self, value: &C::Scalar, blinding: &C::Scalar) -> bool {
+        Self::commit(value, blinding) == *self
+    }
+
+    /// Get the curve point representing the commitment
+    pub fn get_point(&self) -> C::Point {
+        self.point
+    }
+
+    /// Encode the commitment into its canonical compressed form
+    pub fn encode(&self) -> Vec<u8> {
+        self.point.encode()
+    }
+
+    /// Decode a commitment from its canonical compressed form
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        C::Point::decode(bytes).map(|point| Self { point })
+    }
+}
+
+/// The sum of two commitments commits to the sum of the values with the sum of the blindings
+impl<C: CommitmentGroup> Add for PedersenCommitment<C> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            point: self.point + other.point,
+        }
+    }
+}
+
+/// The difference of two commitments commits to the difference of the values with the difference
+/// of the blindings
+impl<C: CommitmentGroup> Sub for PedersenCommitment<C> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            point: self.point - other.point,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Commit to two values, check the openings and that the sum of the commitments opens to the
+    // sum of the values
+    fn check_commitments<C: CommitmentGroup>(values: [C::Scalar; 2], blindings: [C::Scalar; 2]) {
+        let first = PedersenCommitment::<C>::commit(&values[0], &blindings[0]);
+        let second = PedersenCommitment::<C>::commit(&values[1], &blindings[1]);
+        assert!(first.verify_opening(&values[0], &blindings[0]));
+        assert!(!first.verify_opening(&values[1], &blindings[0]));
+        assert!(!first.verify_opening(&values[0], &blindings[1]));
+
+        let sum = first + second;
+        assert!(sum.verify_opening(&(values[0] + values[1]), &(blindings[0] + blindings[1])));
+        assert_eq!(sum - second, first);
+        assert_eq!(
+            PedersenCommitment::<C>::decode(&sum.encode()),
+            Ok(sum),
+            "{}",
+            C::NAME
+        );
+    }
+
+    #[test]
+    fn test_commitments_open_and_add_in_both_groups() {
+        let mut rng = rand::thread_rng();
+        check_commitments::<Ristretto>(
+            [3u64.into(), 4u64.into()],
+            [
+                Ristretto::random_scalar(&mut rng),
+                Ristretto::random_scalar(&mut rng),
+            ],
+        );
+        check_commitments::<Bls12G1>(
+            [3u64.into(), 4u64.into()],
+            [
+                Bls12G1::random_scalar(&mut rng),
+                Bls12G1::random_scalar(&mut rng),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_blinding_generators_are_independent_group_elements() {
+        assert_ne!(
+            Ristretto::blinding_generator(),
+            Ristretto::value_generator()
+        );
+        let h = G1Affine::from(Bls12G1::blinding_generator());
+        assert!(bool::from(h.is_torsion_free()));
+        assert_ne!(Bls12G1::blinding_generator(), Bls12G1::value_generator());
+        assert_eq!(Bls12G1::blinding_generator(), Bls12G1::blinding_generator());
+    }
+}
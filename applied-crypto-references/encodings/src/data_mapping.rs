@@ -0,0 +1,256 @@
+//! Fixed-point quantization of floating-point features and model outputs into scalar field elements
+
+use crate::{Endianness, Error, ScalarEncoding};
+use serde::{Deserialize, Serialize};
+use std::ops::Neg;
+
+// Proofs about inferences work over a prime scalar field, while models consume and produce
+// floating-point numbers. Features and outputs are mapped onto the field with fixed-point
+// quantization:
+//
+// 1. A value x is multiplied by a public scale s and rounded to the nearest integer v, so with a
+// scale of 2^16 the feature 0.25 becomes 16384.
+// 2. Non-negative integers map to the scalar v and negative ones to the field negation q - |v|, so
+// field addition matches integer addition as long as no intermediate result wraps around q.
+// 3. Decoding reverses both steps. A scalar only decodes if it lies within the configured
+// magnitude of zero on either side, which rejects scalars that wrapped around or were never the
+// result of quantization.
+//
+// The magnitude bound is also checked when quantizing, so a feature which doesn't fit is reported
+// instead of silently saturating or wrapping. Quantized vectors keep their integer form for
+// serialization, which unlike field elements is the same for every field.
+
+/// Fixed-point format mapping real numbers to integers of bounded magnitude
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "RawFixedPoint")]
+pub struct FixedPoint {
+    // Factor values are multiplied by before rounding
+    scale: u64,
+    // Largest magnitude of a quantized value
+    max_magnitude: u64,
+}
+
+// Unvalidated fixed-point format as it is deserialized
+#[derive(Deserialize)]
+struct RawFixedPoint {
+    scale: u64,
+    max_magnitude: u64,
+}
+
+impl TryFrom<RawFixedPoint> for FixedPoint {
+    type Error = &'static str;
+
+    fn try_from(raw: RawFixedPoint) -> Result<Self, Self::Error> {
+        Self::new(raw.scale, raw.max_magnitude)
+            .map_err(|_| "fixed-point scale must be non-zero and its magnitude at most i64::MAX")
+    }
+}
+
+impl FixedPoint {
+    /// Create a format with a non-zero scale and a magnitude bound of at most `i64::MAX`
+    pub fn new(scale: u64, max_magnitude: u64) -> Result<Self, Error> {
+        if scale == 0 || max_magnitude > i64::MAX as u64 {
+            return Err(Error::InvalidFixedPoint);
+        }
+        Ok(Self {
+            scale,
+            max_magnitude,
+        })
+    }
+
+    /// Create a format with `fractional_bits` bits after the binary point and `integer_bits` bits
+    /// before it
+    pub fn with_bits(fractional_bits: u32, integer_bits: u32) -> Result<Self, Error> {
+        let bits = fractional_bits + integer_bits;
+        if fractional_bits > 62 || bits > 63 {
+            return Err(Error::InvalidFixedPoint);
+        }
+        Self::new(1 << fractional_bits, (1 << bits) - 1)
+    }
+
+    /// Get the factor values are multiplied by before rounding
+    pub fn get_scale(&self) -> u64 {
+        self.scale
+    }
+
+    /// Get the largest magnitude of a quantized value
+    pub fn get_max_magnitude(&self) -> u64 {
+        self.max_magnitude
+    }
+
+    /// Quantize a value to the nearest fixed-point integer
+    pub fn quantize(&self, value: f64) -> Result<i64, Error> {
+        let quantized = (value * self.scale as f64).round();
+        if !quantized.is_finite() || quantized.abs() > self.max_magnitude as f64 {
+            return Err(Error::QuantizationOverflow);
+        }
+        Ok(quantized as i64)
+    }
+
+    /// Convert a fixed-point integer back into a real number
+    pub fn dequantize(&self, quantized: i64) -> f64 {
+        quantized as f64 / self.scale as f64
+    }
+
+    /// Map a fixed-point integer into the scalar field
+    pub fn to_scalar<S>(&self, quantized: i64) -> Result<S, Error>
+    where
+        S: From<u64> + Neg<Output = S>,
+    {
+        if quantized.unsigned_abs() > self.max_magnitude {
+            return Err(Error::QuantizationOverflow);
+        }
+        let magnitude = S::from(quantized.unsigned_abs());
+        Ok(if quantized < 0 { -magnitude } else { magnitude })
+    }
+
+    /// Map a scalar back to the fixed-point integer it encodes
+    pub fn from_scalar<S>(&self, scalar: &S) -> Result<i64, Error>
+    where
+        S: ScalarEncoding + Copy + Neg<Output = S>,
+    {
+        let small_value = |scalar: &S| {
+            let bytes = scalar.encode(Endianness::Little);
+            let (low, high) = bytes.split_at(8);
+            let value = u64::from_le_bytes(low.try_into().expect("scalars are 32 bytes"));
+            (high.iter().all(|byte| *byte == 0) && value <= self.max_magnitude).then_some(value)
+        };
+        match (small_value(scalar), small_value(&-*scalar)) {
+            (Some(value), _) => Ok(value as i64),
+            (None, Some(value)) => Ok(-(value as i64)),
+            (None, None) => Err(Error::QuantizationOverflow),
+        }
+    }
+
+    /// Quantize a vector of features or outputs
+    pub fn quantize_vector(&self, values: &[f64]) -> Result<QuantizedVector, Error> {
+        Ok(QuantizedVector {
+            format: *self,
+            values: values
+                .iter()
+                .map(|value| self.quantize(*value))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Vector of quantized values along with their fixed-point format
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct QuantizedVector {
+    format: FixedPoint,
+    values: Vec<i64>,
+}
+
+impl QuantizedVector {
+    /// Decode a vector of scalars produced by [`QuantizedVector::to_scalars`]
+    pub fn from_scalars<S>(format: FixedPoint, scalars: &[S]) -> Result<Self, Error>
+    where
+        S: ScalarEncoding + Copy + Neg<Output = S>,
+    {
+        Ok(Self {
+            format,
+            values: scalars
+                .iter()
+                .map(|scalar| format.from_scalar(scalar))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Get the fixed-point format of the values
+    pub fn get_format(&self) -> FixedPoint {
+        self.format
+    }
+
+    /// Get the quantized values
+    pub fn get_values(&self) -> &[i64] {
+        &self.values
+    }
+
+    /// Map the values into the scalar field
+    pub fn to_scalars<S>(&self) -> Result<Vec<S>, Error>
+    where
+        S: From<u64> + Neg<Output = S>,
+    {
+        self.values
+            .iter()
+            .map(|value| self.format.to_scalar(*value))
+            .collect()
+    }
+
+    /// Convert the values back into real numbers
+    pub fn dequantize(&self) -> Vec<f64> {
+        self.values
+            .iter()
+            .map(|value| self.format.dequantize(*value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::Scalar as BlsScalar;
+    use curve25519_dalek::scalar::Scalar as RistrettoScalar;
+
+    #[test]
+    fn test_features_round_trip_through_both_fields_and_serde() {
+        let format = FixedPoint::with_bits(16, 15).unwrap();
+        let features = [0.25, -1.5, 0.0, 2.71, -0.000001];
+        let quantized = format.quantize_vector(&features).unwrap();
+        assert_eq!(&quantized.get_values()[..3], &[16384, -98304, 0]);
+        for (decoded, feature) in quantized.dequantize().iter().zip(features.iter()) {
+            assert!((decoded - feature).abs() <= 0.5 / format.get_scale() as f64);
+        }
+
+        let ristretto: Vec<RistrettoScalar> = quantized.to_scalars().unwrap();
+        assert_eq!(ristretto[1], -RistrettoScalar::from(98304u64));
+        assert_eq!(
+            QuantizedVector::from_scalars(format, &ristretto),
+            Ok(quantized.clone())
+        );
+        let bls: Vec<BlsScalar> = quantized.to_scalars().unwrap();
+        assert_eq!(
+            QuantizedVector::from_scalars(format, &bls),
+            Ok(quantized.clone())
+        );
+
+        let json = serde_json::to_string(&quantized).unwrap();
+        assert_eq!(
+            serde_json::from_str::<QuantizedVector>(&json).unwrap(),
+            quantized
+        );
+        let invalid_format = r#"{"format":{"scale":0,"max_magnitude":1},"values":[]}"#;
+        assert!(serde_json::from_str::<QuantizedVector>(invalid_format).is_err());
+    }
+
+    #[test]
+    fn test_overflowing_values_and_scalars_are_rejected() {
+        let format = FixedPoint::with_bits(8, 4).unwrap();
+        assert_eq!(format.quantize(15.99), Ok(4093));
+        assert_eq!(format.quantize(16.0), Err(Error::QuantizationOverflow));
+        assert_eq!(format.quantize(-16.0), Err(Error::QuantizationOverflow));
+        assert_eq!(format.quantize(f64::NAN), Err(Error::QuantizationOverflow));
+        assert_eq!(
+            format.to_scalar::<BlsScalar>(1 << 12),
+            Err(Error::QuantizationOverflow)
+        );
+
+        // Scalars outside the magnitude bound on either side of zero don't decode
+        for scalar in [
+            RistrettoScalar::from(1u64 << 12),
+            -RistrettoScalar::from(1u64 << 12),
+            RistrettoScalar::from(u128::MAX),
+        ] {
+            assert_eq!(
+                format.from_scalar(&scalar),
+                Err(Error::QuantizationOverflow)
+            );
+        }
+        assert_eq!(
+            format.from_scalar(&-RistrettoScalar::from(4095u64)),
+            Ok(-4095)
+        );
+        assert_eq!(FixedPoint::new(0, 1), Err(Error::InvalidFixedPoint));
+        assert_eq!(FixedPoint::with_bits(40, 24), Err(Error::InvalidFixedPoint));
+    }
+}
@@ -0,0 +1,316 @@
+//! Pluggable hash backends for Merkle trees and other commitments to byte strings
+
+use std::sync::OnceLock;
+
+use bls12_381::Scalar;
+use sha2::Digest as _;
+
+// Which hash function is cheapest depends on where the digests end up being verified:
+//
+// * Off-chain verifiers run on ordinary CPUs, where BLAKE3 is the fastest of the backends.
+// * SHA-256 and SHA3-256 are the conservative choices, available in hardware and in every
+// standard library a verifier might be written against.
+// * Verifiers inside arithmetic circuits, e.g. on-chain, pay for every bit operation of a bitwise
+// hash with many constraints. Poseidon is built from additions and multiplications in the
+// BLS12-381 scalar field instead, so hashing a Merkle node costs a few hundred constraints rather
+// than tens of thousands.
+//
+// Every backend hashes a domain separator along with the data and produces 32 byte digests, so
+// Merkle trees and commitments can be built over any of them. Commitments should bind the label of
+// the backend they were built with, so a digest computed with one backend can't be passed off as
+// one computed with another.
+//
+// The Poseidon instance works over the BLS12-381 scalar field with a state of 3 elements (rate 2,
+// capacity 1), the S-box x^5, 8 full and 57 partial rounds. Its round constants are derived from
+// SHA-256 rather than the Grain LFSR of the reference implementation, and its MDS matrix is the
+// Cauchy matrix 1/(i + j + 3), so circuits verifying these digests must use the same parameters.
+// Bytes are packed into field elements 31 at a time after appending a 0x01 byte and zero padding,
+// and the capacity element separates hashes of byte strings from hashes of node pairs, which are
+// absorbed as field elements directly.
+
+/// Number of field elements in the state of the Poseidon permutation
+pub const POSEIDON_WIDTH: usize = 3;
+
+// Number of full Poseidon rounds, half of them before and half after the partial rounds
+const FULL_ROUNDS: usize = 8;
+
+// Number of partial Poseidon rounds
+const PARTIAL_ROUNDS: usize = 57;
+
+// Number of bytes packed into each field element
+const BYTES_PER_ELEMENT: usize = 31;
+
+// Domain separator for deriving Poseidon round constants
+const POSEIDON_CONSTANTS_DOMAIN_SEP: &[u8] = b"POSEIDON_BLS12_381_T3_CONSTANTS";
+
+/// Round constants and MDS matrix of the Poseidon permutation behind the Poseidon backend, for
+/// circuits recomputing its digests
+pub struct PoseidonParameters {
+    round_constants: Vec<[Scalar; POSEIDON_WIDTH]>,
+    mds: [[Scalar; POSEIDON_WIDTH]; POSEIDON_WIDTH],
+}
+
+impl PoseidonParameters {
+    /// Get the parameters of the Poseidon backend
+    pub fn get() -> &'static Self {
+        POSEIDON_PARAMETERS.get_or_init(poseidon_parameters)
+    }
+
+    /// Get the constants added to the state at the start of each round
+    pub fn round_constants(&self) -> &[[Scalar; POSEIDON_WIDTH]] {
+        &self.round_constants
+    }
+
+    /// Get the MDS matrix mixing the state at the end of each round
+    pub fn mds(&self) -> &[[Scalar; POSEIDON_WIDTH]; POSEIDON_WIDTH] {
+        &self.mds
+    }
+
+    /// Whether a round applies the S-box to the whole state rather than only its first element
+    pub fn is_full_round(&self, round: usize) -> bool {
+        let first_partial_round = FULL_ROUNDS / 2;
+        !(first_partial_round..first_partial_round + PARTIAL_ROUNDS).contains(&round)
+    }
+}
+
+static POSEIDON_PARAMETERS: OnceLock<PoseidonParameters> = OnceLock::new();
+
+/// Hash function producing 32 byte digests of domain separated data
+pub trait Digest {
+    /// Label identifying the hash function, to be bound into commitments built with it
+    fn label(&self) -> &'static [u8];
+
+    /// Hash data under a domain separator
+    fn hash(&self, domain: &[u8], data: &[u8]) -> [u8; 32];
+
+    /// Hash two digests, e.g. the children of a Merkle tree node, under a domain separator
+    fn hash_pair(&self, domain: &[u8], left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut data = [0; 64];
+        data[..32].copy_from_slice(left);
+        data[32..].copy_from_slice(right);
+        self.hash(domain, &data)
+    }
+}
+
+/// Hash backend selected for a deployment
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum HashBackend {
+    /// SHA-256, supported by every platform
+    #[default]
+    Sha256,
+    /// SHA3-256
+    Sha3_256,
+    /// BLAKE3, the fastest backend for off-chain verification
+    Blake3,
+    /// Poseidon over the BLS12-381 scalar field, the cheapest backend to verify in circuits
+    Poseidon,
+}
+
+impl Digest for HashBackend {
+    fn label(&self) -> &'static [u8] {
+        match self {
+            HashBackend::Sha256 => b"DIGEST_SHA256",
+            HashBackend::Sha3_256 => b"DIGEST_SHA3_256",
+            HashBackend::Blake3 => b"DIGEST_BLAKE3",
+            HashBackend::Poseidon => b"DIGEST_POSEIDON_BLS12_381",
+        }
+    }
+
+    fn hash(&self, domain: &[u8], data: &[u8]) -> [u8; 32] {
+        // The domain is length prefixed so no domain and data can be mistaken for another split
+        let domain_len = (domain.len() as u64).to_le_bytes();
+        match self {
+            HashBackend::Sha256 => sha2::Sha256::new()
+                .chain_update(domain_len)
+                .chain_update(domain)
+                .chain_update(data)
+                .finalize()
+                .into(),
+            HashBackend::Sha3_256 => sha3::Sha3_256::new()
+                .chain_update(domain_len)
+                .chain_update(domain)
+                .chain_update(data)
+                .finalize()
+                .into(),
+            HashBackend::Blake3 => blake3::Hasher::new()
+                .update(&domain_len)
+                .update(domain)
+                .update(data)
+                .finalize()
+                .into(),
+            HashBackend::Poseidon => {
+                let mut elements = pack_bytes(domain);
+                elements.extend(pack_bytes(data));
+                poseidon_sponge(Scalar::zero(), &elements).to_bytes()
+            }
+        }
+    }
+
+    fn hash_pair(&self, domain: &[u8], left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        if *self != HashBackend::Poseidon {
+            let mut data = [0; 64];
+            data[..32].copy_from_slice(left);
+            data[32..].copy_from_slice(right);
+            return self.hash(domain, &data);
+        }
+        // Poseidon digests are field elements already and are absorbed as they are, so circuits
+        // don't need to unpack them into bytes
+        let mut elements = pack_bytes(domain);
+        elements.extend([to_element(left), to_element(right)]);
+        poseidon_sponge(Scalar::one(), &elements).to_bytes()
+    }
+}
+
+/// Pack bytes into field elements the way the Poseidon backend absorbs them, after appending a
+/// 0x01 byte and zero padding
+pub fn pack_bytes(bytes: &[u8]) -> Vec<Scalar> {
+    let mut padded = bytes.to_vec();
+    padded.push(1);
+    padded.resize(
+        padded.len().div_ceil(BYTES_PER_ELEMENT) * BYTES_PER_ELEMENT,
+        0,
+    );
+    padded
+        .chunks(BYTES_PER_ELEMENT)
+        .map(|chunk| {
+            let mut bytes = [0; 32];
+            bytes[..BYTES_PER_ELEMENT].copy_from_slice(chunk);
+            Scalar::from_bytes(&bytes).unwrap()
+        })
+        .collect()
+}
+
+// Map a digest to a field element, reducing digests which aren't canonical field elements
+fn to_element(digest: &[u8; 32]) -> Scalar {
+    let mut wide = [0; 64];
+    wide[..32].copy_from_slice(digest);
+    Scalar::from_bytes_wide(&wide)
+}
+
+// Absorb field elements into a Poseidon sponge with the given capacity element and squeeze one
+fn poseidon_sponge(capacity: Scalar, elements: &[Scalar]) -> Scalar {
+    let mut state = [capacity, Scalar::zero(), Scalar::zero()];
+    for block in elements.chunks(POSEIDON_WIDTH - 1) {
+        for (lane, element) in state[1..].iter_mut().zip(block) {
+            *lane += element;
+        }
+        poseidon_permutation(&mut state);
+    }
+    state[1]
+}
+
+// Apply the Poseidon permutation to a state
+fn poseidon_permutation(state: &mut [Scalar; POSEIDON_WIDTH]) {
+    let parameters = PoseidonParameters::get();
+    for (round, constants) in parameters.round_constants.iter().enumerate() {
+        for (element, constant) in state.iter_mut().zip(constants) {
+            *element += constant;
+        }
+        if parameters.is_full_round(round) {
+            state
+                .iter_mut()
+                .for_each(|element| *element = sbox(element));
+        } else {
+            state[0] = sbox(&state[0]);
+        }
+        let mixed = parameters
+            .mds
+            .map(|row| row.iter().zip(state.iter()).map(|(m, x)| m * x).sum());
+        *state = mixed;
+    }
+}
+
+// Raise a field element to the fifth power, which permutes the field since 5 doesn't divide r - 1
+fn sbox(element: &Scalar) -> Scalar {
+    element.square().square() * element
+}
+
+// Derive the round constants from SHA-256 and build the Cauchy MDS matrix
+fn poseidon_parameters() -> PoseidonParameters {
+    let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+        .map(|round| {
+            std::array::from_fn(|lane| {
+                let mut wide = [0; 64];
+                for (counter, half) in wide.chunks_mut(32).enumerate() {
+                    let hash = sha2::Sha256::new()
+                        .chain_update(POSEIDON_CONSTANTS_DOMAIN_SEP)
+                        .chain_update(((round * POSEIDON_WIDTH + lane) as u32).to_le_bytes())
+                        .chain_update([counter as u8])
+                        .finalize();
+                    half.copy_from_slice(&hash);
+                }
+                Scalar::from_bytes_wide(&wide)
+            })
+        })
+        .collect();
+    let mds = std::array::from_fn(|i| {
+        std::array::from_fn(|j| {
+            Scalar::from((i + j + POSEIDON_WIDTH) as u64)
+                .invert()
+                .unwrap()
+        })
+    });
+    PoseidonParameters {
+        round_constants,
+        mds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BACKENDS: [HashBackend; 4] = [
+        HashBackend::Sha256,
+        HashBackend::Sha3_256,
+        HashBackend::Blake3,
+        HashBackend::Poseidon,
+    ];
+
+    #[test]
+    fn test_backends_separate_domains_data_and_each_other() {
+        let mut digests = Vec::new();
+        for backend in BACKENDS {
+            let digest = backend.hash(b"DOMAIN", b"data");
+            assert_eq!(digest, backend.hash(b"DOMAIN", b"data"));
+            assert_ne!(digest, backend.hash(b"DOMAI", b"Ndata"));
+            assert_ne!(digest, backend.hash(b"DOMAIN", b"data\0"));
+            assert_ne!(digest, backend.hash_pair(b"DOMAIN", &[0; 32], &[0; 32]));
+            digests.push(digest);
+        }
+        digests.dedup();
+        assert_eq!(digests.len(), BACKENDS.len());
+
+        // The byte based backends match their reference implementations
+        let mut prefixed = 6u64.to_le_bytes().to_vec();
+        prefixed.extend_from_slice(b"DOMAINdata");
+        assert_eq!(
+            digests[0],
+            <[u8; 32]>::from(sha2::Sha256::digest(&prefixed))
+        );
+        assert_eq!(digests[2], *blake3::hash(&prefixed).as_bytes());
+    }
+
+    #[test]
+    fn test_poseidon_digests_are_field_elements_hashed_as_nodes() {
+        let leaves: Vec<[u8; 32]> = (0..4u8)
+            .map(|i| HashBackend::Poseidon.hash(b"LEAF", &[i; 40]))
+            .collect();
+        for leaf in &leaves {
+            assert!(bool::from(Scalar::from_bytes(leaf).is_some()));
+        }
+        let left = HashBackend::Poseidon.hash_pair(b"NODE", &leaves[0], &leaves[1]);
+        let right = HashBackend::Poseidon.hash_pair(b"NODE", &leaves[2], &leaves[3]);
+        assert_ne!(left, right);
+        assert_ne!(
+            left,
+            HashBackend::Poseidon.hash_pair(b"NODE", &leaves[1], &leaves[0])
+        );
+        let root = HashBackend::Poseidon.hash_pair(b"NODE", &left, &right);
+
+        // The node hash is a sponge over the packed domain and the child elements
+        let mut elements = pack_bytes(b"NODE");
+        elements.extend([to_element(&left), to_element(&right)]);
+        assert_eq!(root, poseidon_sponge(Scalar::one(), &elements).to_bytes());
+    }
+}
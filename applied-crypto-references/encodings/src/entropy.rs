@@ -0,0 +1,215 @@
+//! Accounting of the challenge entropy proofs derive and their resistance to grinding
+
+use std::collections::BTreeMap;
+
+use crate::Profile;
+
+// A challenge drawn from a space of 2^c values gives a special sound protocol a soundness error
+// of 2^-c per round, and k rounds whose answers must all verify give 2^-(c_1 + ... + c_k). That
+// estimate assumes a cheating prover gets a single attempt, which is only true for a live
+// interactive verifier.
+//
+// Once Fiat-Shamir derives the challenges from a hash of the transcript, a cheating prover can
+// grind offline: it retries its message for a round until the hash hands out a challenge it can
+// answer, which takes about 2^c_i hash evaluations, and then moves on to the next round. The
+// rounds are broken one after the other rather than all at once, so the work is only
+// 2^c_1 + ... + 2^c_k. Repeating a protocol with tiny challenges e.g. 128 times gives 128 bits
+// of soundness interactively but barely more than the bits of a single challenge with
+// Fiat-Shamir.
+//
+// An `EntropyLedger` records the challenges each protocol of a proof derives, together with the
+// bits of soundness each one actually contributes, which may be less than its entropy when a
+// challenge only catches a cheater unless it hits one of a few bad values. Its reports estimate the
+// effective soundness of every protocol under the way challenges were derived and flag the ones a
+// prover could grind below the soundness target of the security profile.
+
+/// How the verifier challenges of a proof are derived
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChallengeSource {
+    /// A live verifier samples challenges and a cheating prover gets a single attempt
+    Interactive,
+    /// Challenges are hashes of the transcript which a cheating prover can retry offline
+    FiatShamir,
+}
+
+/// Challenge derived by a protocol
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChallengeRecord {
+    /// Log2 of the number of values the challenge is drawn from
+    pub entropy_bits: f64,
+    /// Negative log2 of the probability that the challenge lets a cheating prover pass
+    pub soundness_bits: f64,
+}
+
+/// Effective soundness estimate of one protocol of a proof
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GrindingReport {
+    /// Label of the protocol
+    pub protocol: &'static str,
+    /// Number of challenges the protocol derived
+    pub challenges: usize,
+    /// Total entropy of the protocol's challenges
+    pub entropy_bits: f64,
+    /// Negative log2 of the soundness error or, for Fiat-Shamir challenges, log2 of the expected
+    /// number of hash evaluations a grinding prover needs
+    pub effective_soundness_bits: f64,
+    /// Whether the effective soundness falls short of the profile's soundness target
+    pub grindable: bool,
+}
+
+/// Ledger of the challenges each protocol of a proof derives
+#[derive(Clone, Debug)]
+pub struct EntropyLedger {
+    source: ChallengeSource,
+    profile: Profile,
+    // Challenges derived by each protocol, in order
+    records: BTreeMap<&'static str, Vec<ChallengeRecord>>,
+}
+
+impl EntropyLedger {
+    /// Create an empty ledger for challenges derived from `source`, checked against the soundness
+    /// target of `profile`
+    pub fn new(source: ChallengeSource, profile: Profile) -> Self {
+        Self {
+            source,
+            profile,
+            records: BTreeMap::new(),
+        }
+    }
+
+    /// Record a challenge drawn uniformly from 2^bits values, each of which except one catches a
+    /// cheating prover
+    pub fn record_challenge(&mut self, protocol: &'static str, bits: f64) {
+        self.record(
+            protocol,
+            ChallengeRecord {
+                entropy_bits: bits,
+                soundness_bits: bits,
+            },
+        );
+    }
+
+    /// Record a challenge along with the soundness it contributes
+    pub fn record(&mut self, protocol: &'static str, record: ChallengeRecord) {
+        self.records.entry(protocol).or_default().push(record);
+    }
+
+    /// Get the challenges a protocol derived
+    pub fn get_records(&self, protocol: &str) -> &[ChallengeRecord] {
+        self.records.get(protocol).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the total entropy of the challenges a protocol derived
+    pub fn challenge_bits(&self, protocol: &str) -> f64 {
+        self.get_records(protocol)
+            .iter()
+            .map(|record| record.entropy_bits)
+            .sum()
+    }
+
+    /// Estimate the effective soundness of every protocol, ordered by protocol label
+    pub fn reports(&self) -> Vec<GrindingReport> {
+        self.records
+            .iter()
+            .map(|(protocol, records)| {
+                let effective_soundness_bits = match self.source {
+                    ChallengeSource::Interactive => {
+                        records.iter().map(|record| record.soundness_bits).sum()
+                    }
+                    ChallengeSource::FiatShamir => grinding_work_bits(records),
+                };
+                GrindingReport {
+                    protocol,
+                    challenges: records.len(),
+                    entropy_bits: records.iter().map(|record| record.entropy_bits).sum(),
+                    effective_soundness_bits,
+                    grindable: effective_soundness_bits < self.profile.soundness_bits() as f64,
+                }
+            })
+            .collect()
+    }
+
+    /// Get the reports of the protocols a prover could grind below the profile's soundness target
+    pub fn grindable(&self) -> Vec<GrindingReport> {
+        self.reports()
+            .into_iter()
+            .filter(|report| report.grindable)
+            .collect()
+    }
+
+    /// Get the lowest effective soundness of any protocol, i.e. of the proof as a whole
+    pub fn effective_soundness_bits(&self) -> Option<f64> {
+        self.reports()
+            .iter()
+            .map(|report| report.effective_soundness_bits)
+            .min_by(f64::total_cmp)
+    }
+}
+
+// Log2 of 2^s_1 + ... + 2^s_k, computed relative to the largest term so it doesn't overflow
+fn grinding_work_bits(records: &[ChallengeRecord]) -> f64 {
+    let max = records
+        .iter()
+        .map(|record| record.soundness_bits)
+        .fold(f64::NEG_INFINITY, f64::max);
+    if max.is_infinite() {
+        return 0.0;
+    }
+    let sum: f64 = records
+        .iter()
+        .map(|record| (record.soundness_bits - max).exp2())
+        .sum();
+    max + sum.log2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fiat_shamir_repetitions_of_tiny_challenges_are_grindable() {
+        let mut interactive =
+            EntropyLedger::new(ChallengeSource::Interactive, Profile::Standard128);
+        let mut fiat_shamir = EntropyLedger::new(ChallengeSource::FiatShamir, Profile::Standard128);
+        for ledger in [&mut interactive, &mut fiat_shamir] {
+            for _ in 0..128 {
+                ledger.record_challenge("bit_challenges", 1.0);
+            }
+            ledger.record_challenge("schnorr", 252.0);
+        }
+        assert_eq!(fiat_shamir.challenge_bits("bit_challenges"), 128.0);
+
+        // 128 one bit challenges are sound interactively but take only 256 hashes to grind
+        assert!(interactive.grindable().is_empty());
+        let grindable = fiat_shamir.grindable();
+        assert_eq!(grindable.len(), 1);
+        assert_eq!(grindable[0].protocol, "bit_challenges");
+        assert_eq!(grindable[0].challenges, 128);
+        assert_eq!(grindable[0].effective_soundness_bits, 8.0);
+        assert_eq!(fiat_shamir.effective_soundness_bits(), Some(8.0));
+        assert_eq!(interactive.effective_soundness_bits(), Some(128.0));
+    }
+
+    #[test]
+    fn test_lossy_challenges_contribute_less_than_their_entropy() {
+        let mut ledger = EntropyLedger::new(ChallengeSource::Interactive, Profile::Interactive);
+        // Challenges from 2^10 values which catch a cheater unless they hit one of 4 roots
+        let record = ChallengeRecord {
+            entropy_bits: 10.0,
+            soundness_bits: 8.0,
+        };
+        for _ in 0..5 {
+            ledger.record("polynomial", record);
+        }
+        let report = ledger.reports()[0];
+        assert_eq!(report.entropy_bits, 50.0);
+        assert_eq!(report.effective_soundness_bits, 40.0);
+        assert!(!report.grindable);
+        assert!(ledger.get_records("other").is_empty());
+        assert_eq!(
+            EntropyLedger::new(ChallengeSource::FiatShamir, Profile::Paranoid)
+                .effective_soundness_bits(),
+            None
+        );
+    }
+}
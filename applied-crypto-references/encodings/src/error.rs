@@ -0,0 +1,41 @@
+//! Errors in canonical encodings and security policies
+
+use crate::SecurityLevel;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Encoded value has the wrong length (expected, actual)
+    InvalidLength(usize, usize),
+    /// Encoded scalar is not reduced modulo the scalar field order
+    NonCanonicalScalar,
+    /// Encoded point is not the canonical encoding of a point in the prime order group
+    NonCanonicalPoint,
+    /// Fixed-point formats need a non-zero scale and a magnitude bound of at most `i64::MAX`
+    InvalidFixedPoint,
+    /// Value doesn't fit the magnitude bound of its fixed-point format
+    QuantizationOverflow,
+    /// Scheme's security level is refused by the policy (scheme level, required level)
+    InsufficientSecurityLevel(SecurityLevel, SecurityLevel),
+    /// Encodings don't behave as specified on the running platform (broken property)
+    SelfCheckFailed(&'static str),
+    /// Archive signature doesn't verify under the verifying key
+    InvalidSignature,
+    /// Table can't be read or its commitments can't be written
+    TableRead(String),
+    /// Table has no header line or its header can't be parsed
+    MalformedHeader,
+    /// Table has no column with this name
+    UnknownColumn(String),
+    /// Row has the wrong number of fields or a value which isn't a number (row)
+    MalformedRow(usize),
+    /// Row has a value which doesn't fit the fixed-point format (row)
+    RowOverflow(usize),
+    /// Feature's scale and range don't fit its type, or its name is repeated (feature name)
+    InvalidFeatureSchema(String),
+    /// Value doesn't fit the range of its feature (feature name)
+    FeatureOutOfRange(String),
+    /// Schemas of the prover and the verifier describe this feature differently (feature name)
+    SchemaMismatch(String),
+    /// Number of values or commitments doesn't match the schema (expected, actual)
+    FeatureCount(usize, usize),
+}
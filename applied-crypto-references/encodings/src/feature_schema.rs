@@ -0,0 +1,597 @@
+//! Per-feature schemas describing how raw features map into committed field elements
+
+use crate::{CommitmentGroup, Digest, Error, FixedPoint, PedersenCommitment};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::ops::Neg;
+
+// A commitment to a feature vector only means something if both counterparties agree on what the
+// committed field elements stand for. Two parties quantizing "income" with different scales, or
+// one of them encoding negative values by negation and the other by an offset, would prove and
+// verify statements about different numbers without either noticing. A [`FeatureSchema`] fixes
+// the mapping for every feature:
+//
+// 1. Each feature has a name, a type, a fixed-point scale, a range of quantized values and an
+// encoding into the field. Types restrict the other fields, e.g. booleans and categories are
+// quantized with a scale of 1 to a range starting at 0.
+// 2. The prover quantizes raw values with the schema, which rejects values outside the range
+// instead of clamping them, and commits to the encoded field elements.
+// 3. The schema is serialized with the commitments in a [`CommitmentLayout`], and the verifier
+// checks it matches its own copy feature by feature before looking at any proof. The schema's
+// digest can be bound into proof transcripts so proofs can't be moved to another layout either.
+//
+// Ranges are given in quantized units, which unlike real bounds are exact. With the offset
+// encoding a value v is committed as v - min, which is never negative and fits in the bit length
+// of max - min, as range proofs over bits expect.
+
+// Domain separator of schema digests
+const FEATURE_SCHEMA_DOMAIN_SEP: &[u8] = b"FEATURE_SCHEMA";
+
+/// Kind of value a feature holds
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FeatureType {
+    /// Real number quantized with the feature's scale
+    Continuous,
+    /// Integer, quantized with a scale of 1
+    Integer,
+    /// Boolean given as 0 or 1
+    Boolean,
+    /// Category given as its index among the number of categories
+    Categorical(u32),
+}
+
+/// Mapping of a quantized value into the scalar field
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FeatureEncoding {
+    /// Non-negative values map to themselves and negative ones to their field negation
+    Signed,
+    /// Values map to their distance from the lower end of the range
+    Offset,
+}
+
+/// Name, type, scale, range and encoding of a single feature
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "RawFeatureSpec")]
+pub struct FeatureSpec {
+    name: String,
+    feature_type: FeatureType,
+    // Factor values are multiplied by before rounding
+    scale: u64,
+    // Smallest and largest quantized value
+    min: i64,
+    max: i64,
+    encoding: FeatureEncoding,
+}
+
+// Unvalidated feature as it is deserialized
+#[derive(Deserialize)]
+struct RawFeatureSpec {
+    name: String,
+    feature_type: FeatureType,
+    scale: u64,
+    min: i64,
+    max: i64,
+    encoding: FeatureEncoding,
+}
+
+impl TryFrom<RawFeatureSpec> for FeatureSpec {
+    type Error = &'static str;
+
+    fn try_from(raw: RawFeatureSpec) -> Result<Self, Self::Error> {
+        Self::new(
+            &raw.name,
+            raw.feature_type,
+            raw.scale,
+            raw.min,
+            raw.max,
+            raw.encoding,
+        )
+        .map_err(|_| "feature's scale and range don't fit its type")
+    }
+}
+
+impl FeatureSpec {
+    /// Create a feature quantized with `scale` to values within [min, max]. Integer, boolean and
+    /// categorical features need a scale of 1, booleans the range [0, 1] and categories the range
+    /// [0, categories - 1].
+    pub fn new(
+        name: &str,
+        feature_type: FeatureType,
+        scale: u64,
+        min: i64,
+        max: i64,
+        encoding: FeatureEncoding,
+    ) -> Result<Self, Error> {
+        let invalid = || Error::InvalidFeatureSchema(name.to_string());
+        // Excluding i64::MIN keeps the magnitudes and the width of the range within i64
+        if scale == 0 || min > max || min == i64::MIN || max.checked_sub(min).is_none() {
+            return Err(invalid());
+        }
+        let valid_type = match feature_type {
+            FeatureType::Continuous => true,
+            FeatureType::Integer => scale == 1,
+            FeatureType::Boolean => scale == 1 && (min, max) == (0, 1),
+            FeatureType::Categorical(categories) => {
+                scale == 1 && categories > 0 && (min, max) == (0, categories as i64 - 1)
+            }
+        };
+        if !valid_type {
+            return Err(invalid());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            feature_type,
+            scale,
+            min,
+            max,
+            encoding,
+        })
+    }
+
+    /// Get the name of the feature
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the type of the feature
+    pub fn get_type(&self) -> FeatureType {
+        self.feature_type
+    }
+
+    /// Get the factor values are multiplied by before rounding
+    pub fn get_scale(&self) -> u64 {
+        self.scale
+    }
+
+    /// Get the smallest and largest quantized value
+    pub fn get_range(&self) -> (i64, i64) {
+        (self.min, self.max)
+    }
+
+    /// Get the mapping of quantized values into the scalar field
+    pub fn get_encoding(&self) -> FeatureEncoding {
+        self.encoding
+    }
+
+    /// Quantize a raw value, which has to be a whole number for all but continuous features
+    pub fn quantize(&self, value: f64) -> Result<i64, Error> {
+        let out_of_range = || Error::FeatureOutOfRange(self.name.clone());
+        if self.feature_type != FeatureType::Continuous && value.fract() != 0.0 {
+            return Err(out_of_range());
+        }
+        let magnitude = self.min.unsigned_abs().max(self.max.unsigned_abs());
+        let format = FixedPoint::new(self.scale, magnitude).map_err(|_| out_of_range())?;
+        let quantized = format.quantize(value).map_err(|_| out_of_range())?;
+        if !(self.min..=self.max).contains(&quantized) {
+            return Err(out_of_range());
+        }
+        Ok(quantized)
+    }
+
+    /// Map a quantized value within the range into the scalar field
+    pub fn to_scalar<S>(&self, quantized: i64) -> Result<S, Error>
+    where
+        S: From<u64> + Neg<Output = S>,
+    {
+        if !(self.min..=self.max).contains(&quantized) {
+            return Err(Error::FeatureOutOfRange(self.name.clone()));
+        }
+        Ok(match self.encoding {
+            FeatureEncoding::Signed if quantized < 0 => -S::from(quantized.unsigned_abs()),
+            FeatureEncoding::Signed => S::from(quantized as u64),
+            FeatureEncoding::Offset => S::from((quantized - self.min) as u64),
+        })
+    }
+
+    // Append the canonical encoding of the feature to a digest's input
+    fn append_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend((self.name.len() as u64).to_le_bytes());
+        bytes.extend(self.name.as_bytes());
+        match self.feature_type {
+            FeatureType::Continuous => bytes.push(0),
+            FeatureType::Integer => bytes.push(1),
+            FeatureType::Boolean => bytes.push(2),
+            FeatureType::Categorical(categories) => {
+                bytes.push(3);
+                bytes.extend(categories.to_le_bytes());
+            }
+        }
+        bytes.extend(self.scale.to_le_bytes());
+        bytes.extend(self.min.to_le_bytes());
+        bytes.extend(self.max.to_le_bytes());
+        bytes.push(match self.encoding {
+            FeatureEncoding::Signed => 0,
+            FeatureEncoding::Offset => 1,
+        });
+    }
+}
+
+/// Ordered features of a committed feature vector
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "RawFeatureSchema")]
+pub struct FeatureSchema {
+    features: Vec<FeatureSpec>,
+}
+
+// Unvalidated schema as it is deserialized
+#[derive(Deserialize)]
+struct RawFeatureSchema {
+    features: Vec<FeatureSpec>,
+}
+
+impl TryFrom<RawFeatureSchema> for FeatureSchema {
+    type Error = &'static str;
+
+    fn try_from(raw: RawFeatureSchema) -> Result<Self, Self::Error> {
+        Self::new(raw.features).map_err(|_| "schema needs features with distinct names")
+    }
+}
+
+impl FeatureSchema {
+    /// Create a schema from at least one feature, with distinct names
+    pub fn new(features: Vec<FeatureSpec>) -> Result<Self, Error> {
+        if features.is_empty() {
+            return Err(Error::InvalidFeatureSchema(String::new()));
+        }
+        for (i, feature) in features.iter().enumerate() {
+            if features[..i].iter().any(|other| other.name == feature.name) {
+                return Err(Error::InvalidFeatureSchema(feature.name.clone()));
+            }
+        }
+        Ok(Self { features })
+    }
+
+    /// Get the features in the order of the feature vector
+    pub fn get_features(&self) -> &[FeatureSpec] {
+        &self.features
+    }
+
+    /// Get the number of features
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Check whether the schema has no features, which valid schemas never do
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Hash the canonical encoding of the schema, e.g. to bind it into proof transcripts
+    pub fn digest<D: Digest>(&self, backend: &D) -> [u8; 32] {
+        let mut bytes = (self.features.len() as u64).to_le_bytes().to_vec();
+        for feature in &self.features {
+            feature.append_bytes(&mut bytes);
+        }
+        backend.hash(FEATURE_SCHEMA_DOMAIN_SEP, &bytes)
+    }
+
+    /// Check another schema describes exactly the same features, reporting the first feature
+    /// which differs
+    pub fn check_matches(&self, other: &FeatureSchema) -> Result<(), Error> {
+        if let Some((ours, _)) = self
+            .features
+            .iter()
+            .zip(&other.features)
+            .find(|(ours, theirs)| ours != theirs)
+        {
+            return Err(Error::SchemaMismatch(ours.name.clone()));
+        }
+        if self.features.len() != other.features.len() {
+            return Err(Error::FeatureCount(self.len(), other.len()));
+        }
+        Ok(())
+    }
+
+    /// Quantize and encode a row of raw values into the scalar field
+    pub fn encode_row<S>(&self, values: &[f64]) -> Result<Vec<S>, Error>
+    where
+        S: From<u64> + Neg<Output = S>,
+    {
+        if values.len() != self.len() {
+            return Err(Error::FeatureCount(self.len(), values.len()));
+        }
+        self.features
+            .iter()
+            .zip(values)
+            .map(|(feature, value)| feature.to_scalar(feature.quantize(*value)?))
+            .collect()
+    }
+
+    /// Commit to a row of raw values, returning the commitments laid out by this schema along
+    /// with the blinding of each commitment
+    pub fn commit_row<C, R>(
+        &self,
+        values: &[f64],
+        rng: &mut R,
+    ) -> Result<(CommitmentLayout<C>, Vec<C::Scalar>), Error>
+    where
+        C: CommitmentGroup,
+        R: CryptoRng + RngCore,
+    {
+        let scalars: Vec<C::Scalar> = self.encode_row(values)?;
+        let blindings: Vec<C::Scalar> = scalars.iter().map(|_| C::random_scalar(rng)).collect();
+        let commitments = scalars
+            .iter()
+            .zip(&blindings)
+            .map(|(value, blinding)| PedersenCommitment::commit(value, blinding))
+            .collect();
+        let layout = CommitmentLayout {
+            schema: self.clone(),
+            commitments,
+        };
+        Ok((layout, blindings))
+    }
+}
+
+/// Commitments to a feature vector serialized along with the schema they were created under
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(
+    bound = "",
+    try_from = "RawCommitmentLayout",
+    into = "RawCommitmentLayout"
+)]
+pub struct CommitmentLayout<C: CommitmentGroup> {
+    schema: FeatureSchema,
+    commitments: Vec<PedersenCommitment<C>>,
+}
+
+// Commitment layout with hex encoded commitments as it is serialized
+#[derive(Deserialize, Serialize)]
+struct RawCommitmentLayout {
+    schema: FeatureSchema,
+    commitments: Vec<String>,
+}
+
+impl<C: CommitmentGroup> TryFrom<RawCommitmentLayout> for CommitmentLayout<C> {
+    type Error = &'static str;
+
+    fn try_from(raw: RawCommitmentLayout) -> Result<Self, Self::Error> {
+        let commitments = raw
+            .commitments
+            .iter()
+            .map(|encoded| {
+                hex::decode(encoded)
+                    .ok()
+                    .and_then(|bytes| PedersenCommitment::decode(&bytes).ok())
+            })
+            .collect::<Option<_>>()
+            .ok_or("commitments must be hex encoded canonical points")?;
+        Self::new(raw.schema, commitments).map_err(|_| "schema needs one commitment per feature")
+    }
+}
+
+impl<C: CommitmentGroup> From<CommitmentLayout<C>> for RawCommitmentLayout {
+    fn from(layout: CommitmentLayout<C>) -> Self {
+        Self {
+            schema: layout.schema,
+            commitments: layout
+                .commitments
+                .iter()
+                .map(|commitment| hex::encode(commitment.encode()))
+                .collect(),
+        }
+    }
+}
+
+impl<C: CommitmentGroup> CommitmentLayout<C> {
+    /// Lay out one commitment per feature of a schema
+    pub fn new(
+        schema: FeatureSchema,
+        commitments: Vec<PedersenCommitment<C>>,
+    ) -> Result<Self, Error> {
+        if commitments.len() != schema.len() {
+            return Err(Error::FeatureCount(schema.len(), commitments.len()));
+        }
+        Ok(Self {
+            schema,
+            commitments,
+        })
+    }
+
+    /// Get the schema the commitments were created under
+    pub fn get_schema(&self) -> &FeatureSchema {
+        &self.schema
+    }
+
+    /// Get the commitment to each feature in the order of the schema
+    pub fn get_commitments(&self) -> &[PedersenCommitment<C>] {
+        &self.commitments
+    }
+
+    /// Check the commitments were created under the schema the verifier expects
+    pub fn verify(&self, expected: &FeatureSchema) -> Result<(), Error> {
+        expected.check_matches(&self.schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bls12G1, HashBackend, Ristretto};
+    use curve25519_dalek::scalar::Scalar as RistrettoScalar;
+
+    fn schema() -> FeatureSchema {
+        FeatureSchema::new(vec![
+            FeatureSpec::new(
+                "income",
+                FeatureType::Continuous,
+                256,
+                -1 << 20,
+                1 << 20,
+                FeatureEncoding::Signed,
+            )
+            .unwrap(),
+            FeatureSpec::new(
+                "age",
+                FeatureType::Integer,
+                1,
+                18,
+                120,
+                FeatureEncoding::Offset,
+            )
+            .unwrap(),
+            FeatureSpec::new(
+                "member",
+                FeatureType::Boolean,
+                1,
+                0,
+                1,
+                FeatureEncoding::Offset,
+            )
+            .unwrap(),
+            FeatureSpec::new(
+                "region",
+                FeatureType::Categorical(4),
+                1,
+                0,
+                3,
+                FeatureEncoding::Offset,
+            )
+            .unwrap(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rows_commit_under_a_schema_the_verifier_checks() {
+        let schema = schema();
+        let scalars: Vec<RistrettoScalar> = schema.encode_row(&[-1.5, 30.0, 1.0, 2.0]).unwrap();
+        assert_eq!(
+            scalars,
+            [
+                -RistrettoScalar::from(384u64),
+                RistrettoScalar::from(12u64),
+                RistrettoScalar::from(1u64),
+                RistrettoScalar::from(2u64)
+            ]
+        );
+
+        let mut rng = rand::thread_rng();
+        let (layout, blindings) = schema
+            .commit_row::<Ristretto, _>(&[-1.5, 30.0, 1.0, 2.0], &mut rng)
+            .unwrap();
+        for ((commitment, value), blinding) in layout
+            .get_commitments()
+            .iter()
+            .zip(&scalars)
+            .zip(&blindings)
+        {
+            assert!(commitment.verify_opening(value, blinding));
+        }
+
+        // The layout survives serialization and is checked against the verifier's schema
+        let json = serde_json::to_string(&layout).unwrap();
+        let decoded: CommitmentLayout<Ristretto> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, layout);
+        assert_eq!(decoded.verify(&schema), Ok(()));
+
+        // A verifier quantizing income with another scale rejects the layout
+        let mut features = schema.get_features().to_vec();
+        features[0] = FeatureSpec::new(
+            "income",
+            FeatureType::Continuous,
+            1024,
+            -1 << 20,
+            1 << 20,
+            FeatureEncoding::Signed,
+        )
+        .unwrap();
+        let other = FeatureSchema::new(features.clone()).unwrap();
+        assert_eq!(
+            decoded.verify(&other),
+            Err(Error::SchemaMismatch("income".to_string()))
+        );
+        assert_ne!(
+            schema.digest(&HashBackend::Blake3),
+            other.digest(&HashBackend::Blake3)
+        );
+        let fewer = FeatureSchema::new(schema.get_features()[..3].to_vec()).unwrap();
+        assert_eq!(decoded.verify(&fewer), Err(Error::FeatureCount(3, 4)));
+
+        // Layouts with a commitment missing don't deserialize
+        let (layout, _) = schema
+            .commit_row::<Bls12G1, _>(&[0.0, 18.0, 0.0, 0.0], &mut rng)
+            .unwrap();
+        let mut value = serde_json::to_value(&layout).unwrap();
+        value["commitments"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<CommitmentLayout<Bls12G1>>(value).is_err());
+    }
+
+    #[test]
+    fn test_invalid_schemas_and_values_are_rejected() {
+        let invalid = |name: &str| Error::InvalidFeatureSchema(name.to_string());
+        assert_eq!(
+            FeatureSpec::new(
+                "age",
+                FeatureType::Integer,
+                2,
+                0,
+                9,
+                FeatureEncoding::Signed
+            ),
+            Err(invalid("age"))
+        );
+        assert_eq!(
+            FeatureSpec::new(
+                "flag",
+                FeatureType::Boolean,
+                1,
+                0,
+                2,
+                FeatureEncoding::Signed
+            ),
+            Err(invalid("flag"))
+        );
+        assert_eq!(
+            FeatureSpec::new(
+                "region",
+                FeatureType::Categorical(4),
+                1,
+                0,
+                4,
+                FeatureEncoding::Offset
+            ),
+            Err(invalid("region"))
+        );
+        assert_eq!(
+            FeatureSpec::new(
+                "x",
+                FeatureType::Continuous,
+                1,
+                3,
+                2,
+                FeatureEncoding::Signed
+            ),
+            Err(invalid("x"))
+        );
+        let age = schema().get_features()[1].clone();
+        assert_eq!(
+            FeatureSchema::new(vec![age.clone(), age]).err(),
+            Some(invalid("age"))
+        );
+        assert_eq!(FeatureSchema::new(Vec::new()).err(), Some(invalid("")));
+
+        // Values outside the range or with a fraction for whole number types are rejected
+        let schema = schema();
+        let out_of_range = |name: &str| Err(Error::FeatureOutOfRange(name.to_string()));
+        assert_eq!(
+            schema.encode_row::<RistrettoScalar>(&[0.0, 17.0, 0.0, 0.0]),
+            out_of_range("age")
+        );
+        assert_eq!(
+            schema.encode_row::<RistrettoScalar>(&[0.0, 18.5, 0.0, 0.0]),
+            out_of_range("age")
+        );
+        assert_eq!(
+            schema.encode_row::<RistrettoScalar>(&[5000.0, 18.0, 0.0, 0.0]),
+            out_of_range("income")
+        );
+        assert_eq!(
+            schema.encode_row::<RistrettoScalar>(&[0.0, 18.0, 0.0]),
+            Err(Error::FeatureCount(4, 3))
+        );
+        let json = r#"{"features":[{"name":"b","feature_type":"Boolean","scale":1,"min":0,
+            "max":3,"encoding":"Offset"}]}"#;
+        assert!(serde_json::from_str::<FeatureSchema>(json).is_err());
+    }
+}
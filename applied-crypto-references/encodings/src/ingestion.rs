@@ -0,0 +1,413 @@
+//! Streaming ingestion of tabular feature rows into fixed-point Pedersen commitments
+
+use crate::{
+    CommitmentGroup, Endianness, Error, FixedPoint, PedersenCommitment, QuantizedVector,
+    ScalarEncoding,
+};
+use rand::{CryptoRng, RngCore};
+use std::{
+    io::{BufRead, Write},
+    marker::PhantomData,
+};
+
+// Datasets exchanged between counterparties are tables with one example per row, often too large
+// to hold in memory at once. Committing to them is done as a stream which holds a single row at a
+// time:
+//
+// 1. A row source yields the features of one row after another as real numbers. [`CsvReader`]
+// reads CSV text a line at a time into a reused buffer and selects the feature columns by their
+// header names. Any other iterator of rows can be committed the same way, e.g. one over the
+// decoded record batches of a Parquet file, which isn't read here to keep the Arrow stack out of
+// this crate.
+// 2. Every row is quantized with a [`FixedPoint`] format. A row which can't be parsed or doesn't
+// fit the format stops the stream with an error naming the row, instead of being skipped.
+// 3. Every quantized value is committed to with a fresh random blinding. [`CommitmentStream`]
+// yields the commitments of each row together with their openings, or writes one line per row of
+// public commitments and one line per row of secret openings to separate writers.
+//
+// Rows are numbered from 0 in the order they are read, not counting the CSV header. Output lines
+// start with the row number so a commitment can be matched to its opening and source row.
+
+/// Reader of feature rows from CSV text with a header line
+pub struct CsvReader<R: BufRead> {
+    reader: R,
+    // Buffer holding the current line, reused for every row
+    line: String,
+    // Names of the selected columns
+    names: Vec<String>,
+    // Indices of the selected columns among the fields of a record
+    columns: Vec<usize>,
+    // Number of fields of every record
+    num_fields: usize,
+    // Number of rows read so far
+    row: usize,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    /// Create a reader selecting every column of the header
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        let header = Self::read_header(&mut reader)?;
+        Ok(Self {
+            reader,
+            line: String::new(),
+            num_fields: header.len(),
+            columns: (0..header.len()).collect(),
+            names: header,
+            row: 0,
+        })
+    }
+
+    /// Create a reader selecting the named columns of the header in the given order
+    pub fn with_columns(mut reader: R, names: &[&str]) -> Result<Self, Error> {
+        let header = Self::read_header(&mut reader)?;
+        let columns = names
+            .iter()
+            .map(|name| {
+                header
+                    .iter()
+                    .position(|column| column == name)
+                    .ok_or_else(|| Error::UnknownColumn(name.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            reader,
+            line: String::new(),
+            num_fields: header.len(),
+            columns,
+            names: names.iter().map(|name| name.to_string()).collect(),
+            row: 0,
+        })
+    }
+
+    // Read the header line and split it into column names
+    fn read_header(reader: &mut R) -> Result<Vec<String>, Error> {
+        let mut line = String::new();
+        let len = reader
+            .read_line(&mut line)
+            .map_err(|error| Error::TableRead(error.to_string()))?;
+        if len == 0 {
+            return Err(Error::MalformedHeader);
+        }
+        split_record(line.trim_end_matches(['\r', '\n'])).ok_or(Error::MalformedHeader)
+    }
+
+    /// Get the names of the selected columns in the order their values are returned
+    pub fn get_columns(&self) -> &[String] {
+        &self.names
+    }
+}
+
+impl<R: BufRead> Iterator for CsvReader<R> {
+    type Item = Result<Vec<f64>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line.clear();
+        match self.reader.read_line(&mut self.line) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(error) => return Some(Err(Error::TableRead(error.to_string()))),
+        }
+        let row = self.row;
+        self.row += 1;
+        let fields = match split_record(self.line.trim_end_matches(['\r', '\n'])) {
+            Some(fields) if fields.len() == self.num_fields => fields,
+            _ => return Some(Err(Error::MalformedRow(row))),
+        };
+        Some(
+            self.columns
+                .iter()
+                .map(|column| {
+                    fields[*column]
+                        .trim()
+                        .parse()
+                        .map_err(|_| Error::MalformedRow(row))
+                })
+                .collect(),
+        )
+    }
+}
+
+// Split a CSV record into its fields. Fields may be quoted to contain commas, with quotes inside
+// quoted fields doubled. Records spanning several lines aren't supported.
+fn split_record(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', false) if field.is_empty() => quoted = true,
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => {
+                // A closing quote must end the field
+                if !matches!(chars.peek(), None | Some(',')) {
+                    return None;
+                }
+                quoted = false;
+            }
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+/// Commitments to the quantized values of one row along with their openings
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RowCommitment<C: CommitmentGroup> {
+    row: usize,
+    values: QuantizedVector,
+    blindings: Vec<C::Scalar>,
+    commitments: Vec<PedersenCommitment<C>>,
+}
+
+impl<C: CommitmentGroup> RowCommitment<C> {
+    /// Get the number of the row the commitments belong to
+    pub fn get_row(&self) -> usize {
+        self.row
+    }
+
+    /// Get the quantized values committed to
+    pub fn get_values(&self) -> &QuantizedVector {
+        &self.values
+    }
+
+    /// Get the blinding of each commitment
+    pub fn get_blindings(&self) -> &[C::Scalar] {
+        &self.blindings
+    }
+
+    /// Get the commitment to each value
+    pub fn get_commitments(&self) -> &[PedersenCommitment<C>] {
+        &self.commitments
+    }
+
+    /// Check every commitment opens to its value with its blinding
+    pub fn verify_openings(&self) -> bool {
+        match self.values.to_scalars::<C::Scalar>() {
+            Ok(scalars) => {
+                scalars.len() == self.commitments.len()
+                    && self
+                        .commitments
+                        .iter()
+                        .zip(scalars.iter().zip(&self.blindings))
+                        .all(|(commitment, (value, blinding))| {
+                            commitment.verify_opening(value, blinding)
+                        })
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Format the public commitments as a CSV record of the row number followed by the hex
+    /// encoded commitments
+    pub fn commitment_record(&self) -> String {
+        let mut record = self.row.to_string();
+        for commitment in &self.commitments {
+            record.push(',');
+            record.push_str(&hex::encode(commitment.encode()));
+        }
+        record
+    }
+
+    /// Format the secret openings as a CSV record of the row number followed by each quantized
+    /// value and its hex encoded little endian blinding
+    pub fn opening_record(&self) -> String {
+        let mut record = self.row.to_string();
+        for (value, blinding) in self.values.get_values().iter().zip(&self.blindings) {
+            record.push_str(&format!(
+                ",{value},{}",
+                hex::encode(blinding.encode(Endianness::Little))
+            ));
+        }
+        record
+    }
+}
+
+/// Stream committing to every row of a row source
+pub struct CommitmentStream<C: CommitmentGroup, I, R> {
+    rows: I,
+    format: FixedPoint,
+    rng: R,
+    // Number of rows committed to so far
+    row: usize,
+    _group: PhantomData<C>,
+}
+
+impl<C, I, R> CommitmentStream<C, I, R>
+where
+    C: CommitmentGroup,
+    I: Iterator<Item = Result<Vec<f64>, Error>>,
+    R: CryptoRng + RngCore,
+{
+    /// Create a stream quantizing rows with a fixed-point format and blinding the commitments
+    /// with randomness from `rng`
+    pub fn new(rows: I, format: FixedPoint, rng: R) -> Self {
+        Self {
+            rows,
+            format,
+            rng,
+            row: 0,
+            _group: PhantomData,
+        }
+    }
+
+    /// Write a commitment record and an opening record for every row, one line each, returning
+    /// the number of rows written
+    pub fn write_to<W: Write, O: Write>(
+        self,
+        commitments: &mut W,
+        openings: &mut O,
+    ) -> Result<usize, Error> {
+        let mut count = 0;
+        for row in self {
+            let row = row?;
+            writeln!(commitments, "{}", row.commitment_record())
+                .and_then(|_| writeln!(openings, "{}", row.opening_record()))
+                .map_err(|error| Error::TableRead(error.to_string()))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<C, I, R> Iterator for CommitmentStream<C, I, R>
+where
+    C: CommitmentGroup,
+    I: Iterator<Item = Result<Vec<f64>, Error>>,
+    R: CryptoRng + RngCore,
+{
+    type Item = Result<RowCommitment<C>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let features = match self.rows.next()? {
+            Ok(features) => features,
+            Err(error) => return Some(Err(error)),
+        };
+        let row = self.row;
+        self.row += 1;
+        let values = match self.format.quantize_vector(&features) {
+            Ok(values) => values,
+            Err(_) => return Some(Err(Error::RowOverflow(row))),
+        };
+        let scalars = values
+            .to_scalars::<C::Scalar>()
+            .expect("quantized values fit their format");
+        let blindings: Vec<C::Scalar> = scalars
+            .iter()
+            .map(|_| C::random_scalar(&mut self.rng))
+            .collect();
+        let commitments = scalars
+            .iter()
+            .zip(&blindings)
+            .map(|(value, blinding)| PedersenCommitment::commit(value, blinding))
+            .collect();
+        Some(Ok(RowCommitment {
+            row,
+            values,
+            blindings,
+            commitments,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bls12G1, Ristretto};
+
+    const TABLE: &str = "id,\"income, monthly\",age,score\r\n\
+                         7,1.5,30,-0.25\r\n\
+                         8,\"2.75\",41,0.5\r\n";
+
+    #[test]
+    fn test_csv_rows_stream_into_commitments_with_openings() {
+        let reader = CsvReader::with_columns(TABLE.as_bytes(), &["score", "income, monthly"]);
+        let reader = reader.unwrap();
+        assert_eq!(reader.get_columns(), ["score", "income, monthly"]);
+        let format = FixedPoint::with_bits(8, 16).unwrap();
+        let rows: Vec<RowCommitment<Ristretto>> =
+            CommitmentStream::new(reader, format, rand::thread_rng())
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get_values().get_values(), [-64, 384]);
+        assert_eq!(rows[1].get_values().get_values(), [128, 704]);
+        assert!(rows.iter().all(RowCommitment::verify_openings));
+
+        // Records start with the row number and hold one commitment per selected column
+        let (mut commitments, mut openings) = (Vec::new(), Vec::new());
+        let reader = CsvReader::new(TABLE.as_bytes()).unwrap();
+        let stream = CommitmentStream::<Bls12G1, _, _>::new(reader, format, rand::thread_rng());
+        assert_eq!(stream.write_to(&mut commitments, &mut openings), Ok(2));
+        let commitments = String::from_utf8(commitments).unwrap();
+        let openings = String::from_utf8(openings).unwrap();
+        for (row, (commitment, opening)) in commitments.lines().zip(openings.lines()).enumerate() {
+            let commitment: Vec<&str> = commitment.split(',').collect();
+            let opening: Vec<&str> = opening.split(',').collect();
+            assert_eq!(
+                (commitment[0], opening[0]),
+                (&*row.to_string(), &*row.to_string())
+            );
+            assert_eq!((commitment.len(), opening.len()), (5, 9));
+            for (i, encoded) in commitment[1..].iter().enumerate() {
+                let commitment =
+                    PedersenCommitment::<Bls12G1>::decode(&hex::decode(encoded).unwrap()).unwrap();
+                let value = format
+                    .to_scalar(opening[2 * i + 1].parse().unwrap())
+                    .unwrap();
+                let blinding = bls12_381::Scalar::decode(
+                    &hex::decode(opening[2 * i + 2]).unwrap(),
+                    Endianness::Little,
+                )
+                .unwrap();
+                assert!(commitment.verify_opening(&value, &blinding));
+            }
+        }
+    }
+
+    #[test]
+    fn test_malformed_tables_are_reported_by_row() {
+        assert_eq!(
+            CsvReader::with_columns(TABLE.as_bytes(), &["height"]).err(),
+            Some(Error::UnknownColumn("height".to_string()))
+        );
+        assert_eq!(
+            CsvReader::new("a,\"b\nc".as_bytes()).err(),
+            Some(Error::MalformedHeader)
+        );
+
+        // Rows with a missing field or a value which isn't a number can't be parsed
+        let table = "a,b\n1,2\n3\n4,x\n";
+        let rows: Vec<_> = CsvReader::new(table.as_bytes()).unwrap().collect();
+        assert_eq!(
+            rows,
+            [
+                Ok(vec![1.0, 2.0]),
+                Err(Error::MalformedRow(1)),
+                Err(Error::MalformedRow(2))
+            ]
+        );
+
+        // A value which doesn't fit the format stops the stream
+        let format = FixedPoint::with_bits(8, 4).unwrap();
+        let reader = CsvReader::new("a\n1\n100\n2\n".as_bytes()).unwrap();
+        let mut stream =
+            CommitmentStream::<Ristretto, _, _>::new(reader, format, rand::thread_rng());
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(stream.next().unwrap(), Err(Error::RowOverflow(1)));
+        let reader = CsvReader::new("a\n1\n100\n".as_bytes()).unwrap();
+        let stream = CommitmentStream::<Ristretto, _, _>::new(reader, format, rand::thread_rng());
+        assert_eq!(
+            stream.write_to(&mut Vec::new(), &mut Vec::new()),
+            Err(Error::RowOverflow(1))
+        );
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(feature = "post-quantum")]
+mod archive_signature;
+mod commit_and_prove;
+mod commitments;
+mod data_mapping;
+mod digest;
+mod entropy;
+mod error;
+mod feature_schema;
+mod ingestion;
+mod point;
+mod profile;
+mod scalar;
+mod secret;
+mod security_level;
+mod self_check;
+mod sharing;
+
+pub use crate::{
+    commit_and_prove::CommitAndProve,
+    commitments::{Bls12G1, CommitmentGroup, PedersenCommitment, Ristretto},
+    data_mapping::{FixedPoint, QuantizedVector},
+    digest::{pack_bytes, Digest, HashBackend, PoseidonParameters, POSEIDON_WIDTH},
+    entropy::{ChallengeRecord, ChallengeSource, EntropyLedger, GrindingReport},
+    error::Error,
+    feature_schema::{
+        CommitmentLayout, FeatureEncoding, FeatureSchema, FeatureSpec, FeatureType,
+    },
+    ingestion::{CommitmentStream, CsvReader, RowCommitment},
+    point::{BatchNormalize, PointEncoding},
+    profile::Profile,
+    scalar::{Endianness, ScalarEncoding},
+    secret::SecretScalar,
+    security_level::{ProofScheme, SecurityLevel, SecurityPolicy},
+    self_check::self_check,
+    sharing::{evaluate_commitments, evaluate_polynomial, lagrange_coefficient, SharingGroup},
+};
+
+#[cfg(feature = "post-quantum")]
+pub use crate::archive_signature::{
+    ArchiveSignature, ArchiveSigningKey, ArchiveVerifyingKey, ARCHIVE_SIGNATURE_LEN,
+    ARCHIVE_VERIFYING_KEY_LEN,
+};
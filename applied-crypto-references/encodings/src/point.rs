@@ -0,0 +1,238 @@
+//! Canonical compressed encodings of Ristretto and BLS12-381 curve points
+
+use crate::{self_check::debug_self_check, Error};
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+
+// Curve points are encoded in compressed form, i.e. the x coordinate (or for Ristretto a field
+// element derived from the coordinates) plus enough bits to recover the rest of the point.
+// Decoding rejects byte strings which aren't the canonical encoding of a point in the prime order
+// group used by the proofs: out of range field elements, incorrectly set flag bits, points not on
+// the curve and, for BLS12-381, points outside of the prime order subgroup.
+//
+// Encoding a projective BLS12-381 point first normalizes it to affine coordinates, which takes a
+// field inversion. Inversions are far more expensive than multiplications, so encoding the
+// thousands of powers of a large reference string one at a time is dominated by them. Batches of
+// points are normalized together instead:
+//
+// 1. The running products of the z coordinates are accumulated and inverted once.
+// 2. Walking back through the batch, each point's inverse is peeled off the inverted product with
+// two multiplications.
+// 3. The result is exactly the per-point normalization, so batch encodings are byte for byte
+// equal to encoding each point in turn.
+//
+// The Ristretto library doesn't expose its field arithmetic, so Ristretto batches are compressed
+// point by point behind the same interface.
+
+/// Canonical compressed encoding and strict decoding of curve points
+pub trait PointEncoding: Sized {
+    /// Number of bytes in an encoded point
+    const ENCODED_LEN: usize;
+
+    /// Encode the point into its canonical compressed form
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decode a point from its canonical compressed form
+    fn decode(bytes: &[u8]) -> Result<Self, Error>;
+
+    /// Encode a batch of points as the concatenation of their canonical compressed forms
+    fn encode_batch(points: &[Self]) -> Vec<u8> {
+        points.iter().flat_map(|point| point.encode()).collect()
+    }
+}
+
+/// Conversion of projective points into affine coordinates sharing a single field inversion
+pub trait BatchNormalize: Sized {
+    /// Affine form of the point
+    type Affine;
+
+    /// Normalize a batch of points into affine coordinates, in the same order
+    fn normalize_batch(points: &[Self]) -> Vec<Self::Affine>;
+}
+
+impl BatchNormalize for G1Projective {
+    type Affine = G1Affine;
+
+    fn normalize_batch(points: &[Self]) -> Vec<G1Affine> {
+        let mut affine = vec![G1Affine::identity(); points.len()];
+        G1Projective::batch_normalize(points, &mut affine);
+        affine
+    }
+}
+
+impl BatchNormalize for G2Projective {
+    type Affine = G2Affine;
+
+    fn normalize_batch(points: &[Self]) -> Vec<G2Affine> {
+        let mut affine = vec![G2Affine::identity(); points.len()];
+        G2Projective::batch_normalize(points, &mut affine);
+        affine
+    }
+}
+
+// Check an encoded point has the expected length and copy it into an array
+fn to_array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], Error> {
+    debug_self_check();
+    bytes
+        .try_into()
+        .map_err(|_| Error::InvalidLength(N, bytes.len()))
+}
+
+impl PointEncoding for RistrettoPoint {
+    const ENCODED_LEN: usize = 32;
+
+    fn encode(&self) -> Vec<u8> {
+        self.compress().to_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        CompressedRistretto(to_array(bytes)?)
+            .decompress()
+            .ok_or(Error::NonCanonicalPoint)
+    }
+}
+
+impl PointEncoding for G1Affine {
+    const ENCODED_LEN: usize = 48;
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_compressed().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        Option::from(G1Affine::from_compressed(&to_array(bytes)?)).ok_or(Error::NonCanonicalPoint)
+    }
+}
+
+impl PointEncoding for G2Affine {
+    const ENCODED_LEN: usize = 96;
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_compressed().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        Option::from(G2Affine::from_compressed(&to_array(bytes)?)).ok_or(Error::NonCanonicalPoint)
+    }
+}
+
+impl PointEncoding for G1Projective {
+    const ENCODED_LEN: usize = G1Affine::ENCODED_LEN;
+
+    fn encode(&self) -> Vec<u8> {
+        G1Affine::from(self).encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        G1Affine::decode(bytes).map(G1Projective::from)
+    }
+
+    fn encode_batch(points: &[Self]) -> Vec<u8> {
+        G1Affine::encode_batch(&Self::normalize_batch(points))
+    }
+}
+
+impl PointEncoding for G2Projective {
+    const ENCODED_LEN: usize = G2Affine::ENCODED_LEN;
+
+    fn encode(&self) -> Vec<u8> {
+        G2Affine::from(self).encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        G2Affine::decode(bytes).map(G2Projective::from)
+    }
+
+    fn encode_batch(points: &[Self]) -> Vec<u8> {
+        G2Affine::encode_batch(&Self::normalize_batch(points))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::Scalar as BlsScalar;
+    use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, traits::Identity};
+    use ff::Field;
+
+    fn assert_round_trips<P: PointEncoding + PartialEq + std::fmt::Debug>(point: &P) {
+        let bytes = point.encode();
+        assert_eq!(bytes.len(), P::ENCODED_LEN);
+        assert_eq!(&P::decode(&bytes).unwrap(), point);
+    }
+
+    #[test]
+    fn test_points_round_trip() {
+        let mut rng = rand::thread_rng();
+        assert_round_trips(&RistrettoPoint::identity());
+        assert_round_trips(&RISTRETTO_BASEPOINT_POINT);
+        assert_round_trips(&G1Affine::identity());
+        assert_round_trips(&G2Affine::identity());
+        for _ in 0..32 {
+            assert_round_trips(&RistrettoPoint::random(&mut rng));
+            let scalar = <BlsScalar as Field>::random(&mut rng);
+            assert_round_trips(&(G1Projective::generator() * scalar));
+            assert_round_trips(&(G2Projective::generator() * scalar));
+            assert_round_trips(&G1Affine::from(G1Projective::generator() * scalar));
+        }
+    }
+
+    #[test]
+    fn test_batch_encodings_match_encoding_each_point() {
+        let mut rng = rand::thread_rng();
+        let scalars: Vec<BlsScalar> = (0..16).map(|_| BlsScalar::random(&mut rng)).collect();
+        let mut g1_points: Vec<G1Projective> = scalars
+            .iter()
+            .map(|scalar| G1Projective::generator() * scalar)
+            .collect();
+        g1_points.push(G1Projective::identity());
+        let g2_points: Vec<G2Projective> = scalars
+            .iter()
+            .map(|scalar| G2Projective::generator() * scalar)
+            .collect();
+        let ristretto_points: Vec<RistrettoPoint> =
+            (0..16).map(|_| RistrettoPoint::random(&mut rng)).collect();
+
+        let expected: Vec<u8> = g1_points.iter().flat_map(|point| point.encode()).collect();
+        assert_eq!(G1Projective::encode_batch(&g1_points), expected);
+        let expected: Vec<u8> = g2_points.iter().flat_map(|point| point.encode()).collect();
+        assert_eq!(G2Projective::encode_batch(&g2_points), expected);
+        let expected: Vec<u8> = ristretto_points
+            .iter()
+            .flat_map(|point| point.encode())
+            .collect();
+        assert_eq!(RistrettoPoint::encode_batch(&ristretto_points), expected);
+
+        // Normalization keeps the order and empty batches encode to nothing
+        let affine = G1Projective::normalize_batch(&g1_points);
+        assert!(affine
+            .iter()
+            .zip(&g1_points)
+            .all(|(affine, point)| *affine == G1Affine::from(point)));
+        assert!(G1Projective::encode_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_non_canonical_points_are_rejected() {
+        // Ristretto encodings must be a non-negative field element below 2^255 - 19
+        assert_eq!(
+            RistrettoPoint::decode(&[0xff; 32]),
+            Err(Error::NonCanonicalPoint)
+        );
+        let mut negative = [0u8; 32];
+        negative[0] = 1;
+        assert_eq!(
+            RistrettoPoint::decode(&negative),
+            Err(Error::NonCanonicalPoint)
+        );
+
+        // Clearing the compression flag of a BLS12-381 point makes its encoding invalid
+        let mut g1_bytes = G1Affine::generator().encode();
+        g1_bytes[0] &= 0x7f;
+        assert_eq!(G1Affine::decode(&g1_bytes), Err(Error::NonCanonicalPoint));
+        assert_eq!(
+            G2Affine::decode(&[0; 48]),
+            Err(Error::InvalidLength(96, 48))
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Named security profiles shared by the provers and verifiers of all proof crates
+
+// Several parameters of the proofs in this repository trade security for speed or proof size:
+//
+// * Challenge size: a sigma protocol with c bit challenges lets a cheating prover succeed with
+// probability 2^-c. Interactive verifiers can use short challenges because a cheater only gets one
+// online attempt, while Fiat-Shamir challenges must resist offline grinding.
+// * Repetitions: protocols such as data availability sampling only catch a cheater with some
+// constant probability per round and have to be repeated until the soundness error is small
+// enough.
+// * Validation: strict verifiers reject degenerate inputs such as identity points, which some
+// verification equations accept for any statement.
+//
+// Rather than choosing each parameter separately at every call site, callers pick a named profile
+// so the trade-off being made is explicit and can be audited in one place.
+
+/// Security profile controlling challenge sizes, repetitions and validation strictness
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Profile {
+    /// Live interactive verification where a cheating prover gets a single online attempt.
+    /// 64 bit challenges, 40 bits of statistical soundness and lenient validation.
+    Interactive,
+    /// Non-interactive proofs at the 128 bit security level with strict validation
+    #[default]
+    Standard128,
+    /// Full width challenges, 192 bits of statistical soundness and strict validation
+    Paranoid,
+}
+
+impl Profile {
+    /// Bit length of verifier challenges
+    pub fn challenge_bits(&self) -> usize {
+        match self {
+            Profile::Interactive => 64,
+            Profile::Standard128 => 128,
+            Profile::Paranoid => 252,
+        }
+    }
+
+    /// Negative log2 of the soundness error protocols must reach by repetition
+    pub fn soundness_bits(&self) -> usize {
+        match self {
+            Profile::Interactive => 40,
+            Profile::Standard128 => 128,
+            Profile::Paranoid => 192,
+        }
+    }
+
+    /// Number of times a protocol with a soundness error of `2^-bits_per_repetition` per run has
+    /// to be repeated to reach the profile's soundness
+    pub fn repetitions(&self, bits_per_repetition: usize) -> usize {
+        self.soundness_bits().div_ceil(bits_per_repetition.max(1))
+    }
+
+    /// Whether verifiers reject degenerate inputs such as identity points
+    pub fn strict_validation(&self) -> bool {
+        !matches!(self, Profile::Interactive)
+    }
+
+    /// Label binding the profile into transcripts so proofs can't be moved between profiles
+    pub fn label(&self) -> &'static [u8] {
+        match self {
+            Profile::Interactive => b"PROFILE_INTERACTIVE",
+            Profile::Standard128 => b"PROFILE_STANDARD_128",
+            Profile::Paranoid => b"PROFILE_PARANOID",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_are_ordered_by_strength() {
+        let profiles = [
+            Profile::Interactive,
+            Profile::Standard128,
+            Profile::Paranoid,
+        ];
+        for pair in profiles.windows(2) {
+            assert!(pair[0].challenge_bits() < pair[1].challenge_bits());
+            assert!(pair[0].soundness_bits() < pair[1].soundness_bits());
+            assert!(pair[0].repetitions(1) < pair[1].repetitions(1));
+        }
+        assert_eq!(Profile::Standard128.repetitions(3), 43);
+        assert!(!Profile::Interactive.strict_validation());
+        assert!(Profile::default().strict_validation());
+    }
+}
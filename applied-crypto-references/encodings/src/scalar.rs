@@ -0,0 +1,141 @@
+//! Canonical byte encodings of scalars in the Ristretto and BLS12-381 scalar fields
+
+use crate::{self_check::debug_self_check, Error};
+use bls12_381::Scalar as BlsScalar;
+use curve25519_dalek::scalar::Scalar as RistrettoScalar;
+
+// Scalars are integers modulo a prime group order `q`. Every scalar has exactly one canonical
+// encoding: the 32 byte representation of the unique integer in [0, q). Byte strings encoding an
+// integer >= q are rejected rather than silently reduced, otherwise two different byte strings
+// would decode to the same scalar and could be used to create distinct transcripts for the same
+// proof values.
+
+/// Byte order of an encoded scalar
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Endianness {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+}
+
+/// Canonical encoding and strict decoding of scalars
+pub trait ScalarEncoding: Sized {
+    /// Number of bytes in an encoded scalar
+    const ENCODED_LEN: usize;
+
+    /// Encode the scalar into bytes with the given byte order
+    fn encode(&self, endianness: Endianness) -> Vec<u8>;
+
+    /// Decode a scalar from bytes with the given byte order, rejecting non-reduced encodings
+    fn decode(bytes: &[u8], endianness: Endianness) -> Result<Self, Error>;
+}
+
+// Convert encoded bytes into a little endian byte array of the expected length
+fn to_le_array(bytes: &[u8], endianness: Endianness) -> Result<[u8; 32], Error> {
+    debug_self_check();
+    let mut buf: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| Error::InvalidLength(32, bytes.len()))?;
+    if endianness == Endianness::Big {
+        buf.reverse();
+    }
+    Ok(buf)
+}
+
+// Convert a little endian byte array into the requested byte order
+fn from_le_array(mut buf: [u8; 32], endianness: Endianness) -> Vec<u8> {
+    if endianness == Endianness::Big {
+        buf.reverse();
+    }
+    buf.to_vec()
+}
+
+impl ScalarEncoding for RistrettoScalar {
+    const ENCODED_LEN: usize = 32;
+
+    fn encode(&self, endianness: Endianness) -> Vec<u8> {
+        from_le_array(self.to_bytes(), endianness)
+    }
+
+    fn decode(bytes: &[u8], endianness: Endianness) -> Result<Self, Error> {
+        let buf = to_le_array(bytes, endianness)?;
+        Option::from(RistrettoScalar::from_canonical_bytes(buf)).ok_or(Error::NonCanonicalScalar)
+    }
+}
+
+impl ScalarEncoding for BlsScalar {
+    const ENCODED_LEN: usize = 32;
+
+    fn encode(&self, endianness: Endianness) -> Vec<u8> {
+        from_le_array(self.to_bytes(), endianness)
+    }
+
+    fn decode(bytes: &[u8], endianness: Endianness) -> Result<Self, Error> {
+        let buf = to_le_array(bytes, endianness)?;
+        Option::from(BlsScalar::from_bytes(&buf)).ok_or(Error::NonCanonicalScalar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+
+    // Round trip a scalar through both byte orders and check the encodings mirror each other
+    fn assert_round_trips<S: ScalarEncoding + PartialEq + std::fmt::Debug>(scalar: &S) {
+        let le_bytes = scalar.encode(Endianness::Little);
+        let mut be_bytes = scalar.encode(Endianness::Big);
+        assert_eq!(le_bytes.len(), S::ENCODED_LEN);
+        assert_eq!(&S::decode(&le_bytes, Endianness::Little).unwrap(), scalar);
+        assert_eq!(&S::decode(&be_bytes, Endianness::Big).unwrap(), scalar);
+        be_bytes.reverse();
+        assert_eq!(le_bytes, be_bytes);
+    }
+
+    #[test]
+    fn test_scalars_round_trip_in_both_byte_orders() {
+        let mut rng = rand::thread_rng();
+        let ristretto_edges = [
+            RistrettoScalar::ZERO,
+            RistrettoScalar::ONE,
+            -RistrettoScalar::ONE,
+        ];
+        let bls_edges = [BlsScalar::zero(), BlsScalar::one(), -BlsScalar::one()];
+        for scalar in ristretto_edges.iter() {
+            assert_round_trips(scalar);
+        }
+        for scalar in bls_edges.iter() {
+            assert_round_trips(scalar);
+        }
+        for _ in 0..256 {
+            assert_round_trips(&RistrettoScalar::random(&mut rng));
+            assert_round_trips(&<BlsScalar as Field>::random(&mut rng));
+        }
+    }
+
+    #[test]
+    fn test_non_reduced_scalars_are_rejected() {
+        // q - 1 plus one in the least significant byte encodes the group order q itself
+        let mut ristretto_order = (-RistrettoScalar::ONE).encode(Endianness::Little);
+        ristretto_order[0] += 1;
+        assert_eq!(
+            RistrettoScalar::decode(&ristretto_order, Endianness::Little),
+            Err(Error::NonCanonicalScalar)
+        );
+        let mut bls_order = (-BlsScalar::one()).encode(Endianness::Big);
+        bls_order[31] += 1;
+        assert_eq!(
+            BlsScalar::decode(&bls_order, Endianness::Big),
+            Err(Error::NonCanonicalScalar)
+        );
+        assert_eq!(
+            BlsScalar::decode(&[0xff; 32], Endianness::Little),
+            Err(Error::NonCanonicalScalar)
+        );
+        assert_eq!(
+            RistrettoScalar::decode(&[0; 31], Endianness::Little),
+            Err(Error::InvalidLength(32, 31))
+        );
+    }
+}
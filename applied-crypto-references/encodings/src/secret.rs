@@ -0,0 +1,86 @@
+//! Wrapper for secret scalars which erases them from memory once they are no longer used
+
+use core::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+// Private keys, proof nonces, blinding factors and trusted setup secrets are plain scalars which
+// are `Copy`, so every assignment leaves another copy of the secret in memory that outlives the
+// proof it was used for. Secrets are therefore held in a `SecretScalar`, which:
+//
+// 1. Overwrites the scalar with zeros when it is dropped, using volatile writes the compiler can't
+// optimize away.
+// 2. Is not `Copy`, so the secret is only duplicated by an explicit `clone`, which is erased on
+// drop as well.
+// 3. Never prints the scalar, so secrets don't end up in logs through `Debug`.
+//
+// Arithmetic on the secret still creates temporary copies in registers and on the stack, which
+// can't be erased without compiler support. Wrapping the secret keeps the long lived copies in
+// structs and local variables from surviving, which is what leaks through memory dumps and swap.
+
+/// Secret scalar which is overwritten with zeros when it is dropped
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct SecretScalar<S: Zeroize>(S);
+
+impl<S: Zeroize> SecretScalar<S> {
+    /// Take ownership of a secret scalar
+    pub fn new(scalar: S) -> Self {
+        Self(scalar)
+    }
+
+    /// Get the secret scalar to compute with
+    pub fn get_scalar(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S: Zeroize> From<S> for SecretScalar<S> {
+    fn from(scalar: S) -> Self {
+        Self::new(scalar)
+    }
+}
+
+impl<S: Zeroize> Zeroize for SecretScalar<S> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<S: Zeroize> Drop for SecretScalar<S> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<S: Zeroize> ZeroizeOnDrop for SecretScalar<S> {}
+
+impl<S: Zeroize> fmt::Debug for SecretScalar<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretScalar(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::Scalar as BlsScalar;
+    use curve25519_dalek::scalar::Scalar as RistrettoScalar;
+    use ff::Field;
+
+    #[test]
+    fn test_secret_scalars_of_both_curves_are_zeroized() {
+        let mut ristretto = SecretScalar::new(RistrettoScalar::from(42u64));
+        let mut bls = SecretScalar::new(BlsScalar::from(42u64));
+        assert_eq!(ristretto.get_scalar(), &RistrettoScalar::from(42u64));
+        ristretto.zeroize();
+        bls.zeroize();
+        assert_eq!(ristretto.get_scalar(), &RistrettoScalar::ZERO);
+        assert_eq!(bls.get_scalar(), &BlsScalar::zero());
+    }
+
+    #[test]
+    fn test_secret_scalars_are_not_printed() {
+        let secret = SecretScalar::new(BlsScalar::random(&mut rand::thread_rng()));
+        assert_eq!(format!("{:?}", secret), "SecretScalar(<redacted>)");
+        assert_eq!(format!("{:?}", secret.clone()), "SecretScalar(<redacted>)");
+    }
+}
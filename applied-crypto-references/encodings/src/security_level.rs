@@ -0,0 +1,125 @@
+//! Review status of the proof schemes in this repository and a policy refusing unsuitable ones
+
+use crate::Error;
+
+// Much of this repository exists to teach: some schemes are deliberately broken to show why a
+// later construction is needed, and others trust a setup generated by whoever calls them. These
+// look just as usable from the outside as the schemes built for real counterparties, so every
+// proof scheme states its level as an associated constant next to its definition:
+//
+// * Educational: demonstrates an idea and is unsound or trusts a party it shouldn't in practice
+// * Experimental: built to be sound but hasn't been independently reviewed
+// * Reviewed: independently reviewed for use with untrusted counterparties
+//
+// Callers pick schemes through a `SecurityPolicy`, which refuses educational schemes unless they
+// were explicitly allowed, so tutorial code can't end up in production by accident.
+
+/// Review status of a proof scheme, ordered from least to most trustworthy
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SecurityLevel {
+    /// Demonstrates an idea and must not be used with untrusted counterparties
+    Educational,
+    /// Designed to be sound but not independently reviewed
+    Experimental,
+    /// Independently reviewed for use with untrusted counterparties
+    Reviewed,
+}
+
+/// Proof scheme tagged with its security level
+pub trait ProofScheme {
+    /// Security level of the scheme
+    const SECURITY_LEVEL: SecurityLevel;
+}
+
+/// Policy deciding which security levels may be used
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SecurityPolicy {
+    minimum_level: SecurityLevel,
+    allow_educational: bool,
+}
+
+impl Default for SecurityPolicy {
+    fn default() -> Self {
+        Self {
+            minimum_level: SecurityLevel::Experimental,
+            allow_educational: false,
+        }
+    }
+}
+
+impl SecurityPolicy {
+    /// Require at least the given security level
+    pub fn require(mut self, minimum_level: SecurityLevel) -> Self {
+        self.minimum_level = minimum_level;
+        self
+    }
+
+    /// Allow educational schemes regardless of the minimum level, e.g. to run the tutorials
+    pub fn allow_educational(mut self, allow_educational: bool) -> Self {
+        self.allow_educational = allow_educational;
+        self
+    }
+
+    /// Check that a security level may be used
+    pub fn check(&self, level: SecurityLevel) -> Result<(), Error> {
+        let allowed = match level {
+            SecurityLevel::Educational => self.allow_educational,
+            _ => level >= self.minimum_level,
+        };
+        if !allowed {
+            return Err(Error::InsufficientSecurityLevel(level, self.minimum_level));
+        }
+        Ok(())
+    }
+
+    /// Check that a proof scheme may be used
+    pub fn check_scheme<S: ProofScheme>(&self) -> Result<(), Error> {
+        self.check(S::SECURITY_LEVEL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Tutorial;
+
+    impl ProofScheme for Tutorial {
+        const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Educational;
+    }
+
+    struct Prototype;
+
+    impl ProofScheme for Prototype {
+        const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+    }
+
+    #[test]
+    fn test_educational_schemes_are_refused_unless_allowed() {
+        let policy = SecurityPolicy::default();
+        assert_eq!(
+            policy.check_scheme::<Tutorial>(),
+            Err(Error::InsufficientSecurityLevel(
+                SecurityLevel::Educational,
+                SecurityLevel::Experimental
+            ))
+        );
+        assert!(policy.check_scheme::<Prototype>().is_ok());
+        assert!(policy
+            .allow_educational(true)
+            .check_scheme::<Tutorial>()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_minimum_levels_refuse_weaker_schemes() {
+        let policy = SecurityPolicy::default().require(SecurityLevel::Reviewed);
+        assert!(policy.check_scheme::<Prototype>().is_err());
+        assert!(policy.check(SecurityLevel::Reviewed).is_ok());
+
+        // Allowing educational schemes doesn't lower the bar for the others
+        let policy = policy.allow_educational(true);
+        assert!(policy.check_scheme::<Tutorial>().is_ok());
+        assert!(policy.check_scheme::<Prototype>().is_err());
+    }
+}
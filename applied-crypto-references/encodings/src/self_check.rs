@@ -0,0 +1,163 @@
+//! Runtime self-tests of the encodings and field constants on the running platform
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bls12_381::{G1Affine, Scalar as BlsScalar};
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar as RistrettoScalar};
+
+use crate::{Endianness, Error, PointEncoding, ScalarEncoding};
+
+// The proofs in this repository are meant to run on edge devices as well as servers, including
+// big endian and 32 bit targets and the ARM and RISC-V targets that cross compilers see far less
+// testing on. A miscompiled field or a byte order mixup on such a target wouldn't necessarily
+// crash: it could silently produce encodings that other platforms decode to different values, or
+// accept non-canonical ones.
+//
+// `self_check` compares the encodings on the running platform against known test vectors that
+// don't depend on the platform:
+//
+// 1. The platform's byte order matches the target the library was compiled for.
+// 2. Small scalars encode to the expected bytes in both byte orders and arithmetic wraps around at
+// the group order, i.e. -1 encodes as q - 1.
+// 3. The group orders themselves are rejected as non-canonical while q - 1 is accepted.
+// 4. The generators of the groups encode to their standard compressed forms and decode back.
+//
+// Debug builds run the checks once per process, the first time anything is decoded, and panic if
+// they fail. Release builds only run them when `self_check` is called.
+
+// Order of the Ristretto group, little endian
+const RISTRETTO_ORDER: &str = "edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010";
+
+// Order of the BLS12-381 scalar field, little endian
+const BLS_ORDER: &str = "01000000fffffffffe5bfeff02a4bd5305d8a10908d83933487d9d2953a7ed73";
+
+// Compressed Ristretto basepoint
+const RISTRETTO_BASEPOINT: &str =
+    "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76";
+
+// Compressed BLS12-381 G1 generator
+const BLS_G1_GENERATOR: &str = "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c\
+                                55e83ff97a1aeffb3af00adb22c6bb";
+
+// Whether the debug build self check has been started in this process
+static SELF_CHECK_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Check the encodings and field constants behave as specified on the running platform
+pub fn self_check() -> Result<(), Error> {
+    check(
+        u32::from_ne_bytes([1, 0, 0, 0]) == 1 && cfg!(target_endian = "little")
+            || u32::from_ne_bytes([0, 0, 0, 1]) == 1 && cfg!(target_endian = "big"),
+        "platform byte order doesn't match the compilation target",
+    )?;
+    check_scalars::<RistrettoScalar>(RISTRETTO_ORDER)?;
+    check_scalars::<BlsScalar>(BLS_ORDER)?;
+    check_generator(&RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT)?;
+    check_generator(&G1Affine::generator(), BLS_G1_GENERATOR)
+}
+
+// Run the self check the first time it is called in a debug build. Checks started by the self
+// check itself, or while another thread runs it, return immediately.
+pub(crate) fn debug_self_check() {
+    if cfg!(debug_assertions) && !SELF_CHECK_STARTED.swap(true, Ordering::SeqCst) {
+        if let Err(error) = self_check() {
+            panic!("encodings are broken on this platform: {error:?}");
+        }
+    }
+}
+
+// Fail with a description of the broken property unless it holds
+fn check(holds: bool, property: &'static str) -> Result<(), Error> {
+    if holds {
+        Ok(())
+    } else {
+        Err(Error::SelfCheckFailed(property))
+    }
+}
+
+// Check the encodings of a scalar field against its little endian group order
+fn check_scalars<S>(order: &str) -> Result<(), Error>
+where
+    S: ScalarEncoding + From<u64> + std::ops::Neg<Output = S> + PartialEq,
+{
+    let value = S::from(0x0102_0304_0506_0708);
+    let mut expected = [0; 32];
+    expected[..8].copy_from_slice(&[8, 7, 6, 5, 4, 3, 2, 1]);
+    check(
+        value.encode(Endianness::Little) == expected,
+        "scalars don't encode to little endian bytes",
+    )?;
+    expected.reverse();
+    check(
+        value.encode(Endianness::Big) == expected,
+        "scalars don't encode to big endian bytes",
+    )?;
+    check(
+        S::decode(&expected, Endianness::Big) == Ok(value),
+        "scalars don't decode from big endian bytes",
+    )?;
+
+    let order = from_hex(order);
+    let mut order_minus_one = order.clone();
+    order_minus_one[0] -= 1;
+    check(
+        (-S::from(1)).encode(Endianness::Little) == order_minus_one,
+        "scalar arithmetic doesn't wrap around at the group order",
+    )?;
+    check(
+        S::decode(&order, Endianness::Little) == Err(Error::NonCanonicalScalar),
+        "the group order decodes as a canonical scalar",
+    )?;
+    check(
+        S::decode(&order_minus_one, Endianness::Little) == Ok(-S::from(1)),
+        "the largest canonical scalar doesn't decode",
+    )
+}
+
+// Check a group generator encodes to its standard compressed form and decodes back
+fn check_generator<P: PointEncoding + PartialEq>(
+    generator: &P,
+    encoded: &str,
+) -> Result<(), Error> {
+    let encoded = from_hex(encoded);
+    check(
+        generator.encode() == encoded,
+        "generator doesn't encode to its standard form",
+    )?;
+    check(
+        P::decode(&encoded).as_ref() == Ok(generator),
+        "generator doesn't decode from its standard form",
+    )
+}
+
+// Decode the hex test vectors above
+fn from_hex(hex: &str) -> Vec<u8> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::ristretto::RistrettoPoint;
+
+    #[test]
+    fn test_self_check_passes_on_this_platform() {
+        assert_eq!(self_check(), Ok(()));
+        // Decoding triggers the debug build check, which must not recurse into itself
+        debug_self_check();
+        assert!(RistrettoPoint::decode(&from_hex(RISTRETTO_BASEPOINT)).is_ok());
+    }
+
+    #[test]
+    fn test_self_check_catches_wrong_constants() {
+        assert_eq!(
+            check_scalars::<RistrettoScalar>(BLS_ORDER),
+            Err(Error::SelfCheckFailed(
+                "scalar arithmetic doesn't wrap around at the group order"
+            ))
+        );
+        assert!(check_generator(&RISTRETTO_BASEPOINT_POINT, &BLS_G1_GENERATOR[..64]).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Polynomial evaluation and Lagrange interpolation shared by the secret sharing schemes
+
+use crate::{Bls12G1, CommitmentGroup, Ristretto};
+
+// Shamir sharing hands party i the evaluation f(i) of a random polynomial whose constant term is
+// the secret, and Feldman verification checks a share against commitments to the coefficients.
+// Both the key escrow of the sigma protocols and the generic secret sharing need the same three
+// pieces of arithmetic, which live here so there is a single implementation of each:
+//
+// 1. Evaluating f(x) = a_0 + a_1*x + .. + a_(t-1)*x^(t-1) at a share index with Horner's rule.
+// 2. Evaluating the same polynomial in the exponent, sum A_j*x^j for A_j = a_j*G, which gives the
+// public key of the share at x.
+// 3. The Lagrange coefficient l_i = prod_(j != i) x_j / (x_j - x_i) of a share when interpolating
+// f(0) from the shares at distinct indices x_j.
+
+/// Group whose scalars can be secret shared, with the field arithmetic interpolation needs
+pub trait SharingGroup: CommitmentGroup {
+    /// Multiply two scalars
+    fn mul_scalars(a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    /// Invert a scalar, or return `None` for zero
+    fn invert_scalar(scalar: &Self::Scalar) -> Option<Self::Scalar>;
+}
+
+impl SharingGroup for Ristretto {
+    fn mul_scalars(a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar {
+        a * b
+    }
+
+    fn invert_scalar(scalar: &Self::Scalar) -> Option<Self::Scalar> {
+        (*scalar != Self::Scalar::from(0u64)).then(|| scalar.invert())
+    }
+}
+
+impl SharingGroup for Bls12G1 {
+    fn mul_scalars(a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar {
+        a * b
+    }
+
+    fn invert_scalar(scalar: &Self::Scalar) -> Option<Self::Scalar> {
+        scalar.invert().into()
+    }
+}
+
+/// Evaluate the polynomial with the given coefficients, constant term first, at an index
+pub fn evaluate_polynomial<G: SharingGroup>(coefficients: &[G::Scalar], index: u64) -> G::Scalar {
+    let x = G::Scalar::from(index);
+    coefficients
+        .iter()
+        .rev()
+        .fold(G::Scalar::from(0), |acc, coefficient| {
+            G::mul_scalars(&acc, &x) + *coefficient
+        })
+}
+
+/// Evaluate the polynomial whose coefficients the commitments are to at an index in the exponent,
+/// giving the commitment to the share at that index
+pub fn evaluate_commitments<G: SharingGroup>(commitments: &[G::Point], index: u64) -> G::Point {
+    let x = G::Scalar::from(index);
+    let identity = G::value_generator() * G::Scalar::from(0);
+    commitments
+        .iter()
+        .rev()
+        .fold(identity, |acc, commitment| acc * x + *commitment)
+}
+
+/// Get the coefficient of the share at `index` when interpolating f(0) from the shares at
+/// `indices`, or `None` if the indices aren't distinct
+pub fn lagrange_coefficient<G: SharingGroup>(index: u64, indices: &[u64]) -> Option<G::Scalar> {
+    if (1..indices.len()).any(|i| indices[..i].contains(&indices[i])) {
+        return None;
+    }
+    let x_i = G::Scalar::from(index);
+    let (numerator, denominator) = indices.iter().filter(|other| **other != index).fold(
+        (G::Scalar::from(1), G::Scalar::from(1)),
+        |(num, den), other| {
+            let x_j = G::Scalar::from(*other);
+            (
+                G::mul_scalars(&num, &x_j),
+                G::mul_scalars(&den, &(x_j + -x_i)),
+            )
+        },
+    );
+    G::invert_scalar(&denominator).map(|inverse| G::mul_scalars(&numerator, &inverse))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Interpolate a polynomial from shares at `indices` and check its commitments evaluate to the
+    // commitments of the shares
+    fn check_interpolation<G: SharingGroup>(indices: &[u64]) {
+        let mut rng = rand::thread_rng();
+        let coefficients: Vec<G::Scalar> = (0..3).map(|_| G::random_scalar(&mut rng)).collect();
+        let commitments: Vec<G::Point> = coefficients
+            .iter()
+            .map(|coefficient| G::value_generator() * *coefficient)
+            .collect();
+
+        let secret = indices.iter().fold(G::Scalar::from(0), |acc, index| {
+            let share = evaluate_polynomial::<G>(&coefficients, *index);
+            assert_eq!(
+                evaluate_commitments::<G>(&commitments, *index),
+                G::value_generator() * share
+            );
+            let coefficient = lagrange_coefficient::<G>(*index, indices).unwrap();
+            acc + G::mul_scalars(&share, &coefficient)
+        });
+        assert_eq!(secret, coefficients[0]);
+        assert_eq!(evaluate_polynomial::<G>(&coefficients, 0), coefficients[0]);
+    }
+
+    #[test]
+    fn test_shares_of_both_groups_interpolate_to_the_constant_term() {
+        check_interpolation::<Ristretto>(&[1, 2, 3]);
+        check_interpolation::<Ristretto>(&[5, 2, 9, 4]);
+        check_interpolation::<Bls12G1>(&[1, 2, 3]);
+        check_interpolation::<Bls12G1>(&[7, 3, 1]);
+    }
+
+    #[test]
+    fn test_repeated_indices_have_no_lagrange_coefficients() {
+        assert_eq!(lagrange_coefficient::<Ristretto>(2, &[1, 2, 2]), None);
+        assert_eq!(lagrange_coefficient::<Bls12G1>(2, &[1, 2, 2]), None);
+        // A repeat of another index makes the coefficient of every share undefined
+        assert_eq!(lagrange_coefficient::<Ristretto>(1, &[1, 2, 2]), None);
+    }
+}
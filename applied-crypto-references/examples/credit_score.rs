@@ -0,0 +1,177 @@
+//! Private credit-score style inference exchange between a model owner, an Edge device and a
+//! counterparty, each running as its own thread
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use encodings::SecurityPolicy;
+use merlin::Transcript;
+use merlin_example::{
+    BucketSchema, FeatureVector, LinearInferenceGenerators, LinearInferenceProof, LinearModel,
+    LinearModelCommitment,
+};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+// The example wires the ZK-Edge parties together the way a deployment would, with messages over
+// channels standing in for the network:
+//
+// 1. The model owner commits to a linear scoring model. It publishes the commitment to the
+// counterparty and ships the model itself, with its blindings, to the Edge device.
+// 2. For every applicant the counterparty opens a session with a fresh nonce.
+// 3. The Edge device scores the applicant's features locally and proves that the committed model's
+// score on the committed features lies in the approved bucket [threshold, max) or the declined
+// bucket [0, threshold). The session nonce is bound into the transcript, so a proof for one
+// session can't be replayed into another.
+// 4. The counterparty verifies the proof against the owner's published commitment and learns only
+// whether the applicant clears the threshold, never the features, the weights or the score.
+
+// Label of the transcripts of the exchange
+const EXCHANGE_LABEL: &[u8] = b"ZK_EDGE_CREDIT_SCORE";
+
+// Score an applicant needs to be approved
+const THRESHOLD: u64 = 500;
+
+// Scores are reported on a 0 to 999 scale
+const MAX_SCORE: u64 = 1000;
+
+// Largest number of features the generators support
+const MAX_FEATURES: usize = 4;
+
+// Applicants as (name, income in thousands, debt to income percent, years of credit history, late
+// payments)
+const APPLICANTS: [(&str, i64, i64, i64, i64); 3] = [
+    ("alice", 85, 35, 12, 1),
+    ("bob", 40, 60, 2, 3),
+    ("carol", 120, 20, 20, 0),
+];
+
+// Message from the counterparty to the Edge device
+enum SessionRequest {
+    // Prove the score of an applicant within a session
+    Score { applicant: usize, nonce: [u8; 32] },
+    // No more applicants
+    Close,
+}
+
+// Message from the Edge device to the counterparty
+struct SessionResponse {
+    nonce: [u8; 32],
+    features_commitment: RistrettoPoint,
+    proof: LinearInferenceProof,
+}
+
+// Schema splitting scores into declined and approved buckets
+fn decision_schema() -> BucketSchema {
+    BucketSchema::new(1, &[0, THRESHOLD, MAX_SCORE]).expect("threshold is inside the scale")
+}
+
+// Transcript of a single session
+fn session_transcript(nonce: &[u8; 32]) -> Transcript {
+    let mut transcript = Transcript::new(EXCHANGE_LABEL);
+    transcript.append_message(b"SESSION_NONCE", nonce);
+    transcript
+}
+
+// Commit to the scoring model, publish the commitment and hand the model to the Edge device
+fn model_owner(to_device: Sender<LinearModel>, to_counterparty: Sender<LinearModelCommitment>) {
+    let generators = LinearInferenceGenerators::new(MAX_FEATURES);
+    // score = 400 + 2*income - 3*debt ratio + 10*history - 40*late payments
+    let model = LinearModel::new(&[2, -3, 10, -40], 400).expect("model has weights");
+    let commitment = model
+        .commitment(&generators)
+        .expect("model fits the generators");
+    println!("[model owner] published model commitment");
+    to_counterparty
+        .send(commitment)
+        .expect("counterparty is listening");
+    to_device.send(model).expect("device is listening");
+}
+
+// Score applicants on request and prove the decision bucket of each score
+fn edge_device(
+    from_owner: Receiver<LinearModel>,
+    requests: Receiver<SessionRequest>,
+    responses: Sender<SessionResponse>,
+) {
+    let generators = LinearInferenceGenerators::new(MAX_FEATURES);
+    let model = from_owner.recv().expect("model owner sends the model");
+    while let Ok(SessionRequest::Score { applicant, nonce }) = requests.recv() {
+        let (name, income, debt_ratio, history, late_payments) = APPLICANTS[applicant];
+        let features = FeatureVector::new(&[income, debt_ratio, history, late_payments]);
+        let proof = LinearInferenceProof::generate_proof(
+            &model,
+            &features,
+            &decision_schema(),
+            &generators,
+            &mut session_transcript(&nonce),
+        )
+        .expect("scores are on the scale");
+        println!("[edge device] proved the decision for {name} without sharing the score");
+        let response = SessionResponse {
+            nonce,
+            features_commitment: features.commitment(&generators).expect("features fit"),
+            proof,
+        };
+        responses.send(response).expect("counterparty is listening");
+    }
+}
+
+// Open a session per applicant and verify the decision the Edge device proves
+fn counterparty(
+    from_owner: Receiver<LinearModelCommitment>,
+    requests: Sender<SessionRequest>,
+    responses: Receiver<SessionResponse>,
+) -> Vec<(&'static str, bool)> {
+    let generators = LinearInferenceGenerators::new(MAX_FEATURES);
+    let model_commitment = from_owner
+        .recv()
+        .expect("model owner publishes the commitment");
+    let mut decisions = Vec::new();
+    for (applicant, (name, ..)) in APPLICANTS.iter().enumerate() {
+        let nonce: [u8; 32] = rand::random();
+        requests
+            .send(SessionRequest::Score { applicant, nonce })
+            .expect("device is listening");
+        let response = responses.recv().expect("device responds");
+        assert_eq!(response.nonce, nonce, "response belongs to another session");
+        let bucket = response
+            .proof
+            .verify_proof(
+                &response.features_commitment,
+                &model_commitment,
+                &decision_schema(),
+                &generators,
+                &mut session_transcript(&nonce),
+            )
+            .expect("proof verifies against the published model");
+        let approved = bucket == 1;
+        println!("[counterparty] {name}: score at least {THRESHOLD}? {approved}");
+        decisions.push((*name, approved));
+    }
+    requests
+        .send(SessionRequest::Close)
+        .expect("device is listening");
+    decisions
+}
+
+fn main() {
+    if let Err(error) = SecurityPolicy::default().check_scheme::<LinearInferenceProof>() {
+        eprintln!("Refusing to run the exchange: {error:?}");
+        std::process::exit(1);
+    }
+    let (model_to_device, device_model) = channel();
+    let (commitment_to_counterparty, counterparty_commitment) = channel();
+    let (request_sender, request_receiver) = channel();
+    let (response_sender, response_receiver) = channel();
+
+    let owner = thread::spawn(move || model_owner(model_to_device, commitment_to_counterparty));
+    let device =
+        thread::spawn(move || edge_device(device_model, request_receiver, response_sender));
+    let decisions = counterparty(counterparty_commitment, request_sender, response_receiver);
+    owner.join().expect("model owner finishes");
+    device.join().expect("edge device finishes");
+    assert_eq!(
+        decisions,
+        [("alice", true), ("bob", false), ("carol", true)],
+        "decisions match the applicants' scores"
+    );
+}
@@ -0,0 +1,223 @@
+//! Anomaly detection across a fleet of sensors streaming committed readings, with every batch
+//! proven within calibration range and flagged by a committed detector model
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::SecurityPolicy;
+use merlin::Transcript;
+use merlin_example::{
+    BucketSchema, FeatureVector, LinearInferenceGenerators, LinearInferenceProof, LinearModel,
+    LinearModelCommitment, OrderedInferenceProof,
+};
+use rand::Rng;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+
+// Every sensor streams batches of readings to the fleet verifier, which learns for each batch only
+// that the readings are valid and whether they are anomalous:
+//
+// 1. The fleet operator commits to a linear drift detector and installs it on every sensor.
+// 2. Each sensor commits to every reading of a batch separately and proves it lies in the
+// calibration range of the sensor, so a broken or tampered sensor can't feed the detector values
+// it could never measure.
+// 3. The sensor runs the detector on the batch and proves the bucket of its output, normal or
+// anomalous. The per-reading commitments use the generators of their position in the detector's
+// feature vector and their blindings sum to the vector's blinding, so the verifier checks that
+// they add up to the vector commitment the detector proof is about.
+// 4. Batches are bound to the sensor and their sequence number, and the verifier expects them in
+// order, so a batch can't be replayed or moved to another sensor.
+// 5. The verifier aggregates the verified flags into a fleet report.
+
+// Label of the transcripts of detector proofs
+const FLEET_LABEL: &[u8] = b"ZK_EDGE_SENSOR_FLEET";
+
+// Readings per batch, which are the features of the detector
+const BATCH_LEN: usize = 4;
+
+// Calibrated range of the sensors in tenths of a degree
+const CALIBRATION_RANGE: [u64; 2] = [0, 1000];
+
+// Rise of the second half of a batch over its first half at which it is anomalous
+const DRIFT_THRESHOLD: u64 = 100;
+
+// Detector outputs are offset so they can't go negative
+const DETECTOR_BIAS: i64 = 2000;
+
+// Number of sensors in the fleet and batches each of them sends
+const NUM_SENSORS: u64 = 3;
+const NUM_BATCHES: u64 = 4;
+
+// Sensor and batch of the simulated heating fault
+const FAULTY_SENSOR: u64 = 2;
+const FAULTY_BATCH: u64 = 2;
+
+// Batch of committed readings with its proofs
+struct SensorBatch {
+    sensor: u64,
+    sequence: u64,
+    calibration_proofs: Vec<OrderedInferenceProof>,
+    features_commitment: RistrettoPoint,
+    detector_proof: LinearInferenceProof,
+}
+
+// Schema with the calibration range as its only bucket
+fn calibration_schema() -> BucketSchema {
+    BucketSchema::new(1, &CALIBRATION_RANGE).expect("range is nonempty")
+}
+
+// Schema splitting detector outputs into normal and anomalous buckets
+fn detector_schema() -> BucketSchema {
+    let anomalous = (DETECTOR_BIAS as u64) + DRIFT_THRESHOLD;
+    BucketSchema::new(1, &[0, anomalous, 2 * DETECTOR_BIAS as u64]).expect("buckets are ordered")
+}
+
+// Transcript of the detector proof of a batch
+fn batch_transcript(sensor: u64, sequence: u64) -> Transcript {
+    let mut transcript = Transcript::new(FLEET_LABEL);
+    transcript.append_u64(b"SENSOR", sensor);
+    transcript.append_u64(b"SEQUENCE", sequence);
+    transcript
+}
+
+// Simulate readings around 20 degrees, with a heating fault in one batch of one sensor
+fn read_batch(sensor: u64, sequence: u64) -> [i64; BATCH_LEN] {
+    let mut rng = rand::thread_rng();
+    std::array::from_fn(|i| {
+        let fault = match (sensor, sequence) {
+            (FAULTY_SENSOR, FAULTY_BATCH) => 80 * i as i64,
+            _ => 0,
+        };
+        200 + 10 * sensor as i64 + rng.gen_range(-5..=5) + fault
+    })
+}
+
+// Stream proven batches of readings to the fleet verifier
+fn sensor(id: u64, detector: LinearModel, to_verifier: Sender<SensorBatch>) {
+    let generators = LinearInferenceGenerators::new(BATCH_LEN);
+    for sequence in 0..NUM_BATCHES {
+        let readings = read_batch(id, sequence);
+        let blindings: Vec<Scalar> = readings
+            .iter()
+            .map(|_| Scalar::random(&mut rand::rngs::OsRng))
+            .collect();
+        let calibration_proofs = readings
+            .iter()
+            .zip(&blindings)
+            .enumerate()
+            .map(|(index, (reading, blinding))| {
+                OrderedInferenceProof::generate_proof(
+                    *reading as u64,
+                    blinding,
+                    &calibration_schema(),
+                    &generators.feature_generators(index)?,
+                )
+            })
+            .collect::<Result<_, _>>()
+            .expect("readings are calibrated");
+        let features = FeatureVector::with_blinding(&readings, blindings.iter().sum());
+        let detector_proof = LinearInferenceProof::generate_proof(
+            &detector,
+            &features,
+            &detector_schema(),
+            &generators,
+            &mut batch_transcript(id, sequence),
+        )
+        .expect("detector outputs are on the scale");
+        let batch = SensorBatch {
+            sensor: id,
+            sequence,
+            calibration_proofs,
+            features_commitment: features.commitment(&generators).expect("features fit"),
+            detector_proof,
+        };
+        to_verifier.send(batch).expect("verifier is listening");
+    }
+}
+
+// Verify a batch, returning whether it is anomalous
+fn verify_batch(
+    batch: &SensorBatch,
+    detector: &LinearModelCommitment,
+    generators: &LinearInferenceGenerators,
+) -> bool {
+    let mut readings_commitment = RistrettoPoint::default();
+    for (index, proof) in batch.calibration_proofs.iter().enumerate() {
+        let reading_generators = generators
+            .feature_generators(index)
+            .expect("batches fit the generators");
+        proof
+            .verify_proof(&calibration_schema(), &reading_generators)
+            .expect("readings are within calibration range");
+        readings_commitment += proof.get_commitment();
+    }
+    assert_eq!(batch.calibration_proofs.len(), BATCH_LEN);
+    assert_eq!(
+        readings_commitment, batch.features_commitment,
+        "calibrated readings are the detector's features"
+    );
+    let bucket = batch
+        .detector_proof
+        .verify_proof(
+            &batch.features_commitment,
+            detector,
+            &detector_schema(),
+            generators,
+            &mut batch_transcript(batch.sensor, batch.sequence),
+        )
+        .expect("detector proof verifies against the committed detector");
+    bucket == 1
+}
+
+fn main() {
+    if let Err(error) = SecurityPolicy::default().check_scheme::<LinearInferenceProof>() {
+        eprintln!("Refusing to run the fleet: {error:?}");
+        std::process::exit(1);
+    }
+    let generators = LinearInferenceGenerators::new(BATCH_LEN);
+
+    // drift = (x_2 + x_3) - (x_0 + x_1), offset by the bias
+    let detector = LinearModel::new(&[-1, -1, 1, 1], DETECTOR_BIAS).expect("detector has weights");
+    let detector_commitment = detector
+        .commitment(&generators)
+        .expect("detector fits the generators");
+    println!("[operator] committed to the drift detector");
+
+    let (to_verifier, batches) = channel();
+    let sensors: Vec<_> = (0..NUM_SENSORS)
+        .map(|id| {
+            let (detector, to_verifier) = (detector.clone(), to_verifier.clone());
+            thread::spawn(move || sensor(id, detector, to_verifier))
+        })
+        .collect();
+    drop(to_verifier);
+
+    // Verify batches as they stream in, until every sensor is done
+    let mut next_sequence = [0; NUM_SENSORS as usize];
+    let mut anomalies = [0; NUM_SENSORS as usize];
+    for batch in batches {
+        let sensor = batch.sensor as usize;
+        assert_eq!(
+            batch.sequence, next_sequence[sensor],
+            "batches arrive in order"
+        );
+        next_sequence[sensor] += 1;
+        let anomalous = verify_batch(&batch, &detector_commitment, &generators);
+        anomalies[sensor] += anomalous as u64;
+        println!(
+            "[verifier] sensor {} batch {}: calibrated, anomalous: {anomalous}",
+            batch.sensor, batch.sequence
+        );
+    }
+    for sensor in sensors {
+        sensor.join().expect("sensor finishes");
+    }
+
+    // Aggregate the verified flags into the fleet report
+    let batches: u64 = next_sequence.iter().sum();
+    let total: u64 = anomalies.iter().sum();
+    println!("[verifier] fleet report: {total} anomalous batches out of {batches}");
+    for (sensor, count) in anomalies.iter().enumerate() {
+        println!("[verifier]   sensor {sensor}: {count} anomalous");
+    }
+    assert_eq!(batches, NUM_SENSORS * NUM_BATCHES);
+    assert_eq!(anomalies, [0, 0, 1], "only the heating fault is flagged");
+}
@@ -0,0 +1,71 @@
+//! Linear inference proof generation and verification on synthetic models of increasing width
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use merlin::Transcript;
+use merlin_example::{
+    BucketSchema, FeatureVector, LinearInferenceGenerators, LinearInferenceProof,
+    SyntheticGenerator,
+};
+
+// Numbers of features of the benchmarked models
+const NUM_FEATURES: [usize; 3] = [4, 16, 64];
+
+// Seed of the synthetic models and features, shared by every run
+const SEED: u64 = 2024;
+
+fn bench_linear_inference(c: &mut Criterion) {
+    let mut generator = SyntheticGenerator::new(SEED);
+    let schema = BucketSchema::new(1, &[0, 1 << 16, 1 << 32]).unwrap();
+    let mut group = c.benchmark_group("linear_inference");
+    group.sample_size(10);
+    for num_features in NUM_FEATURES {
+        let generators = LinearInferenceGenerators::new(num_features);
+        let model = generator.linear_model(num_features, 8).to_model().unwrap();
+        let features = FeatureVector::new(&generator.features(1, num_features, 8)[0]);
+        let features_commitment = features.commitment(&generators).unwrap();
+        let model_commitment = model.commitment(&generators).unwrap();
+        let proof = LinearInferenceProof::generate_proof(
+            &model,
+            &features,
+            &schema,
+            &generators,
+            &mut Transcript::new(b"bench"),
+        )
+        .unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("prove", num_features),
+            &num_features,
+            |b, _| {
+                b.iter(|| {
+                    LinearInferenceProof::generate_proof(
+                        &model,
+                        &features,
+                        &schema,
+                        &generators,
+                        &mut Transcript::new(b"bench"),
+                    )
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("verify", num_features),
+            &num_features,
+            |b, _| {
+                b.iter(|| {
+                    proof.verify_proof(
+                        &features_commitment,
+                        &model_commitment,
+                        &schema,
+                        &generators,
+                        &mut Transcript::new(b"bench"),
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_linear_inference);
+criterion_main!(benches);
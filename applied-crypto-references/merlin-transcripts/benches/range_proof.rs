@@ -0,0 +1,55 @@
+//! Range proof generation and verification over increasing bit widths
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use merlin_example::{PedersenGenerators, RangeProof};
+
+// Bit widths of the proven ranges [0, 2^bits)
+const BIT_WIDTHS: [usize; 4] = [8, 16, 32, 64];
+
+fn bench_range_proof(c: &mut Criterion) {
+    let generators = PedersenGenerators::default();
+    let mut group = c.benchmark_group("range_proof");
+    group.sample_size(10);
+    for bits in BIT_WIDTHS {
+        // Largest value of the range, so every bit of the decomposition is set
+        let value = u64::MAX >> (64 - bits);
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let commitment = generators.commit(&Scalar::from(value), &blinding);
+        let proof = RangeProof::generate_proof(
+            value,
+            &blinding,
+            bits,
+            &generators,
+            &mut Transcript::new(b"bench"),
+        )
+        .unwrap();
+
+        group.bench_with_input(BenchmarkId::new("prove", bits), &bits, |b, bits| {
+            b.iter(|| {
+                RangeProof::generate_proof(
+                    value,
+                    &blinding,
+                    *bits,
+                    &generators,
+                    &mut Transcript::new(b"bench"),
+                )
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("verify", bits), &bits, |b, bits| {
+            b.iter(|| {
+                proof.verify_proof(
+                    &commitment,
+                    *bits,
+                    &generators,
+                    &mut Transcript::new(b"bench"),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_range_proof);
+criterion_main!(benches);
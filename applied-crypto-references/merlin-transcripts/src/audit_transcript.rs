@@ -0,0 +1,400 @@
+//! Transcripts recording every call into a log that can be serialized, compared and replayed
+
+use std::fmt;
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{PointEncoding, Profile};
+use merlin::{Transcript, TranscriptRng};
+
+use crate::{
+    merlin_non_interactive_proof::{CHALLENGE_SCALAR_DOMAIN_SEP, PROOF_VALUE_DOMAIN_SEP},
+    ChallengeOracle, Error, SimpleProofProtocol,
+};
+
+// When a prover and a verifier absorb different bytes, or the same bytes under different domain
+// separators or in a different order, their challenges differ and a valid proof fails to verify
+// with nothing pointing at the cause. An audit transcript wraps a merlin transcript and records
+// every call made on it so the two sides can be compared:
+//
+// 1. Creating the transcript, every appended message and every challenge drawn is recorded as an
+// entry holding the operation, the domain separator, the byte length and the bytes themselves.
+// 2. The log is printed one entry per line, e.g. `append PROOF_VALUE 32 8c6a..`, so logs of the
+// prover and the verifier can be stored or diffed as text and parsed back.
+// 3. The first entry where two logs differ is the call where the transcripts diverged.
+// 4. Replaying a log runs its calls on a fresh transcript and recomputes every challenge, so a log
+// that doesn't reproduce its own challenges was edited or recorded from a different transcript.
+//
+// Merlin only accepts domain separators known at compile time, so replaying a parsed log needs the
+// domain separators of the protocol. Those used by `SimpleProofProtocol` are always known.
+//
+// Keying the prover's rng doesn't change the transcript, so it isn't recorded. Logs hold public
+// transcript messages only, the same values a verifier sees.
+
+// Domain separators every replay can resolve
+const KNOWN_LABELS: [&[u8]; 2] = [PROOF_VALUE_DOMAIN_SEP, CHALLENGE_SCALAR_DOMAIN_SEP];
+
+// Prefix of domain separators printed in hex because they aren't printable as text
+const HEX_LABEL_PREFIX: &str = "0x";
+
+/// Call made on an audited transcript
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AuditOperation {
+    /// Transcript created with the domain separator
+    Initialize,
+    /// Message appended under the domain separator
+    Append,
+    /// Challenge bytes drawn under the domain separator
+    Challenge,
+}
+
+impl AuditOperation {
+    // Name of the operation in the text form of a log
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Initialize => "init",
+            Self::Append => "append",
+            Self::Challenge => "challenge",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Self::Initialize, Self::Append, Self::Challenge]
+            .into_iter()
+            .find(|operation| operation.name() == name)
+    }
+}
+
+/// Entry of an audit log
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditEntry {
+    operation: AuditOperation,
+    label: Vec<u8>,
+    value: Vec<u8>,
+}
+
+impl AuditEntry {
+    /// Get the call recorded
+    pub fn get_operation(&self) -> AuditOperation {
+        self.operation
+    }
+
+    /// Get the domain separator of the call
+    pub fn get_label(&self) -> &[u8] {
+        &self.label
+    }
+
+    /// Get the bytes appended or the challenge bytes drawn
+    pub fn get_value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Get the number of bytes appended or drawn
+    pub fn len(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Check if no bytes were appended or drawn
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    // Parse an entry from its line in the text form of a log
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split(' ');
+        let operation = AuditOperation::from_name(fields.next()?)?;
+        let label = match fields.next()? {
+            label if label.starts_with(HEX_LABEL_PREFIX) => {
+                hex::decode(&label[HEX_LABEL_PREFIX.len()..]).ok()?
+            }
+            label => label.as_bytes().to_vec(),
+        };
+        let len: usize = fields.next()?.parse().ok()?;
+        let value = match fields.next() {
+            Some(value) => hex::decode(value).ok()?,
+            None => Vec::new(),
+        };
+        if value.len() != len || fields.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            operation,
+            label,
+            value,
+        })
+    }
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.operation.name())?;
+        let printable = !self.label.is_empty()
+            && self.label.iter().all(u8::is_ascii_graphic)
+            && !self.label.starts_with(HEX_LABEL_PREFIX.as_bytes());
+        if printable {
+            write!(f, "{}", String::from_utf8_lossy(&self.label))?;
+        } else {
+            write!(f, "{}{}", HEX_LABEL_PREFIX, hex::encode(&self.label))?;
+        }
+        write!(f, " {}", self.value.len())?;
+        if !self.value.is_empty() {
+            write!(f, " {}", hex::encode(&self.value))?;
+        }
+        Ok(())
+    }
+}
+
+/// Log of every call made on an audited transcript, in the order they were made
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+}
+
+impl AuditLog {
+    /// Get the entries of the log
+    pub fn get_entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Parse a log from its text form, one entry per line
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let entries = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(index, line)| AuditEntry::parse(line).ok_or(Error::MalformedAuditLog(index)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Find the index of the first entry where two logs differ, e.g. the logs of a prover and a
+    /// verifier, or None if they are identical
+    pub fn first_divergence(&self, other: &Self) -> Option<usize> {
+        let common = self.entries.len().min(other.entries.len());
+        (0..common)
+            .find(|index| self.entries[*index] != other.entries[*index])
+            .or((self.entries.len() != other.entries.len()).then_some(common))
+    }
+
+    /// Replay the log into a fresh audited transcript, resolving domain separators from `labels`
+    /// and the ones of [`SimpleProofProtocol`], and rejecting recorded challenges the transcript
+    /// doesn't reproduce
+    pub fn replay(&self, labels: &[&'static [u8]]) -> Result<AuditTranscript, Error> {
+        let resolve = |entry: &AuditEntry| {
+            labels
+                .iter()
+                .chain(KNOWN_LABELS.iter())
+                .find(|label| **label == entry.label.as_slice())
+                .copied()
+                .ok_or_else(|| {
+                    Error::UnknownAuditLabel(String::from_utf8_lossy(&entry.label).into_owned())
+                })
+        };
+        let mut entries = self.entries.iter().enumerate();
+        let mut transcript = match entries.next() {
+            Some((_, entry)) if entry.operation == AuditOperation::Initialize => {
+                AuditTranscript::new(resolve(entry)?)
+            }
+            _ => return Err(Error::AuditMismatch(0)),
+        };
+        for (index, entry) in entries {
+            let label = resolve(entry)?;
+            match entry.operation {
+                AuditOperation::Initialize => return Err(Error::AuditMismatch(index)),
+                AuditOperation::Append => transcript.append_message(label, &entry.value),
+                AuditOperation::Challenge => {
+                    let mut challenge = vec![0; entry.value.len()];
+                    transcript.challenge_bytes(label, &mut challenge);
+                    if challenge != entry.value {
+                        return Err(Error::AuditMismatch(index));
+                    }
+                }
+            }
+        }
+        Ok(transcript)
+    }
+}
+
+impl fmt::Display for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Merlin transcript recording every call made on it into an [`AuditLog`]
+#[derive(Clone)]
+pub struct AuditTranscript {
+    transcript: Transcript,
+    log: AuditLog,
+}
+
+impl AuditTranscript {
+    /// Create a transcript separated from other protocols by `label`
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut log = AuditLog::default();
+        log.entries.push(AuditEntry {
+            operation: AuditOperation::Initialize,
+            label: label.to_vec(),
+            value: Vec::new(),
+        });
+        Self {
+            transcript: Transcript::new(label),
+            log,
+        }
+    }
+
+    /// Append a message under a domain separator
+    pub fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.transcript.append_message(label, message);
+        self.record(AuditOperation::Append, label, message.to_vec());
+    }
+
+    /// Append a u64 under a domain separator, recorded as its little endian bytes like merlin
+    /// absorbs it
+    pub fn append_u64(&mut self, label: &'static [u8], value: u64) {
+        self.append_message(label, &value.to_le_bytes());
+    }
+
+    /// Fill `dest` with challenge bytes drawn under a domain separator
+    pub fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.transcript.challenge_bytes(label, dest);
+        self.record(AuditOperation::Challenge, label, dest.to_vec());
+    }
+
+    /// Get the log of the calls made so far
+    pub fn get_log(&self) -> &AuditLog {
+        &self.log
+    }
+
+    /// Unwrap the transcript and its log
+    pub fn into_parts(self) -> (Transcript, AuditLog) {
+        (self.transcript, self.log)
+    }
+
+    fn record(&mut self, operation: AuditOperation, label: &[u8], value: Vec<u8>) {
+        self.log.entries.push(AuditEntry {
+            operation,
+            label: label.to_vec(),
+            value,
+        });
+    }
+}
+
+/// Audited transcripts absorb proof values and derive challenges exactly like a merlin transcript
+impl SimpleProofProtocol for AuditTranscript {
+    fn append_proof_value(&mut self, curve_point: &RistrettoPoint) {
+        self.append_message(PROOF_VALUE_DOMAIN_SEP, &curve_point.encode());
+    }
+
+    fn get_challenge(&mut self) -> Scalar {
+        let mut buf = [0; 64];
+        self.challenge_bytes(CHALLENGE_SCALAR_DOMAIN_SEP, &mut buf);
+        Scalar::from_bytes_mod_order_wide(&buf)
+    }
+
+    fn get_rng(&mut self, public_key: &RistrettoPoint) -> TranscriptRng {
+        self.transcript.get_rng(public_key)
+    }
+}
+
+/// Fiat-Shamir oracle recording the messages and challenges of any protocol run with it
+impl ChallengeOracle for AuditTranscript {
+    type Rng = TranscriptRng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.append_proof_value(curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        self.get_challenge()
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        self.get_rng(statement)
+    }
+
+    fn profile(&self) -> Profile {
+        Profile::Paranoid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_keypair, SimpleSchnorrProof};
+
+    const TEST_DOMAIN_SEP: &[u8] = b"AUDIT_TEST";
+
+    #[test]
+    fn test_audit_logs_round_trip_and_replay() {
+        let (private_key, public_key) = generate_keypair();
+        let mut prover_transcript = AuditTranscript::new(TEST_DOMAIN_SEP);
+        prover_transcript.append_u64(b"ROUNDS", 1);
+        let proof = SimpleSchnorrProof::generate_proof(&private_key, &mut prover_transcript);
+        let log = prover_transcript.get_log().clone();
+        let operations: Vec<AuditOperation> = log
+            .get_entries()
+            .iter()
+            .map(|e| e.get_operation())
+            .collect();
+        assert_eq!(
+            operations,
+            [
+                AuditOperation::Initialize,
+                AuditOperation::Append,
+                AuditOperation::Append,
+                AuditOperation::Challenge
+            ]
+        );
+        assert_eq!(log.get_entries()[2].len(), 32);
+        assert_eq!(log.get_entries()[3].len(), 64);
+
+        // The text form parses back to the same log, which replays to the same transcript state
+        let text = log.to_string();
+        assert!(text.starts_with("init AUDIT_TEST 0\nappend ROUNDS 8 0100000000000000\n"));
+        let parsed = AuditLog::parse(&text).unwrap();
+        assert_eq!(parsed, log);
+        let mut replayed = parsed.replay(&[TEST_DOMAIN_SEP, b"ROUNDS"]).unwrap();
+        assert_eq!(replayed.get_challenge(), prover_transcript.get_challenge());
+        assert_eq!(
+            parsed.replay(&[TEST_DOMAIN_SEP]).err(),
+            Some(Error::UnknownAuditLabel("ROUNDS".to_string()))
+        );
+
+        // A verifier that skips the negotiated parameter diverges at the first append
+        let mut verifier_transcript = AuditTranscript::new(TEST_DOMAIN_SEP);
+        let mut verifier_proof = SimpleSchnorrProof::from(proof.get_proof_pair());
+        assert!(verifier_proof
+            .verify_proof(&public_key, &mut verifier_transcript)
+            .is_err());
+        assert_eq!(log.first_divergence(verifier_transcript.get_log()), Some(1));
+        assert_eq!(log.first_divergence(&parsed), None);
+    }
+
+    #[test]
+    fn test_edited_audit_logs_are_rejected() {
+        let mut transcript = AuditTranscript::new(TEST_DOMAIN_SEP);
+        transcript.append_message(b"\x00 binary label", b"message");
+        let mut challenge = [0; 16];
+        transcript.challenge_bytes(b"CHALLENGE", &mut challenge);
+        let text = transcript.get_log().to_string();
+        let labels: [&'static [u8]; 3] = [TEST_DOMAIN_SEP, b"\x00 binary label", b"CHALLENGE"];
+        assert!(AuditLog::parse(&text).unwrap().replay(&labels).is_ok());
+
+        // Changing an appended message changes the challenge drawn after it
+        let edited = text.replace(&hex::encode(b"message"), &hex::encode(b"massage"));
+        assert_eq!(
+            AuditLog::parse(&edited).unwrap().replay(&labels).err(),
+            Some(Error::AuditMismatch(2))
+        );
+
+        // Lengths must match the recorded bytes
+        let truncated = text.replace(" 7 ", " 6 ");
+        assert_eq!(
+            AuditLog::parse(&truncated),
+            Err(Error::MalformedAuditLog(1))
+        );
+    }
+}
@@ -0,0 +1,566 @@
+//! Sources of verifier challenges allowing tests to replace Fiat-Shamir challenges with chosen ones
+
+use std::collections::VecDeque;
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::IsIdentity};
+use encodings::{ChallengeSource, EntropyLedger, Profile};
+use merlin::{Transcript, TranscriptRng};
+use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+
+use crate::{Error, SimpleProofProtocol};
+
+// In an interactive sigma protocol the verifier picks a random challenge after seeing the
+// prover's first message. Fiat-Shamir replaces the verifier with a hash of the transcript, which
+// is what makes the proofs in this crate non-interactive. For studying a protocol however, it is
+// useful to put the verifier back in control of the challenges:
+//
+// * Special soundness says that two accepting transcripts with the same first message and
+// different challenges reveal the witness. Demonstrating this requires running the prover twice
+// with the same randomness while choosing the challenges.
+// * Honest verifier zero knowledge says that anyone who knows the challenge in advance can
+// produce an accepting transcript without the witness. Demonstrating this requires fixing the
+// challenge before the first message is computed.
+//
+// Proofs which take a `ChallengeOracle` work the same way with either source of challenges. A
+// merlin `Transcript` is the Fiat-Shamir oracle used in practice, while a `SimulatedOracle` hands
+// out scripted challenges and derives everything else deterministically from a seed so that a
+// prover can be "rewound" by running it again with an oracle created from the same seed.
+//
+// Every oracle also reports the security profile it runs under, which verifiers consult for how
+// strictly to validate the points they are given. A bare `Transcript` hands out full width
+// challenges and validates strictly like the paranoid profile, while a `ProfiledTranscript`
+// shortens its challenges to the profile's challenge size and binds the profile into the
+// transcript.
+//
+// Wrapping any oracle in an `AccountedOracle` records the entropy of every challenge it hands out
+// in an `EntropyLedger`, which estimates how well the protocols of a proof resist a prover
+// grinding Fiat-Shamir challenges and flags the ones falling short of the profile's soundness.
+// Wrapping it in a `TracedOracle` records the prover messages and challenges themselves, which
+// makes the transcript of a proof visible for teaching and debugging.
+
+// Domain separator for initializing simulated oracle transcripts
+const SIMULATED_ORACLE_DOMAIN_SEP: &[u8] = b"SIMULATED_CHALLENGE_ORACLE";
+
+// Domain separator for absorbing the caller's seed
+const SEED_DOMAIN_SEP: &[u8] = b"SEED";
+
+// Domain separator for deriving the seed of the simulated prover's rng
+const PROVER_RNG_DOMAIN_SEP: &[u8] = b"SIMULATED_PROVER_RNG";
+
+// Domain separator for binding the security profile into a transcript
+const PROFILE_DOMAIN_SEP: &[u8] = b"SECURITY_PROFILE";
+
+// Domain separator for squeezing shortened challenges out of a profiled transcript
+const PROFILED_CHALLENGE_DOMAIN_SEP: &[u8] = b"PROFILED_CHALLENGE";
+
+/// Source of the verifier challenges and prover randomness used by a sigma protocol
+pub trait ChallengeOracle {
+    /// Rng the prover draws its random scalars from
+    type Rng: RngCore + CryptoRng;
+
+    /// Absorb a prover message which the next challenge may depend on
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint);
+
+    /// Get the next verifier challenge
+    fn next_challenge(&mut self) -> Scalar;
+
+    /// Get an rng for the prover's random scalars bound to the statement being proven
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng;
+
+    /// Get the security profile the oracle runs under
+    fn profile(&self) -> Profile;
+
+    /// Bit length of the challenges the oracle hands out
+    fn challenge_bits(&self) -> usize {
+        self.profile().challenge_bits()
+    }
+
+    /// How the oracle derives its challenges
+    fn challenge_source(&self) -> ChallengeSource {
+        ChallengeSource::FiatShamir
+    }
+}
+
+// Reject identity points when the oracle's profile validates strictly. Proofs about the identity
+// are satisfied by the zero witness, so they prove nothing about a secret.
+pub(crate) fn validate_points<O: ChallengeOracle>(
+    oracle: &O,
+    points: &[&RistrettoPoint],
+) -> Result<(), Error> {
+    if oracle.profile().strict_validation() && points.iter().any(|point| point.is_identity()) {
+        return Err(Error::IdentityPoint);
+    }
+    Ok(())
+}
+
+/// Fiat-Shamir oracle deriving challenges from everything absorbed into the transcript
+impl ChallengeOracle for Transcript {
+    type Rng = TranscriptRng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.append_proof_value(curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        self.get_challenge()
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        self.get_rng(statement)
+    }
+
+    fn profile(&self) -> Profile {
+        Profile::Paranoid
+    }
+}
+
+/// Fiat-Shamir oracle producing challenges of the size chosen by a security profile
+#[derive(Clone)]
+pub struct ProfiledTranscript {
+    transcript: Transcript,
+    profile: Profile,
+}
+
+impl ProfiledTranscript {
+    /// Create a transcript for a protocol bound to a security profile
+    pub fn new(label: &'static [u8], profile: Profile) -> Self {
+        let mut transcript = Transcript::new(label);
+        transcript.append_message(PROFILE_DOMAIN_SEP, profile.label());
+        Self {
+            transcript,
+            profile,
+        }
+    }
+}
+
+impl ChallengeOracle for ProfiledTranscript {
+    type Rng = TranscriptRng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.transcript.append_proof_value(curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        let challenge_bits = self.profile.challenge_bits();
+        if challenge_bits > 252 {
+            return self.transcript.get_challenge();
+        }
+        // Challenges of at most 252 bits are below the group order, so they are taken as integers
+        // directly, without reduction. The bytes are masked down to exactly the profile's bits.
+        let challenge_bytes = challenge_bits.div_ceil(8);
+        let mut buf = [0; 32];
+        self.transcript
+            .challenge_bytes(PROFILED_CHALLENGE_DOMAIN_SEP, &mut buf[..challenge_bytes]);
+        if !challenge_bits.is_multiple_of(8) {
+            buf[challenge_bytes - 1] &= (1 << (challenge_bits % 8)) - 1;
+        }
+        Scalar::from_bytes_mod_order(buf)
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        self.transcript.get_rng(statement)
+    }
+
+    fn profile(&self) -> Profile {
+        self.profile
+    }
+}
+
+/// Oracle simulating an interactive verifier for tests. Scripted challenges are handed out first
+/// and further challenges and all prover randomness are derived deterministically from a seed,
+/// independently of the prover's messages.
+#[derive(Clone)]
+pub struct SimulatedOracle {
+    // Transcript seeded by the caller that unscripted challenges and prover rngs are derived from
+    seed_transcript: Transcript,
+    // Challenges handed out in order before falling back to the seed transcript
+    scripted_challenges: VecDeque<Scalar>,
+    // Prover messages absorbed so far
+    prover_messages: Vec<RistrettoPoint>,
+}
+
+impl SimulatedOracle {
+    /// Create an oracle deriving challenges and prover randomness from a seed
+    pub fn new(seed: &[u8]) -> Self {
+        let mut seed_transcript = Transcript::new(SIMULATED_ORACLE_DOMAIN_SEP);
+        seed_transcript.append_message(SEED_DOMAIN_SEP, seed);
+        Self {
+            seed_transcript,
+            scripted_challenges: VecDeque::new(),
+            prover_messages: Vec::new(),
+        }
+    }
+
+    /// Create an oracle which hands out the given challenges in order before deriving further
+    /// challenges from the seed
+    pub fn with_challenges(seed: &[u8], challenges: &[Scalar]) -> Self {
+        let mut oracle = Self::new(seed);
+        oracle.scripted_challenges.extend(challenges);
+        oracle
+    }
+
+    /// Queue a challenge to be handed out after any previously scripted challenges
+    pub fn push_challenge(&mut self, challenge: Scalar) {
+        self.scripted_challenges.push_back(challenge);
+    }
+
+    /// Get the prover messages absorbed so far
+    pub fn get_prover_messages(&self) -> &[RistrettoPoint] {
+        &self.prover_messages
+    }
+}
+
+impl ChallengeOracle for SimulatedOracle {
+    type Rng = StdRng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.prover_messages.push(*curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        match self.scripted_challenges.pop_front() {
+            Some(challenge) => challenge,
+            None => self.seed_transcript.get_challenge(),
+        }
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        // Deliberately not bound to anything but the seed and the statement so a prover run twice
+        // with oracles from the same seed draws the same random scalars
+        let mut rng_transcript = self.seed_transcript.clone();
+        rng_transcript.append_proof_value(statement);
+        let mut seed = [0; 32];
+        rng_transcript.challenge_bytes(PROVER_RNG_DOMAIN_SEP, &mut seed);
+        StdRng::from_seed(seed)
+    }
+
+    fn profile(&self) -> Profile {
+        // Simulated oracles stand in for a live verifier and must accept the degenerate
+        // transcripts they are used to construct
+        Profile::Interactive
+    }
+
+    fn challenge_source(&self) -> ChallengeSource {
+        ChallengeSource::Interactive
+    }
+}
+
+/// Oracle recording the entropy of the challenges another oracle hands out, attributed to the
+/// protocol currently being run
+#[derive(Clone)]
+pub struct AccountedOracle<O> {
+    oracle: O,
+    // Label challenges are currently recorded under
+    protocol: &'static str,
+    ledger: EntropyLedger,
+}
+
+impl<O: ChallengeOracle> AccountedOracle<O> {
+    /// Wrap an oracle, recording its challenges under `protocol`
+    pub fn new(oracle: O, protocol: &'static str) -> Self {
+        let ledger = EntropyLedger::new(oracle.challenge_source(), oracle.profile());
+        Self {
+            oracle,
+            protocol,
+            ledger,
+        }
+    }
+
+    /// Record further challenges under another protocol of the same proof
+    pub fn set_protocol(&mut self, protocol: &'static str) {
+        self.protocol = protocol;
+    }
+
+    /// Get the challenges recorded so far
+    pub fn get_ledger(&self) -> &EntropyLedger {
+        &self.ledger
+    }
+
+    /// Unwrap the oracle and the challenges recorded
+    pub fn into_parts(self) -> (O, EntropyLedger) {
+        (self.oracle, self.ledger)
+    }
+}
+
+impl<O: ChallengeOracle> ChallengeOracle for AccountedOracle<O> {
+    type Rng = O::Rng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.oracle.absorb_prover_message(curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        self.ledger
+            .record_challenge(self.protocol, self.oracle.challenge_bits() as f64);
+        self.oracle.next_challenge()
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        self.oracle.prover_rng(statement)
+    }
+
+    fn profile(&self) -> Profile {
+        self.oracle.profile()
+    }
+
+    fn challenge_bits(&self) -> usize {
+        self.oracle.challenge_bits()
+    }
+
+    fn challenge_source(&self) -> ChallengeSource {
+        self.oracle.challenge_source()
+    }
+}
+
+/// Message exchanged through an oracle
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TranscriptEvent {
+    /// Prover message absorbed by the oracle
+    ProverMessage(RistrettoPoint),
+    /// Challenge handed out by the oracle
+    Challenge(Scalar),
+}
+
+/// Oracle recording every prover message and challenge passing through another oracle, e.g. to
+/// display the transcript of a proof
+#[derive(Clone)]
+pub struct TracedOracle<O> {
+    oracle: O,
+    events: Vec<TranscriptEvent>,
+}
+
+impl<O: ChallengeOracle> TracedOracle<O> {
+    /// Wrap an oracle, recording the messages passing through it
+    pub fn new(oracle: O) -> Self {
+        Self {
+            oracle,
+            events: Vec::new(),
+        }
+    }
+
+    /// Get the messages recorded so far in the order they passed through the oracle
+    pub fn get_events(&self) -> &[TranscriptEvent] {
+        &self.events
+    }
+
+    /// Unwrap the oracle and the messages recorded
+    pub fn into_parts(self) -> (O, Vec<TranscriptEvent>) {
+        (self.oracle, self.events)
+    }
+}
+
+impl<O: ChallengeOracle> ChallengeOracle for TracedOracle<O> {
+    type Rng = O::Rng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.events
+            .push(TranscriptEvent::ProverMessage(*curve_point));
+        self.oracle.absorb_prover_message(curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        let challenge = self.oracle.next_challenge();
+        self.events.push(TranscriptEvent::Challenge(challenge));
+        challenge
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        self.oracle.prover_rng(statement)
+    }
+
+    fn profile(&self) -> Profile {
+        self.oracle.profile()
+    }
+
+    fn challenge_bits(&self) -> usize {
+        self.oracle.challenge_bits()
+    }
+
+    fn challenge_source(&self) -> ChallengeSource {
+        self.oracle.challenge_source()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_keypair, SimpleSchnorrProof};
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    #[test]
+    fn test_scripted_challenges_replace_fiat_shamir() {
+        let (private_key, public_key) = generate_keypair();
+        let challenge = Scalar::from(42u64);
+
+        // A proof made against a scripted challenge verifies against the same challenge
+        let mut oracle = SimulatedOracle::with_challenges(b"test", &[challenge]);
+        let proof = SimpleSchnorrProof::generate_proof(&private_key, &mut oracle);
+        assert_eq!(oracle.get_prover_messages(), &[proof.get_proof_pair().1]);
+        let mut verifier_proof = SimpleSchnorrProof::from(proof.get_proof_pair());
+        assert!(verifier_proof
+            .verify_proof(
+                &public_key,
+                &mut SimulatedOracle::with_challenges(b"test", &[challenge])
+            )
+            .is_ok());
+
+        // It doesn't verify once Fiat-Shamir derives the challenge from the transcript instead
+        assert!(verifier_proof
+            .verify_proof(
+                &public_key,
+                &mut SimpleSchnorrProof::create_new_transcript()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_known_challenge_allows_simulating_proofs_without_the_witness() {
+        // Knowing the challenge before committing lets anyone pick the response first and solve
+        // for the public scalar A = r*G - c*K, which is why Fiat-Shamir must hash A
+        let (_, public_key) = generate_keypair();
+        let mut oracle = SimulatedOracle::new(b"test");
+        let challenge = oracle.clone().next_challenge();
+        let response = Scalar::from(7u64);
+        let public_scalar = response * RISTRETTO_BASEPOINT_POINT - challenge * public_key;
+
+        let mut simulated_proof = SimpleSchnorrProof::from((response, public_scalar));
+        assert!(simulated_proof
+            .verify_proof(&public_key, &mut oracle)
+            .is_ok());
+    }
+
+    // Number of bits up to and including the most significant set bit of a scalar
+    fn bit_length(scalar: &Scalar) -> usize {
+        let bytes = scalar.to_bytes();
+        bytes
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |i| 8 * i + 8 - bytes[i].leading_zeros() as usize)
+    }
+
+    #[test]
+    fn test_profiled_transcripts_shorten_challenges_and_validate_strictly() {
+        let (private_key, public_key) = generate_keypair();
+        for profile in [
+            Profile::Interactive,
+            Profile::Standard128,
+            Profile::Paranoid,
+        ] {
+            // Every challenge fits the profile's bits and the longest of a few uses all of them
+            let mut transcript = ProfiledTranscript::new(b"test", profile);
+            let longest = (0..16)
+                .map(|_| bit_length(&transcript.next_challenge()))
+                .max();
+            assert_eq!(longest, Some(profile.challenge_bits()));
+
+            let proof = SimpleSchnorrProof::generate_proof(
+                &private_key,
+                &mut ProfiledTranscript::new(b"test", profile),
+            );
+            let mut verifier_proof = SimpleSchnorrProof::from(proof.get_proof_pair());
+            assert!(verifier_proof
+                .verify_proof(&public_key, &mut ProfiledTranscript::new(b"test", profile))
+                .is_ok());
+        }
+
+        // A proof for the identity is only accepted by the lenient interactive profile
+        let mut identity_proof = SimpleSchnorrProof::generate_proof(
+            &Scalar::ZERO,
+            &mut ProfiledTranscript::new(b"test", Profile::Interactive),
+        );
+        let identity = RistrettoPoint::default();
+        assert!(identity_proof
+            .verify_proof(
+                &identity,
+                &mut ProfiledTranscript::new(b"test", Profile::Interactive)
+            )
+            .is_ok());
+        assert_eq!(
+            identity_proof
+                .verify_proof(
+                    &identity,
+                    &mut ProfiledTranscript::new(b"test", Profile::Standard128)
+                )
+                .err(),
+            Some(Error::IdentityPoint)
+        );
+    }
+
+    #[test]
+    fn test_accounted_oracles_flag_short_fiat_shamir_challenges() {
+        let (private_key, public_key) = generate_keypair();
+        let mut oracle = AccountedOracle::new(
+            ProfiledTranscript::new(b"test", Profile::Interactive),
+            "schnorr",
+        );
+        let proof = SimpleSchnorrProof::generate_proof(&private_key, &mut oracle);
+        oracle.set_protocol("second_schnorr");
+        SimpleSchnorrProof::generate_proof(&private_key, &mut oracle);
+        let (_, ledger) = oracle.into_parts();
+        assert_eq!(ledger.challenge_bits("schnorr"), 64.0);
+        assert_eq!(ledger.reports().len(), 2);
+
+        // 64 bit Fiat-Shamir challenges meet the interactive profile's 40 bits of soundness but
+        // fall short of the standard profile's 128
+        assert!(ledger.grindable().is_empty());
+        let mut verifier = AccountedOracle::new(
+            ProfiledTranscript::new(b"test", Profile::Interactive),
+            "schnorr",
+        );
+        let mut verifier_proof = SimpleSchnorrProof::from(proof.get_proof_pair());
+        assert!(verifier_proof
+            .verify_proof(&public_key, &mut verifier)
+            .is_ok());
+        assert_eq!(verifier.get_ledger().effective_soundness_bits(), Some(64.0));
+        let mut standard = EntropyLedger::new(ChallengeSource::FiatShamir, Profile::Standard128);
+        standard.record_challenge("schnorr", 64.0);
+        assert_eq!(standard.grindable()[0].protocol, "schnorr");
+
+        // Simulated verifiers are interactive and the full width transcript isn't grindable
+        let simulated = AccountedOracle::new(SimulatedOracle::new(b"test"), "schnorr");
+        assert_eq!(simulated.challenge_source(), ChallengeSource::Interactive);
+        assert_eq!(Transcript::new(b"test").challenge_bits(), 252);
+    }
+
+    #[test]
+    fn test_traced_oracles_record_the_transcript_of_a_proof() {
+        let (private_key, public_key) = generate_keypair();
+        let mut oracle = TracedOracle::new(SimpleSchnorrProof::create_new_transcript());
+        let proof = SimpleSchnorrProof::generate_proof(&private_key, &mut oracle);
+        let (response, public_scalar) = proof.get_proof_pair();
+        let [TranscriptEvent::ProverMessage(message), TranscriptEvent::Challenge(challenge)] =
+            oracle.get_events()
+        else {
+            panic!("unexpected transcript {:?}", oracle.get_events());
+        };
+        assert_eq!(*message, public_scalar);
+        assert_eq!(
+            response * RISTRETTO_BASEPOINT_POINT,
+            public_scalar + challenge * public_key
+        );
+
+        // The verifier sees the same transcript
+        let mut verifier_oracle = TracedOracle::new(SimpleSchnorrProof::create_new_transcript());
+        SimpleSchnorrProof::from(proof.get_proof_pair())
+            .verify_proof(&public_key, &mut verifier_oracle)
+            .unwrap();
+        assert_eq!(verifier_oracle.into_parts().1, oracle.get_events());
+    }
+
+    #[test]
+    fn test_oracles_from_the_same_seed_rewind_the_prover() {
+        let (private_key, _) = generate_keypair();
+        let first = SimpleSchnorrProof::generate_proof(
+            &private_key,
+            &mut SimulatedOracle::with_challenges(b"rewind", &[Scalar::ONE]),
+        );
+        let second = SimpleSchnorrProof::generate_proof(
+            &private_key,
+            &mut SimulatedOracle::with_challenges(b"rewind", &[Scalar::from(2u64)]),
+        );
+
+        // The same random scalar is used for both runs, only the responses differ
+        assert_eq!(first.get_proof_pair().1, second.get_proof_pair().1);
+        assert_ne!(first.get_proof_pair().0, second.get_proof_pair().0);
+    }
+}
@@ -0,0 +1,310 @@
+//! Two-party proofs of knowledge of a committed witness which is additively shared between the
+//! parties
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{PointEncoding, ProofScheme, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{Error, PedersenGenerators, SimpleProofProtocol};
+
+// Sometimes no single party holds the whole witness, e.g. a model owner holds the weights' share
+// and a device the inputs' share of a committed value. With the witness additively shared as
+// v = v_A + v_B and r = r_A + r_B, each party commits to its own share as C_i = v_i*G + r_i*H and
+// by the homomorphism C = C_A + C_B commits to the whole witness. The parties then jointly prove
+// knowledge of an opening of C without revealing their shares to each other:
+//
+// 1. Nonce commitment: each party picks random nonces (a_i, b_i), computes R_i = a_i*G + b_i*H
+// and sends a hash of (C_i, R_i) to the other party.
+// 2. Reveal: once both hashes are exchanged, the parties reveal C_i and R_i and check them against
+// the hashes. Committing first stops a party from choosing R_i after seeing the other's nonce,
+// which would let it bias the joint nonce R = R_A + R_B.
+// 3. Respond: both derive the challenge c from C and R and answer with their partial responses
+// (a_i + c*v_i, b_i + c*r_i).
+// 4. Combine: anyone holding both partial responses checks each against its party's C_i and R_i,
+// which identifies a misbehaving party, and sums them into an ordinary opening proof for C.
+//
+// The combined proof is the same as one created by a single prover holding the whole witness, so
+// verifiers don't need to know the witness was shared. Relations which multiply the shares of both
+// parties, e.g. weights times inputs, additionally need oblivious transfer or another
+// multiplication protocol to produce shares of the product and aren't covered here.
+
+// Domain separator for hashing a party's revealed commitments
+const NONCE_HASH_DOMAIN_SEP: &[u8] = b"COLLABORATIVE_NONCE_HASH";
+
+// Domain separator for initializing joint opening proof transcripts
+const JOINT_OPENING_DOMAIN_SEP: &[u8] = b"COLLABORATIVE_OPENING";
+
+/// A party's additive share of a committed value and its blinding
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WitnessShare {
+    value: Scalar,
+    blinding: Scalar,
+}
+
+impl WitnessShare {
+    /// Create a share of a value and its blinding
+    pub fn new(value: Scalar, blinding: Scalar) -> Self {
+        Self { value, blinding }
+    }
+
+    /// Split a value and blinding into two random shares
+    pub fn split(value: &Scalar, blinding: &Scalar) -> (Self, Self) {
+        let mut rng = rand::rngs::OsRng;
+        let first = Self::new(Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let second = Self::new(value - first.value, blinding - first.blinding);
+        (first, second)
+    }
+
+    /// Get the commitment to the share
+    pub fn commitment(&self, generators: &PedersenGenerators) -> RistrettoPoint {
+        generators.commit(&self.value, &self.blinding)
+    }
+}
+
+/// Commitments a party reveals after the nonce hashes have been exchanged
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PartyReveal {
+    /// Commitment to the party's share
+    pub share_commitment: RistrettoPoint,
+    /// Commitment to the party's nonces
+    pub nonce_commitment: RistrettoPoint,
+}
+
+impl PartyReveal {
+    /// Hash the revealed commitments, which is sent to the other party before revealing them
+    pub fn hash(&self) -> [u8; 32] {
+        let mut transcript = Transcript::new(NONCE_HASH_DOMAIN_SEP);
+        transcript.append_message(b"SHARE_COMMITMENT", &self.share_commitment.encode());
+        transcript.append_message(b"NONCE_COMMITMENT", &self.nonce_commitment.encode());
+        let mut hash = [0; 32];
+        transcript.challenge_bytes(NONCE_HASH_DOMAIN_SEP, &mut hash);
+        hash
+    }
+}
+
+// Derive the joint challenge from the joint commitment and nonce commitment
+fn joint_challenge(
+    context: &[u8],
+    commitment: &RistrettoPoint,
+    nonce_commitment: &RistrettoPoint,
+) -> Scalar {
+    let mut transcript = Transcript::new(JOINT_OPENING_DOMAIN_SEP);
+    transcript.append_message(b"CONTEXT", context);
+    transcript.append_proof_value(commitment);
+    transcript.append_proof_value(nonce_commitment);
+    transcript.get_challenge()
+}
+
+/// A party's partial response to the joint challenge
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PartialResponse {
+    // Commitments the response belongs to
+    reveal: PartyReveal,
+    // a_i + c*v_i
+    value_response: Scalar,
+    // b_i + c*r_i
+    blinding_response: Scalar,
+}
+
+/// State of one party in a joint opening proof
+#[derive(Clone, Debug)]
+pub struct JointOpeningParty {
+    share: WitnessShare,
+    // Statement context both parties bind the proof to
+    context: Vec<u8>,
+    // Random nonces (a_i, b_i)
+    nonces: (Scalar, Scalar),
+    reveal: PartyReveal,
+}
+
+impl JointOpeningParty {
+    /// Start a joint proof for a share, returning the party state and the hash to send to the
+    /// other party
+    pub fn new(
+        share: WitnessShare,
+        generators: &PedersenGenerators,
+        context: &[u8],
+    ) -> (Self, [u8; 32]) {
+        let mut rng = rand::rngs::OsRng;
+        let nonces = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let reveal = PartyReveal {
+            share_commitment: share.commitment(generators),
+            nonce_commitment: generators.commit(&nonces.0, &nonces.1),
+        };
+        let party = Self {
+            share,
+            context: context.to_vec(),
+            nonces,
+            reveal,
+        };
+        let hash = reveal.hash();
+        (party, hash)
+    }
+
+    /// Get the commitments to reveal once the other party's hash was received
+    pub fn reveal(&self) -> PartyReveal {
+        self.reveal
+    }
+
+    /// Check the other party's revealed commitments against its hash and answer the joint
+    /// challenge
+    pub fn respond(
+        self,
+        other_hash: &[u8; 32],
+        other_reveal: &PartyReveal,
+    ) -> Result<PartialResponse, Error> {
+        if other_reveal.hash() != *other_hash || *other_reveal == self.reveal {
+            return Err(Error::CoproverMismatch);
+        }
+        let challenge = joint_challenge(
+            &self.context,
+            &(self.reveal.share_commitment + other_reveal.share_commitment),
+            &(self.reveal.nonce_commitment + other_reveal.nonce_commitment),
+        );
+        Ok(PartialResponse {
+            reveal: self.reveal,
+            value_response: self.nonces.0 + challenge * self.share.value,
+            blinding_response: self.nonces.1 + challenge * self.share.blinding,
+        })
+    }
+}
+
+/// Proof of knowledge of an opening of a Pedersen commitment
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct JointOpeningProof {
+    nonce_commitment: RistrettoPoint,
+    value_response: Scalar,
+    blinding_response: Scalar,
+}
+
+impl JointOpeningProof {
+    /// Check both parties' partial responses and combine them into a proof for the sum of their
+    /// share commitments
+    pub fn combine(
+        responses: [&PartialResponse; 2],
+        generators: &PedersenGenerators,
+        context: &[u8],
+    ) -> Result<Self, Error> {
+        let commitment =
+            responses[0].reveal.share_commitment + responses[1].reveal.share_commitment;
+        let nonce_commitment =
+            responses[0].reveal.nonce_commitment + responses[1].reveal.nonce_commitment;
+        let challenge = joint_challenge(context, &commitment, &nonce_commitment);
+        for response in responses {
+            if generators.commit(&response.value_response, &response.blinding_response)
+                != response.reveal.nonce_commitment + challenge * response.reveal.share_commitment
+            {
+                return Err(Error::CoproverMismatch);
+            }
+        }
+        Ok(Self {
+            nonce_commitment,
+            value_response: responses[0].value_response + responses[1].value_response,
+            blinding_response: responses[0].blinding_response + responses[1].blinding_response,
+        })
+    }
+
+    /// Verify knowledge of an opening of the commitment
+    pub fn verify_proof(
+        &self,
+        commitment: &RistrettoPoint,
+        generators: &PedersenGenerators,
+        context: &[u8],
+    ) -> Result<(), Error> {
+        let challenge = joint_challenge(context, commitment, &self.nonce_commitment);
+        if generators.commit(&self.value_response, &self.blinding_response)
+            != self.nonce_commitment + challenge * commitment
+        {
+            return Err(Error::OpeningMismatch);
+        }
+        Ok(())
+    }
+}
+
+impl ProofScheme for JointOpeningProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run the protocol between two parties, returning both partial responses
+    fn run_protocol(
+        shares: (WitnessShare, WitnessShare),
+        generators: &PedersenGenerators,
+    ) -> (PartialResponse, PartialResponse) {
+        let (owner, owner_hash) = JointOpeningParty::new(shares.0, generators, b"inference 7");
+        let (device, device_hash) = JointOpeningParty::new(shares.1, generators, b"inference 7");
+        let (owner_reveal, device_reveal) = (owner.reveal(), device.reveal());
+        (
+            owner.respond(&device_hash, &device_reveal).unwrap(),
+            device.respond(&owner_hash, &owner_reveal).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_shared_witnesses_prove_the_joint_commitment() {
+        let generators = PedersenGenerators::default();
+        let (value, blinding) = (Scalar::from(42u64), Scalar::random(&mut rand::rngs::OsRng));
+        let shares = WitnessShare::split(&value, &blinding);
+        let commitment = generators.commit(&value, &blinding);
+        assert_eq!(
+            shares.0.commitment(&generators) + shares.1.commitment(&generators),
+            commitment
+        );
+
+        let (owner_response, device_response) = run_protocol(shares, &generators);
+        let proof = JointOpeningProof::combine(
+            [&owner_response, &device_response],
+            &generators,
+            b"inference 7",
+        )
+        .unwrap();
+        assert!(proof
+            .verify_proof(&commitment, &generators, b"inference 7")
+            .is_ok());
+        assert_eq!(
+            proof.verify_proof(&commitment, &generators, b"inference 8"),
+            Err(Error::OpeningMismatch)
+        );
+        assert_eq!(
+            proof.verify_proof(
+                &shares.0.commitment(&generators),
+                &generators,
+                b"inference 7"
+            ),
+            Err(Error::OpeningMismatch)
+        );
+    }
+
+    #[test]
+    fn test_misbehaving_parties_are_caught() {
+        let generators = PedersenGenerators::default();
+        let shares = WitnessShare::split(&Scalar::ONE, &Scalar::ONE);
+
+        // A party can't change its nonce commitment after committing to it
+        let (owner, _) = JointOpeningParty::new(shares.0, &generators, b"context");
+        let (device, device_hash) = JointOpeningParty::new(shares.1, &generators, b"context");
+        let changed_reveal = PartyReveal {
+            nonce_commitment: generators.value_generator,
+            ..device.reveal()
+        };
+        assert_eq!(
+            owner.respond(&device_hash, &changed_reveal),
+            Err(Error::CoproverMismatch)
+        );
+
+        // A party answering with a wrong partial response is identified when combining
+        let (owner_response, mut device_response) = run_protocol(shares, &generators);
+        device_response.value_response += Scalar::ONE;
+        assert_eq!(
+            JointOpeningProof::combine(
+                [&owner_response, &device_response],
+                &generators,
+                b"inference 7"
+            ),
+            Err(Error::CoproverMismatch)
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Sigma protocol and range proof backends proving statements about the same Pedersen commitments
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{CommitAndProve, ProofScheme, ScalarEncoding, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{BlindingProof, Error, PedersenGenerators, RangeProof};
+
+// Both backends commit to a scalar as C = v*G + r*H with the same generators and share the opening
+// (v, r), so a value committed to once can be proven equal to a disclosed value with one backend
+// and to lie in a range with the other. Each proof's transcript is bound to the backend, the
+// commitment, the statement and the caller's context.
+
+// Domain separator for initializing sigma backend transcripts
+const SIGMA_BACKEND_DOMAIN_SEP: &[u8] = b"COMMIT_AND_PROVE_SIGMA";
+
+// Domain separator for initializing range backend transcripts
+const RANGE_BACKEND_DOMAIN_SEP: &[u8] = b"COMMIT_AND_PROVE_RANGE";
+
+/// Opening of a Pedersen commitment shared by the Ristretto backends
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PedersenOpening {
+    /// Committed value
+    pub value: Scalar,
+    /// Blinding scalar
+    pub blinding: Scalar,
+}
+
+// Commit to a value with a fresh blinding
+fn commit(generators: &PedersenGenerators, value: &Scalar) -> (RistrettoPoint, PedersenOpening) {
+    let blinding = Scalar::random(&mut rand::rngs::OsRng);
+    let opening = PedersenOpening {
+        value: *value,
+        blinding,
+    };
+    (generators.commit(value, &blinding), opening)
+}
+
+// Create a transcript bound to a backend, the commitment and the caller's context
+fn backend_transcript(
+    domain_sep: &'static [u8],
+    commitment: &RistrettoPoint,
+    context: &[u8],
+) -> Transcript {
+    let mut transcript = Transcript::new(domain_sep);
+    transcript.append_message(b"CONTEXT", context);
+    transcript.append_message(b"COMMITMENT", commitment.compress().as_bytes());
+    transcript
+}
+
+/// Sigma protocol backend proving a committed value equals a public value
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SigmaBackend {
+    /// Generators commitments are created with
+    pub generators: PedersenGenerators,
+}
+
+impl CommitAndProve for SigmaBackend {
+    type Witness = Scalar;
+    type Commitment = RistrettoPoint;
+    type Opening = PedersenOpening;
+    /// Public value the commitment is claimed to commit to
+    type Statement = Scalar;
+    type Proof = BlindingProof;
+    type Error = Error;
+
+    fn commit(&self, witness: &Scalar) -> Result<(RistrettoPoint, PedersenOpening), Error> {
+        Ok(commit(&self.generators, witness))
+    }
+
+    fn prove(
+        &self,
+        commitment: &RistrettoPoint,
+        opening: &PedersenOpening,
+        statement: &Scalar,
+        context: &[u8],
+    ) -> Result<BlindingProof, Error> {
+        if opening.value != *statement {
+            return Err(Error::OpeningMismatch);
+        }
+        let mut transcript = backend_transcript(SIGMA_BACKEND_DOMAIN_SEP, commitment, context);
+        transcript.append_message(b"VALUE", statement.as_bytes());
+        Ok(BlindingProof::generate_proof(
+            &opening.blinding,
+            &self.generators,
+            &mut transcript,
+        ))
+    }
+
+    fn verify(
+        &self,
+        commitment: &RistrettoPoint,
+        statement: &Scalar,
+        proof: &BlindingProof,
+        context: &[u8],
+    ) -> Result<(), Error> {
+        let mut transcript = backend_transcript(SIGMA_BACKEND_DOMAIN_SEP, commitment, context);
+        transcript.append_message(b"VALUE", statement.as_bytes());
+        proof
+            .verify_proof(
+                &(commitment - statement * self.generators.value_generator),
+                &self.generators,
+                &mut transcript,
+            )
+            .map_err(|_| Error::OpeningMismatch)
+    }
+}
+
+/// Range proof backend proving a committed value lies within [0, 2^bits)
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RangeBackend {
+    /// Generators commitments are created with
+    pub generators: PedersenGenerators,
+}
+
+impl CommitAndProve for RangeBackend {
+    type Witness = Scalar;
+    type Commitment = RistrettoPoint;
+    type Opening = PedersenOpening;
+    /// Number of bits the committed value is claimed to fit into
+    type Statement = usize;
+    type Proof = RangeProof;
+    type Error = Error;
+
+    fn commit(&self, witness: &Scalar) -> Result<(RistrettoPoint, PedersenOpening), Error> {
+        Ok(commit(&self.generators, witness))
+    }
+
+    fn prove(
+        &self,
+        commitment: &RistrettoPoint,
+        opening: &PedersenOpening,
+        statement: &usize,
+        context: &[u8],
+    ) -> Result<RangeProof, Error> {
+        // Range proofs cover values up to 64 bits
+        let bytes = opening.value.encode(encodings::Endianness::Little);
+        let (low, high) = bytes.split_at(8);
+        if high.iter().any(|byte| *byte != 0) {
+            return Err(Error::ValueOutOfRange(u64::MAX, *statement));
+        }
+        let value = u64::from_le_bytes(low.try_into().expect("scalars are 32 bytes"));
+        let mut transcript = backend_transcript(RANGE_BACKEND_DOMAIN_SEP, commitment, context);
+        transcript.append_u64(b"BITS", *statement as u64);
+        RangeProof::generate_proof(
+            value,
+            &opening.blinding,
+            *statement,
+            &self.generators,
+            &mut transcript,
+        )
+    }
+
+    fn verify(
+        &self,
+        commitment: &RistrettoPoint,
+        statement: &usize,
+        proof: &RangeProof,
+        context: &[u8],
+    ) -> Result<(), Error> {
+        let mut transcript = backend_transcript(RANGE_BACKEND_DOMAIN_SEP, commitment, context);
+        transcript.append_u64(b"BITS", *statement as u64);
+        proof.verify_proof(commitment, *statement, &self.generators, &mut transcript)
+    }
+}
+
+impl ProofScheme for SigmaBackend {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+impl ProofScheme for RangeBackend {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_commitment_backs_proofs_from_both_backends() {
+        let (sigma, range) = (SigmaBackend::default(), RangeBackend::default());
+        let value = Scalar::from(200u64);
+        let (commitment, opening) = sigma.commit(&value).unwrap();
+
+        let equality = sigma
+            .prove(&commitment, &opening, &value, b"audit")
+            .unwrap();
+        assert!(sigma
+            .verify(&commitment, &value, &equality, b"audit")
+            .is_ok());
+        let in_range = range.prove(&commitment, &opening, &8, b"audit").unwrap();
+        assert!(range.verify(&commitment, &8, &in_range, b"audit").is_ok());
+
+        // Proofs are bound to their statement and context
+        assert_eq!(
+            sigma.verify(&commitment, &Scalar::from(201u64), &equality, b"audit"),
+            Err(Error::OpeningMismatch)
+        );
+        assert_eq!(
+            range.verify(&commitment, &8, &in_range, b"other"),
+            Err(Error::RangeProofMismatch)
+        );
+    }
+
+    #[test]
+    fn test_false_statements_cant_be_proven() {
+        let (sigma, range) = (SigmaBackend::default(), RangeBackend::default());
+        let (commitment, opening) = range.commit(&Scalar::from(300u64)).unwrap();
+        assert_eq!(
+            range.prove(&commitment, &opening, &8, b"").err(),
+            Some(Error::ValueOutOfRange(300, 8))
+        );
+        assert_eq!(
+            sigma.prove(&commitment, &opening, &Scalar::ONE, b"").err(),
+            Some(Error::OpeningMismatch)
+        );
+
+        let (commitment, opening) = range.commit(&-Scalar::ONE).unwrap();
+        assert_eq!(
+            range.prove(&commitment, &opening, &64, b"").err(),
+            Some(Error::ValueOutOfRange(u64::MAX, 64))
+        );
+    }
+}
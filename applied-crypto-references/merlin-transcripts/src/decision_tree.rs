@@ -0,0 +1,777 @@
+//! Zero knowledge proofs that a committed decision tree classifies a committed input
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{ProofScheme, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{Error, PedersenGenerators, RangeProof, SetMembershipProof, SimpleProofProtocol};
+
+// A decision tree classifies an input by walking from the root to a leaf, going right at a split
+// node when the node's feature is at least its threshold and left otherwise, and outputting the
+// label of the leaf it reaches. Proving only the comparisons along the path taken would reveal the
+// path, and with it a lot about the input, so the prover evaluates every node obliviously instead.
+// The tree's shape and the feature each split node reads are public, while the thresholds, the
+// labels, the input and the path stay hidden behind Pedersen commitments:
+//
+// 1. Direction: for every split node the prover commits to the bit `d = [x >= t]` and proves
+// `d*d = d`, which only holds for 0 and 1.
+// 2. Comparison: the prover commits to `p = d*(2x - 2t + 1)` with a product proof, so that
+// `u = p + t - 1 - x` is `x - t` when going right and `t - 1 - x` when going left. A range proof
+// shows `u` lies in [0, 2^bits), which is exactly the comparison the direction claims.
+// 3. Reachability: the root is reached with `e = 1`, which is committed as `G` without blinding.
+// A split node reached with `e` passes `e*d` on to its right child with a product proof and
+// `e - e*d` to its left child, which the verifier computes from the commitments itself, so
+// exactly one leaf is reached with 1 and every other node with 0.
+// 4. Output: every leaf contributes `e*label` with a product proof, and the sum of the
+// contributions commits to the label of the leaf reached. A set membership proof shows that this
+// output commitment opens to one of the tree's labels.
+//
+// The product proofs show that commitments `C_a`, `C_b` and `C_c` satisfy `c = a*b` by proving
+// knowledge of `a`, `r_a` and `r'` such that `C_a = a*G + r_a*H` and `C_c = a*C_b + r'*H`.
+//
+// Proofs grow linearly with the number of nodes, and every split node costs a range proof over
+// the bit length of the features, so this suits small trees of low precision features.
+
+// Domain separator for initializing decision tree transcripts
+const DECISION_TREE_DOMAIN_SEP: &[u8] = b"DECISION_TREE_INFERENCE";
+
+// Maximum bit length of features and thresholds, which keeps `t - 1 - x` within a range proof
+const MAX_FEATURE_BITS: usize = 63;
+
+/// Node of a decision tree whose nodes are listed with every child after its parent
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TreeNode {
+    /// Go to `right` if the feature at index `feature` is at least `threshold`, else to `left`
+    Split {
+        /// Index of the input feature compared
+        feature: usize,
+        /// Threshold the feature is compared against
+        threshold: u64,
+        /// Index of the node taken when the feature is below the threshold
+        left: usize,
+        /// Index of the node taken when the feature is at least the threshold
+        right: usize,
+    },
+    /// Output `label`
+    Leaf {
+        /// Label of the leaf
+        label: u64,
+    },
+}
+
+/// Node of a committed decision tree, whose threshold or label is hidden
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommittedNode {
+    /// Split node with a committed threshold
+    Split {
+        /// Index of the input feature compared
+        feature: usize,
+        /// Commitment to the threshold
+        threshold: RistrettoPoint,
+        /// Index of the node taken when the feature is below the threshold
+        left: usize,
+        /// Index of the node taken when the feature is at least the threshold
+        right: usize,
+    },
+    /// Leaf with a committed label
+    Leaf {
+        /// Commitment to the label
+        label: RistrettoPoint,
+    },
+}
+
+/// Decision tree over features of up to `bits` bits, with its thresholds and labels committed
+/// with random blindings
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecisionTree {
+    nodes: Vec<TreeNode>,
+    bits: usize,
+    // Blinding of each node's threshold or label
+    blindings: Vec<Scalar>,
+}
+
+/// Public commitment to a [`DecisionTree`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecisionTreeCommitment {
+    /// Committed nodes, every child listed after its parent
+    pub nodes: Vec<CommittedNode>,
+    /// Bit length of features and thresholds
+    pub bits: usize,
+    /// Labels the tree can output
+    pub labels: Vec<u64>,
+}
+
+impl DecisionTree {
+    /// Create a tree from nodes rooted at index 0, with thresholds below 2^bits
+    pub fn new(nodes: &[TreeNode], bits: usize) -> Result<Self, Error> {
+        let children = nodes.iter().map(|node| match node {
+            TreeNode::Split { left, right, .. } => Some((*left, *right)),
+            TreeNode::Leaf { .. } => None,
+        });
+        check_shape(children, nodes.len(), bits)?;
+        let thresholds_fit = nodes.iter().all(|node| match node {
+            TreeNode::Split { threshold, .. } => threshold >> bits == 0,
+            TreeNode::Leaf { .. } => true,
+        });
+        if !thresholds_fit {
+            return Err(Error::InvalidDecisionTree);
+        }
+        Ok(Self {
+            nodes: nodes.to_vec(),
+            bits,
+            blindings: nodes
+                .iter()
+                .map(|_| Scalar::random(&mut rand::rngs::OsRng))
+                .collect(),
+        })
+    }
+
+    /// Get the labels the tree can output, in increasing order
+    pub fn labels(&self) -> Vec<u64> {
+        let mut labels: Vec<u64> = self
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                TreeNode::Leaf { label } => Some(*label),
+                TreeNode::Split { .. } => None,
+            })
+            .collect();
+        labels.sort_unstable();
+        labels.dedup();
+        labels
+    }
+
+    /// Get the commitment to the tree
+    pub fn commitment(&self, generators: &PedersenGenerators) -> DecisionTreeCommitment {
+        let nodes = self
+            .nodes
+            .iter()
+            .zip(&self.blindings)
+            .map(|(node, blinding)| match *node {
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => CommittedNode::Split {
+                    feature,
+                    threshold: generators.commit(&Scalar::from(threshold), blinding),
+                    left,
+                    right,
+                },
+                TreeNode::Leaf { label } => CommittedNode::Leaf {
+                    label: generators.commit(&Scalar::from(label), blinding),
+                },
+            })
+            .collect();
+        DecisionTreeCommitment {
+            nodes,
+            bits: self.bits,
+            labels: self.labels(),
+        }
+    }
+
+    /// Classify an input, rejecting inputs with missing features or features of more than `bits`
+    /// bits. A feature is missing if any split reads it, even one off the path the input takes.
+    pub fn evaluate(&self, input: &[u64]) -> Result<u64, Error> {
+        if let Some(feature) = input.iter().find(|feature| *feature >> self.bits != 0) {
+            return Err(Error::ValueOutOfRange(*feature, self.bits));
+        }
+        let features_exist = self.nodes.iter().all(|node| match node {
+            TreeNode::Split { feature, .. } => *feature < input.len(),
+            TreeNode::Leaf { .. } => true,
+        });
+        if !features_exist {
+            return Err(Error::InvalidVectorLength(input.len()));
+        }
+        let mut index = 0;
+        loop {
+            match self.nodes[index] {
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    let value = input
+                        .get(feature)
+                        .ok_or(Error::InvalidVectorLength(input.len()))?;
+                    index = if *value >= threshold { right } else { left };
+                }
+                TreeNode::Leaf { label } => return Ok(label),
+            }
+        }
+    }
+}
+
+/// Input features committed with random blindings
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TreeInput {
+    values: Vec<u64>,
+    blindings: Vec<Scalar>,
+}
+
+impl TreeInput {
+    /// Create an input with fresh blindings
+    pub fn new(values: &[u64]) -> Self {
+        Self {
+            values: values.to_vec(),
+            blindings: values
+                .iter()
+                .map(|_| Scalar::random(&mut rand::rngs::OsRng))
+                .collect(),
+        }
+    }
+
+    /// Get the commitment to each feature
+    pub fn commitments(&self, generators: &PedersenGenerators) -> Vec<RistrettoPoint> {
+        self.values
+            .iter()
+            .zip(&self.blindings)
+            .map(|(value, blinding)| generators.commit(&Scalar::from(*value), blinding))
+            .collect()
+    }
+}
+
+/// Proof that commitments satisfy `c = a*b`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct ProductProof {
+    // Nonce commitments k_a*G + k_r*H and k_a*C_b + k_r'*H
+    nonce_commitments: [RistrettoPoint; 2],
+    // Responses for a, r_a and r'
+    responses: [Scalar; 3],
+}
+
+impl ProductProof {
+    // Prove that `commitments` to `a`, `b` and `a*b` satisfy the product relation
+    fn generate_proof(
+        a: &Scalar,
+        blindings: [&Scalar; 3],
+        commitments: [&RistrettoPoint; 3],
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Self {
+        let [a_blinding, b_blinding, c_blinding] = blindings;
+        let mut rng = rand::rngs::OsRng;
+        let nonces = [(); 3].map(|_| Scalar::random(&mut rng));
+        let nonce_commitments = [
+            generators.commit(&nonces[0], &nonces[1]),
+            nonces[0] * commitments[1] + nonces[2] * generators.blinding_generator,
+        ];
+        let challenge_scalar = absorb_product(proof_transcript, commitments, &nonce_commitments);
+        let witness = [*a, *a_blinding, c_blinding - a * b_blinding];
+        Self {
+            nonce_commitments,
+            responses: [0, 1, 2].map(|i| nonces[i] + challenge_scalar * witness[i]),
+        }
+    }
+
+    // Verify the commitments satisfy the product relation
+    fn verify_proof(
+        &self,
+        commitments: [&RistrettoPoint; 3],
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> bool {
+        let challenge_scalar =
+            absorb_product(proof_transcript, commitments, &self.nonce_commitments);
+        let [a, a_blinding, c_blinding] = self.responses;
+        generators.commit(&a, &a_blinding)
+            == self.nonce_commitments[0] + challenge_scalar * commitments[0]
+            && a * commitments[1] + c_blinding * generators.blinding_generator
+                == self.nonce_commitments[1] + challenge_scalar * commitments[2]
+    }
+}
+
+// Absorb the commitments of a product statement and the nonce commitments, deriving the challenge
+fn absorb_product(
+    proof_transcript: &mut Transcript,
+    commitments: [&RistrettoPoint; 3],
+    nonce_commitments: &[RistrettoPoint; 2],
+) -> Scalar {
+    for point in commitments.into_iter().chain(nonce_commitments) {
+        proof_transcript.append_proof_value(point);
+    }
+    proof_transcript.get_challenge()
+}
+
+/// Proofs about a split node
+#[derive(Clone, Debug)]
+struct SplitProof {
+    // Commitment to the direction bit d and the proof that d*d = d
+    direction: RistrettoPoint,
+    direction_proof: ProductProof,
+    // Commitment to p = d*(2x - 2t + 1) and the proof of the product
+    comparison_product: RistrettoPoint,
+    comparison_product_proof: ProductProof,
+    // Proof that p + t - 1 - x lies in [0, 2^bits)
+    comparison_proof: RangeProof,
+    // Commitment to e*d passed on to the right child and the proof of the product
+    right_reach: RistrettoPoint,
+    right_reach_proof: ProductProof,
+}
+
+/// Proofs about a leaf
+#[derive(Clone, Copy, Debug)]
+struct LeafProof {
+    // Commitment to e*label and the proof of the product
+    contribution: RistrettoPoint,
+    contribution_proof: ProductProof,
+}
+
+/// Proof that a committed decision tree classifies a committed input with a committed label
+#[derive(Clone, Debug)]
+pub struct DecisionTreeProof {
+    // Proofs about every split node, in the order of the tree's nodes
+    splits: Vec<SplitProof>,
+    // Proofs about every leaf, in the order of the tree's nodes
+    leaves: Vec<LeafProof>,
+    // Proof that the output commitment opens to one of the tree's labels
+    membership_proof: SetMembershipProof,
+}
+
+impl DecisionTreeProof {
+    /// Get the commitment to the output label
+    pub fn get_output_commitment(&self) -> RistrettoPoint {
+        self.leaves.iter().map(|leaf| leaf.contribution).sum()
+    }
+}
+
+/// Prover classifying committed inputs with committed decision trees
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DecisionTreeProver {
+    generators: PedersenGenerators,
+}
+
+impl DecisionTreeProver {
+    /// Create a prover committing with the given generators
+    pub fn new(generators: PedersenGenerators) -> Self {
+        Self { generators }
+    }
+
+    /// Classify the input with the tree and prove it, returning the proof along with the blinding
+    /// of the output commitment
+    pub fn prove(
+        &self,
+        tree: &DecisionTree,
+        input: &TreeInput,
+    ) -> Result<(DecisionTreeProof, Scalar), Error> {
+        let label = tree.evaluate(&input.values)?;
+        let generators = &self.generators;
+        let g = generators.value_generator;
+        let commitment = tree.commitment(generators);
+        let input_commitments = input.commitments(generators);
+        let mut transcript = tree_transcript(&commitment, &input_commitments);
+        let mut rng = rand::rngs::OsRng;
+
+        // Value, blinding and commitment each node is reached with, the root with 1*G
+        let mut reach =
+            vec![(Scalar::ZERO, Scalar::ZERO, RistrettoPoint::default()); tree.nodes.len()];
+        reach[0] = (Scalar::ONE, Scalar::ZERO, g);
+        let (mut splits, mut leaves) = (Vec::new(), Vec::new());
+        let mut output_blinding = Scalar::ZERO;
+        for (index, node) in tree.nodes.iter().enumerate() {
+            let (node_reach, reach_blinding, reach_commitment) = reach[index];
+            let blinding = &tree.blindings[index];
+            let node_commitment = match commitment.nodes[index] {
+                CommittedNode::Split { threshold, .. } => threshold,
+                CommittedNode::Leaf { label } => label,
+            };
+            match *node {
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    let (x, x_blinding) = (input.values[feature], input.blindings[feature]);
+                    let x_commitment = input_commitments[feature];
+                    let goes_right = x >= threshold;
+
+                    // Step 1: commit to the direction and prove it's a bit
+                    let d = Scalar::from(goes_right as u64);
+                    let d_blinding = Scalar::random(&mut rng);
+                    let direction = generators.commit(&d, &d_blinding);
+                    let direction_proof = ProductProof::generate_proof(
+                        &d,
+                        [&d_blinding; 3],
+                        [&direction; 3],
+                        generators,
+                        &mut transcript,
+                    );
+
+                    // Step 2: prove the comparison the direction claims
+                    let delta_blinding = x_blinding + x_blinding - blinding - blinding;
+                    let delta_commitment =
+                        x_commitment + x_commitment - node_commitment - node_commitment + g;
+                    let p_blinding = Scalar::random(&mut rng);
+                    let p = d * (Scalar::from(2 * x + 1) - Scalar::from(2 * threshold));
+                    let comparison_product = generators.commit(&p, &p_blinding);
+                    let comparison_product_proof = ProductProof::generate_proof(
+                        &d,
+                        [&d_blinding, &delta_blinding, &p_blinding],
+                        [&direction, &delta_commitment, &comparison_product],
+                        generators,
+                        &mut transcript,
+                    );
+                    let difference = match goes_right {
+                        true => x - threshold,
+                        false => threshold - 1 - x,
+                    };
+                    let comparison_proof = RangeProof::generate_proof(
+                        difference,
+                        &(p_blinding + blinding - x_blinding),
+                        tree.bits,
+                        generators,
+                        &mut transcript,
+                    )?;
+
+                    // Step 3: pass the reach on to the children
+                    let right_value = node_reach * d;
+                    let right_blinding = Scalar::random(&mut rng);
+                    let right_reach = generators.commit(&right_value, &right_blinding);
+                    let right_reach_proof = ProductProof::generate_proof(
+                        &node_reach,
+                        [&reach_blinding, &d_blinding, &right_blinding],
+                        [&reach_commitment, &direction, &right_reach],
+                        generators,
+                        &mut transcript,
+                    );
+                    reach[right] = (right_value, right_blinding, right_reach);
+                    reach[left] = (
+                        node_reach - right_value,
+                        reach_blinding - right_blinding,
+                        reach_commitment - right_reach,
+                    );
+                    splits.push(SplitProof {
+                        direction,
+                        direction_proof,
+                        comparison_product,
+                        comparison_product_proof,
+                        comparison_proof,
+                        right_reach,
+                        right_reach_proof,
+                    });
+                }
+                TreeNode::Leaf { label } => {
+                    // Step 4: contribute the label if the leaf is reached
+                    let contribution_blinding = Scalar::random(&mut rng);
+                    let contribution = generators
+                        .commit(&(node_reach * Scalar::from(label)), &contribution_blinding);
+                    let contribution_proof = ProductProof::generate_proof(
+                        &node_reach,
+                        [&reach_blinding, blinding, &contribution_blinding],
+                        [&reach_commitment, &node_commitment, &contribution],
+                        generators,
+                        &mut transcript,
+                    );
+                    output_blinding += contribution_blinding;
+                    leaves.push(LeafProof {
+                        contribution,
+                        contribution_proof,
+                    });
+                }
+            }
+        }
+        let membership_proof = SetMembershipProof::generate_proof(
+            label,
+            &output_blinding,
+            &commitment.labels,
+            generators,
+            &mut transcript,
+        )?;
+        Ok((
+            DecisionTreeProof {
+                splits,
+                leaves,
+                membership_proof,
+            },
+            output_blinding,
+        ))
+    }
+}
+
+/// Verifier checking classifications of committed inputs by committed decision trees
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DecisionTreeVerifier {
+    generators: PedersenGenerators,
+}
+
+impl DecisionTreeVerifier {
+    /// Create a verifier for proofs made with the given generators
+    pub fn new(generators: PedersenGenerators) -> Self {
+        Self { generators }
+    }
+
+    /// Verify the committed tree classifies the committed input with the label committed to by
+    /// [`DecisionTreeProof::get_output_commitment`]
+    pub fn verify(
+        &self,
+        tree: &DecisionTreeCommitment,
+        input_commitments: &[RistrettoPoint],
+        proof: &DecisionTreeProof,
+    ) -> Result<(), Error> {
+        let children = tree.nodes.iter().map(|node| match node {
+            CommittedNode::Split { left, right, .. } => Some((*left, *right)),
+            CommittedNode::Leaf { .. } => None,
+        });
+        check_shape(children, tree.nodes.len(), tree.bits)?;
+        let num_splits = tree
+            .nodes
+            .iter()
+            .filter(|node| matches!(node, CommittedNode::Split { .. }))
+            .count();
+        if proof.splits.len() != num_splits || proof.leaves.len() != tree.nodes.len() - num_splits {
+            return Err(Error::DecisionTreeMismatch);
+        }
+        let generators = &self.generators;
+        let g = generators.value_generator;
+        let mut transcript = tree_transcript(tree, input_commitments);
+
+        let mut reach = vec![RistrettoPoint::default(); tree.nodes.len()];
+        reach[0] = g;
+        let (mut splits, mut leaves) = (proof.splits.iter(), proof.leaves.iter());
+        for (index, node) in tree.nodes.iter().enumerate() {
+            let verified = match *node {
+                CommittedNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    let split = splits
+                        .next()
+                        .expect("proof has a split proof per split node");
+                    let x_commitment = input_commitments
+                        .get(feature)
+                        .ok_or(Error::InvalidVectorLength(input_commitments.len()))?;
+                    let delta_commitment = x_commitment + x_commitment - threshold - threshold + g;
+                    let verified = split.direction_proof.verify_proof(
+                        [&split.direction; 3],
+                        generators,
+                        &mut transcript,
+                    ) && split.comparison_product_proof.verify_proof(
+                        [
+                            &split.direction,
+                            &delta_commitment,
+                            &split.comparison_product,
+                        ],
+                        generators,
+                        &mut transcript,
+                    );
+                    split.comparison_proof.verify_proof(
+                        &(split.comparison_product + threshold - g - x_commitment),
+                        tree.bits,
+                        generators,
+                        &mut transcript,
+                    )?;
+                    reach[right] = split.right_reach;
+                    reach[left] = reach[index] - split.right_reach;
+                    verified
+                        && split.right_reach_proof.verify_proof(
+                            [&reach[index], &split.direction, &split.right_reach],
+                            generators,
+                            &mut transcript,
+                        )
+                }
+                CommittedNode::Leaf { label } => {
+                    let leaf = leaves.next().expect("proof has a leaf proof per leaf");
+                    leaf.contribution_proof.verify_proof(
+                        [&reach[index], &label, &leaf.contribution],
+                        generators,
+                        &mut transcript,
+                    )
+                }
+            };
+            if !verified {
+                return Err(Error::DecisionTreeMismatch);
+            }
+        }
+        proof.membership_proof.verify_proof(
+            &proof.get_output_commitment(),
+            &tree.labels,
+            generators,
+            &mut transcript,
+        )
+    }
+}
+
+// Check nodes with the given children form a tree rooted at index 0 with every child after its
+// parent, and that features of `bits` bits can be compared
+fn check_shape(
+    children: impl Iterator<Item = Option<(usize, usize)>>,
+    num_nodes: usize,
+    bits: usize,
+) -> Result<(), Error> {
+    if num_nodes == 0 || bits == 0 || bits > MAX_FEATURE_BITS {
+        return Err(Error::InvalidDecisionTree);
+    }
+    let mut has_parent = vec![false; num_nodes];
+    for (index, children) in children.enumerate() {
+        for child in children.into_iter().flat_map(|(left, right)| [left, right]) {
+            if child <= index || child >= num_nodes || has_parent[child] {
+                return Err(Error::InvalidDecisionTree);
+            }
+            has_parent[child] = true;
+        }
+    }
+    // Every node but the root must be reachable
+    if has_parent.iter().skip(1).any(|has_parent| !has_parent) {
+        return Err(Error::InvalidDecisionTree);
+    }
+    Ok(())
+}
+
+// Create a transcript bound to the committed tree and input
+fn tree_transcript(
+    tree: &DecisionTreeCommitment,
+    input_commitments: &[RistrettoPoint],
+) -> Transcript {
+    let mut transcript = Transcript::new(DECISION_TREE_DOMAIN_SEP);
+    transcript.append_u64(b"BITS", tree.bits as u64);
+    transcript.append_u64(b"NUM_NODES", tree.nodes.len() as u64);
+    for node in tree.nodes.iter() {
+        match node {
+            CommittedNode::Split {
+                feature,
+                threshold,
+                left,
+                right,
+            } => {
+                transcript.append_u64(b"FEATURE", *feature as u64);
+                transcript.append_u64(b"LEFT", *left as u64);
+                transcript.append_u64(b"RIGHT", *right as u64);
+                transcript.append_proof_value(threshold);
+            }
+            CommittedNode::Leaf { label } => transcript.append_proof_value(label),
+        }
+    }
+    transcript.append_u64(b"NUM_FEATURES", input_commitments.len() as u64);
+    for commitment in input_commitments {
+        transcript.append_proof_value(commitment);
+    }
+    transcript
+}
+
+impl ProofScheme for DecisionTreeProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Splits on feature 0 at 50, then on feature 1 at 20 below it
+    fn tree() -> DecisionTree {
+        DecisionTree::new(
+            &[
+                TreeNode::Split {
+                    feature: 0,
+                    threshold: 50,
+                    left: 1,
+                    right: 2,
+                },
+                TreeNode::Split {
+                    feature: 1,
+                    threshold: 20,
+                    left: 3,
+                    right: 4,
+                },
+                TreeNode::Leaf { label: 2 },
+                TreeNode::Leaf { label: 0 },
+                TreeNode::Leaf { label: 1 },
+            ],
+            8,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decision_tree_proofs_verify_the_committed_label() {
+        let generators = PedersenGenerators::default();
+        let tree = tree();
+        let commitment = tree.commitment(&generators);
+        assert_eq!(commitment.labels, vec![0, 1, 2]);
+        for (values, label) in [([60, 0], 2), ([50, 20], 2), ([10, 5], 0), ([10, 30], 1)] {
+            let input = TreeInput::new(&values);
+            assert_eq!(tree.evaluate(&values), Ok(label));
+            let (proof, blinding) = DecisionTreeProver::new(generators)
+                .prove(&tree, &input)
+                .unwrap();
+            assert_eq!(
+                proof.get_output_commitment(),
+                generators.commit(&Scalar::from(label), &blinding)
+            );
+            assert_eq!(
+                DecisionTreeVerifier::new(generators).verify(
+                    &commitment,
+                    &input.commitments(&generators),
+                    &proof
+                ),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            tree.evaluate(&[256, 0]),
+            Err(Error::ValueOutOfRange(256, 8))
+        );
+        assert_eq!(tree.evaluate(&[10]), Err(Error::InvalidVectorLength(1)));
+
+        // Feature 1 is off the path of inputs at or above 50 but is still proven against
+        assert_eq!(tree.evaluate(&[60]), Err(Error::InvalidVectorLength(1)));
+        assert_eq!(
+            DecisionTreeProver::new(generators)
+                .prove(&tree, &TreeInput::new(&[60]))
+                .err(),
+            Some(Error::InvalidVectorLength(1))
+        );
+    }
+
+    #[test]
+    fn test_decision_tree_proofs_reject_other_inputs_and_malformed_trees() {
+        let generators = PedersenGenerators::default();
+        let tree = tree();
+        let commitment = tree.commitment(&generators);
+        let input = TreeInput::new(&[10, 30]);
+        let (proof, _) = DecisionTreeProver::default().prove(&tree, &input).unwrap();
+        let verifier = DecisionTreeVerifier::default();
+
+        // Another input, and the same input against another commitment to the same tree
+        let other_input = TreeInput::new(&[10, 30]);
+        assert!(verifier
+            .verify(&commitment, &other_input.commitments(&generators), &proof)
+            .is_err());
+        let recommitted = DecisionTree::new(&tree.nodes, 8).unwrap();
+        assert!(verifier
+            .verify(
+                &recommitted.commitment(&generators),
+                &input.commitments(&generators),
+                &proof
+            )
+            .is_err());
+
+        // Children listed before their parents, shared children and unreachable nodes
+        let leaf = TreeNode::Leaf { label: 0 };
+        let split = |left, right| TreeNode::Split {
+            feature: 0,
+            threshold: 1,
+            left,
+            right,
+        };
+        for nodes in [
+            vec![split(1, 2), split(0, 3), leaf, leaf],
+            vec![split(1, 1), leaf],
+            vec![split(1, 2), leaf, leaf, leaf],
+            vec![],
+        ] {
+            assert_eq!(
+                DecisionTree::new(&nodes, 8),
+                Err(Error::InvalidDecisionTree)
+            );
+        }
+        assert_eq!(
+            DecisionTree::new(&[split(1, 2), leaf, leaf], 64),
+            Err(Error::InvalidDecisionTree)
+        );
+    }
+}
@@ -0,0 +1,540 @@
+//! Gadgets decomposing committed scalars into bits or base-2^k limbs with proofs that the
+//! decomposition is valid
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{Endianness, PointEncoding, ScalarEncoding};
+use merlin::Transcript;
+
+use crate::{Error, PedersenGenerators, SimpleProofProtocol};
+
+// Many statements about a committed value are easier to prove about its digits than about the
+// value itself. Range proofs, comparisons and proofs linking a value committed on one curve to
+// the same value committed on another all start by splitting a Pedersen commitment
+// `C = v*G + r*H` into commitments to smaller pieces of `v`.
+//
+// A decomposition into digits of `k` bits works as follows:
+//
+// 1. The Prover writes `v = d_0 + d_1*2^k + .. + d_(m-1)*2^(k*(m-1))` and commits to each digit as
+// `C_j = d_j*G + r_j*H`, choosing the digit blinding factors so that
+// `r = r_0 + r_1*2^k + .. + r_(m-1)*2^(k*(m-1))`.
+// 2. The Verifier checks that `C = C_0 + C_1*2^k + .. + C_(m-1)*2^(k*(m-1))`, so by the
+// homomorphic property of Pedersen commitments the digits recompose to the committed value.
+// 3. The Prover shows every digit commitment opens to a value in [0, 2^k). For bits (k = 1) this
+// is an OR proof that the commitment opens to 0 or 1. For larger limbs the limb commitment is
+// itself decomposed into bits.
+//
+// Step 3 is what makes the decomposition unique. Without it a Prover could commit to "digits"
+// such as -1 or 2^k which still recompose to `C` but say nothing about the digits of `v`. The
+// total number of bits is capped at 252 so that no recomposition can wrap around the group order
+// (which is slightly larger than 2^252). This also means the digits and the recomposed value fit
+// within the scalar fields of BLS12-381 and BLS12-377, which cross-curve proofs rely on.
+
+/// Maximum number of bits a decomposition can cover
+pub const MAX_DECOMPOSITION_BITS: usize = 252;
+
+// Length of an encoded bit commitment with its OR proof: the compressed commitment followed by
+// both challenges and both responses as 32 little endian bytes each
+const ENCODED_BIT_LEN: usize = 160;
+
+// Domain separator for binding the number of bits of a bit decomposition into the transcript
+const DECOMPOSITION_BITS_DOMAIN_SEP: &[u8] = b"DECOMPOSITION_BITS";
+
+// Domain separator for binding the size of each limb into the transcript
+const LIMB_BITS_DOMAIN_SEP: &[u8] = b"LIMB_BITS";
+
+// Domain separator for binding the number of limbs into the transcript
+const NUM_LIMBS_DOMAIN_SEP: &[u8] = b"NUM_LIMBS";
+
+/// OR proof that a bit commitment opens to either 0 or 1
+#[derive(Clone, Copy, Debug)]
+struct BitProof {
+    // Challenges for the "bit is 0" and "bit is 1" statements respectively
+    challenges: [Scalar; 2],
+    // Responses for the "bit is 0" and "bit is 1" statements respectively
+    responses: [Scalar; 2],
+}
+
+impl BitProof {
+    // Prove that `commitment = bit*G + blinding*H` for a bit that is 0 or 1
+    fn generate_proof(
+        bit: usize,
+        commitment: &RistrettoPoint,
+        blinding: &Scalar,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Self {
+        let mut rng = rand::rngs::OsRng;
+        let h = generators.blinding_generator;
+        let statements = [*commitment, commitment - generators.value_generator];
+        let simulated = 1 - bit;
+
+        // Simulate the false statement by choosing its challenge and response up front
+        let mut challenges = [Scalar::ZERO; 2];
+        let mut responses = [Scalar::ZERO; 2];
+        challenges[simulated] = Scalar::random(&mut rng);
+        responses[simulated] = Scalar::random(&mut rng);
+        let mut public_scalars = [RistrettoPoint::default(); 2];
+        public_scalars[simulated] =
+            responses[simulated] * h - challenges[simulated] * statements[simulated];
+
+        // Commit honestly to the true statement
+        let random_scalar = Scalar::random(&mut rng);
+        public_scalars[bit] = random_scalar * h;
+
+        proof_transcript.append_proof_value(commitment);
+        proof_transcript.append_proof_value(&public_scalars[0]);
+        proof_transcript.append_proof_value(&public_scalars[1]);
+        let challenge_scalar = proof_transcript.get_challenge();
+
+        // The true statement's challenge is whatever is left over from the simulated one
+        challenges[bit] = challenge_scalar - challenges[simulated];
+        responses[bit] = random_scalar + challenges[bit] * blinding;
+
+        Self {
+            challenges,
+            responses,
+        }
+    }
+
+    // Verify the bit commitment opens to either 0 or 1
+    fn verify_proof(
+        &self,
+        commitment: &RistrettoPoint,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> bool {
+        let h = generators.blinding_generator;
+        let statements = [*commitment, commitment - generators.value_generator];
+
+        // Recompute the prover's public scalars from the challenges and responses
+        let public_scalars = [
+            self.responses[0] * h - self.challenges[0] * statements[0],
+            self.responses[1] * h - self.challenges[1] * statements[1],
+        ];
+
+        proof_transcript.append_proof_value(commitment);
+        proof_transcript.append_proof_value(&public_scalars[0]);
+        proof_transcript.append_proof_value(&public_scalars[1]);
+        let challenge_scalar = proof_transcript.get_challenge();
+
+        self.challenges[0] + self.challenges[1] == challenge_scalar
+    }
+}
+
+/// Commitments to the bits of a committed scalar along with proofs that each commits to 0 or 1
+#[derive(Clone, Debug)]
+pub struct BitDecomposition {
+    // Pedersen commitments to each bit of the value, least significant bit first
+    bit_commitments: Vec<RistrettoPoint>,
+    // OR proofs that each bit commitment opens to 0 or 1
+    bit_proofs: Vec<BitProof>,
+}
+
+impl BitDecomposition {
+    /// Decompose the commitment `value*G + blinding*H` into `bits` bit commitments and prove the
+    /// decomposition is valid
+    pub fn generate_proof(
+        value: &Scalar,
+        blinding: &Scalar,
+        bits: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<Self, Error> {
+        check_fits(value, bits)?;
+        let bit_blindings = split_blinding(blinding, bits, 1);
+
+        let commitment = generators.commit(value, blinding);
+        proof_transcript.append_u64(DECOMPOSITION_BITS_DOMAIN_SEP, bits as u64);
+        proof_transcript.append_proof_value(&commitment);
+
+        let mut bit_commitments = Vec::with_capacity(bits);
+        let mut bit_proofs = Vec::with_capacity(bits);
+        for (i, bit_blinding) in bit_blindings.iter().enumerate() {
+            let bit = get_bit(value, i);
+            let bit_commitment = generators.commit(&Scalar::from(bit as u64), bit_blinding);
+            bit_proofs.push(BitProof::generate_proof(
+                bit,
+                &bit_commitment,
+                bit_blinding,
+                generators,
+                proof_transcript,
+            ));
+            bit_commitments.push(bit_commitment);
+        }
+
+        Ok(Self {
+            bit_commitments,
+            bit_proofs,
+        })
+    }
+
+    /// Verify that `commitment` decomposes into `bits` valid bit commitments
+    pub fn verify_proof(
+        &self,
+        commitment: &RistrettoPoint,
+        bits: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        if bits == 0 || bits > MAX_DECOMPOSITION_BITS {
+            return Err(Error::InvalidBitLength(bits));
+        }
+        if self.bit_commitments.len() != bits || self.bit_proofs.len() != bits {
+            return Err(Error::DecompositionMismatch);
+        }
+        if recompose(&self.bit_commitments, 1) != *commitment {
+            return Err(Error::DecompositionMismatch);
+        }
+
+        proof_transcript.append_u64(DECOMPOSITION_BITS_DOMAIN_SEP, bits as u64);
+        proof_transcript.append_proof_value(commitment);
+        for (bit_commitment, bit_proof) in self.bit_commitments.iter().zip(self.bit_proofs.iter()) {
+            if !bit_proof.verify_proof(bit_commitment, generators, proof_transcript) {
+                return Err(Error::DecompositionMismatch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the commitments to each bit, least significant bit first
+    pub fn get_bit_commitments(&self) -> &[RistrettoPoint] {
+        &self.bit_commitments
+    }
+
+    /// Encode the bit commitments and their proofs, least significant bit first
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.bit_commitments.len() * ENCODED_BIT_LEN);
+        for (bit_commitment, bit_proof) in self.bit_commitments.iter().zip(self.bit_proofs.iter()) {
+            bytes.extend(bit_commitment.encode());
+            for scalar in bit_proof
+                .challenges
+                .iter()
+                .chain(bit_proof.responses.iter())
+            {
+                bytes.extend(scalar.encode(Endianness::Little));
+            }
+        }
+        bytes
+    }
+
+    /// Decode a decomposition of `bits` bits, rejecting malformed encodings
+    pub fn from_bytes(bytes: &[u8], bits: usize) -> Result<Self, Error> {
+        if bits == 0 || bits > MAX_DECOMPOSITION_BITS {
+            return Err(Error::InvalidBitLength(bits));
+        }
+        if bytes.len() != bits * ENCODED_BIT_LEN {
+            return Err(Error::InvalidProofEncoding(
+                encodings::Error::InvalidLength(bits * ENCODED_BIT_LEN, bytes.len()),
+            ));
+        }
+
+        let decode_scalar = |bytes: &[u8]| {
+            Scalar::decode(bytes, Endianness::Little).map_err(Error::InvalidProofEncoding)
+        };
+        let mut bit_commitments = Vec::with_capacity(bits);
+        let mut bit_proofs = Vec::with_capacity(bits);
+        for chunk in bytes.chunks_exact(ENCODED_BIT_LEN) {
+            bit_commitments
+                .push(RistrettoPoint::decode(&chunk[..32]).map_err(Error::InvalidProofEncoding)?);
+            bit_proofs.push(BitProof {
+                challenges: [
+                    decode_scalar(&chunk[32..64])?,
+                    decode_scalar(&chunk[64..96])?,
+                ],
+                responses: [
+                    decode_scalar(&chunk[96..128])?,
+                    decode_scalar(&chunk[128..])?,
+                ],
+            });
+        }
+        Ok(Self {
+            bit_commitments,
+            bit_proofs,
+        })
+    }
+}
+
+/// Commitments to the base-2^k limbs of a committed scalar along with proofs that each limb is
+/// within [0, 2^k)
+#[derive(Clone, Debug)]
+pub struct LimbDecomposition {
+    // Pedersen commitments to each limb of the value, least significant limb first
+    limb_commitments: Vec<RistrettoPoint>,
+    // Bit decompositions showing each limb is within [0, 2^k)
+    limb_proofs: Vec<BitDecomposition>,
+}
+
+impl LimbDecomposition {
+    /// Decompose the commitment `value*G + blinding*H` into `num_limbs` commitments to limbs of
+    /// `limb_bits` bits each and prove the decomposition is valid
+    pub fn generate_proof(
+        value: &Scalar,
+        blinding: &Scalar,
+        limb_bits: usize,
+        num_limbs: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<Self, Error> {
+        check_fits(value, limb_bits.saturating_mul(num_limbs))?;
+        let limb_blindings = split_blinding(blinding, num_limbs, limb_bits);
+
+        let commitment = generators.commit(value, blinding);
+        bind_limb_statement(&commitment, limb_bits, num_limbs, proof_transcript);
+
+        let mut limb_commitments = Vec::with_capacity(num_limbs);
+        let mut limb_proofs = Vec::with_capacity(num_limbs);
+        for (j, limb_blinding) in limb_blindings.iter().enumerate() {
+            let limb = get_bits(value, j * limb_bits, limb_bits);
+            limb_commitments.push(generators.commit(&limb, limb_blinding));
+            limb_proofs.push(BitDecomposition::generate_proof(
+                &limb,
+                limb_blinding,
+                limb_bits,
+                generators,
+                proof_transcript,
+            )?);
+        }
+
+        Ok(Self {
+            limb_commitments,
+            limb_proofs,
+        })
+    }
+
+    /// Verify that `commitment` decomposes into `num_limbs` valid limbs of `limb_bits` bits each
+    pub fn verify_proof(
+        &self,
+        commitment: &RistrettoPoint,
+        limb_bits: usize,
+        num_limbs: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        let bits = limb_bits.saturating_mul(num_limbs);
+        if limb_bits == 0 || bits == 0 || bits > MAX_DECOMPOSITION_BITS {
+            return Err(Error::InvalidBitLength(bits));
+        }
+        if self.limb_commitments.len() != num_limbs || self.limb_proofs.len() != num_limbs {
+            return Err(Error::DecompositionMismatch);
+        }
+        if recompose(&self.limb_commitments, limb_bits) != *commitment {
+            return Err(Error::DecompositionMismatch);
+        }
+
+        bind_limb_statement(commitment, limb_bits, num_limbs, proof_transcript);
+        for (limb_commitment, limb_proof) in
+            self.limb_commitments.iter().zip(self.limb_proofs.iter())
+        {
+            limb_proof.verify_proof(limb_commitment, limb_bits, generators, proof_transcript)?;
+        }
+        Ok(())
+    }
+
+    /// Get the commitments to each limb, least significant limb first
+    pub fn get_limb_commitments(&self) -> &[RistrettoPoint] {
+        &self.limb_commitments
+    }
+}
+
+// Bind the shape of a limb decomposition and the commitment being decomposed into the transcript
+fn bind_limb_statement(
+    commitment: &RistrettoPoint,
+    limb_bits: usize,
+    num_limbs: usize,
+    proof_transcript: &mut Transcript,
+) {
+    proof_transcript.append_u64(LIMB_BITS_DOMAIN_SEP, limb_bits as u64);
+    proof_transcript.append_u64(NUM_LIMBS_DOMAIN_SEP, num_limbs as u64);
+    proof_transcript.append_proof_value(commitment);
+}
+
+// Check a scalar can be represented within the given number of bits
+fn check_fits(value: &Scalar, bits: usize) -> Result<(), Error> {
+    if bits == 0 || bits > MAX_DECOMPOSITION_BITS {
+        return Err(Error::InvalidBitLength(bits));
+    }
+    if (bits..256).any(|i| get_bit(value, i) == 1) {
+        return Err(Error::ScalarOutOfRange(bits));
+    }
+    Ok(())
+}
+
+// Get bit `i` of the canonical little endian representation of a scalar
+fn get_bit(value: &Scalar, i: usize) -> usize {
+    ((value.as_bytes()[i / 8] >> (i % 8)) & 1) as usize
+}
+
+// Get `len` bits of a scalar starting at bit `offset` as a scalar
+fn get_bits(value: &Scalar, offset: usize, len: usize) -> Scalar {
+    let mut bytes = [0u8; 32];
+    for i in 0..len {
+        bytes[i / 8] |= (get_bit(value, offset + i) as u8) << (i % 8);
+    }
+    Scalar::from_bytes_mod_order(bytes)
+}
+
+// Compute 2^exponent as a scalar
+fn power_of_two(exponent: usize) -> Scalar {
+    (0..exponent).fold(Scalar::ONE, |acc, _| acc + acc)
+}
+
+// Recompose digit commitments with digits of `shift` bits, i.e. sum C_j * 2^(shift*j)
+fn recompose(commitments: &[RistrettoPoint], shift: usize) -> RistrettoPoint {
+    let base = power_of_two(shift);
+    let mut weight = Scalar::ONE;
+    let mut recomposed = RistrettoPoint::default();
+    for commitment in commitments {
+        recomposed += weight * commitment;
+        weight *= base;
+    }
+    recomposed
+}
+
+// Split a blinding factor into `count` digit blinding factors r_j such that
+// r = r_0 + r_1*2^shift + .. + r_(count-1)*2^(shift*(count-1)). Every blinding factor except the
+// least significant one is random.
+fn split_blinding(blinding: &Scalar, count: usize, shift: usize) -> Vec<Scalar> {
+    let base = power_of_two(shift);
+    let mut blindings = vec![Scalar::ZERO; count];
+    let mut remaining_blinding = *blinding;
+    let mut weight = Scalar::ONE;
+    for digit_blinding in blindings.iter_mut().skip(1) {
+        weight *= base;
+        *digit_blinding = Scalar::random(&mut rand::rngs::OsRng);
+        remaining_blinding -= weight * *digit_blinding;
+    }
+    blindings[0] = remaining_blinding;
+    blindings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_decomposition_verifies_and_recomposes() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let value = Scalar::from(u128::MAX);
+        let commitment = generators.commit(&value, &blinding);
+
+        let proof = BitDecomposition::generate_proof(
+            &value,
+            &blinding,
+            128,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        assert_eq!(proof.get_bit_commitments().len(), 128);
+        assert!(proof
+            .verify_proof(&commitment, 128, &generators, &mut Transcript::new(b"test"))
+            .is_ok());
+
+        // Values which need more bits than requested can't be decomposed
+        assert_eq!(
+            BitDecomposition::generate_proof(
+                &value,
+                &blinding,
+                127,
+                &generators,
+                &mut Transcript::new(b"test")
+            )
+            .err(),
+            Some(Error::ScalarOutOfRange(127))
+        );
+        assert_eq!(
+            proof.verify_proof(&commitment, 253, &generators, &mut Transcript::new(b"test")),
+            Err(Error::InvalidBitLength(253))
+        );
+    }
+
+    #[test]
+    fn test_limb_decomposition_verifies_and_exposes_limb_commitments() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let value = Scalar::from(0xBEEF_CAFEu64);
+        let commitment = generators.commit(&value, &blinding);
+
+        let proof = LimbDecomposition::generate_proof(
+            &value,
+            &blinding,
+            8,
+            4,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        assert!(proof
+            .verify_proof(
+                &commitment,
+                8,
+                4,
+                &generators,
+                &mut Transcript::new(b"test")
+            )
+            .is_ok());
+
+        // The limb commitments recompose to the original commitment with base 2^8
+        let limbs = proof.get_limb_commitments();
+        assert_eq!(limbs.len(), 4);
+        assert_eq!(recompose(limbs, 8), commitment);
+
+        // The proof doesn't verify for a different commitment or limb layout
+        let other_commitment = generators.commit(&Scalar::from(0xBEEF_CAFFu64), &blinding);
+        assert_eq!(
+            proof.verify_proof(
+                &other_commitment,
+                8,
+                4,
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::DecompositionMismatch)
+        );
+        assert_eq!(
+            proof.verify_proof(
+                &commitment,
+                16,
+                2,
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::DecompositionMismatch)
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_digits_are_rejected() {
+        let generators = PedersenGenerators::default();
+        let value = Scalar::from(5u64);
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let commitment = generators.commit(&value, &blinding);
+
+        // Commit to the digits 5 and 0 in base 2 which recompose correctly but aren't bits
+        let other_blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let forged_commitments = vec![
+            generators.commit(&value, &(blinding - other_blinding - other_blinding)),
+            generators.commit(&Scalar::ZERO, &other_blinding),
+        ];
+        assert_eq!(recompose(&forged_commitments, 1), commitment);
+
+        // Reuse valid OR proofs for other bit commitments, which fail for the forged digits
+        let honest = BitDecomposition::generate_proof(
+            &Scalar::ONE,
+            &blinding,
+            2,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        let forged = BitDecomposition {
+            bit_commitments: forged_commitments,
+            bit_proofs: honest.bit_proofs,
+        };
+        assert_eq!(
+            forged.verify_proof(&commitment, 2, &generators, &mut Transcript::new(b"test")),
+            Err(Error::DecompositionMismatch)
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Blinding factors derived from a committed device secret with proofs of correct derivation
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{ProofScheme, SecretScalar, SecurityLevel};
+use rand::{CryptoRng, RngCore};
+
+use crate::{challenge_oracle::validate_points, ChallengeOracle, Error, PedersenGenerators};
+
+// A device free to pick the blinding factor `r` of a commitment `C = v*G + r*H` can use it as a
+// covert channel: the commitment still hides `v` from the verifier, but whoever knows how the
+// device picks `r` can read data encoded in it, e.g. by trying candidate blinding factors until
+// `C - r*H` is a multiple of `G` by a small value. Taking the choice away from the device closes
+// the channel:
+//
+// 1. The device commits to a secret `x` once, by publishing `X = x*G`.
+// 2. The blinding factor of its i-th commitment is the Dodis-Yampolskiy PRF `r_i = 1/(x + i)`.
+// It is fixed by `x` and `i` and looks random to anyone who doesn't know `x`.
+// 3. Along with every commitment the device proves knowledge of `v` and `r` such that
+// `C = v*G + r*H` and `G = r*(X + i*G)`. The second equation holds exactly when `r*(x + i) = 1`,
+// so the proof shows the blinding factor is the derived one without revealing it. Both
+// equations share the response for `r`, the same way a DLEQ proof ties two statements together.
+//
+// Unlike a freshly sampled blinding factor, a derived one only hides `v` computationally:
+// checking a guess `v'` means deciding whether `C - v'*G` and `G` have the same discrete logs
+// relative to `H` and `X + i*G`, which is a decisional Diffie-Hellman problem. The index has to be
+// fresh for every commitment, since two commitments with the same index share their blinding
+// factor and their difference reveals `(v_1 - v_2)*G`.
+
+/// Secret of a device from which the blinding factors of its commitments are derived, overwritten
+/// with zeros when it is dropped
+pub struct DeviceSecret {
+    secret: SecretScalar<Scalar>,
+}
+
+// The secret determines every blinding factor of the device, so it is never printed
+impl std::fmt::Debug for DeviceSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DeviceSecret(..)")
+    }
+}
+
+impl DeviceSecret {
+    /// Sample a new device secret
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self {
+            secret: SecretScalar::new(Scalar::random(rng)),
+        }
+    }
+
+    /// Get the commitment `X = x*G` to the secret, which is published once for the device
+    pub fn get_commitment(&self, generators: &PedersenGenerators) -> RistrettoPoint {
+        self.secret.get_scalar() * generators.value_generator
+    }
+
+    /// Derive the blinding factor `1/(x + index)` of the commitment with the given index
+    pub fn derive_blinding(&self, index: u64) -> Result<Scalar, Error> {
+        let denominator = self.secret.get_scalar() + Scalar::from(index);
+        if denominator == Scalar::ZERO {
+            return Err(Error::InvalidDerivationIndex(index));
+        }
+        Ok(denominator.invert())
+    }
+
+    /// Commit to `value` with the blinding factor derived for `index`
+    pub fn commit(
+        &self,
+        value: &Scalar,
+        index: u64,
+        generators: &PedersenGenerators,
+    ) -> Result<RistrettoPoint, Error> {
+        Ok(generators.commit(value, &self.derive_blinding(index)?))
+    }
+}
+
+/// Proof that a commitment's blinding factor is the one derived from a device secret for an index
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DerivationProof {
+    // Nonce commitment `a_v*G + a_r*H` for the commitment equation
+    commitment_nonce: RistrettoPoint,
+    // Nonce commitment `a_r*(X + i*G)` for the derivation equation
+    derivation_nonce: RistrettoPoint,
+    // Response `a_v + c*v` for the committed value
+    value_response: Scalar,
+    // Response `a_r + c*r` for the blinding factor, shared by both equations
+    blinding_response: Scalar,
+}
+
+impl DerivationProof {
+    /// Prove that the commitment to `value` for `index` uses the blinding factor derived from the
+    /// device secret. Any [`ChallengeOracle`] can stand in for the transcript.
+    pub fn generate_proof<O: ChallengeOracle>(
+        device_secret: &DeviceSecret,
+        value: &Scalar,
+        index: u64,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut O,
+    ) -> Result<Self, Error> {
+        let blinding = device_secret.derive_blinding(index)?;
+        let commitment = generators.commit(value, &blinding);
+        let index_base = device_secret.get_commitment(generators)
+            + Scalar::from(index) * generators.value_generator;
+        absorb_statement(proof_transcript, generators, &index_base, &commitment);
+
+        let mut rng = proof_transcript.prover_rng(&commitment);
+        let (value_nonce, blinding_nonce) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let commitment_nonce = generators.commit(&value_nonce, &blinding_nonce);
+        let derivation_nonce = blinding_nonce * index_base;
+        proof_transcript.absorb_prover_message(&commitment_nonce);
+        proof_transcript.absorb_prover_message(&derivation_nonce);
+
+        let challenge_scalar = proof_transcript.next_challenge();
+        Ok(Self {
+            commitment_nonce,
+            derivation_nonce,
+            value_response: value_nonce + challenge_scalar * value,
+            blinding_response: blinding_nonce + challenge_scalar * blinding,
+        })
+    }
+
+    /// Verify that `commitment` uses the blinding factor derived for `index` from the secret
+    /// behind `device_commitment`
+    pub fn verify_proof<O: ChallengeOracle>(
+        &self,
+        device_commitment: &RistrettoPoint,
+        commitment: &RistrettoPoint,
+        index: u64,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut O,
+    ) -> Result<(), Error> {
+        validate_points(
+            proof_transcript,
+            &[
+                device_commitment,
+                commitment,
+                &self.commitment_nonce,
+                &self.derivation_nonce,
+            ],
+        )?;
+        let index_base = device_commitment + Scalar::from(index) * generators.value_generator;
+        absorb_statement(proof_transcript, generators, &index_base, commitment);
+        proof_transcript.absorb_prover_message(&self.commitment_nonce);
+        proof_transcript.absorb_prover_message(&self.derivation_nonce);
+        let challenge_scalar = proof_transcript.next_challenge();
+
+        // z_v*G + z_r*H == A_1 + c*C and z_r*(X + i*G) == A_2 + c*G
+        let commits = generators.commit(&self.value_response, &self.blinding_response)
+            == self.commitment_nonce + challenge_scalar * commitment;
+        let derives = self.blinding_response * index_base
+            == self.derivation_nonce + challenge_scalar * generators.value_generator;
+        if commits && derives {
+            return Ok(());
+        }
+        Err(Error::DerivationMismatch(index))
+    }
+}
+
+// Absorb the generators, the base `X + i*G` binding the device commitment and index, and the
+// commitment the proof is about
+fn absorb_statement<O: ChallengeOracle>(
+    proof_transcript: &mut O,
+    generators: &PedersenGenerators,
+    index_base: &RistrettoPoint,
+    commitment: &RistrettoPoint,
+) {
+    proof_transcript.absorb_prover_message(&generators.value_generator);
+    proof_transcript.absorb_prover_message(&generators.blinding_generator);
+    proof_transcript.absorb_prover_message(index_base);
+    proof_transcript.absorb_prover_message(commitment);
+}
+
+impl ProofScheme for DerivationProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use merlin::Transcript;
+
+    #[test]
+    fn test_commitments_with_derived_blinding_factors_verify() {
+        let generators = PedersenGenerators::default();
+        let device_secret = DeviceSecret::random(&mut rand::rngs::OsRng);
+        let device_commitment = device_secret.get_commitment(&generators);
+        let value = Scalar::from(21u64);
+
+        for index in [0, 1, 1 << 40] {
+            let commitment = device_secret.commit(&value, index, &generators).unwrap();
+            let proof = DerivationProof::generate_proof(
+                &device_secret,
+                &value,
+                index,
+                &generators,
+                &mut Transcript::new(b"test"),
+            )
+            .unwrap();
+            assert!(proof
+                .verify_proof(
+                    &device_commitment,
+                    &commitment,
+                    index,
+                    &generators,
+                    &mut Transcript::new(b"test")
+                )
+                .is_ok());
+
+            // The proof is bound to the index and the device
+            assert_eq!(
+                proof.verify_proof(
+                    &device_commitment,
+                    &commitment,
+                    index + 1,
+                    &generators,
+                    &mut Transcript::new(b"test")
+                ),
+                Err(Error::DerivationMismatch(index + 1))
+            );
+            let other_device =
+                DeviceSecret::random(&mut rand::rngs::OsRng).get_commitment(&generators);
+            assert!(proof
+                .verify_proof(
+                    &other_device,
+                    &commitment,
+                    index,
+                    &generators,
+                    &mut Transcript::new(b"test")
+                )
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_commitments_with_chosen_blinding_factors_are_rejected() {
+        let generators = PedersenGenerators::default();
+        let device_secret = DeviceSecret::random(&mut rand::rngs::OsRng);
+        let device_commitment = device_secret.get_commitment(&generators);
+        let value = Scalar::from(21u64);
+        let proof = DerivationProof::generate_proof(
+            &device_secret,
+            &value,
+            7,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+
+        // A commitment to the same value with a blinding factor of the device's choosing doesn't
+        // verify with a derivation proof
+        let covert_commitment = generators.commit(&value, &Scalar::from(0xc0ffeeu64));
+        assert_eq!(
+            proof.verify_proof(
+                &device_commitment,
+                &covert_commitment,
+                7,
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::DerivationMismatch(7))
+        );
+
+        // The index making the PRF undefined is rejected
+        let degenerate = DeviceSecret {
+            secret: SecretScalar::new(-Scalar::from(3u64)),
+        };
+        assert_eq!(
+            degenerate.derive_blinding(3),
+            Err(Error::InvalidDerivationIndex(3))
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Errors in merlin-transcripts proofs
+
+/// Proof errors
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Proof doesn't match
+    ProofMismatch(String, String),
+    /// Range proofs must cover between 1 and 64 bits and decompositions between 1 and 252 bits
+    InvalidBitLength(usize),
+    /// Value cannot be represented within the requested number of bits
+    ValueOutOfRange(u64, usize),
+    /// Scalar cannot be represented within the requested number of bits
+    ScalarOutOfRange(usize),
+    /// Bit or limb decomposition failed to verify
+    DecompositionMismatch,
+    /// Range proof failed to verify
+    RangeProofMismatch,
+    /// Transcripts given to a knowledge extractor start from different first messages
+    MismatchedCommitments,
+    /// Transcripts given to a knowledge extractor use the same challenge
+    RepeatedChallenge,
+    /// Usage counter has reached its licensed maximum
+    LicenseExhausted(u64),
+    /// Chunk index is beyond the number of committed chunks
+    ChunkOutOfRange(usize),
+    /// Chunks must be between 1 byte and the committed chunk length long
+    InvalidChunkLength(usize),
+    /// Chunk doesn't match the model commitment
+    ChunkMismatch(usize),
+    /// Reading weights or storing session state failed
+    Io(String),
+    /// Identity point rejected by a strictly validating security profile
+    IdentityPoint,
+    /// Feature index is beyond the number of committed features
+    FeatureOutOfRange(usize),
+    /// Disclosed value doesn't match the inference record
+    DisclosureMismatch,
+    /// Threshold must be between 1 and the number of custodians (threshold, custodians)
+    InvalidThreshold(usize, usize),
+    /// Key share at this custodian index doesn't match the escrow commitment
+    InvalidShare(u64),
+    /// Fewer shares were provided than are needed to recover the key (provided, threshold)
+    InsufficientShares(usize, usize),
+    /// Key share at this custodian index was provided more than once
+    RepeatedShare(u64),
+    /// Bucket schemas need a non-zero scale and at least two strictly increasing boundaries
+    InvalidBucketSchema,
+    /// Bucket index is beyond the number of buckets in the schema
+    BucketOutOfRange(usize),
+    /// Encoded output doesn't fall into any bucket of the schema
+    ValueOutsideSchema(u64),
+    /// Output can't be encoded as a non-negative fixed-point integer
+    InvalidFixedPoint,
+    /// Proof wasn't created with the committed model
+    ModelMismatch,
+    /// Other party's revealed commitments or partial response don't match what it committed to
+    CoproverMismatch,
+    /// Proof doesn't open the commitment
+    OpeningMismatch,
+    /// Prover's public key isn't part of the aggregate key
+    KeyNotAggregated,
+    /// Encoded proof has a wire format version this library doesn't support
+    UnsupportedProofVersion(u8),
+    /// Encoded proof belongs to a different proof scheme (scheme byte)
+    WrongProofScheme(u8),
+    /// Encoded proof has the wrong length or contains a non-canonical point or scalar
+    InvalidProofEncoding(encodings::Error),
+    /// Composed sigma protocol proof failed to verify
+    SigmaProofMismatch,
+    /// Time-lock puzzles need at least one squaring and a large enough modulus (squarings, bits)
+    InvalidTimeLock(u64, usize),
+    /// Opening recovered from a time-lock puzzle doesn't open its commitment
+    TimeLockMismatch,
+    /// Vector length isn't supported by the generators or doesn't match the other vector
+    InvalidVectorLength(usize),
+    /// Inner product proof has the wrong number of rounds or a degenerate challenge
+    InnerProductMismatch,
+    /// Value isn't a member of the set it is proven to belong to
+    ValueNotInSet(u64),
+    /// Set membership proof doesn't match the set or the commitment
+    SetMembershipMismatch,
+    /// Decision tree isn't rooted at its first node with every child after its parent, or its
+    /// thresholds don't fit the bit length
+    InvalidDecisionTree,
+    /// Decision tree proof doesn't match the committed tree or input
+    DecisionTreeMismatch,
+    /// Test set has no examples
+    EmptyTestSet,
+    /// Example proof doesn't belong to the committed test set at its position (index)
+    TestSetMismatch(usize),
+    /// Minimum accuracy is above 100 percent
+    InvalidAccuracy(u64),
+    /// Model doesn't classify enough examples correctly (correct, required)
+    AccuracyBelowThreshold(usize, usize),
+    /// Maximum disparity is above 100 percent
+    InvalidDisparity(u64),
+    /// Evaluation set has no examples of a subgroup (subgroup)
+    EmptySubgroup(usize),
+    /// Rates of the subgroups differ by more than the bound allows (disparity, margin)
+    DisparityAboveBound(u64, u64),
+    /// Spot checks have to check at least one statement in every check rate statements
+    InvalidCheckRate,
+    /// Device failed a spot check and its digests aren't accepted anymore (device)
+    DeviceFlagged(u64),
+    /// Statement (sequence) isn't awaiting a proof from a spot check challenge
+    NotChallenged(u64),
+    /// Proof of the selected statement (sequence) arrived after the deadline
+    DeadlineMissed(u64),
+    /// Spot check selection doesn't match its VRF output and the digests it covers
+    SelectionMismatch,
+    /// Session state is truncated, has trailing bytes or contains an unknown event
+    MalformedSession,
+    /// Recorded challenge (event index) isn't reproduced by replaying the session
+    SessionMismatch(usize),
+    /// No session state is stored under the session id
+    SessionNotFound(String),
+    /// Randomness tape is truncated or contains an unknown entry
+    MalformedTape,
+    /// Opened randomness tape doesn't match its digest, e.g. when opened with the wrong key
+    TapeMismatch,
+    /// Replay asked for a value the tape didn't record at that position (entry index)
+    ReplayMismatch(usize),
+    /// Device secret plus the index is zero, so no blinding factor can be derived (index)
+    InvalidDerivationIndex(u64),
+    /// Commitment doesn't use the blinding factor derived for the index (index)
+    DerivationMismatch(u64),
+    /// Commitments on the two curves aren't shown to open to the same value within the range
+    CrossCurveLinkMismatch,
+    /// Vocabulary has no labels
+    EmptyVocabulary,
+    /// Label appears more than once in a vocabulary
+    DuplicateLabel(String),
+    /// Vocabulary has no such label
+    UnknownLabel(String),
+    /// Merkle path doesn't prove the label's encoding under the vocabulary commitment
+    LabelMismatch(String),
+    /// Registry already has this or a later version of the vocabulary (name, version)
+    StaleVocabularyVersion(String, u64),
+    /// Audit log line isn't a well formed entry (line index)
+    MalformedAuditLog(usize),
+    /// Audit log uses a domain separator the replay wasn't given
+    UnknownAuditLabel(String),
+    /// Replaying an audit log doesn't reproduce the recorded entry (entry index)
+    AuditMismatch(usize),
+    /// Certificate or receipt is truncated, has an unknown version or an invalid verdict
+    MalformedCertificate,
+    /// Receipt certifies another envelope or statement than the one recomputed
+    ReceiptEnvelopeMismatch,
+    /// Receipt certifies other challenges than the ones recomputed from the envelope
+    ReceiptChallengeMismatch,
+    /// Index isn't one of the parties holding shares of the key (index)
+    UnknownParty(u64),
+    /// DKG broadcast doesn't prove knowledge of the dealt secret or uses another threshold (dealer)
+    InvalidDkgBroadcast(u64),
+    /// Share sent by a dealer doesn't match its DKG broadcast (dealer)
+    InvalidDealerShare(u64),
+    /// Signer's partial response doesn't answer the joint challenge with its key share (index)
+    InvalidPartialResponse(u64),
+    /// OR prover state was created for the other branch's witness than the one responding
+    MismatchedOrWitness,
+    /// Label of a test set example isn't 0 or 1 (label)
+    InvalidLabel(usize),
+}
@@ -0,0 +1,291 @@
+//! Proofs that a committed classifier treats two subgroups of a Merkle-committed evaluation set
+//! alike up to a public bound
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{ProofScheme, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{
+    BucketSchema, Error, ExampleProof, LabeledExample, LinearInferenceGenerators, LinearModel,
+    LinearModelCommitment, RangeProof, SimpleProofProtocol, TestSet, TestSetCommitment,
+};
+
+// Compliance rules often ask that a model treats protected subgroups alike, e.g. that the rate of
+// approvals of one subgroup is within a few percent of the rate of the other (demographic
+// parity). A model owner proves this about their committed model on an evaluation set committed
+// by an auditor, without revealing the model or any prediction:
+//
+// 1. Every example of the evaluation set is labeled with its subgroup, 0 or 1, and the model is a
+// binary classifier whose positive predictions are outputs in the second bucket of a public two
+// bucket schema.
+// 2. For every example the prover hides its prediction in a commitment `P = p*G + s*H` with an
+// [`ExampleProof`], exactly as accuracy proofs do. The subgroups are public, so the verifier
+// knows the sizes `n_0` and `n_1` of the subgroups.
+// 3. Aggregation: the sum of the `P` of a subgroup commits to its number of positive predictions
+// `c_0` or `c_1`.
+// 4. The rates differ by at most `bound` percent when `|c_0/n_0 - c_1/n_1| <= bound/100`, i.e.
+// when `d = c_0*n_1 - c_1*n_0` lies within `[-m, m]` for `m = floor(bound*n_0*n_1/100)`, as `d`
+// is an integer. The verifier computes a commitment to `d` as `n_1*sum(P_0) - n_0*sum(P_1)`.
+// 5. Comparison: range proofs show that `m + d` and `m - d` are both small and not negative,
+// which reveals nothing about `d` beyond being within the bound.
+
+// Domain separator for initializing fairness proof transcripts
+const FAIRNESS_DOMAIN_SEP: &[u8] = b"MODEL_FAIRNESS";
+
+// Create a transcript bound to the evaluation set, the model and the claimed bound
+fn disparity_transcript(
+    evaluation_set: &TestSetCommitment,
+    model: &LinearModelCommitment,
+    max_disparity: u64,
+) -> Transcript {
+    let mut transcript = Transcript::new(FAIRNESS_DOMAIN_SEP);
+    evaluation_set.bind_transcript(&mut transcript);
+    transcript.append_proof_value(&model.weights);
+    transcript.append_proof_value(&model.bias);
+    transcript.append_u64(b"MAX_DISPARITY", max_disparity);
+    transcript
+}
+
+// Get the largest allowed |d| for subgroups of sizes `n_0` and `n_1`
+fn disparity_margin(max_disparity: u64, sizes: [u64; 2]) -> Result<u64, Error> {
+    if max_disparity > 100 {
+        return Err(Error::InvalidDisparity(max_disparity));
+    }
+    if let Some(subgroup) = sizes.iter().position(|size| *size == 0) {
+        return Err(Error::EmptySubgroup(subgroup));
+    }
+    Ok(max_disparity * sizes[0] * sizes[1] / 100)
+}
+
+// Count the examples of each subgroup
+fn subgroup_sizes<'a>(
+    mut examples: impl Iterator<Item = &'a LabeledExample>,
+) -> Result<[u64; 2], Error> {
+    examples.try_fold([0; 2], |mut sizes, example| {
+        let subgroup = example.get_label();
+        *sizes
+            .get_mut(subgroup)
+            .ok_or(Error::BucketOutOfRange(subgroup))? += 1;
+        Ok(sizes)
+    })
+}
+
+/// Proof that the rates of positive predictions of a committed binary classifier on the two
+/// subgroups of a committed evaluation set differ by at most a bound
+#[derive(Clone, Debug)]
+pub struct FairnessProof {
+    // Proof of the hidden prediction on every example, in the order of the evaluation set
+    example_proofs: Vec<ExampleProof>,
+    // Proof that m + d isn't negative
+    lower_proof: RangeProof,
+    // Proof that m - d isn't negative
+    upper_proof: RangeProof,
+}
+
+impl FairnessProof {
+    /// Prove that the rates of outputs of `model` in the second bucket of `schema` on the examples
+    /// labeled 0 and on the examples labeled 1 differ by at most `max_disparity` percent
+    pub fn generate_proof(
+        model: &LinearModel,
+        evaluation_set: &TestSet,
+        schema: &BucketSchema,
+        max_disparity: u64,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<Self, Error> {
+        let sizes = subgroup_sizes(evaluation_set.get_examples().iter())?;
+        let margin = disparity_margin(max_disparity, sizes)?;
+
+        // Steps 2 and 3: hide the predictions and aggregate them per subgroup
+        let mut example_proofs = Vec::with_capacity(evaluation_set.get_examples().len());
+        let (mut positives, mut blindings) = ([0; 2], [Scalar::ZERO; 2]);
+        for (proof, prediction, prediction_blinding) in
+            ExampleProof::generate_proofs(model, evaluation_set, schema, generators)?
+        {
+            let subgroup = proof.get_example().get_label();
+            positives[subgroup] += prediction as u64;
+            blindings[subgroup] += prediction_blinding;
+            example_proofs.push(proof);
+        }
+
+        // Step 4: d = c_0*n_1 - c_1*n_0 has to be within [-m, m]
+        let disparity = (positives[0] * sizes[1]) as i128 - (positives[1] * sizes[0]) as i128;
+        if disparity.unsigned_abs() > margin as u128 {
+            return Err(Error::DisparityAboveBound(
+                disparity.unsigned_abs() as u64,
+                margin,
+            ));
+        }
+        let blinding =
+            Scalar::from(sizes[1]) * blindings[0] - Scalar::from(sizes[0]) * blindings[1];
+
+        // Step 5: m + d and m - d aren't negative
+        let bits = BucketSchema::bucket_bits(0, 2 * margin + 1);
+        let pedersen = &generators.pedersen_generators;
+        let mut transcript = disparity_transcript(
+            &evaluation_set.commitment(),
+            &model.commitment(generators)?,
+            max_disparity,
+        );
+        let lower_proof = RangeProof::generate_proof(
+            (margin as i128 + disparity) as u64,
+            &blinding,
+            bits,
+            pedersen,
+            &mut transcript,
+        )?;
+        let upper_proof = RangeProof::generate_proof(
+            (margin as i128 - disparity) as u64,
+            &-blinding,
+            bits,
+            pedersen,
+            &mut transcript,
+        )?;
+        Ok(Self {
+            example_proofs,
+            lower_proof,
+            upper_proof,
+        })
+    }
+
+    /// Verify the rates of positive predictions of the committed model on the two subgroups of
+    /// the committed evaluation set differ by at most `max_disparity` percent
+    pub fn verify_proof(
+        &self,
+        evaluation_set: &TestSetCommitment,
+        model: &LinearModelCommitment,
+        schema: &BucketSchema,
+        max_disparity: u64,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<(), Error> {
+        let predictions = ExampleProof::verify_proofs(
+            &self.example_proofs,
+            evaluation_set,
+            model,
+            schema,
+            generators,
+        )?;
+        let sizes = subgroup_sizes(self.example_proofs.iter().map(ExampleProof::get_example))?;
+        let margin = disparity_margin(max_disparity, sizes)?;
+
+        // Steps 3 and 4: aggregate the predictions per subgroup into a commitment to d
+        let mut positives = [RistrettoPoint::default(); 2];
+        for (proof, prediction) in self.example_proofs.iter().zip(predictions) {
+            positives[proof.get_example().get_label()] += prediction;
+        }
+        let disparity =
+            Scalar::from(sizes[1]) * positives[0] - Scalar::from(sizes[0]) * positives[1];
+
+        // Step 5: m + d and m - d aren't negative
+        let bits = BucketSchema::bucket_bits(0, 2 * margin + 1);
+        let pedersen = &generators.pedersen_generators;
+        let margin = Scalar::from(margin) * pedersen.value_generator;
+        let mut transcript = disparity_transcript(evaluation_set, model, max_disparity);
+        self.lower_proof
+            .verify_proof(&(margin + disparity), bits, pedersen, &mut transcript)?;
+        self.upper_proof
+            .verify_proof(&(margin - disparity), bits, pedersen, &mut transcript)
+    }
+
+    /// Get the proofs of the hidden predictions, in the order of the evaluation set
+    pub fn get_example_proofs(&self) -> &[ExampleProof] {
+        &self.example_proofs
+    }
+}
+
+impl ProofScheme for FairnessProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encodings::HashBackend;
+
+    // Positive predictions for outputs of at least 0.5 on a scale of 100 * 100
+    fn schema() -> BucketSchema {
+        BucketSchema::new(10000, &[0, 5000, 20000]).unwrap()
+    }
+
+    // y = 0.5*x_0 + 0.25*x_1 + 0.1
+    fn model() -> LinearModel {
+        LinearModel::new(&[50, 25], 1000).unwrap()
+    }
+
+    // Subgroup 0 gets 2 of 3 positive predictions and subgroup 1 gets 1 of 2, rates a sixth apart
+    fn evaluation_set() -> TestSet {
+        let examples = [
+            (vec![100, 40], 0),
+            (vec![10, 20], 0),
+            (vec![60, 100], 0),
+            (vec![0, 0], 1),
+            (vec![90, 90], 1),
+        ];
+        let examples = examples
+            .iter()
+            .map(|(features, subgroup)| LabeledExample::new(features, *subgroup).unwrap())
+            .collect();
+        TestSet::new(examples, HashBackend::Sha256).unwrap()
+    }
+
+    #[test]
+    fn test_fairness_proof_verifies_for_bounds_above_the_disparity() {
+        let generators = LinearInferenceGenerators::new(2);
+        let (model, evaluation_set) = (model(), evaluation_set());
+        let commitment = evaluation_set.commitment();
+        let model_commitment = model.commitment(&generators).unwrap();
+        let proof =
+            FairnessProof::generate_proof(&model, &evaluation_set, &schema(), 20, &generators)
+                .unwrap();
+        assert_eq!(proof.get_example_proofs().len(), 5);
+        assert_eq!(
+            proof.verify_proof(&commitment, &model_commitment, &schema(), 20, &generators),
+            Ok(())
+        );
+
+        // d = 2*2 - 1*3 = 1 and m = floor(bound*6/100), so a bound of 16 percent is too tight
+        assert!(proof
+            .verify_proof(&commitment, &model_commitment, &schema(), 16, &generators)
+            .is_err());
+        assert_eq!(
+            FairnessProof::generate_proof(&model, &evaluation_set, &schema(), 16, &generators)
+                .err(),
+            Some(Error::DisparityAboveBound(1, 0))
+        );
+        assert_eq!(
+            FairnessProof::generate_proof(&model, &evaluation_set, &schema(), 101, &generators)
+                .err(),
+            Some(Error::InvalidDisparity(101))
+        );
+    }
+
+    #[test]
+    fn test_fairness_proof_rejects_other_models_and_single_subgroups() {
+        let generators = LinearInferenceGenerators::new(2);
+        let (model, evaluation_set) = (model(), evaluation_set());
+        let proof =
+            FairnessProof::generate_proof(&model, &evaluation_set, &schema(), 50, &generators)
+                .unwrap();
+        let other_model = LinearModel::new(&[50, 25], 1000)
+            .unwrap()
+            .commitment(&generators)
+            .unwrap();
+        assert!(proof
+            .verify_proof(
+                &evaluation_set.commitment(),
+                &other_model,
+                &schema(),
+                50,
+                &generators
+            )
+            .is_err());
+
+        // Rates can't be compared without examples of both subgroups
+        let examples = evaluation_set.get_examples()[..3].to_vec();
+        let single_subgroup = TestSet::new(examples, HashBackend::Sha256).unwrap();
+        assert_eq!(
+            FairnessProof::generate_proof(&model, &single_subgroup, &schema(), 50, &generators)
+                .err(),
+            Some(Error::EmptySubgroup(1))
+        );
+    }
+}
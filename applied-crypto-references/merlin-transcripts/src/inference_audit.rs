@@ -0,0 +1,275 @@
+//! Committed inference records which can later be selectively disclosed to an auditor
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use merlin::Transcript;
+
+use crate::{BlindingProof, Error, ModelCommitment, PedersenGenerators, SimpleProofProtocol};
+
+// Regulators may need to inspect individual inferences long after they were proven, e.g. to check
+// which features a credit decision was based on. Re-running the inference would need the model and
+// all inputs, and disclosing the whole record would reveal more than the audit needs. Instead the
+// prover commits to every value of the inference when it is proven and later opens only the values
+// the auditor asks for:
+//
+// 1. Each feature value `x_i` and the output `y` get their own Pedersen commitment, e.g.
+// `C_i = x_i*G + r_i*H`.
+// 2. The record commitment binds the model commitment and all value commitments together, and is
+// what gets published or signed alongside the original inference proof.
+// 3. To disclose a value `v` at a position, the prover reveals `v` and proves that `C - v*G` is a
+// multiple of `H`. The proof's transcript is bound to the record digest and the position, which
+// links the disclosed value to the original record without revealing the blinding factor.
+//
+// Undisclosed values stay hidden since their commitments are perfectly hiding, so an audit reveals
+// nothing beyond the values it asks for.
+
+// Domain separator for hashing a record into its digest
+const INFERENCE_RECORD_DOMAIN_SEP: &[u8] = b"INFERENCE_RECORD";
+
+// Domain separator for initializing disclosure transcripts
+const DISCLOSURE_DOMAIN_SEP: &[u8] = b"INFERENCE_DISCLOSURE";
+
+/// Position of a value within an inference record
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RecordField {
+    /// Input feature at an index
+    Feature(usize),
+    /// Output of the inference
+    Output,
+}
+
+/// Public commitment to the inputs and output of an inference run on a committed model
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InferenceRecord {
+    // Commitment to the model the inference was run on
+    model: ModelCommitment,
+    // Pedersen commitment to each feature value
+    feature_commitments: Vec<RistrettoPoint>,
+    // Pedersen commitment to the output
+    output_commitment: RistrettoPoint,
+}
+
+impl InferenceRecord {
+    /// Get the commitment to the model the inference was run on
+    pub fn get_model(&self) -> ModelCommitment {
+        self.model
+    }
+
+    /// Get the number of committed features
+    pub fn num_features(&self) -> usize {
+        self.feature_commitments.len()
+    }
+
+    /// Hash the record into the digest which disclosures are linked to
+    pub fn digest(&self) -> [u8; 32] {
+        let mut transcript = Transcript::new(INFERENCE_RECORD_DOMAIN_SEP);
+        transcript.append_message(b"MODEL_ROOT", &self.model.get_root());
+        transcript.append_u64(b"NUM_FEATURES", self.feature_commitments.len() as u64);
+        for commitment in self.feature_commitments.iter() {
+            transcript.append_proof_value(commitment);
+        }
+        transcript.append_proof_value(&self.output_commitment);
+        let mut digest = [0; 32];
+        transcript.challenge_bytes(INFERENCE_RECORD_DOMAIN_SEP, &mut digest);
+        digest
+    }
+
+    // Get the commitment at a position of the record
+    fn commitment(&self, field: RecordField) -> Result<RistrettoPoint, Error> {
+        match field {
+            RecordField::Feature(index) => self
+                .feature_commitments
+                .get(index)
+                .copied()
+                .ok_or(Error::FeatureOutOfRange(index)),
+            RecordField::Output => Ok(self.output_commitment),
+        }
+    }
+
+    // Create a transcript linking a disclosure to the record and the disclosed position
+    fn disclosure_transcript(&self, field: RecordField) -> Transcript {
+        let mut transcript = Transcript::new(DISCLOSURE_DOMAIN_SEP);
+        transcript.append_message(INFERENCE_RECORD_DOMAIN_SEP, &self.digest());
+        match field {
+            RecordField::Feature(index) => transcript.append_u64(b"FEATURE", index as u64),
+            RecordField::Output => transcript.append_message(b"OUTPUT", b""),
+        }
+        transcript
+    }
+
+    /// Verify a value disclosed from the record
+    pub fn verify_disclosure(
+        &self,
+        disclosure: &Disclosure,
+        generators: &PedersenGenerators,
+    ) -> Result<(), Error> {
+        let commitment = self.commitment(disclosure.field)?;
+        disclosure
+            .proof
+            .verify_proof(
+                &(commitment - disclosure.value * generators.value_generator),
+                generators,
+                &mut self.disclosure_transcript(disclosure.field),
+            )
+            .map_err(|_| Error::DisclosureMismatch)
+    }
+}
+
+/// Value opened from an inference record along with the proof linking it to the record
+#[derive(Clone, Copy, Debug)]
+pub struct Disclosure {
+    // Position of the disclosed value
+    field: RecordField,
+    // Disclosed value
+    value: Scalar,
+    // Proof the position's commitment minus the value is a multiple of the blinding generator
+    proof: BlindingProof,
+}
+
+impl Disclosure {
+    /// Get the position of the disclosed value
+    pub fn get_field(&self) -> RecordField {
+        self.field
+    }
+
+    /// Get the disclosed value
+    pub fn get_value(&self) -> Scalar {
+        self.value
+    }
+}
+
+/// Prover's side of an inference record holding the committed values and their blindings
+#[derive(Clone, Debug)]
+pub struct CommittedInference {
+    record: InferenceRecord,
+    generators: PedersenGenerators,
+    // Feature values and their blindings
+    features: Vec<(Scalar, Scalar)>,
+    // Output value and its blinding
+    output: (Scalar, Scalar),
+}
+
+impl CommittedInference {
+    /// Commit to the features and output of an inference run on a committed model
+    pub fn new(
+        model: ModelCommitment,
+        features: &[Scalar],
+        output: Scalar,
+        generators: &PedersenGenerators,
+    ) -> Self {
+        let mut rng = rand::rngs::OsRng;
+        let features: Vec<(Scalar, Scalar)> = features
+            .iter()
+            .map(|value| (*value, Scalar::random(&mut rng)))
+            .collect();
+        let output = (output, Scalar::random(&mut rng));
+        let record = InferenceRecord {
+            model,
+            feature_commitments: features
+                .iter()
+                .map(|(value, blinding)| generators.commit(value, blinding))
+                .collect(),
+            output_commitment: generators.commit(&output.0, &output.1),
+        };
+        Self {
+            record,
+            generators: *generators,
+            features,
+            output,
+        }
+    }
+
+    /// Get the public record commitment
+    pub fn get_record(&self) -> &InferenceRecord {
+        &self.record
+    }
+
+    /// Disclose the value at a position of the record
+    pub fn disclose(&self, field: RecordField) -> Result<Disclosure, Error> {
+        let (value, blinding) = match field {
+            RecordField::Feature(index) => *self
+                .features
+                .get(index)
+                .ok_or(Error::FeatureOutOfRange(index))?,
+            RecordField::Output => self.output,
+        };
+        let proof = BlindingProof::generate_proof(
+            &blinding,
+            &self.generators,
+            &mut self.record.disclosure_transcript(field),
+        );
+        Ok(Disclosure {
+            field,
+            value,
+            proof,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommittedModel;
+
+    fn committed_inference(generators: &PedersenGenerators) -> CommittedInference {
+        let model = CommittedModel::commit(&[7; 100], 32, generators).unwrap();
+        let features: Vec<Scalar> = [12u64, 40, 3].iter().map(|v| Scalar::from(*v)).collect();
+        CommittedInference::new(
+            model.get_commitment(),
+            &features,
+            Scalar::from(1u64),
+            generators,
+        )
+    }
+
+    #[test]
+    fn test_selected_values_are_disclosed_against_the_record() {
+        let generators = PedersenGenerators::default();
+        let inference = committed_inference(&generators);
+        let record = inference.get_record();
+        assert_eq!(record.num_features(), 3);
+
+        for field in [RecordField::Feature(1), RecordField::Output] {
+            let disclosure = inference.disclose(field).unwrap();
+            assert!(record.verify_disclosure(&disclosure, &generators).is_ok());
+        }
+        let disclosure = inference.disclose(RecordField::Feature(1)).unwrap();
+        assert_eq!(disclosure.get_value(), Scalar::from(40u64));
+        assert_eq!(
+            inference.disclose(RecordField::Feature(3)).err(),
+            Some(Error::FeatureOutOfRange(3))
+        );
+    }
+
+    #[test]
+    fn test_disclosures_dont_transfer_to_other_values_positions_or_records() {
+        let generators = PedersenGenerators::default();
+        let inference = committed_inference(&generators);
+        let record = inference.get_record();
+        let disclosure = inference.disclose(RecordField::Feature(0)).unwrap();
+
+        let wrong_value = Disclosure {
+            value: Scalar::from(13u64),
+            ..disclosure
+        };
+        let wrong_field = Disclosure {
+            field: RecordField::Feature(2),
+            ..disclosure
+        };
+        for forged in [wrong_value, wrong_field] {
+            assert_eq!(
+                record.verify_disclosure(&forged, &generators),
+                Err(Error::DisclosureMismatch)
+            );
+        }
+
+        // Another inference on the same values has a different record
+        let other_inference = committed_inference(&generators);
+        assert_ne!(other_inference.get_record().digest(), record.digest());
+        assert_eq!(
+            other_inference
+                .get_record()
+                .verify_disclosure(&disclosure, &generators),
+            Err(Error::DisclosureMismatch)
+        );
+    }
+}
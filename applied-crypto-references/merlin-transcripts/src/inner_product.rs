@@ -0,0 +1,468 @@
+//! Vector Pedersen commitments and the Bulletproofs inner product argument
+
+use curve25519_dalek::{
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use encodings::{PointEncoding, ProofScheme, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{challenge_oracle::validate_points, ChallengeOracle, Error};
+
+// A vector Pedersen commitment commits to a whole vector `a = (a_0, .., a_(n-1))` with a single
+// point `C = a_0*G_0 + .. + a_(n-1)*G_(n-1) + r*B`, or `C = <a, G> + r*B` for short. It binds the
+// committer to every entry as long as nobody knows a discrete log relation between the generators,
+// which is why they are all derived by hashing to the curve.
+//
+// The inner product argument from the Bulletproofs paper (Bünz et al., protocol 2) proves
+// knowledge of two vectors `a` and `b` such that
+//
+//     P = <a, G> + <b, H> + <a, b>*U
+//
+// with a proof of only `2*log2(n)` points and two scalars. Each round halves the vectors:
+// 1. The prover splits `a`, `b`, `G` and `H` into low and high halves and sends the cross terms
+// `L = <a_lo, G_hi> + <b_hi, H_lo> + <a_lo, b_hi>*U` and `R = <a_hi, G_lo> + <b_lo, H_hi> +
+// <a_hi, b_lo>*U`.
+// 2. The verifier sends a challenge `x`.
+// 3. Both sides fold the vectors into `a' = x*a_lo + x^-1*a_hi`, `b' = x^-1*b_lo + x*b_hi`,
+// `G' = x^-1*G_lo + x*G_hi` and `H' = x*H_lo + x^-1*H_hi`, which satisfy the same relation for
+// `P' = x^2*L + P + x^-2*R`.
+// After `log2(n)` rounds the vectors have a single entry and the prover sends `a` and `b` in the
+// clear. Instead of folding the generators round by round, the verifier expands the challenges
+// into the coefficients each generator ends up with and checks everything in one multiscalar
+// multiplication.
+//
+// To prove a claimed inner product `c`, the verifier first picks a challenge `w` and the argument
+// runs on `P + c*(w*U)` with the generator `w*U` (protocol 1), so a prover can't shift value
+// between the vector terms and the product term.
+//
+// The argument is not zero knowledge: the final scalars and cross terms leak information about
+// `a` and `b`. Protocols using it to keep vectors secret first blind them, as Bulletproofs range
+// proofs do.
+
+// Domain separator for the transcript used to derive the generators
+const INNER_PRODUCT_DOMAIN_SEP: &[u8] = b"INNER_PRODUCT_GENERATORS";
+
+/// Generators for vector Pedersen commitments and inner product arguments of up to `capacity`
+/// entries
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InnerProductGenerators {
+    // Generators the entries of the first vector are multiplied by
+    g: Vec<RistrettoPoint>,
+    // Generators the entries of the second vector are multiplied by
+    h: Vec<RistrettoPoint>,
+    // Generator the inner product is multiplied by
+    u: RistrettoPoint,
+    // Generator the blinding scalar of a vector commitment is multiplied by
+    blinding: RistrettoPoint,
+}
+
+impl InnerProductGenerators {
+    /// Derive generators for vectors of up to `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        let mut transcript = Transcript::new(INNER_PRODUCT_DOMAIN_SEP);
+        let mut next_generator = |label: &'static [u8], index: usize| {
+            transcript.append_u64(b"INDEX", index as u64);
+            let mut buf = [0; 64];
+            transcript.challenge_bytes(label, &mut buf);
+            RistrettoPoint::from_uniform_bytes(&buf)
+        };
+        let g = (0..capacity).map(|i| next_generator(b"G", i)).collect();
+        let h = (0..capacity).map(|i| next_generator(b"H", i)).collect();
+        Self {
+            g,
+            h,
+            u: next_generator(b"U", 0),
+            blinding: next_generator(b"BLINDING", 0),
+        }
+    }
+
+    /// Get the maximum number of entries in a committed vector
+    pub fn capacity(&self) -> usize {
+        self.g.len()
+    }
+
+    /// Commit to a vector with a blinding scalar as `<values, G> + blinding*B`
+    pub fn commit(&self, values: &[Scalar], blinding: &Scalar) -> Result<RistrettoPoint, Error> {
+        if values.len() > self.capacity() {
+            return Err(Error::InvalidVectorLength(values.len()));
+        }
+        Ok(RistrettoPoint::vartime_multiscalar_mul(
+            values.iter().chain([blinding]),
+            self.g[..values.len()].iter().chain([&self.blinding]),
+        ))
+    }
+
+    /// Commit to a vector with a blinding scalar against the second set of generators as
+    /// `<values, H> + blinding*B`, which pairs with a commitment from
+    /// [`InnerProductGenerators::commit`] in an inner product argument
+    pub fn commit_second(
+        &self,
+        values: &[Scalar],
+        blinding: &Scalar,
+    ) -> Result<RistrettoPoint, Error> {
+        if values.len() > self.capacity() {
+            return Err(Error::InvalidVectorLength(values.len()));
+        }
+        Ok(RistrettoPoint::vartime_multiscalar_mul(
+            values.iter().chain([blinding]),
+            self.h[..values.len()].iter().chain([&self.blinding]),
+        ))
+    }
+
+    /// Commit to a pair of vectors without blinding as `<a, G> + <b, H>`, the statement an
+    /// inner product argument is made for
+    pub fn commit_pair(&self, a: &[Scalar], b: &[Scalar]) -> Result<RistrettoPoint, Error> {
+        if a.len() != b.len() || a.len() > self.capacity() {
+            return Err(Error::InvalidVectorLength(a.len().max(b.len())));
+        }
+        Ok(RistrettoPoint::vartime_multiscalar_mul(
+            a.iter().chain(b),
+            self.g[..a.len()].iter().chain(&self.h[..b.len()]),
+        ))
+    }
+
+    // Get the generators of the first and second vectors and the blinding generator
+    pub(crate) fn get_generators(&self) -> (&[RistrettoPoint], &[RistrettoPoint], RistrettoPoint) {
+        (&self.g, &self.h, self.blinding)
+    }
+
+    // Check a vector length is a power of two the generators can cover
+    pub(crate) fn check_length(&self, len: usize) -> Result<(), Error> {
+        if !len.is_power_of_two() || len > self.capacity() {
+            return Err(Error::InvalidVectorLength(len));
+        }
+        Ok(())
+    }
+}
+
+/// Proof that the vectors behind `P = <a, G> + <b, H>` have the inner product `c`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InnerProductProof {
+    // Cross terms L of every folding round
+    left: Vec<RistrettoPoint>,
+    // Cross terms R of every folding round
+    right: Vec<RistrettoPoint>,
+    // Single entry left of the first vector
+    a: Scalar,
+    // Single entry left of the second vector
+    b: Scalar,
+}
+
+impl InnerProductProof {
+    /// Prove the inner product of `a` and `b`, whose lengths must be equal and a power of two
+    pub fn generate_proof<O: ChallengeOracle>(
+        a: &[Scalar],
+        b: &[Scalar],
+        generators: &InnerProductGenerators,
+        proof_transcript: &mut O,
+    ) -> Result<Self, Error> {
+        if a.len() != b.len() {
+            return Err(Error::InvalidVectorLength(a.len().max(b.len())));
+        }
+        generators.check_length(a.len())?;
+        let commitment = generators.commit_pair(a, b)?;
+        let u = product_generator(
+            &commitment,
+            &inner_product(a, b),
+            generators,
+            proof_transcript,
+        );
+
+        let (mut a, mut b) = (a.to_vec(), b.to_vec());
+        let mut g = generators.g[..a.len()].to_vec();
+        let mut h = generators.h[..b.len()].to_vec();
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        while a.len() > 1 {
+            let half = a.len() / 2;
+            let (a_lo, a_hi) = a.split_at(half);
+            let (b_lo, b_hi) = b.split_at(half);
+            let (g_lo, g_hi) = g.split_at(half);
+            let (h_lo, h_hi) = h.split_at(half);
+
+            let l = RistrettoPoint::vartime_multiscalar_mul(
+                a_lo.iter().chain(b_hi).chain([&inner_product(a_lo, b_hi)]),
+                g_hi.iter().chain(h_lo).chain([&u]),
+            );
+            let r = RistrettoPoint::vartime_multiscalar_mul(
+                a_hi.iter().chain(b_lo).chain([&inner_product(a_hi, b_lo)]),
+                g_lo.iter().chain(h_hi).chain([&u]),
+            );
+            proof_transcript.absorb_prover_message(&l);
+            proof_transcript.absorb_prover_message(&r);
+            left.push(l);
+            right.push(r);
+
+            let x = proof_transcript.next_challenge();
+            let x_inv = x.invert();
+            a = fold(a_lo, a_hi, &x, &x_inv);
+            b = fold(b_lo, b_hi, &x_inv, &x);
+            g = fold(g_lo, g_hi, &x_inv, &x);
+            h = fold(h_lo, h_hi, &x, &x_inv);
+        }
+        Ok(Self {
+            left,
+            right,
+            a: a[0],
+            b: b[0],
+        })
+    }
+
+    /// Verify that the vectors of length `len` behind `commitment = <a, G> + <b, H>` have the
+    /// inner product `product`
+    pub fn verify_proof<O: ChallengeOracle>(
+        &self,
+        commitment: &RistrettoPoint,
+        product: &Scalar,
+        len: usize,
+        generators: &InnerProductGenerators,
+        proof_transcript: &mut O,
+    ) -> Result<(), Error> {
+        generators.check_length(len)?;
+        if self.left.len() != len.trailing_zeros() as usize || self.right.len() != self.left.len() {
+            return Err(Error::InnerProductMismatch);
+        }
+        validate_points(proof_transcript, &[commitment])?;
+        let u = product_generator(commitment, product, generators, proof_transcript);
+
+        let mut challenges = Vec::with_capacity(self.left.len());
+        for (l, r) in self.left.iter().zip(&self.right) {
+            proof_transcript.absorb_prover_message(l);
+            proof_transcript.absorb_prover_message(r);
+            let x = proof_transcript.next_challenge();
+            if x == Scalar::ZERO {
+                return Err(Error::InnerProductMismatch);
+            }
+            challenges.push(x);
+        }
+        let challenges_inv: Vec<Scalar> = challenges.iter().map(Scalar::invert).collect();
+
+        // Coefficient of G_i after all rounds: the product over rounds of x^-1 if bit j of `i`
+        // (counting from the most significant of the log2(n) bits) is 0 and x if it is 1. H_i
+        // gets the inverse coefficient.
+        let rounds = challenges.len();
+        let g_coefficients: Vec<Scalar> = (0..len)
+            .map(|i| {
+                (0..rounds)
+                    .map(|j| match (i >> (rounds - 1 - j)) & 1 {
+                        0 => challenges_inv[j],
+                        _ => challenges[j],
+                    })
+                    .product()
+            })
+            .collect();
+        let h_coefficients: Vec<Scalar> = g_coefficients.iter().map(Scalar::invert).collect();
+
+        let folded_commitment = RistrettoPoint::vartime_multiscalar_mul(
+            challenges
+                .iter()
+                .map(|x| x * x)
+                .chain(challenges_inv.iter().map(|x| x * x))
+                .chain([Scalar::ONE, *product]),
+            self.left.iter().chain(&self.right).chain([commitment, &u]),
+        );
+        let expected = RistrettoPoint::vartime_multiscalar_mul(
+            g_coefficients
+                .iter()
+                .map(|s| self.a * s)
+                .chain(h_coefficients.iter().map(|s| self.b * s))
+                .chain([self.a * self.b]),
+            generators.g[..len]
+                .iter()
+                .chain(&generators.h[..len])
+                .chain([&u]),
+        );
+        let verified = folded_commitment == expected;
+
+        // Recompute P' - a*G' - b*H' - a*b*U == 0 as a single multiscalar multiplication as a
+        // debug build cross-check of the verification equation
+        debug_assert_eq!(
+            verified,
+            RistrettoPoint::vartime_multiscalar_mul(
+                challenges
+                    .iter()
+                    .map(|x| x * x)
+                    .chain(challenges_inv.iter().map(|x| x * x))
+                    .chain([Scalar::ONE, product - self.a * self.b])
+                    .chain(g_coefficients.iter().map(|s| -(self.a * s)))
+                    .chain(h_coefficients.iter().map(|s| -(self.b * s))),
+                self.left
+                    .iter()
+                    .chain(&self.right)
+                    .chain([commitment, &u])
+                    .chain(&generators.g[..len])
+                    .chain(&generators.h[..len]),
+            )
+            .is_identity(),
+            "verification equation disagrees with its multiscalar form"
+        );
+        if !verified {
+            return Err(Error::ProofMismatch(
+                hex::encode(folded_commitment.encode()),
+                hex::encode(expected.encode()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the cross terms L and R of every round and the final entries of both vectors
+    pub fn get_proof_values(&self) -> (&[RistrettoPoint], &[RistrettoPoint], Scalar, Scalar) {
+        (&self.left, &self.right, self.a, self.b)
+    }
+}
+
+/// Create a proof object from published cross terms and final entries
+impl From<(Vec<RistrettoPoint>, Vec<RistrettoPoint>, Scalar, Scalar)> for InnerProductProof {
+    fn from(proof_values: (Vec<RistrettoPoint>, Vec<RistrettoPoint>, Scalar, Scalar)) -> Self {
+        Self {
+            left: proof_values.0,
+            right: proof_values.1,
+            a: proof_values.2,
+            b: proof_values.3,
+        }
+    }
+}
+
+/// Inner product `<a, b>` of two scalar vectors of the same length
+pub fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter().zip(b).map(|(a_i, b_i)| a_i * b_i).sum()
+}
+
+// Bind the statement to the transcript and derive the generator `w*U` the product is multiplied by
+fn product_generator<O: ChallengeOracle>(
+    commitment: &RistrettoPoint,
+    product: &Scalar,
+    generators: &InnerProductGenerators,
+    proof_transcript: &mut O,
+) -> RistrettoPoint {
+    proof_transcript.absorb_prover_message(commitment);
+    proof_transcript.absorb_prover_message(&(product * generators.u));
+    proof_transcript.next_challenge() * generators.u
+}
+
+// Fold the halves of a vector into `lo_factor*lo + hi_factor*hi`
+fn fold<T>(lo: &[T], hi: &[T], lo_factor: &Scalar, hi_factor: &Scalar) -> Vec<T>
+where
+    T: Copy + std::ops::Add<Output = T>,
+    for<'a> &'a Scalar: std::ops::Mul<T, Output = T>,
+{
+    lo.iter()
+        .zip(hi)
+        .map(|(lo, hi)| lo_factor * *lo + hi_factor * *hi)
+        .collect()
+}
+
+impl ProofScheme for InnerProductProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_vector(len: usize) -> Vec<Scalar> {
+        (0..len)
+            .map(|_| Scalar::random(&mut rand::rngs::OsRng))
+            .collect()
+    }
+
+    #[test]
+    fn test_inner_product_proof_verifies_the_dot_product() {
+        let generators = InnerProductGenerators::new(16);
+        for len in [1, 2, 8, 16] {
+            let (a, b) = (random_vector(len), random_vector(len));
+            let commitment = generators.commit_pair(&a, &b).unwrap();
+            let proof = InnerProductProof::generate_proof(
+                &a,
+                &b,
+                &generators,
+                &mut Transcript::new(b"test"),
+            )
+            .unwrap();
+            assert_eq!(
+                proof.get_proof_values().0.len(),
+                len.trailing_zeros() as usize
+            );
+            assert!(proof
+                .verify_proof(
+                    &commitment,
+                    &inner_product(&a, &b),
+                    len,
+                    &generators,
+                    &mut Transcript::new(b"test")
+                )
+                .is_ok());
+        }
+
+        // Vector commitments are binding to every entry and hidden by the blinding
+        let values = random_vector(4);
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let commitment = generators.commit(&values, &blinding).unwrap();
+        let mut changed = values.clone();
+        changed[3] += Scalar::ONE;
+        assert_ne!(generators.commit(&changed, &blinding).unwrap(), commitment);
+        assert_eq!(
+            generators.commit(&random_vector(17), &blinding),
+            Err(Error::InvalidVectorLength(17))
+        );
+    }
+
+    #[test]
+    fn test_inner_product_proof_rejects_wrong_statements() {
+        let generators = InnerProductGenerators::new(8);
+        let (a, b) = (random_vector(8), random_vector(8));
+        let commitment = generators.commit_pair(&a, &b).unwrap();
+        let product = inner_product(&a, &b);
+        let proof =
+            InnerProductProof::generate_proof(&a, &b, &generators, &mut Transcript::new(b"test"))
+                .unwrap();
+
+        // A different claimed product
+        assert!(proof
+            .verify_proof(
+                &commitment,
+                &(product + Scalar::ONE),
+                8,
+                &generators,
+                &mut Transcript::new(b"test")
+            )
+            .is_err());
+        // A different length
+        assert_eq!(
+            proof.verify_proof(
+                &commitment,
+                &product,
+                4,
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::InnerProductMismatch)
+        );
+        // Tampered cross terms
+        let (left, right, a_final, b_final) = proof.get_proof_values();
+        let mut right = right.to_vec();
+        right.swap(0, 1);
+        assert!(
+            InnerProductProof::from((left.to_vec(), right, a_final, b_final))
+                .verify_proof(
+                    &commitment,
+                    &product,
+                    8,
+                    &generators,
+                    &mut Transcript::new(b"test")
+                )
+                .is_err()
+        );
+        // Lengths which aren't powers of two
+        assert_eq!(
+            InnerProductProof::generate_proof(
+                &a[..6],
+                &b[..6],
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::InvalidVectorLength(6))
+        );
+    }
+}
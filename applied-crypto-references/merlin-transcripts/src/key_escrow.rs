@@ -0,0 +1,240 @@
+//! Threshold escrow of device proving keys with verifiable shares and a proof of recovery
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use encodings::{evaluate_commitments, evaluate_polynomial, Ristretto, SecretScalar};
+use merlin::Transcript;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{Error, SimpleProofProtocol, SimpleSchnorrProof};
+
+// A device signs its attestations with a proving key `k` whose public key `K = k*G` is known to the
+// fleet. If the device is lost, the key is gone with it and nobody can prove anything more about
+// its attestation history. At provisioning time the key is therefore escrowed among n custodians
+// so that any t of them can recover it, while fewer than t learn nothing about it:
+//
+// 1. Shamir sharing: the device picks a random polynomial f(x) = k + a_1*x + .. + a_(t-1)*x^(t-1)
+// and hands custodian i the share s_i = f(i). Any t shares determine f and therefore k = f(0) by
+// Lagrange interpolation, while t - 1 shares are consistent with every possible key.
+// 2. Feldman verification: the device also publishes A_j = a_j*G for every coefficient, where
+// A_0 = K is the public key. Custodian i checks s_i*G = sum A_j*i^j, so a device can't hand out
+// shares which don't recover its actual key.
+// 3. Recovery proof: whoever recovers the key proves knowledge of k for K with a Schnorr proof
+// bound to the escrow commitment, so the fleet can check the recovery succeeded without the key
+// being revealed to it.
+//
+// The polynomial arithmetic of the first two steps and the interpolation are the generic ones of
+// the `encodings` crate, instantiated with the Ristretto group.
+
+// Generator the proving key and the Feldman commitments are multiplied by
+const G: RistrettoPoint = RISTRETTO_BASEPOINT_POINT;
+
+// Domain separator for initializing key recovery transcripts
+const KEY_RECOVERY_DOMAIN_SEP: &[u8] = b"ESCROWED_KEY_RECOVERY";
+
+/// Share of an escrowed key held by a single custodian, overwritten with zeros when it is dropped
+#[derive(Clone, Eq, PartialEq)]
+pub struct KeyShare {
+    // Point the share polynomial was evaluated at, starting at 1
+    pub(crate) index: u64,
+    // Evaluation of the share polynomial at the index
+    pub(crate) share: SecretScalar<Scalar>,
+}
+
+impl KeyShare {
+    /// Get the custodian index of the share
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+}
+
+// Only the index is printed, the share is redacted by `SecretScalar`
+impl std::fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("index", &self.index)
+            .field("share", &self.share)
+            .finish()
+    }
+}
+
+impl Zeroize for KeyShare {
+    fn zeroize(&mut self) {
+        self.share.zeroize();
+    }
+}
+
+// The share is a `SecretScalar`, which zeroizes itself when the key share is dropped
+impl ZeroizeOnDrop for KeyShare {}
+
+/// Public commitments to the coefficients of the polynomial a key was shared with
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowCommitment {
+    // A_j = a_j*G for every coefficient, starting with the public key
+    pub(crate) coefficient_commitments: Vec<RistrettoPoint>,
+}
+
+impl EscrowCommitment {
+    /// Get the public key of the escrowed key
+    pub fn get_public_key(&self) -> RistrettoPoint {
+        self.coefficient_commitments[0]
+    }
+
+    /// Get the number of shares needed to recover the key
+    pub fn get_threshold(&self) -> usize {
+        self.coefficient_commitments.len()
+    }
+
+    /// Check a custodian's share is consistent with the escrowed key
+    pub fn verify_share(&self, share: &KeyShare) -> Result<(), Error> {
+        if share.index == 0 || share.share.get_scalar() * G != self.evaluate(share.index) {
+            return Err(Error::InvalidShare(share.index));
+        }
+        Ok(())
+    }
+
+    // Evaluate the committed polynomial at an index in the exponent, giving the public key of the
+    // share at that index: sum A_j*i^j
+    pub(crate) fn evaluate(&self, index: u64) -> RistrettoPoint {
+        evaluate_commitments::<Ristretto>(&self.coefficient_commitments, index)
+    }
+
+    // Create a transcript bound to the escrow commitment
+    fn recovery_transcript(&self) -> Transcript {
+        let mut transcript = Transcript::new(KEY_RECOVERY_DOMAIN_SEP);
+        for commitment in self.coefficient_commitments.iter() {
+            transcript.append_proof_value(commitment);
+        }
+        transcript
+    }
+
+    /// Recover the escrowed key from at least a threshold of verified shares, returning it along
+    /// with a proof of the recovery
+    pub fn recover(&self, shares: &[KeyShare]) -> Result<(Scalar, SimpleSchnorrProof), Error> {
+        for (i, share) in shares.iter().enumerate() {
+            self.verify_share(share)?;
+            if shares[..i].iter().any(|other| other.index == share.index) {
+                return Err(Error::RepeatedShare(share.index));
+            }
+        }
+        if shares.len() < self.get_threshold() {
+            return Err(Error::InsufficientShares(
+                shares.len(),
+                self.get_threshold(),
+            ));
+        }
+
+        let shares = &shares[..self.get_threshold()];
+        let indices: Vec<u64> = shares.iter().map(KeyShare::get_index).collect();
+        let key = shares.iter().fold(Scalar::ZERO, |acc, share| {
+            acc + share.share.get_scalar() * lagrange_coefficient(share.index, &indices)
+        });
+        let proof = SimpleSchnorrProof::generate_proof(&key, &mut self.recovery_transcript());
+        Ok((key, proof))
+    }
+
+    /// Verify a proof that the escrowed key was recovered
+    pub fn verify_recovery(&self, proof: &SimpleSchnorrProof) -> Result<(), Error> {
+        let mut proof = *proof;
+        proof
+            .verify_proof(&self.get_public_key(), &mut self.recovery_transcript())
+            .map(|_| ())
+    }
+}
+
+// Coefficient of the share at `index` when interpolating f(0) from the shares at `indices`, which
+// callers check are distinct
+pub(crate) fn lagrange_coefficient(index: u64, indices: &[u64]) -> Scalar {
+    encodings::lagrange_coefficient::<Ristretto>(index, indices).expect("indices are distinct")
+}
+
+/// Split a proving key into `num_custodians` shares, any `threshold` of which recover it
+pub fn escrow_key(
+    private_key: &Scalar,
+    threshold: usize,
+    num_custodians: usize,
+) -> Result<(EscrowCommitment, Vec<KeyShare>), Error> {
+    if threshold == 0 || threshold > num_custodians {
+        return Err(Error::InvalidThreshold(threshold, num_custodians));
+    }
+    let mut rng = rand::rngs::OsRng;
+    let mut coefficients = vec![*private_key];
+    coefficients.extend((1..threshold).map(|_| Scalar::random(&mut rng)));
+
+    let shares = (1..=num_custodians as u64)
+        .map(|index| KeyShare {
+            index,
+            share: SecretScalar::new(evaluate_polynomial::<Ristretto>(&coefficients, index)),
+        })
+        .collect();
+    let commitment = EscrowCommitment {
+        coefficient_commitments: coefficients
+            .iter()
+            .map(|coefficient| coefficient * G)
+            .collect(),
+    };
+    // The coefficients determine the key just like the shares do
+    coefficients.zeroize();
+    Ok((commitment, shares))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_keypair;
+
+    #[test]
+    fn test_any_threshold_of_custodians_recovers_the_key() {
+        let (private_key, public_key) = generate_keypair();
+        let (commitment, shares) = escrow_key(&private_key, 3, 5).unwrap();
+        assert_eq!(commitment.get_public_key(), public_key);
+        for share in shares.iter() {
+            assert!(commitment.verify_share(share).is_ok());
+        }
+        let printed = format!("{:?}", shares[0]);
+        assert!(printed.contains("SecretScalar(<redacted>)"));
+        assert!(!printed.contains(&format!("{:?}", shares[0].share.get_scalar())));
+
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset_shares: Vec<KeyShare> = subset.iter().map(|i| shares[*i].clone()).collect();
+            let (recovered, proof) = commitment.recover(&subset_shares).unwrap();
+            assert_eq!(recovered, private_key);
+            assert!(commitment.verify_recovery(&proof).is_ok());
+        }
+        assert_eq!(
+            commitment.recover(&shares[..2]).err(),
+            Some(Error::InsufficientShares(2, 3))
+        );
+        assert_eq!(
+            commitment
+                .recover(&[shares[0].clone(), shares[1].clone(), shares[0].clone()])
+                .err(),
+            Some(Error::RepeatedShare(1))
+        );
+    }
+
+    #[test]
+    fn test_tampered_shares_and_foreign_recovery_proofs_are_rejected() {
+        let (private_key, _) = generate_keypair();
+        let (commitment, mut shares) = escrow_key(&private_key, 2, 3).unwrap();
+        shares[1].share = SecretScalar::new(shares[1].share.get_scalar() + Scalar::ONE);
+        assert_eq!(
+            commitment.verify_share(&shares[1]),
+            Err(Error::InvalidShare(2))
+        );
+        assert_eq!(
+            commitment.recover(&shares).err(),
+            Some(Error::InvalidShare(2))
+        );
+        assert_eq!(
+            escrow_key(&private_key, 4, 3).err(),
+            Some(Error::InvalidThreshold(4, 3))
+        );
+
+        // A recovery proof for another escrow of the same key doesn't verify
+        let (other_commitment, other_shares) = escrow_key(&private_key, 2, 3).unwrap();
+        let (_, proof) = other_commitment.recover(&other_shares).unwrap();
+        assert!(commitment.verify_recovery(&proof).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Knowledge extractors recovering the witness of Schnorr-style proofs from two accepting
+//! transcripts
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+use crate::Error;
+
+// A proof of knowledge is only convincing if a prover who can answer the verifier's challenges
+// must actually know the witness. Sigma protocols show this through "special soundness": given two
+// accepting transcripts `(A, c_1, r_1)` and `(A, c_2, r_2)` with the same first message and
+// different challenges, the witness can be computed directly.
+//
+// For a Schnorr proof of `K = k*G` both transcripts satisfy `r_i*G = A + c_i*K`. Subtracting the
+// equations gives `(r_1 - r_2)*G = (c_1 - c_2)*K`, so `k = (r_1 - r_2) / (c_1 - c_2)`.
+//
+// An extractor gets these transcripts by running a prover, rewinding it to just after it sent
+// `A` and giving it a different challenge. A `SimulatedOracle` seeded the same way twice does
+// exactly this. The same algebra is why a real prover must never reuse the random scalar `a` for
+// two proofs: anyone seeing both proofs can run the extractor.
+
+/// Accepting transcript `(A, c, r)` of a Schnorr-style proof of knowledge of a discrete log
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AcceptingTranscript {
+    // Prover's first message A = a*G
+    public_scalar: RistrettoPoint,
+    // Verifier's challenge c
+    challenge: Scalar,
+    // Prover's response r = a + c*k
+    response: Scalar,
+}
+
+impl AcceptingTranscript {
+    /// Create a transcript from a published proof pair `(r, A)` and the challenge it was
+    /// verified against
+    pub fn new(proof_pair: (Scalar, RistrettoPoint), challenge: Scalar) -> Self {
+        Self {
+            public_scalar: proof_pair.1,
+            challenge,
+            response: proof_pair.0,
+        }
+    }
+}
+
+/// Recover the witness `k` of a Schnorr-style proof from two accepting transcripts which share a
+/// first message but have different challenges
+pub fn extract_witness(
+    first: &AcceptingTranscript,
+    second: &AcceptingTranscript,
+) -> Result<Scalar, Error> {
+    if first.public_scalar != second.public_scalar {
+        return Err(Error::MismatchedCommitments);
+    }
+    if first.challenge == second.challenge {
+        return Err(Error::RepeatedChallenge);
+    }
+    Ok((first.response - second.response) * (first.challenge - second.challenge).invert())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generate_keypair, BlindingProof, PedersenGenerators, SimpleSchnorrProof, SimulatedOracle,
+    };
+
+    #[test]
+    fn test_rewound_schnorr_prover_reveals_private_key() {
+        let (private_key, public_key) = generate_keypair();
+        let challenges = [Scalar::from(3u64), Scalar::from(11u64)];
+
+        // Run the prover twice from the same seed, verifying each transcript against its challenge
+        let transcripts: Vec<AcceptingTranscript> = challenges
+            .iter()
+            .map(|challenge| {
+                let mut oracle = SimulatedOracle::with_challenges(b"extract", &[*challenge]);
+                let mut proof = SimpleSchnorrProof::generate_proof(&private_key, &mut oracle);
+                let mut verifier_oracle =
+                    SimulatedOracle::with_challenges(b"extract", &[*challenge]);
+                assert!(proof
+                    .verify_proof(&public_key, &mut verifier_oracle)
+                    .is_ok());
+                AcceptingTranscript::new(proof.get_proof_pair(), *challenge)
+            })
+            .collect();
+
+        assert_eq!(
+            extract_witness(&transcripts[0], &transcripts[1]).unwrap(),
+            private_key
+        );
+        assert_eq!(
+            extract_witness(&transcripts[0], &transcripts[0]),
+            Err(Error::RepeatedChallenge)
+        );
+    }
+
+    #[test]
+    fn test_extractor_recovers_blinding_factors_and_requires_shared_commitment() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let first_proof = BlindingProof::generate_proof(
+            &blinding,
+            &generators,
+            &mut SimulatedOracle::with_challenges(b"extract", &[Scalar::ONE]),
+        );
+        let second_proof = BlindingProof::generate_proof(
+            &blinding,
+            &generators,
+            &mut SimulatedOracle::with_challenges(b"extract", &[Scalar::from(2u64)]),
+        );
+        let first = AcceptingTranscript::new(first_proof.get_proof_pair(), Scalar::ONE);
+        let second = AcceptingTranscript::new(second_proof.get_proof_pair(), Scalar::from(2u64));
+        assert_eq!(extract_witness(&first, &second).unwrap(), blinding);
+
+        // Proofs with independent randomness don't share a first message
+        let fresh_proof = BlindingProof::generate_proof(
+            &blinding,
+            &generators,
+            &mut SimulatedOracle::with_challenges(b"other seed", &[Scalar::from(2u64)]),
+        );
+        let fresh = AcceptingTranscript::new(fresh_proof.get_proof_pair(), Scalar::from(2u64));
+        assert_eq!(
+            extract_witness(&first, &fresh),
+            Err(Error::MismatchedCommitments)
+        );
+    }
+}
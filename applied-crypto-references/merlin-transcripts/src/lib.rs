@@ -1,9 +1,103 @@
+mod audit_transcript;
+mod challenge_oracle;
+mod collaborative;
+mod commit_and_prove;
+mod decision_tree;
+mod decomposition;
+mod derived_blinding;
+mod dleq;
+mod error;
+mod fairness;
+mod inference_audit;
+mod inner_product;
+mod key_escrow;
+mod knowledge_extractor;
+mod linear_inference;
 mod merlin_non_interactive_proof;
+mod model_accuracy;
+mod model_commitment;
+mod ordered_inference;
+mod pedersen;
+mod randomness_tape;
+mod range_proof;
+mod recomputation;
+mod reproducible;
+mod session;
+mod set_membership;
+mod sigma;
+mod spot_check;
+mod synthetic;
+mod time_lock;
+mod translation;
+mod threshold;
 mod tutorials;
+mod usage_counter;
+mod vocabulary;
 
 pub use crate::{
-    merlin_non_interactive_proof::{Error, SimpleProofProtocol, SimpleSchnorrProof},
+    audit_transcript::{AuditEntry, AuditLog, AuditOperation, AuditTranscript},
+    challenge_oracle::{
+        AccountedOracle, ChallengeOracle, ProfiledTranscript, SimulatedOracle, TracedOracle,
+        TranscriptEvent,
+    },
+    collaborative::{
+        JointOpeningParty, JointOpeningProof, PartialResponse, PartyReveal, WitnessShare,
+    },
+    commit_and_prove::{PedersenOpening, RangeBackend, SigmaBackend},
+    decision_tree::{
+        CommittedNode, DecisionTree, DecisionTreeCommitment, DecisionTreeProof, DecisionTreeProver,
+        DecisionTreeVerifier, TreeInput, TreeNode,
+    },
+    decomposition::{BitDecomposition, LimbDecomposition, MAX_DECOMPOSITION_BITS},
+    derived_blinding::{DerivationProof, DeviceSecret},
+    dleq::DleqProof,
+    error::Error,
+    fairness::FairnessProof,
+    inference_audit::{CommittedInference, Disclosure, InferenceRecord, RecordField},
+    inner_product::{inner_product, InnerProductGenerators, InnerProductProof},
+    key_escrow::{escrow_key, EscrowCommitment, KeyShare},
+    knowledge_extractor::{extract_witness, AcceptingTranscript},
+    linear_inference::{
+        FeatureVector, LinearInferenceGenerators, LinearInferenceProof, LinearModel,
+        LinearModelCommitment,
+    },
+    merlin_non_interactive_proof::{
+        AggregateKey, AggregateProver, AggregatedSchnorrProof, PartialSchnorrResponse,
+        SchnorrProof, SimpleProofProtocol, SimpleSchnorrProof, SCHNORR_PROOF_LEN,
+    },
+    model_accuracy::{AccuracyProof, ExampleProof, LabeledExample, TestSet, TestSetCommitment},
+    model_commitment::{
+        ChunkOpening, CommittedModel, ModelCommitment, ModelProof, DEFAULT_CHUNK_LEN,
+    },
+    ordered_inference::{BucketSchema, OrderedInferenceProof},
+    pedersen::{BlindingProof, PedersenGenerators},
+    randomness_tape::{
+        RandomnessTape, RecordedOracle, ReplayedOracle, SealedTape, TapeEntry, TapeReplay,
+    },
+    range_proof::{FixedRangeProof, RangeProof, SUPPORTED_RANGE_BITS},
+    recomputation::{
+        RecomputableProof, RecomputationCertificate, VerificationReceipt, CERTIFICATE_LEN,
+    },
+    reproducible::{ReproducibleSeed, SeededTranscript},
+    session::{
+        FileSessionStore, MemorySessionStore, ResumableTranscript, SessionEvent, SessionStore,
+    },
+    set_membership::SetMembershipProof,
+    sigma::{
+        And, DiscreteLog, DiscreteLogStatement, Or, OrProverState, OrResponse, OrWitness,
+        SigmaProof, SigmaProtocol,
+    },
+    spot_check::{DeviceStatus, FlagReason, SpotCheckChallenge, SpotChecker},
+    synthetic::{DenseLayer, SyntheticGenerator, SyntheticLinearModel, SyntheticMlp},
+    threshold::{
+        DkgBroadcast, DkgParticipant, PartialThresholdResponse, ThresholdKeyShare,
+        ThresholdPublicKey, ThresholdSigner,
+    },
+    translation::{CrossCurveLinkProof, TranslatedStatement},
+    time_lock::{TimeLockedOpening, DEFAULT_MODULUS_BITS},
     tutorials::{merlin_basics_tutorial, merlin_non_interactive_proof_tutorial},
+    usage_counter::{LicenseVerifier, UsageCounter, UsageProof},
+    vocabulary::{LabelPath, Vocabulary, VocabularyCommitment, VocabularyRegistry},
 };
 
 pub(crate) use crate::merlin_non_interactive_proof::generate_keypair;
@@ -0,0 +1,559 @@
+//! Zero knowledge proofs that a committed linear model evaluated on committed features gives an
+//! output in a public bucket
+
+use curve25519_dalek::{
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use encodings::{PointEncoding, ProofScheme, SecurityLevel};
+use tracing::instrument;
+
+use crate::{
+    challenge_oracle::validate_points, inner_product, BucketSchema, ChallengeOracle, Error,
+    InnerProductGenerators, InnerProductProof, OrderedInferenceProof, PedersenGenerators,
+};
+
+// Linear and logistic regression models compute `y = <w, x> + b` from a feature vector `x`, a
+// weight vector `w` and a bias `b` (logistic regression then applies a sigmoid, which is monotonic,
+// so bucketing `y` by the inverse sigmoid of the bucket boundaries gives the same answer). This
+// module proves such an inference while keeping the features, the model and the exact output
+// secret:
+//
+// 1. Features and weights are fixed-point integers, e.g. 0.25 at a scale of 100 is 25. The
+// features are committed as `C_x = <x, G> + a_x*B` and the weights as `C_w = <w, H> + a_w*B` with
+// the vector generators of an [`InnerProductGenerators`], and the bias as a Pedersen commitment
+// `C_b = b*G_0 + r_b*H_0`. The output is on the scale of the feature scale times the weight scale.
+// 2. The prover commits to the output as `C_y = y*G_0 + r_y*H_0`, so `C_y - C_b` commits to
+// `t_0 = <x, w>` with blinding `r_y - r_b`.
+// 3. To show `t_0` is the inner product without revealing `x` and `w`, the prover blinds them
+// into `l(X) = x + s_x*X` and `r(X) = w + s_w*X` with random vectors `s_x` and `s_w`, whose
+// commitment `S = <s_x, G> + <s_w, H> + p*B` it sends along with commitments `T_1` and `T_2` to
+// the coefficients of `t(X) = <l(X), r(X)> = t_0 + t_1*X + t_2*X^2`.
+// 4. For a challenge `c`, the prover sends `t(c)` with its blinding and the blinding
+// `m = a_x + a_w + p*c` of `l(c)` and `r(c)`. The verifier checks
+// `t(c)*G_0 + blinding*H_0 = C_y - C_b + c*T_1 + c^2*T_2`, which ties `t(c)` to `t_0`.
+// 5. The prover shows `<l(c), r(c)> = t(c)` for the vectors behind
+// `C_x + C_w + c*S - m*B = <l(c), G> + <r(c), H>` with an [`InnerProductProof`]. The argument
+// itself leaks its vectors, but `l(c)` and `r(c)` are masked by the random `s_x` and `s_w`.
+// 6. Finally an [`OrderedInferenceProof`] on `C_y` shows which bucket of a public schema the
+// output falls into, without revealing it any further.
+//
+// This is the construction Bulletproofs range proofs use to blind their inner product argument.
+// Vectors are padded with zeros to a power of two, which doesn't change their commitments.
+
+/// Generators for committing to linear models and feature vectors and proving inferences
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinearInferenceGenerators {
+    /// Generators for the feature and weight vectors
+    pub vector_generators: InnerProductGenerators,
+    /// Generators for the bias, the output and the inner product coefficients
+    pub pedersen_generators: PedersenGenerators,
+}
+
+impl LinearInferenceGenerators {
+    /// Derive generators for models with up to `max_features` features
+    pub fn new(max_features: usize) -> Self {
+        Self {
+            vector_generators: InnerProductGenerators::new(max_features.next_power_of_two()),
+            pedersen_generators: PedersenGenerators::default(),
+        }
+    }
+
+    /// Get Pedersen generators for committing to the feature at `index` on its own. Commitments
+    /// to every feature of a vector sum to the vector's commitment when their blindings sum to the
+    /// vector's blinding, which ties proofs about single features to inferences on the vector.
+    pub fn feature_generators(&self, index: usize) -> Result<PedersenGenerators, Error> {
+        let (g, _, b) = self.vector_generators.get_generators();
+        let value_generator = *g.get(index).ok_or(Error::InvalidVectorLength(index + 1))?;
+        Ok(PedersenGenerators {
+            value_generator,
+            blinding_generator: b,
+        })
+    }
+}
+
+/// Linear model with fixed-point weights and bias, committed with random blindings
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinearModel {
+    weights: Vec<i64>,
+    bias: i64,
+    weights_blinding: Scalar,
+    bias_blinding: Scalar,
+}
+
+/// Public commitment to a [`LinearModel`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LinearModelCommitment {
+    /// Vector commitment to the weights
+    pub weights: RistrettoPoint,
+    /// Pedersen commitment to the bias
+    pub bias: RistrettoPoint,
+    /// Number of features the model takes
+    pub num_features: usize,
+}
+
+impl LinearModel {
+    /// Create a model from at least one weight and a bias, with fresh blindings
+    pub fn new(weights: &[i64], bias: i64) -> Result<Self, Error> {
+        if weights.is_empty() {
+            return Err(Error::InvalidVectorLength(0));
+        }
+        let mut rng = rand::rngs::OsRng;
+        Ok(Self {
+            weights: weights.to_vec(),
+            bias,
+            weights_blinding: Scalar::random(&mut rng),
+            bias_blinding: Scalar::random(&mut rng),
+        })
+    }
+
+    /// Get the commitment to the model
+    pub fn commitment(
+        &self,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<LinearModelCommitment, Error> {
+        Ok(LinearModelCommitment {
+            weights: generators
+                .vector_generators
+                .commit_second(&to_scalars(&self.weights), &self.weights_blinding)?,
+            bias: generators
+                .pedersen_generators
+                .commit(&to_scalar(self.bias), &self.bias_blinding),
+            num_features: self.weights.len(),
+        })
+    }
+
+    /// Evaluate the model on features of the same scale, rejecting outputs which are negative or
+    /// don't fit into a `u64`
+    pub fn evaluate(&self, features: &FeatureVector) -> Result<u64, Error> {
+        if features.values.len() != self.weights.len() {
+            return Err(Error::InvalidVectorLength(features.values.len()));
+        }
+        self.weights
+            .iter()
+            .zip(&features.values)
+            .try_fold(self.bias as i128, |sum, (weight, feature)| {
+                sum.checked_add(*weight as i128 * *feature as i128)
+            })
+            .and_then(|output| u64::try_from(output).ok())
+            .ok_or(Error::InvalidFixedPoint)
+    }
+}
+
+/// Fixed-point feature vector committed with a random blinding
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureVector {
+    values: Vec<i64>,
+    blinding: Scalar,
+}
+
+impl FeatureVector {
+    /// Create a feature vector with a fresh blinding
+    pub fn new(values: &[i64]) -> Self {
+        Self {
+            values: values.to_vec(),
+            blinding: Scalar::random(&mut rand::rngs::OsRng),
+        }
+    }
+
+    /// Create a feature vector with a chosen blinding, e.g. the sum of the blindings of commitments
+    /// to its features from [`LinearInferenceGenerators::feature_generators`]
+    pub fn with_blinding(values: &[i64], blinding: Scalar) -> Self {
+        Self {
+            values: values.to_vec(),
+            blinding,
+        }
+    }
+
+    /// Get the vector commitment to the features
+    pub fn commitment(
+        &self,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<RistrettoPoint, Error> {
+        generators
+            .vector_generators
+            .commit(&to_scalars(&self.values), &self.blinding)
+    }
+}
+
+/// Proof that a committed linear model evaluated on committed features gives an output in a
+/// bucket of a [`BucketSchema`]
+#[derive(Clone, Debug)]
+pub struct LinearInferenceProof {
+    // Commitment S to the blinding vectors of the features and weights
+    blinding_commitment: RistrettoPoint,
+    // Commitments T_1 and T_2 to the coefficients of t(X)
+    coefficient_commitments: (RistrettoPoint, RistrettoPoint),
+    // t(c), the inner product of the blinded vectors at the challenge
+    evaluation: Scalar,
+    // Blinding of t(c) relative to the Pedersen generators
+    evaluation_blinding: Scalar,
+    // Blinding m of the vector commitment to l(c) and r(c)
+    vector_blinding: Scalar,
+    // Proof that <l(c), r(c)> = t(c)
+    inner_product_proof: InnerProductProof,
+    // Proof of the bucket of the committed output, which carries the output commitment
+    bucket_proof: OrderedInferenceProof,
+}
+
+impl LinearInferenceProof {
+    /// Prove the bucket of the output of a committed model on committed features
+    #[instrument(name = "prove", level = "debug", skip_all)]
+    pub fn generate_proof<O: ChallengeOracle>(
+        model: &LinearModel,
+        features: &FeatureVector,
+        schema: &BucketSchema,
+        generators: &LinearInferenceGenerators,
+        proof_transcript: &mut O,
+    ) -> Result<Self, Error> {
+        Self::generate_proof_with_output_blinding(
+            model,
+            features,
+            schema,
+            generators,
+            proof_transcript,
+        )
+        .map(|(proof, _)| proof)
+    }
+
+    // Prove the bucket of the output, returning the proof along with the blinding of the output
+    // commitment so other proofs can build on the committed output
+    pub(crate) fn generate_proof_with_output_blinding<O: ChallengeOracle>(
+        model: &LinearModel,
+        features: &FeatureVector,
+        schema: &BucketSchema,
+        generators: &LinearInferenceGenerators,
+        proof_transcript: &mut O,
+    ) -> Result<(Self, Scalar), Error> {
+        let output = model.evaluate(features)?;
+        let len = features.values.len().next_power_of_two();
+        let vector_generators = &generators.vector_generators;
+        let pedersen = &generators.pedersen_generators;
+        vector_generators.check_length(len)?;
+        let (g, h, b) = vector_generators.get_generators();
+
+        // Steps 1 and 2: commit to the output and bind the statement to the transcript
+        let (bucket_proof, output_blinding) =
+            OrderedInferenceProof::commit_and_prove(output, schema, pedersen)?;
+        let model_commitment = model.commitment(generators)?;
+        absorb_statement(
+            proof_transcript,
+            &features.commitment(generators)?,
+            &model_commitment,
+            &bucket_proof.get_commitment(),
+        );
+
+        // Step 3: blind the vectors and commit to the coefficients of t(X)
+        let mut x = to_scalars(&features.values);
+        let mut w = to_scalars(&model.weights);
+        x.resize(len, Scalar::ZERO);
+        w.resize(len, Scalar::ZERO);
+        let mut rng = proof_transcript.prover_rng(&bucket_proof.get_commitment());
+        let s_x: Vec<Scalar> = (0..len).map(|_| Scalar::random(&mut rng)).collect();
+        let s_w: Vec<Scalar> = (0..len).map(|_| Scalar::random(&mut rng)).collect();
+        let [p, tau_1, tau_2] = [(); 3].map(|_| Scalar::random(&mut rng));
+        let blinding_commitment = RistrettoPoint::vartime_multiscalar_mul(
+            s_x.iter().chain(&s_w).chain([&p]),
+            g[..len].iter().chain(&h[..len]).chain([&b]),
+        );
+        let t_1 = inner_product(&x, &s_w) + inner_product(&s_x, &w);
+        let t_2 = inner_product(&s_x, &s_w);
+        let coefficient_commitments =
+            (pedersen.commit(&t_1, &tau_1), pedersen.commit(&t_2, &tau_2));
+        proof_transcript.absorb_prover_message(&blinding_commitment);
+        proof_transcript.absorb_prover_message(&coefficient_commitments.0);
+        proof_transcript.absorb_prover_message(&coefficient_commitments.1);
+
+        // Step 4: evaluate everything at the challenge
+        let c = proof_transcript.next_challenge();
+        let l: Vec<Scalar> = x.iter().zip(&s_x).map(|(x, s)| x + s * c).collect();
+        let r: Vec<Scalar> = w.iter().zip(&s_w).map(|(w, s)| w + s * c).collect();
+        let evaluation_blinding = tau_2 * c * c + tau_1 * c + output_blinding - model.bias_blinding;
+        let vector_blinding = features.blinding + model.weights_blinding + p * c;
+
+        // Step 5: prove the inner product of the blinded vectors
+        let inner_product_proof =
+            InnerProductProof::generate_proof(&l, &r, vector_generators, proof_transcript)?;
+        let proof = Self {
+            blinding_commitment,
+            coefficient_commitments,
+            evaluation: inner_product(&l, &r),
+            evaluation_blinding,
+            vector_blinding,
+            inner_product_proof,
+            bucket_proof,
+        };
+        Ok((proof, output_blinding))
+    }
+
+    /// Verify the output of the committed model on the committed features lies in the claimed
+    /// bucket of the schema, returning the bucket
+    #[instrument(name = "verify", level = "debug", skip_all)]
+    pub fn verify_proof<O: ChallengeOracle>(
+        &self,
+        features_commitment: &RistrettoPoint,
+        model_commitment: &LinearModelCommitment,
+        schema: &BucketSchema,
+        generators: &LinearInferenceGenerators,
+        proof_transcript: &mut O,
+    ) -> Result<usize, Error> {
+        let len = model_commitment.num_features.next_power_of_two();
+        let vector_generators = &generators.vector_generators;
+        let pedersen = &generators.pedersen_generators;
+        vector_generators.check_length(len)?;
+        let (_, _, b) = vector_generators.get_generators();
+        let output_commitment = self.bucket_proof.get_commitment();
+        let (t_1_commitment, t_2_commitment) = self.coefficient_commitments;
+        validate_points(
+            proof_transcript,
+            &[
+                features_commitment,
+                &model_commitment.weights,
+                &model_commitment.bias,
+                &output_commitment,
+                &self.blinding_commitment,
+            ],
+        )?;
+
+        // Step 6: the output commitment lies in its bucket
+        let bucket = self.bucket_proof.verify_proof(schema, pedersen)?;
+
+        // Step 4: t(c) matches the committed inner product and coefficients
+        absorb_statement(
+            proof_transcript,
+            features_commitment,
+            model_commitment,
+            &output_commitment,
+        );
+        proof_transcript.absorb_prover_message(&self.blinding_commitment);
+        proof_transcript.absorb_prover_message(&t_1_commitment);
+        proof_transcript.absorb_prover_message(&t_2_commitment);
+        let c = proof_transcript.next_challenge();
+        let evaluation_commitment = pedersen.commit(&self.evaluation, &self.evaluation_blinding);
+        let expected =
+            output_commitment - model_commitment.bias + c * t_1_commitment + c * c * t_2_commitment;
+        let verified = evaluation_commitment == expected;
+
+        // Recompute t(c)*G_0 + blinding*H_0 - C_y + C_b - c*T_1 - c^2*T_2 == 0 with a
+        // multiscalar multiplication as a debug build cross-check of the verification equation
+        debug_assert_eq!(
+            verified,
+            RistrettoPoint::vartime_multiscalar_mul(
+                [
+                    self.evaluation,
+                    self.evaluation_blinding,
+                    -Scalar::ONE,
+                    Scalar::ONE,
+                    -c,
+                    -(c * c)
+                ],
+                [
+                    pedersen.value_generator,
+                    pedersen.blinding_generator,
+                    output_commitment,
+                    model_commitment.bias,
+                    t_1_commitment,
+                    t_2_commitment
+                ],
+            )
+            .is_identity(),
+            "verification equation disagrees with its multiscalar form"
+        );
+        if !verified {
+            return Err(Error::ProofMismatch(
+                hex::encode(evaluation_commitment.encode()),
+                hex::encode(expected.encode()),
+            ));
+        }
+
+        // Step 5: the blinded vectors have the inner product t(c)
+        let vector_commitment =
+            features_commitment + model_commitment.weights + c * self.blinding_commitment
+                - self.vector_blinding * b;
+        self.inner_product_proof.verify_proof(
+            &vector_commitment,
+            &self.evaluation,
+            len,
+            vector_generators,
+            proof_transcript,
+        )?;
+        Ok(bucket)
+    }
+
+    /// Get the commitment to the output
+    pub fn get_output_commitment(&self) -> RistrettoPoint {
+        self.bucket_proof.get_commitment()
+    }
+}
+
+// Absorb the feature, model and output commitments of the statement being proven
+fn absorb_statement<O: ChallengeOracle>(
+    proof_transcript: &mut O,
+    features_commitment: &RistrettoPoint,
+    model_commitment: &LinearModelCommitment,
+    output_commitment: &RistrettoPoint,
+) {
+    for point in [
+        features_commitment,
+        &model_commitment.weights,
+        &model_commitment.bias,
+        output_commitment,
+    ] {
+        proof_transcript.absorb_prover_message(point);
+    }
+}
+
+// Convert a signed fixed-point integer into a scalar, mapping negative values to their additive
+// inverse
+fn to_scalar(value: i64) -> Scalar {
+    match value < 0 {
+        true => -Scalar::from(value.unsigned_abs()),
+        false => Scalar::from(value as u64),
+    }
+}
+
+// Convert signed fixed-point integers into scalars
+fn to_scalars(values: &[i64]) -> Vec<Scalar> {
+    values.iter().copied().map(to_scalar).collect()
+}
+
+impl ProofScheme for LinearInferenceProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use merlin::Transcript;
+
+    // Output buckets on a scale of 100 * 100: [0, 0.5), [0.5, 1.0) and [1.0, 100)
+    fn schema() -> BucketSchema {
+        BucketSchema::new(10000, &[0, 5000, 10000, 1_000_000]).unwrap()
+    }
+
+    #[test]
+    fn test_linear_inference_proves_the_output_bucket() {
+        let generators = LinearInferenceGenerators::new(8);
+        // y = 0.5*x_0 - 0.25*x_1 + 1.5*x_2 + 0.1
+        let model = LinearModel::new(&[50, -25, 150], 1000).unwrap();
+        for (features, bucket) in [
+            (vec![100, 80, 10], 1),
+            (vec![10, 40, 0], 0),
+            (vec![100; 3], 2),
+        ] {
+            let features = FeatureVector::new(&features);
+            let proof = LinearInferenceProof::generate_proof(
+                &model,
+                &features,
+                &schema(),
+                &generators,
+                &mut Transcript::new(b"test"),
+            )
+            .unwrap();
+            assert_eq!(
+                proof.verify_proof(
+                    &features.commitment(&generators).unwrap(),
+                    &model.commitment(&generators).unwrap(),
+                    &schema(),
+                    &generators,
+                    &mut Transcript::new(b"test"),
+                ),
+                Ok(bucket)
+            );
+        }
+
+        // Outputs which are negative can't be bucketed
+        let negative = FeatureVector::new(&[0, 100, 0]);
+        assert_eq!(model.evaluate(&negative), Err(Error::InvalidFixedPoint));
+        assert_eq!(
+            model.evaluate(&FeatureVector::new(&[1, 2])),
+            Err(Error::InvalidVectorLength(2))
+        );
+    }
+
+    #[test]
+    fn test_linear_inference_proofs_dont_transfer_to_other_inputs_or_models() {
+        let generators = LinearInferenceGenerators::new(4);
+        let model = LinearModel::new(&[50, -25, 150, 10], 1000).unwrap();
+        let features = FeatureVector::new(&[40, 80, 10, 5]);
+        let proof = LinearInferenceProof::generate_proof(
+            &model,
+            &features,
+            &schema(),
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        let features_commitment = features.commitment(&generators).unwrap();
+        let model_commitment = model.commitment(&generators).unwrap();
+
+        // Other features
+        let other_features = FeatureVector::new(&[40, 80, 10, 6]);
+        assert!(proof
+            .verify_proof(
+                &other_features.commitment(&generators).unwrap(),
+                &model_commitment,
+                &schema(),
+                &generators,
+                &mut Transcript::new(b"test"),
+            )
+            .is_err());
+        // The same weights with another bias
+        let other_model = LinearModel {
+            bias: 1001,
+            ..model.clone()
+        };
+        assert!(proof
+            .verify_proof(
+                &features_commitment,
+                &other_model.commitment(&generators).unwrap(),
+                &schema(),
+                &generators,
+                &mut Transcript::new(b"test"),
+            )
+            .is_err());
+        // Too many features for the generators
+        let large_model = LinearModelCommitment {
+            num_features: 5,
+            ..model_commitment
+        };
+        assert_eq!(
+            proof.verify_proof(
+                &features_commitment,
+                &large_model,
+                &schema(),
+                &generators,
+                &mut Transcript::new(b"test"),
+            ),
+            Err(Error::InvalidVectorLength(8))
+        );
+    }
+
+    #[test]
+    fn test_feature_commitments_sum_to_the_vector_commitment() {
+        let generators = LinearInferenceGenerators::new(4);
+        let values = [40, 80, 10];
+        let blindings = values.map(|_| Scalar::random(&mut rand::rngs::OsRng));
+        let feature_commitments: RistrettoPoint = values
+            .iter()
+            .zip(&blindings)
+            .enumerate()
+            .map(|(index, (value, blinding))| {
+                generators
+                    .feature_generators(index)
+                    .unwrap()
+                    .commit(&Scalar::from(*value as u64), blinding)
+            })
+            .sum();
+        let features = FeatureVector::with_blinding(&values, blindings.iter().sum());
+        assert_eq!(
+            features.commitment(&generators).unwrap(),
+            feature_commitments
+        );
+        assert_eq!(
+            generators.feature_generators(4),
+            Err(Error::InvalidVectorLength(5))
+        );
+    }
+}
@@ -1,52 +1,63 @@
 //! Example of a non-interactive zero knowledge proof implementation using Merlin Transcripts.
 
 use curve25519_dalek::{
-    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
 };
 
+use encodings::{
+    Endianness, PointEncoding, ProofScheme, ScalarEncoding, SecretScalar, SecurityLevel,
+};
 use merlin::{Transcript, TranscriptRng};
+use subtle::ConstantTimeEq;
+use tracing::instrument;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{challenge_oracle::validate_points, ChallengeOracle, Error};
+
+// This example uses a very simple Schnorr Signature scheme to prove knowledge of a private key.
+// The proof demonstrated would not be suitable for production use as it is susceptible to known
+// attacks, but it demonstrates how to define a transcript protocol and subsequently use it to
+// perform out a non-interactive proof. The hardened `SchnorrProof` further below fixes them.
 
-/// This example uses a very simple Schnorr Signature scheme to prove knowledge of a private key.
-/// The proof demonstrated would not be suitable for production use as it is susceptible to known
-/// attacks, but it demonstrates how to define a transcript protocol and subsequently use it to
-/// perform out a non-interactive proof.
-
-/// In a proof of private key, there are 2 parties the "prover" who owns the private key `k` and the
-/// "verifier" who verifies the "prover" owns the key.
-///
-/// In the interactive case, the proof is as follows:
-/// 1. A generator point `G` is selected within the group used to perform the proof math. This is
-/// often either an integer within a cyclic group or a point in an elliptic curve group. The public
-/// key `K` is defined as `K = k*G`.
-/// 2. The Prover chooses a random scalar `a` and computes `A = a*G` and sends it to the verifier.
-/// 3. The Verifier defines a challenge scalar `c` and sends it to the prover
-/// 4. The Prover computes the response `r` as `r = a + c*k` and sends it to the verifier
-/// 5. The Verifier computes `R = r*G` and `R' = A + c*K` and if `R = R'`, the proof is valid
-///
-/// Merlin Transcripts allow us to define a non-interactive version of this proof by allowing
-/// both parties to compute a deterministic challenge scalar `c`. To do this a transcript protocol
-/// that the verifier both agree on is defined. To define a proof both the prover and the verifier
-/// would agree on a set of domain separators for different steps in the proof process and scheme
-/// for encoding all mathematical objects in the proof in a canonical way.
-///
-/// In the example below of a transcript protocol defined for non-interactive proofs, domain
-/// separators are created for different proof steps, and two crucial functions are defined:
-/// * `append_proof_value()`- a function that serializes proof values into bytes in a canonical
-/// * `get_challenge()` - a function that transforms the bytes into a scalar in a canonical way.
-///
-/// After this is defined the proof works as follows:
-/// 1. The Prover chooses a random scalar `a` and computes `A = aG` and absorbs `A` into a Merlin
-/// transcript `T` using `T.append_proof_value(A)`
-/// 2. Prover defines a scalar `c` using `T.get_challenge()` and computes the response `r`
-/// as `r = a + c*k` and publishes the proof pair (`A`, `r`)
-/// 3. Verifier gets the random scalar `c` defining a transcript `T'` and deriving `c` by calling
-/// `T'.append_proof_value(A)` and `c = T'.get_challenge()`
-/// 4. Verifier computes `R = rG` and `R' = A + c*K` and if `R = R'`, the proof is valid
-///
-/// The main difference with the latter version of this proof is that the prover can compute the
-/// proof values `A` and `r` without any interaction with the verifier. Likewise any verifier who
-/// uses the same transcript protocol can verify the verifier's published proof values without any
-/// interaction with the prover.
+// In a proof of private key, there are 2 parties the "prover" who owns the private key `k` and the
+// "verifier" who verifies the "prover" owns the key.
+//
+// In the interactive case, the proof is as follows:
+// 1. A generator point `G` is selected within the group used to perform the proof math. This is
+// often either an integer within a cyclic group or a point in an elliptic curve group. The public
+// key `K` is defined as `K = k*G`.
+// 2. The Prover chooses a random scalar `a` and computes `A = a*G` and sends it to the verifier.
+// 3. The Verifier defines a challenge scalar `c` and sends it to the prover
+// 4. The Prover computes the response `r` as `r = a + c*k` and sends it to the verifier
+// 5. The Verifier computes `R = r*G` and `R' = A + c*K` and if `R = R'`, the proof is valid
+//
+// Merlin Transcripts allow us to define a non-interactive version of this proof by allowing
+// both parties to compute a deterministic challenge scalar `c`. To do this a transcript protocol
+// that the verifier both agree on is defined. To define a proof both the prover and the verifier
+// would agree on a set of domain separators for different steps in the proof process and scheme
+// for encoding all mathematical objects in the proof in a canonical way.
+//
+// In the example below of a transcript protocol defined for non-interactive proofs, domain
+// separators are created for different proof steps, and two crucial functions are defined:
+// * `append_proof_value()`- a function that serializes proof values into bytes in a canonical
+// * `get_challenge()` - a function that transforms the bytes into a scalar in a canonical way.
+//
+// After this is defined the proof works as follows:
+// 1. The Prover chooses a random scalar `a` and computes `A = aG` and absorbs `A` into a Merlin
+// transcript `T` using `T.append_proof_value(A)`
+// 2. Prover defines a scalar `c` using `T.get_challenge()` and computes the response `r`
+// as `r = a + c*k` and publishes the proof pair (`A`, `r`)
+// 3. Verifier gets the random scalar `c` defining a transcript `T'` and deriving `c` by calling
+// `T'.append_proof_value(A)` and `c = T'.get_challenge()`
+// 4. Verifier computes `R = rG` and `R' = A + c*K` and if `R = R'`, the proof is valid
+//
+// The main difference with the latter version of this proof is that the prover can compute the
+// proof values `A` and `r` without any interaction with the verifier. Likewise any verifier who
+// uses the same transcript protocol can verify the verifier's published proof values without any
+// interaction with the prover.
 
 // TRANSCRIPT PROTOCOL DEFINITION
 // Transcript protocols are defined in 2 steps:
@@ -67,14 +78,75 @@ const G: RistrettoPoint = RISTRETTO_BASEPOINT_POINT;
 const PROOF_DOMAIN_SEP: &[u8] = b"NON_INTERACTIVE_PRIVATE_KEY_PROOF";
 
 // Domain separator for sinking challenge values into the transcript
-const PROOF_VALUE_DOMAIN_SEP: &[u8] = b"PROOF_VALUE";
+pub(crate) const PROOF_VALUE_DOMAIN_SEP: &[u8] = b"PROOF_VALUE";
 
 // Domain separator for getting a challenge scalar from the transcript
-const CHALLENGE_SCALAR_DOMAIN_SEP: &[u8] = b"CHALLENGE_SCALAR";
+pub(crate) const CHALLENGE_SCALAR_DOMAIN_SEP: &[u8] = b"CHALLENGE_SCALAR";
 
 // Domain separator for keying a transcript based RNG for generating random scalars
 const WITNESS_DOMAIN_SEP: &[u8] = b"WITNESS_BYTES";
 
+// Domain separator for initializing hardened proof transcripts
+const HARDENED_PROOF_DOMAIN_SEP: &[u8] = b"SCHNORR_PRIVATE_KEY_PROOF";
+
+// WIRE FORMAT
+// Proofs are persisted and exchanged as 66 bytes:
+// 1. A version byte, so the format can change without old proofs being misread.
+// 2. A scheme byte, so the bytes of one kind of proof can't be parsed as another kind, e.g. a
+// simple proof passed off as a hardened one.
+// 3. The compressed nonce commitment `A` followed by the response `r` as 32 little endian bytes.
+// Both are decoded strictly, rejecting non-canonical encodings.
+
+/// Length of an encoded Schnorr proof
+pub const SCHNORR_PROOF_LEN: usize = 66;
+
+// Current version of the proof wire format
+const PROOF_FORMAT_VERSION: u8 = 1;
+
+// Scheme byte of encoded simple proofs
+const SIMPLE_PROOF_SCHEME: u8 = 0x01;
+
+// Scheme byte of encoded hardened proofs
+const HARDENED_PROOF_SCHEME: u8 = 0x02;
+
+// Encode a proof pair under a scheme byte
+fn encode_proof(scheme: u8, response: &Scalar, nonce_commitment: &RistrettoPoint) -> Vec<u8> {
+    let mut bytes = vec![PROOF_FORMAT_VERSION, scheme];
+    bytes.extend(nonce_commitment.encode());
+    bytes.extend(response.encode(Endianness::Little));
+    bytes
+}
+
+// Compare the two sides of a verification equation in constant time, so how long a rejection takes
+// doesn't reveal how much of a forged proof matched
+fn points_match(response_point: &RistrettoPoint, verification_point: &RistrettoPoint) -> bool {
+    bool::from(
+        response_point
+            .compress()
+            .ct_eq(&verification_point.compress()),
+    )
+}
+
+// Decode a proof pair, checking the version and scheme bytes
+fn decode_proof(scheme: u8, bytes: &[u8]) -> Result<(Scalar, RistrettoPoint), Error> {
+    if bytes.len() != SCHNORR_PROOF_LEN {
+        return Err(Error::InvalidProofEncoding(
+            encodings::Error::InvalidLength(SCHNORR_PROOF_LEN, bytes.len()),
+        ));
+    }
+    if bytes[0] != PROOF_FORMAT_VERSION {
+        return Err(Error::UnsupportedProofVersion(bytes[0]));
+    }
+    if bytes[1] != scheme {
+        return Err(Error::WrongProofScheme(bytes[1]));
+    }
+    let nonce_commitment =
+        RistrettoPoint::decode(&bytes[2..34]).map_err(Error::InvalidProofEncoding)?;
+    let response =
+        Scalar::decode(&bytes[34..], Endianness::Little).map_err(Error::InvalidProofEncoding)?;
+    Ok((response, nonce_commitment))
+}
+
 // DEFINING ENCODINGS
 
 // To help in defining a canonical encoding of proof values, we define a trait which defines several
@@ -99,7 +171,7 @@ pub trait SimpleProofProtocol {
 
 impl SimpleProofProtocol for Transcript {
     fn append_proof_value(&mut self, curve_point: &RistrettoPoint) {
-        self.append_message(PROOF_VALUE_DOMAIN_SEP, curve_point.compress().as_bytes());
+        self.append_message(PROOF_VALUE_DOMAIN_SEP, &curve_point.encode());
     }
 
     fn get_challenge(&mut self) -> Scalar {
@@ -110,7 +182,7 @@ impl SimpleProofProtocol for Transcript {
 
     fn get_rng(&mut self, public_key: &RistrettoPoint) -> TranscriptRng {
         self.build_rng()
-            .rekey_with_witness_bytes(WITNESS_DOMAIN_SEP, public_key.compress().as_bytes())
+            .rekey_with_witness_bytes(WITNESS_DOMAIN_SEP, &public_key.encode())
             .finalize(&mut rand::rngs::OsRng)
     }
 }
@@ -123,32 +195,30 @@ pub struct SimpleSchnorrProof {
     public_scalar: RistrettoPoint,
 }
 
-/// Proof errors
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Error {
-    /// Proof doesn't match
-    ProofMismatch(String, String),
-}
-
 impl SimpleSchnorrProof {
     /// Create a non-interactive proof pair to prove ownership of a private key. This function takes
     /// a transcript, and the private_key as inputs and returns a proof object that can be sent to
-    /// verifiers.
-    pub fn generate_proof(private_key: &Scalar, proof_transcript: &mut Transcript) -> Self {
+    /// verifiers. Any other [`ChallengeOracle`] can stand in for the transcript to choose the
+    /// challenge directly.
+    #[instrument(name = "prove", level = "debug", skip_all)]
+    pub fn generate_proof<O: ChallengeOracle>(
+        private_key: &Scalar,
+        proof_transcript: &mut O,
+    ) -> Self {
         // Generate the public key value
         let public_key = private_key * G;
 
         // Get a keyed rng to generate the random scalar `a` and public scalar `aG` and append
         // `aG` to the transcript
-        let mut rng = proof_transcript.get_rng(&public_key);
-        let random_scalar = Scalar::random(&mut rng);
-        let public_scalar = random_scalar * G;
-        proof_transcript.append_proof_value(&public_scalar);
+        let mut rng = proof_transcript.prover_rng(&public_key);
+        let random_scalar = SecretScalar::new(Scalar::random(&mut rng));
+        let public_scalar = random_scalar.get_scalar() * G;
+        proof_transcript.absorb_prover_message(&public_scalar);
 
         // Generate the challenge scalar using the merlin-transcripts transcript which the prover can later
         // reproduce and define the reesponse
-        let challenge_scalar = proof_transcript.get_challenge();
-        let response = random_scalar + private_key * challenge_scalar;
+        let challenge_scalar = proof_transcript.next_challenge();
+        let response = random_scalar.get_scalar() + private_key * challenge_scalar;
 
         Self {
             response,
@@ -158,36 +228,66 @@ impl SimpleSchnorrProof {
 
     /// Verify that the proof of ownership of the private key can be verified from a published
     /// public key.
-    pub fn verify_proof(
+    #[instrument(name = "verify", level = "debug", skip_all)]
+    pub fn verify_proof<O: ChallengeOracle>(
         &mut self,
         public_key: &RistrettoPoint,
-        proof_transcript: &mut Transcript,
+        proof_transcript: &mut O,
     ) -> Result<RistrettoPoint, Error> {
-        // As the verifier, append the public scalar `aG` to the transcript
-        proof_transcript.append_proof_value(&self.public_scalar);
+        validate_points(proof_transcript, &[public_key, &self.public_scalar])?;
 
-        // Get the same challenge scalar that prover used to generate the proof
-        let challenge_scalar: Scalar = proof_transcript.get_challenge();
+        // As the verifier, append the public scalar `aG` to the transcript and get the same
+        // challenge scalar that prover used to generate the proof
+        let challenge_scalar = self.derive_challenge(proof_transcript);
 
         // Use the proof values the prover published to verify the proof
         let response_point = self.response * G;
         let verification_point = self.public_scalar + challenge_scalar * public_key;
+        let verified = points_match(&response_point, &verification_point);
+
+        // Recompute r*G - c*K - A == 0 with a multiscalar multiplication as a debug build
+        // cross-check of the verification equation
+        debug_assert_eq!(
+            verified,
+            RistrettoPoint::vartime_multiscalar_mul(
+                [self.response, -challenge_scalar, -Scalar::ONE],
+                [G, *public_key, self.public_scalar],
+            )
+            .is_identity(),
+            "verification equation disagrees with its multiscalar form"
+        );
 
         // If the points match, it's been proven the prover knows the private key
-        if response_point.eq(&verification_point) {
+        if verified {
             return Ok(response_point);
         }
         Err(Error::ProofMismatch(
-            hex::encode(response_point.compress().as_bytes()),
-            hex::encode(response_point.compress().as_bytes()),
+            hex::encode(response_point.encode()),
+            hex::encode(verification_point.encode()),
         ))
     }
 
+    // Absorb the prover's message into the transcript and derive the challenge the proof answers
+    pub(crate) fn derive_challenge<O: ChallengeOracle>(&self, proof_transcript: &mut O) -> Scalar {
+        proof_transcript.absorb_prover_message(&self.public_scalar);
+        proof_transcript.next_challenge()
+    }
+
     /// Get proof pair data
     pub fn get_proof_pair(&self) -> (Scalar, RistrettoPoint) {
         (self.response, self.public_scalar)
     }
 
+    /// Encode the proof into its versioned wire format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_proof(SIMPLE_PROOF_SCHEME, &self.response, &self.public_scalar)
+    }
+
+    /// Decode a proof from its versioned wire format, rejecting malformed encodings
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        decode_proof(SIMPLE_PROOF_SCHEME, bytes).map(Self::from)
+    }
+
     /// Get a newly initialized proof object
     pub fn create_new_transcript() -> Transcript {
         Transcript::new(PROOF_DOMAIN_SEP)
@@ -204,6 +304,373 @@ impl From<(Scalar, RistrettoPoint)> for SimpleSchnorrProof {
     }
 }
 
+// HARDENED PROOFS
+// The simple proof derives its challenge from the nonce commitment `A` alone, which is known as
+// weak Fiat-Shamir. Since `c` doesn't depend on the public key, anyone can pick any `A` and `r`
+// and solve for a public key the proof verifies for, `K' = c^-1 * (r*G - A)`, even though nobody
+// knows its private key. Likewise nothing ties the proof to
+// the generator, so a proof can be replayed in a protocol using a different generator.
+//
+// Strong Fiat-Shamir fixes this by hashing the whole statement along with the prover's message:
+// the hardened proof absorbs the generator `G` and the public key `K` into the transcript before
+// `A`, so the challenge and therefore the proof are only valid for that statement. Its transcripts
+// use their own domain separator so hardened and simple proofs can't be mixed up.
+
+/// Schnorr proof of knowledge of a private key bound to the generator and public key it proves a
+/// statement about
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SchnorrProof {
+    response: Scalar,
+    nonce_commitment: RistrettoPoint,
+}
+
+impl SchnorrProof {
+    /// Prove knowledge of a private key. Any [`ChallengeOracle`] can stand in for the transcript.
+    #[instrument(name = "prove", level = "debug", skip_all)]
+    pub fn generate_proof<O: ChallengeOracle>(
+        private_key: &Scalar,
+        proof_transcript: &mut O,
+    ) -> Self {
+        let public_key = private_key * G;
+        proof_transcript.absorb_prover_message(&G);
+        proof_transcript.absorb_prover_message(&public_key);
+
+        let mut rng = proof_transcript.prover_rng(&public_key);
+        let nonce = SecretScalar::new(Scalar::random(&mut rng));
+        let nonce_commitment = nonce.get_scalar() * G;
+        proof_transcript.absorb_prover_message(&nonce_commitment);
+
+        let challenge_scalar = proof_transcript.next_challenge();
+        Self {
+            response: nonce.get_scalar() + private_key * challenge_scalar,
+            nonce_commitment,
+        }
+    }
+
+    /// Verify knowledge of the private key of a public key
+    #[instrument(name = "verify", level = "debug", skip_all)]
+    pub fn verify_proof<O: ChallengeOracle>(
+        &self,
+        public_key: &RistrettoPoint,
+        proof_transcript: &mut O,
+    ) -> Result<(), Error> {
+        validate_points(proof_transcript, &[public_key, &self.nonce_commitment])?;
+        let challenge_scalar = self.derive_challenge(public_key, proof_transcript);
+
+        let response_point = self.response * G;
+        let verification_point = self.nonce_commitment + challenge_scalar * public_key;
+        if points_match(&response_point, &verification_point) {
+            return Ok(());
+        }
+        Err(Error::ProofMismatch(
+            hex::encode(response_point.encode()),
+            hex::encode(verification_point.encode()),
+        ))
+    }
+
+    // Absorb the statement and the nonce commitment into the transcript and derive the challenge
+    // the proof answers
+    pub(crate) fn derive_challenge<O: ChallengeOracle>(
+        &self,
+        public_key: &RistrettoPoint,
+        proof_transcript: &mut O,
+    ) -> Scalar {
+        proof_transcript.absorb_prover_message(&G);
+        proof_transcript.absorb_prover_message(public_key);
+        proof_transcript.absorb_prover_message(&self.nonce_commitment);
+        proof_transcript.next_challenge()
+    }
+
+    /// Get the response and nonce commitment
+    pub fn get_proof_pair(&self) -> (Scalar, RistrettoPoint) {
+        (self.response, self.nonce_commitment)
+    }
+
+    /// Encode the proof into its versioned wire format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_proof(
+            HARDENED_PROOF_SCHEME,
+            &self.response,
+            &self.nonce_commitment,
+        )
+    }
+
+    /// Decode a proof from its versioned wire format, rejecting malformed encodings
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        decode_proof(HARDENED_PROOF_SCHEME, bytes).map(Self::from)
+    }
+
+    /// Get a newly initialized transcript for hardened proofs
+    pub fn create_new_transcript() -> Transcript {
+        Transcript::new(HARDENED_PROOF_DOMAIN_SEP)
+    }
+}
+
+/// Create a proof object from a published response and nonce commitment
+impl From<(Scalar, RistrettoPoint)> for SchnorrProof {
+    fn from(proof_pair: (Scalar, RistrettoPoint)) -> Self {
+        Self {
+            response: proof_pair.0,
+            nonce_commitment: proof_pair.1,
+        }
+    }
+}
+
+impl ProofScheme for SchnorrProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+// AGGREGATED PROOFS
+// Several provers each holding a private key x_i can jointly produce a single proof of knowledge
+// of all their keys, MuSig style, which is verified with one equation just like a proof for one
+// key. Simply adding the public keys up doesn't work: a malicious prover announcing its key last
+// can pick X_n = x*G - (X_1 + .. + X_(n-1)) and prove knowledge of the sum x*G alone. Instead the
+// keys are aggregated as X = a_1*X_1 + .. + a_n*X_n with coefficients a_i = H(L, X_i) derived from
+// the list L of all keys, so no key can be chosen after the aggregate it contributes to.
+//
+// 1. Nonce commitment: each prover picks a random nonce r_i, computes R_i = r_i*G and sends a hash
+// of R_i to the others. Without this round the last prover could choose its nonce after seeing
+// all others and bias the joint nonce, which breaks the security proof.
+// 2. Reveal: once all hashes are exchanged, the provers reveal R_i and check them against the
+// hashes.
+// 3. Respond: every prover derives the challenge c from X, R = R_1 + .. + R_n and the context and
+// answers with s_i = r_i + c*a_i*x_i.
+// 4. Combine: anyone holding all partial responses checks s_i*G == R_i + c*a_i*X_i, which
+// identifies a misbehaving prover, and sums them into the proof (R, s = s_1 + .. + s_n) which
+// verifies as s*G == R + c*X.
+
+// Domain separator for deriving key aggregation coefficients
+const KEY_AGGREGATION_DOMAIN_SEP: &[u8] = b"MUSIG_KEY_AGGREGATION";
+
+// Domain separator for hashing nonce commitments
+const MUSIG_NONCE_HASH_DOMAIN_SEP: &[u8] = b"MUSIG_NONCE_HASH";
+
+// Domain separator for initializing aggregated proof transcripts
+const MUSIG_PROOF_DOMAIN_SEP: &[u8] = b"MUSIG_PRIVATE_KEY_PROOF";
+
+/// Public keys of several provers aggregated into a single key
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AggregateKey {
+    public_keys: Vec<RistrettoPoint>,
+    // Coefficient a_i of each public key
+    coefficients: Vec<Scalar>,
+    aggregate: RistrettoPoint,
+}
+
+impl AggregateKey {
+    /// Aggregate the public keys of all provers, in the order the provers agreed on
+    pub fn new(public_keys: &[RistrettoPoint]) -> Self {
+        let mut key_list = Transcript::new(KEY_AGGREGATION_DOMAIN_SEP);
+        key_list.append_u64(b"NUM_KEYS", public_keys.len() as u64);
+        for public_key in public_keys {
+            key_list.append_proof_value(public_key);
+        }
+        let coefficients: Vec<Scalar> = public_keys
+            .iter()
+            .map(|public_key| {
+                let mut transcript = key_list.clone();
+                transcript.append_proof_value(public_key);
+                transcript.get_challenge()
+            })
+            .collect();
+        let aggregate = RistrettoPoint::vartime_multiscalar_mul(&coefficients, public_keys);
+        Self {
+            public_keys: public_keys.to_vec(),
+            coefficients,
+            aggregate,
+        }
+    }
+
+    /// Get the aggregated public key the joint proof verifies against
+    pub fn get_aggregate(&self) -> RistrettoPoint {
+        self.aggregate
+    }
+}
+
+// Hash a nonce commitment, which is sent to the other provers before revealing it
+pub(crate) fn nonce_hash(nonce_commitment: &RistrettoPoint) -> [u8; 32] {
+    let mut transcript = Transcript::new(MUSIG_NONCE_HASH_DOMAIN_SEP);
+    transcript.append_proof_value(nonce_commitment);
+    let mut hash = [0; 32];
+    transcript.challenge_bytes(MUSIG_NONCE_HASH_DOMAIN_SEP, &mut hash);
+    hash
+}
+
+// Derive the joint challenge from the aggregate key, the joint nonce commitment and the context
+fn aggregate_challenge(
+    aggregate: &RistrettoPoint,
+    nonce_commitment: &RistrettoPoint,
+    context: &[u8],
+) -> Scalar {
+    let mut transcript = Transcript::new(MUSIG_PROOF_DOMAIN_SEP);
+    transcript.append_message(b"CONTEXT", context);
+    transcript.append_proof_value(aggregate);
+    transcript.append_proof_value(nonce_commitment);
+    transcript.get_challenge()
+}
+
+/// A prover's partial response to the joint challenge
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PartialSchnorrResponse {
+    // Position of the prover's key in the aggregate key
+    index: usize,
+    // R_i
+    nonce_commitment: RistrettoPoint,
+    // r_i + c*a_i*x_i
+    response: Scalar,
+}
+
+/// State of one prover in an aggregated proof, whose private key and nonce are overwritten with
+/// zeros when it is dropped. The prover can't be cloned, since answering two challenges with the
+/// same nonce reveals the private key.
+#[derive(Debug)]
+pub struct AggregateProver {
+    private_key: SecretScalar<Scalar>,
+    // Position of the prover's key in the aggregate key
+    index: usize,
+    key: AggregateKey,
+    // Statement context all provers bind the proof to
+    context: Vec<u8>,
+    nonce: SecretScalar<Scalar>,
+    nonce_commitment: RistrettoPoint,
+}
+
+impl AggregateProver {
+    /// Start an aggregated proof for a private key whose public key is part of the aggregate key,
+    /// returning the prover state and the hash to send to the other provers
+    pub fn new(
+        private_key: &Scalar,
+        key: &AggregateKey,
+        context: &[u8],
+    ) -> Result<(Self, [u8; 32]), Error> {
+        let index = key
+            .public_keys
+            .iter()
+            .position(|public_key| *public_key == private_key * G)
+            .ok_or(Error::KeyNotAggregated)?;
+        let nonce = SecretScalar::new(Scalar::random(&mut rand::rngs::OsRng));
+        let prover = Self {
+            private_key: SecretScalar::new(*private_key),
+            index,
+            key: key.clone(),
+            context: context.to_vec(),
+            nonce_commitment: nonce.get_scalar() * G,
+            nonce,
+        };
+        let hash = nonce_hash(&prover.nonce_commitment);
+        Ok((prover, hash))
+    }
+
+    /// Get the nonce commitment to reveal once every other prover's hash was received
+    pub fn reveal(&self) -> RistrettoPoint {
+        self.nonce_commitment
+    }
+
+    /// Check every prover's revealed nonce commitment against its hash, both given in the order of
+    /// the aggregate key, and answer the joint challenge
+    pub fn respond(
+        self,
+        hashes: &[[u8; 32]],
+        nonce_commitments: &[RistrettoPoint],
+    ) -> Result<PartialSchnorrResponse, Error> {
+        if hashes.len() != self.key.public_keys.len()
+            || nonce_commitments.len() != hashes.len()
+            || nonce_commitments[self.index] != self.nonce_commitment
+            || nonce_commitments
+                .iter()
+                .zip(hashes)
+                .any(|(nonce_commitment, hash)| nonce_hash(nonce_commitment) != *hash)
+        {
+            return Err(Error::CoproverMismatch);
+        }
+        let nonce_commitment: RistrettoPoint = nonce_commitments.iter().sum();
+        let challenge = aggregate_challenge(&self.key.aggregate, &nonce_commitment, &self.context);
+        Ok(PartialSchnorrResponse {
+            index: self.index,
+            nonce_commitment: self.nonce_commitment,
+            response: self.nonce.get_scalar()
+                + challenge * self.key.coefficients[self.index] * self.private_key.get_scalar(),
+        })
+    }
+}
+
+impl Zeroize for AggregateProver {
+    fn zeroize(&mut self) {
+        self.private_key.zeroize();
+        self.nonce.zeroize();
+    }
+}
+
+// Both secrets are `SecretScalar`s, which zeroize themselves when the prover is dropped
+impl ZeroizeOnDrop for AggregateProver {}
+
+/// Proof of knowledge of the private keys behind an aggregate key
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AggregatedSchnorrProof {
+    nonce_commitment: RistrettoPoint,
+    response: Scalar,
+}
+
+impl AggregatedSchnorrProof {
+    /// Check every prover's partial response, given in the order of the aggregate key, and combine
+    /// them into a single proof
+    pub fn combine(
+        key: &AggregateKey,
+        responses: &[PartialSchnorrResponse],
+        context: &[u8],
+    ) -> Result<Self, Error> {
+        if responses.len() != key.public_keys.len()
+            || responses
+                .iter()
+                .enumerate()
+                .any(|(index, response)| response.index != index)
+        {
+            return Err(Error::CoproverMismatch);
+        }
+        let nonce_commitment: RistrettoPoint = responses
+            .iter()
+            .map(|response| response.nonce_commitment)
+            .sum();
+        let challenge = aggregate_challenge(&key.aggregate, &nonce_commitment, context);
+        for (response, (public_key, coefficient)) in responses
+            .iter()
+            .zip(key.public_keys.iter().zip(&key.coefficients))
+        {
+            if response.response * G
+                != response.nonce_commitment + challenge * coefficient * public_key
+            {
+                return Err(Error::CoproverMismatch);
+            }
+        }
+        Ok(Self {
+            nonce_commitment,
+            response: responses.iter().map(|response| response.response).sum(),
+        })
+    }
+
+    /// Verify knowledge of the private keys behind an aggregated public key
+    #[instrument(name = "verify", level = "debug", skip_all)]
+    pub fn verify_proof(&self, aggregate: &RistrettoPoint, context: &[u8]) -> Result<(), Error> {
+        if aggregate.is_identity() {
+            return Err(Error::IdentityPoint);
+        }
+        let challenge = aggregate_challenge(aggregate, &self.nonce_commitment, context);
+        let response_point = self.response * G;
+        let verification_point = self.nonce_commitment + challenge * aggregate;
+        if points_match(&response_point, &verification_point) {
+            return Ok(());
+        }
+        Err(Error::ProofMismatch(
+            hex::encode(response_point.encode()),
+            hex::encode(verification_point.encode()),
+        ))
+    }
+}
+
+impl ProofScheme for AggregatedSchnorrProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
 /// Generate a sample private key for use within the proof
 pub(crate) fn generate_keypair() -> (Scalar, RistrettoPoint) {
     let private_key = Scalar::random(&mut rand::rngs::OsRng);
@@ -211,6 +678,11 @@ pub(crate) fn generate_keypair() -> (Scalar, RistrettoPoint) {
     (private_key, public_key)
 }
 
+// Demonstrates transcript protocols and isn't hardened against the attacks noted above
+impl ProofScheme for SimpleSchnorrProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Educational;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,4 +715,274 @@ mod tests {
         // Assert that the proof verification succeeded
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_hardened_proofs_resist_key_substitution() {
+        // Under weak Fiat-Shamir anyone can forge a proof for a key nobody knows the private key of:
+        // pick any A and r, then solve r*G == A + c*K' for K' with c only depending on A
+        let mut rng = rand::thread_rng();
+        let (nonce_commitment, response) =
+            (RistrettoPoint::random(&mut rng), Scalar::random(&mut rng));
+        let mut transcript = SimpleSchnorrProof::create_new_transcript();
+        transcript.append_proof_value(&nonce_commitment);
+        let substituted_key =
+            transcript.get_challenge().invert() * (response * G - nonce_commitment);
+        assert!(SimpleSchnorrProof::from((response, nonce_commitment))
+            .verify_proof(
+                &substituted_key,
+                &mut SimpleSchnorrProof::create_new_transcript()
+            )
+            .is_ok());
+
+        // The hardened challenge depends on the key, so solving for it with the challenge of any
+        // other key gives a key the proof doesn't verify for
+        let mut transcript = SchnorrProof::create_new_transcript();
+        transcript.append_proof_value(&G);
+        transcript.append_proof_value(&substituted_key);
+        transcript.append_proof_value(&nonce_commitment);
+        let substituted_key =
+            transcript.get_challenge().invert() * (response * G - nonce_commitment);
+        assert!(SchnorrProof::from((response, nonce_commitment))
+            .verify_proof(&substituted_key, &mut SchnorrProof::create_new_transcript())
+            .is_err());
+
+        // Honest proofs only verify for the key they were made for
+        let (private_key, public_key) = generate_keypair();
+        let proof =
+            SchnorrProof::generate_proof(&private_key, &mut SchnorrProof::create_new_transcript());
+        assert!(proof
+            .verify_proof(&public_key, &mut SchnorrProof::create_new_transcript())
+            .is_ok());
+        assert!(proof
+            .verify_proof(
+                &generate_keypair().1,
+                &mut SchnorrProof::create_new_transcript()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_proofs_round_trip_through_the_wire_format() {
+        let (private_key, public_key) = generate_keypair();
+        let proof = SimpleSchnorrProof::generate_proof(
+            &private_key,
+            &mut SimpleSchnorrProof::create_new_transcript(),
+        );
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), SCHNORR_PROOF_LEN);
+        assert_eq!(bytes[..2], [PROOF_FORMAT_VERSION, SIMPLE_PROOF_SCHEME]);
+        let mut decoded = SimpleSchnorrProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.get_proof_pair(), proof.get_proof_pair());
+        assert!(decoded
+            .verify_proof(
+                &public_key,
+                &mut SimpleSchnorrProof::create_new_transcript()
+            )
+            .is_ok());
+
+        let hardened =
+            SchnorrProof::generate_proof(&private_key, &mut SchnorrProof::create_new_transcript());
+        assert_eq!(SchnorrProof::from_bytes(&hardened.to_bytes()), Ok(hardened));
+    }
+
+    #[test]
+    fn test_malformed_proof_encodings_are_rejected() {
+        let (private_key, _) = generate_keypair();
+        let bytes = SimpleSchnorrProof::generate_proof(
+            &private_key,
+            &mut SimpleSchnorrProof::create_new_transcript(),
+        )
+        .to_bytes();
+
+        assert_eq!(
+            SimpleSchnorrProof::from_bytes(&bytes[..65]).err(),
+            Some(Error::InvalidProofEncoding(
+                encodings::Error::InvalidLength(66, 65)
+            ))
+        );
+        let mut future_version = bytes.clone();
+        future_version[0] = 2;
+        assert_eq!(
+            SimpleSchnorrProof::from_bytes(&future_version).err(),
+            Some(Error::UnsupportedProofVersion(2))
+        );
+        // A simple proof can't be decoded as a hardened one
+        assert_eq!(
+            SchnorrProof::from_bytes(&bytes).err(),
+            Some(Error::WrongProofScheme(SIMPLE_PROOF_SCHEME))
+        );
+        let mut invalid_point = bytes.clone();
+        invalid_point[2..34].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            SimpleSchnorrProof::from_bytes(&invalid_point).err(),
+            Some(Error::InvalidProofEncoding(
+                encodings::Error::NonCanonicalPoint
+            ))
+        );
+        let mut unreduced_response = bytes;
+        unreduced_response[34..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            SimpleSchnorrProof::from_bytes(&unreduced_response).err(),
+            Some(Error::InvalidProofEncoding(
+                encodings::Error::NonCanonicalScalar
+            ))
+        );
+    }
+
+    #[test]
+    fn test_hardened_and_simple_proofs_cant_be_mixed() {
+        let (private_key, public_key) = generate_keypair();
+        let simple = SimpleSchnorrProof::generate_proof(
+            &private_key,
+            &mut SimpleSchnorrProof::create_new_transcript(),
+        );
+        assert!(SchnorrProof::from(simple.get_proof_pair())
+            .verify_proof(&public_key, &mut SchnorrProof::create_new_transcript())
+            .is_err());
+        let hardened =
+            SchnorrProof::generate_proof(&private_key, &mut SchnorrProof::create_new_transcript());
+        assert!(SimpleSchnorrProof::from(hardened.get_proof_pair())
+            .verify_proof(
+                &public_key,
+                &mut SimpleSchnorrProof::create_new_transcript()
+            )
+            .is_err());
+    }
+
+    // Run the aggregated proof protocol between all provers
+    fn aggregate_proof(
+        private_keys: &[Scalar],
+        key: &AggregateKey,
+        context: &[u8],
+    ) -> Result<AggregatedSchnorrProof, Error> {
+        let (provers, hashes): (Vec<_>, Vec<_>) = private_keys
+            .iter()
+            .map(|private_key| AggregateProver::new(private_key, key, context))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        let nonce_commitments: Vec<_> = provers.iter().map(AggregateProver::reveal).collect();
+        let responses = provers
+            .into_iter()
+            .map(|prover| prover.respond(&hashes, &nonce_commitments))
+            .collect::<Result<Vec<_>, _>>()?;
+        AggregatedSchnorrProof::combine(key, &responses, context)
+    }
+
+    #[test]
+    fn test_aggregated_proofs_verify_against_the_aggregate_key() {
+        let keypairs: Vec<_> = (0..3).map(|_| generate_keypair()).collect();
+        let private_keys: Vec<_> = keypairs
+            .iter()
+            .map(|(private_key, _)| *private_key)
+            .collect();
+        let key = AggregateKey::new(
+            &keypairs
+                .iter()
+                .map(|(_, public_key)| *public_key)
+                .collect::<Vec<_>>(),
+        );
+
+        let proof = aggregate_proof(&private_keys, &key, b"model v1").unwrap();
+        assert!(proof
+            .verify_proof(&key.get_aggregate(), b"model v1")
+            .is_ok());
+        assert!(proof
+            .verify_proof(&key.get_aggregate(), b"model v2")
+            .is_err());
+
+        // Every key is needed, and a key outside the aggregate can't join
+        assert!(aggregate_proof(&private_keys[..2], &key, b"model v1").is_err());
+        assert_eq!(
+            AggregateProver::new(&generate_keypair().0, &key, b"model v1").err(),
+            Some(Error::KeyNotAggregated)
+        );
+
+        // A prover revealing a different nonce than it committed to is caught
+        let (first, first_hash) = AggregateProver::new(&private_keys[0], &key, b"").unwrap();
+        let (second, second_hash) = AggregateProver::new(&private_keys[1], &key, b"").unwrap();
+        let (third, third_hash) = AggregateProver::new(&private_keys[2], &key, b"").unwrap();
+        let hashes = [first_hash, second_hash, third_hash];
+        let reveals = [first.reveal(), second.reveal(), third.reveal()];
+        assert_eq!(
+            first
+                .respond(&hashes, &[reveals[0], reveals[1], reveals[1]])
+                .err(),
+            Some(Error::CoproverMismatch)
+        );
+        assert!(third.respond(&hashes, &reveals).is_ok());
+    }
+
+    #[test]
+    fn test_aggregate_provers_erase_their_secrets() {
+        let (private_key, public_key) = generate_keypair();
+        let key = AggregateKey::new(&[public_key]);
+        let (mut prover, hash) = AggregateProver::new(&private_key, &key, b"").unwrap();
+        assert!(format!("{:?}", prover).contains("SecretScalar(<redacted>)"));
+        assert!(!format!("{:?}", prover).contains(&hex::encode(private_key.to_bytes())));
+
+        // Once zeroized the prover has nothing left to answer the challenge with
+        prover.zeroize();
+        let nonce_commitment = prover.reveal();
+        let response = prover.respond(&[hash], &[nonce_commitment]).unwrap();
+        assert_eq!(response.response, Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_rogue_keys_cant_claim_the_aggregate() {
+        // The attacker announces X_2 = x*G - X_1 so that naively summed keys equal x*G
+        let (_, honest_key) = generate_keypair();
+        let (attacker_secret, attacker_target) = generate_keypair();
+        let rogue_key = attacker_target - honest_key;
+        let key = AggregateKey::new(&[honest_key, rogue_key]);
+        assert_eq!(honest_key + rogue_key, attacker_target);
+        assert_ne!(key.get_aggregate(), attacker_target);
+
+        // A proof for the naive sum made with the attacker's secret alone doesn't verify
+        let nonce = Scalar::random(&mut rand::rngs::OsRng);
+        let nonce_commitment = nonce * G;
+        let challenge = aggregate_challenge(&key.get_aggregate(), &nonce_commitment, b"");
+        let forged = AggregatedSchnorrProof {
+            nonce_commitment,
+            response: nonce + challenge * attacker_secret,
+        };
+        assert!(forged.verify_proof(&key.get_aggregate(), b"").is_err());
+    }
+
+    // Wall clock ratios are flaky on loaded machines, so the check only runs when asked for:
+    // cargo test --release -p merlin-example -- --ignored test_rejecting_a_proof_takes_as_long
+    #[test]
+    #[ignore = "timing sensitive, run with --release and --ignored"]
+    fn test_rejecting_a_proof_takes_as_long_as_accepting_one() {
+        let (private_key, public_key) = generate_keypair();
+        let proof = SimpleSchnorrProof::generate_proof(
+            &private_key,
+            &mut SimpleSchnorrProof::create_new_transcript(),
+        );
+        let (response, public_scalar) = proof.get_proof_pair();
+
+        // Take the fastest of many runs to filter out scheduling noise
+        let fastest_verification = |response: Scalar| {
+            (0..50)
+                .map(|_| {
+                    let mut proof = SimpleSchnorrProof::from((response, public_scalar));
+                    let mut transcript = SimpleSchnorrProof::create_new_transcript();
+                    let start = std::time::Instant::now();
+                    let result = proof.verify_proof(&public_key, &mut transcript);
+                    (start.elapsed(), result.is_ok())
+                })
+                .min()
+                .unwrap()
+        };
+        let (accepted, accepted_ok) = fastest_verification(response);
+        let (rejected, rejected_ok) = fastest_verification(response + Scalar::ONE);
+        assert!(accepted_ok && !rejected_ok);
+
+        // Rejections also encode both points for the error, so the bound is loose
+        let ratio = rejected.as_secs_f64() / accepted.as_secs_f64();
+        assert!(
+            (0.5..2.0).contains(&ratio),
+            "rejection took {rejected:?} and acceptance {accepted:?}"
+        );
+    }
 }
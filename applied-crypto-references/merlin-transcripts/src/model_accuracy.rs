@@ -0,0 +1,634 @@
+//! Proofs that a committed linear classifier reaches a minimum accuracy on a Merkle-committed
+//! test set
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{Digest, HashBackend, ProofScheme, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{
+    model_commitment::merkle_tree, BucketSchema, Error, FeatureVector, LinearInferenceGenerators,
+    LinearInferenceProof, LinearModel, LinearModelCommitment, RangeProof, SetMembershipProof,
+    SimpleProofProtocol,
+};
+
+// A model owner wants to show buyers that their committed model is accurate on a public benchmark
+// without revealing the model. A benchmark publisher commits to the test set as the root of a
+// Merkle tree over its labeled examples, and the model owner proves that the committed model
+// classifies at least a given percentage of them correctly, without revealing which ones:
+//
+// 1. The model is a binary classifier: its output falls into one of the two buckets of a public
+// schema [lower, threshold) and [threshold, upper), and the bucket is the predicted label.
+// 2. For every example the prover reveals the example with its Merkle path, so the verifier
+// only needs the published root rather than the whole test set, and proves with a
+// [`LinearInferenceProof`] that the committed output `C_y` of the model on the example lies in
+// [lower, upper), without revealing its bucket.
+// 3. The prover commits to the predicted label as `P = p*G + s*H` and proves with a
+// [`SetMembershipProof`] that `p` is 0 or 1.
+// 4. Two range proofs tie the prediction to the output. `C_y - lower*G - (threshold - lower)*P`
+// commits to `y - lower` when `p = 0` and to `y - threshold` when `p = 1`, and
+// `(threshold - 1)*G - C_y + (upper - threshold)*P` commits to `threshold - 1 - y` when `p = 0`
+// and to `upper - 1 - y` when `p = 1`. Both being small and not negative means `y` lies in the
+// bucket of the predicted label.
+// 5. The verifier derives a commitment to whether the prediction is correct from the public
+// label: `K = P` for examples labeled 1 and `K = G - P` for examples labeled 0.
+// 6. The sum of the `K` commits to the number of correct predictions. A final range proof shows
+// that this sum minus the required number of correct predictions is not negative, which
+// reveals neither the exact accuracy nor the predictions.
+//
+// Every example proof is bound to the test set commitment and the position of its example, and
+// the verifier expects exactly one proof per position, so examples can't be left out, repeated or
+// swapped for easier ones.
+
+// Domain separator for initializing accuracy proof transcripts
+const ACCURACY_DOMAIN_SEP: &[u8] = b"MODEL_ACCURACY";
+
+// Domain separator for hashing examples into Merkle tree leaves
+const LEAF_DOMAIN_SEP: &[u8] = b"TEST_SET_LEAF";
+
+// Domain separator for hashing Merkle tree nodes
+const NODE_DOMAIN_SEP: &[u8] = b"TEST_SET_NODE";
+
+// Hash a labeled example into a Merkle tree leaf
+fn hash_leaf(digest: &HashBackend, example: &LabeledExample) -> [u8; 32] {
+    let mut data = (example.label as u64).to_le_bytes().to_vec();
+    for feature in example.features.iter() {
+        data.extend_from_slice(&feature.to_le_bytes());
+    }
+    digest.hash(LEAF_DOMAIN_SEP, &data)
+}
+
+// Hash two child nodes into their parent node
+fn hash_node(digest: &HashBackend, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    digest.hash_pair(NODE_DOMAIN_SEP, left, right)
+}
+
+/// Example of a test set with fixed-point features and the bucket of its true label
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LabeledExample {
+    features: Vec<i64>,
+    label: usize,
+}
+
+impl LabeledExample {
+    /// Create an example from its features and label, which must be 0 or 1
+    pub fn new(features: &[i64], label: usize) -> Result<Self, Error> {
+        if label > 1 {
+            return Err(Error::InvalidLabel(label));
+        }
+        Ok(Self {
+            features: features.to_vec(),
+            label,
+        })
+    }
+
+    /// Get the features of the example
+    pub fn get_features(&self) -> &[i64] {
+        &self.features
+    }
+
+    /// Get the label of the example
+    pub fn get_label(&self) -> usize {
+        self.label
+    }
+}
+
+/// Test set of labeled examples committed to with a Merkle tree
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TestSet {
+    examples: Vec<LabeledExample>,
+    // Levels of the tree from the leaves, padded with zero nodes to a power of two, up to the root
+    tree: Vec<Vec<[u8; 32]>>,
+    digest: HashBackend,
+}
+
+/// Public commitment to a [`TestSet`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TestSetCommitment {
+    // Root of the Merkle tree over the examples
+    root: [u8; 32],
+    // Number of examples in the test set
+    num_examples: usize,
+    // Hash backend the tree is hashed with
+    digest: HashBackend,
+}
+
+impl TestSet {
+    /// Commit to at least one example with a Merkle tree hashed with `digest`
+    pub fn new(examples: Vec<LabeledExample>, digest: HashBackend) -> Result<Self, Error> {
+        if examples.is_empty() {
+            return Err(Error::EmptyTestSet);
+        }
+        let leaves: Vec<[u8; 32]> = examples
+            .iter()
+            .map(|example| hash_leaf(&digest, example))
+            .collect();
+        let tree = merkle_tree(&digest, NODE_DOMAIN_SEP, leaves);
+        Ok(Self {
+            examples,
+            tree,
+            digest,
+        })
+    }
+
+    /// Get the commitment to the test set
+    pub fn commitment(&self) -> TestSetCommitment {
+        TestSetCommitment {
+            root: self.tree[self.tree.len() - 1][0],
+            num_examples: self.examples.len(),
+            digest: self.digest,
+        }
+    }
+
+    /// Get the examples of the test set
+    pub fn get_examples(&self) -> &[LabeledExample] {
+        &self.examples
+    }
+
+    // Get the sibling nodes from the leaf of an example up to the root
+    fn merkle_path(&self, index: usize) -> Vec<[u8; 32]> {
+        self.tree[..self.tree.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+            .collect()
+    }
+}
+
+impl TestSetCommitment {
+    /// Get the Merkle root of the examples
+    pub fn get_root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Get the number of examples in the test set
+    pub fn get_num_examples(&self) -> usize {
+        self.num_examples
+    }
+
+    /// Get the hash backend the Merkle tree is hashed with
+    pub fn get_digest(&self) -> HashBackend {
+        self.digest
+    }
+
+    /// Bind the test set commitment into a proof transcript
+    pub fn bind_transcript(&self, transcript: &mut Transcript) {
+        transcript.append_message(b"TEST_SET_ROOT", &self.root);
+        transcript.append_u64(b"NUM_EXAMPLES", self.num_examples as u64);
+        transcript.append_message(b"DIGEST", self.digest.label());
+    }
+
+    // Check an example is in the test set at `index`
+    fn verify_inclusion(
+        &self,
+        index: usize,
+        example: &LabeledExample,
+        merkle_path: &[[u8; 32]],
+    ) -> Result<(), Error> {
+        let expected_depth = self.num_examples.next_power_of_two().trailing_zeros() as usize;
+        let root = merkle_path.iter().enumerate().fold(
+            hash_leaf(&self.digest, example),
+            |node, (level, sibling)| match (index >> level) & 1 {
+                0 => hash_node(&self.digest, &node, sibling),
+                _ => hash_node(&self.digest, sibling, &node),
+            },
+        );
+        if index >= self.num_examples || merkle_path.len() != expected_depth || root != self.root {
+            return Err(Error::TestSetMismatch(index));
+        }
+        Ok(())
+    }
+
+    // Create a transcript bound to the test set and the position of an example
+    fn example_transcript(&self, index: usize) -> Transcript {
+        let mut transcript = Transcript::new(ACCURACY_DOMAIN_SEP);
+        self.bind_transcript(&mut transcript);
+        transcript.append_u64(b"EXAMPLE_INDEX", index as u64);
+        transcript
+    }
+
+    // Create a transcript bound to the test set, the model and the claimed accuracy
+    fn count_transcript(&self, model: &LinearModelCommitment, min_accuracy: u64) -> Transcript {
+        let mut transcript = Transcript::new(ACCURACY_DOMAIN_SEP);
+        self.bind_transcript(&mut transcript);
+        transcript.append_proof_value(&model.weights);
+        transcript.append_proof_value(&model.bias);
+        transcript.append_u64(b"MIN_ACCURACY", min_accuracy);
+        transcript
+    }
+
+    // Number of correct predictions needed for an accuracy of `min_accuracy` percent
+    fn required_correct(&self, min_accuracy: u64) -> Result<usize, Error> {
+        if min_accuracy > 100 {
+            return Err(Error::InvalidAccuracy(min_accuracy));
+        }
+        Ok((self.num_examples * min_accuracy as usize).div_ceil(100))
+    }
+}
+
+/// Proof that a committed model's prediction on an example of a committed test set is hidden in
+/// a commitment
+#[derive(Clone, Debug)]
+pub struct ExampleProof {
+    // Position of the example in the test set
+    index: usize,
+    // The example itself
+    example: LabeledExample,
+    // Sibling nodes from the example's leaf up to the Merkle root
+    merkle_path: Vec<[u8; 32]>,
+    // Proof the committed output of the model on the example lies on the schema
+    inference_proof: LinearInferenceProof,
+    // Commitment P to the predicted label
+    prediction_commitment: RistrettoPoint,
+    // Proof the predicted label is 0 or 1
+    prediction_proof: SetMembershipProof,
+    // Proof the output is at least the lower bound of the predicted bucket
+    lower_proof: RangeProof,
+    // Proof the output is below the upper bound of the predicted bucket
+    upper_proof: RangeProof,
+}
+
+impl ExampleProof {
+    // Prove the prediction of the model on an example, returning the proof along with the
+    // prediction and the blinding of its commitment
+    pub(crate) fn generate_proof(
+        model: &LinearModel,
+        test_set: &TestSet,
+        index: usize,
+        schema: &BucketSchema,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<(Self, usize, Scalar), Error> {
+        let (bounds, pedersen) = (bucket_bounds(schema)?, &generators.pedersen_generators);
+        let example = &test_set.examples[index];
+        schema.get_bucket(example.label)?;
+        let mut transcript = test_set.commitment().example_transcript(index);
+
+        // Step 2: prove the committed output is on the schema. The features are public, so they
+        // are committed with a public blinding of one, which keeps an example of all zeros from
+        // committing to the identity.
+        let features = FeatureVector::with_blinding(&example.features, Scalar::ONE);
+        let output = model.evaluate(&features)?;
+        let (inference_proof, output_blinding) =
+            LinearInferenceProof::generate_proof_with_output_blinding(
+                model,
+                &features,
+                &schema.merged(),
+                generators,
+                &mut transcript,
+            )?;
+
+        // Step 3: commit to the predicted label
+        let prediction = schema.bucket_of(output)?;
+        let prediction_blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let prediction_commitment =
+            pedersen.commit(&Scalar::from(prediction as u64), &prediction_blinding);
+        let prediction_proof = SetMembershipProof::generate_proof(
+            prediction as u64,
+            &prediction_blinding,
+            &[0, 1],
+            pedersen,
+            &mut transcript,
+        )?;
+
+        // Step 4: the output lies in the bucket of the predicted label
+        let [lower, threshold, upper] = bounds;
+        let bits = BucketSchema::bucket_bits(lower, upper);
+        let lower_proof = RangeProof::generate_proof(
+            output - [lower, threshold][prediction],
+            &(output_blinding - Scalar::from(threshold - lower) * prediction_blinding),
+            bits,
+            pedersen,
+            &mut transcript,
+        )?;
+        let upper_proof = RangeProof::generate_proof(
+            [threshold, upper][prediction] - 1 - output,
+            &(Scalar::from(upper - threshold) * prediction_blinding - output_blinding),
+            bits,
+            pedersen,
+            &mut transcript,
+        )?;
+
+        let proof = Self {
+            index,
+            example: example.clone(),
+            merkle_path: test_set.merkle_path(index),
+            inference_proof,
+            prediction_commitment,
+            prediction_proof,
+            lower_proof,
+            upper_proof,
+        };
+        Ok((proof, prediction, prediction_blinding))
+    }
+
+    // Prove the prediction of the model on every example of the test set, in order
+    pub(crate) fn generate_proofs(
+        model: &LinearModel,
+        test_set: &TestSet,
+        schema: &BucketSchema,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<Vec<(Self, usize, Scalar)>, Error> {
+        (0..test_set.examples.len())
+            .map(|index| Self::generate_proof(model, test_set, index, schema, generators))
+            .collect()
+    }
+
+    // Verify the proof against the test set and the model, returning the commitment P to the
+    // prediction
+    pub(crate) fn verify_proof(
+        &self,
+        test_set: &TestSetCommitment,
+        model: &LinearModelCommitment,
+        schema: &BucketSchema,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<RistrettoPoint, Error> {
+        let (bounds, pedersen) = (bucket_bounds(schema)?, &generators.pedersen_generators);
+        test_set.verify_inclusion(self.index, &self.example, &self.merkle_path)?;
+        schema.get_bucket(self.example.label)?;
+        let mut transcript = test_set.example_transcript(self.index);
+
+        // Step 2: the committed output is on the schema
+        let features = FeatureVector::with_blinding(&self.example.features, Scalar::ONE);
+        self.inference_proof.verify_proof(
+            &features.commitment(generators)?,
+            model,
+            &schema.merged(),
+            generators,
+            &mut transcript,
+        )?;
+
+        // Step 3: the predicted label is 0 or 1
+        self.prediction_proof.verify_proof(
+            &self.prediction_commitment,
+            &[0, 1],
+            pedersen,
+            &mut transcript,
+        )?;
+
+        // Step 4: the output lies in the bucket of the predicted label
+        let [lower, threshold, upper] = bounds.map(Scalar::from);
+        let bits = BucketSchema::bucket_bits(bounds[0], bounds[2]);
+        let (g, p) = (pedersen.value_generator, self.prediction_commitment);
+        let output = self.inference_proof.get_output_commitment();
+        self.lower_proof.verify_proof(
+            &(output - lower * g - (threshold - lower) * p),
+            bits,
+            pedersen,
+            &mut transcript,
+        )?;
+        self.upper_proof.verify_proof(
+            &((threshold - Scalar::ONE) * g - output + (upper - threshold) * p),
+            bits,
+            pedersen,
+            &mut transcript,
+        )?;
+        Ok(p)
+    }
+
+    // Verify there is a proof for every example of the test set, in order, returning the
+    // commitments to the predictions
+    pub(crate) fn verify_proofs(
+        proofs: &[Self],
+        test_set: &TestSetCommitment,
+        model: &LinearModelCommitment,
+        schema: &BucketSchema,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<Vec<RistrettoPoint>, Error> {
+        if proofs.len() != test_set.num_examples {
+            return Err(Error::TestSetMismatch(proofs.len()));
+        }
+        proofs
+            .iter()
+            .enumerate()
+            .map(|(index, proof)| match proof.index == index {
+                true => proof.verify_proof(test_set, model, schema, generators),
+                false => Err(Error::TestSetMismatch(proof.index)),
+            })
+            .collect()
+    }
+
+    /// Get the position of the example in the test set
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the example the prediction was made on
+    pub fn get_example(&self) -> &LabeledExample {
+        &self.example
+    }
+}
+
+// Get the bounds [lower, threshold, upper] of a schema with two buckets
+fn bucket_bounds(schema: &BucketSchema) -> Result<[u64; 3], Error> {
+    if schema.num_buckets() != 2 {
+        return Err(Error::InvalidBucketSchema);
+    }
+    let ((lower, threshold), (_, upper)) = (schema.get_bucket(0)?, schema.get_bucket(1)?);
+    Ok([lower, threshold, upper])
+}
+
+/// Proof that a committed binary classifier labels at least a minimum percentage of the examples
+/// of a committed test set correctly
+#[derive(Clone, Debug)]
+pub struct AccuracyProof {
+    // Proof of the hidden prediction on every example, in the order of the test set
+    example_proofs: Vec<ExampleProof>,
+    // Proof the number of correct predictions is at least the required number
+    count_proof: RangeProof,
+}
+
+impl AccuracyProof {
+    /// Prove that `model` labels at least `min_accuracy` percent of the test set correctly, where
+    /// the predicted label of an example is the bucket of the two bucket `schema` its output falls
+    /// into
+    pub fn generate_proof(
+        model: &LinearModel,
+        test_set: &TestSet,
+        schema: &BucketSchema,
+        min_accuracy: u64,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<Self, Error> {
+        let commitment = test_set.commitment();
+        let required = commitment.required_correct(min_accuracy)?;
+        let mut example_proofs = Vec::with_capacity(commitment.num_examples);
+        let (mut correct, mut blinding) = (0, Scalar::ZERO);
+        for (proof, prediction, prediction_blinding) in
+            ExampleProof::generate_proofs(model, test_set, schema, generators)?
+        {
+            // Step 5: K = P opens with the blinding of P, and K = G - P with its negation
+            correct += (prediction == proof.example.label) as usize;
+            blinding += match proof.example.label {
+                1 => prediction_blinding,
+                _ => -prediction_blinding,
+            };
+            example_proofs.push(proof);
+        }
+
+        // Step 6: the sum of the correctness commitments minus the required count isn't negative
+        let surplus = correct
+            .checked_sub(required)
+            .ok_or(Error::AccuracyBelowThreshold(correct, required))?;
+        let count_proof = RangeProof::generate_proof(
+            surplus as u64,
+            &blinding,
+            BucketSchema::bucket_bits(0, commitment.num_examples as u64 + 1),
+            &generators.pedersen_generators,
+            &mut commitment.count_transcript(&model.commitment(generators)?, min_accuracy),
+        )?;
+        Ok(Self {
+            example_proofs,
+            count_proof,
+        })
+    }
+
+    /// Verify the committed model labels at least `min_accuracy` percent of the committed test set
+    /// correctly
+    pub fn verify_proof(
+        &self,
+        test_set: &TestSetCommitment,
+        model: &LinearModelCommitment,
+        schema: &BucketSchema,
+        min_accuracy: u64,
+        generators: &LinearInferenceGenerators,
+    ) -> Result<(), Error> {
+        let required = test_set.required_correct(min_accuracy)?;
+        let predictions =
+            ExampleProof::verify_proofs(&self.example_proofs, test_set, model, schema, generators)?;
+
+        // Step 5: derive the correctness commitments from the public labels
+        let pedersen = &generators.pedersen_generators;
+        let correct: RistrettoPoint = self
+            .example_proofs
+            .iter()
+            .zip(predictions)
+            .map(|(proof, p)| match proof.example.label {
+                1 => p,
+                _ => pedersen.value_generator - p,
+            })
+            .sum();
+        self.count_proof.verify_proof(
+            &(correct - Scalar::from(required as u64) * pedersen.value_generator),
+            BucketSchema::bucket_bits(0, test_set.num_examples as u64 + 1),
+            pedersen,
+            &mut test_set.count_transcript(model, min_accuracy),
+        )
+    }
+
+    /// Get the proofs of the hidden predictions, in the order of the test set
+    pub fn get_example_proofs(&self) -> &[ExampleProof] {
+        &self.example_proofs
+    }
+}
+
+impl ProofScheme for AccuracyProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Label 1 for outputs of at least 0.5 on a scale of 100 * 100
+    fn schema() -> BucketSchema {
+        BucketSchema::new(10000, &[0, 5000, 20000]).unwrap()
+    }
+
+    // y = 0.5*x_0 + 0.25*x_1 + 0.1, which labels the first four examples correctly
+    fn model() -> LinearModel {
+        LinearModel::new(&[50, 25], 1000).unwrap()
+    }
+
+    fn test_set() -> TestSet {
+        let examples = [
+            (vec![100, 40], 1),
+            (vec![10, 20], 0),
+            (vec![60, 100], 1),
+            (vec![0, 0], 0),
+            (vec![90, 90], 0),
+        ];
+        let examples = examples
+            .iter()
+            .map(|(features, label)| LabeledExample::new(features, *label).unwrap())
+            .collect();
+        TestSet::new(examples, HashBackend::Sha256).unwrap()
+    }
+
+    #[test]
+    fn test_accuracy_proof_verifies_up_to_the_achieved_accuracy() {
+        let generators = LinearInferenceGenerators::new(2);
+        let (model, test_set) = (model(), test_set());
+        let commitment = test_set.commitment();
+        let model_commitment = model.commitment(&generators).unwrap();
+        let proof =
+            AccuracyProof::generate_proof(&model, &test_set, &schema(), 80, &generators).unwrap();
+        assert_eq!(proof.get_example_proofs().len(), 5);
+        assert_eq!(
+            proof.verify_proof(&commitment, &model_commitment, &schema(), 80, &generators),
+            Ok(())
+        );
+
+        // The proof doesn't claim more than it shows, and more can't be proven
+        assert!(proof
+            .verify_proof(&commitment, &model_commitment, &schema(), 81, &generators)
+            .is_err());
+        assert_eq!(
+            AccuracyProof::generate_proof(&model, &test_set, &schema(), 81, &generators).err(),
+            Some(Error::AccuracyBelowThreshold(4, 5))
+        );
+        assert_eq!(
+            AccuracyProof::generate_proof(&model, &test_set, &schema(), 101, &generators).err(),
+            Some(Error::InvalidAccuracy(101))
+        );
+        assert_eq!(
+            TestSet::new(vec![], HashBackend::Sha256),
+            Err(Error::EmptyTestSet)
+        );
+    }
+
+    #[test]
+    fn test_accuracy_proof_rejects_other_test_sets_models_and_examples() {
+        let generators = LinearInferenceGenerators::new(2);
+        let (model, test_set) = (model(), test_set());
+        let commitment = test_set.commitment();
+        let model_commitment = model.commitment(&generators).unwrap();
+        let proof =
+            AccuracyProof::generate_proof(&model, &test_set, &schema(), 60, &generators).unwrap();
+
+        // Another test set with an easier example
+        let mut examples = test_set.get_examples().to_vec();
+        examples[4] = LabeledExample::new(&[90, 90], 1).unwrap();
+        assert_eq!(
+            LabeledExample::new(&[90, 90], 2),
+            Err(Error::InvalidLabel(2))
+        );
+        let other_set = TestSet::new(examples, HashBackend::Sha256)
+            .unwrap()
+            .commitment();
+        assert_eq!(
+            proof.verify_proof(&other_set, &model_commitment, &schema(), 60, &generators),
+            Err(Error::TestSetMismatch(0))
+        );
+
+        // Another model with the same weights but other blindings
+        let other_model = LinearModel::new(&[50, 25], 1000)
+            .unwrap()
+            .commitment(&generators)
+            .unwrap();
+        assert!(proof
+            .verify_proof(&commitment, &other_model, &schema(), 60, &generators)
+            .is_err());
+
+        // Examples left out or swapped around
+        let mut dropped = proof.clone();
+        dropped.example_proofs.pop();
+        assert_eq!(
+            dropped.verify_proof(&commitment, &model_commitment, &schema(), 60, &generators),
+            Err(Error::TestSetMismatch(4))
+        );
+        let mut swapped = proof.clone();
+        swapped.example_proofs.swap(0, 1);
+        assert_eq!(
+            swapped.verify_proof(&commitment, &model_commitment, &schema(), 60, &generators),
+            Err(Error::TestSetMismatch(1))
+        );
+    }
+}
@@ -0,0 +1,756 @@
+//! Chunked commitments to model weight files allowing provers to open only the chunks an
+//! inference touches
+
+use std::io::{Read, Seek, SeekFrom};
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{Digest, HashBackend, PointEncoding, ProofScheme, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{
+    reproducible::parallel_map, BlindingProof, Error, PedersenGenerators, ReproducibleSeed,
+    SeededTranscript, SimpleProofProtocol,
+};
+
+// Model weight files are often far larger than the memory of the edge devices running inference,
+// and a single inference usually only touches a fraction of them. Committing to the whole file
+// as one value would force a prover to load all of it to prove anything about the model. Instead
+// the file is split into fixed size chunks which are committed to individually:
+//
+// 1. Each chunk is hashed into a scalar `d_i` and committed as `C_i = d_i*G + r_i*H`.
+// 2. The chunk commitments are the leaves of a Merkle tree, padded with zero nodes up to a power
+// of two.
+// 3. The model commitment is the hash of the tree root, the chunk length and the number of chunks,
+// so the same weights split differently can't be passed off as the same model.
+//
+// The tree and the model commitment are hashed with a [`HashBackend`] chosen by the deployment,
+// e.g. Poseidon when chunk openings are verified in a circuit. The backend is part of the
+// commitment, so verifiers always hash with the backend the commitment was created with.
+//
+// To show that a chunk belongs to the model, the prover reveals the chunk commitment and its
+// Merkle path, and proves that `C_i - d_i*G` is a multiple of `H`. Neither the prover nor the
+// verifier ever needs more than the opened chunks and a logarithmic number of tree nodes in memory
+// at a time.
+//
+// To show that a proof was produced with the committed model rather than some other inference
+// function, the model commitment is bound into the proof transcript before anything else. A
+// verifier holding the commitment rebuilds the same transcript, so a proof bound to one model
+// doesn't verify against another. The model proof itself reveals the chunk commitments, which
+// the verifier hashes back up to the root, and proves knowledge of an opening `(d, r)` of their
+// sum `S = d*G + r*H` with `d = sum d_i` and `r = sum r_i`, so only a holder of the chunk
+// blindings can produce it.
+
+/// Default chunk length of 1 MiB
+pub const DEFAULT_CHUNK_LEN: usize = 1 << 20;
+
+// Domain separator for hashing a chunk into a scalar
+const CHUNK_DOMAIN_SEP: &[u8] = b"MODEL_WEIGHT_CHUNK";
+
+// Domain separator for hashing Merkle tree leaves
+const LEAF_DOMAIN_SEP: &[u8] = b"MODEL_COMMITMENT_LEAF";
+
+// Domain separator for hashing Merkle tree nodes
+const NODE_DOMAIN_SEP: &[u8] = b"MODEL_COMMITMENT_NODE";
+
+// Domain separator for hashing the tree root and chunk layout into the model commitment
+const ROOT_DOMAIN_SEP: &[u8] = b"MODEL_COMMITMENT_ROOT";
+
+// Domain separator for initializing chunk opening transcripts
+const CHUNK_OPENING_DOMAIN_SEP: &[u8] = b"MODEL_CHUNK_OPENING";
+
+// Label of the rng streams chunk blindings are drawn from in reproducibility mode
+const CHUNK_BLINDING_STREAM: &[u8] = b"MODEL_CHUNK_BLINDING";
+
+// Label of the rng streams chunk opening proofs are drawn from in reproducibility mode
+const CHUNK_OPENING_STREAM: &[u8] = b"MODEL_CHUNK_OPENING";
+
+// Domain separator for initializing model proof transcripts
+const MODEL_PROOF_DOMAIN_SEP: &[u8] = b"MODEL_BOUND_PROOF";
+
+// Domain separator for squeezing hashes out of transcripts
+const HASH_DOMAIN_SEP: &[u8] = b"HASH";
+
+// Hash a chunk of weights into a scalar
+fn hash_chunk(chunk: &[u8]) -> Scalar {
+    let mut transcript = Transcript::new(CHUNK_DOMAIN_SEP);
+    transcript.append_message(CHUNK_DOMAIN_SEP, chunk);
+    let mut buf = [0; 64];
+    transcript.challenge_bytes(HASH_DOMAIN_SEP, &mut buf);
+    Scalar::from_bytes_mod_order_wide(&buf)
+}
+
+// Hash a chunk commitment into a Merkle tree leaf
+fn hash_leaf(digest: &HashBackend, chunk_commitment: &RistrettoPoint) -> [u8; 32] {
+    digest.hash(LEAF_DOMAIN_SEP, &chunk_commitment.encode())
+}
+
+// Hash two child nodes into their parent node
+fn hash_node(digest: &HashBackend, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    digest.hash_pair(NODE_DOMAIN_SEP, left, right)
+}
+
+// Build the Merkle tree levels from the leaves, padded with zero nodes to a power of two, up to
+// the root, hashing each pair of children under `node_domain_sep`. The vocabulary and test set
+// commitments build their trees the same way under their own domain separators.
+pub(crate) fn merkle_tree(
+    digest: &HashBackend,
+    node_domain_sep: &[u8],
+    mut leaves: Vec<[u8; 32]>,
+) -> Vec<Vec<[u8; 32]>> {
+    leaves.resize(leaves.len().next_power_of_two(), [0; 32]);
+    let mut tree = vec![leaves];
+    while tree[tree.len() - 1].len() > 1 {
+        let level = tree[tree.len() - 1]
+            .chunks(2)
+            .map(|pair| digest.hash_pair(node_domain_sep, &pair[0], &pair[1]))
+            .collect();
+        tree.push(level);
+    }
+    tree
+}
+
+// Read up to `buf.len()` bytes, stopping early only at the end of the reader
+fn read_chunk<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::Io(e.to_string())),
+        }
+    }
+    Ok(filled)
+}
+
+/// Public commitment to a model's weights split into fixed size chunks
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ModelCommitment {
+    // Hash of the Merkle root of the chunk commitments and the chunk layout
+    root: [u8; 32],
+    // Length of every chunk but the last
+    chunk_len: usize,
+    // Number of chunks the weights are split into
+    num_chunks: usize,
+    // Hash backend the tree and commitment are hashed with
+    digest: HashBackend,
+}
+
+impl ModelCommitment {
+    fn from_tree_root(
+        tree_root: &[u8; 32],
+        chunk_len: usize,
+        num_chunks: usize,
+        digest: HashBackend,
+    ) -> Self {
+        let mut data = digest.label().to_vec();
+        data.extend_from_slice(tree_root);
+        data.extend_from_slice(&(chunk_len as u64).to_le_bytes());
+        data.extend_from_slice(&(num_chunks as u64).to_le_bytes());
+        Self {
+            root: digest.hash(ROOT_DOMAIN_SEP, &data),
+            chunk_len,
+            num_chunks,
+            digest,
+        }
+    }
+
+    /// Commit to a serialized model, i.e. its architecture and weights, split into chunks of
+    /// [`DEFAULT_CHUNK_LEN`]
+    pub fn commit(
+        model_bytes: &[u8],
+        generators: &PedersenGenerators,
+    ) -> Result<CommittedModel, Error> {
+        CommittedModel::commit(model_bytes, DEFAULT_CHUNK_LEN, generators)
+    }
+
+    /// Bind the model commitment into a proof transcript, so proofs created with it only verify
+    /// against this model
+    pub fn bind_transcript(&self, transcript: &mut Transcript) {
+        transcript.append_message(ROOT_DOMAIN_SEP, &self.root);
+        transcript.append_u64(b"CHUNK_LEN", self.chunk_len as u64);
+        transcript.append_u64(b"NUM_CHUNKS", self.num_chunks as u64);
+        transcript.append_message(b"DIGEST", self.digest.label());
+    }
+
+    // Create a model proof transcript bound to the model and the proven statement
+    fn proof_transcript(&self, statement: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(MODEL_PROOF_DOMAIN_SEP);
+        self.bind_transcript(&mut transcript);
+        transcript.append_message(b"STATEMENT", statement);
+        transcript
+    }
+
+    /// Verify a statement was proven by a holder of this committed model
+    pub fn verify_against_proof(
+        &self,
+        statement: &[u8],
+        proof: &ModelProof,
+        generators: &PedersenGenerators,
+    ) -> Result<(), Error> {
+        if proof.chunk_commitments.len() != self.num_chunks {
+            return Err(Error::ModelMismatch);
+        }
+        let leaves = proof
+            .chunk_commitments
+            .iter()
+            .map(|chunk_commitment| hash_leaf(&self.digest, chunk_commitment))
+            .collect();
+        let tree = merkle_tree(&self.digest, NODE_DOMAIN_SEP, leaves);
+        let tree_root = &tree[tree.len() - 1][0];
+        if Self::from_tree_root(tree_root, self.chunk_len, self.num_chunks, self.digest) != *self {
+            return Err(Error::ModelMismatch);
+        }
+
+        let sum: RistrettoPoint = proof.chunk_commitments.iter().sum();
+        let mut transcript = self.proof_transcript(statement);
+        transcript.append_proof_value(&sum);
+        transcript.append_proof_value(&proof.nonce_commitment);
+        let challenge = transcript.get_challenge();
+        if generators.commit(&proof.value_response, &proof.blinding_response)
+            != proof.nonce_commitment + challenge * sum
+        {
+            return Err(Error::ModelMismatch);
+        }
+        Ok(())
+    }
+
+    /// Get the commitment hash
+    pub fn get_root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Get the length of every chunk but the last
+    pub fn get_chunk_len(&self) -> usize {
+        self.chunk_len
+    }
+
+    /// Get the number of chunks the weights are split into
+    pub fn get_num_chunks(&self) -> usize {
+        self.num_chunks
+    }
+
+    /// Get the hash backend the Merkle tree and commitment are hashed with
+    pub fn get_digest(&self) -> HashBackend {
+        self.digest
+    }
+
+    /// Load a single chunk from a weight file without reading the rest of it
+    pub fn load_chunk<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        index: usize,
+    ) -> Result<Vec<u8>, Error> {
+        if index >= self.num_chunks {
+            return Err(Error::ChunkOutOfRange(index));
+        }
+        reader
+            .seek(SeekFrom::Start((index * self.chunk_len) as u64))
+            .map_err(|e| Error::Io(e.to_string()))?;
+        let mut chunk = vec![0; self.chunk_len];
+        let len = read_chunk(reader, &mut chunk)?;
+        chunk.truncate(len);
+        Ok(chunk)
+    }
+
+    // Create a transcript bound to the model and the chunk being opened
+    fn opening_transcript(&self, index: usize, chunk_commitment: &RistrettoPoint) -> Transcript {
+        let mut transcript = Transcript::new(CHUNK_OPENING_DOMAIN_SEP);
+        transcript.append_message(ROOT_DOMAIN_SEP, &self.root);
+        transcript.append_u64(b"CHUNK_INDEX", index as u64);
+        transcript.append_proof_value(chunk_commitment);
+        transcript
+    }
+
+    /// Verify that a chunk of weights belongs to the committed model
+    pub fn verify_chunk(
+        &self,
+        chunk: &[u8],
+        opening: &ChunkOpening,
+        generators: &PedersenGenerators,
+    ) -> Result<(), Error> {
+        let index = opening.index;
+        if index >= self.num_chunks {
+            return Err(Error::ChunkOutOfRange(index));
+        }
+        if chunk.is_empty() || chunk.len() > self.chunk_len {
+            return Err(Error::InvalidChunkLength(chunk.len()));
+        }
+
+        // Walk the Merkle path from the chunk commitment up to the root
+        let mut node = hash_leaf(&self.digest, &opening.chunk_commitment);
+        let mut position = index;
+        for sibling in opening.merkle_path.iter() {
+            node = match position % 2 {
+                0 => hash_node(&self.digest, &node, sibling),
+                _ => hash_node(&self.digest, sibling, &node),
+            };
+            position /= 2;
+        }
+        let expected_depth = self.num_chunks.next_power_of_two().trailing_zeros() as usize;
+        if opening.merkle_path.len() != expected_depth
+            || Self::from_tree_root(&node, self.chunk_len, self.num_chunks, self.digest) != *self
+        {
+            return Err(Error::ChunkMismatch(index));
+        }
+
+        // Check the chunk commitment opens to the hash of the chunk
+        let blinding_point =
+            opening.chunk_commitment - hash_chunk(chunk) * generators.value_generator;
+        opening
+            .blinding_proof
+            .verify_proof(
+                &blinding_point,
+                generators,
+                &mut self.opening_transcript(index, &opening.chunk_commitment),
+            )
+            .map_err(|_| Error::ChunkMismatch(index))
+    }
+}
+
+/// Proof that a chunk of weights belongs to a committed model
+#[derive(Clone, Debug)]
+pub struct ChunkOpening {
+    // Position of the chunk within the weights
+    index: usize,
+    // Pedersen commitment to the hash of the chunk
+    chunk_commitment: RistrettoPoint,
+    // Sibling nodes from the chunk's leaf up to the Merkle root
+    merkle_path: Vec<[u8; 32]>,
+    // Proof the chunk commitment minus the chunk hash is a multiple of the blinding generator
+    blinding_proof: BlindingProof,
+}
+
+impl ChunkOpening {
+    /// Get the position of the opened chunk
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the commitment to the opened chunk
+    pub fn get_chunk_commitment(&self) -> RistrettoPoint {
+        self.chunk_commitment
+    }
+}
+
+/// Proof that a statement was proven by a holder of a committed model. The proof carries every
+/// chunk commitment, so its size grows with the number of chunks.
+#[derive(Clone, Debug)]
+pub struct ModelProof {
+    // Pedersen commitment of each chunk
+    chunk_commitments: Vec<RistrettoPoint>,
+    // Commitment `a*G + b*H` to the random nonces
+    nonce_commitment: RistrettoPoint,
+    // Response `a + c*d` for the sum of the chunk hashes
+    value_response: Scalar,
+    // Response `b + c*r` for the sum of the chunk blindings
+    blinding_response: Scalar,
+}
+
+/// Prover's side of a model commitment holding the chunk blindings and Merkle tree, but not the
+/// weights themselves
+#[derive(Clone, Debug)]
+pub struct CommittedModel {
+    commitment: ModelCommitment,
+    generators: PedersenGenerators,
+    // Hash of each chunk
+    chunk_hashes: Vec<Scalar>,
+    // Blinding scalar of each chunk commitment
+    blindings: Vec<Scalar>,
+    // Pedersen commitment of each chunk
+    chunk_commitments: Vec<RistrettoPoint>,
+    // Merkle tree levels from the padded leaves up to the root
+    tree: Vec<Vec<[u8; 32]>>,
+    // Seed the blindings and opening proofs are derived from in reproducibility mode
+    seed: Option<ReproducibleSeed>,
+}
+
+impl CommittedModel {
+    /// Commit to weights streamed from a reader one chunk at a time
+    pub fn commit_reader<R: Read>(
+        reader: &mut R,
+        chunk_len: usize,
+        generators: &PedersenGenerators,
+    ) -> Result<Self, Error> {
+        Self::commit_reader_with_digest(reader, chunk_len, generators, HashBackend::default())
+    }
+
+    /// Commit to weights streamed from a reader one chunk at a time, hashing the Merkle tree with
+    /// the given backend
+    pub fn commit_reader_with_digest<R: Read>(
+        reader: &mut R,
+        chunk_len: usize,
+        generators: &PedersenGenerators,
+        digest: HashBackend,
+    ) -> Result<Self, Error> {
+        if chunk_len == 0 {
+            return Err(Error::InvalidChunkLength(chunk_len));
+        }
+        let mut rng = rand::rngs::OsRng;
+        let mut buf = vec![0; chunk_len];
+        let mut chunk_hashes = Vec::new();
+        let mut blindings = Vec::new();
+        let mut chunk_commitments = Vec::new();
+        loop {
+            let len = read_chunk(reader, &mut buf)?;
+            if len == 0 {
+                break;
+            }
+            let chunk_hash = hash_chunk(&buf[..len]);
+            let blinding = Scalar::random(&mut rng);
+            chunk_commitments.push(generators.commit(&chunk_hash, &blinding));
+            chunk_hashes.push(chunk_hash);
+            blindings.push(blinding);
+            if len < chunk_len {
+                break;
+            }
+        }
+        Self::from_chunks(
+            chunk_len,
+            generators,
+            chunk_hashes,
+            blindings,
+            chunk_commitments,
+            digest,
+            None,
+        )
+    }
+
+    /// Commit to weights held in memory in reproducibility mode, spreading the chunks over up to
+    /// `num_threads` threads. Chunk blindings and opening proofs are derived from the seed, so the
+    /// same weights and seed always give bit-identical commitments and openings, whatever the
+    /// number of threads.
+    pub fn commit_reproducible(
+        weights: &[u8],
+        chunk_len: usize,
+        generators: &PedersenGenerators,
+        seed: &ReproducibleSeed,
+        num_threads: usize,
+    ) -> Result<Self, Error> {
+        if chunk_len == 0 {
+            return Err(Error::InvalidChunkLength(chunk_len));
+        }
+        let chunks: Vec<&[u8]> = weights.chunks(chunk_len).collect();
+        let committed = parallel_map(&chunks, num_threads, |index, chunk| {
+            let chunk_hash = hash_chunk(chunk);
+            let blinding = seed.scalar(CHUNK_BLINDING_STREAM, index as u64);
+            let chunk_commitment = generators.commit(&chunk_hash, &blinding);
+            (chunk_hash, blinding, chunk_commitment)
+        });
+        let mut chunk_hashes = Vec::with_capacity(committed.len());
+        let mut blindings = Vec::with_capacity(committed.len());
+        let mut chunk_commitments = Vec::with_capacity(committed.len());
+        for (chunk_hash, blinding, chunk_commitment) in committed {
+            chunk_hashes.push(chunk_hash);
+            blindings.push(blinding);
+            chunk_commitments.push(chunk_commitment);
+        }
+        Self::from_chunks(
+            chunk_len,
+            generators,
+            chunk_hashes,
+            blindings,
+            chunk_commitments,
+            HashBackend::default(),
+            Some(seed.clone()),
+        )
+    }
+
+    // Build the Merkle tree over the chunk commitments, which must be in chunk order
+    fn from_chunks(
+        chunk_len: usize,
+        generators: &PedersenGenerators,
+        chunk_hashes: Vec<Scalar>,
+        blindings: Vec<Scalar>,
+        chunk_commitments: Vec<RistrettoPoint>,
+        digest: HashBackend,
+        seed: Option<ReproducibleSeed>,
+    ) -> Result<Self, Error> {
+        if chunk_commitments.is_empty() {
+            return Err(Error::InvalidChunkLength(0));
+        }
+
+        let leaves = chunk_commitments
+            .iter()
+            .map(|chunk_commitment| hash_leaf(&digest, chunk_commitment))
+            .collect();
+        let tree = merkle_tree(&digest, NODE_DOMAIN_SEP, leaves);
+        let commitment = ModelCommitment::from_tree_root(
+            &tree[tree.len() - 1][0],
+            chunk_len,
+            blindings.len(),
+            digest,
+        );
+
+        Ok(Self {
+            commitment,
+            generators: *generators,
+            chunk_hashes,
+            blindings,
+            chunk_commitments,
+            tree,
+            seed,
+        })
+    }
+
+    /// Commit to weights held in memory
+    pub fn commit(
+        weights: &[u8],
+        chunk_len: usize,
+        generators: &PedersenGenerators,
+    ) -> Result<Self, Error> {
+        Self::commit_reader(&mut &weights[..], chunk_len, generators)
+    }
+
+    /// Get the public model commitment
+    pub fn get_commitment(&self) -> ModelCommitment {
+        self.commitment
+    }
+
+    /// Prove that a chunk loaded from the weight file belongs to the committed model
+    pub fn open_chunk(&self, index: usize, chunk: &[u8]) -> Result<ChunkOpening, Error> {
+        let chunk_commitment = *self
+            .chunk_commitments
+            .get(index)
+            .ok_or(Error::ChunkOutOfRange(index))?;
+        let blinding = self.blindings[index];
+        if self.generators.commit(&hash_chunk(chunk), &blinding) != chunk_commitment {
+            return Err(Error::ChunkMismatch(index));
+        }
+
+        let merkle_path = self.tree[..self.tree.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(depth, level)| level[(index >> depth) ^ 1])
+            .collect();
+        let mut transcript = self.commitment.opening_transcript(index, &chunk_commitment);
+        let blinding_proof = match &self.seed {
+            Some(seed) => BlindingProof::generate_proof(
+                &blinding,
+                &self.generators,
+                &mut SeededTranscript::new(transcript, seed, CHUNK_OPENING_STREAM, index as u64),
+            ),
+            None => BlindingProof::generate_proof(&blinding, &self.generators, &mut transcript),
+        };
+
+        Ok(ChunkOpening {
+            index,
+            chunk_commitment,
+            merkle_path,
+            blinding_proof,
+        })
+    }
+
+    /// Prove a statement, e.g. the digest of an inference, as the holder of the committed model
+    pub fn prove_statement(&self, statement: &[u8]) -> ModelProof {
+        let value: Scalar = self.chunk_hashes.iter().sum();
+        let blinding: Scalar = self.blindings.iter().sum();
+        let sum: RistrettoPoint = self.chunk_commitments.iter().sum();
+
+        let mut transcript = self.commitment.proof_transcript(statement);
+        transcript.append_proof_value(&sum);
+        let mut rng = transcript.get_rng(&sum);
+        let (value_nonce, blinding_nonce) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let nonce_commitment = self.generators.commit(&value_nonce, &blinding_nonce);
+        transcript.append_proof_value(&nonce_commitment);
+        let challenge = transcript.get_challenge();
+
+        ModelProof {
+            chunk_commitments: self.chunk_commitments.clone(),
+            nonce_commitment,
+            value_response: value_nonce + challenge * value,
+            blinding_response: blinding_nonce + challenge * blinding,
+        }
+    }
+}
+
+impl ProofScheme for ModelProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn test_weights() -> Vec<u8> {
+        (0..1000u32).map(|i| (i * 7 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_chunks_loaded_on_demand_verify_against_the_model_commitment() {
+        let generators = PedersenGenerators::default();
+        let weights = test_weights();
+        let mut weight_file = Cursor::new(weights.clone());
+        let model = CommittedModel::commit_reader(&mut weight_file, 128, &generators).unwrap();
+        let commitment = model.get_commitment();
+        assert_eq!(commitment.get_num_chunks(), 8);
+
+        // Only the chunks touched by an inference are loaded and opened, including the short last
+        // chunk
+        for index in [2, 7] {
+            let chunk = commitment.load_chunk(&mut weight_file, index).unwrap();
+            assert_eq!(chunk, weights.chunks(128).nth(index).unwrap());
+            let opening = model.open_chunk(index, &chunk).unwrap();
+            assert!(commitment
+                .verify_chunk(&chunk, &opening, &generators)
+                .is_ok());
+        }
+        assert_eq!(
+            commitment.load_chunk(&mut weight_file, 8),
+            Err(Error::ChunkOutOfRange(8))
+        );
+    }
+
+    #[test]
+    fn test_tampered_chunks_and_openings_are_rejected() {
+        let generators = PedersenGenerators::default();
+        let weights = test_weights();
+        let model = CommittedModel::commit(&weights, 300, &generators).unwrap();
+        let commitment = model.get_commitment();
+
+        // The prover can't open a chunk it doesn't hold
+        let mut chunk = weights[..300].to_vec();
+        chunk[0] ^= 1;
+        assert_eq!(
+            model.open_chunk(0, &chunk).err(),
+            Some(Error::ChunkMismatch(0))
+        );
+
+        // An opening doesn't verify for a different chunk or at a different position
+        let opening = model.open_chunk(1, &weights[300..600]).unwrap();
+        assert_eq!(
+            commitment.verify_chunk(&weights[600..900], &opening, &generators),
+            Err(Error::ChunkMismatch(1))
+        );
+        let moved_opening = ChunkOpening {
+            index: 0,
+            ..opening.clone()
+        };
+        assert_eq!(
+            commitment.verify_chunk(&weights[300..600], &moved_opening, &generators),
+            Err(Error::ChunkMismatch(0))
+        );
+
+        // The same weights split differently give a different commitment
+        let rechunked = CommittedModel::commit(&weights, 250, &generators).unwrap();
+        assert_ne!(rechunked.get_commitment().get_root(), commitment.get_root());
+    }
+
+    #[test]
+    fn test_statements_verify_only_against_the_model_they_were_proven_with() {
+        let generators = PedersenGenerators::default();
+        let weights = test_weights();
+        let model = ModelCommitment::commit(&weights, &generators).unwrap();
+        let commitment = model.get_commitment();
+        assert_eq!(commitment.get_chunk_len(), DEFAULT_CHUNK_LEN);
+
+        let proof = model.prove_statement(b"inference digest");
+        assert!(commitment
+            .verify_against_proof(b"inference digest", &proof, &generators)
+            .is_ok());
+        assert_eq!(
+            commitment.verify_against_proof(b"other digest", &proof, &generators),
+            Err(Error::ModelMismatch)
+        );
+
+        // A proof made with a different model doesn't verify against the commitment, even with
+        // the original chunk commitments swapped in
+        let mut other_weights = weights.clone();
+        other_weights[0] ^= 1;
+        let other_model = ModelCommitment::commit(&other_weights, &generators).unwrap();
+        let other_proof = other_model.prove_statement(b"inference digest");
+        assert_eq!(
+            commitment.verify_against_proof(b"inference digest", &other_proof, &generators),
+            Err(Error::ModelMismatch)
+        );
+        let spliced_proof = ModelProof {
+            chunk_commitments: proof.chunk_commitments.clone(),
+            ..other_proof
+        };
+        assert_eq!(
+            commitment.verify_against_proof(b"inference digest", &spliced_proof, &generators),
+            Err(Error::ModelMismatch)
+        );
+    }
+
+    #[test]
+    fn test_commitments_are_bound_to_their_hash_backend() {
+        let generators = PedersenGenerators::default();
+        let weights = test_weights();
+        let mut roots = Vec::new();
+        for digest in [HashBackend::Blake3, HashBackend::Poseidon] {
+            let model = CommittedModel::commit_reader_with_digest(
+                &mut &weights[..],
+                128,
+                &generators,
+                digest,
+            )
+            .unwrap();
+            let commitment = model.get_commitment();
+            assert_eq!(commitment.get_digest(), digest);
+            let opening = model.open_chunk(3, &weights[384..512]).unwrap();
+            assert!(commitment
+                .verify_chunk(&weights[384..512], &opening, &generators)
+                .is_ok());
+            let proof = model.prove_statement(b"inference digest");
+            assert!(commitment
+                .verify_against_proof(b"inference digest", &proof, &generators)
+                .is_ok());
+
+            // The same tree can't be checked against a commitment claiming another backend
+            let relabeled = ModelCommitment {
+                digest: HashBackend::Sha256,
+                ..commitment
+            };
+            assert_eq!(
+                relabeled.verify_chunk(&weights[384..512], &opening, &generators),
+                Err(Error::ChunkMismatch(3))
+            );
+            roots.push(commitment.get_root());
+        }
+        let default_model = CommittedModel::commit(&weights, 128, &generators).unwrap();
+        assert_eq!(
+            default_model.get_commitment().get_digest(),
+            HashBackend::Sha256
+        );
+        roots.push(default_model.get_commitment().get_root());
+        roots.dedup();
+        assert_eq!(roots.len(), 3);
+    }
+
+    #[test]
+    fn test_reproducible_commitments_are_identical_for_any_thread_count() {
+        let generators = PedersenGenerators::default();
+        let weights = test_weights();
+        let seed = ReproducibleSeed::new(b"compliance seed");
+        let model =
+            CommittedModel::commit_reproducible(&weights, 128, &generators, &seed, 1).unwrap();
+        let commitment = model.get_commitment();
+        let opening = model.open_chunk(5, &weights[640..768]).unwrap();
+        assert!(commitment
+            .verify_chunk(&weights[640..768], &opening, &generators)
+            .is_ok());
+
+        for num_threads in [3, 8] {
+            let parallel_model =
+                CommittedModel::commit_reproducible(&weights, 128, &generators, &seed, num_threads)
+                    .unwrap();
+            assert_eq!(parallel_model.get_commitment(), commitment);
+            let parallel_opening = parallel_model.open_chunk(5, &weights[640..768]).unwrap();
+            assert_eq!(parallel_opening.chunk_commitment, opening.chunk_commitment);
+            assert_eq!(parallel_opening.merkle_path, opening.merkle_path);
+            assert_eq!(
+                parallel_opening.blinding_proof.get_proof_pair(),
+                opening.blinding_proof.get_proof_pair()
+            );
+        }
+
+        // A different seed blinds the chunks differently
+        let other_seed = ReproducibleSeed::new(b"other seed");
+        let other_model =
+            CommittedModel::commit_reproducible(&weights, 128, &generators, &other_seed, 3)
+                .unwrap();
+        assert_ne!(other_model.get_commitment(), commitment);
+    }
+}
@@ -0,0 +1,271 @@
+//! Proofs that a committed fixed-point model output falls into a bucket of a public schema
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{ProofScheme, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{Error, PedersenGenerators, RangeProof, SimpleProofProtocol};
+
+// Models output fixed-point numbers such as a confidence of 0.7312, and many applications only
+// need to know which bucket of an ordered scale the output falls into, e.g. "confidence is in
+// [0.7, 0.8)", without learning the exact value. Range proofs only cover ranges [0, 2^n), so a
+// bucket statement is mapped onto two of them:
+//
+// 1. Encoding: outputs are scaled by a public factor and rounded down to integers, so with a scale
+// of 10000 the confidence 0.7312 becomes 7312. A public schema lists the increasing bucket
+// boundaries b_0 < b_1 < .. < b_m on the same scale, bucket i being [b_i, b_(i+1)).
+// 2. Commitment: the prover commits to the encoded output as C = v*G + r*H.
+// 3. Lower bound: C - b_i*G commits to v - b_i with blinding r, which lies in [0, 2^k) exactly when
+// v >= b_i, provided v - b_i can't wrap around the group order.
+// 4. Upper bound: (b_(i+1) - 1)*G - C commits to b_(i+1) - 1 - v with blinding -r, which lies in
+// [0, 2^k) exactly when v < b_(i+1).
+//
+// With k the number of bits of the bucket width, both differences being small rules out
+// wraparound, so together the two range proofs show b_i <= v < b_(i+1). The schema and the bucket
+// are bound into the transcript, so a proof can't be replayed against another schema.
+
+// Domain separator for initializing ordered inference transcripts
+const ORDERED_INFERENCE_DOMAIN_SEP: &[u8] = b"ORDERED_INFERENCE_BUCKET";
+
+/// Public schema of ordered buckets over fixed-point encoded model outputs
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BucketSchema {
+    // Factor outputs are multiplied by before rounding down to an integer
+    scale: u64,
+    // Strictly increasing bucket boundaries on the encoded scale
+    boundaries: Vec<u64>,
+}
+
+impl BucketSchema {
+    /// Create a schema from at least two strictly increasing boundaries, given on the encoded
+    /// scale. Bucket i covers [boundaries[i], boundaries[i + 1]).
+    pub fn new(scale: u64, boundaries: &[u64]) -> Result<Self, Error> {
+        if scale == 0
+            || boundaries.len() < 2
+            || boundaries.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            return Err(Error::InvalidBucketSchema);
+        }
+        Ok(Self {
+            scale,
+            boundaries: boundaries.to_vec(),
+        })
+    }
+
+    /// Get the number of buckets
+    pub fn num_buckets(&self) -> usize {
+        self.boundaries.len() - 1
+    }
+
+    /// Get the bounds [lower, upper) of a bucket on the encoded scale
+    pub fn get_bucket(&self, bucket: usize) -> Result<(u64, u64), Error> {
+        if bucket >= self.num_buckets() {
+            return Err(Error::BucketOutOfRange(bucket));
+        }
+        Ok((self.boundaries[bucket], self.boundaries[bucket + 1]))
+    }
+
+    /// Encode a model output as a fixed-point integer on the schema's scale
+    pub fn encode(&self, output: f64) -> Result<u64, Error> {
+        let encoded = (output * self.scale as f64).floor();
+        if !(0.0..u64::MAX as f64).contains(&encoded) {
+            return Err(Error::InvalidFixedPoint);
+        }
+        Ok(encoded as u64)
+    }
+
+    /// Find the bucket an encoded output falls into
+    pub fn bucket_of(&self, value: u64) -> Result<usize, Error> {
+        (0..self.num_buckets())
+            .find(|bucket| {
+                self.boundaries[*bucket] <= value && value < self.boundaries[*bucket + 1]
+            })
+            .ok_or(Error::ValueOutsideSchema(value))
+    }
+
+    // Schema with a single bucket covering every bucket of this one
+    pub(crate) fn merged(&self) -> Self {
+        Self {
+            scale: self.scale,
+            boundaries: vec![
+                self.boundaries[0],
+                self.boundaries[self.boundaries.len() - 1],
+            ],
+        }
+    }
+
+    // Number of bits needed to cover every value of a bucket in a range proof
+    pub(crate) fn bucket_bits(lower: u64, upper: u64) -> usize {
+        (64 - (upper - 1 - lower).leading_zeros() as usize).max(1)
+    }
+
+    // Create a transcript bound to the schema, the committed output and the claimed bucket
+    fn transcript(&self, commitment: &RistrettoPoint, bucket: usize) -> Transcript {
+        let mut transcript = Transcript::new(ORDERED_INFERENCE_DOMAIN_SEP);
+        transcript.append_u64(b"SCALE", self.scale);
+        transcript.append_u64(b"NUM_BOUNDARIES", self.boundaries.len() as u64);
+        for boundary in self.boundaries.iter() {
+            transcript.append_u64(b"BOUNDARY", *boundary);
+        }
+        transcript.append_proof_value(commitment);
+        transcript.append_u64(b"BUCKET", bucket as u64);
+        transcript
+    }
+}
+
+/// Proof that a committed model output lies in a bucket of a [`BucketSchema`]
+#[derive(Clone, Debug)]
+pub struct OrderedInferenceProof {
+    // Commitment to the encoded output
+    commitment: RistrettoPoint,
+    // Bucket the output lies in
+    bucket: usize,
+    // Proof the output minus the bucket's lower bound is small and not negative
+    lower_proof: RangeProof,
+    // Proof the bucket's last value minus the output is small and not negative
+    upper_proof: RangeProof,
+}
+
+impl OrderedInferenceProof {
+    /// Commit to an encoded output with a fresh blinding and prove its bucket, returning the
+    /// proof along with the blinding
+    pub fn commit_and_prove(
+        value: u64,
+        schema: &BucketSchema,
+        generators: &PedersenGenerators,
+    ) -> Result<(Self, Scalar), Error> {
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        Self::generate_proof(value, &blinding, schema, generators).map(|proof| (proof, blinding))
+    }
+
+    /// Prove that the commitment `value*G + blinding*H` commits to an output in the bucket of the
+    /// schema which contains `value`
+    pub fn generate_proof(
+        value: u64,
+        blinding: &Scalar,
+        schema: &BucketSchema,
+        generators: &PedersenGenerators,
+    ) -> Result<Self, Error> {
+        let bucket = schema.bucket_of(value)?;
+        let (lower, upper) = schema.get_bucket(bucket)?;
+        let bits = BucketSchema::bucket_bits(lower, upper);
+        let commitment = generators.commit(&Scalar::from(value), blinding);
+        let mut transcript = schema.transcript(&commitment, bucket);
+        let lower_proof =
+            RangeProof::generate_proof(value - lower, blinding, bits, generators, &mut transcript)?;
+        let upper_proof = RangeProof::generate_proof(
+            upper - 1 - value,
+            &-blinding,
+            bits,
+            generators,
+            &mut transcript,
+        )?;
+        Ok(Self {
+            commitment,
+            bucket,
+            lower_proof,
+            upper_proof,
+        })
+    }
+
+    /// Get the commitment to the encoded output
+    pub fn get_commitment(&self) -> RistrettoPoint {
+        self.commitment
+    }
+
+    /// Get the bucket the output is claimed to lie in
+    pub fn get_bucket(&self) -> usize {
+        self.bucket
+    }
+
+    /// Verify the committed output lies in the claimed bucket of the schema, returning the bucket
+    pub fn verify_proof(
+        &self,
+        schema: &BucketSchema,
+        generators: &PedersenGenerators,
+    ) -> Result<usize, Error> {
+        let (lower, upper) = schema.get_bucket(self.bucket)?;
+        let bits = BucketSchema::bucket_bits(lower, upper);
+        let mut transcript = schema.transcript(&self.commitment, self.bucket);
+        let g = generators.value_generator;
+        self.lower_proof.verify_proof(
+            &(self.commitment - Scalar::from(lower) * g),
+            bits,
+            generators,
+            &mut transcript,
+        )?;
+        self.upper_proof.verify_proof(
+            &(Scalar::from(upper - 1) * g - self.commitment),
+            bits,
+            generators,
+            &mut transcript,
+        )?;
+        Ok(self.bucket)
+    }
+}
+
+impl ProofScheme for OrderedInferenceProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Confidence buckets [0, 0.5), [0.5, 0.7), [0.7, 0.8) and [0.8, 1.0]
+    fn confidence_schema() -> BucketSchema {
+        BucketSchema::new(10000, &[0, 5000, 7000, 8000, 10001]).unwrap()
+    }
+
+    #[test]
+    fn test_outputs_prove_their_bucket() {
+        let generators = PedersenGenerators::default();
+        let schema = confidence_schema();
+        for (output, bucket) in [(0.7312, 2), (0.0, 0), (0.7, 2), (0.79999, 2), (1.0, 3)] {
+            let value = schema.encode(output).unwrap();
+            let (proof, blinding) =
+                OrderedInferenceProof::commit_and_prove(value, &schema, &generators).unwrap();
+            assert_eq!(proof.verify_proof(&schema, &generators), Ok(bucket));
+            assert_eq!(
+                proof.get_commitment(),
+                generators.commit(&Scalar::from(value), &blinding)
+            );
+        }
+        assert_eq!(
+            OrderedInferenceProof::commit_and_prove(10001, &schema, &generators).err(),
+            Some(Error::ValueOutsideSchema(10001))
+        );
+        assert_eq!(schema.encode(-0.1), Err(Error::InvalidFixedPoint));
+        assert_eq!(
+            BucketSchema::new(100, &[0, 50, 50]),
+            Err(Error::InvalidBucketSchema)
+        );
+    }
+
+    #[test]
+    fn test_proofs_dont_transfer_to_other_buckets_or_schemas() {
+        let generators = PedersenGenerators::default();
+        let schema = confidence_schema();
+        let (proof, _) =
+            OrderedInferenceProof::commit_and_prove(7312, &schema, &generators).unwrap();
+
+        let moved = OrderedInferenceProof {
+            bucket: 3,
+            ..proof.clone()
+        };
+        assert_eq!(
+            moved.verify_proof(&schema, &generators),
+            Err(Error::RangeProofMismatch)
+        );
+        let other_schema = BucketSchema::new(10000, &[0, 7000, 7500, 10001]).unwrap();
+        assert_eq!(
+            proof.verify_proof(&other_schema, &generators),
+            Err(Error::RangeProofMismatch)
+        );
+        let out_of_range = OrderedInferenceProof { bucket: 4, ..proof };
+        assert_eq!(
+            out_of_range.verify_proof(&schema, &generators),
+            Err(Error::BucketOutOfRange(4))
+        );
+    }
+}
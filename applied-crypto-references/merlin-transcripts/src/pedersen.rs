@@ -0,0 +1,159 @@
+//! Pedersen commitments over the Ristretto group and proofs of knowledge of their blinding factors
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use encodings::{CommitmentGroup, PointEncoding, ProofScheme, Ristretto, SecurityLevel};
+
+use crate::{challenge_oracle::validate_points, ChallengeOracle, Error};
+
+// A Pedersen commitment to a value `v` is the point `C = v*G + r*H` where `r` is a random blinding
+// scalar. The commitment hides `v` because `r*H` is a uniformly random point, and it binds the
+// committer to `v` as long as nobody knows the discrete log of `H` relative to `G`.
+//
+// To guarantee nobody knows that discrete log, `H` is the blinding generator of the `encodings`
+// crate's Ristretto group, which maps a hash onto the curve with the Ristretto hash-to-group map.
+// Sharing the one derivation means commitments made with `PedersenCommitment<Ristretto>` can be
+// opened and proven by every proof in this crate.
+
+/// Pair of generators `G` and `H` used to create Pedersen commitments `C = v*G + r*H`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PedersenGenerators {
+    /// Generator the committed value is multiplied by
+    pub value_generator: RistrettoPoint,
+    /// Generator the blinding scalar is multiplied by
+    pub blinding_generator: RistrettoPoint,
+}
+
+impl Default for PedersenGenerators {
+    fn default() -> Self {
+        Self {
+            value_generator: RISTRETTO_BASEPOINT_POINT,
+            blinding_generator: Ristretto::blinding_generator(),
+        }
+    }
+}
+
+impl PedersenGenerators {
+    /// Commit to a value using a blinding scalar
+    pub fn commit(&self, value: &Scalar, blinding: &Scalar) -> RistrettoPoint {
+        value * self.value_generator + blinding * self.blinding_generator
+    }
+}
+
+/// Schnorr proof of knowledge of a scalar `r` such that `P = r*H` where `H` is the Pedersen
+/// blinding generator.
+///
+/// Proving knowledge of the blinding factor of a point is how a prover shows a commitment opens
+/// to zero without revealing the blinding factor. By subtracting a public value from a commitment
+/// first (i.e. `P = C - v*G`), the same proof shows that `C` commits to `v`.
+#[derive(Clone, Copy, Debug)]
+pub struct BlindingProof {
+    response: Scalar,
+    public_scalar: RistrettoPoint,
+}
+
+impl BlindingProof {
+    /// Prove knowledge of `blinding` such that `point = blinding * H`. Unlike the
+    /// [`SimpleSchnorrProof`](crate::SimpleSchnorrProof), the point being proven is absorbed into
+    /// the transcript before the challenge is derived.
+    pub fn generate_proof<O: ChallengeOracle>(
+        blinding: &Scalar,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut O,
+    ) -> Self {
+        let point = blinding * generators.blinding_generator;
+        proof_transcript.absorb_prover_message(&point);
+
+        let random_scalar = Scalar::random(&mut proof_transcript.prover_rng(&point));
+        let public_scalar = random_scalar * generators.blinding_generator;
+        proof_transcript.absorb_prover_message(&public_scalar);
+
+        let challenge_scalar = proof_transcript.next_challenge();
+        let response = random_scalar + blinding * challenge_scalar;
+
+        Self {
+            response,
+            public_scalar,
+        }
+    }
+
+    /// Verify the prover knows the blinding factor of `point`
+    pub fn verify_proof<O: ChallengeOracle>(
+        &self,
+        point: &RistrettoPoint,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut O,
+    ) -> Result<(), Error> {
+        validate_points(proof_transcript, &[point, &self.public_scalar])?;
+        proof_transcript.absorb_prover_message(point);
+        proof_transcript.absorb_prover_message(&self.public_scalar);
+        let challenge_scalar = proof_transcript.next_challenge();
+
+        let response_point = self.response * generators.blinding_generator;
+        let verification_point = self.public_scalar + challenge_scalar * point;
+        let verified = response_point == verification_point;
+
+        // Recompute r*H - c*P - A == 0 with a multiscalar multiplication as a debug build
+        // cross-check of the verification equation
+        debug_assert_eq!(
+            verified,
+            RistrettoPoint::vartime_multiscalar_mul(
+                [self.response, -challenge_scalar, -Scalar::ONE],
+                [generators.blinding_generator, *point, self.public_scalar],
+            )
+            .is_identity(),
+            "verification equation disagrees with its multiscalar form"
+        );
+        if verified {
+            return Ok(());
+        }
+        Err(Error::ProofMismatch(
+            hex::encode(response_point.encode()),
+            hex::encode(verification_point.encode()),
+        ))
+    }
+
+    /// Get proof pair data
+    pub fn get_proof_pair(&self) -> (Scalar, RistrettoPoint) {
+        (self.response, self.public_scalar)
+    }
+}
+
+impl ProofScheme for BlindingProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use merlin::Transcript;
+
+    #[test]
+    fn test_blinding_proof_verifies_only_for_zero_commitments() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+
+        // A commitment to zero is a multiple of the blinding generator
+        let zero_commitment = generators.commit(&Scalar::ZERO, &blinding);
+        let proof =
+            BlindingProof::generate_proof(&blinding, &generators, &mut Transcript::new(b"test"));
+        assert!(proof
+            .verify_proof(&zero_commitment, &generators, &mut Transcript::new(b"test"))
+            .is_ok());
+
+        // The same proof cannot be used for a commitment to a non-zero value
+        let one_commitment = generators.commit(&Scalar::ONE, &blinding);
+        assert!(proof
+            .verify_proof(&one_commitment, &generators, &mut Transcript::new(b"test"))
+            .is_err());
+
+        // Commitments of the generic Ristretto group are the same points
+        let commitment =
+            encodings::PedersenCommitment::<Ristretto>::commit(&Scalar::ONE, &blinding);
+        assert_eq!(commitment.get_point(), one_commitment);
+    }
+}
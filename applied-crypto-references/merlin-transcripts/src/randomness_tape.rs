@@ -0,0 +1,430 @@
+//! Opt-in recording of a prover's randomness, sealed to an auditor so the prover can later show
+//! its blinding factors and nonces were generated honestly
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use encodings::{ChallengeSource, Endianness, PointEncoding, Profile, ScalarEncoding};
+use merlin::Transcript;
+use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+
+use crate::{ChallengeOracle, Error, SimpleProofProtocol};
+
+// A zero knowledge proof says nothing about how the prover picked its randomness. A prover who
+// reuses a nonce leaks its witness, and one who derives blinding factors from the committed values
+// can later open the commitments to whoever knows the derivation. Regulated deployments may
+// therefore need to show, after the fact, which randomness went into a proof. A randomness tape
+// makes that possible without weakening the proof for anyone but the auditor:
+//
+// 1. While proving, every random scalar drawn for the proof is recorded on a tape: blinding
+// factors drawn through `RandomnessTape::scalar` and, through a `RecordedOracle`, the seed of each
+// rng the oracle hands the prover for its nonces.
+// 2. The tape is sealed to the auditor's public key `A = a*G`. The prover picks an ephemeral key
+// `e`, publishes `E = e*G` and encrypts the tape with a key stream derived from the shared point
+// `e*A`. The tape digest is published in the clear alongside the proof, which commits the prover to
+// the tape at proving time.
+// 3. Under audit, the auditor recomputes the shared point as `a*E`, decrypts the tape and checks it
+// against the digest. Replaying the prover on the witness with a `TapeReplay` in place of fresh
+// randomness must reproduce the published proof bit for bit, which shows that exactly the recorded
+// values were used.
+//
+// Recording is opt-in: only proofs whose prover draws through a tape are recorded, and everyone
+// except the auditor learns nothing beyond the digest. The tape reveals every blinding factor, so
+// the audit key must be guarded like the witnesses themselves.
+
+// Domain separator for hashing a tape into its digest
+const RANDOMNESS_TAPE_DOMAIN_SEP: &[u8] = b"RANDOMNESS_TAPE";
+
+// Domain separator for deriving the key stream a tape is sealed with
+const SEALED_TAPE_DOMAIN_SEP: &[u8] = b"SEALED_RANDOMNESS_TAPE";
+
+// Tag of an encoded scalar entry
+const SCALAR_TAG: u8 = 0x01;
+
+// Tag of an encoded prover rng seed entry
+const PROVER_RNG_TAG: u8 = 0x02;
+
+/// Random value recorded on a randomness tape
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TapeEntry {
+    /// Scalar drawn directly, e.g. a blinding factor (label, scalar)
+    Scalar(Vec<u8>, Scalar),
+    /// Seed of a prover rng an oracle handed out for a statement (statement, seed)
+    ProverRng(RistrettoPoint, [u8; 32]),
+}
+
+/// Record of every random value drawn while generating a proof
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RandomnessTape {
+    entries: Vec<TapeEntry>,
+}
+
+impl RandomnessTape {
+    /// Create an empty tape
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw a random scalar from `rng` and record it under `label`
+    pub fn scalar<R: RngCore + CryptoRng>(&mut self, label: &[u8], rng: &mut R) -> Scalar {
+        let scalar = Scalar::random(rng);
+        self.entries.push(TapeEntry::Scalar(label.to_vec(), scalar));
+        scalar
+    }
+
+    /// Get the recorded values in the order they were drawn
+    pub fn get_entries(&self) -> &[TapeEntry] {
+        &self.entries
+    }
+
+    /// Hash the tape into the digest published alongside the proof
+    pub fn digest(&self) -> [u8; 32] {
+        let mut transcript = Transcript::new(RANDOMNESS_TAPE_DOMAIN_SEP);
+        transcript.append_message(b"TAPE", &self.to_bytes());
+        let mut digest = [0; 32];
+        transcript.challenge_bytes(RANDOMNESS_TAPE_DOMAIN_SEP, &mut digest);
+        digest
+    }
+
+    /// Encrypt the tape to the auditor's public key
+    pub fn seal<R: RngCore + CryptoRng>(
+        &self,
+        audit_key: &RistrettoPoint,
+        rng: &mut R,
+    ) -> SealedTape {
+        let ephemeral_key = Scalar::random(rng);
+        let mut sealed = SealedTape {
+            ephemeral_point: ephemeral_key * RISTRETTO_BASEPOINT_POINT,
+            digest: self.digest(),
+            ciphertext: self.to_bytes(),
+        };
+        sealed.apply_key(audit_key, &(ephemeral_key * audit_key));
+        sealed
+    }
+
+    /// Start replaying the tape in place of fresh randomness
+    pub fn replay(&self) -> TapeReplay {
+        TapeReplay {
+            entries: self.entries.clone(),
+            position: 0,
+            mismatch: None,
+        }
+    }
+
+    // Encode the entries, each as a tag byte followed by its contents
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for entry in self.entries.iter() {
+            match entry {
+                TapeEntry::Scalar(label, scalar) => {
+                    bytes.push(SCALAR_TAG);
+                    bytes.extend((label.len() as u32).to_le_bytes());
+                    bytes.extend(label);
+                    bytes.extend(scalar.encode(Endianness::Little));
+                }
+                TapeEntry::ProverRng(statement, seed) => {
+                    bytes.push(PROVER_RNG_TAG);
+                    bytes.extend(statement.encode());
+                    bytes.extend(seed);
+                }
+            }
+        }
+        bytes
+    }
+
+    // Decode entries encoded by `to_bytes`
+    fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+        let mut take = |len: usize| {
+            if bytes.len() < len {
+                return Err(Error::MalformedTape);
+            }
+            let (taken, rest) = bytes.split_at(len);
+            bytes = rest;
+            Ok(taken)
+        };
+        let mut entries = Vec::new();
+        while let Ok(tag) = take(1) {
+            let entry = match tag[0] {
+                SCALAR_TAG => {
+                    let label_len = u32::from_le_bytes(take(4)?.try_into().expect("took 4 bytes"));
+                    let label = take(label_len as usize)?.to_vec();
+                    let scalar = Scalar::decode(take(32)?, Endianness::Little)
+                        .map_err(|_| Error::MalformedTape)?;
+                    TapeEntry::Scalar(label, scalar)
+                }
+                PROVER_RNG_TAG => {
+                    let statement =
+                        RistrettoPoint::decode(take(32)?).map_err(|_| Error::MalformedTape)?;
+                    TapeEntry::ProverRng(statement, take(32)?.try_into().expect("took 32 bytes"))
+                }
+                _ => return Err(Error::MalformedTape),
+            };
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Randomness tape encrypted to an auditor, together with the digest committing to it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SealedTape {
+    // Ephemeral public key `E = e*G` of the sealer
+    ephemeral_point: RistrettoPoint,
+    // Digest of the plaintext tape
+    digest: [u8; 32],
+    // Encoded tape XORed with the key stream derived from `e*A`
+    ciphertext: Vec<u8>,
+}
+
+impl SealedTape {
+    /// Get the digest of the sealed tape, which is published alongside the proof
+    pub fn get_digest(&self) -> [u8; 32] {
+        self.digest
+    }
+
+    /// Decrypt the tape with the auditor's private key, checking it matches the digest
+    pub fn open(&self, audit_secret: &Scalar) -> Result<RandomnessTape, Error> {
+        let mut opened = self.clone();
+        opened.apply_key(
+            &(audit_secret * RISTRETTO_BASEPOINT_POINT),
+            &(audit_secret * self.ephemeral_point),
+        );
+        let tape = RandomnessTape::from_bytes(&opened.ciphertext)?;
+        if tape.digest() != self.digest {
+            return Err(Error::TapeMismatch);
+        }
+        Ok(tape)
+    }
+
+    // XOR the ciphertext with a key stream derived from the shared point and the sealed tape's
+    // public values, which encrypts and decrypts alike
+    fn apply_key(&mut self, audit_key: &RistrettoPoint, shared_point: &RistrettoPoint) {
+        let mut transcript = Transcript::new(SEALED_TAPE_DOMAIN_SEP);
+        transcript.append_proof_value(audit_key);
+        transcript.append_proof_value(&self.ephemeral_point);
+        transcript.append_message(b"DIGEST", &self.digest);
+        transcript.append_proof_value(shared_point);
+        let mut key = vec![0; self.ciphertext.len()];
+        transcript.challenge_bytes(b"KEY", &mut key);
+        for (byte, key_byte) in self.ciphertext.iter_mut().zip(key) {
+            *byte ^= key_byte;
+        }
+    }
+}
+
+/// Oracle recording the seed of every prover rng it hands out on a randomness tape
+pub struct RecordedOracle<'a, O> {
+    oracle: O,
+    tape: &'a mut RandomnessTape,
+}
+
+impl<'a, O: ChallengeOracle> RecordedOracle<'a, O> {
+    /// Wrap an oracle, recording its prover randomness on `tape`
+    pub fn new(oracle: O, tape: &'a mut RandomnessTape) -> Self {
+        Self { oracle, tape }
+    }
+
+    /// Unwrap the oracle
+    pub fn into_inner(self) -> O {
+        self.oracle
+    }
+}
+
+impl<O: ChallengeOracle> ChallengeOracle for RecordedOracle<'_, O> {
+    type Rng = StdRng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.oracle.absorb_prover_message(curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        self.oracle.next_challenge()
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        // Draw a seed from the wrapped oracle's rng so recording doesn't change where the
+        // randomness comes from, only that it can be reproduced from the tape
+        let mut seed = [0; 32];
+        self.oracle.prover_rng(statement).fill_bytes(&mut seed);
+        self.tape
+            .entries
+            .push(TapeEntry::ProverRng(*statement, seed));
+        StdRng::from_seed(seed)
+    }
+
+    fn profile(&self) -> Profile {
+        self.oracle.profile()
+    }
+
+    fn challenge_bits(&self) -> usize {
+        self.oracle.challenge_bits()
+    }
+
+    fn challenge_source(&self) -> ChallengeSource {
+        self.oracle.challenge_source()
+    }
+}
+
+/// Replay of an opened randomness tape, handing out the recorded values in order
+#[derive(Clone, Debug)]
+pub struct TapeReplay {
+    entries: Vec<TapeEntry>,
+    position: usize,
+    // Position of the first request which didn't match the tape
+    mismatch: Option<usize>,
+}
+
+impl TapeReplay {
+    /// Get the next recorded scalar, which must have been recorded under `label`
+    pub fn scalar(&mut self, label: &[u8]) -> Result<Scalar, Error> {
+        match self.entries.get(self.position) {
+            Some(TapeEntry::Scalar(recorded, scalar)) if recorded == label => {
+                self.position += 1;
+                Ok(*scalar)
+            }
+            _ => {
+                self.mismatch.get_or_insert(self.position);
+                Err(Error::ReplayMismatch(self.position))
+            }
+        }
+    }
+
+    /// Check the replay used up the tape without diverging from it
+    pub fn finish(&self) -> Result<(), Error> {
+        match self.mismatch {
+            Some(position) => Err(Error::ReplayMismatch(position)),
+            None if self.position != self.entries.len() => {
+                Err(Error::ReplayMismatch(self.position))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Oracle handing the prover the rngs recorded on a randomness tape instead of fresh ones
+pub struct ReplayedOracle<'a, O> {
+    oracle: O,
+    replay: &'a mut TapeReplay,
+}
+
+impl<'a, O: ChallengeOracle> ReplayedOracle<'a, O> {
+    /// Wrap an oracle, taking prover randomness from `replay`
+    pub fn new(oracle: O, replay: &'a mut TapeReplay) -> Self {
+        Self { oracle, replay }
+    }
+}
+
+impl<O: ChallengeOracle> ChallengeOracle for ReplayedOracle<'_, O> {
+    type Rng = StdRng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.oracle.absorb_prover_message(curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        self.oracle.next_challenge()
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        let replay = &mut *self.replay;
+        match replay.entries.get(replay.position) {
+            Some(TapeEntry::ProverRng(recorded, seed)) if recorded == statement => {
+                replay.position += 1;
+                StdRng::from_seed(*seed)
+            }
+            // The trait can't fail here, so the divergence is reported by `TapeReplay::finish`
+            // and the prover continues with randomness that won't reproduce the proof
+            _ => {
+                replay.mismatch.get_or_insert(replay.position);
+                StdRng::from_seed([0; 32])
+            }
+        }
+    }
+
+    fn profile(&self) -> Profile {
+        self.oracle.profile()
+    }
+
+    fn challenge_bits(&self) -> usize {
+        self.oracle.challenge_bits()
+    }
+
+    fn challenge_source(&self) -> ChallengeSource {
+        self.oracle.challenge_source()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlindingProof, PedersenGenerators};
+
+    // Commit to a value and prove knowledge of the commitment's blinding, drawing all randomness
+    // through the given sources
+    fn prove(
+        blinding: Scalar,
+        oracle: &mut impl ChallengeOracle,
+    ) -> (RistrettoPoint, (Scalar, RistrettoPoint)) {
+        let generators = PedersenGenerators::default();
+        let commitment = generators.commit(&Scalar::from(42u64), &blinding);
+        let proof = BlindingProof::generate_proof(&blinding, &generators, oracle);
+        (commitment, proof.get_proof_pair())
+    }
+
+    #[test]
+    fn test_audited_replay_reproduces_the_proof() {
+        let mut rng = rand::rngs::OsRng;
+        let audit_secret = Scalar::random(&mut rng);
+        let audit_key = audit_secret * RISTRETTO_BASEPOINT_POINT;
+
+        // The prover records its randomness and publishes the proof and the sealed tape
+        let mut tape = RandomnessTape::new();
+        let blinding = tape.scalar(b"BLINDING", &mut rng);
+        let published = prove(
+            blinding,
+            &mut RecordedOracle::new(Transcript::new(b"TEST"), &mut tape),
+        );
+        assert_eq!(tape.get_entries().len(), 2);
+        let sealed = tape.seal(&audit_key, &mut rng);
+        assert_eq!(sealed.get_digest(), tape.digest());
+
+        // The auditor opens the tape and replays the prover, which reproduces the proof exactly
+        let opened = sealed.open(&audit_secret).unwrap();
+        assert_eq!(opened, tape);
+        let mut replay = opened.replay();
+        let blinding = replay.scalar(b"BLINDING").unwrap();
+        let replayed = prove(
+            blinding,
+            &mut ReplayedOracle::new(Transcript::new(b"TEST"), &mut replay),
+        );
+        assert_eq!(replayed, published);
+        assert!(replay.finish().is_ok());
+    }
+
+    #[test]
+    fn test_wrong_keys_tampering_and_diverging_replays_are_detected() {
+        let mut rng = rand::rngs::OsRng;
+        let audit_secret = Scalar::random(&mut rng);
+        let mut tape = RandomnessTape::new();
+        let blinding = tape.scalar(b"BLINDING", &mut rng);
+        prove(
+            blinding,
+            &mut RecordedOracle::new(Transcript::new(b"TEST"), &mut tape),
+        );
+        let sealed = tape.seal(&(audit_secret * RISTRETTO_BASEPOINT_POINT), &mut rng);
+
+        // Only the auditor's key opens the tape, and the ciphertext can't be altered
+        assert!(sealed.open(&Scalar::random(&mut rng)).is_err());
+        let mut tampered = sealed.clone();
+        tampered.ciphertext[40] ^= 1;
+        assert!(tampered.open(&audit_secret).is_err());
+
+        // Replays must draw the recorded values in the recorded order and use all of them
+        let mut replay = tape.replay();
+        assert_eq!(replay.scalar(b"NONCE"), Err(Error::ReplayMismatch(0)));
+        assert_eq!(replay.finish(), Err(Error::ReplayMismatch(0)));
+        let mut replay = tape.replay();
+        replay.scalar(b"BLINDING").unwrap();
+        assert_eq!(replay.finish(), Err(Error::ReplayMismatch(1)));
+    }
+}
@@ -0,0 +1,360 @@
+//! Range proofs for Pedersen commitments built from bit decomposition and OR proofs
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{ProofScheme, SecurityLevel};
+use merlin::Transcript;
+use tracing::instrument;
+
+use crate::{BitDecomposition, Error, PedersenGenerators};
+
+// A range proof shows that a Pedersen commitment `C = v*G + r*H` commits to a value `v` in the
+// range [0, 2^n) without revealing `v`. This simple version works as follows:
+//
+// 1. The Prover writes `v` in binary as `v = b_0*2^0 + b_1*2^1 + .. + b_(n-1)*2^(n-1)` and commits
+// to each bit separately as `C_i = b_i*G + r_i*H`. The bit blinding factors are chosen such that
+// `r = r_0*2^0 + r_1*2^1 + .. + r_(n-1)*2^(n-1)`.
+// 2. The Verifier checks that `C = C_0*2^0 + C_1*2^1 + .. + C_(n-1)*2^(n-1)`, which by the
+// homomorphic property of Pedersen commitments shows the bit commitments recompose to `C`.
+// 3. For every bit commitment, the Prover shows that either `C_i = r_i*H` (the bit is 0) OR
+// `C_i - G = r_i*H` (the bit is 1) without revealing which statement is true.
+//
+// The OR proof in step 3 is the Cramer-Damgard-Schoenmakers construction. The Prover runs an
+// honest Schnorr proof for the true statement and simulates a transcript for the false one by
+// picking its challenge and response first. The Verifier only checks that the two challenges sum
+// to the Fiat-Shamir challenge, so the Prover can choose at most one of them freely.
+//
+// Steps 1 to 3 are exactly a [`BitDecomposition`] of the commitment, so a range proof is a bit
+// decomposition restricted to values that fit in a `u64`.
+//
+// This proof has a size linear in `n`. Bulletproofs achieve logarithmic size, but the linear
+// version makes the underlying idea much easier to follow.
+
+// WIRE FORMAT
+// Range proofs are exchanged as a version byte and the number of bits `n` as one byte, followed by
+// the `n` bit commitments with their OR proofs, least significant bit first. Each takes 160 bytes:
+// the compressed commitment `C_i`, then both challenges and both responses as 32 little endian
+// bytes each, all decoded strictly.
+
+// Maximum number of bits a range proof can cover
+const MAX_BITS: usize = 64;
+
+// Current version of the range proof wire format
+const RANGE_PROOF_FORMAT_VERSION: u8 = 1;
+
+/// Proof that a Pedersen commitment commits to a value within the range [0, 2^n)
+#[derive(Clone, Debug)]
+pub struct RangeProof {
+    // Decomposition of the commitment into bit commitments with proofs each is 0 or 1
+    decomposition: BitDecomposition,
+}
+
+impl RangeProof {
+    /// Prove that the commitment `value*G + blinding*H` commits to a value within [0, 2^bits)
+    #[instrument(name = "prove", level = "debug", skip_all, fields(bits))]
+    pub fn generate_proof(
+        value: u64,
+        blinding: &Scalar,
+        bits: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<Self, Error> {
+        if bits == 0 || bits > MAX_BITS {
+            return Err(Error::InvalidBitLength(bits));
+        }
+        if bits < MAX_BITS && value >> bits != 0 {
+            return Err(Error::ValueOutOfRange(value, bits));
+        }
+        let decomposition = BitDecomposition::generate_proof(
+            &Scalar::from(value),
+            blinding,
+            bits,
+            generators,
+            proof_transcript,
+        )?;
+        Ok(Self { decomposition })
+    }
+
+    /// Verify that `commitment` commits to a value within [0, 2^bits)
+    #[instrument(name = "verify", level = "debug", skip_all, fields(bits))]
+    pub fn verify_proof(
+        &self,
+        commitment: &RistrettoPoint,
+        bits: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        if bits == 0 || bits > MAX_BITS {
+            return Err(Error::InvalidBitLength(bits));
+        }
+        self.decomposition
+            .verify_proof(commitment, bits, generators, proof_transcript)
+            .map_err(|_| Error::RangeProofMismatch)
+    }
+
+    /// Get the commitments to each bit of the value, least significant bit first
+    pub fn get_bit_commitments(&self) -> &[RistrettoPoint] {
+        self.decomposition.get_bit_commitments()
+    }
+
+    /// Encode the proof into its versioned wire format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bits = self.decomposition.get_bit_commitments().len();
+        let mut bytes = vec![RANGE_PROOF_FORMAT_VERSION, bits as u8];
+        bytes.extend(self.decomposition.to_bytes());
+        bytes
+    }
+
+    /// Decode a proof from its versioned wire format, rejecting malformed encodings
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < 2 {
+            return Err(Error::InvalidProofEncoding(
+                encodings::Error::InvalidLength(2, bytes.len()),
+            ));
+        }
+        if bytes[0] != RANGE_PROOF_FORMAT_VERSION {
+            return Err(Error::UnsupportedProofVersion(bytes[0]));
+        }
+        let bits = bytes[1] as usize;
+        if bits == 0 || bits > MAX_BITS {
+            return Err(Error::InvalidBitLength(bits));
+        }
+        let decomposition = BitDecomposition::from_bytes(&bytes[2..], bits)?;
+        Ok(Self { decomposition })
+    }
+}
+
+impl ProofScheme for RangeProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+// FIXED BIT WIDTHS
+// The bit length of a `RangeProof` is a runtime argument, so a bit length of 0, one above 64 or
+// one the prover and verifier disagree on only shows up as an error once the proof is generated
+// or verified. Most protocols range check values of a fixed width, e.g. an 8 bit age or a 32 bit
+// balance, and can fix the width in the type instead:
+//
+// 1. `FixedRangeProof<N>` covers [0, 2^N) and checks N is one of the widths of
+// `SUPPORTED_RANGE_BITS`, the powers of two 8 to 64 that aggregated range proofs such as
+// Bulletproofs support. The check is a constant evaluated when the type is used, so an
+// unsupported width such as `FixedRangeProof<12>` fails to compile.
+// 2. Proving and verifying forward to `RangeProof` with N bits, and a fixed proof converts to and
+// from a runtime one, checking its bit length.
+// 3. A proof for one width can't be verified as another, since the width is part of its type.
+
+/// Bit widths a [`FixedRangeProof`] can cover
+pub const SUPPORTED_RANGE_BITS: [usize; 4] = [8, 16, 32, 64];
+
+/// Proof that a Pedersen commitment commits to a value within the range [0, 2^N), for a bit width
+/// N fixed at compile time to one of [`SUPPORTED_RANGE_BITS`]
+///
+/// Other widths are rejected when the proof is built rather than when it runs:
+///
+/// ```compile_fail
+/// use curve25519_dalek::scalar::Scalar;
+/// use merlin::Transcript;
+/// use merlin_example::{FixedRangeProof, PedersenGenerators};
+///
+/// let proof = FixedRangeProof::<12>::generate_proof(
+///     5,
+///     &Scalar::ONE,
+///     &PedersenGenerators::default(),
+///     &mut Transcript::new(b"range"),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct FixedRangeProof<const N: usize> {
+    proof: RangeProof,
+}
+
+impl<const N: usize> FixedRangeProof<N> {
+    // Evaluated wherever the proof is generated or decoded, failing the build for unsupported
+    // bit widths
+    const SUPPORTED: () = assert!(
+        N.is_power_of_two() && N >= SUPPORTED_RANGE_BITS[0] && N <= MAX_BITS,
+        "range proofs support bit widths of 8, 16, 32 or 64"
+    );
+
+    /// Prove that the commitment `value*G + blinding*H` commits to a value within [0, 2^N)
+    pub fn generate_proof(
+        value: u64,
+        blinding: &Scalar,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<Self, Error> {
+        let () = Self::SUPPORTED;
+        RangeProof::generate_proof(value, blinding, N, generators, proof_transcript)
+            .map(|proof| Self { proof })
+    }
+
+    /// Verify that `commitment` commits to a value within [0, 2^N)
+    pub fn verify_proof(
+        &self,
+        commitment: &RistrettoPoint,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        self.proof
+            .verify_proof(commitment, N, generators, proof_transcript)
+    }
+
+    /// Get the proof with a runtime bit length
+    pub fn get_proof(&self) -> &RangeProof {
+        &self.proof
+    }
+
+    /// Encode the proof into the wire format of [`RangeProof`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.proof.to_bytes()
+    }
+
+    /// Decode a proof from the wire format of [`RangeProof`], rejecting proofs of other widths
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::try_from(RangeProof::from_bytes(bytes)?)
+    }
+}
+
+/// Fix the bit width of a range proof, rejecting proofs covering another number of bits
+impl<const N: usize> TryFrom<RangeProof> for FixedRangeProof<N> {
+    type Error = Error;
+
+    fn try_from(proof: RangeProof) -> Result<Self, Self::Error> {
+        let () = Self::SUPPORTED;
+        match proof.get_bit_commitments().len() {
+            bits if bits == N => Ok(Self { proof }),
+            bits => Err(Error::InvalidBitLength(bits)),
+        }
+    }
+}
+
+/// Forget the fixed bit width of a range proof
+impl<const N: usize> From<FixedRangeProof<N>> for RangeProof {
+    fn from(proof: FixedRangeProof<N>) -> Self {
+        proof.proof
+    }
+}
+
+impl<const N: usize> ProofScheme for FixedRangeProof<N> {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_proof_verifies_for_values_in_range() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let commitment = generators.commit(&Scalar::from(200u64), &blinding);
+
+        let proof = RangeProof::generate_proof(
+            200,
+            &blinding,
+            8,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        assert!(proof
+            .verify_proof(&commitment, 8, &generators, &mut Transcript::new(b"test"))
+            .is_ok());
+
+        // The proof survives a round trip through its wire format, which rejects truncation
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), 2 + 8 * 160);
+        assert!(RangeProof::from_bytes(&bytes)
+            .unwrap()
+            .verify_proof(&commitment, 8, &generators, &mut Transcript::new(b"test"))
+            .is_ok());
+        assert!(matches!(
+            RangeProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(Error::InvalidProofEncoding(_))
+        ));
+
+        // The proof doesn't verify against a different commitment or range
+        let other_commitment = generators.commit(&Scalar::from(201u64), &blinding);
+        assert_eq!(
+            proof.verify_proof(
+                &other_commitment,
+                8,
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::RangeProofMismatch)
+        );
+        assert_eq!(
+            proof.verify_proof(&commitment, 9, &generators, &mut Transcript::new(b"test")),
+            Err(Error::RangeProofMismatch)
+        );
+    }
+
+    #[test]
+    fn test_range_proof_rejects_values_out_of_range() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        assert_eq!(
+            RangeProof::generate_proof(
+                256,
+                &blinding,
+                8,
+                &generators,
+                &mut Transcript::new(b"test")
+            )
+            .err(),
+            Some(Error::ValueOutOfRange(256, 8))
+        );
+        assert_eq!(
+            RangeProof::generate_proof(
+                1,
+                &blinding,
+                65,
+                &generators,
+                &mut Transcript::new(b"test")
+            )
+            .err(),
+            Some(Error::InvalidBitLength(65))
+        );
+    }
+
+    #[test]
+    fn test_fixed_width_proofs_convert_to_and_from_runtime_proofs() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let commitment = generators.commit(&Scalar::from(200u64), &blinding);
+        let proof = FixedRangeProof::<8>::generate_proof(
+            200,
+            &blinding,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        assert!(proof
+            .verify_proof(&commitment, &generators, &mut Transcript::new(b"test"))
+            .is_ok());
+
+        // It is a runtime proof over 8 bits with the same wire format
+        let bytes = proof.to_bytes();
+        let runtime = RangeProof::from(proof);
+        assert!(runtime
+            .verify_proof(&commitment, 8, &generators, &mut Transcript::new(b"test"))
+            .is_ok());
+        assert_eq!(runtime.to_bytes(), bytes);
+        assert!(FixedRangeProof::<8>::try_from(runtime).is_ok());
+
+        // Proofs of another width are rejected, and values outside the range can't be proven
+        assert_eq!(
+            FixedRangeProof::<16>::from_bytes(&bytes).err(),
+            Some(Error::InvalidBitLength(8))
+        );
+        assert_eq!(
+            FixedRangeProof::<8>::generate_proof(
+                256,
+                &blinding,
+                &generators,
+                &mut Transcript::new(b"test")
+            )
+            .err(),
+            Some(Error::ValueOutOfRange(256, 8))
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Recomputation certificates letting an auditor check the challenges a verifier derived
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{Endianness, PointEncoding, ScalarEncoding};
+use merlin::Transcript;
+
+use crate::{
+    ChallengeOracle, Error, SchnorrProof, SimpleSchnorrProof, TracedOracle, TranscriptEvent,
+};
+
+// A prover and a verifier disagreeing about a verification, e.g. a prover claiming its proof was
+// rejected unfairly, can ask a third party to settle it. Most such disputes come down to the
+// Fiat-Shamir challenges: a verifier that absorbed the statement differently, or verified against
+// another statement than the one published, derives other challenges than the prover did. The
+// auditor settles this without trusting either side and without checking the proof itself:
+//
+// 1. The prover publishes the proof envelope, i.e. the encoded proof in its versioned wire format,
+// and the public statement it proves.
+// 2. The verifier verifies the envelope and issues a receipt holding its verdict and a certificate
+// of what it verified: digests of the statement and envelope, and of every challenge it derived.
+// 3. The auditor decodes the envelope and only replays the transcript of the proof, recomputing the
+// challenges from the envelope and statement. The certificate of the recomputation is compared to
+// the one in the verifier's receipt.
+//
+// Matching certificates show the verifier checked this envelope against this statement with the
+// right challenges, so the verdict only depends on the verification equation which anyone can
+// check. A certificate for other bytes means the verifier verified something else, and matching
+// bytes with other challenges mean its transcript diverged from the protocol's.
+//
+// Certificates are 66 bytes: a version byte, the scheme byte of the envelope, the 32 byte digest
+// of the statement and envelope and the 32 byte digest of the challenges. Receipts append a byte
+// for the verdict.
+
+// Domain separator for digesting the statement and envelope of a certificate
+const ENVELOPE_DIGEST_DOMAIN_SEP: &[u8] = b"RECOMPUTATION_ENVELOPE";
+
+// Domain separator for digesting the challenges of a certificate
+const CHALLENGE_DIGEST_DOMAIN_SEP: &[u8] = b"RECOMPUTATION_CHALLENGES";
+
+// Current version of the certificate format
+const CERTIFICATE_FORMAT_VERSION: u8 = 1;
+
+/// Length of an encoded recomputation certificate
+pub const CERTIFICATE_LEN: usize = 66;
+
+/// Proof whose Fiat-Shamir challenges can be recomputed from its envelope and public statement
+pub trait RecomputableProof: Sized {
+    /// Decode the proof from its envelope
+    fn from_envelope(envelope: &[u8]) -> Result<Self, Error>;
+
+    /// Get a fresh transcript of the proof's protocol
+    fn create_transcript() -> Transcript;
+
+    /// Replay the transcript of the proof, returning the challenges it answers
+    fn recompute_challenges<O: ChallengeOracle>(
+        &self,
+        statement: &RistrettoPoint,
+        oracle: &mut O,
+    ) -> Vec<Scalar>;
+
+    /// Verify the proof of the statement
+    fn verify_statement<O: ChallengeOracle>(
+        &self,
+        statement: &RistrettoPoint,
+        oracle: &mut O,
+    ) -> Result<(), Error>;
+}
+
+impl RecomputableProof for SimpleSchnorrProof {
+    fn from_envelope(envelope: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(envelope)
+    }
+
+    fn create_transcript() -> Transcript {
+        Self::create_new_transcript()
+    }
+
+    fn recompute_challenges<O: ChallengeOracle>(
+        &self,
+        _statement: &RistrettoPoint,
+        oracle: &mut O,
+    ) -> Vec<Scalar> {
+        vec![self.derive_challenge(oracle)]
+    }
+
+    fn verify_statement<O: ChallengeOracle>(
+        &self,
+        statement: &RistrettoPoint,
+        oracle: &mut O,
+    ) -> Result<(), Error> {
+        let mut proof = *self;
+        proof.verify_proof(statement, oracle).map(|_| ())
+    }
+}
+
+impl RecomputableProof for SchnorrProof {
+    fn from_envelope(envelope: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(envelope)
+    }
+
+    fn create_transcript() -> Transcript {
+        Self::create_new_transcript()
+    }
+
+    fn recompute_challenges<O: ChallengeOracle>(
+        &self,
+        statement: &RistrettoPoint,
+        oracle: &mut O,
+    ) -> Vec<Scalar> {
+        vec![self.derive_challenge(statement, oracle)]
+    }
+
+    fn verify_statement<O: ChallengeOracle>(
+        &self,
+        statement: &RistrettoPoint,
+        oracle: &mut O,
+    ) -> Result<(), Error> {
+        self.verify_proof(statement, oracle)
+    }
+}
+
+/// Compact record of the statement, envelope and challenges of a verification
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RecomputationCertificate {
+    scheme: u8,
+    envelope_digest: [u8; 32],
+    challenge_digest: [u8; 32],
+}
+
+impl RecomputationCertificate {
+    // Certify the challenges derived for an envelope and statement
+    fn new(envelope: &[u8], statement: &RistrettoPoint, challenges: &[Scalar]) -> Self {
+        let mut transcript = Transcript::new(ENVELOPE_DIGEST_DOMAIN_SEP);
+        transcript.append_message(b"STATEMENT", &statement.encode());
+        transcript.append_message(b"ENVELOPE", envelope);
+        let mut envelope_digest = [0; 32];
+        transcript.challenge_bytes(b"DIGEST", &mut envelope_digest);
+
+        let mut transcript = Transcript::new(CHALLENGE_DIGEST_DOMAIN_SEP);
+        transcript.append_u64(b"COUNT", challenges.len() as u64);
+        for challenge in challenges {
+            transcript.append_message(b"CHALLENGE", &challenge.encode(Endianness::Little));
+        }
+        let mut challenge_digest = [0; 32];
+        transcript.challenge_bytes(b"DIGEST", &mut challenge_digest);
+
+        Self {
+            scheme: envelope[1],
+            envelope_digest,
+            challenge_digest,
+        }
+    }
+
+    /// Recompute the challenges of a proof envelope and its public statement as an auditor,
+    /// without checking the proof
+    pub fn recompute<P: RecomputableProof>(
+        envelope: &[u8],
+        statement: &RistrettoPoint,
+    ) -> Result<Self, Error> {
+        let proof = P::from_envelope(envelope)?;
+        let challenges = proof.recompute_challenges(statement, &mut P::create_transcript());
+        Ok(Self::new(envelope, statement, &challenges))
+    }
+
+    /// Check the receipt of a verifier was issued for the same envelope and statement with the
+    /// recomputed challenges
+    pub fn check_receipt(&self, receipt: &VerificationReceipt) -> Result<(), Error> {
+        let certificate = receipt.get_certificate();
+        if (self.scheme, self.envelope_digest) != (certificate.scheme, certificate.envelope_digest)
+        {
+            return Err(Error::ReceiptEnvelopeMismatch);
+        }
+        if self.challenge_digest != certificate.challenge_digest {
+            return Err(Error::ReceiptChallengeMismatch);
+        }
+        Ok(())
+    }
+
+    /// Get the scheme byte of the certified envelope
+    pub fn get_scheme(&self) -> u8 {
+        self.scheme
+    }
+
+    /// Encode the certificate into its versioned format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![CERTIFICATE_FORMAT_VERSION, self.scheme];
+        bytes.extend(self.envelope_digest);
+        bytes.extend(self.challenge_digest);
+        bytes
+    }
+
+    /// Decode a certificate from its versioned format
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != CERTIFICATE_LEN || bytes[0] != CERTIFICATE_FORMAT_VERSION {
+            return Err(Error::MalformedCertificate);
+        }
+        let mut envelope_digest = [0; 32];
+        envelope_digest.copy_from_slice(&bytes[2..34]);
+        let mut challenge_digest = [0; 32];
+        challenge_digest.copy_from_slice(&bytes[34..]);
+        Ok(Self {
+            scheme: bytes[1],
+            envelope_digest,
+            challenge_digest,
+        })
+    }
+}
+
+/// Verdict of a verifier on a proof envelope along with the certificate of what it verified
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VerificationReceipt {
+    certificate: RecomputationCertificate,
+    accepted: bool,
+}
+
+impl VerificationReceipt {
+    /// Verify a proof envelope for its public statement and issue a receipt of the verdict,
+    /// failing only for envelopes that can't be decoded
+    pub fn verify<P: RecomputableProof>(
+        envelope: &[u8],
+        statement: &RistrettoPoint,
+    ) -> Result<Self, Error> {
+        let proof = P::from_envelope(envelope)?;
+        let mut oracle = TracedOracle::new(P::create_transcript());
+        let accepted = proof.verify_statement(statement, &mut oracle).is_ok();
+        let challenges: Vec<Scalar> = oracle
+            .get_events()
+            .iter()
+            .filter_map(|event| match event {
+                TranscriptEvent::Challenge(challenge) => Some(*challenge),
+                TranscriptEvent::ProverMessage(_) => None,
+            })
+            .collect();
+        Ok(Self {
+            certificate: RecomputationCertificate::new(envelope, statement, &challenges),
+            accepted,
+        })
+    }
+
+    /// Get the certificate of the envelope, statement and challenges verified
+    pub fn get_certificate(&self) -> &RecomputationCertificate {
+        &self.certificate
+    }
+
+    /// Check if the verifier accepted the proof
+    pub fn is_accepted(&self) -> bool {
+        self.accepted
+    }
+
+    /// Encode the receipt as its certificate followed by a verdict byte
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.certificate.to_bytes();
+        bytes.push(self.accepted as u8);
+        bytes
+    }
+
+    /// Decode a receipt from its certificate and verdict byte
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (verdict, certificate) = bytes.split_last().ok_or(Error::MalformedCertificate)?;
+        let accepted = match verdict {
+            0 => false,
+            1 => true,
+            _ => return Err(Error::MalformedCertificate),
+        };
+        Ok(Self {
+            certificate: RecomputationCertificate::from_bytes(certificate)?,
+            accepted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_keypair;
+
+    #[test]
+    fn test_auditor_certificate_matches_the_verifier_receipt() {
+        let (private_key, public_key) = generate_keypair();
+        let proof =
+            SchnorrProof::generate_proof(&private_key, &mut SchnorrProof::create_new_transcript());
+        let envelope = proof.to_bytes();
+
+        // The verifier's receipt survives encoding and matches the auditor's recomputation
+        let receipt = VerificationReceipt::verify::<SchnorrProof>(&envelope, &public_key).unwrap();
+        assert!(receipt.is_accepted());
+        let receipt = VerificationReceipt::from_bytes(&receipt.to_bytes()).unwrap();
+        let certificate =
+            RecomputationCertificate::recompute::<SchnorrProof>(&envelope, &public_key).unwrap();
+        assert_eq!(certificate.to_bytes().len(), CERTIFICATE_LEN);
+        assert!(certificate.check_receipt(&receipt).is_ok());
+
+        // A rejection is certified the same way, so the dispute is about the verdict alone
+        let (_, other_key) = generate_keypair();
+        let rejected = VerificationReceipt::verify::<SchnorrProof>(&envelope, &other_key).unwrap();
+        assert!(!rejected.is_accepted());
+        let other_certificate =
+            RecomputationCertificate::recompute::<SchnorrProof>(&envelope, &other_key).unwrap();
+        assert!(other_certificate.check_receipt(&rejected).is_ok());
+
+        // A receipt for another statement doesn't match
+        assert_eq!(
+            certificate.check_receipt(&rejected),
+            Err(Error::ReceiptEnvelopeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_diverging_verifier_challenges_are_caught() {
+        let (private_key, public_key) = generate_keypair();
+        let proof = SimpleSchnorrProof::generate_proof(
+            &private_key,
+            &mut SimpleSchnorrProof::create_new_transcript(),
+        );
+        let envelope = proof.to_bytes();
+        let certificate =
+            RecomputationCertificate::recompute::<SimpleSchnorrProof>(&envelope, &public_key)
+                .unwrap();
+
+        // A verifier deriving its challenge from a differently separated transcript issues a
+        // receipt for the right envelope with the wrong challenges
+        let mut transcript = Transcript::new(b"WRONG_DOMAIN");
+        let challenge = proof.derive_challenge(&mut transcript);
+        let receipt = VerificationReceipt {
+            certificate: RecomputationCertificate::new(&envelope, &public_key, &[challenge]),
+            accepted: false,
+        };
+        assert_eq!(
+            certificate.check_receipt(&receipt),
+            Err(Error::ReceiptChallengeMismatch)
+        );
+
+        // Envelopes of another scheme and malformed certificates are rejected
+        assert_eq!(
+            RecomputationCertificate::recompute::<SchnorrProof>(&envelope, &public_key),
+            Err(Error::WrongProofScheme(envelope[1]))
+        );
+        assert_eq!(
+            VerificationReceipt::from_bytes(&certificate.to_bytes()[1..]),
+            Err(Error::MalformedCertificate)
+        );
+    }
+}
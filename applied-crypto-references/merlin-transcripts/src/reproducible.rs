@@ -0,0 +1,205 @@
+//! Reproducibility mode producing bit-identical proofs from a seed regardless of thread count
+
+use std::thread;
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{PointEncoding, Profile};
+use merlin::{Transcript, TranscriptRng};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{ChallengeOracle, SimpleProofProtocol};
+
+// Provers normally draw blinding factors and proof nonces from the operating system's rng, so
+// proving the same statement twice gives different proof bytes. Some compliance users need to
+// re-run a prover and get exactly the same bytes back, given the same witness and a recorded
+// randomness seed, even when the work is spread over several threads. Two things break this under
+// parallel execution:
+//
+// * Shared rngs: if threads draw from one rng, which thread draws first decides who gets which
+// random values. Instead every unit of work (e.g. a chunk of weights) gets its own rng stream
+// derived from the seed, a label and the unit's index, so its randomness doesn't depend on the
+// scheduling or on how many units share a thread.
+// * Reduction order: results combined in the order threads finish come out in a different order on
+// every run. Instead work is split into contiguous blocks of indices and the results are always
+// reassembled in index order before anything is hashed or summed.
+//
+// Proofs generated this way verify with the ordinary Fiat-Shamir transcripts, since only the
+// prover's randomness changes and not the way challenges are derived. Anyone who learns the seed
+// learns every blinding factor, so it must be kept as secret as the witness itself.
+
+// Domain separator for initializing the seed transcript
+const REPRODUCIBLE_SEED_DOMAIN_SEP: &[u8] = b"REPRODUCIBLE_SEED";
+
+// Domain separator for deriving the seed of an rng stream
+const RNG_STREAM_DOMAIN_SEP: &[u8] = b"RNG_STREAM";
+
+// Domain separator for keying a seeded transcript rng with the statement being proven
+const SEEDED_WITNESS_DOMAIN_SEP: &[u8] = b"SEEDED_WITNESS_BYTES";
+
+/// Secret seed from which all randomness of a reproducible prover is derived
+#[derive(Clone)]
+pub struct ReproducibleSeed {
+    transcript: Transcript,
+}
+
+// The seed is as secret as the witness, so it is never printed
+impl std::fmt::Debug for ReproducibleSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReproducibleSeed(..)")
+    }
+}
+
+impl ReproducibleSeed {
+    /// Create a seed from secret bytes
+    pub fn new(seed: &[u8]) -> Self {
+        let mut transcript = Transcript::new(REPRODUCIBLE_SEED_DOMAIN_SEP);
+        transcript.append_message(REPRODUCIBLE_SEED_DOMAIN_SEP, seed);
+        Self { transcript }
+    }
+
+    /// Get the independent rng stream for the unit of work with the given label and index
+    pub fn stream(&self, label: &'static [u8], index: u64) -> StdRng {
+        let mut transcript = self.transcript.clone();
+        transcript.append_message(RNG_STREAM_DOMAIN_SEP, label);
+        transcript.append_u64(b"INDEX", index);
+        let mut seed = [0; 32];
+        transcript.challenge_bytes(RNG_STREAM_DOMAIN_SEP, &mut seed);
+        StdRng::from_seed(seed)
+    }
+
+    /// Draw the random scalar of the unit of work with the given label and index
+    pub fn scalar(&self, label: &'static [u8], index: u64) -> Scalar {
+        Scalar::random(&mut self.stream(label, index))
+    }
+}
+
+/// Fiat-Shamir oracle deriving challenges exactly like a [`Transcript`], but seeding the prover's
+/// rng from a [`ReproducibleSeed`] stream instead of the operating system
+pub struct SeededTranscript {
+    transcript: Transcript,
+    rng: StdRng,
+}
+
+impl SeededTranscript {
+    /// Wrap a protocol transcript, taking prover randomness from the seed's stream for the given
+    /// label and index
+    pub fn new(
+        transcript: Transcript,
+        seed: &ReproducibleSeed,
+        label: &'static [u8],
+        index: u64,
+    ) -> Self {
+        Self {
+            transcript,
+            rng: seed.stream(label, index),
+        }
+    }
+}
+
+impl ChallengeOracle for SeededTranscript {
+    type Rng = TranscriptRng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.transcript.append_proof_value(curve_point);
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        self.transcript.get_challenge()
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        self.transcript
+            .build_rng()
+            .rekey_with_witness_bytes(SEEDED_WITNESS_DOMAIN_SEP, &statement.encode())
+            .finalize(&mut self.rng)
+    }
+
+    fn profile(&self) -> Profile {
+        Profile::Paranoid
+    }
+}
+
+// Map every item on up to `num_threads` threads, returning the results in item order. Each thread
+// handles a contiguous block of items, and the blocks are reassembled in order, so the result is
+// the same for any number of threads as long as `f` only depends on the index and item.
+pub(crate) fn parallel_map<T, U, F>(items: &[T], num_threads: usize, f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(usize, &T) -> U + Sync,
+{
+    let block_len = items.len().div_ceil(num_threads.max(1)).max(1);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(block_len)
+            .enumerate()
+            .map(|(block, block_items)| {
+                scope.spawn(move || {
+                    block_items
+                        .iter()
+                        .enumerate()
+                        .map(|(offset, item)| f(block * block_len + offset, item))
+                        .collect::<Vec<U>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlindingProof, PedersenGenerators};
+
+    #[test]
+    fn test_streams_and_parallel_maps_are_reproducible() {
+        let seed = ReproducibleSeed::new(b"compliance seed");
+        assert_eq!(seed.scalar(b"A", 3), seed.scalar(b"A", 3));
+        assert_ne!(seed.scalar(b"A", 3), seed.scalar(b"A", 4));
+        assert_ne!(seed.scalar(b"A", 3), seed.scalar(b"B", 3));
+        assert_ne!(
+            seed.scalar(b"A", 3),
+            ReproducibleSeed::new(b"other seed").scalar(b"A", 3)
+        );
+
+        let items: Vec<u64> = (0..37).collect();
+        let sequential = parallel_map(&items, 1, |index, item| {
+            seed.scalar(b"A", index as u64 + item)
+        });
+        for num_threads in [0, 2, 5, 64] {
+            assert_eq!(
+                parallel_map(&items, num_threads, |index, item| seed
+                    .scalar(b"A", index as u64 + item)),
+                sequential
+            );
+        }
+    }
+
+    #[test]
+    fn test_seeded_proofs_are_identical_and_verify_with_plain_transcripts() {
+        let generators = PedersenGenerators::default();
+        let seed = ReproducibleSeed::new(b"compliance seed");
+        let blinding = seed.scalar(b"BLINDING", 0);
+        let prove = || {
+            BlindingProof::generate_proof(
+                &blinding,
+                &generators,
+                &mut SeededTranscript::new(Transcript::new(b"TEST"), &seed, b"NONCE", 0),
+            )
+        };
+        let proof = prove();
+        assert_eq!(proof.get_proof_pair(), prove().get_proof_pair());
+        assert!(proof
+            .verify_proof(
+                &(blinding * generators.blinding_generator),
+                &generators,
+                &mut Transcript::new(b"TEST"),
+            )
+            .is_ok());
+    }
+}
@@ -0,0 +1,414 @@
+//! Interactive proving sessions whose transcripts can be persisted and resumed after a restart
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{Endianness, PointEncoding, Profile, ScalarEncoding};
+use merlin::{Transcript, TranscriptRng};
+
+use crate::{ChallengeOracle, Error, SimpleProofProtocol};
+
+// Edge devices run interactive sessions of several proofs bound to one transcript, e.g. a
+// provisioning proof followed by usage proofs, and may lose power or be restarted at any point.
+// A merlin transcript can't export its internal sponge state, but the state is a function of
+// everything absorbed into it, so a session is persisted as the log of its events instead:
+//
+// 1. The session is opened with a domain separator and every negotiated parameter, e.g. the bit
+// length of range proofs, is bound into the transcript as it is agreed on.
+// 2. Every prover message absorbed and every challenge handed out is appended to the log.
+// 3. Saving encodes the log as bytes and hands it to a `SessionStore`, e.g. a file per session.
+// 4. Resuming replays the log into a fresh transcript. Challenges are recomputed rather than
+// trusted, and a recorded challenge that doesn't match its recomputation means the stored state
+// was corrupted or tampered with, so the session is rejected.
+//
+// The log holds public messages only. Witnesses and the prover's nonces are never stored, so a
+// session should only be saved between complete proofs: a prover resuming between a commitment
+// and its response would need the nonce of the commitment again, and reusing a nonce with two
+// challenges reveals the witness.
+//
+// Sessions are persisted in a versioned format: a version byte, the length prefixed domain
+// separator, the number of events and every event as a tag byte followed by its payload. Lengths
+// and counts are little endian.
+
+// Domain separator for initializing resumable session transcripts
+const SESSION_DOMAIN_SEP: &[u8] = b"RESUMABLE_SESSION";
+
+// Current version of the session state format
+const SESSION_FORMAT_VERSION: u8 = 1;
+
+// Tag bytes of the encoded events
+const PARAMETER_TAG: u8 = 0x01;
+const PROVER_MESSAGE_TAG: u8 = 0x02;
+const CHALLENGE_TAG: u8 = 0x03;
+
+// File extension of sessions persisted by a `FileSessionStore`
+const SESSION_FILE_EXTENSION: &str = "session";
+
+/// Event recorded by a resumable session
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SessionEvent {
+    /// Parameter negotiated for the session (label, value)
+    Parameter(Vec<u8>, u64),
+    /// Prover message absorbed into the transcript
+    ProverMessage(RistrettoPoint),
+    /// Challenge handed out by the transcript
+    Challenge(Scalar),
+}
+
+/// Fiat-Shamir oracle recording its events so it can be saved and resumed later
+#[derive(Clone)]
+pub struct ResumableTranscript {
+    domain_sep: Vec<u8>,
+    transcript: Transcript,
+    events: Vec<SessionEvent>,
+}
+
+impl ResumableTranscript {
+    /// Open a new session separated from other sessions by `domain_sep`
+    pub fn new(domain_sep: &[u8]) -> Self {
+        let mut transcript = Transcript::new(SESSION_DOMAIN_SEP);
+        transcript.append_message(b"DOMAIN", domain_sep);
+        Self {
+            domain_sep: domain_sep.to_vec(),
+            transcript,
+            events: Vec::new(),
+        }
+    }
+
+    /// Bind a parameter agreed on by the prover and verifier into the session
+    pub fn negotiate(&mut self, label: &[u8], value: u64) {
+        self.transcript.append_message(b"PARAMETER_LABEL", label);
+        self.transcript.append_u64(b"PARAMETER", value);
+        self.events
+            .push(SessionEvent::Parameter(label.to_vec(), value));
+    }
+
+    /// Get the value most recently negotiated for `label`
+    pub fn get_parameter(&self, label: &[u8]) -> Option<u64> {
+        self.events.iter().rev().find_map(|event| match event {
+            SessionEvent::Parameter(parameter, value) if parameter == label => Some(*value),
+            _ => None,
+        })
+    }
+
+    /// Get the number of challenges handed out so far
+    pub fn get_round(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|event| matches!(event, SessionEvent::Challenge(_)))
+            .count()
+    }
+
+    /// Get the events recorded so far in the order they happened
+    pub fn get_events(&self) -> &[SessionEvent] {
+        &self.events
+    }
+
+    /// Encode the session into its versioned state format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![SESSION_FORMAT_VERSION];
+        bytes.extend((self.domain_sep.len() as u32).to_le_bytes());
+        bytes.extend(&self.domain_sep);
+        bytes.extend((self.events.len() as u32).to_le_bytes());
+        for event in &self.events {
+            match event {
+                SessionEvent::Parameter(label, value) => {
+                    bytes.push(PARAMETER_TAG);
+                    bytes.extend((label.len() as u32).to_le_bytes());
+                    bytes.extend(label);
+                    bytes.extend(value.to_le_bytes());
+                }
+                SessionEvent::ProverMessage(message) => {
+                    bytes.push(PROVER_MESSAGE_TAG);
+                    bytes.extend(message.encode());
+                }
+                SessionEvent::Challenge(challenge) => {
+                    bytes.push(CHALLENGE_TAG);
+                    bytes.extend(challenge.encode(Endianness::Little));
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Decode a session from its state format and replay it into a fresh transcript, rejecting
+    /// malformed states and recorded challenges the transcript doesn't reproduce
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = StateReader { bytes };
+        if reader.take(1)?[0] != SESSION_FORMAT_VERSION {
+            return Err(Error::MalformedSession);
+        }
+        let domain_len = reader.take_u32()?;
+        let mut session = Self::new(reader.take(domain_len)?);
+        for index in 0..reader.take_u32()? {
+            match reader.take(1)?[0] {
+                PARAMETER_TAG => {
+                    let label_len = reader.take_u32()?;
+                    let label = reader.take(label_len)?;
+                    session.negotiate(label, reader.take_u64()?);
+                }
+                PROVER_MESSAGE_TAG => {
+                    let message = RistrettoPoint::decode(reader.take(32)?)
+                        .map_err(|_| Error::MalformedSession)?;
+                    session.absorb_prover_message(&message);
+                }
+                CHALLENGE_TAG => {
+                    let challenge = Scalar::decode(reader.take(32)?, Endianness::Little)
+                        .map_err(|_| Error::MalformedSession)?;
+                    if session.next_challenge() != challenge {
+                        return Err(Error::SessionMismatch(index));
+                    }
+                }
+                _ => return Err(Error::MalformedSession),
+            }
+        }
+        if !reader.bytes.is_empty() {
+            return Err(Error::MalformedSession);
+        }
+        Ok(session)
+    }
+
+    /// Persist the session under `session_id`, replacing any state saved before
+    pub fn save<S: SessionStore>(&self, session_id: &str, store: &mut S) -> Result<(), Error> {
+        store.store(session_id, &self.to_bytes())
+    }
+
+    /// Resume the session persisted under `session_id`
+    pub fn resume<S: SessionStore>(session_id: &str, store: &S) -> Result<Self, Error> {
+        let bytes = store
+            .load(session_id)?
+            .ok_or_else(|| Error::SessionNotFound(session_id.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Resumable sessions derive challenges and prover randomness exactly like a merlin transcript
+impl ChallengeOracle for ResumableTranscript {
+    type Rng = TranscriptRng;
+
+    fn absorb_prover_message(&mut self, curve_point: &RistrettoPoint) {
+        self.transcript.append_proof_value(curve_point);
+        self.events.push(SessionEvent::ProverMessage(*curve_point));
+    }
+
+    fn next_challenge(&mut self) -> Scalar {
+        let challenge = self.transcript.get_challenge();
+        self.events.push(SessionEvent::Challenge(challenge));
+        challenge
+    }
+
+    fn prover_rng(&mut self, statement: &RistrettoPoint) -> Self::Rng {
+        self.transcript.get_rng(statement)
+    }
+
+    fn profile(&self) -> Profile {
+        Profile::Paranoid
+    }
+}
+
+// Cursor over encoded session state which fails on truncated input
+struct StateReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::MalformedSession);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_u32(&mut self) -> Result<usize, Error> {
+        let bytes = self.take(4)?.try_into().expect("took 4 bytes");
+        Ok(u32::from_le_bytes(bytes) as usize)
+    }
+
+    fn take_u64(&mut self) -> Result<u64, Error> {
+        let bytes = self.take(8)?.try_into().expect("took 8 bytes");
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+/// Storage for the encoded state of sessions, keyed by session id
+pub trait SessionStore {
+    /// Store the state of a session, replacing any state stored before
+    fn store(&mut self, session_id: &str, state: &[u8]) -> Result<(), Error>;
+
+    /// Load the state of a session, if any was stored
+    fn load(&self, session_id: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Remove the state of a finished session
+    fn remove(&mut self, session_id: &str) -> Result<(), Error>;
+}
+
+/// Session store keeping states in memory, e.g. for tests or devices with battery backed memory
+#[derive(Clone, Debug, Default)]
+pub struct MemorySessionStore {
+    states: HashMap<String, Vec<u8>>,
+}
+
+impl SessionStore for MemorySessionStore {
+    fn store(&mut self, session_id: &str, state: &[u8]) -> Result<(), Error> {
+        self.states.insert(session_id.to_string(), state.to_vec());
+        Ok(())
+    }
+
+    fn load(&self, session_id: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.states.get(session_id).cloned())
+    }
+
+    fn remove(&mut self, session_id: &str) -> Result<(), Error> {
+        self.states.remove(session_id);
+        Ok(())
+    }
+}
+
+/// Session store keeping a file per session in a directory. States are written to a temporary
+/// file first and renamed over the previous state, so a restart while saving leaves either the
+/// previous or the new state behind.
+#[derive(Clone, Debug)]
+pub struct FileSessionStore {
+    directory: PathBuf,
+}
+
+impl FileSessionStore {
+    /// Create a store in `directory`, creating the directory if it doesn't exist
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, Error> {
+        fs::create_dir_all(&directory).map_err(|e| Error::Io(e.to_string()))?;
+        Ok(Self {
+            directory: directory.as_ref().to_path_buf(),
+        })
+    }
+
+    // Session ids are hex encoded into file names so they can't escape the directory
+    fn path(&self, session_id: &str) -> PathBuf {
+        self.directory
+            .join(hex::encode(session_id))
+            .with_extension(SESSION_FILE_EXTENSION)
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn store(&mut self, session_id: &str, state: &[u8]) -> Result<(), Error> {
+        let path = self.path(session_id);
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, state)
+            .and_then(|_| fs::rename(&temporary, &path))
+            .map_err(|e| Error::Io(e.to_string()))
+    }
+
+    fn load(&self, session_id: &str) -> Result<Option<Vec<u8>>, Error> {
+        match fs::read(self.path(session_id)) {
+            Ok(state) => Ok(Some(state)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Io(e.to_string())),
+        }
+    }
+
+    fn remove(&mut self, session_id: &str) -> Result<(), Error> {
+        match fs::remove_file(self.path(session_id)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(Error::Io(e.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlindingProof, DleqProof, PedersenGenerators};
+
+    #[test]
+    fn test_sessions_resume_from_files_after_a_restart() {
+        let directory = std::env::temp_dir().join(format!("sessions-{}", std::process::id()));
+        let generators = PedersenGenerators::default();
+        let (g, h) = (generators.value_generator, generators.blinding_generator);
+        let secret = Scalar::random(&mut rand::rngs::OsRng);
+
+        // The device proves its first statement and saves the session between proofs
+        let mut store = FileSessionStore::new(&directory).unwrap();
+        let mut session = ResumableTranscript::new(b"EDGE_SESSION");
+        session.negotiate(b"ROUNDS", 2);
+        let blinding_proof = BlindingProof::generate_proof(&secret, &generators, &mut session);
+        session.save("device/7", &mut store).unwrap();
+        drop(session);
+
+        // After a restart it picks up where it left off
+        let mut session =
+            ResumableTranscript::resume("device/7", &FileSessionStore::new(&directory).unwrap())
+                .unwrap();
+        assert_eq!(session.get_parameter(b"ROUNDS"), Some(2));
+        assert_eq!(session.get_round(), 1);
+        let dleq_proof = DleqProof::generate_proof(&secret, &g, &h, &mut session);
+        assert_eq!(session.get_round(), 2);
+
+        // The verifier runs through the same session without ever restarting
+        let mut verifier = ResumableTranscript::new(b"EDGE_SESSION");
+        verifier.negotiate(b"ROUNDS", 2);
+        assert!(blinding_proof
+            .verify_proof(&(secret * h), &generators, &mut verifier)
+            .is_ok());
+        assert!(dleq_proof
+            .verify_proof(&g, &h, &(secret * g), &(secret * h), &mut verifier)
+            .is_ok());
+        assert_eq!(verifier.get_events(), session.get_events());
+
+        store.remove("device/7").unwrap();
+        assert_eq!(
+            ResumableTranscript::resume("device/7", &store).err(),
+            Some(Error::SessionNotFound("device/7".to_string()))
+        );
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_session_states_are_rejected() {
+        let mut store = MemorySessionStore::default();
+        let mut session = ResumableTranscript::new(b"EDGE_SESSION");
+        session.negotiate(b"BITS", 8);
+        session.absorb_prover_message(&PedersenGenerators::default().value_generator);
+        session.next_challenge();
+        session.save("device", &mut store).unwrap();
+        let state = store.load("device").unwrap().unwrap();
+        assert_eq!(
+            ResumableTranscript::from_bytes(&state)
+                .unwrap()
+                .get_events(),
+            session.get_events()
+        );
+
+        // A recorded challenge the replayed transcript doesn't reproduce
+        let mut tampered = state.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            ResumableTranscript::from_bytes(&tampered).err(),
+            Some(Error::SessionMismatch(2))
+        );
+        // A changed parameter changes every later challenge
+        let mut tampered = state.clone();
+        tampered[state.len() - 33 - 33 - 8] ^= 1;
+        assert_eq!(
+            ResumableTranscript::from_bytes(&tampered).err(),
+            Some(Error::SessionMismatch(2))
+        );
+        // Truncated, extended and unknown version states
+        for malformed in [
+            state[..state.len() - 1].to_vec(),
+            [state.as_slice(), &[0]].concat(),
+            [&[2], &state[1..]].concat(),
+        ] {
+            assert_eq!(
+                ResumableTranscript::from_bytes(&malformed).err(),
+                Some(Error::MalformedSession)
+            );
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Proofs that a Pedersen commitment opens to one of a public set of values
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{ProofScheme, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{Error, PedersenGenerators, SimpleProofProtocol, Vocabulary, VocabularyCommitment};
+
+// A set membership proof shows that `C = v*G + r*H` commits to one of the values `v_0, .., v_(n-1)`
+// without revealing which, e.g. that a committed classification is one of the model's labels.
+//
+// `C` commits to `v_i` exactly when `C - v_i*G = r*H`, so the proof is an OR over the `n` Schnorr
+// statements "I know the discrete log of `C - v_i*G` relative to `H`". This is the
+// Cramer-Damgard-Schoenmakers construction bit proofs use, generalized from two branches to `n`:
+//
+// 1. For every value except the committed one, the prover picks a challenge `c_i` and response
+// `z_i` up front and simulates the nonce commitment `A_i = z_i*H - c_i*(C - v_i*G)`.
+// 2. For the committed value it commits honestly to `A_j = k*H`.
+// 3. After the Fiat-Shamir challenge `c` is derived from the set, `C` and every `A_i`, the prover
+// sets `c_j = c - (sum of the simulated c_i)` and responds `z_j = k + c_j*r`.
+// 4. The verifier recomputes every `A_i` from `c_i` and `z_i` and checks that the challenges sum
+// to `c`.
+//
+// Since the challenges must sum to a value fixed only after all nonce commitments, at most `n - 1`
+// of them can be chosen freely and one branch must be answered honestly. The proof grows linearly
+// with the set, so it suits small sets such as class labels.
+//
+// Sets of labels should come from a committed [`Vocabulary`] rather than an ad-hoc list. The
+// vocabulary proofs bind the vocabulary commitment into the transcript before the set of its
+// encodings, so the verifier checks membership in exactly the published version.
+
+// Domain separator for binding the set into the transcript
+const SET_MEMBERSHIP_DOMAIN_SEP: &[u8] = b"SET_MEMBERSHIP";
+
+/// Proof that a Pedersen commitment opens to one of a public set of values
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SetMembershipProof {
+    // Challenge of the statement for each value of the set
+    challenges: Vec<Scalar>,
+    // Response of the statement for each value of the set
+    responses: Vec<Scalar>,
+}
+
+impl SetMembershipProof {
+    /// Prove that `value*G + blinding*H` commits to one of the values of `set`
+    pub fn generate_proof(
+        value: u64,
+        blinding: &Scalar,
+        set: &[u64],
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<Self, Error> {
+        let member = set
+            .iter()
+            .position(|element| *element == value)
+            .ok_or(Error::ValueNotInSet(value))?;
+        let mut rng = rand::rngs::OsRng;
+        let h = generators.blinding_generator;
+        let commitment = generators.commit(&Scalar::from(value), blinding);
+        let statements = statements(&commitment, set, generators);
+
+        // Simulate every statement but the true one by choosing its challenge and response first
+        let mut challenges: Vec<Scalar> = set.iter().map(|_| Scalar::random(&mut rng)).collect();
+        let mut responses: Vec<Scalar> = set.iter().map(|_| Scalar::random(&mut rng)).collect();
+        let random_scalar = Scalar::random(&mut rng);
+        let public_scalars: Vec<RistrettoPoint> = statements
+            .iter()
+            .enumerate()
+            .map(|(i, statement)| match i == member {
+                true => random_scalar * h,
+                false => responses[i] * h - challenges[i] * statement,
+            })
+            .collect();
+        let challenge_scalar = absorb_proof(proof_transcript, &commitment, set, &public_scalars);
+
+        // The true statement's challenge is whatever the simulated ones leave over
+        challenges[member] = Scalar::ZERO;
+        challenges[member] = challenge_scalar - challenges.iter().sum::<Scalar>();
+        responses[member] = random_scalar + challenges[member] * blinding;
+        Ok(Self {
+            challenges,
+            responses,
+        })
+    }
+
+    /// Prove that `encoding*G + blinding*H` commits to the encoding of a label of a vocabulary
+    pub fn generate_vocabulary_proof(
+        label: &str,
+        blinding: &Scalar,
+        vocabulary: &Vocabulary,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<Self, Error> {
+        let encoding = vocabulary.encode(label)?;
+        let commitment = vocabulary.commitment();
+        commitment.append_to_transcript(proof_transcript);
+        Self::generate_proof(
+            encoding,
+            blinding,
+            &commitment.encodings(),
+            generators,
+            proof_transcript,
+        )
+    }
+
+    /// Verify the commitment opens to one of the values of `set`
+    pub fn verify_proof(
+        &self,
+        commitment: &RistrettoPoint,
+        set: &[u64],
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        if set.is_empty() || self.challenges.len() != set.len() || self.responses.len() != set.len()
+        {
+            return Err(Error::SetMembershipMismatch);
+        }
+        let h = generators.blinding_generator;
+
+        // Recompute the prover's public scalars from the challenges and responses
+        let public_scalars: Vec<RistrettoPoint> = statements(commitment, set, generators)
+            .iter()
+            .zip(self.challenges.iter().zip(&self.responses))
+            .map(|(statement, (challenge, response))| response * h - challenge * statement)
+            .collect();
+        let challenge_scalar = absorb_proof(proof_transcript, commitment, set, &public_scalars);
+        if self.challenges.iter().sum::<Scalar>() != challenge_scalar {
+            return Err(Error::SetMembershipMismatch);
+        }
+        Ok(())
+    }
+
+    /// Verify the commitment opens to the encoding of a label of the committed vocabulary
+    pub fn verify_vocabulary_proof(
+        &self,
+        commitment: &RistrettoPoint,
+        vocabulary: &VocabularyCommitment,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        vocabulary.append_to_transcript(proof_transcript);
+        self.verify_proof(
+            commitment,
+            &vocabulary.encodings(),
+            generators,
+            proof_transcript,
+        )
+    }
+}
+
+// Statements `C - v_i*G` whose discrete log relative to `H` is known for the committed value
+fn statements(
+    commitment: &RistrettoPoint,
+    set: &[u64],
+    generators: &PedersenGenerators,
+) -> Vec<RistrettoPoint> {
+    set.iter()
+        .map(|element| commitment - Scalar::from(*element) * generators.value_generator)
+        .collect()
+}
+
+// Bind the set, the commitment and the nonce commitments to the transcript and derive the challenge
+fn absorb_proof(
+    proof_transcript: &mut Transcript,
+    commitment: &RistrettoPoint,
+    set: &[u64],
+    public_scalars: &[RistrettoPoint],
+) -> Scalar {
+    proof_transcript.append_u64(SET_MEMBERSHIP_DOMAIN_SEP, set.len() as u64);
+    for element in set {
+        proof_transcript.append_u64(b"ELEMENT", *element);
+    }
+    proof_transcript.append_proof_value(commitment);
+    for public_scalar in public_scalars {
+        proof_transcript.append_proof_value(public_scalar);
+    }
+    proof_transcript.get_challenge()
+}
+
+impl ProofScheme for SetMembershipProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encodings::HashBackend;
+
+    #[test]
+    fn test_set_membership_proof_verifies_for_every_member() {
+        let generators = PedersenGenerators::default();
+        let set = [3, 7, 11, 42];
+        for value in set {
+            let blinding = Scalar::random(&mut rand::rngs::OsRng);
+            let proof = SetMembershipProof::generate_proof(
+                value,
+                &blinding,
+                &set,
+                &generators,
+                &mut Transcript::new(b"test"),
+            )
+            .unwrap();
+            let commitment = generators.commit(&Scalar::from(value), &blinding);
+            assert_eq!(
+                proof.verify_proof(
+                    &commitment,
+                    &set,
+                    &generators,
+                    &mut Transcript::new(b"test")
+                ),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            SetMembershipProof::generate_proof(
+                5,
+                &Scalar::ONE,
+                &set,
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::ValueNotInSet(5))
+        );
+    }
+
+    #[test]
+    fn test_set_membership_proof_rejects_other_sets_and_commitments() {
+        let generators = PedersenGenerators::default();
+        let set = [3, 7, 11];
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let proof = SetMembershipProof::generate_proof(
+            7,
+            &blinding,
+            &set,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        let commitment = generators.commit(&Scalar::from(7u64), &blinding);
+
+        // A commitment to a value outside the set
+        let outside = generators.commit(&Scalar::from(8u64), &blinding);
+        assert!(proof
+            .verify_proof(&outside, &set, &generators, &mut Transcript::new(b"test"))
+            .is_err());
+        // The same size set with other values, and a set missing a value
+        for other_set in [&[3, 7, 12][..], &[3, 7]] {
+            assert_eq!(
+                proof.verify_proof(
+                    &commitment,
+                    other_set,
+                    &generators,
+                    &mut Transcript::new(b"test")
+                ),
+                Err(Error::SetMembershipMismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn test_vocabulary_proofs_are_bound_to_the_vocabulary_version() {
+        let generators = PedersenGenerators::default();
+        let labels = ["cat", "dog", "bird"];
+        let vocabulary = Vocabulary::new("animals", 1, &labels, HashBackend::Sha256).unwrap();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let proof = SetMembershipProof::generate_vocabulary_proof(
+            "dog",
+            &blinding,
+            &vocabulary,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        let commitment = generators.commit(&Scalar::from(1u64), &blinding);
+        assert_eq!(
+            proof.verify_vocabulary_proof(
+                &commitment,
+                &vocabulary.commitment(),
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Ok(())
+        );
+
+        // The same labels published as another version don't verify the proof
+        let next = Vocabulary::new("animals", 2, &labels, HashBackend::Sha256).unwrap();
+        assert_eq!(
+            proof.verify_vocabulary_proof(
+                &commitment,
+                &next.commitment(),
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::SetMembershipMismatch)
+        );
+        assert_eq!(
+            SetMembershipProof::generate_vocabulary_proof(
+                "cow",
+                &blinding,
+                &vocabulary,
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::UnknownLabel("cow".to_string()))
+        );
+    }
+}
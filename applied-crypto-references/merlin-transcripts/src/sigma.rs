@@ -0,0 +1,534 @@
+//! Generic sigma protocols over the Ristretto group composed with AND and OR combinators
+
+use std::{fmt::Debug, marker::PhantomData};
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{ProofScheme, SecurityLevel};
+use rand::{CryptoRng, RngCore};
+
+use crate::{challenge_oracle::validate_points, ChallengeOracle, Error};
+
+// Most proofs in this crate are sigma protocols of the same three move shape: the prover commits
+// to random nonces, the verifier picks a challenge `c` and the prover responds. Rather than hand
+// writing every combination of statements, a `SigmaProtocol` describes the three moves of one
+// statement and combinators build protocols for compound statements out of them:
+//
+// * AND: both protocols run side by side and answer the same challenge, proving both statements.
+// * OR (Cramer, Damgard and Schoenmakers '94): the verifier's challenge `c` is split into `c_0` and
+// `c_1` with `c_0 + c_1 = c`. The prover knows the witness of one branch only, so it picks the
+// other branch's challenge in advance and simulates an accepting transcript for it, which honest
+// verifier zero knowledge makes possible without the witness. Once `c` is known, the real branch
+// gets the remainder `c - c_i` and is answered honestly. The verifier sees two accepting
+// transcripts whose challenges sum to `c` and can't tell which one was simulated, while a prover
+// knowing neither witness would have to fix both challenges before seeing `c`.
+//
+// Since combined protocols are sigma protocols themselves, they nest, e.g. to prove knowledge of
+// one of three keys or of two keys out of two pairs. `SigmaProof` makes any of them
+// non-interactive: it absorbs the statement and the prover's commitment into a challenge oracle
+// before drawing the challenge, so the proof is bound to the statement it was made for.
+
+/// Three move sigma protocol for a statement about points in the Ristretto group
+pub trait SigmaProtocol {
+    /// Public statement being proven
+    type Statement;
+    /// Secret witness of the statement
+    type Witness;
+    /// Prover's first message
+    type Commitment: Clone + Debug + Eq;
+    /// Prover's answer to the challenge
+    type Response: Clone + Debug + Eq;
+    /// Secrets the prover keeps between its first message and its response
+    type ProverState;
+
+    /// Points making up a statement, absorbed before the prover's commitment
+    fn statement_points(statement: &Self::Statement) -> Vec<RistrettoPoint>;
+
+    /// Points making up a commitment, absorbed before the challenge is drawn
+    fn commitment_points(commitment: &Self::Commitment) -> Vec<RistrettoPoint>;
+
+    /// Create the prover's first message
+    fn commit<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut R,
+    ) -> (Self::ProverState, Self::Commitment);
+
+    /// Answer a challenge, failing if the prover state wasn't created for the witness
+    fn respond(
+        witness: &Self::Witness,
+        state: Self::ProverState,
+        challenge: &Scalar,
+    ) -> Result<Self::Response, Error>;
+
+    /// Create an accepting transcript for a challenge chosen in advance, without the witness
+    fn simulate<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &Scalar,
+        rng: &mut R,
+    ) -> (Self::Commitment, Self::Response);
+
+    /// Check a transcript is accepting
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        challenge: &Scalar,
+        response: &Self::Response,
+    ) -> bool;
+}
+
+/// Knowledge of `x` such that `P = x*G` for a generator `G`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DiscreteLog;
+
+/// Statement of knowing the discrete log of a point relative to a generator
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DiscreteLogStatement {
+    /// Generator the point is a multiple of
+    pub generator: RistrettoPoint,
+    /// Point whose discrete log is known
+    pub point: RistrettoPoint,
+}
+
+impl SigmaProtocol for DiscreteLog {
+    type Statement = DiscreteLogStatement;
+    type Witness = Scalar;
+    type Commitment = RistrettoPoint;
+    type Response = Scalar;
+    type ProverState = Scalar;
+
+    fn statement_points(statement: &Self::Statement) -> Vec<RistrettoPoint> {
+        vec![statement.generator, statement.point]
+    }
+
+    fn commitment_points(commitment: &Self::Commitment) -> Vec<RistrettoPoint> {
+        vec![*commitment]
+    }
+
+    fn commit<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        _witness: &Self::Witness,
+        rng: &mut R,
+    ) -> (Self::ProverState, Self::Commitment) {
+        let nonce = Scalar::random(rng);
+        (nonce, nonce * statement.generator)
+    }
+
+    fn respond(
+        witness: &Self::Witness,
+        state: Self::ProverState,
+        challenge: &Scalar,
+    ) -> Result<Self::Response, Error> {
+        Ok(state + challenge * witness)
+    }
+
+    fn simulate<R: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &Scalar,
+        rng: &mut R,
+    ) -> (Self::Commitment, Self::Response) {
+        let response = Scalar::random(rng);
+        (
+            response * statement.generator - challenge * statement.point,
+            response,
+        )
+    }
+
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        challenge: &Scalar,
+        response: &Self::Response,
+    ) -> bool {
+        response * statement.generator == commitment + challenge * statement.point
+    }
+}
+
+/// Proof of both statements of two sigma protocols
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct And<L, R>(PhantomData<(L, R)>);
+
+impl<L: SigmaProtocol, R: SigmaProtocol> SigmaProtocol for And<L, R> {
+    type Statement = (L::Statement, R::Statement);
+    type Witness = (L::Witness, R::Witness);
+    type Commitment = (L::Commitment, R::Commitment);
+    type Response = (L::Response, R::Response);
+    type ProverState = (L::ProverState, R::ProverState);
+
+    fn statement_points(statement: &Self::Statement) -> Vec<RistrettoPoint> {
+        let mut points = L::statement_points(&statement.0);
+        points.extend(R::statement_points(&statement.1));
+        points
+    }
+
+    fn commitment_points(commitment: &Self::Commitment) -> Vec<RistrettoPoint> {
+        let mut points = L::commitment_points(&commitment.0);
+        points.extend(R::commitment_points(&commitment.1));
+        points
+    }
+
+    fn commit<G: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut G,
+    ) -> (Self::ProverState, Self::Commitment) {
+        let (left_state, left_commitment) = L::commit(&statement.0, &witness.0, rng);
+        let (right_state, right_commitment) = R::commit(&statement.1, &witness.1, rng);
+        (
+            (left_state, right_state),
+            (left_commitment, right_commitment),
+        )
+    }
+
+    fn respond(
+        witness: &Self::Witness,
+        state: Self::ProverState,
+        challenge: &Scalar,
+    ) -> Result<Self::Response, Error> {
+        Ok((
+            L::respond(&witness.0, state.0, challenge)?,
+            R::respond(&witness.1, state.1, challenge)?,
+        ))
+    }
+
+    fn simulate<G: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &Scalar,
+        rng: &mut G,
+    ) -> (Self::Commitment, Self::Response) {
+        let (left_commitment, left_response) = L::simulate(&statement.0, challenge, rng);
+        let (right_commitment, right_response) = R::simulate(&statement.1, challenge, rng);
+        (
+            (left_commitment, right_commitment),
+            (left_response, right_response),
+        )
+    }
+
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        challenge: &Scalar,
+        response: &Self::Response,
+    ) -> bool {
+        L::verify(&statement.0, &commitment.0, challenge, &response.0)
+            && R::verify(&statement.1, &commitment.1, challenge, &response.1)
+    }
+}
+
+/// Proof of one of the statements of two sigma protocols, without revealing which
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Or<L, R>(PhantomData<(L, R)>);
+
+/// Witness of one branch of an OR statement
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OrWitness<L, R> {
+    /// Witness of the left statement
+    Left(L),
+    /// Witness of the right statement
+    Right(R),
+}
+
+/// Prover's state in an OR proof: the real branch's state and the simulated branch's transcript
+pub enum OrProverState<L: SigmaProtocol, R: SigmaProtocol> {
+    /// The left branch is real and the right one simulated
+    Left(L::ProverState, Scalar, R::Response),
+    /// The right branch is real and the left one simulated
+    Right(R::ProverState, Scalar, L::Response),
+}
+
+/// Response to an OR proof's challenge, split between its two branches
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OrResponse<L, R> {
+    /// Challenge answered by the left branch, the right one answers the rest
+    pub left_challenge: Scalar,
+    /// Response of the left branch
+    pub left: L,
+    /// Response of the right branch
+    pub right: R,
+}
+
+impl<L: SigmaProtocol, R: SigmaProtocol> SigmaProtocol for Or<L, R> {
+    type Statement = (L::Statement, R::Statement);
+    type Witness = OrWitness<L::Witness, R::Witness>;
+    type Commitment = (L::Commitment, R::Commitment);
+    type Response = OrResponse<L::Response, R::Response>;
+    type ProverState = OrProverState<L, R>;
+
+    fn statement_points(statement: &Self::Statement) -> Vec<RistrettoPoint> {
+        And::<L, R>::statement_points(statement)
+    }
+
+    fn commitment_points(commitment: &Self::Commitment) -> Vec<RistrettoPoint> {
+        And::<L, R>::commitment_points(commitment)
+    }
+
+    fn commit<G: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        rng: &mut G,
+    ) -> (Self::ProverState, Self::Commitment) {
+        // Simulate the branch without a witness for a challenge of its own choosing
+        let simulated_challenge = Scalar::random(rng);
+        match witness {
+            OrWitness::Left(witness) => {
+                let (state, commitment) = L::commit(&statement.0, witness, rng);
+                let (simulated_commitment, simulated_response) =
+                    R::simulate(&statement.1, &simulated_challenge, rng);
+                (
+                    OrProverState::Left(state, simulated_challenge, simulated_response),
+                    (commitment, simulated_commitment),
+                )
+            }
+            OrWitness::Right(witness) => {
+                let (state, commitment) = R::commit(&statement.1, witness, rng);
+                let (simulated_commitment, simulated_response) =
+                    L::simulate(&statement.0, &simulated_challenge, rng);
+                (
+                    OrProverState::Right(state, simulated_challenge, simulated_response),
+                    (simulated_commitment, commitment),
+                )
+            }
+        }
+    }
+
+    fn respond(
+        witness: &Self::Witness,
+        state: Self::ProverState,
+        challenge: &Scalar,
+    ) -> Result<Self::Response, Error> {
+        // The real branch answers whatever is left of the challenge
+        match (witness, state) {
+            (OrWitness::Left(witness), OrProverState::Left(state, right_challenge, right)) => {
+                let left_challenge = challenge - right_challenge;
+                Ok(OrResponse {
+                    left_challenge,
+                    left: L::respond(witness, state, &left_challenge)?,
+                    right,
+                })
+            }
+            (OrWitness::Right(witness), OrProverState::Right(state, left_challenge, left)) => {
+                Ok(OrResponse {
+                    left_challenge,
+                    left,
+                    right: R::respond(witness, state, &(challenge - left_challenge))?,
+                })
+            }
+            _ => Err(Error::MismatchedOrWitness),
+        }
+    }
+
+    fn simulate<G: RngCore + CryptoRng>(
+        statement: &Self::Statement,
+        challenge: &Scalar,
+        rng: &mut G,
+    ) -> (Self::Commitment, Self::Response) {
+        let left_challenge = Scalar::random(rng);
+        let (left_commitment, left) = L::simulate(&statement.0, &left_challenge, rng);
+        let (right_commitment, right) =
+            R::simulate(&statement.1, &(challenge - left_challenge), rng);
+        (
+            (left_commitment, right_commitment),
+            OrResponse {
+                left_challenge,
+                left,
+                right,
+            },
+        )
+    }
+
+    fn verify(
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        challenge: &Scalar,
+        response: &Self::Response,
+    ) -> bool {
+        let right_challenge = challenge - response.left_challenge;
+        L::verify(
+            &statement.0,
+            &commitment.0,
+            &response.left_challenge,
+            &response.left,
+        ) && R::verify(
+            &statement.1,
+            &commitment.1,
+            &right_challenge,
+            &response.right,
+        )
+    }
+}
+
+/// Non-interactive proof of a sigma protocol's statement
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SigmaProof<S: SigmaProtocol> {
+    commitment: S::Commitment,
+    response: S::Response,
+}
+
+impl<S: SigmaProtocol> SigmaProof<S> {
+    /// Prove a statement with its witness
+    pub fn generate_proof<O: ChallengeOracle>(
+        statement: &S::Statement,
+        witness: &S::Witness,
+        proof_transcript: &mut O,
+    ) -> Result<Self, Error> {
+        let statement_points = S::statement_points(statement);
+        statement_points
+            .iter()
+            .for_each(|point| proof_transcript.absorb_prover_message(point));
+
+        let mut rng = proof_transcript.prover_rng(&statement_points.iter().sum());
+        let (state, commitment) = S::commit(statement, witness, &mut rng);
+        S::commitment_points(&commitment)
+            .iter()
+            .for_each(|point| proof_transcript.absorb_prover_message(point));
+
+        let challenge = proof_transcript.next_challenge();
+        Ok(Self {
+            response: S::respond(witness, state, &challenge)?,
+            commitment,
+        })
+    }
+
+    /// Verify the proof of a statement
+    pub fn verify_proof<O: ChallengeOracle>(
+        &self,
+        statement: &S::Statement,
+        proof_transcript: &mut O,
+    ) -> Result<(), Error> {
+        let statement_points = S::statement_points(statement);
+        let commitment_points = S::commitment_points(&self.commitment);
+        let points: Vec<&RistrettoPoint> =
+            statement_points.iter().chain(&commitment_points).collect();
+        validate_points(proof_transcript, &points)?;
+        points
+            .iter()
+            .for_each(|point| proof_transcript.absorb_prover_message(point));
+
+        let challenge = proof_transcript.next_challenge();
+        if S::verify(statement, &self.commitment, &challenge, &self.response) {
+            return Ok(());
+        }
+        Err(Error::SigmaProofMismatch)
+    }
+
+    /// Get the prover's commitment and response
+    pub fn get_proof_values(&self) -> (&S::Commitment, &S::Response) {
+        (&self.commitment, &self.response)
+    }
+}
+
+impl<S: SigmaProtocol> ProofScheme for SigmaProof<S> {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_keypair;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+    use merlin::Transcript;
+
+    fn key_statement(public_key: RistrettoPoint) -> DiscreteLogStatement {
+        DiscreteLogStatement {
+            generator: G,
+            point: public_key,
+        }
+    }
+
+    #[test]
+    fn test_or_proofs_verify_for_either_witness_and_hide_which() {
+        let (key_a, public_a) = generate_keypair();
+        let (key_b, public_b) = generate_keypair();
+        let statement = (key_statement(public_a), key_statement(public_b));
+
+        for witness in [OrWitness::Left(key_a), OrWitness::Right(key_b)] {
+            let proof = SigmaProof::<Or<DiscreteLog, DiscreteLog>>::generate_proof(
+                &statement,
+                &witness,
+                &mut Transcript::new(b"test"),
+            )
+            .unwrap();
+            assert!(proof
+                .verify_proof(&statement, &mut Transcript::new(b"test"))
+                .is_ok());
+
+            // Both branches answer a challenge, neither of them obviously the real one
+            let (_, response) = proof.get_proof_values();
+            assert_ne!(response.left_challenge, Scalar::ZERO);
+            assert!(proof
+                .verify_proof(&(statement.1, statement.0), &mut Transcript::new(b"test"))
+                .is_err());
+        }
+
+        // Knowing neither key isn't enough: the "witness" of a third key doesn't verify
+        let (other_key, _) = generate_keypair();
+        let proof = SigmaProof::<Or<DiscreteLog, DiscreteLog>>::generate_proof(
+            &statement,
+            &OrWitness::Left(other_key),
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify_proof(&statement, &mut Transcript::new(b"test")),
+            Err(Error::SigmaProofMismatch)
+        );
+
+        // Responding for the other branch than the one committed to is an error, not a panic
+        type Either = Or<DiscreteLog, DiscreteLog>;
+        let (state, _) =
+            Either::commit(&statement, &OrWitness::Left(key_a), &mut rand::rngs::OsRng);
+        assert_eq!(
+            Either::respond(&OrWitness::Right(key_b), state, &Scalar::ONE),
+            Err(Error::MismatchedOrWitness)
+        );
+    }
+
+    #[test]
+    fn test_composed_protocols_nest() {
+        let keys: Vec<_> = (0..3).map(|_| generate_keypair()).collect();
+        let statements: Vec<_> = keys
+            .iter()
+            .map(|(_, public)| key_statement(*public))
+            .collect();
+
+        // Key 0 AND (key 1 OR key 2), proven with key 0 and key 2
+        type Nested = And<DiscreteLog, Or<DiscreteLog, DiscreteLog>>;
+        let statement = (statements[0], (statements[1], statements[2]));
+        let proof = SigmaProof::<Nested>::generate_proof(
+            &statement,
+            &(keys[0].0, OrWitness::Right(keys[2].0)),
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        assert!(proof
+            .verify_proof(&statement, &mut Transcript::new(b"test"))
+            .is_ok());
+
+        // The AND half can't be skipped by knowing only the OR half
+        let proof = SigmaProof::<Nested>::generate_proof(
+            &statement,
+            &(keys[1].0, OrWitness::Right(keys[2].0)),
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        assert!(proof
+            .verify_proof(&statement, &mut Transcript::new(b"test"))
+            .is_err());
+
+        // Simulated transcripts are accepting for the challenge they were made for
+        let challenge = Scalar::from(42u64);
+        let (commitment, response) =
+            Nested::simulate(&statement, &challenge, &mut rand::rngs::OsRng);
+        assert!(Nested::verify(
+            &statement,
+            &commitment,
+            &challenge,
+            &response
+        ));
+        assert!(!Nested::verify(
+            &statement,
+            &commitment,
+            &Scalar::from(43u64),
+            &response
+        ));
+    }
+}
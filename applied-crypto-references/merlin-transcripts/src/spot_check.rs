@@ -0,0 +1,507 @@
+//! Verifier-initiated spot checks of statement digests with selections devices can't predict
+
+use std::{collections::BTreeMap, fmt};
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT as G, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use encodings::{ProofScheme, SecretScalar, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{generate_keypair, DleqProof, Error, SimpleProofProtocol};
+
+// Fleets of constrained devices can't afford to ship a full proof for every statement they make,
+// e.g. for every inference they run. Instead a device ships a 32 byte digest of each statement and
+// keeps the statement and its witness around, and the verifier spot checks a random subset:
+//
+// 1. The device sends the digest of every statement and the verifier numbers them in order.
+// 2. Every so often the verifier issues a challenge over the digests received since the last one.
+// It evaluates a verifiable random function (VRF) on those digests, `Γ = x*H` for its secret key
+// `x` and a point `H` hashed from the device, the round and the digests, and selects every
+// sequence number whose challenge derived from `Γ` is 0 modulo the check rate `N`.
+// 3. The challenge carries `Γ` and a DLEQ proof that it shares its discrete log with the public
+// key `x*G`, so the device can check the selection is honest and not targeted at it, yet it can't
+// predict the selection before it has committed to its digests.
+// 4. The device answers with a full proof of every selected statement before the deadline. A
+// device whose proof doesn't match its digest or verify, or which misses the deadline, is flagged
+// and none of its digests are accepted anymore.
+//
+// A device which fakes `k` statements escapes detection with probability `(1 - 1/N)^k`, while
+// the bandwidth spent on proofs drops by a factor of `N`. Checking one statement in ten catches a
+// device faking 50 statements with a probability above 99 percent.
+
+// Domain separator for hashing the digests of a challenge to the VRF input point
+const SPOT_CHECK_INPUT_DOMAIN_SEP: &[u8] = b"SPOT_CHECK_VRF_INPUT";
+
+// Domain separator for the proof that the VRF output was computed with the verifier's key
+const SPOT_CHECK_PROOF_DOMAIN_SEP: &[u8] = b"SPOT_CHECK_VRF_PROOF";
+
+// Domain separator for deriving the selection from the VRF output
+const SPOT_CHECK_SELECTION_DOMAIN_SEP: &[u8] = b"SPOT_CHECK_SELECTION";
+
+// Hash the device, the round and the digests covered by a challenge to the VRF input point
+fn vrf_input(
+    device: u64,
+    round: u64,
+    first_sequence: u64,
+    check_rate: u64,
+    digests: &[[u8; 32]],
+) -> RistrettoPoint {
+    let mut transcript = Transcript::new(SPOT_CHECK_INPUT_DOMAIN_SEP);
+    transcript.append_u64(b"DEVICE", device);
+    transcript.append_u64(b"ROUND", round);
+    transcript.append_u64(b"FIRST_SEQUENCE", first_sequence);
+    transcript.append_u64(b"CHECK_RATE", check_rate);
+    transcript.append_u64(b"DIGESTS", digests.len() as u64);
+    for digest in digests {
+        transcript.append_message(b"DIGEST", digest);
+    }
+    let mut buf = [0u8; 64];
+    transcript.challenge_bytes(b"INPUT_POINT", &mut buf);
+    RistrettoPoint::from_uniform_bytes(&buf)
+}
+
+// Select the sequence numbers whose challenge derived from the VRF output is 0 modulo the rate
+fn select_sequences(
+    output: &RistrettoPoint,
+    first_sequence: u64,
+    count: usize,
+    check_rate: u64,
+) -> Vec<u64> {
+    let mut transcript = Transcript::new(SPOT_CHECK_SELECTION_DOMAIN_SEP);
+    transcript.append_proof_value(output);
+    (first_sequence..first_sequence + count as u64)
+        .filter(|sequence| {
+            let mut buf = [0u8; 8];
+            transcript.append_u64(b"SEQUENCE", *sequence);
+            transcript.challenge_bytes(b"SELECTION", &mut buf);
+            u64::from_le_bytes(buf) % check_rate == 0
+        })
+        .collect()
+}
+
+/// Reason a device was flagged by its verifier
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FlagReason {
+    /// No proof of the selected statement (sequence) arrived before the deadline
+    MissedDeadline(u64),
+    /// Proof of the selected statement (sequence) didn't verify against its digest
+    InvalidProof(u64),
+}
+
+/// Standing of a device with its verifier
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DeviceStatus {
+    /// Device answered every challenge so far
+    Active,
+    /// Device failed a challenge and its digests aren't accepted anymore
+    Flagged(FlagReason),
+}
+
+/// Challenge selecting the statements a device has to prove in full before a deadline
+#[derive(Clone, Debug)]
+pub struct SpotCheckChallenge {
+    device: u64,
+    round: u64,
+    // Sequence number of the first digest covered by the challenge
+    first_sequence: u64,
+    selected: Vec<u64>,
+    // VRF output selecting the statements
+    output: RistrettoPoint,
+    // Proof the output was computed with the verifier's key
+    proof: DleqProof,
+    deadline: u64,
+}
+
+impl SpotCheckChallenge {
+    /// Verify the selection was computed with the verifier's key over `digests`, the digests the
+    /// device sent since its previous challenge
+    pub fn verify(
+        &self,
+        public_key: &RistrettoPoint,
+        digests: &[[u8; 32]],
+        check_rate: u64,
+    ) -> Result<(), Error> {
+        if check_rate == 0 {
+            return Err(Error::InvalidCheckRate);
+        }
+        let input = vrf_input(
+            self.device,
+            self.round,
+            self.first_sequence,
+            check_rate,
+            digests,
+        );
+        self.proof.verify_proof(
+            &G,
+            &input,
+            public_key,
+            &self.output,
+            &mut Transcript::new(SPOT_CHECK_PROOF_DOMAIN_SEP),
+        )?;
+        let selected =
+            select_sequences(&self.output, self.first_sequence, digests.len(), check_rate);
+        if selected != self.selected {
+            return Err(Error::SelectionMismatch);
+        }
+        Ok(())
+    }
+
+    /// Get the device the challenge is addressed to
+    pub fn get_device(&self) -> u64 {
+        self.device
+    }
+
+    /// Get the sequence numbers of the statements to prove in full
+    pub fn get_selected(&self) -> &[u64] {
+        &self.selected
+    }
+
+    /// Get the time by which the selected statements have to be proven
+    pub fn get_deadline(&self) -> u64 {
+        self.deadline
+    }
+}
+
+// Digests received from a device and the state of its challenges
+#[derive(Clone, Debug)]
+struct DeviceRecord {
+    digests: Vec<[u8; 32]>,
+    // Number of digests covered by a challenge so far
+    checked_up_to: usize,
+    round: u64,
+    // Deadlines of the selected statements which weren't proven yet, by sequence number
+    pending: BTreeMap<u64, u64>,
+    status: DeviceStatus,
+}
+
+impl Default for DeviceRecord {
+    fn default() -> Self {
+        Self {
+            digests: Vec::new(),
+            checked_up_to: 0,
+            round: 0,
+            pending: BTreeMap::new(),
+            status: DeviceStatus::Active,
+        }
+    }
+}
+
+impl DeviceRecord {
+    // Reject digests and proofs of flagged devices
+    fn ensure_active(&self, device: u64) -> Result<(), Error> {
+        match self.status {
+            DeviceStatus::Active => Ok(()),
+            DeviceStatus::Flagged(_) => Err(Error::DeviceFlagged(device)),
+        }
+    }
+
+    fn flag(&mut self, reason: FlagReason) {
+        self.status = DeviceStatus::Flagged(reason);
+        self.pending.clear();
+    }
+}
+
+/// Verifier collecting statement digests from devices and spot checking one in `check_rate`. Its
+/// VRF secret is overwritten with zeros when it is dropped.
+pub struct SpotChecker {
+    secret: SecretScalar<Scalar>,
+    public_key: RistrettoPoint,
+    check_rate: u64,
+    // Time a device has to answer a challenge
+    response_window: u64,
+    devices: BTreeMap<u64, DeviceRecord>,
+}
+
+// Anyone knowing the VRF secret can predict which statements are checked, so it is never printed
+impl fmt::Debug for SpotChecker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpotChecker")
+            .field("secret", &self.secret)
+            .field("public_key", &self.public_key)
+            .field("check_rate", &self.check_rate)
+            .field("response_window", &self.response_window)
+            .field("devices", &self.devices)
+            .finish()
+    }
+}
+
+impl SpotChecker {
+    /// Create a verifier checking one statement in `check_rate` and giving devices
+    /// `response_window` units of time to answer a challenge
+    pub fn new(check_rate: u64, response_window: u64) -> Result<Self, Error> {
+        if check_rate == 0 {
+            return Err(Error::InvalidCheckRate);
+        }
+        let (secret, public_key) = generate_keypair();
+        Ok(Self {
+            secret: SecretScalar::new(secret),
+            public_key,
+            check_rate,
+            response_window,
+            devices: BTreeMap::new(),
+        })
+    }
+
+    /// Get the public key devices check challenges against
+    pub fn get_public_key(&self) -> RistrettoPoint {
+        self.public_key
+    }
+
+    /// Get the rate statements are checked at
+    pub fn get_check_rate(&self) -> u64 {
+        self.check_rate
+    }
+
+    /// Record the digest of a statement made by `device`, returning its sequence number
+    pub fn receive_digest(&mut self, device: u64, digest: [u8; 32]) -> Result<u64, Error> {
+        let record = self.devices.entry(device).or_default();
+        record.ensure_active(device)?;
+        record.digests.push(digest);
+        Ok(record.digests.len() as u64 - 1)
+    }
+
+    /// Challenge `device` to prove the statements selected among the digests it sent since its
+    /// previous challenge
+    pub fn issue_challenge(&mut self, device: u64, now: u64) -> Result<SpotCheckChallenge, Error> {
+        let record = self.devices.entry(device).or_default();
+        record.ensure_active(device)?;
+        let first_sequence = record.checked_up_to as u64;
+        let digests = &record.digests[record.checked_up_to..];
+
+        let input = vrf_input(
+            device,
+            record.round,
+            first_sequence,
+            self.check_rate,
+            digests,
+        );
+        let output = self.secret.get_scalar() * input;
+        let proof = DleqProof::generate_proof(
+            self.secret.get_scalar(),
+            &G,
+            &input,
+            &mut Transcript::new(SPOT_CHECK_PROOF_DOMAIN_SEP),
+        );
+        let selected = select_sequences(&output, first_sequence, digests.len(), self.check_rate);
+
+        let deadline = now + self.response_window;
+        let challenge = SpotCheckChallenge {
+            device,
+            round: record.round,
+            first_sequence,
+            selected,
+            output,
+            proof,
+            deadline,
+        };
+        record.checked_up_to = record.digests.len();
+        record.round += 1;
+        record.pending.extend(
+            challenge
+                .selected
+                .iter()
+                .map(|sequence| (*sequence, deadline)),
+        );
+        Ok(challenge)
+    }
+
+    /// Check the full proof of the selected statement `sequence` of `device` received at `now`.
+    /// `verify` checks the proof against the digest the device sent for the statement. A failed
+    /// check or a late answer flags the device.
+    pub fn respond(
+        &mut self,
+        device: u64,
+        sequence: u64,
+        now: u64,
+        verify: impl FnOnce(&[u8; 32]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let record = self
+            .devices
+            .get_mut(&device)
+            .ok_or(Error::NotChallenged(sequence))?;
+        record.ensure_active(device)?;
+        let deadline = record
+            .pending
+            .remove(&sequence)
+            .ok_or(Error::NotChallenged(sequence))?;
+        if now > deadline {
+            record.flag(FlagReason::MissedDeadline(sequence));
+            return Err(Error::DeadlineMissed(sequence));
+        }
+        verify(&record.digests[sequence as usize])
+            .inspect_err(|_| record.flag(FlagReason::InvalidProof(sequence)))
+    }
+
+    /// Flag every device with a selected statement left unproven past its deadline, returning
+    /// the devices flagged by this call
+    pub fn expire(&mut self, now: u64) -> Vec<u64> {
+        let mut flagged = Vec::new();
+        for (device, record) in self.devices.iter_mut() {
+            let missed = record
+                .pending
+                .iter()
+                .find(|(_, deadline)| now > **deadline)
+                .map(|(sequence, _)| *sequence);
+            if let Some(sequence) = missed {
+                record.flag(FlagReason::MissedDeadline(sequence));
+                flagged.push(*device);
+            }
+        }
+        flagged
+    }
+
+    /// Get the standing of `device`, if it ever sent a digest or was challenged
+    pub fn get_status(&self, device: u64) -> Option<DeviceStatus> {
+        self.devices.get(&device).map(|record| record.status)
+    }
+}
+
+impl ProofScheme for SpotCheckChallenge {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlindingProof, PedersenGenerators};
+    use encodings::{Digest, HashBackend, PointEncoding};
+
+    const STATEMENT_DOMAIN_SEP: &[u8] = b"TEST_STATEMENT";
+
+    // Device statements are commitments `C = r*H` whose full proofs are proofs of knowledge of `r`
+    struct Device {
+        blindings: Vec<Scalar>,
+        generators: PedersenGenerators,
+    }
+
+    impl Device {
+        fn new(statements: usize) -> Self {
+            let blindings = (0..statements)
+                .map(|_| Scalar::random(&mut rand::rngs::OsRng))
+                .collect();
+            Self {
+                blindings,
+                generators: PedersenGenerators::default(),
+            }
+        }
+
+        fn statement(&self, sequence: u64) -> RistrettoPoint {
+            self.blindings[sequence as usize] * self.generators.blinding_generator
+        }
+
+        fn digests(&self) -> Vec<[u8; 32]> {
+            (0..self.blindings.len() as u64)
+                .map(|sequence| digest(&self.statement(sequence)))
+                .collect()
+        }
+
+        fn prove(&self, sequence: u64) -> (RistrettoPoint, BlindingProof) {
+            let proof = BlindingProof::generate_proof(
+                &self.blindings[sequence as usize],
+                &self.generators,
+                &mut Transcript::new(STATEMENT_DOMAIN_SEP),
+            );
+            (self.statement(sequence), proof)
+        }
+    }
+
+    fn digest(statement: &RistrettoPoint) -> [u8; 32] {
+        HashBackend::default().hash(STATEMENT_DOMAIN_SEP, &statement.encode())
+    }
+
+    // Check a full proof matches the digest it was selected by and verifies
+    fn verify_statement(
+        (statement, proof): (RistrettoPoint, BlindingProof),
+    ) -> impl FnOnce(&[u8; 32]) -> Result<(), Error> {
+        move |expected| {
+            if digest(&statement) != *expected {
+                return Err(Error::SelectionMismatch);
+            }
+            proof.verify_proof(
+                &statement,
+                &PedersenGenerators::default(),
+                &mut Transcript::new(STATEMENT_DOMAIN_SEP),
+            )
+        }
+    }
+
+    #[test]
+    fn test_honest_device_answers_verifiable_challenges() {
+        let mut checker = SpotChecker::new(2, 10).unwrap();
+        assert!(format!("{checker:?}").contains("SecretScalar(<redacted>)"));
+        let device = Device::new(40);
+        let digests = device.digests();
+        for (sequence, digest) in digests.iter().enumerate() {
+            assert_eq!(checker.receive_digest(7, *digest), Ok(sequence as u64));
+        }
+
+        let challenge = checker.issue_challenge(7, 100).unwrap();
+        assert!(challenge
+            .verify(&checker.get_public_key(), &digests, 2)
+            .is_ok());
+        assert!(!challenge.get_selected().is_empty());
+        for sequence in challenge.get_selected() {
+            let proof = device.prove(*sequence);
+            assert!(checker
+                .respond(7, *sequence, 105, verify_statement(proof))
+                .is_ok());
+        }
+        assert!(checker.expire(200).is_empty());
+        assert_eq!(checker.get_status(7), Some(DeviceStatus::Active));
+
+        // The selection can't be changed without the verifier's key and covers only new digests
+        let mut tampered = challenge.clone();
+        tampered.selected.pop();
+        assert_eq!(
+            tampered.verify(&checker.get_public_key(), &digests, 2),
+            Err(Error::SelectionMismatch)
+        );
+        assert!(challenge
+            .verify(&checker.get_public_key(), &digests[1..], 2)
+            .is_err());
+        let next = checker.issue_challenge(7, 200).unwrap();
+        assert!(next.get_selected().is_empty());
+        assert!(next.verify(&checker.get_public_key(), &[], 2).is_ok());
+    }
+
+    #[test]
+    fn test_cheating_and_silent_devices_are_flagged() {
+        let mut checker = SpotChecker::new(1, 10).unwrap();
+        let (honest, forged) = (Device::new(3), Device::new(3));
+        for digest in honest.digests() {
+            checker.receive_digest(1, digest).unwrap();
+            checker.receive_digest(2, digest).unwrap();
+        }
+
+        // Proofs of statements other than the digested ones flag the device
+        let challenge = checker.issue_challenge(1, 0).unwrap();
+        assert_eq!(challenge.get_selected(), &[0, 1, 2]);
+        assert_eq!(
+            checker.respond(1, 0, 5, verify_statement(forged.prove(0))),
+            Err(Error::SelectionMismatch)
+        );
+        assert_eq!(
+            checker.get_status(1),
+            Some(DeviceStatus::Flagged(FlagReason::InvalidProof(0)))
+        );
+        assert_eq!(
+            checker.receive_digest(1, [0; 32]),
+            Err(Error::DeviceFlagged(1))
+        );
+
+        // Devices which don't answer in time are flagged
+        checker.issue_challenge(2, 0).unwrap();
+        assert_eq!(
+            checker.respond(2, 3, 5, verify_statement(honest.prove(0))),
+            Err(Error::NotChallenged(3))
+        );
+        assert!(checker.expire(10).is_empty());
+        assert_eq!(checker.expire(11), vec![2]);
+        assert_eq!(
+            checker.get_status(2),
+            Some(DeviceStatus::Flagged(FlagReason::MissedDeadline(0)))
+        );
+        assert_eq!(SpotChecker::new(0, 10).err(), Some(Error::InvalidCheckRate));
+    }
+}
@@ -0,0 +1,342 @@
+//! Reproducible toy datasets and trained-weight sets with known outputs for inference proofs
+
+use rand::Rng;
+
+use crate::{Error, LabeledExample, LinearModel, ReproducibleSeed, TreeNode};
+
+// The inference proofs need models and inputs to prove anything about, and tests and benchmarks
+// of different modules should agree on them instead of each inventing their own. This module
+// generates small fixtures without pulling in an ML framework:
+//
+// 1. Every fixture is drawn from its own rng stream of a [`ReproducibleSeed`], labeled by the kind
+// of fixture and numbered in the order fixtures are requested. The same seed and the same
+// sequence of requests always give the same fixtures.
+// 2. Features and weights are fixed-point integers in the ranges the proofs accept, e.g. features
+// of at most `bits` bits for decision trees and non-negative linear models so their outputs can be
+// bucketed.
+// 3. The "trained" weights are random rather than fitted to data, and the labels of a dataset are
+// whatever the generated model predicts. Every output is therefore known exactly from the plain
+// evaluation of the fixture, which is what a proof about the fixture has to reproduce.
+
+// Labels of the rng streams of each kind of fixture
+const FEATURES_LABEL: &[u8] = b"SYNTHETIC_FEATURES";
+const LINEAR_LABEL: &[u8] = b"SYNTHETIC_LINEAR";
+const MLP_LABEL: &[u8] = b"SYNTHETIC_MLP";
+const TREE_LABEL: &[u8] = b"SYNTHETIC_TREE";
+
+/// Linear model with fixed-point weights and bias
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyntheticLinearModel {
+    /// Weight of each feature
+    pub weights: Vec<i64>,
+    /// Bias added to the weighted sum
+    pub bias: i64,
+}
+
+impl SyntheticLinearModel {
+    /// Compute the output `<w, x> + b` in the clear
+    pub fn evaluate(&self, features: &[i64]) -> i64 {
+        self.weights
+            .iter()
+            .zip(features)
+            .fold(self.bias, |sum, (weight, feature)| sum + weight * feature)
+    }
+
+    /// Get the model as a committable [`LinearModel`] with fresh blindings
+    pub fn to_model(&self) -> Result<LinearModel, Error> {
+        LinearModel::new(&self.weights, self.bias)
+    }
+}
+
+/// Fully connected layer of a [`SyntheticMlp`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DenseLayer {
+    /// Weights of each output, one per input
+    pub weights: Vec<Vec<i64>>,
+    /// Bias of each output
+    pub biases: Vec<i64>,
+}
+
+/// Multilayer perceptron with fixed-point weights and ReLU activations between its layers
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyntheticMlp {
+    /// Layers from the input to the output
+    pub layers: Vec<DenseLayer>,
+    /// Fixed-point scale, which every layer's weighted sums are divided by
+    pub scale: i64,
+}
+
+impl SyntheticMlp {
+    /// Compute the outputs of the last layer in the clear. Weighted sums are rounded down after
+    /// dividing by the scale, and every layer but the last applies ReLU.
+    pub fn evaluate(&self, features: &[i64]) -> Vec<i64> {
+        let last = self.layers.len() - 1;
+        self.layers
+            .iter()
+            .enumerate()
+            .fold(features.to_vec(), |inputs, (index, layer)| {
+                layer
+                    .weights
+                    .iter()
+                    .zip(&layer.biases)
+                    .map(|(weights, bias)| {
+                        let sum = weights
+                            .iter()
+                            .zip(&inputs)
+                            .fold(*bias, |sum, (weight, input)| sum + weight * input);
+                        let output = sum.div_euclid(self.scale);
+                        if index == last {
+                            output
+                        } else {
+                            output.max(0)
+                        }
+                    })
+                    .collect()
+            })
+    }
+
+    /// Classify features as the index of the largest output, the first one on ties
+    pub fn classify(&self, features: &[i64]) -> usize {
+        let outputs = self.evaluate(features);
+        (0..outputs.len())
+            .reduce(|best, index| {
+                if outputs[index] > outputs[best] {
+                    index
+                } else {
+                    best
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Generator of reproducible fixtures from a seed
+#[derive(Clone, Debug)]
+pub struct SyntheticGenerator {
+    seed: ReproducibleSeed,
+    // Number of fixtures generated so far, which numbers the rng stream of the next one
+    count: u64,
+}
+
+impl SyntheticGenerator {
+    /// Create a generator from a seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed: ReproducibleSeed::new(&seed.to_le_bytes()),
+            count: 0,
+        }
+    }
+
+    // Get the rng stream of the next fixture of a kind
+    fn next_stream(&mut self, label: &'static [u8]) -> rand::rngs::StdRng {
+        self.count += 1;
+        self.seed.stream(label, self.count)
+    }
+
+    /// Generate `num_examples` feature vectors of `num_features` features within [0, 2^bits)
+    pub fn features(
+        &mut self,
+        num_examples: usize,
+        num_features: usize,
+        bits: usize,
+    ) -> Vec<Vec<i64>> {
+        let mut rng = self.next_stream(FEATURES_LABEL);
+        (0..num_examples)
+            .map(|_| {
+                (0..num_features)
+                    .map(|_| rng.gen_range(0..1i64 << bits))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Generate a linear model with weights and bias within [0, 2^weight_bits), so its outputs on
+    /// features from [`SyntheticGenerator::features`] are never negative
+    pub fn linear_model(
+        &mut self,
+        num_features: usize,
+        weight_bits: usize,
+    ) -> SyntheticLinearModel {
+        let mut rng = self.next_stream(LINEAR_LABEL);
+        SyntheticLinearModel {
+            weights: (0..num_features)
+                .map(|_| rng.gen_range(0..1i64 << weight_bits))
+                .collect(),
+            bias: rng.gen_range(0..1i64 << weight_bits),
+        }
+    }
+
+    /// Generate examples with features within [0, 2^bits) labeled 1 when the model's output is at
+    /// least `threshold` and 0 otherwise
+    pub fn labeled_examples(
+        &mut self,
+        model: &SyntheticLinearModel,
+        num_examples: usize,
+        bits: usize,
+        threshold: i64,
+    ) -> Vec<LabeledExample> {
+        self.features(num_examples, model.weights.len(), bits)
+            .iter()
+            .map(|features| {
+                let label = (model.evaluate(features) >= threshold) as usize;
+                LabeledExample::new(features, label).expect("labels are 0 or 1")
+            })
+            .collect()
+    }
+
+    /// Generate a multilayer perceptron with the given layer sizes, starting with the number of
+    /// features, and weights and biases within (-2^weight_bits, 2^weight_bits)
+    pub fn mlp(&mut self, layer_sizes: &[usize], weight_bits: usize, scale: i64) -> SyntheticMlp {
+        let mut rng = self.next_stream(MLP_LABEL);
+        let bound = 1i64 << weight_bits;
+        let layers = layer_sizes
+            .windows(2)
+            .map(|sizes| DenseLayer {
+                weights: (0..sizes[1])
+                    .map(|_| {
+                        (0..sizes[0])
+                            .map(|_| rng.gen_range(1 - bound..bound))
+                            .collect()
+                    })
+                    .collect(),
+                biases: (0..sizes[1])
+                    .map(|_| rng.gen_range(1 - bound..bound))
+                    .collect(),
+            })
+            .collect();
+        SyntheticMlp { layers, scale }
+    }
+
+    /// Generate the nodes of a complete decision tree of the given depth comparing features within
+    /// [0, 2^bits), with leaves labeled within [0, num_labels)
+    pub fn decision_tree(
+        &mut self,
+        depth: usize,
+        num_features: usize,
+        bits: usize,
+        num_labels: u64,
+    ) -> Vec<TreeNode> {
+        let mut rng = self.next_stream(TREE_LABEL);
+        let num_splits = (1 << depth) - 1;
+        (0..2 * num_splits + 1)
+            .map(|index| match index < num_splits {
+                true => TreeNode::Split {
+                    feature: rng.gen_range(0..num_features),
+                    threshold: rng.gen_range(0..1u64 << bits),
+                    left: 2 * index + 1,
+                    right: 2 * index + 2,
+                },
+                false => TreeNode::Leaf {
+                    label: rng.gen_range(0..num_labels),
+                },
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BucketSchema, DecisionTree, DecisionTreeProver, DecisionTreeVerifier, FeatureVector,
+        LinearInferenceGenerators, LinearInferenceProof, PedersenGenerators, TreeInput,
+    };
+    use merlin::Transcript;
+
+    #[test]
+    fn test_fixtures_are_reproducible_from_the_seed() {
+        let generate = |seed| {
+            let mut generator = SyntheticGenerator::new(seed);
+            (
+                generator.features(4, 3, 8),
+                generator.linear_model(3, 6),
+                generator.mlp(&[3, 4, 2], 5, 16),
+                generator.decision_tree(2, 3, 8, 4),
+            )
+        };
+        assert_eq!(generate(7), generate(7));
+        assert_ne!(generate(7), generate(8));
+
+        // Known outputs follow from the plain evaluation
+        let model = SyntheticLinearModel {
+            weights: vec![2, 3],
+            bias: 1,
+        };
+        assert_eq!(model.evaluate(&[4, 5]), 24);
+        let mlp = SyntheticMlp {
+            layers: vec![
+                DenseLayer {
+                    weights: vec![vec![4, -4], vec![-8, 0]],
+                    biases: vec![0, 2],
+                },
+                DenseLayer {
+                    weights: vec![vec![2, 0], vec![0, 2]],
+                    biases: vec![-1, 0],
+                },
+            ],
+            scale: 4,
+        };
+        // Hidden layer: relu(floor(4/4)) = 1 and relu(floor(-14/4)) = 0
+        assert_eq!(mlp.evaluate(&[2, 1]), vec![0, 0]);
+        assert_eq!(mlp.evaluate(&[4, 1]), vec![1, 0]);
+        assert_eq!(mlp.classify(&[4, 1]), 0);
+    }
+
+    #[test]
+    fn test_fixtures_prove_their_known_outputs_end_to_end() {
+        let mut generator = SyntheticGenerator::new(42);
+
+        // Linear inference on a generated model lands in the bucket of the known output
+        let model = generator.linear_model(4, 6);
+        let schema = BucketSchema::new(1, &[0, 4000, 1 << 20]).unwrap();
+        let generators = LinearInferenceGenerators::new(4);
+        let committed_model = model.to_model().unwrap();
+        for features in generator.features(3, 4, 6) {
+            let expected = (model.evaluate(&features) >= 4000) as usize;
+            let features = FeatureVector::new(&features);
+            let proof = LinearInferenceProof::generate_proof(
+                &committed_model,
+                &features,
+                &schema,
+                &generators,
+                &mut Transcript::new(b"test"),
+            )
+            .unwrap();
+            assert_eq!(
+                proof.verify_proof(
+                    &features.commitment(&generators).unwrap(),
+                    &committed_model.commitment(&generators).unwrap(),
+                    &schema,
+                    &generators,
+                    &mut Transcript::new(b"test"),
+                ),
+                Ok(expected)
+            );
+        }
+
+        // Generated trees are accepted by the decision tree proofs
+        let tree = DecisionTree::new(&generator.decision_tree(2, 3, 6, 4), 6).unwrap();
+        let input: Vec<u64> = generator.features(1, 3, 6)[0]
+            .iter()
+            .map(|feature| *feature as u64)
+            .collect();
+        let generators = PedersenGenerators::default();
+        let input = TreeInput::new(&input);
+        let (proof, _) = DecisionTreeProver::new(generators)
+            .prove(&tree, &input)
+            .unwrap();
+        assert!(DecisionTreeVerifier::new(generators)
+            .verify(
+                &tree.commitment(&generators),
+                &input.commitments(&generators),
+                &proof
+            )
+            .is_ok());
+
+        // Labels of generated examples are the model's predictions
+        for example in generator.labeled_examples(&model, 8, 6, 4000) {
+            let output = model.evaluate(example.get_features());
+            assert_eq!(example.get_label(), (output >= 4000) as usize);
+        }
+    }
+}
@@ -0,0 +1,606 @@
+//! Threshold Schnorr proofs where any t of n parties holding key shares jointly prove knowledge
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use encodings::SecretScalar;
+use merlin::Transcript;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{
+    escrow_key, key_escrow::lagrange_coefficient, merlin_non_interactive_proof::nonce_hash,
+    ChallengeOracle, Error, EscrowCommitment, KeyShare, SchnorrProof,
+};
+
+// A counterparty's proving key `x` is held by n parties such that any t of them can produce a
+// `SchnorrProof` for the public key `X = x*G`, while fewer than t can't, and the key is never
+// assembled in one place. The proof is an ordinary hardened Schnorr proof, so verifiers don't
+// need to know it was produced by a threshold of parties.
+//
+// Distributed key generation (Pedersen's DKG over Feldman VSS) creates the shares without a
+// trusted dealer:
+// 1. Every party i escrows a random secret s_i among all n parties, which gives it a Feldman
+// commitment C_i to a polynomial f_i with f_i(0) = s_i and the shares f_i(j) for every party j.
+// 2. It broadcasts C_i along with a Schnorr proof of knowledge of s_i bound to its index and C_i.
+// Without the proof, the last party to broadcast could pick its commitment to cancel out the
+// others' and control the group key.
+// 3. It sends f_i(j) privately to party j, who checks it against C_i.
+// 4. Every party j sums the shares it received into its key share x_j = f_1(j) + .. + f_n(j) and
+// the commitments into the commitment to f = f_1 + .. + f_n. The group key is X = f(0)*G and the
+// public key of every share X_j = x_j*G can be computed from the commitment.
+//
+// Signing by a set S of at least t parties follows the aggregated proofs of
+// `merlin_non_interactive_proof`, with Lagrange coefficients in place of the key aggregation
+// coefficients:
+// 1. Every signer i picks a nonce r_i, sends a hash of R_i = r_i*G and reveals R_i once all hashes
+// were received.
+// 2. Every signer derives the challenge c of a hardened Schnorr proof for X with the nonce
+// commitment R = sum R_i and answers with z_i = r_i + c*l_i*x_i, where l_i is its Lagrange
+// coefficient for interpolating f(0) from the indices in S.
+// 3. The combiner checks z_i*G == R_i + c*l_i*X_i for every signer, which identifies a signer
+// sending a bad response, and sums them into the proof (R, z = sum z_i) since
+// sum l_i*x_i = f(0) = x.
+
+// Domain separator for initializing DKG proofs of knowledge of the dealt secret
+const DKG_PROOF_DOMAIN_SEP: &[u8] = b"THRESHOLD_DKG_PROOF";
+
+// Generator the key shares and their commitments are multiplied by
+const G: RistrettoPoint = RISTRETTO_BASEPOINT_POINT;
+
+// Create the transcript a dealer proves knowledge of its secret with
+fn dkg_transcript(dealer: u64, commitment: &EscrowCommitment) -> Transcript {
+    let mut transcript = Transcript::new(DKG_PROOF_DOMAIN_SEP);
+    transcript.append_u64(b"DEALER", dealer);
+    for coefficient_commitment in commitment.coefficient_commitments.iter() {
+        transcript.absorb_prover_message(coefficient_commitment);
+    }
+    transcript
+}
+
+/// Message a party broadcasts to every other party during distributed key generation
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DkgBroadcast {
+    // Index of the party which dealt the secret
+    dealer: u64,
+    // Feldman commitment to the polynomial the secret was shared with
+    commitment: EscrowCommitment,
+    // Proof of knowledge of the dealt secret
+    proof: SchnorrProof,
+}
+
+impl DkgBroadcast {
+    /// Get the index of the party which sent the broadcast
+    pub fn get_dealer(&self) -> u64 {
+        self.dealer
+    }
+
+    /// Get the commitment to the polynomial the dealer shared its secret with
+    pub fn get_commitment(&self) -> &EscrowCommitment {
+        &self.commitment
+    }
+
+    /// Check the broadcast proves knowledge of a secret shared with the given threshold
+    pub fn verify(&self, threshold: usize) -> Result<(), Error> {
+        if self.commitment.get_threshold() != threshold
+            || self
+                .proof
+                .verify_proof(
+                    &self.commitment.get_public_key(),
+                    &mut dkg_transcript(self.dealer, &self.commitment),
+                )
+                .is_err()
+        {
+            return Err(Error::InvalidDkgBroadcast(self.dealer));
+        }
+        Ok(())
+    }
+}
+
+/// State of one party during distributed key generation, whose dealt shares are overwritten with
+/// zeros when it is dropped
+#[derive(Debug)]
+pub struct DkgParticipant {
+    index: u64,
+    threshold: usize,
+    // Shares of the party's own secret, one for every party in index order
+    shares: Vec<KeyShare>,
+}
+
+impl DkgParticipant {
+    /// Join a key generation among `num_parties` parties indexed from 1, any `threshold` of which
+    /// can produce proofs with the group key, returning the party state and its broadcast
+    pub fn new(
+        index: u64,
+        threshold: usize,
+        num_parties: usize,
+    ) -> Result<(Self, DkgBroadcast), Error> {
+        if index == 0 || index > num_parties as u64 {
+            return Err(Error::UnknownParty(index));
+        }
+        let secret = SecretScalar::new(Scalar::random(&mut rand::rngs::OsRng));
+        let (commitment, shares) = escrow_key(secret.get_scalar(), threshold, num_parties)?;
+        let proof = SchnorrProof::generate_proof(
+            secret.get_scalar(),
+            &mut dkg_transcript(index, &commitment),
+        );
+        let broadcast = DkgBroadcast {
+            dealer: index,
+            commitment,
+            proof,
+        };
+        Ok((
+            Self {
+                index,
+                threshold,
+                shares,
+            },
+            broadcast,
+        ))
+    }
+
+    /// Get the share of this party's secret to send privately to the party at `recipient`
+    pub fn share_for(&self, recipient: u64) -> Result<KeyShare, Error> {
+        self.shares
+            .iter()
+            .find(|share| share.index == recipient)
+            .cloned()
+            .ok_or(Error::UnknownParty(recipient))
+    }
+
+    /// Check the broadcasts of all parties and the shares they sent to this party, both given in
+    /// the order of the dealers' indices, and combine them into this party's key share and the
+    /// group's public key
+    pub fn finish(
+        self,
+        broadcasts: &[DkgBroadcast],
+        shares: &[KeyShare],
+    ) -> Result<(ThresholdKeyShare, ThresholdPublicKey), Error> {
+        if broadcasts.len() != self.shares.len() || shares.len() != broadcasts.len() {
+            return Err(Error::InsufficientShares(
+                broadcasts.len().min(shares.len()),
+                self.shares.len(),
+            ));
+        }
+        let mut share = SecretScalar::new(Scalar::ZERO);
+        let mut coefficient_commitments = vec![RistrettoPoint::default(); self.threshold];
+        for (dealer, (broadcast, dealt_share)) in (1..).zip(broadcasts.iter().zip(shares)) {
+            if broadcast.dealer != dealer {
+                return Err(Error::UnknownParty(broadcast.dealer));
+            }
+            broadcast.verify(self.threshold)?;
+            if dealt_share.index != self.index
+                || broadcast.commitment.verify_share(dealt_share).is_err()
+            {
+                return Err(Error::InvalidDealerShare(dealer));
+            }
+            share = SecretScalar::new(share.get_scalar() + dealt_share.share.get_scalar());
+            for (sum, commitment) in coefficient_commitments
+                .iter_mut()
+                .zip(broadcast.commitment.coefficient_commitments.iter())
+            {
+                *sum += commitment;
+            }
+        }
+
+        let key_share = ThresholdKeyShare {
+            index: self.index,
+            share,
+        };
+        let public_key = ThresholdPublicKey {
+            commitment: EscrowCommitment {
+                coefficient_commitments,
+            },
+            num_parties: self.shares.len(),
+        };
+        Ok((key_share, public_key))
+    }
+}
+
+impl Zeroize for DkgParticipant {
+    fn zeroize(&mut self) {
+        for share in self.shares.iter_mut() {
+            share.share.zeroize();
+        }
+    }
+}
+
+impl Drop for DkgParticipant {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for DkgParticipant {}
+
+/// Share of a group's proving key held by one party, overwritten with zeros when it is dropped.
+/// The share is never printed and can't be cloned, so it isn't copied outside the signers.
+#[derive(Debug)]
+pub struct ThresholdKeyShare {
+    index: u64,
+    share: SecretScalar<Scalar>,
+}
+
+impl ThresholdKeyShare {
+    /// Get the index of the party holding the share
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+}
+
+impl Zeroize for ThresholdKeyShare {
+    fn zeroize(&mut self) {
+        self.share.zeroize();
+    }
+}
+
+// The share is a `SecretScalar`, which zeroizes itself when the key share is dropped
+impl ZeroizeOnDrop for ThresholdKeyShare {}
+
+/// Public key of a group of parties together with the public keys of their shares
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ThresholdPublicKey {
+    // Feldman commitment to the polynomial the group key is shared with
+    commitment: EscrowCommitment,
+    num_parties: usize,
+}
+
+impl ThresholdPublicKey {
+    /// Get the group key the joint proofs verify against
+    pub fn get_public_key(&self) -> RistrettoPoint {
+        self.commitment.get_public_key()
+    }
+
+    /// Get the number of parties needed to produce a proof
+    pub fn get_threshold(&self) -> usize {
+        self.commitment.get_threshold()
+    }
+
+    /// Get the number of parties holding a share of the key
+    pub fn get_num_parties(&self) -> usize {
+        self.num_parties
+    }
+
+    /// Get the public key of the share held by the party at `index`
+    pub fn get_verification_share(&self, index: u64) -> Result<RistrettoPoint, Error> {
+        if index == 0 || index > self.num_parties as u64 {
+            return Err(Error::UnknownParty(index));
+        }
+        Ok(self.commitment.evaluate(index))
+    }
+
+    // Check the signers are at least a threshold of distinct parties of the group
+    fn check_signers(&self, signers: &[u64]) -> Result<(), Error> {
+        for (i, signer) in signers.iter().enumerate() {
+            self.get_verification_share(*signer)?;
+            if signers[..i].contains(signer) {
+                return Err(Error::RepeatedShare(*signer));
+            }
+        }
+        if signers.len() < self.get_threshold() {
+            return Err(Error::InsufficientShares(
+                signers.len(),
+                self.get_threshold(),
+            ));
+        }
+        Ok(())
+    }
+
+    // Derive the challenge of a hardened Schnorr proof for the group key with the joint nonce
+    // commitment, which the response doesn't enter
+    fn challenge<O: ChallengeOracle>(
+        &self,
+        nonce_commitment: &RistrettoPoint,
+        proof_transcript: &mut O,
+    ) -> Scalar {
+        SchnorrProof::from((Scalar::ZERO, *nonce_commitment))
+            .derive_challenge(&self.get_public_key(), proof_transcript)
+    }
+
+    /// Check the partial responses of the signers, given in the order of `signers`, and combine
+    /// them into a Schnorr proof for the group key. The transcript must be initialized the same
+    /// way as the ones the signers responded with.
+    pub fn combine<O: ChallengeOracle>(
+        &self,
+        signers: &[u64],
+        responses: &[PartialThresholdResponse],
+        proof_transcript: &mut O,
+    ) -> Result<SchnorrProof, Error> {
+        self.check_signers(signers)?;
+        if responses.len() != signers.len()
+            || responses
+                .iter()
+                .zip(signers)
+                .any(|(response, signer)| response.index != *signer)
+        {
+            return Err(Error::CoproverMismatch);
+        }
+        let nonce_commitment: RistrettoPoint = responses
+            .iter()
+            .map(|response| response.nonce_commitment)
+            .sum();
+        let challenge = self.challenge(&nonce_commitment, proof_transcript);
+        for response in responses {
+            let verification_share = self.get_verification_share(response.index)?;
+            let coefficient = lagrange_coefficient(response.index, signers);
+            if response.response * G
+                != response.nonce_commitment + challenge * coefficient * verification_share
+            {
+                return Err(Error::InvalidPartialResponse(response.index));
+            }
+        }
+        let response = responses.iter().map(|response| response.response).sum();
+        Ok(SchnorrProof::from((response, nonce_commitment)))
+    }
+}
+
+/// Response of one signer to the joint challenge of a threshold proof
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PartialThresholdResponse {
+    // Index of the party which responded
+    index: u64,
+    // R_i
+    nonce_commitment: RistrettoPoint,
+    // r_i + c*l_i*x_i
+    response: Scalar,
+}
+
+impl PartialThresholdResponse {
+    /// Get the index of the party which responded
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+}
+
+/// State of one signer in a threshold proof, whose key share and nonce are overwritten with zeros
+/// when it is dropped. The signer can't be cloned, since answering two challenges with the same
+/// nonce reveals the key share.
+#[derive(Debug)]
+pub struct ThresholdSigner {
+    index: u64,
+    share: SecretScalar<Scalar>,
+    public_key: ThresholdPublicKey,
+    // Indices of all signers taking part in the proof
+    signers: Vec<u64>,
+    nonce: SecretScalar<Scalar>,
+    nonce_commitment: RistrettoPoint,
+}
+
+impl ThresholdSigner {
+    /// Start a threshold proof together with the parties at `signers`, returning the signer state
+    /// and the hash to send to the other signers
+    pub fn new(
+        key_share: &ThresholdKeyShare,
+        public_key: &ThresholdPublicKey,
+        signers: &[u64],
+    ) -> Result<(Self, [u8; 32]), Error> {
+        public_key.check_signers(signers)?;
+        if !signers.contains(&key_share.index) {
+            return Err(Error::UnknownParty(key_share.index));
+        }
+        let nonce = SecretScalar::new(Scalar::random(&mut rand::rngs::OsRng));
+        let signer = Self {
+            index: key_share.index,
+            share: SecretScalar::new(*key_share.share.get_scalar()),
+            public_key: public_key.clone(),
+            signers: signers.to_vec(),
+            nonce_commitment: nonce.get_scalar() * G,
+            nonce,
+        };
+        let hash = nonce_hash(&signer.nonce_commitment);
+        Ok((signer, hash))
+    }
+
+    /// Get the nonce commitment to reveal once every other signer's hash was received
+    pub fn reveal(&self) -> RistrettoPoint {
+        self.nonce_commitment
+    }
+
+    /// Check every signer's revealed nonce commitment against its hash, both given in the order of
+    /// the signers, and answer the joint challenge derived with the transcript
+    pub fn respond<O: ChallengeOracle>(
+        self,
+        hashes: &[[u8; 32]],
+        nonce_commitments: &[RistrettoPoint],
+        proof_transcript: &mut O,
+    ) -> Result<PartialThresholdResponse, Error> {
+        let position = self
+            .signers
+            .iter()
+            .position(|signer| *signer == self.index)
+            .expect("signer is part of the signers");
+        if hashes.len() != self.signers.len()
+            || nonce_commitments.len() != hashes.len()
+            || nonce_commitments[position] != self.nonce_commitment
+            || nonce_commitments
+                .iter()
+                .zip(hashes)
+                .any(|(nonce_commitment, hash)| nonce_hash(nonce_commitment) != *hash)
+        {
+            return Err(Error::CoproverMismatch);
+        }
+        let nonce_commitment: RistrettoPoint = nonce_commitments.iter().sum();
+        let challenge = self
+            .public_key
+            .challenge(&nonce_commitment, proof_transcript);
+        let coefficient = lagrange_coefficient(self.index, &self.signers);
+        Ok(PartialThresholdResponse {
+            index: self.index,
+            nonce_commitment: self.nonce_commitment,
+            response: self.nonce.get_scalar() + challenge * coefficient * self.share.get_scalar(),
+        })
+    }
+}
+
+impl Zeroize for ThresholdSigner {
+    fn zeroize(&mut self) {
+        self.share.zeroize();
+        self.nonce.zeroize();
+    }
+}
+
+// Both secrets are held in `SecretScalar`s, which zeroize themselves when the signer is dropped
+impl ZeroizeOnDrop for ThresholdSigner {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run a key generation between all parties
+    fn generate_keys(
+        threshold: usize,
+        num_parties: usize,
+    ) -> Vec<(ThresholdKeyShare, ThresholdPublicKey)> {
+        let (participants, broadcasts): (Vec<_>, Vec<_>) = (1..=num_parties as u64)
+            .map(|index| DkgParticipant::new(index, threshold, num_parties).unwrap())
+            .unzip();
+        let dealt: Vec<Vec<KeyShare>> = participants
+            .iter()
+            .map(|dealer| {
+                (1..=num_parties as u64)
+                    .map(|recipient| dealer.share_for(recipient).unwrap())
+                    .collect()
+            })
+            .collect();
+        participants
+            .into_iter()
+            .enumerate()
+            .map(|(i, participant)| {
+                let shares: Vec<KeyShare> = dealt.iter().map(|shares| shares[i].clone()).collect();
+                participant.finish(&broadcasts, &shares).unwrap()
+            })
+            .collect()
+    }
+
+    // Run the threshold proof protocol between the parties at `signers`
+    fn threshold_proof(
+        keys: &[(ThresholdKeyShare, ThresholdPublicKey)],
+        signers: &[u64],
+    ) -> Result<SchnorrProof, Error> {
+        let public_key = &keys[0].1;
+        let (states, hashes): (Vec<_>, Vec<_>) = signers
+            .iter()
+            .map(|signer| ThresholdSigner::new(&keys[*signer as usize - 1].0, public_key, signers))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        let nonce_commitments: Vec<_> = states.iter().map(ThresholdSigner::reveal).collect();
+        let responses = states
+            .into_iter()
+            .map(|state| {
+                state.respond(
+                    &hashes,
+                    &nonce_commitments,
+                    &mut SchnorrProof::create_new_transcript(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        public_key.combine(
+            signers,
+            &responses,
+            &mut SchnorrProof::create_new_transcript(),
+        )
+    }
+
+    #[test]
+    fn test_any_threshold_of_parties_proves_knowledge_of_the_group_key() {
+        let keys = generate_keys(3, 5);
+        let public_key = &keys[0].1;
+        assert!(keys.iter().all(|(_, key)| key == public_key));
+        for (key_share, _) in keys.iter() {
+            assert_eq!(
+                public_key
+                    .get_verification_share(key_share.get_index())
+                    .unwrap(),
+                key_share.share.get_scalar() * G
+            );
+        }
+
+        // Neither the key share nor a signer holding it prints the share
+        let (signer, _) = ThresholdSigner::new(&keys[0].0, public_key, &[1, 2, 3]).unwrap();
+        let share = format!("{:?}", keys[0].0.share.get_scalar());
+        for debug in [format!("{:?}", keys[0].0), format!("{signer:?}")] {
+            assert!(debug.contains("SecretScalar(<redacted>)") && !debug.contains(&share));
+        }
+
+        // The joint proof is an ordinary Schnorr proof for the group key
+        for signers in [vec![1, 2, 3], vec![5, 3, 1], vec![2, 3, 4, 5]] {
+            let proof = threshold_proof(&keys, &signers).unwrap();
+            assert!(proof
+                .verify_proof(
+                    &public_key.get_public_key(),
+                    &mut SchnorrProof::create_new_transcript()
+                )
+                .is_ok());
+        }
+        assert_eq!(
+            threshold_proof(&keys, &[1, 2]).err(),
+            Some(Error::InsufficientShares(2, 3))
+        );
+        assert_eq!(
+            threshold_proof(&keys, &[1, 2, 2]).err(),
+            Some(Error::RepeatedShare(2))
+        );
+        assert_eq!(
+            threshold_proof(&keys, &[1, 2, 6]).err(),
+            Some(Error::UnknownParty(6))
+        );
+    }
+
+    #[test]
+    fn test_cheating_dealers_and_signers_are_identified() {
+        let (first, first_broadcast) = DkgParticipant::new(1, 2, 2).unwrap();
+        let (second, second_broadcast) = DkgParticipant::new(2, 2, 2).unwrap();
+        let broadcasts = [first_broadcast, second_broadcast];
+
+        // A share that doesn't match the dealer's commitment, or a broadcast whose proof was made
+        // for another commitment, names the dealer
+        let mut tampered = second.share_for(1).unwrap();
+        tampered.share = SecretScalar::new(tampered.share.get_scalar() + Scalar::ONE);
+        let shares = [first.share_for(1).unwrap(), tampered];
+        let (_, other_broadcast) = DkgParticipant::new(2, 2, 2).unwrap();
+        let forged = DkgBroadcast {
+            proof: other_broadcast.proof,
+            ..broadcasts[1].clone()
+        };
+        assert_eq!(forged.verify(2), Err(Error::InvalidDkgBroadcast(2)));
+        let (other, _) = DkgParticipant::new(1, 2, 2).unwrap();
+        assert_eq!(
+            other.finish(&broadcasts, &shares).err(),
+            Some(Error::InvalidDealerShare(2))
+        );
+
+        // A signer answering with a wrong response is caught when combining
+        let keys = generate_keys(2, 3);
+        let public_key = &keys[0].1;
+        let signers = [1, 3];
+        let (first, first_hash) = ThresholdSigner::new(&keys[0].0, public_key, &signers).unwrap();
+        let (third, third_hash) = ThresholdSigner::new(&keys[2].0, public_key, &signers).unwrap();
+        let hashes = [first_hash, third_hash];
+        let reveals = [first.reveal(), third.reveal()];
+        let honest = first
+            .respond(
+                &hashes,
+                &reveals,
+                &mut SchnorrProof::create_new_transcript(),
+            )
+            .unwrap();
+        let mut cheating = third
+            .respond(
+                &hashes,
+                &reveals,
+                &mut SchnorrProof::create_new_transcript(),
+            )
+            .unwrap();
+        cheating.response += Scalar::ONE;
+        assert_eq!(
+            public_key
+                .combine(
+                    &signers,
+                    &[honest, cheating],
+                    &mut SchnorrProof::create_new_transcript()
+                )
+                .err(),
+            Some(Error::InvalidPartialResponse(3))
+        );
+    }
+}
@@ -0,0 +1,508 @@
+//! Translation of range statements about Ristretto commitments into statements about BLS12-381
+//! commitments, linked by a cross-curve proof
+
+use bls12_381::G1Projective;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{
+    Bls12G1, CommitmentGroup, PedersenCommitment, PointEncoding, ProofScheme, SecurityLevel,
+};
+use merlin::Transcript;
+use rand::{rngs::OsRng, RngCore};
+
+use crate::{Error, PedersenGenerators, SimpleProofProtocol};
+
+// Commitments made for one proof backend can't be consumed by a backend working over another
+// curve: a range statement about a Ristretto commitment means nothing to a SNARK over BLS12-381.
+// Rather than asking every device to commit again, which would invalidate the commitments already
+// on record, a statement is translated by committing to the same value on the other curve and
+// proving both commitments open to it:
+//
+// 1. The Prover writes the value in binary and commits to each bit on both curves, as
+// `A_i = b_i*G + s_i*H` on Ristretto and `B_i = b_i*G' + t_i*H'` on BLS12-381 G1. The bit
+// blinding factors recompose to the blinding factor of the commitment on each curve.
+// 2. The Verifier checks that the bit commitments recompose to the original commitment on
+// Ristretto and to the new commitment on BLS12-381, just like a [`BitDecomposition`].
+// 3. For every bit the Prover shows that either `A_i = s_i*H` and `B_i = t_i*H'` (both bits are
+// 0) OR `A_i - G = s_i*H` and `B_i - G' = t_i*H'` (both bits are 1) with an OR proof whose
+// branches are Schnorr proofs on both curves.
+//
+// The group orders of the two curves differ, so a challenge can't be a scalar of either field.
+// Challenges are 128 bit integers instead, which are smaller than both group orders and therefore
+// mean the same in both fields, and the two branch challenges are split by XOR rather than by
+// addition. Every branch has its own responses on each curve, so no response has to be
+// meaningful in both fields. Since all bits are 0 or 1 and there are at most 64 of them, the
+// recomposed value can't wrap around either group order, so both commitments open to the same
+// integer within [0, 2^n).
+//
+// The translated statement carries the original commitment, the new commitment and the linking
+// proof. Historical range proofs keep verifying against the original commitment, while the BLS
+// backend takes the new one.
+//
+// [`BitDecomposition`]: crate::BitDecomposition
+
+// Maximum number of bits a translated range statement can cover
+const MAX_BITS: usize = 64;
+
+// Domain separator for binding the number of bits into the transcript
+const TRANSLATION_BITS_DOMAIN_SEP: &[u8] = b"TRANSLATION_BITS";
+
+// Domain separator for absorbing BLS12-381 points into the transcript
+const BLS_POINT_DOMAIN_SEP: &[u8] = b"BLS12_381_G1_POINT";
+
+// Domain separator for the challenges of the linking proof
+const LINK_CHALLENGE_DOMAIN_SEP: &[u8] = b"LINK_CHALLENGE";
+
+/// Proof that a bit is committed to on both curves and is 0 or 1
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct LinkedBitProof {
+    // Commitment `A_i` to the bit on Ristretto
+    ristretto_commitment: RistrettoPoint,
+    // Commitment `B_i` to the bit on BLS12-381 G1
+    bls_commitment: G1Projective,
+    // Challenges for the "bit is 0" and "bit is 1" branches, which XOR to the challenge
+    challenges: [u128; 2],
+    // Responses of both branches on Ristretto
+    ristretto_responses: [Scalar; 2],
+    // Responses of both branches on BLS12-381 G1
+    bls_responses: [bls12_381::Scalar; 2],
+}
+
+impl LinkedBitProof {
+    // Prove that both bit commitments open to the same bit, which is 0 or 1
+    fn generate_proof(
+        bit: usize,
+        blindings: (&Scalar, &bls12_381::Scalar),
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Self {
+        let mut rng = OsRng;
+        let ristretto_commitment = generators.commit(&Scalar::from(bit as u64), blindings.0);
+        let bls_commitment = PedersenCommitment::<Bls12G1>::commit(
+            &bls12_381::Scalar::from(bit as u64),
+            blindings.1,
+        )
+        .get_point();
+        let (ristretto_statements, bls_statements) =
+            branch_statements(&ristretto_commitment, &bls_commitment, generators);
+        let simulated = 1 - bit;
+
+        // Simulate the false branch by choosing its challenge and responses up front
+        let mut challenges = [0; 2];
+        let mut ristretto_responses = [Scalar::ZERO; 2];
+        let mut bls_responses = [bls12_381::Scalar::zero(); 2];
+        challenges[simulated] = random_challenge(&mut rng);
+        ristretto_responses[simulated] = Scalar::random(&mut rng);
+        bls_responses[simulated] = random_bls_scalar(&mut rng);
+        let mut ristretto_nonces = [RistrettoPoint::default(); 2];
+        let mut bls_nonces = [G1Projective::identity(); 2];
+        ristretto_nonces[simulated] = ristretto_responses[simulated]
+            * generators.blinding_generator
+            - Scalar::from(challenges[simulated]) * ristretto_statements[simulated];
+        bls_nonces[simulated] = Bls12G1::blinding_generator() * bls_responses[simulated]
+            - bls_statements[simulated] * bls_challenge(challenges[simulated]);
+
+        // Commit honestly to the true branch on both curves
+        let ristretto_nonce = Scalar::random(&mut rng);
+        let bls_nonce = random_bls_scalar(&mut rng);
+        ristretto_nonces[bit] = ristretto_nonce * generators.blinding_generator;
+        bls_nonces[bit] = Bls12G1::blinding_generator() * bls_nonce;
+
+        let challenge = bit_challenge(
+            proof_transcript,
+            &ristretto_commitment,
+            &bls_commitment,
+            &ristretto_nonces,
+            &bls_nonces,
+        );
+        challenges[bit] = challenge ^ challenges[simulated];
+        ristretto_responses[bit] = ristretto_nonce + Scalar::from(challenges[bit]) * blindings.0;
+        bls_responses[bit] = bls_nonce + bls_challenge(challenges[bit]) * blindings.1;
+
+        Self {
+            ristretto_commitment,
+            bls_commitment,
+            challenges,
+            ristretto_responses,
+            bls_responses,
+        }
+    }
+
+    // Verify that both bit commitments open to the same bit, which is 0 or 1
+    fn verify_proof(
+        &self,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> bool {
+        let (ristretto_statements, bls_statements) =
+            branch_statements(&self.ristretto_commitment, &self.bls_commitment, generators);
+        let mut ristretto_nonces = [RistrettoPoint::default(); 2];
+        let mut bls_nonces = [G1Projective::identity(); 2];
+        for branch in 0..2 {
+            ristretto_nonces[branch] = self.ristretto_responses[branch]
+                * generators.blinding_generator
+                - Scalar::from(self.challenges[branch]) * ristretto_statements[branch];
+            bls_nonces[branch] = Bls12G1::blinding_generator() * self.bls_responses[branch]
+                - bls_statements[branch] * bls_challenge(self.challenges[branch]);
+        }
+        let challenge = bit_challenge(
+            proof_transcript,
+            &self.ristretto_commitment,
+            &self.bls_commitment,
+            &ristretto_nonces,
+            &bls_nonces,
+        );
+        self.challenges[0] ^ self.challenges[1] == challenge
+    }
+}
+
+/// Proof that a Ristretto commitment and a BLS12-381 G1 commitment open to the same value within
+/// [0, 2^n)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CrossCurveLinkProof {
+    // Bit commitments on both curves with their OR proofs, least significant bit first
+    bit_proofs: Vec<LinkedBitProof>,
+}
+
+impl CrossCurveLinkProof {
+    /// Prove that `value*G + ristretto_blinding*H` on Ristretto and `value*G' + bls_blinding*H'` on
+    /// BLS12-381 G1 commit to the same value within [0, 2^bits)
+    pub fn generate_proof(
+        value: u64,
+        ristretto_blinding: &Scalar,
+        bls_blinding: &bls12_381::Scalar,
+        bits: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<Self, Error> {
+        if bits == 0 || bits > MAX_BITS {
+            return Err(Error::InvalidBitLength(bits));
+        }
+        if bits < MAX_BITS && value >> bits != 0 {
+            return Err(Error::ValueOutOfRange(value, bits));
+        }
+        let ristretto_commitment = generators.commit(&Scalar::from(value), ristretto_blinding);
+        let bls_commitment =
+            PedersenCommitment::<Bls12G1>::commit(&bls12_381::Scalar::from(value), bls_blinding);
+        absorb_statement(
+            proof_transcript,
+            &ristretto_commitment,
+            &bls_commitment,
+            bits,
+        );
+
+        // Pick the blinding factors of all bits but the first at random and solve for the first
+        // so that they recompose to the blinding factors of the commitments
+        let mut rng = OsRng;
+        let mut ristretto_blindings = vec![Scalar::ZERO; bits];
+        let mut bls_blindings = vec![bls12_381::Scalar::zero(); bits];
+        let mut ristretto_remainder = *ristretto_blinding;
+        let mut bls_remainder = *bls_blinding;
+        for i in 1..bits {
+            ristretto_blindings[i] = Scalar::random(&mut rng);
+            bls_blindings[i] = random_bls_scalar(&mut rng);
+            ristretto_remainder -= Scalar::from(1u64 << i) * ristretto_blindings[i];
+            bls_remainder -= bls12_381::Scalar::from(1u64 << i) * bls_blindings[i];
+        }
+        ristretto_blindings[0] = ristretto_remainder;
+        bls_blindings[0] = bls_remainder;
+
+        let bit_proofs = (0..bits)
+            .map(|i| {
+                LinkedBitProof::generate_proof(
+                    ((value >> i) & 1) as usize,
+                    (&ristretto_blindings[i], &bls_blindings[i]),
+                    generators,
+                    proof_transcript,
+                )
+            })
+            .collect();
+        Ok(Self { bit_proofs })
+    }
+
+    /// Verify that both commitments open to the same value within [0, 2^bits)
+    pub fn verify_proof(
+        &self,
+        ristretto_commitment: &RistrettoPoint,
+        bls_commitment: &PedersenCommitment<Bls12G1>,
+        bits: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        if bits == 0 || bits > MAX_BITS {
+            return Err(Error::InvalidBitLength(bits));
+        }
+        if self.bit_proofs.len() != bits {
+            return Err(Error::CrossCurveLinkMismatch);
+        }
+        absorb_statement(proof_transcript, ristretto_commitment, bls_commitment, bits);
+
+        // The bit commitments recompose to the commitment on each curve
+        let (ristretto_sum, bls_sum) = self.bit_proofs.iter().enumerate().fold(
+            (RistrettoPoint::default(), G1Projective::identity()),
+            |(ristretto_sum, bls_sum), (i, proof)| {
+                (
+                    ristretto_sum + Scalar::from(1u64 << i) * proof.ristretto_commitment,
+                    bls_sum + proof.bls_commitment * bls12_381::Scalar::from(1u64 << i),
+                )
+            },
+        );
+        if ristretto_sum != *ristretto_commitment || bls_sum != bls_commitment.get_point() {
+            return Err(Error::CrossCurveLinkMismatch);
+        }
+
+        // Every pair of bit commitments opens to the same bit
+        if self
+            .bit_proofs
+            .iter()
+            .all(|proof| proof.verify_proof(generators, proof_transcript))
+        {
+            return Ok(());
+        }
+        Err(Error::CrossCurveLinkMismatch)
+    }
+}
+
+/// Range statement about a Ristretto commitment translated into the same statement about a
+/// BLS12-381 G1 commitment
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TranslatedStatement {
+    // Original commitment the statement was proved about
+    source: RistrettoPoint,
+    // Commitment to the same value on BLS12-381 G1
+    target: PedersenCommitment<Bls12G1>,
+    // Number of bits the committed value fits into
+    bits: usize,
+    // Proof linking the two commitments
+    proof: CrossCurveLinkProof,
+}
+
+impl TranslatedStatement {
+    /// Translate the statement that the commitment `value*G + blinding*H` opens to a value within
+    /// [0, 2^bits), e.g. one proved with a [`RangeProof`], into a statement about a new BLS12-381
+    /// G1 commitment to the value. The blinding factor of the new commitment is returned along
+    /// with the statement so it can be opened by the BLS backend.
+    ///
+    /// [`RangeProof`]: crate::RangeProof
+    pub fn translate(
+        value: u64,
+        blinding: &Scalar,
+        bits: usize,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(Self, bls12_381::Scalar), Error> {
+        let target_blinding = random_bls_scalar(&mut OsRng);
+        let proof = CrossCurveLinkProof::generate_proof(
+            value,
+            blinding,
+            &target_blinding,
+            bits,
+            generators,
+            proof_transcript,
+        )?;
+        let statement = Self {
+            source: generators.commit(&Scalar::from(value), blinding),
+            target: PedersenCommitment::commit(&bls12_381::Scalar::from(value), &target_blinding),
+            bits,
+            proof,
+        };
+        Ok((statement, target_blinding))
+    }
+
+    /// Verify that the target commitment opens to the same value within [0, 2^bits) as the source
+    /// commitment
+    pub fn verify(
+        &self,
+        generators: &PedersenGenerators,
+        proof_transcript: &mut Transcript,
+    ) -> Result<(), Error> {
+        self.proof.verify_proof(
+            &self.source,
+            &self.target,
+            self.bits,
+            generators,
+            proof_transcript,
+        )
+    }
+
+    /// Get the original Ristretto commitment
+    pub fn get_source(&self) -> &RistrettoPoint {
+        &self.source
+    }
+
+    /// Get the BLS12-381 G1 commitment the statement was translated to
+    pub fn get_target(&self) -> &PedersenCommitment<Bls12G1> {
+        &self.target
+    }
+
+    /// Get the number of bits the committed value fits into
+    pub fn get_bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Get the proof linking the source and target commitments
+    pub fn get_proof(&self) -> &CrossCurveLinkProof {
+        &self.proof
+    }
+}
+
+// Statements of the "bit is 0" and "bit is 1" branches on both curves
+fn branch_statements(
+    ristretto_commitment: &RistrettoPoint,
+    bls_commitment: &G1Projective,
+    generators: &PedersenGenerators,
+) -> ([RistrettoPoint; 2], [G1Projective; 2]) {
+    (
+        [
+            *ristretto_commitment,
+            ristretto_commitment - generators.value_generator,
+        ],
+        [*bls_commitment, bls_commitment - Bls12G1::value_generator()],
+    )
+}
+
+// Absorb both commitments and the number of bits
+fn absorb_statement(
+    proof_transcript: &mut Transcript,
+    ristretto_commitment: &RistrettoPoint,
+    bls_commitment: &PedersenCommitment<Bls12G1>,
+    bits: usize,
+) {
+    proof_transcript.append_u64(TRANSLATION_BITS_DOMAIN_SEP, bits as u64);
+    proof_transcript.append_proof_value(ristretto_commitment);
+    proof_transcript.append_message(BLS_POINT_DOMAIN_SEP, &bls_commitment.encode());
+}
+
+// Absorb the bit commitments and the nonce commitments of both branches and derive the 128 bit
+// challenge of a bit
+fn bit_challenge(
+    proof_transcript: &mut Transcript,
+    ristretto_commitment: &RistrettoPoint,
+    bls_commitment: &G1Projective,
+    ristretto_nonces: &[RistrettoPoint; 2],
+    bls_nonces: &[G1Projective; 2],
+) -> u128 {
+    proof_transcript.append_proof_value(ristretto_commitment);
+    proof_transcript.append_message(BLS_POINT_DOMAIN_SEP, &bls_commitment.encode());
+    for branch in 0..2 {
+        proof_transcript.append_proof_value(&ristretto_nonces[branch]);
+        proof_transcript.append_message(BLS_POINT_DOMAIN_SEP, &bls_nonces[branch].encode());
+    }
+    let mut challenge = [0; 16];
+    proof_transcript.challenge_bytes(LINK_CHALLENGE_DOMAIN_SEP, &mut challenge);
+    u128::from_le_bytes(challenge)
+}
+
+// Sample a uniformly random 128 bit challenge
+fn random_challenge<R: RngCore>(rng: &mut R) -> u128 {
+    let mut challenge = [0; 16];
+    rng.fill_bytes(&mut challenge);
+    u128::from_le_bytes(challenge)
+}
+
+// Map a 128 bit challenge into the BLS12-381 scalar field, where it is the same integer
+fn bls_challenge(challenge: u128) -> bls12_381::Scalar {
+    bls12_381::Scalar::from_raw([challenge as u64, (challenge >> 64) as u64, 0, 0])
+}
+
+// Sample a uniformly random BLS12-381 scalar by reducing 64 random bytes
+fn random_bls_scalar<R: RngCore>(rng: &mut R) -> bls12_381::Scalar {
+    let mut bytes = [0; 64];
+    rng.fill_bytes(&mut bytes);
+    bls12_381::Scalar::from_bytes_wide(&bytes)
+}
+
+impl ProofScheme for CrossCurveLinkProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RangeProof;
+
+    #[test]
+    fn test_translated_statement_links_historical_commitment() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut OsRng);
+        let commitment = generators.commit(&Scalar::from(200u64), &blinding);
+
+        // A range proof made for the Ristretto backend before the migration
+        let range_proof = RangeProof::generate_proof(
+            200,
+            &blinding,
+            8,
+            &generators,
+            &mut Transcript::new(b"old"),
+        )
+        .unwrap();
+
+        let (statement, target_blinding) = TranslatedStatement::translate(
+            200,
+            &blinding,
+            8,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+        assert!(statement
+            .verify(&generators, &mut Transcript::new(b"test"))
+            .is_ok());
+
+        // The source is the historical commitment, which the old range proof still verifies
+        // against, and the target opens to the same value on BLS12-381
+        assert_eq!(statement.get_source(), &commitment);
+        assert!(range_proof
+            .verify_proof(&commitment, 8, &generators, &mut Transcript::new(b"old"))
+            .is_ok());
+        assert!(statement
+            .get_target()
+            .verify_opening(&bls12_381::Scalar::from(200u64), &target_blinding));
+        assert_eq!(statement.get_bits(), 8);
+    }
+
+    #[test]
+    fn test_commitments_to_different_values_are_not_linked() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut OsRng);
+        let (statement, target_blinding) = TranslatedStatement::translate(
+            42,
+            &blinding,
+            8,
+            &generators,
+            &mut Transcript::new(b"test"),
+        )
+        .unwrap();
+
+        // The proof doesn't link a target committing to another value or a different range
+        let mut forged = statement.clone();
+        forged.target =
+            PedersenCommitment::commit(&bls12_381::Scalar::from(43u64), &target_blinding);
+        assert_eq!(
+            forged.verify(&generators, &mut Transcript::new(b"test")),
+            Err(Error::CrossCurveLinkMismatch)
+        );
+        assert_eq!(
+            statement.get_proof().verify_proof(
+                statement.get_source(),
+                statement.get_target(),
+                9,
+                &generators,
+                &mut Transcript::new(b"test")
+            ),
+            Err(Error::CrossCurveLinkMismatch)
+        );
+
+        // Values outside the range can't be translated
+        assert_eq!(
+            TranslatedStatement::translate(
+                256,
+                &blinding,
+                8,
+                &generators,
+                &mut Transcript::new(b"test")
+            )
+            .err(),
+            Some(Error::ValueOutOfRange(256, 8))
+        );
+    }
+}
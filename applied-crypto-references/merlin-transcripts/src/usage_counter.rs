@@ -0,0 +1,260 @@
+//! Committed usage counters allowing a device to prove it stays within a licensed number of
+//! model invocations
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{ProofScheme, SecretScalar, SecurityLevel};
+use merlin::Transcript;
+
+use crate::{BlindingProof, Error, PedersenGenerators, RangeProof, SimpleProofProtocol};
+
+// In a pay-per-inference business model, a model owner licenses a device to run a model a maximum
+// number of times. The device shouldn't have to reveal exactly how many inferences it has run,
+// but the model owner needs assurance that each inference is counted and the license isn't
+// exceeded.
+//
+// The device keeps its usage count `n` inside a Pedersen commitment `C_n = n*G + r_n*H`:
+// 1. At provisioning the device publishes `C_0 = r_0*H` along with a proof it knows `r_0`,
+// showing the counter starts at zero.
+// 2. For each inference the device publishes a fresh commitment `C_(n+1)` and proves:
+//    * `C_(n+1) - C_n - G = (r_(n+1) - r_n)*H`, i.e. the counter went up by exactly one
+//    * `max*G - C_(n+1)` commits to a value in [0, 2^k) where 2^k > max, i.e. `n + 1 <= max`
+// 3. The verifier keeps the latest commitment and only accepts proofs which build on it.
+//
+// Because every commitment is re-randomized, the verifier learns that the chain of proofs is
+// valid without learning the count itself from the commitments.
+
+// Domain separator for initializing usage counter transcripts
+const USAGE_COUNTER_DOMAIN_SEP: &[u8] = b"LICENSED_USAGE_COUNTER";
+
+// Domain separator for binding the license maximum into the transcript
+const LICENSE_MAX_DOMAIN_SEP: &[u8] = b"LICENSE_MAX";
+
+// Create a transcript bound to the license and the commitments involved in a counter update
+fn usage_transcript(license_max: u64, commitments: &[RistrettoPoint]) -> Transcript {
+    let mut transcript = Transcript::new(USAGE_COUNTER_DOMAIN_SEP);
+    transcript.append_u64(LICENSE_MAX_DOMAIN_SEP, license_max);
+    for commitment in commitments {
+        transcript.append_proof_value(commitment);
+    }
+    transcript
+}
+
+// Number of bits needed for a range proof covering [0, license_max]
+fn license_bits(license_max: u64) -> usize {
+    (64 - license_max.leading_zeros() as usize).max(1)
+}
+
+/// Proof that a usage counter was incremented by exactly one and remains within its license
+#[derive(Clone, Debug)]
+pub struct UsageProof {
+    // Commitment to the incremented counter
+    commitment: RistrettoPoint,
+    // Proof the new commitment is the previous commitment plus one
+    increment_proof: BlindingProof,
+    // Proof the remaining license allowance is not negative
+    range_proof: RangeProof,
+}
+
+impl UsageProof {
+    /// Get the commitment to the incremented usage counter
+    pub fn get_commitment(&self) -> RistrettoPoint {
+        self.commitment
+    }
+}
+
+/// Device-side usage counter kept inside a Pedersen commitment
+#[derive(Clone, Debug)]
+pub struct UsageCounter {
+    count: u64,
+    blinding: SecretScalar<Scalar>,
+    commitment: RistrettoPoint,
+    license_max: u64,
+    generators: PedersenGenerators,
+}
+
+impl UsageCounter {
+    /// Create a new counter starting at zero for a license allowing `license_max` invocations.
+    /// Returns the counter along with a proof that its initial commitment is to zero, both of
+    /// which are sent to the license verifier.
+    pub fn new(license_max: u64) -> (Self, BlindingProof) {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let commitment = generators.commit(&Scalar::ZERO, &blinding);
+        let zero_proof = BlindingProof::generate_proof(
+            &blinding,
+            &generators,
+            &mut usage_transcript(license_max, &[]),
+        );
+        (
+            Self {
+                count: 0,
+                blinding: SecretScalar::new(blinding),
+                commitment,
+                license_max,
+                generators,
+            },
+            zero_proof,
+        )
+    }
+
+    /// Get the commitment to the current usage count
+    pub fn get_commitment(&self) -> RistrettoPoint {
+        self.commitment
+    }
+
+    /// Get the number of invocations counted so far
+    pub fn get_count(&self) -> u64 {
+        self.count
+    }
+
+    /// Count a model invocation, returning a proof of the increment to send to the verifier
+    pub fn increment(&mut self) -> Result<UsageProof, Error> {
+        if self.count >= self.license_max {
+            return Err(Error::LicenseExhausted(self.license_max));
+        }
+        let count = self.count + 1;
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let commitment = self.generators.commit(&Scalar::from(count), &blinding);
+        let mut transcript = usage_transcript(self.license_max, &[self.commitment, commitment]);
+
+        // C_(n+1) - C_n - G only has a component in H if the count went up by exactly one
+        let increment_proof = BlindingProof::generate_proof(
+            &(blinding - self.blinding.get_scalar()),
+            &self.generators,
+            &mut transcript,
+        );
+
+        // max*G - C_(n+1) commits to the remaining allowance with blinding factor -r_(n+1)
+        let range_proof = RangeProof::generate_proof(
+            self.license_max - count,
+            &-blinding,
+            license_bits(self.license_max),
+            &self.generators,
+            &mut transcript,
+        )?;
+
+        self.count = count;
+        self.blinding = SecretScalar::new(blinding);
+        self.commitment = commitment;
+        Ok(UsageProof {
+            commitment,
+            increment_proof,
+            range_proof,
+        })
+    }
+}
+
+/// Verifier-side view of a device's usage counter that only ever sees commitments
+#[derive(Clone, Debug)]
+pub struct LicenseVerifier {
+    license_max: u64,
+    commitment: RistrettoPoint,
+    invocations: u64,
+    generators: PedersenGenerators,
+}
+
+impl LicenseVerifier {
+    /// Create a verifier for a device's counter after checking its initial commitment is to zero
+    pub fn new(
+        license_max: u64,
+        initial_commitment: &RistrettoPoint,
+        zero_proof: &BlindingProof,
+    ) -> Result<Self, Error> {
+        let generators = PedersenGenerators::default();
+        zero_proof.verify_proof(
+            initial_commitment,
+            &generators,
+            &mut usage_transcript(license_max, &[]),
+        )?;
+        Ok(Self {
+            license_max,
+            commitment: *initial_commitment,
+            invocations: 0,
+            generators,
+        })
+    }
+
+    /// Verify a counter increment builds on the last accepted commitment and stays within the
+    /// license. Returns the number of invocations accepted so far.
+    pub fn verify_increment(&mut self, proof: &UsageProof) -> Result<u64, Error> {
+        let mut transcript =
+            usage_transcript(self.license_max, &[self.commitment, proof.commitment]);
+        let increment_point = proof.commitment - self.commitment - self.generators.value_generator;
+        proof
+            .increment_proof
+            .verify_proof(&increment_point, &self.generators, &mut transcript)?;
+
+        let remaining_commitment =
+            Scalar::from(self.license_max) * self.generators.value_generator - proof.commitment;
+        proof.range_proof.verify_proof(
+            &remaining_commitment,
+            license_bits(self.license_max),
+            &self.generators,
+            &mut transcript,
+        )?;
+
+        self.commitment = proof.commitment;
+        self.invocations += 1;
+        Ok(self.invocations)
+    }
+
+    /// Get the number of invocations accepted so far
+    pub fn get_invocations(&self) -> u64 {
+        self.invocations
+    }
+}
+
+impl ProofScheme for UsageProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_counter_verifies_until_license_is_exhausted() {
+        let (mut counter, zero_proof) = UsageCounter::new(3);
+        let mut verifier = LicenseVerifier::new(3, &counter.get_commitment(), &zero_proof).unwrap();
+
+        for expected in 1..=3 {
+            let proof = counter.increment().unwrap();
+            assert_eq!(verifier.verify_increment(&proof).unwrap(), expected);
+        }
+
+        // The device can't produce a proof beyond its license
+        assert_eq!(counter.increment().err(), Some(Error::LicenseExhausted(3)));
+        assert_eq!(counter.get_count(), 3);
+        assert_eq!(verifier.get_invocations(), 3);
+    }
+
+    #[test]
+    fn test_usage_proofs_cannot_be_replayed_or_skipped() {
+        let (mut counter, zero_proof) = UsageCounter::new(10);
+        let mut verifier =
+            LicenseVerifier::new(10, &counter.get_commitment(), &zero_proof).unwrap();
+
+        let first_proof = counter.increment().unwrap();
+        let second_proof = counter.increment().unwrap();
+
+        // The second proof builds on a commitment the verifier hasn't accepted yet
+        assert!(verifier.verify_increment(&second_proof).is_err());
+        assert!(verifier.verify_increment(&first_proof).is_ok());
+
+        // Replaying an already accepted proof fails
+        assert!(verifier.verify_increment(&first_proof).is_err());
+        assert!(verifier.verify_increment(&second_proof).is_ok());
+    }
+
+    #[test]
+    fn test_license_verifier_rejects_non_zero_initial_counter() {
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let zero_proof =
+            BlindingProof::generate_proof(&blinding, &generators, &mut usage_transcript(5, &[]));
+
+        // A counter starting at 1 can't reuse a proof of knowledge of the blinding factor
+        let commitment = generators.commit(&Scalar::ONE, &blinding);
+        assert!(LicenseVerifier::new(5, &commitment, &zero_proof).is_err());
+    }
+}
@@ -0,0 +1,369 @@
+//! Versioned vocabularies of categorical labels committed to with Merkle trees
+
+use std::collections::BTreeMap;
+
+use encodings::{Digest, HashBackend};
+use merlin::Transcript;
+
+use crate::{model_commitment::merkle_tree, Error};
+
+// Categorical features and model outputs are committed to as field elements, so a prover and a
+// verifier have to agree on which label every element stands for. Passing ad-hoc lists of labels
+// around invites mismatches, e.g. a verifier checking a classification against an older list in
+// which index 2 meant something else. A vocabulary fixes the mapping once:
+//
+// 1. Label i of the vocabulary is encoded as the field element i. Labels are distinct, so every
+// label has exactly one encoding.
+// 2. The labels are the leaves of a Merkle tree, each hashed along with its index and padded with
+// zero nodes up to a power of two. The vocabulary commitment is the hash of the tree root, the
+// vocabulary's name, its version and its size.
+// 3. Commitments are published in a [`VocabularyRegistry`], where each name's versions only ever
+// increase. Changing a vocabulary means publishing a new version rather than silently changing an
+// old one.
+//
+// Anyone holding the commitment can check the encoding of a single label with its Merkle path,
+// without the rest of the vocabulary. Set membership proofs over a vocabulary bind its commitment
+// into their transcript and prove membership in the encodings 0..size, so a proof made against
+// one version of a vocabulary doesn't verify against another.
+
+// Domain separator for hashing a label and its index into a Merkle tree leaf
+const LABEL_DOMAIN_SEP: &[u8] = b"VOCABULARY_LABEL";
+
+// Domain separator for hashing Merkle tree nodes
+const NODE_DOMAIN_SEP: &[u8] = b"VOCABULARY_NODE";
+
+// Domain separator for hashing the tree root, name, version and size into the commitment
+const ROOT_DOMAIN_SEP: &[u8] = b"VOCABULARY_ROOT";
+
+// Domain separator for binding a vocabulary commitment into a transcript
+const VOCABULARY_DOMAIN_SEP: &[u8] = b"VOCABULARY";
+
+// Hash a label and its encoding into a Merkle tree leaf
+fn hash_leaf(digest: &HashBackend, index: u64, label: &str) -> [u8; 32] {
+    let mut data = index.to_le_bytes().to_vec();
+    data.extend(label.as_bytes());
+    digest.hash(LABEL_DOMAIN_SEP, &data)
+}
+
+// Hash two child nodes into their parent node
+fn hash_node(digest: &HashBackend, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    digest.hash_pair(NODE_DOMAIN_SEP, left, right)
+}
+
+// Hash the tree root along with the vocabulary's identity into its commitment
+fn hash_root(
+    digest: &HashBackend,
+    tree_root: &[u8; 32],
+    name: &str,
+    version: u64,
+    size: u64,
+) -> [u8; 32] {
+    let mut data = (name.len() as u64).to_le_bytes().to_vec();
+    data.extend(name.as_bytes());
+    data.extend(version.to_le_bytes());
+    data.extend(size.to_le_bytes());
+    data.extend(tree_root);
+    digest.hash(ROOT_DOMAIN_SEP, &data)
+}
+
+/// Versioned list of labels, each encoded as its index
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Vocabulary {
+    name: String,
+    version: u64,
+    labels: Vec<String>,
+    // Hash backend the Merkle tree and commitment are hashed with
+    digest: HashBackend,
+    // Merkle tree levels from the padded leaves up to the root
+    tree: Vec<Vec<[u8; 32]>>,
+}
+
+impl Vocabulary {
+    /// Create a version of a vocabulary from distinct labels, hashing its Merkle tree with
+    /// `digest`
+    pub fn new(
+        name: &str,
+        version: u64,
+        labels: &[&str],
+        digest: HashBackend,
+    ) -> Result<Self, Error> {
+        if labels.is_empty() {
+            return Err(Error::EmptyVocabulary);
+        }
+        for (i, label) in labels.iter().enumerate() {
+            if labels[..i].contains(label) {
+                return Err(Error::DuplicateLabel(label.to_string()));
+            }
+        }
+        let leaves: Vec<[u8; 32]> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| hash_leaf(&digest, i as u64, label))
+            .collect();
+        let tree = merkle_tree(&digest, NODE_DOMAIN_SEP, leaves);
+        Ok(Self {
+            name: name.to_string(),
+            version,
+            labels: labels.iter().map(|label| label.to_string()).collect(),
+            digest,
+            tree,
+        })
+    }
+
+    /// Get the name of the vocabulary
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the version of the vocabulary
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Get the labels in the order of their encodings
+    pub fn get_labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Get the field element encoding a label
+    pub fn encode(&self, label: &str) -> Result<u64, Error> {
+        self.labels
+            .iter()
+            .position(|other| other == label)
+            .map(|index| index as u64)
+            .ok_or_else(|| Error::UnknownLabel(label.to_string()))
+    }
+
+    /// Get the label an encoding stands for
+    pub fn decode(&self, encoding: u64) -> Option<&str> {
+        self.labels.get(encoding as usize).map(String::as_str)
+    }
+
+    /// Get the public commitment to the vocabulary
+    pub fn commitment(&self) -> VocabularyCommitment {
+        let size = self.labels.len() as u64;
+        let tree_root = self.tree[self.tree.len() - 1][0];
+        VocabularyCommitment {
+            name: self.name.clone(),
+            version: self.version,
+            size,
+            root: hash_root(&self.digest, &tree_root, &self.name, self.version, size),
+            digest: self.digest,
+        }
+    }
+
+    /// Prove the encoding of a label to a holder of the vocabulary commitment
+    pub fn prove_label(&self, label: &str) -> Result<LabelPath, Error> {
+        let encoding = self.encode(label)?;
+        let mut position = encoding as usize;
+        let mut siblings = Vec::with_capacity(self.tree.len() - 1);
+        for level in &self.tree[..self.tree.len() - 1] {
+            siblings.push(level[position ^ 1]);
+            position /= 2;
+        }
+        Ok(LabelPath { encoding, siblings })
+    }
+}
+
+/// Public commitment to a version of a vocabulary
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VocabularyCommitment {
+    name: String,
+    version: u64,
+    // Number of labels
+    size: u64,
+    // Hash of the Merkle root of the labels, the name, the version and the size
+    root: [u8; 32],
+    // Hash backend the Merkle tree and commitment are hashed with
+    digest: HashBackend,
+}
+
+impl VocabularyCommitment {
+    /// Get the name of the committed vocabulary
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the version of the committed vocabulary
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Get the number of labels in the committed vocabulary
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+
+    /// Get the hash committing to the vocabulary
+    pub fn get_root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Get the hash backend the commitment was created with
+    pub fn get_digest(&self) -> HashBackend {
+        self.digest
+    }
+
+    /// Get the encodings of every label, i.e. 0..size
+    pub fn encodings(&self) -> Vec<u64> {
+        (0..self.size).collect()
+    }
+
+    /// Verify a label's encoding with its Merkle path, returning the encoding
+    pub fn verify_label(&self, label: &str, path: &LabelPath) -> Result<u64, Error> {
+        let mismatch = || Error::LabelMismatch(label.to_string());
+        let depth = self.size.next_power_of_two().trailing_zeros() as usize;
+        if path.encoding >= self.size || path.siblings.len() != depth {
+            return Err(mismatch());
+        }
+        let mut node = hash_leaf(&self.digest, path.encoding, label);
+        let mut position = path.encoding;
+        for sibling in &path.siblings {
+            node = match position % 2 {
+                0 => hash_node(&self.digest, &node, sibling),
+                _ => hash_node(&self.digest, sibling, &node),
+            };
+            position /= 2;
+        }
+        if hash_root(&self.digest, &node, &self.name, self.version, self.size) != self.root {
+            return Err(mismatch());
+        }
+        Ok(path.encoding)
+    }
+
+    // Bind the commitment into a proof transcript
+    pub(crate) fn append_to_transcript(&self, transcript: &mut Transcript) {
+        transcript.append_message(VOCABULARY_DOMAIN_SEP, self.digest.label());
+        transcript.append_message(b"NAME", self.name.as_bytes());
+        transcript.append_u64(b"VERSION", self.version);
+        transcript.append_u64(b"SIZE", self.size);
+        transcript.append_message(b"ROOT", &self.root);
+    }
+}
+
+/// Merkle path proving the encoding of a label
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LabelPath {
+    // Encoding of the label, which is its position among the leaves
+    encoding: u64,
+    // Sibling nodes from the label's leaf up to the Merkle root
+    siblings: Vec<[u8; 32]>,
+}
+
+impl LabelPath {
+    /// Get the encoding the path proves
+    pub fn get_encoding(&self) -> u64 {
+        self.encoding
+    }
+}
+
+/// Registry of published vocabulary commitments whose versions only increase
+#[derive(Clone, Debug, Default)]
+pub struct VocabularyRegistry {
+    // Published commitments of each vocabulary in increasing order of version
+    vocabularies: BTreeMap<String, Vec<VocabularyCommitment>>,
+}
+
+impl VocabularyRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish a vocabulary commitment, which needs a higher version than every published
+    /// version of the same vocabulary
+    pub fn publish(&mut self, commitment: VocabularyCommitment) -> Result<(), Error> {
+        let versions = self
+            .vocabularies
+            .entry(commitment.name.clone())
+            .or_default();
+        if let Some(latest) = versions.last() {
+            if commitment.version <= latest.version {
+                return Err(Error::StaleVocabularyVersion(
+                    commitment.name,
+                    commitment.version,
+                ));
+            }
+        }
+        versions.push(commitment);
+        Ok(())
+    }
+
+    /// Get a published version of a vocabulary
+    pub fn get(&self, name: &str, version: u64) -> Option<&VocabularyCommitment> {
+        self.vocabularies
+            .get(name)?
+            .iter()
+            .find(|commitment| commitment.version == version)
+    }
+
+    /// Get the latest published version of a vocabulary
+    pub fn latest(&self, name: &str) -> Option<&VocabularyCommitment> {
+        self.vocabularies.get(name)?.last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_verify_against_the_vocabulary_commitment() {
+        let labels = ["cat", "dog", "bird", "fish", "horse"];
+        let vocabulary = Vocabulary::new("animals", 1, &labels, HashBackend::Blake3).unwrap();
+        let commitment = vocabulary.commitment();
+        assert_eq!(commitment.encodings(), [0, 1, 2, 3, 4]);
+        for (i, label) in labels.iter().enumerate() {
+            assert_eq!(vocabulary.encode(label), Ok(i as u64));
+            assert_eq!(vocabulary.decode(i as u64), Some(*label));
+            let path = vocabulary.prove_label(label).unwrap();
+            assert_eq!(commitment.verify_label(label, &path), Ok(i as u64));
+        }
+
+        // A path doesn't prove another label, or the label under another version
+        let path = vocabulary.prove_label("dog").unwrap();
+        let mismatch = Err(Error::LabelMismatch("cat".to_string()));
+        assert_eq!(commitment.verify_label("cat", &path), mismatch);
+        let reordered = ["dog", "cat", "bird", "fish", "horse"];
+        let next = Vocabulary::new("animals", 2, &reordered, HashBackend::Blake3).unwrap();
+        let next_path = next.prove_label("cat").unwrap();
+        assert_eq!(commitment.verify_label("cat", &next_path), mismatch);
+        assert_ne!(commitment.get_root(), next.commitment().get_root());
+
+        assert_eq!(
+            Vocabulary::new("animals", 1, &["cat", "cat"], HashBackend::Sha256),
+            Err(Error::DuplicateLabel("cat".to_string()))
+        );
+        assert_eq!(
+            Vocabulary::new("animals", 1, &[], HashBackend::Sha256),
+            Err(Error::EmptyVocabulary)
+        );
+        assert_eq!(
+            vocabulary.encode("cow"),
+            Err(Error::UnknownLabel("cow".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_registry_only_accepts_increasing_versions() {
+        let mut registry = VocabularyRegistry::new();
+        let first = Vocabulary::new("colors", 3, &["red", "green"], HashBackend::Sha256).unwrap();
+        let second = Vocabulary::new("colors", 4, &["red", "blue"], HashBackend::Sha256).unwrap();
+        registry.publish(first.commitment()).unwrap();
+        registry.publish(second.commitment()).unwrap();
+        assert_eq!(registry.get("colors", 3), Some(&first.commitment()));
+        assert_eq!(registry.latest("colors"), Some(&second.commitment()));
+        assert_eq!(registry.get("colors", 5), None);
+
+        // Republishing a version, even unchanged, or going back is rejected
+        for stale in [&first, &second] {
+            assert_eq!(
+                registry.publish(stale.commitment()),
+                Err(Error::StaleVocabularyVersion(
+                    "colors".to_string(),
+                    stale.get_version()
+                ))
+            );
+        }
+    }
+}
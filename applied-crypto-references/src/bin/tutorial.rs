@@ -1,16 +1,56 @@
 //! Demonstrating the usage of Merlin STROBE based transcripts for creating non-interative
 //! public coin arguments and consistent hashing schemes.
+//!
+//! Setup, prove and verify phases are traced as spans, so setting e.g.
+//! `RUST_LOG=merlin_example=debug` prints how long each of them takes.
+//!
+//! The `prove` and `verify` subcommands run the proofs on witnesses and proofs read from JSON
+//! files, e.g. `tutorial prove witness.json --output proof.json` and `tutorial verify proof.json`.
 
-use applied_crypto_references::{ConfigArgs, Tutorials};
+use applied_crypto_references::{find_tutorial, Command, ConfigArgs, ProofFile, Witness};
 use clap::Parser;
-use merlin_example::{merlin_basics_tutorial, merlin_non_interactive_proof_tutorial};
+use encodings::SecurityPolicy;
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
 fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
     let config = ConfigArgs::parse();
-    match config.tutorial {
-        Tutorials::Merlin => merlin_basics_tutorial(),
-        Tutorials::MerlinNonInteractiveProof => {
-            merlin_non_interactive_proof_tutorial();
-        }
+    if let Some(command) = config.command {
+        run_command(command);
+        return;
+    }
+    let tutorial = config
+        .tutorial
+        .as_deref()
+        .and_then(find_tutorial)
+        .expect("clap only accepts registered tutorials");
+    let policy = SecurityPolicy::default().allow_educational(config.allow_educational);
+    if let Err(error) = policy.check(tutorial.security_level) {
+        eprintln!(
+            "Refusing to run the tutorial: {error:?}. Pass --allow-educational to run it anyway"
+        );
+        std::process::exit(1);
+    }
+    (tutorial.run)();
+}
+
+// Prove a witness file or verify a proof file, exiting with an error code if it fails
+fn run_command(command: Command) {
+    let result = match command {
+        Command::Prove { witness, output } => Witness::read(&witness)
+            .and_then(|witness| witness.prove())
+            .and_then(|proof_file| proof_file.write(&output))
+            .map(|_| println!("Proof written to {}", output.display())),
+        Command::Verify { proof } => ProofFile::read(&proof)
+            .and_then(|proof_file| proof_file.verify())
+            .map(|_| println!("Proof in {} verified", proof.display())),
+    };
+    if let Err(error) = result {
+        eprintln!("{error:?}");
+        std::process::exit(1);
     }
 }
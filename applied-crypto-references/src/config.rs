@@ -1,17 +1,42 @@
-use clap::{AppSettings, Parser, ValueEnum};
+use std::path::PathBuf;
+
+use clap::{AppSettings, Parser, Subcommand};
+
+use crate::tutorials::{tutorial_help, tutorial_parser};
 
 #[derive(Parser)]
 #[clap(name = "Applied Cryptography Examples")]
 #[clap(about = "Short Illustrative Examples of Cryptography Underlying Zero Knowledge Proofs")]
 #[clap(global_setting(AppSettings::ArgRequiredElseHelp))]
+#[clap(after_help = tutorial_help())]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct ConfigArgs {
-    #[clap(arg_enum, value_parser)]
+    #[clap(value_parser = tutorial_parser(), required = true)]
     /// Which tutorial to run
-    pub tutorial: Tutorials,
+    pub tutorial: Option<String>,
+    #[clap(long)]
+    /// Allow running educational schemes which must not be used with untrusted counterparties
+    pub allow_educational: bool,
+    #[clap(subcommand)]
+    /// Prove or verify statements from files instead of running a tutorial
+    pub command: Option<Command>,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-pub enum Tutorials {
-    Merlin,
-    MerlinNonInteractiveProof,
+#[derive(Subcommand)]
+pub enum Command {
+    /// Prove the statement of a JSON witness file and write the proof to a file
+    Prove {
+        #[clap(value_parser)]
+        /// JSON file holding the witness, e.g. {"scheme": "schnorr", "private_key": "<hex>"}
+        witness: PathBuf,
+        #[clap(long, short, value_parser)]
+        /// File to write the proof to
+        output: PathBuf,
+    },
+    /// Verify a proof file written by `prove`
+    Verify {
+        #[clap(value_parser)]
+        /// JSON file holding the statement and its proof
+        proof: PathBuf,
+    },
 }
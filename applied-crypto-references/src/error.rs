@@ -0,0 +1,23 @@
+//! Errors in proving and verifying statements from files
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Reading a witness or proof file or writing a proof file failed
+    Io(String),
+    /// File isn't JSON of the expected shape
+    InvalidJson(String),
+    /// Field isn't valid hex (field name)
+    InvalidHex(&'static str),
+    /// Key, blinding factor, commitment or proof isn't canonically encoded
+    InvalidEncoding(encodings::Error),
+    /// Proof couldn't be created or failed to verify
+    Proof(merlin_example::Error),
+    /// Threshold must be between 1 and the number of shares (threshold, shares)
+    InvalidThreshold(usize, usize),
+    /// Share has index zero or doesn't match the commitment of the shared secret (share index)
+    InvalidShare(u64),
+    /// Fewer shares than the threshold were given (shares, threshold)
+    InsufficientShares(usize, usize),
+    /// Share index appears more than once (share index)
+    RepeatedShare(u64),
+}
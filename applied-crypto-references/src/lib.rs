@@ -1,3 +1,18 @@
 mod config;
+mod error;
+mod proof_files;
+mod secret_sharing;
+mod tutorials;
 
-pub use crate::config::{ConfigArgs, Tutorials};
+pub use crate::{
+    config::{Command, ConfigArgs},
+    error::Error,
+    proof_files::{ProofFile, Witness},
+    secret_sharing::{
+        reconstruct_secret, split_secret, split_verifiable_secret, FeldmanCommitment, Share,
+    },
+    tutorials::{find_tutorial, Tutorial, TUTORIALS},
+};
+
+// Groups secrets can be shared over, defined next to the interpolation the key escrow shares
+pub use encodings::SharingGroup;
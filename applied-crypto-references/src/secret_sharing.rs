@@ -0,0 +1,266 @@
+//! Shamir secret sharing with Feldman verifiable shares over the Ristretto and BLS12-381 scalars
+
+use encodings::{evaluate_commitments, evaluate_polynomial, lagrange_coefficient, SharingGroup};
+use rand::{CryptoRng, RngCore};
+
+use crate::Error;
+
+// Threshold protocols split a secret scalar `s` among n parties so that any t of them can recover
+// it while t - 1 of them learn nothing about it:
+//
+// 1. Sharing: the dealer picks a random polynomial f(x) = s + a_1*x + .. + a_(t-1)*x^(t-1) over the
+// scalar field and hands party i the share s_i = f(i), for i from 1 to n. Index 0 is never used,
+// since f(0) is the secret itself.
+// 2. Reconstruction: t points determine a polynomial of degree t - 1, so any t shares give back
+// s = f(0) = sum s_i*l_i by Lagrange interpolation with l_i = prod_(j != i) x_j / (x_j - x_i).
+// Every value of s is consistent with t - 1 shares, which is why fewer shares reveal nothing.
+// 3. Verification (Feldman): the dealer also publishes A_j = a_j*G for every coefficient, with
+// A_0 = s*G. Party i checks s_i*G == sum A_j*i^j, so a dealer can't hand out shares which
+// reconstruct to different secrets depending on which t parties combine them. The commitments
+// reveal s*G, so Feldman sharing only hides secrets whose public key may be known.
+//
+// The sharing is generic over the groups of the `encodings` crate, so the same code shares
+// Ristretto scalars for the sigma protocols and BLS12-381 scalars for the pairing based proofs. The
+// polynomial evaluation and interpolation are the ones of `encodings`, which the key escrow of the
+// sigma protocols uses too.
+
+/// Share of a secret held by a single party
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Share<G: SharingGroup> {
+    // Point the sharing polynomial was evaluated at, starting at 1
+    index: u64,
+    // Evaluation of the sharing polynomial at the index
+    value: G::Scalar,
+}
+
+impl<G: SharingGroup> Share<G> {
+    /// Create a share from the index it was evaluated at and its value
+    pub fn new(index: u64, value: G::Scalar) -> Self {
+        Self { index, value }
+    }
+
+    /// Get the index of the party holding the share
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+
+    /// Get the value of the share
+    pub fn get_value(&self) -> G::Scalar {
+        self.value
+    }
+}
+
+/// Public commitments to the coefficients of the polynomial a secret was shared with
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeldmanCommitment<G: SharingGroup> {
+    // A_j = a_j*G for every coefficient, starting with the commitment to the secret
+    coefficient_commitments: Vec<G::Point>,
+}
+
+impl<G: SharingGroup> FeldmanCommitment<G> {
+    /// Get the commitment s*G to the shared secret
+    pub fn get_secret_commitment(&self) -> G::Point {
+        self.coefficient_commitments[0]
+    }
+
+    /// Get the number of shares needed to reconstruct the secret
+    pub fn get_threshold(&self) -> usize {
+        self.coefficient_commitments.len()
+    }
+
+    /// Check a share is the evaluation of the committed polynomial at its index
+    pub fn verify_share(&self, share: &Share<G>) -> Result<(), Error> {
+        let expected = evaluate_commitments::<G>(&self.coefficient_commitments, share.index);
+        if share.index == 0 || G::value_generator() * share.value != expected {
+            return Err(Error::InvalidShare(share.index));
+        }
+        Ok(())
+    }
+
+    /// Reconstruct the secret from at least a threshold of verified shares with distinct indices.
+    /// A share given more than once counts once, since verified shares at an index are equal.
+    pub fn reconstruct(&self, shares: &[Share<G>]) -> Result<G::Scalar, Error> {
+        let mut distinct: Vec<Share<G>> = Vec::with_capacity(shares.len());
+        for share in shares {
+            self.verify_share(share)?;
+            if distinct.iter().all(|other| other.index != share.index) {
+                distinct.push(*share);
+            }
+        }
+        if distinct.len() < self.get_threshold() {
+            return Err(Error::InsufficientShares(
+                distinct.len(),
+                self.get_threshold(),
+            ));
+        }
+        reconstruct_secret(&distinct[..self.get_threshold()])
+    }
+}
+
+// Sample the coefficients of a random polynomial of degree `threshold - 1` with the secret at 0
+fn sharing_polynomial<G: SharingGroup, R: CryptoRng + RngCore>(
+    secret: &G::Scalar,
+    threshold: usize,
+    num_shares: usize,
+    rng: &mut R,
+) -> Result<Vec<G::Scalar>, Error> {
+    if threshold == 0 || threshold > num_shares {
+        return Err(Error::InvalidThreshold(threshold, num_shares));
+    }
+    let mut coefficients = vec![*secret];
+    coefficients.extend((1..threshold).map(|_| G::random_scalar(rng)));
+    Ok(coefficients)
+}
+
+// Evaluate the polynomial at every index from 1 to `num_shares`
+fn evaluate_shares<G: SharingGroup>(
+    coefficients: &[G::Scalar],
+    num_shares: usize,
+) -> Vec<Share<G>> {
+    (1..=num_shares as u64)
+        .map(|index| Share::new(index, evaluate_polynomial::<G>(coefficients, index)))
+        .collect()
+}
+
+/// Split a secret into `num_shares` shares, any `threshold` of which reconstruct it
+pub fn split_secret<G: SharingGroup, R: CryptoRng + RngCore>(
+    secret: &G::Scalar,
+    threshold: usize,
+    num_shares: usize,
+    rng: &mut R,
+) -> Result<Vec<Share<G>>, Error> {
+    let coefficients = sharing_polynomial::<G, R>(secret, threshold, num_shares, rng)?;
+    Ok(evaluate_shares(&coefficients, num_shares))
+}
+
+/// Split a secret into `num_shares` shares, any `threshold` of which reconstruct it, along with a
+/// commitment every share can be verified against
+pub fn split_verifiable_secret<G: SharingGroup, R: CryptoRng + RngCore>(
+    secret: &G::Scalar,
+    threshold: usize,
+    num_shares: usize,
+    rng: &mut R,
+) -> Result<(FeldmanCommitment<G>, Vec<Share<G>>), Error> {
+    let coefficients = sharing_polynomial::<G, R>(secret, threshold, num_shares, rng)?;
+    let commitment = FeldmanCommitment {
+        coefficient_commitments: coefficients
+            .iter()
+            .map(|coefficient| G::value_generator() * *coefficient)
+            .collect(),
+    };
+    Ok((commitment, evaluate_shares(&coefficients, num_shares)))
+}
+
+/// Interpolate the secret from shares with distinct non-zero indices. Fewer shares than the
+/// threshold give an unrelated scalar rather than an error, since unverified shares don't carry
+/// the threshold.
+pub fn reconstruct_secret<G: SharingGroup>(shares: &[Share<G>]) -> Result<G::Scalar, Error> {
+    if shares.is_empty() {
+        return Err(Error::InsufficientShares(0, 1));
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.index == 0 {
+            return Err(Error::InvalidShare(share.index));
+        }
+        if shares[..i].iter().any(|other| other.index == share.index) {
+            return Err(Error::RepeatedShare(share.index));
+        }
+    }
+    let indices: Vec<u64> = shares.iter().map(Share::get_index).collect();
+    Ok(shares.iter().fold(G::Scalar::from(0), |secret, share| {
+        let coefficient =
+            lagrange_coefficient::<G>(share.index, &indices).expect("indices are distinct");
+        secret + G::mul_scalars(&share.value, &coefficient)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encodings::{Bls12G1, CommitmentGroup, Ristretto};
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    // Check every subset of at least a threshold of shares reconstructs the secret, while one
+    // share less doesn't
+    fn check_threshold<G: SharingGroup>(threshold: usize, num_shares: usize, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let secret = G::random_scalar(&mut rng);
+        let (commitment, mut shares) =
+            split_verifiable_secret::<G, _>(&secret, threshold, num_shares, &mut rng).unwrap();
+        assert_eq!(
+            commitment.get_secret_commitment(),
+            G::value_generator() * secret
+        );
+        assert!(shares
+            .iter()
+            .all(|share| commitment.verify_share(share).is_ok()));
+
+        shares.shuffle(&mut rng);
+        for len in threshold..=num_shares {
+            assert_eq!(reconstruct_secret(&shares[..len]), Ok(secret));
+            assert_eq!(commitment.reconstruct(&shares[..len]), Ok(secret));
+        }
+        if threshold > 1 {
+            let below = &shares[..threshold - 1];
+            assert_ne!(reconstruct_secret(below), Ok(secret));
+            assert_eq!(
+                commitment.reconstruct(below),
+                Err(Error::InsufficientShares(threshold - 1, threshold))
+            );
+        }
+
+        let unverified = split_secret::<G, _>(&secret, threshold, num_shares, &mut rng).unwrap();
+        assert_eq!(reconstruct_secret(&unverified[..threshold]), Ok(secret));
+    }
+
+    proptest! {
+        // Every case multiplies BLS12-381 points for each share, so a few cases keep the test fast
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_any_threshold_of_shares_reconstructs_the_secret(
+            threshold in 1usize..6,
+            extra_shares in 0usize..4,
+            seed in any::<u64>(),
+        ) {
+            check_threshold::<Ristretto>(threshold, threshold + extra_shares, seed);
+            check_threshold::<Bls12G1>(threshold, threshold + extra_shares, seed);
+        }
+    }
+
+    #[test]
+    fn test_tampered_and_repeated_shares_are_rejected() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let secret = Bls12G1::random_scalar(&mut rng);
+        let (commitment, mut shares) =
+            split_verifiable_secret::<Bls12G1, _>(&secret, 2, 3, &mut rng).unwrap();
+        assert_eq!(
+            reconstruct_secret(&[shares[0], shares[0]]),
+            Err(Error::RepeatedShare(1))
+        );
+
+        // Verified shares given twice count once towards the threshold
+        assert_eq!(
+            commitment.reconstruct(&[shares[0], shares[0]]),
+            Err(Error::InsufficientShares(1, 2))
+        );
+        assert_eq!(
+            commitment.reconstruct(&[shares[0], shares[0], shares[1]]),
+            Ok(secret)
+        );
+        shares[1] = Share::new(
+            2,
+            shares[1].get_value() + <Bls12G1 as CommitmentGroup>::Scalar::from(1),
+        );
+        assert_eq!(
+            commitment.verify_share(&shares[1]),
+            Err(Error::InvalidShare(2))
+        );
+        assert_eq!(commitment.reconstruct(&shares), Err(Error::InvalidShare(2)));
+        assert_eq!(
+            split_secret::<Ristretto, _>(&1u64.into(), 4, 3, &mut rng),
+            Err(Error::InvalidThreshold(4, 3))
+        );
+    }
+}
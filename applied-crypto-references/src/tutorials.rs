@@ -0,0 +1,119 @@
+//! Registry of the tutorials the binary can run
+
+use std::{fmt::Write, sync::OnceLock};
+
+use clap::{builder::PossibleValuesParser, PossibleValue};
+use encodings::SecurityLevel;
+use merlin_example::{merlin_basics_tutorial, merlin_non_interactive_proof_tutorial};
+use zksnarks_example::zksnark_tutorial;
+
+// Every tutorial the binary can run is an entry of `TUTORIALS`. The command line parser takes its
+// possible values and the list of tutorials in its help from the registry, so adding a tutorial,
+// e.g. a KZG commitment tutorial, only takes a new entry here for it to be selectable and listed
+// in `--help`. The security level of each entry is checked against the security policy before the
+// tutorial runs.
+
+/// Tutorial the binary can run
+#[derive(Clone, Copy, Debug)]
+pub struct Tutorial {
+    /// Name the tutorial is selected by on the command line
+    pub name: &'static str,
+    /// One line description shown in `--help`
+    pub description: &'static str,
+    /// Security level of the schemes the tutorial runs
+    pub security_level: SecurityLevel,
+    /// Run the tutorial
+    pub run: fn(),
+}
+
+/// All tutorials, in the order they are listed in `--help`
+pub const TUTORIALS: &[Tutorial] = &[
+    Tutorial {
+        name: "merlin",
+        description: "Transcripts, domain separators and challenges with Merlin",
+        security_level: SecurityLevel::Educational,
+        run: merlin_basics_tutorial,
+    },
+    Tutorial {
+        name: "merlin-non-interactive-proof",
+        description: "Non-interactive Schnorr proof of a private key",
+        security_level: SecurityLevel::Educational,
+        run: || {
+            merlin_non_interactive_proof_tutorial();
+        },
+    },
+    Tutorial {
+        name: "zksnark",
+        description: "Encrypted polynomial zkSNARK step by step with intermediate values",
+        security_level: SecurityLevel::Educational,
+        run: zksnark_tutorial,
+    },
+];
+
+/// Look up a registered tutorial by name
+pub fn find_tutorial(name: &str) -> Option<&'static Tutorial> {
+    TUTORIALS.iter().find(|tutorial| tutorial.name == name)
+}
+
+// Parser accepting the names of the registered tutorials
+pub(crate) fn tutorial_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(
+        TUTORIALS
+            .iter()
+            .map(|tutorial| PossibleValue::new(tutorial.name).help(tutorial.description)),
+    )
+}
+
+// List of the registered tutorials with their descriptions, shown below the options in `--help`
+pub(crate) fn tutorial_help() -> &'static str {
+    static HELP: OnceLock<String> = OnceLock::new();
+    HELP.get_or_init(|| {
+        let width = TUTORIALS
+            .iter()
+            .map(|tutorial| tutorial.name.len())
+            .max()
+            .unwrap_or_default();
+        let mut help = String::from("TUTORIALS:");
+        for tutorial in TUTORIALS {
+            write!(
+                help,
+                "\n    {:width$}    {}",
+                tutorial.name, tutorial.description
+            )
+            .expect("writing to a string can't fail");
+        }
+        help
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::ConfigArgs;
+
+    #[test]
+    fn test_every_registered_tutorial_is_selectable_by_name() {
+        let names: HashSet<&str> = TUTORIALS.iter().map(|tutorial| tutorial.name).collect();
+        assert_eq!(names.len(), TUTORIALS.len());
+        for tutorial in TUTORIALS {
+            let config = ConfigArgs::try_parse_from(["tutorial", tutorial.name]).unwrap();
+            assert_eq!(config.tutorial.as_deref(), Some(tutorial.name));
+            assert_eq!(find_tutorial(tutorial.name).unwrap().name, tutorial.name);
+        }
+    }
+
+    #[test]
+    fn test_unregistered_tutorials_are_rejected() {
+        assert!(ConfigArgs::try_parse_from(["tutorial", "bulletproofs"]).is_err());
+        assert!(find_tutorial("bulletproofs").is_none());
+
+        // The help lists every registered tutorial with its description
+        for tutorial in TUTORIALS {
+            assert!(tutorial_help().contains(tutorial.description));
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Functions and types exported to JavaScript through wasm-bindgen
+
+use wasm_bindgen::prelude::*;
+
+use crate::{range_proof, schnorr, CommittedRange, Error};
+
+// The exports are thin wrappers around the byte level functions of this crate, which stay
+// callable and testable from Rust. Errors are handed to JavaScript as thrown `Error` objects
+// carrying the debug representation of the Rust error, and verification functions return
+// nothing on success so a failed check can't be mistaken for `false` being ignored.
+
+// Convert an error into a JavaScript exception
+fn to_js_error(error: Error) -> JsError {
+    JsError::new(&format!("{error:?}"))
+}
+
+/// Sample a new private key as 32 little endian bytes
+#[wasm_bindgen(js_name = generatePrivateKey)]
+pub fn generate_private_key() -> Vec<u8> {
+    schnorr::generate_private_key()
+}
+
+/// Derive the compressed public key of a private key
+#[wasm_bindgen(js_name = publicKey)]
+pub fn public_key(private_key: &[u8]) -> Result<Vec<u8>, JsError> {
+    schnorr::public_key(private_key).map_err(to_js_error)
+}
+
+/// Prove knowledge of a private key with a Schnorr proof
+#[wasm_bindgen(js_name = proveSchnorr)]
+pub fn prove_schnorr(private_key: &[u8]) -> Result<Vec<u8>, JsError> {
+    schnorr::prove(private_key).map_err(to_js_error)
+}
+
+/// Verify a Schnorr proof against a compressed public key, throwing if it doesn't verify
+#[wasm_bindgen(js_name = verifySchnorr)]
+pub fn verify_schnorr(public_key: &[u8], proof: &[u8]) -> Result<(), JsError> {
+    schnorr::verify(public_key, proof).map_err(to_js_error)
+}
+
+/// Commitment to a value together with a proof it lies within a range
+#[wasm_bindgen(js_name = CommittedRange)]
+pub struct JsCommittedRange(CommittedRange);
+
+#[wasm_bindgen(js_class = CommittedRange)]
+impl JsCommittedRange {
+    /// Compressed Pedersen commitment to the value
+    #[wasm_bindgen(getter)]
+    pub fn commitment(&self) -> Vec<u8> {
+        self.0.commitment.clone()
+    }
+
+    /// Blinding factor opening the commitment
+    #[wasm_bindgen(getter)]
+    pub fn blinding(&self) -> Vec<u8> {
+        self.0.blinding.clone()
+    }
+
+    /// Encoded range proof
+    #[wasm_bindgen(getter)]
+    pub fn proof(&self) -> Vec<u8> {
+        self.0.proof.clone()
+    }
+}
+
+/// Commit to a value and prove it lies within [0, 2^bits)
+#[wasm_bindgen(js_name = proveRange)]
+pub fn prove_range(value: u64, bits: usize) -> Result<JsCommittedRange, JsError> {
+    range_proof::prove(value, bits)
+        .map(JsCommittedRange)
+        .map_err(to_js_error)
+}
+
+/// Verify a range proof against a compressed commitment, throwing if it doesn't verify
+#[wasm_bindgen(js_name = verifyRange)]
+pub fn verify_range(commitment: &[u8], proof: &[u8], bits: usize) -> Result<(), JsError> {
+    range_proof::verify(commitment, proof, bits).map_err(to_js_error)
+}
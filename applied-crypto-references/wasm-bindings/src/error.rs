@@ -0,0 +1,9 @@
+//! Errors surfaced to JavaScript callers of the bindings
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Key, blinding factor or commitment bytes aren't a canonical scalar or point
+    InvalidEncoding(encodings::Error),
+    /// Proof couldn't be created, decoded or verified
+    Proof(merlin_example::Error),
+}
@@ -0,0 +1,33 @@
+mod bindings;
+mod error;
+mod range_proof;
+mod schnorr;
+
+pub use crate::{
+    bindings::{
+        generate_private_key, prove_range, prove_schnorr, public_key, verify_range, verify_schnorr,
+        JsCommittedRange,
+    },
+    error::Error,
+    range_proof::CommittedRange,
+};
+
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+
+// The proofs need randomness for private keys, blinding factors and proof nonces, all of which
+// are drawn from `OsRng` here and in the proof library. `OsRng` asks `getrandom` for entropy,
+// which has no source on `wasm32-unknown-unknown` since there's no operating system to ask.
+// Enabling the `wasm-rng` feature switches `getrandom` to the browser's (or Node's)
+// `crypto.getRandomValues`, so the same `OsRng` is cryptographically secure in the browser too.
+// Building for the web without it is a compile error rather than a runtime panic.
+#[cfg(all(
+    target_arch = "wasm32",
+    target_os = "unknown",
+    not(feature = "wasm-rng")
+))]
+compile_error!("enable the `wasm-rng` feature to build for wasm32-unknown-unknown");
+
+// Source of randomness for keys and blinding factors sampled by the bindings
+pub(crate) fn rng() -> impl CryptoRng + RngCore {
+    OsRng
+}
@@ -0,0 +1,115 @@
+//! Range proofs over byte encoded commitments and proofs
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use encodings::{Endianness, PointEncoding, ScalarEncoding};
+use merlin::Transcript;
+use merlin_example::{PedersenGenerators, RangeProof};
+
+use crate::{rng, Error};
+
+// A device proving a reading lies within a range commits to it with the default
+// [`PedersenGenerators`] and proves the commitment opens to a value in [0, 2^bits):
+//
+// 1. The blinding factor is sampled inside the bindings, so callers never have to produce
+// uniformly random scalars in JavaScript. It is handed back so the device can open the
+// commitment later.
+// 2. The commitment is a 32 byte compressed point and the proof uses the versioned wire format of
+// [`RangeProof`].
+// 3. Both sides start from a transcript with the domain separator below, so proofs made in the
+// browser verify with the Rust library and vice versa.
+
+// Domain separator for initializing range proof transcripts
+const RANGE_PROOF_DOMAIN_SEP: &[u8] = b"WASM_RANGE_PROOF";
+
+/// Commitment to a value together with a proof it lies within a range
+#[derive(Clone, Debug)]
+pub struct CommittedRange {
+    /// Compressed Pedersen commitment to the value
+    pub commitment: Vec<u8>,
+    /// Blinding factor of the commitment as 32 little endian bytes
+    pub blinding: Vec<u8>,
+    /// Encoded range proof
+    pub proof: Vec<u8>,
+}
+
+/// Commit to `value` under a fresh blinding factor and prove it lies within [0, 2^bits)
+pub fn prove(value: u64, bits: usize) -> Result<CommittedRange, Error> {
+    let generators = PedersenGenerators::default();
+    let blinding = Scalar::random(&mut rng());
+    let commitment = generators.commit(&Scalar::from(value), &blinding);
+    let proof = RangeProof::generate_proof(
+        value,
+        &blinding,
+        bits,
+        &generators,
+        &mut Transcript::new(RANGE_PROOF_DOMAIN_SEP),
+    )
+    .map_err(Error::Proof)?;
+    Ok(CommittedRange {
+        commitment: commitment.encode(),
+        blinding: blinding.encode(Endianness::Little),
+        proof: proof.to_bytes(),
+    })
+}
+
+/// Verify an encoded proof that a compressed commitment opens to a value within [0, 2^bits)
+pub fn verify(commitment: &[u8], proof: &[u8], bits: usize) -> Result<(), Error> {
+    let commitment = RistrettoPoint::decode(commitment).map_err(Error::InvalidEncoding)?;
+    RangeProof::from_bytes(proof)
+        .and_then(|proof| {
+            proof.verify_proof(
+                &commitment,
+                bits,
+                &PedersenGenerators::default(),
+                &mut Transcript::new(RANGE_PROOF_DOMAIN_SEP),
+            )
+        })
+        .map_err(Error::Proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_proof_made_from_bytes_verifies() {
+        let committed = prove(1000, 16).unwrap();
+        assert!(verify(&committed.commitment, &committed.proof, 16).is_ok());
+
+        // The returned blinding factor opens the commitment
+        let blinding = Scalar::decode(&committed.blinding, Endianness::Little).unwrap();
+        assert_eq!(
+            PedersenGenerators::default()
+                .commit(&Scalar::from(1000u64), &blinding)
+                .encode(),
+            committed.commitment
+        );
+
+        // The proof doesn't verify for a different range
+        assert_eq!(
+            verify(&committed.commitment, &committed.proof, 12),
+            Err(Error::Proof(merlin_example::Error::RangeProofMismatch))
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_values_and_malformed_proofs_are_rejected() {
+        assert_eq!(
+            prove(256, 8).err(),
+            Some(Error::Proof(merlin_example::Error::ValueOutOfRange(256, 8)))
+        );
+        let committed = prove(7, 8).unwrap();
+        let mut proof = committed.proof.clone();
+        proof[0] = 2;
+        assert_eq!(
+            verify(&committed.commitment, &proof, 8),
+            Err(Error::Proof(
+                merlin_example::Error::UnsupportedProofVersion(2)
+            ))
+        );
+        assert!(matches!(
+            verify(&[0xff; 32], &committed.proof, 8),
+            Err(Error::InvalidEncoding(_))
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+//! Schnorr proofs of private key over byte encoded keys and proofs
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use encodings::{Endianness, PointEncoding, ScalarEncoding};
+use merlin_example::SimpleSchnorrProof;
+
+use crate::{rng, Error};
+
+// JavaScript has no notion of scalars or curve points, so everything crossing the boundary is
+// bytes in the canonical encodings of the `encodings` crate:
+//
+// 1. Private keys are 32 byte little endian scalars and public keys 32 byte compressed points.
+// 2. Proofs use the 66 byte versioned wire format of [`SimpleSchnorrProof`].
+// 3. Proofs are made with the transcript of [`SimpleSchnorrProof::create_new_transcript`], so a
+// proof made in the browser verifies with the Rust library and vice versa.
+
+/// Sample a new private key as 32 little endian bytes
+pub fn generate_private_key() -> Vec<u8> {
+    Scalar::random(&mut rng()).encode(Endianness::Little)
+}
+
+/// Derive the compressed public key `k*G` of a private key
+pub fn public_key(private_key: &[u8]) -> Result<Vec<u8>, Error> {
+    let private_key = decode_private_key(private_key)?;
+    Ok((private_key * RISTRETTO_BASEPOINT_POINT).encode())
+}
+
+/// Prove knowledge of a private key, returning the encoded proof
+pub fn prove(private_key: &[u8]) -> Result<Vec<u8>, Error> {
+    let private_key = decode_private_key(private_key)?;
+    let proof = SimpleSchnorrProof::generate_proof(
+        &private_key,
+        &mut SimpleSchnorrProof::create_new_transcript(),
+    );
+    Ok(proof.to_bytes())
+}
+
+/// Verify an encoded proof of knowledge of the private key of a compressed public key
+pub fn verify(public_key: &[u8], proof: &[u8]) -> Result<(), Error> {
+    let public_key = RistrettoPoint::decode(public_key).map_err(Error::InvalidEncoding)?;
+    let mut proof = SimpleSchnorrProof::from_bytes(proof).map_err(Error::Proof)?;
+    proof
+        .verify_proof(
+            &public_key,
+            &mut SimpleSchnorrProof::create_new_transcript(),
+        )
+        .map(|_| ())
+        .map_err(Error::Proof)
+}
+
+// Decode a private key, rejecting non-canonical scalars
+fn decode_private_key(bytes: &[u8]) -> Result<Scalar, Error> {
+    Scalar::decode(bytes, Endianness::Little).map_err(Error::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_made_from_bytes_verifies_against_the_public_key() {
+        let private_key = generate_private_key();
+        let public_key = public_key(&private_key).unwrap();
+        let proof = prove(&private_key).unwrap();
+        assert!(verify(&public_key, &proof).is_ok());
+
+        // The proof doesn't verify for another key
+        let other_key = super::public_key(&generate_private_key()).unwrap();
+        assert!(matches!(
+            verify(&other_key, &proof),
+            Err(Error::Proof(merlin_example::Error::ProofMismatch(_, _)))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_keys_and_proofs_are_rejected() {
+        assert_eq!(
+            prove(&[0xff; 32]),
+            Err(Error::InvalidEncoding(encodings::Error::NonCanonicalScalar))
+        );
+        let public_key = public_key(&generate_private_key()).unwrap();
+        assert!(matches!(
+            verify(&public_key, &[0; 65]),
+            Err(Error::Proof(merlin_example::Error::InvalidProofEncoding(_)))
+        ));
+        assert!(matches!(
+            verify(&[0xff; 32], &prove(&generate_private_key()).unwrap()),
+            Err(Error::InvalidEncoding(_))
+        ));
+    }
+}
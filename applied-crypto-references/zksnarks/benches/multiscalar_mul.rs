@@ -0,0 +1,35 @@
+//! Comparison of evaluating polynomials at encrypted powers term by term and with Pippenger's method
+
+use bls12_381::{G1Projective, Scalar};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ff::Field;
+use zksnarks_example::{multiscalar_mul, naive_multiscalar_mul};
+
+fn bench_multiscalar_mul(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let mut group = c.benchmark_group("multiscalar_mul");
+    group.sample_size(10);
+    for degree in [64, 256, 1024, 4096] {
+        // Encrypted powers G1*s^i of a secret s and random coefficients
+        let s = Scalar::random(&mut rng);
+        let powers: Vec<G1Projective> = (0..=degree)
+            .scan(Scalar::one(), |power, _| {
+                let point = G1Projective::generator() * *power;
+                *power *= s;
+                Some(point)
+            })
+            .collect();
+        let coefficients: Vec<Scalar> = (0..=degree).map(|_| Scalar::random(&mut rng)).collect();
+
+        group.bench_with_input(BenchmarkId::new("naive", degree), &degree, |b, _| {
+            b.iter(|| naive_multiscalar_mul(&powers, &coefficients))
+        });
+        group.bench_with_input(BenchmarkId::new("pippenger", degree), &degree, |b, _| {
+            b.iter(|| multiscalar_mul(&powers, &coefficients))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_multiscalar_mul);
+criterion_main!(benches);
@@ -0,0 +1,28 @@
+//! Comparison of multiplying polynomials term by term and with the FFT
+
+use bls12_381::Scalar;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ff::Field;
+use zksnarks_example::{fft_polynomial_mul, naive_polynomial_mul};
+
+fn bench_polynomial_mul(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let mut group = c.benchmark_group("polynomial_mul");
+    group.sample_size(10);
+    for len in [16, 32, 64, 128, 256, 1024] {
+        // Two random factors with the same number of coefficients
+        let a: Vec<Scalar> = (0..len).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<Scalar> = (0..len).map(|_| Scalar::random(&mut rng)).collect();
+
+        group.bench_with_input(BenchmarkId::new("naive", len), &len, |bencher, _| {
+            bencher.iter(|| naive_polynomial_mul(&a, &b))
+        });
+        group.bench_with_input(BenchmarkId::new("fft", len), &len, |bencher, _| {
+            bencher.iter(|| fft_polynomial_mul(&a, &b))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_polynomial_mul);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+//! Proof generation and verification of the encrypted zkSNARK and KZG openings over increasing
+//! polynomial degrees
+
+use bls12_381::Scalar;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ff::Field;
+use zksnarks_example::{KzgSetup, Polynomial, Root, VerifierTranscript};
+
+fn bench_encrypted_zksnark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encrypted_zksnark");
+    group.sample_size(10);
+    for degree in [4, 16, 64] {
+        // Roots x + i with half of them public
+        let roots = (1..=degree)
+            .map(|i| Root::try_from((1, i)).unwrap())
+            .collect();
+        let polynomial = Polynomial::new(roots, degree as usize / 2).unwrap();
+        let verifier_transcript = VerifierTranscript::new(&polynomial);
+        let proof = polynomial.generate_response(&verifier_transcript);
+
+        group.bench_with_input(BenchmarkId::new("prove", degree), &degree, |b, _| {
+            b.iter(|| polynomial.generate_response(&verifier_transcript))
+        });
+        group.bench_with_input(BenchmarkId::new("verify", degree), &degree, |b, _| {
+            b.iter(|| verifier_transcript.verify_proof(&proof))
+        });
+    }
+    group.finish();
+}
+
+fn bench_kzg(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let mut group = c.benchmark_group("kzg");
+    group.sample_size(10);
+    for degree in [16, 64, 256] {
+        let setup = KzgSetup::new(degree);
+        let coefficients: Vec<Scalar> = (0..=degree).map(|_| Scalar::random(&mut rng)).collect();
+        let point = Scalar::random(&mut rng);
+        let commitment = setup.commit(&coefficients).unwrap();
+        let opening = setup.open(&coefficients, &point).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("commit", degree), &degree, |b, _| {
+            b.iter(|| setup.commit(&coefficients))
+        });
+        group.bench_with_input(BenchmarkId::new("open", degree), &degree, |b, _| {
+            b.iter(|| setup.open(&coefficients, &point))
+        });
+        group.bench_with_input(BenchmarkId::new("verify", degree), &degree, |b, _| {
+            b.iter(|| setup.verify_opening(&commitment, &point, &opening))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encrypted_zksnark, bench_kzg);
+criterion_main!(benches);
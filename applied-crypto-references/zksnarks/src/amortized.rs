@@ -0,0 +1,182 @@
+//! Verification of many KZG evaluation proofs about the same statements, reusing the work which
+//! only depends on the statement
+
+use crate::kzg::{EvaluationProof, EvaluationStatement, KzgSetup};
+use bls12_381::{multi_miller_loop, G2Affine, G2Prepared, Gt};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+// A KZG evaluation proof for the statement f(z) = y about the commitment C is checked with
+// pair(C - G1*y, G2) == pair(Q, G2*tau - G2*z), where Q is the proof. Only Q differs between proofs
+// of the same statement, so a verifier checking many proofs about the same model, range or
+// statement shape can compute everything else once:
+//
+// 1. The statement is hashed into a digest which keys the cache.
+// 2. On the first proof of a statement, the left pairing pair(C - G1*y, G2) is computed and the
+// point G2*tau - G2*z is prepared for the Miller loop, i.e. its line coefficients are
+// precomputed.
+// 3. Every proof of the statement then costs a single Miller loop with the prepared point and a
+// final exponentiation, instead of two full pairings.
+//
+// The cache holds at most a fixed number of statements and is emptied when it fills up, so a
+// stream of distinct statements can't make it grow without bound.
+
+// Domain separator for hashing a statement into its cache key
+const STATEMENT_DIGEST_DOMAIN_SEP: &[u8] = b"KZG_EVALUATION_STATEMENT";
+
+/// Default number of statements held by an [`AmortizedVerifier`]
+pub const DEFAULT_STATEMENT_CACHE_LEN: usize = 1024;
+
+/// Hash an evaluation statement into the digest identifying it
+pub fn statement_digest(statement: &EvaluationStatement) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(STATEMENT_DIGEST_DOMAIN_SEP);
+    hasher.update(statement.commitment.to_bytes());
+    hasher.update(statement.point.to_bytes());
+    hasher.update(statement.value.to_bytes());
+    hasher.finalize().into()
+}
+
+// Precomputed verification work which only depends on the statement
+#[derive(Clone, Debug)]
+struct PreparedStatement {
+    // pair(C - G1*y, G2)
+    commitment_pairing: Gt,
+    // G2*tau - G2*z prepared for the Miller loop
+    point_key: G2Prepared,
+}
+
+/// Verifier caching the per-statement part of KZG evaluation checks
+#[derive(Clone, Debug)]
+pub struct AmortizedVerifier<'a> {
+    setup: &'a KzgSetup,
+    // Prepared statements keyed by their digest
+    statements: HashMap<[u8; 32], PreparedStatement>,
+    // Maximum number of statements held before the cache is emptied
+    capacity: usize,
+    // Number of verifications which found their statement in the cache
+    hits: usize,
+}
+
+impl<'a> AmortizedVerifier<'a> {
+    /// Create a verifier holding up to [`DEFAULT_STATEMENT_CACHE_LEN`] statements
+    pub fn new(setup: &'a KzgSetup) -> Self {
+        Self::with_capacity(setup, DEFAULT_STATEMENT_CACHE_LEN)
+    }
+
+    /// Create a verifier holding up to `capacity` statements
+    pub fn with_capacity(setup: &'a KzgSetup, capacity: usize) -> Self {
+        Self {
+            setup,
+            statements: HashMap::new(),
+            capacity: capacity.max(1),
+            hits: 0,
+        }
+    }
+
+    /// Get the number of statements currently cached
+    pub fn num_cached_statements(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// Get the number of verifications which reused a cached statement
+    pub fn get_cache_hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Verify a public evaluation statement, reusing earlier work on the same statement
+    pub fn verify_evaluation(
+        &mut self,
+        statement: &EvaluationStatement,
+        proof: &EvaluationProof,
+    ) -> bool {
+        let digest = statement_digest(statement);
+        if self.statements.contains_key(&digest) {
+            self.hits += 1;
+        } else {
+            if self.statements.len() >= self.capacity {
+                self.statements.clear();
+            }
+            let (commitment_key, point_key) = self.setup.statement_keys(statement);
+            self.statements.insert(
+                digest,
+                PreparedStatement {
+                    commitment_pairing: bls12_381::pairing(&commitment_key, &G2Affine::generator()),
+                    point_key: G2Prepared::from(point_key),
+                },
+            );
+        }
+        let prepared = &self.statements[&digest];
+        let proof_pairing = multi_miller_loop(&[(&proof.get_quotient(), &prepared.point_key)])
+            .final_exponentiation();
+        proof_pairing == prepared.commitment_pairing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::Scalar;
+
+    // Prove f(x) = 3 + 2x + x^2 evaluates to 38 at 5
+    fn statement_and_proof(setup: &KzgSetup) -> (EvaluationStatement, EvaluationProof) {
+        let coefficients = vec![Scalar::from(3u64), Scalar::from(2u64), Scalar::one()];
+        let commitment = setup.commit(&coefficients).unwrap();
+        let point = Scalar::from(5u64);
+        setup
+            .open(&coefficients, &point)
+            .unwrap()
+            .into_evaluation(commitment, point)
+    }
+
+    #[test]
+    fn test_repeated_statements_reuse_cached_work() {
+        let setup = KzgSetup::new(4);
+        let (statement, proof) = statement_and_proof(&setup);
+        let mut verifier = AmortizedVerifier::new(&setup);
+        for _ in 0..3 {
+            assert!(verifier.verify_evaluation(&statement, &proof));
+        }
+        assert_eq!(verifier.num_cached_statements(), 1);
+        assert_eq!(verifier.get_cache_hits(), 2);
+
+        // A cached statement doesn't make false statements or other proofs pass
+        let false_statement = EvaluationStatement {
+            value: Scalar::from(39u64),
+            ..statement
+        };
+        assert!(!verifier.verify_evaluation(&false_statement, &proof));
+        let other_proof = setup
+            .prove_evaluation(
+                &[Scalar::one()],
+                &EvaluationStatement {
+                    commitment: setup.commit(&[Scalar::one()]).unwrap(),
+                    point: statement.point,
+                    value: Scalar::one(),
+                },
+            )
+            .unwrap();
+        assert!(!verifier.verify_evaluation(&statement, &other_proof));
+        assert_eq!(verifier.num_cached_statements(), 2);
+    }
+
+    #[test]
+    fn test_full_cache_is_emptied() {
+        let setup = KzgSetup::new(4);
+        let (statement, proof) = statement_and_proof(&setup);
+        let mut verifier = AmortizedVerifier::with_capacity(&setup, 1);
+        assert!(verifier.verify_evaluation(&statement, &proof));
+        let other_statement = EvaluationStatement {
+            point: Scalar::from(6u64),
+            ..statement
+        };
+        assert!(!verifier.verify_evaluation(&other_statement, &proof));
+        assert_eq!(verifier.num_cached_statements(), 1);
+        assert!(verifier.verify_evaluation(&statement, &proof));
+        assert_eq!(verifier.get_cache_hits(), 0);
+        assert_ne!(
+            statement_digest(&statement),
+            statement_digest(&other_statement)
+        );
+    }
+}
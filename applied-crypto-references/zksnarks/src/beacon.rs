@@ -0,0 +1,305 @@
+//! Verified public randomness beacon rounds, e.g. from drand, mixed into verifier challenges and
+//! KZG setups
+
+use crate::{error::Error, KzgSetup};
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    pairing, G1Affine, G2Affine, G2Projective, Scalar,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+// A prover deriving Fiat-Shamir challenges from its own messages can grind: it retries slightly
+// different messages until it finds challenges it can answer, which is cheap whenever the
+// challenge space or the number of checked samples is small. Likewise the last contributor to a
+// setup ceremony can retry its contribution until the result suits it. Mixing in randomness which
+// is published only after the prover or contributor has committed removes that freedom.
+//
+// drand publishes such randomness in rounds. The beacon network holds a threshold BLS key whose
+// public key is a G1 point, and round r is a BLS signature on G2 over
+//
+// - chained scheme: SHA256(signature of round r - 1 || r as 8 big endian bytes)
+// - unchained scheme: SHA256(r as 8 big endian bytes)
+//
+// hashed to G2 with the standard BLS signature domain separation tag. The signature verifies if
+// e(public key, H(m)) == e(G1 generator, signature) and the round's randomness is
+// SHA256(signature). Since nobody can produce the signature before the threshold of nodes signs
+// the round, a verifier only has to check that the round was published after the prover's
+// commitments, e.g. from drand's fixed round schedule.
+//
+// For setups, the beacon randomness b is the final contribution: every power tau^i of the secret
+// is multiplied by b^i, turning the secret into tau*b. The result is deterministic, so anyone can
+// recompute it from the previous setup and the round.
+
+// Hash to curve domain separation tag of drand's BLS signatures on G2
+const DRAND_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+// Domain separator for mixing beacon randomness into challenges
+const BEACON_CHALLENGE_DOMAIN_SEP: &[u8] = b"BEACON_CHALLENGE";
+
+// Domain separator for deriving setup contributions from beacon randomness
+const BEACON_SETUP_DOMAIN_SEP: &[u8] = b"BEACON_SETUP";
+
+/// Message a beacon signs in every round
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BeaconScheme {
+    /// Rounds sign the previous round's signature and the round number
+    Chained,
+    /// Rounds sign only the round number
+    Unchained,
+}
+
+/// Round published by a beacon
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BeaconRound {
+    /// Round number
+    pub round: u64,
+    /// Compressed G2 signature of the round
+    pub signature: Vec<u8>,
+    /// Compressed G2 signature of the previous round, only needed by chained beacons
+    pub previous_signature: Vec<u8>,
+}
+
+// Round in drand's HTTP API representation
+#[derive(Deserialize)]
+struct DrandRound {
+    round: u64,
+    signature: String,
+    #[serde(default)]
+    previous_signature: String,
+}
+
+impl BeaconRound {
+    /// Parse a round returned by drand's HTTP API
+    pub fn from_drand_json(json: &str) -> Result<Self, Error> {
+        let round: DrandRound =
+            serde_json::from_str(json).map_err(|e| Error::InvalidBeaconRound(e.to_string()))?;
+        let decode =
+            |hex: &str| hex::decode(hex).map_err(|e| Error::InvalidBeaconRound(e.to_string()));
+        Ok(Self {
+            round: round.round,
+            signature: decode(&round.signature)?,
+            previous_signature: decode(&round.previous_signature)?,
+        })
+    }
+}
+
+/// Randomness of a beacon round whose signature was verified
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BeaconRandomness {
+    round: u64,
+    value: [u8; 32],
+}
+
+impl BeaconRandomness {
+    /// Get the round the randomness was published in
+    pub fn get_round(&self) -> u64 {
+        self.round
+    }
+
+    /// Get the randomness, e.g. to append it to a transcript
+    pub fn get_value(&self) -> [u8; 32] {
+        self.value
+    }
+
+    // Hash the randomness along with a domain separator and input into a scalar
+    fn hash_to_scalar(&self, domain_sep: &[u8], input: &[u8]) -> Scalar {
+        let mut wide = [0; 64];
+        for (counter, half) in wide.chunks_mut(32).enumerate() {
+            let mut hasher = Sha256::new();
+            hasher.update(domain_sep);
+            hasher.update([counter as u8]);
+            hasher.update(self.round.to_be_bytes());
+            hasher.update(self.value);
+            hasher.update(input);
+            half.copy_from_slice(&hasher.finalize());
+        }
+        Scalar::from_bytes_wide(&wide)
+    }
+
+    /// Derive a verifier challenge from a digest of the prover's messages and the randomness
+    pub fn mix_challenge(&self, transcript_digest: &[u8]) -> Scalar {
+        self.hash_to_scalar(BEACON_CHALLENGE_DOMAIN_SEP, transcript_digest)
+    }
+}
+
+/// Beacon network identified by its public key
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BeaconChain {
+    public_key: G1Affine,
+    scheme: BeaconScheme,
+}
+
+impl BeaconChain {
+    /// Create a beacon from its G1 public key and scheme
+    pub fn new(public_key: G1Affine, scheme: BeaconScheme) -> Self {
+        Self { public_key, scheme }
+    }
+
+    /// Get the point a round's signature signs
+    pub fn round_message(&self, round: &BeaconRound) -> G2Affine {
+        let mut hasher = Sha256::new();
+        if self.scheme == BeaconScheme::Chained {
+            hasher.update(&round.previous_signature);
+        }
+        hasher.update(round.round.to_be_bytes());
+        <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+            hasher.finalize(),
+            DRAND_DST,
+        )
+        .into()
+    }
+
+    /// Verify a round's signature, returning its randomness
+    pub fn verify_round(&self, round: &BeaconRound) -> Result<BeaconRandomness, Error> {
+        let signature = <[u8; 96]>::try_from(round.signature.as_slice())
+            .ok()
+            .and_then(|bytes| Option::from(G2Affine::from_compressed(&bytes)))
+            .ok_or(Error::InvalidBeaconSignature(round.round))?;
+        if pairing(&self.public_key, &self.round_message(round))
+            != pairing(&G1Affine::generator(), &signature)
+        {
+            return Err(Error::InvalidBeaconSignature(round.round));
+        }
+        Ok(BeaconRandomness {
+            round: round.round,
+            value: Sha256::digest(&round.signature).into(),
+        })
+    }
+}
+
+impl KzgSetup {
+    /// Apply beacon randomness as the final contribution to the reference string
+    pub fn apply_beacon(&self, randomness: &BeaconRandomness) -> Self {
+        self.rerandomize(&randomness.hash_to_scalar(BEACON_SETUP_DOMAIN_SEP, &[]))
+    }
+}
+
+/// Client fetching rounds from a drand HTTP endpoint
+pub struct BeaconClient {
+    http: reqwest::Client,
+    // Base url of the chain, e.g. https://api.drand.sh/<chain hash>
+    chain_url: String,
+}
+
+impl BeaconClient {
+    /// Create a client for the chain at `chain_url`
+    pub fn new(chain_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            chain_url: chain_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Fetch a round and verify it against the chain's public key
+    pub async fn fetch_round(
+        &self,
+        chain: &BeaconChain,
+        round: u64,
+    ) -> Result<(BeaconRound, BeaconRandomness), Error> {
+        let json = self
+            .http
+            .get(format!("{}/public/{}", self.chain_url, round))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::BeaconRequest(e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| Error::BeaconRequest(e.to_string()))?;
+        let round = BeaconRound::from_drand_json(&json)?;
+        let randomness = chain.verify_round(&round)?;
+        Ok((round, randomness))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::G1Projective;
+    use ff::Field;
+
+    // Local beacon standing in for a drand network
+    fn sign_round(
+        chain: &BeaconChain,
+        secret: &Scalar,
+        round: u64,
+        previous_signature: &[u8],
+    ) -> BeaconRound {
+        let mut round = BeaconRound {
+            round,
+            signature: vec![],
+            previous_signature: previous_signature.to_vec(),
+        };
+        let signature = G2Projective::from(chain.round_message(&round)) * secret;
+        round.signature = G2Affine::from(signature).to_compressed().to_vec();
+        round
+    }
+
+    #[test]
+    fn test_beacon_rounds_verify_only_under_their_chain() {
+        let secret = Scalar::random(&mut rand::thread_rng());
+        let chain = BeaconChain::new(
+            (G1Projective::generator() * secret).into(),
+            BeaconScheme::Chained,
+        );
+        let first = sign_round(&chain, &secret, 1, &[]);
+        let second = sign_round(&chain, &secret, 2, &first.signature);
+        let randomness = chain.verify_round(&second).unwrap();
+        assert_eq!(randomness.get_round(), 2);
+        assert_ne!(randomness, chain.verify_round(&first).unwrap());
+
+        // Rounds can't be renumbered, re-chained or verified under another key
+        let renumbered = BeaconRound {
+            round: 3,
+            ..second.clone()
+        };
+        let rechained = BeaconRound {
+            previous_signature: vec![],
+            ..second.clone()
+        };
+        let unchained = BeaconChain::new(chain.public_key, BeaconScheme::Unchained);
+        assert!(chain.verify_round(&renumbered).is_err());
+        assert!(chain.verify_round(&rechained).is_err());
+        assert!(unchained.verify_round(&second).is_err());
+
+        let json = format!(
+            r#"{{"round":2,"randomness":"00","signature":"{}","previous_signature":"{}"}}"#,
+            hex::encode(&second.signature),
+            hex::encode(&first.signature)
+        );
+        assert_eq!(BeaconRound::from_drand_json(&json).unwrap(), second);
+    }
+
+    #[test]
+    fn test_beacon_randomness_rerandomizes_challenges_and_setups() {
+        let secret = Scalar::random(&mut rand::thread_rng());
+        let chain = BeaconChain::new(
+            (G1Projective::generator() * secret).into(),
+            BeaconScheme::Unchained,
+        );
+        let first = chain
+            .verify_round(&sign_round(&chain, &secret, 7, &[]))
+            .unwrap();
+        let second = chain
+            .verify_round(&sign_round(&chain, &secret, 8, &[]))
+            .unwrap();
+        assert_ne!(
+            first.mix_challenge(b"proof"),
+            second.mix_challenge(b"proof")
+        );
+        assert_ne!(first.mix_challenge(b"proof"), first.mix_challenge(b"other"));
+
+        // The contributed setup is a different but equally valid reference string
+        let setup = KzgSetup::new(4);
+        let contributed = setup.apply_beacon(&first);
+        assert_ne!(contributed, setup);
+        assert_eq!(contributed, setup.apply_beacon(&first));
+        let coefficients = [Scalar::from(3u64), Scalar::from(2u64), Scalar::one()];
+        let point = Scalar::from(5u64);
+        let commitment = contributed.commit(&coefficients).unwrap();
+        let opening = contributed.open(&coefficients, &point).unwrap();
+        assert!(contributed.verify_opening(&commitment, &point, &opening));
+        assert!(!setup.verify_opening(&commitment, &point, &opening));
+    }
+}
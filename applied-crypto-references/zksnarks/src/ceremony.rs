@@ -0,0 +1,279 @@
+//! Simulation of a multi-party powers-of-tau ceremony producing a KZG reference string
+
+use crate::{error::Error, kzg::KzgSetup, msm::multiscalar_mul};
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use encodings::{ProofScheme, SecretScalar, SecurityLevel};
+use ff::Field;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+// Anyone who knows the secret tau behind a KZG reference string can open commitments to any value,
+// so a reference string generated by a single party is only as trustworthy as that party. A
+// powers-of-tau ceremony spreads the trust over many contributors:
+//
+// 1. The ceremony starts from the public reference string for tau = 1, i.e. every power is G1.
+// 2. Each contributor picks a secret s, multiplies every power tau^i by s^i to turn the secret
+// into tau*s, publishes the new reference string with a proof of the update and discards s.
+// 3. The final secret is the product of all contributions, which stays unknown as long as a single
+// contributor discarded theirs. Nobody has to trust any particular contributor.
+//
+// Contributions are public, so anyone can check the whole chain. For an update with proof
+// (G1*s, G2*s) from reference string (P_i, G2*tau) to (P'_i, G2*tau') the verifier checks that
+//
+// * the contributor knows s, with a Schnorr proof bound to the previous reference string so a
+// contribution can't be copied from elsewhere in the ceremony
+// * G1*s and G2*s hide the same s: e(G1*s, G2) == e(G1, G2*s)
+// * the secret was multiplied by s: e(P'_1, G2) == e(P_1, G2*s)
+// * G2*tau' matches the new secret: e(G1, G2*tau') == e(P'_1, G2)
+// * every power is the previous one times tau': e(P'_(i+1), G2) == e(P'_i, G2*tau'), checked for
+// all i at once with a random linear combination of the powers
+// * the secret isn't zero and the reference string still starts at G1
+//
+// A contribution of s = 1 passes these checks but adds nothing, which is why the ceremony relies
+// on at least one honest contributor rather than on every contribution being useful.
+
+// Domain separator for deriving contribution proof challenges
+const CONTRIBUTION_DOMAIN_SEP: &[u8] = b"CEREMONY_CONTRIBUTION";
+
+/// Proof that a reference string was updated by multiplying its secret with a known scalar
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ContributionProof {
+    // Contribution multiplied by the G1 generator
+    secret_g1: G1Affine,
+    // Contribution multiplied by the G2 generator
+    secret_g2: G2Affine,
+    // Schnorr proof of knowledge of the contribution
+    nonce_commitment: G1Affine,
+    response: Scalar,
+}
+
+impl ContributionProof {
+    /// Get the contribution multiplied by the G1 and G2 generators
+    pub fn get_public_contribution(&self) -> (G1Affine, G2Affine) {
+        (self.secret_g1, self.secret_g2)
+    }
+}
+
+/// Reference string produced by a contributor along with the proof of the update
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Contribution {
+    /// Updated reference string
+    pub setup: KzgSetup,
+    /// Proof the reference string extends the previous one
+    pub proof: ContributionProof,
+}
+
+/// Powers-of-tau ceremony run by a sequence of contributors
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ceremony {
+    // Public reference string for the secret 1 the ceremony starts from
+    initial: KzgSetup,
+    contributions: Vec<Contribution>,
+}
+
+impl Ceremony {
+    /// Start a ceremony for polynomials up to `max_degree`, which is at least 1
+    pub fn new(max_degree: usize) -> Self {
+        Self {
+            initial: KzgSetup::from_secret(&Scalar::one(), max_degree.max(1)),
+            contributions: Vec::new(),
+        }
+    }
+
+    /// Get the reference string of the latest contribution
+    pub fn get_setup(&self) -> &KzgSetup {
+        self.contributions
+            .last()
+            .map_or(&self.initial, |contribution| &contribution.setup)
+    }
+
+    /// Get the contributions made so far
+    pub fn get_contributions(&self) -> &[Contribution] {
+        &self.contributions
+    }
+
+    /// Contribute a random secret which is overwritten with zeros once the reference string is
+    /// updated
+    pub fn contribute<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> &Contribution {
+        let secret = loop {
+            let secret = SecretScalar::new(Scalar::random(&mut *rng));
+            if !bool::from(secret.get_scalar().is_zero()) {
+                break secret;
+            }
+        };
+        let nonce = SecretScalar::new(Scalar::random(&mut *rng));
+        let contribution = contribute(self.get_setup(), secret.get_scalar(), nonce.get_scalar());
+        self.add_contribution(contribution)
+            .expect("honest contributions extend the reference string")
+    }
+
+    /// Add a contribution made elsewhere if it extends the latest reference string
+    pub fn add_contribution(&mut self, contribution: Contribution) -> Result<&Contribution, Error> {
+        if !verify_contribution(self.get_setup(), &contribution) {
+            return Err(Error::InvalidContribution(self.contributions.len()));
+        }
+        self.contributions.push(contribution);
+        Ok(self.contributions.last().expect("contribution was added"))
+    }
+
+    /// Check every contribution extends the one before it, starting from the initial reference
+    /// string
+    pub fn verify(&self) -> Result<(), Error> {
+        let mut previous = &self.initial;
+        for (i, contribution) in self.contributions.iter().enumerate() {
+            if !verify_contribution(previous, contribution) {
+                return Err(Error::InvalidContribution(i));
+            }
+            previous = &contribution.setup;
+        }
+        Ok(())
+    }
+}
+
+// The ceremony runs every contributor in one process, which only makes sense for demonstrations
+impl ProofScheme for Ceremony {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Educational;
+}
+
+// Update a reference string with a secret contribution and prove the update
+pub(crate) fn contribute(previous: &KzgSetup, secret: &Scalar, nonce: &Scalar) -> Contribution {
+    let secret_g1 = G1Affine::from(G1Projective::generator() * secret);
+    let nonce_commitment = G1Affine::from(G1Projective::generator() * nonce);
+    let challenge = contribution_challenge(previous, &secret_g1, &nonce_commitment);
+    Contribution {
+        setup: previous.rerandomize(secret),
+        proof: ContributionProof {
+            secret_g1,
+            secret_g2: G2Affine::from(G2Projective::generator() * secret),
+            nonce_commitment,
+            response: nonce + challenge * secret,
+        },
+    }
+}
+
+/// Check a contribution updates `previous` with the secret its proof shows knowledge of
+pub fn verify_contribution(previous: &KzgSetup, contribution: &Contribution) -> bool {
+    let (setup, proof) = (&contribution.setup, &contribution.proof);
+    let g1 = G1Affine::generator();
+    let g2 = G2Affine::generator();
+    if setup.powers.len() != previous.powers.len()
+        || setup.powers.len() < 2
+        || setup.powers[0] != G1Projective::generator()
+        || bool::from(proof.secret_g1.is_identity())
+    {
+        return false;
+    }
+
+    let challenge = contribution_challenge(previous, &proof.secret_g1, &proof.nonce_commitment);
+    let knows_secret = G1Projective::generator() * proof.response
+        == proof.nonce_commitment + G1Projective::from(proof.secret_g1) * challenge;
+
+    // Random linear combination of P'_1, .., P'_n against one of P'_0, .., P'_(n-1)
+    let mut rng = rand::thread_rng();
+    let weights: Vec<Scalar> = (1..setup.powers.len())
+        .map(|_| Scalar::random(&mut rng))
+        .collect();
+    let shifted = multiscalar_mul(&setup.powers[1..], &weights);
+    let unshifted = multiscalar_mul(&setup.powers, &weights);
+
+    let (tau_g1, previous_tau_g1) = (
+        G1Affine::from(setup.powers[1]),
+        G1Affine::from(previous.powers[1]),
+    );
+    knows_secret
+        && pairings_match(&proof.secret_g1, &g2, &g1, &proof.secret_g2)
+        && pairings_match(&tau_g1, &g2, &previous_tau_g1, &proof.secret_g2)
+        && pairings_match(&g1, &setup.tau_g2, &tau_g1, &g2)
+        && pairings_match(&shifted.into(), &g2, &unshifted.into(), &setup.tau_g2)
+}
+
+// Check e(a, b) == e(c, d) with a single final exponentiation
+fn pairings_match(a: &G1Affine, b: &G2Affine, c: &G1Affine, d: &G2Affine) -> bool {
+    multi_miller_loop(&[(a, &G2Prepared::from(*b)), (&-c, &G2Prepared::from(*d))])
+        .final_exponentiation()
+        == Gt::identity()
+}
+
+// Derive the challenge of a contribution's proof of knowledge, bound to the reference string it
+// extends
+fn contribution_challenge(
+    previous: &KzgSetup,
+    secret_g1: &G1Affine,
+    nonce_commitment: &G1Affine,
+) -> Scalar {
+    let mut wide = [0; 64];
+    for (counter, half) in wide.chunks_mut(32).enumerate() {
+        let mut hasher = Sha256::new();
+        hasher.update(CONTRIBUTION_DOMAIN_SEP);
+        hasher.update([counter as u8]);
+        hasher.update(G1Affine::from(previous.powers[1]).to_compressed());
+        hasher.update(previous.tau_g2.to_compressed());
+        hasher.update(secret_g1.to_compressed());
+        hasher.update(nonce_commitment.to_compressed());
+        half.copy_from_slice(&hasher.finalize());
+    }
+    Scalar::from_bytes_wide(&wide)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ceremony_combines_every_contribution() {
+        let mut ceremony = Ceremony::new(4);
+        let secrets = [Scalar::from(3u64), Scalar::from(5u64), Scalar::from(7u64)];
+        for secret in &secrets {
+            let contribution = contribute(ceremony.get_setup(), secret, &Scalar::from(11u64));
+            ceremony.add_contribution(contribution).unwrap();
+        }
+        ceremony.contribute(&mut rand::thread_rng());
+        assert_eq!(ceremony.get_contributions().len(), 4);
+        assert!(ceremony.verify().is_ok());
+
+        // Without the random contribution the secret is the product of the known ones
+        let known = &ceremony.get_contributions()[2].setup;
+        assert_eq!(*known, KzgSetup::from_secret(&Scalar::from(105u64), 4));
+
+        // The result is an ordinary reference string
+        let setup = ceremony.get_setup();
+        let coefficients = [Scalar::from(3u64), Scalar::from(2u64), Scalar::one()];
+        let commitment = setup.commit(&coefficients).unwrap();
+        let opening = setup.open(&coefficients, &Scalar::from(5u64)).unwrap();
+        assert!(setup.verify_opening(&commitment, &Scalar::from(5u64), &opening));
+    }
+
+    #[test]
+    fn test_invalid_contributions_are_rejected() {
+        let mut ceremony = Ceremony::new(3);
+        ceremony.contribute(&mut rand::thread_rng());
+        let previous = ceremony.get_setup().clone();
+        let honest = contribute(&previous, &Scalar::from(9u64), &Scalar::from(4u64));
+
+        // A reference string which doesn't match the proven secret
+        let mismatched = Contribution {
+            setup: previous.rerandomize(&Scalar::from(10u64)),
+            ..honest.clone()
+        };
+        // Powers which aren't consecutive powers of one secret
+        let mut unstructured = honest.clone();
+        unstructured.setup.powers[3] = unstructured.setup.powers[2];
+        // A zero contribution would make the secret known to everyone
+        let zero = contribute(&previous, &Scalar::zero(), &Scalar::from(4u64));
+        for contribution in [mismatched, unstructured, zero] {
+            assert_eq!(
+                ceremony.add_contribution(contribution).err(),
+                Some(Error::InvalidContribution(1))
+            );
+        }
+        assert!(ceremony.add_contribution(honest).is_ok());
+
+        // Tampering with a contribution already in the chain is caught when it is verified
+        ceremony.contributions[0].setup = ceremony.contributions[0]
+            .setup
+            .rerandomize(&Scalar::from(2u64));
+        assert_eq!(ceremony.verify(), Err(Error::InvalidContribution(0)));
+    }
+}
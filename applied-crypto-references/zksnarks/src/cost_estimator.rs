@@ -0,0 +1,215 @@
+//! Machine specific cost estimates for KZG proving and verification from calibration benchmarks
+
+use crate::error::Error;
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use ff::Field;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    hint::black_box,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+// How long a proof takes depends mostly on a handful of primitives: a KZG commitment to a degree d
+// polynomial is a multi-scalar multiplication (MSM) of d + 1 points, an opening is another MSM for
+// the quotient, verification is two pairings, and transcripts and content addresses hash their
+// inputs. The cost of each primitive differs by an order of magnitude between a server and an
+// edge device, so rather than hard-coding timings, the estimator reads them from a calibration
+// profile:
+//
+// 1. A calibration run times each primitive on the local machine, repeating it enough times to
+// average out noise, and records the mean time per operation in nanoseconds.
+// 2. The profile is saved as JSON so it only has to be measured once per machine.
+// 3. The estimator counts how many of each primitive an operation performs and multiplies by the
+// calibrated timings.
+//
+// Without a calibration the default profile holds rough timings of a modern desktop machine.
+
+/// Mean time per operation of each primitive in nanoseconds
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CalibrationProfile {
+    /// Multiplication of a G1 point by a full width scalar
+    pub g1_scalar_mul_ns: f64,
+    /// Addition of two G1 points
+    pub g1_add_ns: f64,
+    /// Multiplication of a G2 point by a full width scalar
+    pub g2_scalar_mul_ns: f64,
+    /// Single pairing including the final exponentiation
+    pub pairing_ns: f64,
+    /// Multiplication of two scalars
+    pub scalar_mul_ns: f64,
+    /// Hashing a single byte with SHA-256
+    pub sha256_byte_ns: f64,
+}
+
+impl Default for CalibrationProfile {
+    fn default() -> Self {
+        Self {
+            g1_scalar_mul_ns: 150_000.0,
+            g1_add_ns: 1_000.0,
+            g2_scalar_mul_ns: 500_000.0,
+            pairing_ns: 1_500_000.0,
+            scalar_mul_ns: 50.0,
+            sha256_byte_ns: 3.0,
+        }
+    }
+}
+
+// Time an operation over `iterations` runs and return the mean in nanoseconds
+fn mean_ns<T>(iterations: usize, mut operation: impl FnMut() -> T) -> f64 {
+    let iterations = iterations.max(1);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(operation());
+    }
+    start.elapsed().as_nanos() as f64 / iterations as f64
+}
+
+impl CalibrationProfile {
+    /// Benchmark every primitive on the local machine, running each `iterations` times
+    pub fn calibrate(iterations: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        let scalar = Scalar::random(&mut rng);
+        let other_scalar = Scalar::random(&mut rng);
+        let g1 = G1Projective::generator() * scalar;
+        let other_g1 = G1Projective::generator() * other_scalar;
+        let g2 = G2Projective::generator() * scalar;
+        let (g1_affine, g2_affine) = (G1Affine::from(g1), G2Affine::from(g2));
+        let block = [0x5a; 1024];
+
+        Self {
+            g1_scalar_mul_ns: mean_ns(iterations, || black_box(g1) * black_box(scalar)),
+            g1_add_ns: mean_ns(iterations, || black_box(g1) + black_box(other_g1)),
+            g2_scalar_mul_ns: mean_ns(iterations, || black_box(g2) * black_box(scalar)),
+            pairing_ns: mean_ns(iterations, || {
+                bls12_381::pairing(black_box(&g1_affine), black_box(&g2_affine))
+            }),
+            scalar_mul_ns: mean_ns(iterations, || black_box(scalar) * black_box(other_scalar)),
+            sha256_byte_ns: mean_ns(iterations, || Sha256::digest(black_box(block)))
+                / block.len() as f64,
+        }
+    }
+
+    /// Load a profile saved by [`CalibrationProfile::save`]
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let json =
+            fs::read_to_string(path).map_err(|e| Error::InvalidCalibration(e.to_string()))?;
+        serde_json::from_str(&json).map_err(|e| Error::InvalidCalibration(e.to_string()))
+    }
+
+    /// Save the profile as JSON
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::InvalidCalibration(e.to_string()))?;
+        fs::write(path, json).map_err(|e| Error::InvalidCalibration(e.to_string()))
+    }
+}
+
+/// Estimator of KZG proving and verification times from a calibration profile
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CostEstimator {
+    profile: CalibrationProfile,
+}
+
+impl CostEstimator {
+    /// Create an estimator using the timings of a calibration profile
+    pub fn new(profile: CalibrationProfile) -> Self {
+        Self { profile }
+    }
+
+    /// Get the calibration profile the estimates are based on
+    pub fn get_profile(&self) -> &CalibrationProfile {
+        &self.profile
+    }
+
+    // Time of an MSM over `num_points` points computed as independent scalar multiplications
+    fn msm_ns(&self, num_points: usize) -> f64 {
+        num_points as f64 * (self.profile.g1_scalar_mul_ns + self.profile.g1_add_ns)
+    }
+
+    /// Estimate the time to create a reference string supporting polynomials up to `max_degree`
+    pub fn estimate_setup(&self, max_degree: usize) -> Duration {
+        let powers = (max_degree + 1) as f64;
+        to_duration(
+            powers * (self.profile.g1_scalar_mul_ns + self.profile.scalar_mul_ns)
+                + self.profile.g2_scalar_mul_ns,
+        )
+    }
+
+    /// Estimate the time to commit to a polynomial of `degree`
+    pub fn estimate_commit(&self, degree: usize) -> Duration {
+        to_duration(self.msm_ns(degree + 1))
+    }
+
+    /// Estimate the time to open a polynomial of `degree` at a point, i.e. to evaluate it, divide
+    /// out the point and commit to the quotient
+    pub fn estimate_open(&self, degree: usize) -> Duration {
+        to_duration(2.0 * degree as f64 * self.profile.scalar_mul_ns + self.msm_ns(degree))
+    }
+
+    /// Estimate the time to verify an evaluation proof
+    pub fn estimate_verify_evaluation(&self) -> Duration {
+        to_duration(
+            2.0 * self.profile.pairing_ns
+                + self.profile.g1_scalar_mul_ns
+                + self.profile.g2_scalar_mul_ns,
+        )
+    }
+
+    /// Estimate the time to hash `len` bytes with SHA-256
+    pub fn estimate_hash(&self, len: usize) -> Duration {
+        to_duration(len as f64 * self.profile.sha256_byte_ns)
+    }
+}
+
+// Convert an estimate in nanoseconds into a duration
+fn to_duration(ns: f64) -> Duration {
+    Duration::from_nanos(ns.max(0.0).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibrated_profile_round_trips_and_drives_estimates() {
+        let profile = CalibrationProfile::calibrate(2);
+        assert!(profile.pairing_ns > profile.g1_add_ns);
+        assert!(profile.g1_scalar_mul_ns > profile.scalar_mul_ns);
+
+        let path = std::env::temp_dir().join(format!("calibration-{}.json", std::process::id()));
+        profile.save(&path).unwrap();
+        assert_eq!(CalibrationProfile::load(&path), Ok(profile));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            CalibrationProfile::load(&path),
+            Err(Error::InvalidCalibration(_))
+        ));
+
+        let estimator = CostEstimator::new(profile);
+        assert!(estimator.estimate_commit(1024) > estimator.estimate_commit(16));
+        assert!(estimator.estimate_open(16) < estimator.estimate_commit(1024));
+        assert!(estimator.estimate_verify_evaluation() > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_estimates_scale_with_the_profile() {
+        let profile = CalibrationProfile::default();
+        let slow_profile = CalibrationProfile {
+            g1_scalar_mul_ns: 2.0 * profile.g1_scalar_mul_ns,
+            g1_add_ns: 2.0 * profile.g1_add_ns,
+            ..profile
+        };
+        let (estimator, slow_estimator) = (
+            CostEstimator::new(profile),
+            CostEstimator::new(slow_profile),
+        );
+        assert_eq!(
+            slow_estimator.estimate_commit(100),
+            2 * estimator.estimate_commit(100)
+        );
+        assert_eq!(estimator.estimate_hash(1000), Duration::from_nanos(3000));
+    }
+}
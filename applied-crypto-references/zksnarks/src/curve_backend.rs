@@ -0,0 +1,161 @@
+//! Abstraction over the prime order groups the polynomial examples can be run over
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, traits::Identity,
+    traits::VartimeMultiscalarMul,
+};
+use rand::{CryptoRng, RngCore};
+use std::{
+    fmt::Debug,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+// Evaluating a polynomial at encrypted powers of a secret only needs a few things from a curve:
+// a prime order group of points with a generator, its scalar field and a way to compute the sum
+// `c_1*P_1 + .. + c_n*P_n` of a multi-scalar multiplication (MSM). Checking the evaluations needs
+// a pairing on top, which is what [`PairingGroup`](crate::PairingGroup) adds.
+//
+// * BLS12-381 and BN254 are pairing-friendly, so the whole encrypted proof can be run over them.
+// Their points are the points of the first source group G1.
+// * Ristretto has no pairing, but is much faster for everything up to the verification, e.g. for
+// commitments to the encrypted evaluations which are opened with sigma protocols instead.
+//
+// A new curve, e.g. Pallas or Vesta, can be slotted in by implementing this trait for a marker
+// type. The MSM defaults to the naive sum of scalar multiplications and polynomial multiplication
+// to the term by term product. Both should be overridden with faster implementations where the
+// curve library or the scalar field allows them, e.g. the FFT over BLS12-381's scalar field.
+
+/// Prime order group of curve points with its scalar field
+pub trait CurveBackend: Clone + Copy + Debug + Eq {
+    /// Human readable name of the curve
+    const NAME: &'static str;
+
+    /// Element of the scalar field
+    type Scalar: Copy
+        + Debug
+        + Eq
+        + From<u64>
+        + Add<Output = Self::Scalar>
+        + Sub<Output = Self::Scalar>
+        + Mul<Output = Self::Scalar>
+        + Neg<Output = Self::Scalar>;
+
+    /// Point of the prime order group
+    type Point: Copy
+        + Debug
+        + Eq
+        + Add<Output = Self::Point>
+        + Neg<Output = Self::Point>
+        + Mul<Self::Scalar, Output = Self::Point>;
+
+    /// Generator of the group
+    fn generator() -> Self::Point;
+
+    /// Identity of the group
+    fn identity() -> Self::Point;
+
+    /// Sample a uniformly random scalar
+    fn random_scalar<R: CryptoRng + RngCore>(rng: &mut R) -> Self::Scalar;
+
+    /// Invert a scalar, or return `None` for zero
+    fn invert_scalar(scalar: &Self::Scalar) -> Option<Self::Scalar>;
+
+    /// Compute the sum of `scalars[i] * points[i]`, ignoring terms without a counterpart
+    fn multiscalar_mul(points: &[Self::Point], scalars: &[Self::Scalar]) -> Self::Point {
+        points
+            .iter()
+            .zip(scalars.iter())
+            .fold(Self::identity(), |acc, (point, scalar)| {
+                acc + *point * *scalar
+            })
+    }
+    /// Multiply two polynomials given by coefficients in ascending order of degree
+    fn polynomial_mul(a: &[Self::Scalar], b: &[Self::Scalar]) -> Vec<Self::Scalar> {
+        if a.is_empty() || b.is_empty() {
+            return Vec::new();
+        }
+        let mut product = vec![Self::Scalar::from(0); a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                product[i + j] = product[i + j] + *x * *y;
+            }
+        }
+        product
+    }
+}
+
+/// The Ristretto group over Curve25519
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ristretto;
+
+impl CurveBackend for Ristretto {
+    const NAME: &'static str = "Ristretto";
+
+    type Scalar = curve25519_dalek::scalar::Scalar;
+    type Point = RistrettoPoint;
+
+    fn generator() -> Self::Point {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn identity() -> Self::Point {
+        RistrettoPoint::identity()
+    }
+
+    fn random_scalar<R: CryptoRng + RngCore>(rng: &mut R) -> Self::Scalar {
+        curve25519_dalek::scalar::Scalar::random(rng)
+    }
+
+    fn invert_scalar(scalar: &Self::Scalar) -> Option<Self::Scalar> {
+        (*scalar != curve25519_dalek::scalar::Scalar::ZERO).then(|| scalar.invert())
+    }
+
+    fn multiscalar_mul(points: &[Self::Point], scalars: &[Self::Scalar]) -> Self::Point {
+        let len = points.len().min(scalars.len());
+        RistrettoPoint::vartime_multiscalar_mul(&scalars[..len], &points[..len])
+    }
+}
+
+/// Map a signed integer into the scalar field of a curve
+pub fn scalar_from_i64<C: CurveBackend>(value: i64) -> C::Scalar {
+    let scalar = C::Scalar::from(value.unsigned_abs());
+    if value < 0 {
+        -scalar
+    } else {
+        scalar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bls12_381, Bn254};
+
+    fn check_multiscalar_mul<C: CurveBackend>() {
+        let mut rng = rand::thread_rng();
+        let points: Vec<C::Point> = (1..=40u64)
+            .map(|i| C::generator() * C::Scalar::from(i))
+            .collect();
+        let mut scalars: Vec<C::Scalar> = (0..40).map(|_| C::random_scalar(&mut rng)).collect();
+        let expected = points
+            .iter()
+            .zip(scalars.iter())
+            .fold(C::identity(), |acc, (point, scalar)| acc + *point * *scalar);
+        assert_eq!(C::multiscalar_mul(&points, &scalars), expected);
+
+        // Extra scalars are ignored and negated integers map to negated scalars
+        scalars.push(C::Scalar::from(7));
+        assert_eq!(C::multiscalar_mul(&points, &scalars), expected);
+        assert_eq!(
+            C::generator() * scalar_from_i64::<C>(-5) + C::generator() * C::Scalar::from(5),
+            C::identity()
+        );
+    }
+
+    #[test]
+    fn test_multiscalar_mul_matches_the_naive_sum_on_all_curves() {
+        check_multiscalar_mul::<Ristretto>();
+        check_multiscalar_mul::<Bls12_381>();
+        check_multiscalar_mul::<Bn254>();
+    }
+}
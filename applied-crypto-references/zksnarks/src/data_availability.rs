@@ -0,0 +1,258 @@
+//! Data availability sampling using erasure coded data committed with KZG
+
+use crate::{
+    error::Error,
+    kzg::{evaluate, interpolate, KzgCommitment, KzgOpening, KzgSetup},
+};
+use bls12_381::Scalar;
+use encodings::{Profile, ProofScheme, SecurityLevel};
+use rand::seq::index::sample;
+
+// Data availability sampling lets a counterparty check that a large piece of data (such as model
+// weights or a dataset) was published in full while only downloading a few random pieces of it.
+//
+// 1. The publisher treats the k data chunks as the coefficients of a polynomial f(x) of degree
+// k - 1 and evaluates it at the 2k points 0, 1, .., 2k - 1. This is a Reed-Solomon erasure code:
+// any k of the 2k evaluations are enough to recover f(x) and therefore the data.
+// 2. The publisher commits to f(x) with KZG and publishes the commitment.
+// 3. A sampler asks for a handful of random evaluations and checks each against the commitment
+// with a KZG opening proof.
+//
+// To make the data unrecoverable a publisher has to withhold more than half of the evaluations,
+// so every random sample has at least a 1/2 chance of hitting a withheld evaluation. A publisher
+// withholding data therefore passes s samples with probability at most 2^-s.
+
+// Number of evaluations published per data chunk
+const EXPANSION_FACTOR: usize = 2;
+
+/// Public commitment to erasure coded data
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DataAvailabilityCommitment {
+    // KZG commitment to the data polynomial
+    commitment: KzgCommitment,
+    // Number of data chunks committed to
+    data_len: usize,
+}
+
+impl DataAvailabilityCommitment {
+    /// Number of erasure coded evaluations that can be sampled
+    pub fn extended_len(&self) -> usize {
+        self.data_len * EXPANSION_FACTOR
+    }
+
+    /// Recover the original data chunks from any `data_len` distinct samples, each of which must
+    /// open the commitment
+    pub fn reconstruct(
+        &self,
+        setup: &KzgSetup,
+        samples: &[DataSample],
+    ) -> Result<Vec<Scalar>, Error> {
+        let mut points: Vec<(Scalar, Scalar)> = Vec::with_capacity(self.data_len);
+        let mut seen = vec![false; self.extended_len()];
+        for data_sample in samples {
+            if data_sample.index >= self.extended_len() {
+                return Err(Error::SampleOutOfRange(data_sample.index));
+            }
+            let point = Scalar::from(data_sample.index as u64);
+            if !setup.verify_opening(&self.commitment, &point, &data_sample.opening) {
+                return Err(Error::InvalidSample(data_sample.index));
+            }
+            if !seen[data_sample.index] {
+                seen[data_sample.index] = true;
+                points.push((point, data_sample.opening.get_value()));
+            }
+        }
+        if points.len() < self.data_len {
+            return Err(Error::InsufficientSamples(points.len(), self.data_len));
+        }
+        points.truncate(self.data_len);
+        Ok(interpolate(&points))
+    }
+}
+
+/// Single erasure coded evaluation with a proof that it matches the committed data
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DataSample {
+    // Index of the evaluation within the extended data
+    index: usize,
+    // KZG opening of the data polynomial at the index
+    opening: KzgOpening,
+}
+
+/// Publisher-side erasure coded data
+#[derive(Clone, Debug)]
+pub struct ErasureCodedData {
+    // Data chunks which double as the coefficients of the data polynomial
+    coefficients: Vec<Scalar>,
+    // Public commitment to the data
+    commitment: DataAvailabilityCommitment,
+}
+
+impl ErasureCodedData {
+    /// Erasure code and commit to data chunks. The setup must support polynomials of degree
+    /// `data.len() - 1`.
+    pub fn new(setup: &KzgSetup, data: &[Scalar]) -> Result<Self, Error> {
+        if data.is_empty() {
+            return Err(Error::NoData);
+        }
+        let commitment = setup.commit(data)?;
+        Ok(Self {
+            coefficients: data.to_vec(),
+            commitment: DataAvailabilityCommitment {
+                commitment,
+                data_len: data.len(),
+            },
+        })
+    }
+
+    /// Get the public commitment to the data
+    pub fn get_commitment(&self) -> DataAvailabilityCommitment {
+        self.commitment
+    }
+
+    /// Get all erasure coded evaluations of the data
+    pub fn get_extended_data(&self) -> Vec<Scalar> {
+        (0..self.commitment.extended_len())
+            .map(|i| evaluate(&self.coefficients, &Scalar::from(i as u64)))
+            .collect()
+    }
+
+    /// Answer a sampler's request for the evaluations at the given indices
+    pub fn respond(&self, setup: &KzgSetup, indices: &[usize]) -> Result<Vec<DataSample>, Error> {
+        indices
+            .iter()
+            .map(|&index| {
+                if index >= self.commitment.extended_len() {
+                    return Err(Error::SampleOutOfRange(index));
+                }
+                let opening = setup.open(&self.coefficients, &Scalar::from(index as u64))?;
+                Ok(DataSample { index, opening })
+            })
+            .collect()
+    }
+}
+
+/// Sampler that checks the availability of committed data by requesting random evaluations
+#[derive(Clone, Debug)]
+pub struct DataAvailabilitySampler {
+    // Commitment to the data being sampled
+    commitment: DataAvailabilityCommitment,
+    // Indices requested from the publisher
+    requested_indices: Vec<usize>,
+}
+
+impl DataAvailabilitySampler {
+    /// Choose `num_samples` distinct random evaluations to request from the publisher
+    pub fn new(commitment: DataAvailabilityCommitment, num_samples: usize) -> Self {
+        let extended_len = commitment.extended_len();
+        let requested_indices = sample(
+            &mut rand::thread_rng(),
+            extended_len,
+            num_samples.min(extended_len),
+        )
+        .into_vec();
+        Self {
+            commitment,
+            requested_indices,
+        }
+    }
+
+    /// Choose enough samples to catch a withholding publisher with the soundness of a profile.
+    /// Each sample contributes one bit of soundness.
+    pub fn for_profile(commitment: DataAvailabilityCommitment, profile: Profile) -> Self {
+        Self::new(commitment, profile.repetitions(1))
+    }
+
+    /// Get the indices to request from the publisher
+    pub fn get_requested_indices(&self) -> &[usize] {
+        &self.requested_indices
+    }
+
+    /// Verify the publisher answered every requested index with a valid opening
+    pub fn verify_samples(&self, setup: &KzgSetup, samples: &[DataSample]) -> bool {
+        samples.len() == self.requested_indices.len()
+            && samples
+                .iter()
+                .zip(self.requested_indices.iter())
+                .all(|(data_sample, &index)| {
+                    data_sample.index == index
+                        && setup.verify_opening(
+                            &self.commitment.commitment,
+                            &Scalar::from(index as u64),
+                            &data_sample.opening,
+                        )
+                })
+    }
+}
+
+impl ProofScheme for DataAvailabilitySampler {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_data() -> Vec<Scalar> {
+        (1..=8u64).map(|i| Scalar::from(i * 1000 + 7)).collect()
+    }
+
+    #[test]
+    fn test_random_samples_verify_against_commitment() {
+        let setup = KzgSetup::new(8);
+        let data = ErasureCodedData::new(&setup, &test_data()).unwrap();
+        let sampler = DataAvailabilitySampler::new(data.get_commitment(), 6);
+        let samples = data
+            .respond(&setup, sampler.get_requested_indices())
+            .unwrap();
+        assert!(sampler.verify_samples(&setup, &samples));
+
+        // Profiles needing more samples than there are evaluations check every evaluation
+        let profile_sampler =
+            DataAvailabilitySampler::for_profile(data.get_commitment(), Profile::Interactive);
+        assert_eq!(profile_sampler.get_requested_indices().len(), 16);
+
+        // Samples from different data don't verify
+        let mut other_data = test_data();
+        other_data[3] += Scalar::one();
+        let other_data = ErasureCodedData::new(&setup, &other_data).unwrap();
+        let other_samples = other_data
+            .respond(&setup, sampler.get_requested_indices())
+            .unwrap();
+        assert!(!sampler.verify_samples(&setup, &other_samples));
+    }
+
+    #[test]
+    fn test_data_reconstructs_from_any_half_of_extended_data() {
+        let setup = KzgSetup::new(8);
+        let data = ErasureCodedData::new(&setup, &test_data()).unwrap();
+        let commitment = data.get_commitment();
+        assert_eq!(data.get_extended_data().len(), 16);
+
+        // Only use the erasure coded evaluations beyond the original data length
+        let indices: Vec<usize> = (8..16).collect();
+        let samples = data.respond(&setup, &indices).unwrap();
+        assert_eq!(
+            commitment.reconstruct(&setup, &samples).unwrap(),
+            test_data()
+        );
+
+        // Too few distinct samples can't recover the data
+        assert_eq!(
+            commitment.reconstruct(&setup, &samples[..7]).err(),
+            Some(Error::InsufficientSamples(7, 8))
+        );
+        assert_eq!(
+            data.respond(&setup, &[16]).err(),
+            Some(Error::SampleOutOfRange(16))
+        );
+
+        // A sample carrying another index's opening is rejected instead of changing the data
+        let mut tampered = samples.clone();
+        tampered[2].opening = samples[3].opening;
+        assert_eq!(
+            commitment.reconstruct(&setup, &tampered).err(),
+            Some(Error::InvalidSample(10))
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Delegated KZG proving where a weak device outsources multi-scalar multiplications to an
+//! untrusted helper
+
+use crate::{
+    error::Error,
+    kzg::{divide_by_linear, evaluate},
+    KzgCommitment, KzgOpening, KzgSetup,
+};
+use bls12_381::{G1Affine, G1Projective, Scalar};
+use ff::Field;
+
+// Committing to a polynomial and opening it both end in a multi-scalar multiplication (MSM) over
+// the reference string, which dominates proving time and is out of reach for many edge devices.
+// Everything else, i.e. evaluating the polynomial and dividing out the opened point, is a linear
+// number of field operations the device can do itself. The MSM is outsourced to a helper which
+// must learn nothing about the polynomial and can't get a wrong result accepted:
+//
+// 1. Masks: ahead of time, e.g. while the device is idle and charging or by a trusted dealer, the
+// device obtains random masking polynomials r together with their commitments R = G1*r(tau).
+// 2. Blinding: to commit to f, the device picks a random non-zero scalar rho and sends the helper
+// the two polynomials f + r1 and rho*f + r2. With r1 and r2 uniformly random, both look uniformly
+// random to the helper, whatever f is.
+// 3. Helper: the helper commits to both polynomials with the reference string and returns the
+// commitments D1 and D2.
+// 4. Unblinding: the device computes C = D1 - R1 and checks rho*C == D2 - R2. A helper returning
+// D1 + E1 and D2 + E2 passes the check only if E2 = rho*E1, which requires guessing rho.
+//
+// Openings delegate the commitment to the quotient polynomial the same way. Each mask must only be
+// used once, since two polynomials blinded with the same mask reveal their difference.
+
+/// Random masking polynomial along with its commitment, used once to blind a delegated commitment
+#[derive(Clone, Debug)]
+pub struct DelegationMask {
+    // Coefficients of the masking polynomial
+    coefficients: Vec<Scalar>,
+    // Commitment to the masking polynomial
+    commitment: G1Projective,
+}
+
+impl DelegationMask {
+    /// Generate a mask blinding polynomials of up to `len` coefficients. This needs a full MSM, so
+    /// it is meant to run ahead of time or on a trusted machine.
+    pub fn generate(setup: &KzgSetup, len: usize) -> Result<Self, Error> {
+        let mut rng = rand::thread_rng();
+        let coefficients: Vec<Scalar> = (0..len).map(|_| Scalar::random(&mut rng)).collect();
+        let commitment = setup.commit(&coefficients)?.get_point().into();
+        Ok(Self {
+            coefficients,
+            commitment,
+        })
+    }
+
+    /// Get the number of coefficients the mask blinds
+    pub fn len(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Check whether the mask has no coefficients
+    pub fn is_empty(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    // Blind a polynomial padded to the mask length
+    fn blind(&self, coefficients: &[Scalar], factor: &Scalar) -> Result<Vec<Scalar>, Error> {
+        if coefficients.len() > self.len() {
+            return Err(Error::MaskTooShort(self.len(), coefficients.len()));
+        }
+        Ok(self
+            .coefficients
+            .iter()
+            .enumerate()
+            .map(|(i, mask)| coefficients.get(i).map_or(*mask, |c| c * factor + mask))
+            .collect())
+    }
+}
+
+/// Blinded polynomials sent to the helper
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelegationRequest {
+    // f + r1
+    first: Vec<Scalar>,
+    // rho*f + r2
+    second: Vec<Scalar>,
+}
+
+/// Helper's commitments to the blinded polynomials of a [`DelegationRequest`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DelegationResponse {
+    /// Commitment to the first blinded polynomial
+    pub first: G1Affine,
+    /// Commitment to the second blinded polynomial
+    pub second: G1Affine,
+}
+
+impl KzgSetup {
+    /// Answer a delegation request as the helper by committing to both blinded polynomials
+    pub fn answer_delegation(
+        &self,
+        request: &DelegationRequest,
+    ) -> Result<DelegationResponse, Error> {
+        Ok(DelegationResponse {
+            first: self.commit(&request.first)?.get_point(),
+            second: self.commit(&request.second)?.get_point(),
+        })
+    }
+}
+
+/// Device state of a delegated commitment waiting for the helper's response
+#[derive(Clone, Debug)]
+pub struct PendingCommitment {
+    // Secret factor the second polynomial was scaled by
+    rho: Scalar,
+    // Commitments to the masks of the first and second polynomial
+    mask_commitments: (G1Projective, G1Projective),
+}
+
+impl PendingCommitment {
+    /// Blind a polynomial with two unused masks, returning the device state and the request for
+    /// the helper
+    pub fn new(
+        coefficients: &[Scalar],
+        masks: (DelegationMask, DelegationMask),
+    ) -> Result<(Self, DelegationRequest), Error> {
+        let mut rng = rand::thread_rng();
+        let rho = loop {
+            let rho = Scalar::random(&mut rng);
+            if !bool::from(rho.is_zero()) {
+                break rho;
+            }
+        };
+        let request = DelegationRequest {
+            first: masks.0.blind(coefficients, &Scalar::one())?,
+            second: masks.1.blind(coefficients, &rho)?,
+        };
+        let pending = Self {
+            rho,
+            mask_commitments: (masks.0.commitment, masks.1.commitment),
+        };
+        Ok((pending, request))
+    }
+
+    /// Unblind the helper's response into the commitment, rejecting incorrect responses
+    pub fn unblind(self, response: &DelegationResponse) -> Result<KzgCommitment, Error> {
+        let point = G1Projective::from(response.first) - self.mask_commitments.0;
+        if point * self.rho != G1Projective::from(response.second) - self.mask_commitments.1 {
+            return Err(Error::DelegationMismatch);
+        }
+        Ok(KzgCommitment {
+            point: point.into(),
+        })
+    }
+}
+
+/// Device state of a delegated opening waiting for the helper's commitment to the quotient
+#[derive(Clone, Debug)]
+pub struct PendingOpening {
+    // Value of the polynomial at the opened point
+    value: Scalar,
+    // Delegated commitment to the quotient polynomial
+    quotient: PendingCommitment,
+}
+
+impl PendingOpening {
+    /// Evaluate a polynomial at a point and blind the quotient with two unused masks, returning
+    /// the device state and the request for the helper
+    pub fn new(
+        coefficients: &[Scalar],
+        point: &Scalar,
+        masks: (DelegationMask, DelegationMask),
+    ) -> Result<(Self, DelegationRequest), Error> {
+        let quotient = divide_by_linear(coefficients, point);
+        let (quotient, request) = PendingCommitment::new(&quotient, masks)?;
+        let pending = Self {
+            value: evaluate(coefficients, point),
+            quotient,
+        };
+        Ok((pending, request))
+    }
+
+    /// Unblind the helper's response into the opening, rejecting incorrect responses
+    pub fn unblind(self, response: &DelegationResponse) -> Result<KzgOpening, Error> {
+        let proof = self.quotient.unblind(response)?;
+        Ok(KzgOpening {
+            value: self.value,
+            proof: proof.point,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masks(setup: &KzgSetup, len: usize) -> (DelegationMask, DelegationMask) {
+        (
+            DelegationMask::generate(setup, len).unwrap(),
+            DelegationMask::generate(setup, len).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_delegated_commitments_and_openings_match_local_proving() {
+        let setup = KzgSetup::new(8);
+        let coefficients: Vec<Scalar> = (1..=5u64).map(Scalar::from).collect();
+        let point = Scalar::from(7u64);
+
+        let (pending, request) = PendingCommitment::new(&coefficients, masks(&setup, 9)).unwrap();
+        assert_ne!(request.first[..5], coefficients[..]);
+        let response = setup.answer_delegation(&request).unwrap();
+        let commitment = pending.unblind(&response).unwrap();
+        assert_eq!(commitment, setup.commit(&coefficients).unwrap());
+
+        let (pending, request) =
+            PendingOpening::new(&coefficients, &point, masks(&setup, 8)).unwrap();
+        let opening = pending
+            .unblind(&setup.answer_delegation(&request).unwrap())
+            .unwrap();
+        assert_eq!(opening, setup.open(&coefficients, &point).unwrap());
+        assert!(setup.verify_opening(&commitment, &point, &opening));
+
+        assert!(matches!(
+            PendingCommitment::new(&coefficients, masks(&setup, 4)),
+            Err(Error::MaskTooShort(4, 5))
+        ));
+    }
+
+    #[test]
+    fn test_incorrect_helper_responses_are_rejected() {
+        let setup = KzgSetup::new(4);
+        let coefficients = [Scalar::from(3u64), Scalar::from(2u64)];
+        let (pending, request) = PendingCommitment::new(&coefficients, masks(&setup, 5)).unwrap();
+        let response = setup.answer_delegation(&request).unwrap();
+
+        // Shifting both commitments by the same error doesn't help without knowing rho
+        let error = G1Projective::generator();
+        let shifted = DelegationResponse {
+            first: (G1Projective::from(response.first) + error).into(),
+            second: (G1Projective::from(response.second) + error).into(),
+        };
+        let swapped = DelegationResponse {
+            first: response.second,
+            second: response.first,
+        };
+        for forged in [shifted, swapped] {
+            assert!(matches!(
+                pending.clone().unblind(&forged),
+                Err(Error::DelegationMismatch)
+            ));
+        }
+        assert!(pending.unblind(&response).is_ok());
+    }
+}
@@ -0,0 +1,178 @@
+//! Polynomials in coefficient form with addition, multiplication and division with remainder
+
+use std::ops::{Add, Mul};
+
+use crate::{curve_backend::CurveBackend, error::Error, pairing::Bls12_381};
+
+// A polynomial is stored as its coefficients in ascending order of degree, with trailing zero
+// coefficients trimmed so every polynomial has exactly one representation and its degree is the
+// index of its last coefficient. The zero polynomial has no coefficients.
+//
+// Division follows the schoolbook long division algorithm: the leading term of the remainder is
+// divided by the leading term of the divisor, which needs the inverse of the divisor's leading
+// coefficient, and the result times the divisor is subtracted until the remainder's degree drops
+// below the divisor's. Over a field this always succeeds for a nonzero divisor, and
+// `p = q*d + r` with `deg(r) < deg(d)` determines `q` and `r` uniquely.
+
+/// Polynomial with coefficients in the scalar field of a curve, the 381-bit prime field used by
+/// curve BLS12-381 unless another backend is chosen
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DensePolynomial<C: CurveBackend = Bls12_381> {
+    // Coefficients in ascending order of degree without trailing zeros
+    coefficients: Vec<C::Scalar>,
+}
+
+impl<C: CurveBackend> DensePolynomial<C> {
+    /// Create a polynomial from its coefficients in ascending order of degree
+    pub fn from_coefficients(mut coefficients: Vec<C::Scalar>) -> Self {
+        let zero = C::Scalar::from(0);
+        while coefficients.last() == Some(&zero) {
+            coefficients.pop();
+        }
+        Self { coefficients }
+    }
+
+    /// Get the zero polynomial
+    pub fn zero() -> Self {
+        Self {
+            coefficients: Vec::new(),
+        }
+    }
+
+    /// Check whether this is the zero polynomial
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Get the coefficients in ascending order of degree, without trailing zeros
+    pub fn get_coefficients(&self) -> &[C::Scalar] {
+        &self.coefficients
+    }
+
+    /// Get the degree of the polynomial, which is 0 for constants including zero
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    /// Evaluate the polynomial at a scalar using Horner's rule
+    pub fn evaluate(&self, x: &C::Scalar) -> C::Scalar {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(C::Scalar::from(0), |acc, c| acc * *x + *c)
+    }
+
+    /// Divide by a nonzero polynomial, returning the quotient and the remainder
+    pub fn div_rem(&self, divisor: &Self) -> Result<(Self, Self), Error> {
+        let leading = divisor.coefficients.last().ok_or(Error::ZeroDivisor)?;
+        let leading_inverse = C::invert_scalar(leading).ok_or(Error::ZeroDivisor)?;
+        if self.coefficients.len() < divisor.coefficients.len() {
+            return Ok((Self::zero(), self.clone()));
+        }
+
+        let divisor_len = divisor.coefficients.len();
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![C::Scalar::from(0); remainder.len() - divisor_len + 1];
+        for i in (0..quotient.len()).rev() {
+            // Cancel the leading term of the remainder
+            let factor = remainder[i + divisor_len - 1] * leading_inverse;
+            quotient[i] = factor;
+            for (j, c) in divisor.coefficients.iter().enumerate() {
+                remainder[i + j] = remainder[i + j] - factor * *c;
+            }
+        }
+        remainder.truncate(divisor_len - 1);
+        Ok((
+            Self::from_coefficients(quotient),
+            Self::from_coefficients(remainder),
+        ))
+    }
+}
+
+impl<'a, C: CurveBackend> Add<&'a DensePolynomial<C>> for &'a DensePolynomial<C> {
+    type Output = DensePolynomial<C>;
+
+    fn add(self, other: &'a DensePolynomial<C>) -> DensePolynomial<C> {
+        let (longer, shorter) = match self.coefficients.len() >= other.coefficients.len() {
+            true => (self, other),
+            false => (other, self),
+        };
+        let mut coefficients = longer.coefficients.clone();
+        for (sum, c) in coefficients.iter_mut().zip(&shorter.coefficients) {
+            *sum = *sum + *c;
+        }
+        DensePolynomial::from_coefficients(coefficients)
+    }
+}
+
+impl<'a, C: CurveBackend> Mul<&'a DensePolynomial<C>> for &'a DensePolynomial<C> {
+    type Output = DensePolynomial<C>;
+
+    fn mul(self, other: &'a DensePolynomial<C>) -> DensePolynomial<C> {
+        let coefficients = C::polynomial_mul(&self.coefficients, &other.coefficients);
+        DensePolynomial::from_coefficients(coefficients)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scalar_from_i64, Bn254, Ristretto};
+
+    fn polynomial<C: CurveBackend>(coefficients: &[i64]) -> DensePolynomial<C> {
+        DensePolynomial::from_coefficients(
+            coefficients
+                .iter()
+                .map(|c| scalar_from_i64::<C>(*c))
+                .collect(),
+        )
+    }
+
+    fn check_arithmetic<C: CurveBackend>() {
+        // (x^2 - 1) + (2x + 1) = x^2 + 2x and (x + 1)(x - 1) = x^2 - 1
+        let p = polynomial::<C>(&[-1, 0, 1]);
+        assert_eq!(&p + &polynomial(&[1, 2]), polynomial(&[0, 2, 1]));
+        assert_eq!(&polynomial::<C>(&[1, 1]) * &polynomial(&[-1, 1]), p);
+        assert_eq!(&p + &polynomial(&[1, 0, -1]), DensePolynomial::zero());
+        assert_eq!(p.evaluate(&C::Scalar::from(3)), C::Scalar::from(8));
+        assert_eq!(p.degree(), 2);
+
+        // x^3 + 2x + 5 = (x^2 - 1)(x) + (3x + 5)
+        let (quotient, remainder) = polynomial::<C>(&[5, 2, 0, 1]).div_rem(&p).unwrap();
+        assert_eq!(quotient, polynomial(&[0, 1]));
+        assert_eq!(remainder, polynomial(&[5, 3]));
+
+        // Dividing by a polynomial of larger degree leaves everything in the remainder
+        let (quotient, remainder) = polynomial::<C>(&[1, 1]).div_rem(&p).unwrap();
+        assert!(quotient.is_zero());
+        assert_eq!(remainder, polynomial(&[1, 1]));
+    }
+
+    #[test]
+    fn test_polynomial_arithmetic_on_all_backends() {
+        check_arithmetic::<Ristretto>();
+        check_arithmetic::<Bls12_381>();
+        check_arithmetic::<Bn254>();
+    }
+
+    #[test]
+    fn test_division_recovers_factors_and_rejects_zero_divisors() {
+        // Trailing zeros are trimmed, so the representation is unique
+        assert_eq!(polynomial::<Bls12_381>(&[1, 2, 0, 0]), polynomial(&[1, 2]));
+        assert_eq!(
+            polynomial::<Bls12_381>(&[1, 2]).div_rem(&polynomial(&[0, 0])),
+            Err(Error::ZeroDivisor)
+        );
+
+        // A product divided by either factor gives the other without remainder
+        let a = polynomial::<Bls12_381>(&[3, -1, 4, 1]);
+        let b = polynomial::<Bls12_381>(&[-5, 9, 2]);
+        let product = &a * &b;
+        assert_eq!(product.degree(), 5);
+        let (quotient, remainder) = product.div_rem(&b).unwrap();
+        assert_eq!(quotient, a);
+        assert!(remainder.is_zero());
+        let x = scalar_from_i64::<Bls12_381>(-7);
+        assert_eq!(product.evaluate(&x), a.evaluate(&x) * b.evaluate(&x));
+    }
+}
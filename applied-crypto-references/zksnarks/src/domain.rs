@@ -0,0 +1,201 @@
+//! Evaluation domains of roots of unity, vanishing polynomials and barycentric interpolation over
+//! the BLS12-381 scalar field
+
+use crate::error::Error;
+use bls12_381::Scalar;
+use ff::PrimeField;
+
+// Most polynomial protocols fix a set of points, the evaluation domain, at which a circuit's
+// constraints or a table's rows are placed. Choosing the domain as the n-th roots of unity
+// {1, w, w^2, .., w^(n-1)} where w^n = 1 makes many operations cheap:
+//
+// * The vanishing polynomial which is zero on the whole domain is simply Z(x) = x^n - 1, so it
+// can be evaluated with a single exponentiation instead of a product of n factors.
+// * Lagrange basis polynomials L_i(x), which are 1 at the i-th point and 0 at all others, can be
+// evaluated in barycentric form L_i(x) = Z(x) * v_i / (x - w^i) with the barycentric weights
+// v_i = 1 / prod_(j != i) (w^i - w^j), which for roots of unity simplify to v_i = w^i / n.
+//
+// The BLS12-381 scalar field has roots of unity of every power of two order up to 2^32, so
+// domains are rounded up to the next power of two.
+
+/// Multiplicative subgroup of the n-th roots of unity for a power of two n
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EvaluationDomain {
+    // Number of points n
+    size: usize,
+    // Primitive n-th root of unity w
+    generator: Scalar,
+}
+
+impl EvaluationDomain {
+    /// Create the smallest domain of roots of unity holding at least `min_size` points
+    pub fn new(min_size: usize) -> Result<Self, Error> {
+        let size = min_size.max(1).next_power_of_two();
+        let log_size = size.trailing_zeros();
+        if log_size > Scalar::S {
+            return Err(Error::DomainTooLarge(min_size));
+        }
+        // The field's 2^S root of unity raised to 2^(S - log n) has order n
+        let mut generator = Scalar::root_of_unity();
+        for _ in log_size..Scalar::S {
+            generator = generator.square();
+        }
+        Ok(Self { size, generator })
+    }
+
+    /// Get the number of points in the domain
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Get the primitive root of unity generating the domain
+    pub fn generator(&self) -> Scalar {
+        self.generator
+    }
+
+    /// Get the i-th point w^i of the domain
+    pub fn element(&self, index: usize) -> Scalar {
+        self.generator.pow_vartime(&[index as u64, 0, 0, 0])
+    }
+
+    /// Get every point of the domain in order
+    pub fn elements(&self) -> Vec<Scalar> {
+        let mut elements = Vec::with_capacity(self.size);
+        let mut element = Scalar::one();
+        for _ in 0..self.size {
+            elements.push(element);
+            element *= self.generator;
+        }
+        elements
+    }
+
+    /// Get the coefficients of the vanishing polynomial x^n - 1 in ascending order of degree
+    pub fn vanishing_polynomial(&self) -> Vec<Scalar> {
+        let mut coefficients = vec![Scalar::zero(); self.size + 1];
+        coefficients[0] = -Scalar::one();
+        coefficients[self.size] = Scalar::one();
+        coefficients
+    }
+
+    /// Evaluate the vanishing polynomial x^n - 1 at a point
+    pub fn evaluate_vanishing_polynomial(&self, x: &Scalar) -> Scalar {
+        x.pow_vartime(&[self.size as u64, 0, 0, 0]) - Scalar::one()
+    }
+
+    /// Get the barycentric weights w^i / n of the domain's points
+    pub fn barycentric_weights(&self) -> Vec<Scalar> {
+        let size_inverse = Scalar::from(self.size as u64).invert().unwrap();
+        self.elements()
+            .iter()
+            .map(|element| element * size_inverse)
+            .collect()
+    }
+
+    /// Evaluate every Lagrange basis polynomial of the domain at a point
+    pub fn evaluate_lagrange_basis(&self, x: &Scalar) -> Vec<Scalar> {
+        let elements = self.elements();
+        // Inside the domain the basis is 1 at the matching point and 0 everywhere else
+        if let Some(index) = elements.iter().position(|element| element == x) {
+            let mut basis = vec![Scalar::zero(); self.size];
+            basis[index] = Scalar::one();
+            return basis;
+        }
+        let vanishing = self.evaluate_vanishing_polynomial(x);
+        elements
+            .iter()
+            .zip(self.barycentric_weights().iter())
+            .map(|(element, weight)| vanishing * weight * (x - element).invert().unwrap())
+            .collect()
+    }
+
+    /// Evaluate the polynomial taking `values` on the domain's points at another point without
+    /// interpolating its coefficients
+    pub fn evaluate_interpolation(&self, values: &[Scalar], x: &Scalar) -> Result<Scalar, Error> {
+        if values.len() != self.size {
+            return Err(Error::DomainSizeMismatch(self.size, values.len()));
+        }
+        Ok(self
+            .evaluate_lagrange_basis(x)
+            .iter()
+            .zip(values.iter())
+            .fold(Scalar::zero(), |acc, (basis, value)| acc + basis * value))
+    }
+}
+
+/// Compute the barycentric weights 1 / prod_(j != i) (x_i - x_j) of an arbitrary set of distinct
+/// points
+pub fn barycentric_weights(points: &[Scalar]) -> Result<Vec<Scalar>, Error> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            let product = points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(Scalar::one(), |acc, (_, x_j)| acc * (x_i - x_j));
+            Option::from(product.invert()).ok_or(Error::RepeatedDomainPoint(i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kzg::{evaluate, interpolate};
+
+    #[test]
+    fn test_domain_points_are_roots_of_the_vanishing_polynomial() {
+        let domain = EvaluationDomain::new(5).unwrap();
+        assert_eq!(domain.size(), 8);
+        assert_eq!(domain.element(8), Scalar::one());
+        assert_ne!(domain.element(4), Scalar::one());
+
+        let vanishing = domain.vanishing_polynomial();
+        for element in domain.elements() {
+            assert_eq!(evaluate(&vanishing, &element), Scalar::zero());
+            assert_eq!(
+                domain.evaluate_vanishing_polynomial(&element),
+                Scalar::zero()
+            );
+        }
+        let x = Scalar::from(12345u64);
+        assert_eq!(
+            domain.evaluate_vanishing_polynomial(&x),
+            evaluate(&vanishing, &x)
+        );
+        assert_eq!(
+            EvaluationDomain::new((1 << 32) + 1).err(),
+            Some(Error::DomainTooLarge((1 << 32) + 1))
+        );
+    }
+
+    #[test]
+    fn test_barycentric_evaluation_matches_interpolation() {
+        let domain = EvaluationDomain::new(4).unwrap();
+        let values: Vec<Scalar> = (1..=4u64).map(|v| Scalar::from(v * v + 3)).collect();
+        let points: Vec<(Scalar, Scalar)> = domain
+            .elements()
+            .into_iter()
+            .zip(values.iter().copied())
+            .collect();
+        let coefficients = interpolate(&points);
+
+        for x in [Scalar::from(7u64), domain.element(2)] {
+            assert_eq!(
+                domain.evaluate_interpolation(&values, &x).unwrap(),
+                evaluate(&coefficients, &x)
+            );
+        }
+
+        // The general weights agree with the simplified roots of unity weights
+        assert_eq!(
+            barycentric_weights(&domain.elements()).unwrap(),
+            domain.barycentric_weights()
+        );
+        assert_eq!(
+            barycentric_weights(&[Scalar::one(), Scalar::one()]).err(),
+            Some(Error::RepeatedDomainPoint(0))
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! EIP-712 style structured statement hashing and secp256k1 signing for Ethereum interop
+
+use crate::error::Error;
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+// Attestations about proofs are often consumed by Ethereum contracts and wallets, which expect
+// signed messages in the EIP-712 "typed structured data" format rather than arbitrary bytes:
+//
+// 1. Every struct type has a type string such as `ProofStatement(bytes32 proofHash,...)`. Its
+// keccak256 hash is the type hash.
+// 2. A struct is hashed as `keccak256(typeHash || enc(field_1) || .. || enc(field_n))` where every
+// field is encoded as a 32 byte word. Dynamic values such as strings and bytes are replaced by
+// their keccak256 hash.
+// 3. A domain separator binds the signature to a particular application, version, chain and
+// contract so a signature can't be replayed elsewhere.
+// 4. The signed digest is `keccak256(0x19 || 0x01 || domainSeparator || structHash)`. The leading
+// bytes guarantee the digest can never collide with the hash of an Ethereum transaction.
+//
+// Signatures are secp256k1 ECDSA signatures in the `r || s || v` format accepted by the `ecrecover`
+// precompile, which recovers the signer's Ethereum address from the digest and signature.
+
+// Type string of the EIP-712 domain
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// Length of an Ethereum address
+pub const ADDRESS_LEN: usize = 20;
+
+/// Length of a signature in `r || s || v` format
+pub const ETH_SIGNATURE_LEN: usize = 65;
+
+// Hash bytes with keccak256
+fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    Keccak256::digest(bytes).into()
+}
+
+// Encode an integer as a 32 byte big endian word
+fn encode_u64(value: u64) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+// Encode an address as a 32 byte word padded on the left
+fn encode_address(address: &[u8; ADDRESS_LEN]) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[32 - ADDRESS_LEN..].copy_from_slice(address);
+    word
+}
+
+/// Struct which can be hashed and signed following EIP-712
+pub trait TypedStatement {
+    /// EIP-712 type string of the struct
+    const TYPE: &'static str;
+
+    /// Encode each field of the struct as a 32 byte word in the order of the type string
+    fn encode_fields(&self) -> Vec<[u8; 32]>;
+
+    /// Hash the struct together with its type hash
+    fn struct_hash(&self) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(keccak256(Self::TYPE.as_bytes()));
+        for field in self.encode_fields() {
+            hasher.update(field);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// Domain a statement signature is valid for
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Eip712Domain {
+    /// Name of the signing application
+    pub name: String,
+    /// Version of the signing application
+    pub version: String,
+    /// Chain id of the network signatures are valid on
+    pub chain_id: u64,
+    /// Address of the contract which verifies signatures
+    pub verifying_contract: [u8; ADDRESS_LEN],
+}
+
+impl Eip712Domain {
+    /// Compute the domain separator
+    pub fn separator(&self) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(keccak256(EIP712_DOMAIN_TYPE.as_bytes()));
+        hasher.update(keccak256(self.name.as_bytes()));
+        hasher.update(keccak256(self.version.as_bytes()));
+        hasher.update(encode_u64(self.chain_id));
+        hasher.update(encode_address(&self.verifying_contract));
+        hasher.finalize().into()
+    }
+
+    /// Compute the digest which is signed for a statement within this domain
+    pub fn digest<T: TypedStatement>(&self, statement: &T) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update([0x19, 0x01]);
+        hasher.update(self.separator());
+        hasher.update(statement.struct_hash());
+        hasher.finalize().into()
+    }
+}
+
+/// Statement that a proof was verified for a set of public inputs
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofStatement {
+    // keccak256 hash of the encoded proof
+    proof_hash: [u8; 32],
+    // keccak256 hash of the encoded public inputs
+    public_inputs_hash: [u8; 32],
+    // Unix timestamp of the verification
+    timestamp: u64,
+}
+
+impl ProofStatement {
+    /// Create a statement about an encoded proof and its encoded public inputs
+    pub fn new(proof: &[u8], public_inputs: &[u8], timestamp: u64) -> Self {
+        Self {
+            proof_hash: keccak256(proof),
+            public_inputs_hash: keccak256(public_inputs),
+            timestamp,
+        }
+    }
+}
+
+impl TypedStatement for ProofStatement {
+    const TYPE: &'static str =
+        "ProofStatement(bytes32 proofHash,bytes32 publicInputsHash,uint64 timestamp)";
+
+    fn encode_fields(&self) -> Vec<[u8; 32]> {
+        vec![
+            self.proof_hash,
+            self.public_inputs_hash,
+            encode_u64(self.timestamp),
+        ]
+    }
+}
+
+/// Compute the Ethereum address of a secp256k1 public key
+pub fn eth_address(verifying_key: &VerifyingKey) -> [u8; ADDRESS_LEN] {
+    let uncompressed = verifying_key.to_encoded_point(false);
+    let hash = keccak256(&uncompressed.as_bytes()[1..]);
+    let mut address = [0; ADDRESS_LEN];
+    address.copy_from_slice(&hash[32 - ADDRESS_LEN..]);
+    address
+}
+
+/// Signer of typed statements holding a secp256k1 key
+#[derive(Clone, Debug)]
+pub struct StatementSigner {
+    signing_key: SigningKey,
+}
+
+impl StatementSigner {
+    /// Create a signer from a 32 byte secp256k1 private key
+    pub fn from_bytes(private_key: &[u8]) -> Result<Self, Error> {
+        let signing_key =
+            SigningKey::from_slice(private_key).map_err(|_| Error::InvalidSigningKey)?;
+        Ok(Self { signing_key })
+    }
+
+    /// Create a signer with a random private key
+    pub fn random() -> Self {
+        Self {
+            signing_key: SigningKey::random(&mut rand::thread_rng()),
+        }
+    }
+
+    /// Get the Ethereum address of the signer
+    pub fn address(&self) -> [u8; ADDRESS_LEN] {
+        eth_address(self.signing_key.verifying_key())
+    }
+
+    /// Sign a statement within a domain, returning the signature as `r || s || v`
+    pub fn sign<T: TypedStatement>(
+        &self,
+        domain: &Eip712Domain,
+        statement: &T,
+    ) -> Result<[u8; ETH_SIGNATURE_LEN], Error> {
+        let (signature, recovery_id) = self
+            .signing_key
+            .sign_prehash_recoverable(&domain.digest(statement))
+            .map_err(|_| Error::InvalidSignature)?;
+        let mut bytes = [0; ETH_SIGNATURE_LEN];
+        bytes[..64].copy_from_slice(&signature.to_bytes());
+        bytes[64] = 27 + recovery_id.to_byte();
+        Ok(bytes)
+    }
+}
+
+/// Recover the address which signed a statement within a domain, as `ecrecover` would
+pub fn recover_signer<T: TypedStatement>(
+    domain: &Eip712Domain,
+    statement: &T,
+    signature: &[u8; ETH_SIGNATURE_LEN],
+) -> Result<[u8; ADDRESS_LEN], Error> {
+    let recovery_id = signature[64]
+        .checked_sub(27)
+        .and_then(RecoveryId::from_byte)
+        .ok_or(Error::InvalidSignature)?;
+    let signature = Signature::from_slice(&signature[..64]).map_err(|_| Error::InvalidSignature)?;
+    let verifying_key =
+        VerifyingKey::recover_from_prehash(&domain.digest(statement), &signature, recovery_id)
+            .map_err(|_| Error::InvalidSignature)?;
+    Ok(eth_address(&verifying_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn test_domain() -> Eip712Domain {
+        // Domain from the example in EIP-712
+        let mut verifying_contract = [0; ADDRESS_LEN];
+        verifying_contract.copy_from_slice(&hex_bytes("cccccccccccccccccccccccccccccccccccccccc"));
+        Eip712Domain {
+            name: "Ether Mail".to_string(),
+            version: "1".to_string(),
+            chain_id: 1,
+            verifying_contract,
+        }
+    }
+
+    #[test]
+    fn test_domain_separator_and_address_match_known_values() {
+        assert_eq!(
+            test_domain().separator().to_vec(),
+            hex_bytes("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+
+        // The address of the private key 1
+        let mut private_key = [0; 32];
+        private_key[31] = 1;
+        let signer = StatementSigner::from_bytes(&private_key).unwrap();
+        assert_eq!(
+            signer.address().to_vec(),
+            hex_bytes("7e5f4552091a69125d5dfcb7b8c2659029395bdf")
+        );
+        assert_eq!(
+            StatementSigner::from_bytes(&[0; 32]).err(),
+            Some(Error::InvalidSigningKey)
+        );
+    }
+
+    #[test]
+    fn test_signed_statements_recover_to_signer_address() {
+        let domain = test_domain();
+        let signer = StatementSigner::random();
+        let statement = ProofStatement::new(b"proof bytes", b"public inputs", 1_700_000_000);
+        let signature = signer.sign(&domain, &statement).unwrap();
+        assert_eq!(
+            recover_signer(&domain, &statement, &signature).unwrap(),
+            signer.address()
+        );
+
+        // A different statement or domain recovers a different address
+        let other_statement = ProofStatement::new(b"proof bytes", b"other inputs", 1_700_000_000);
+        assert_ne!(
+            recover_signer(&domain, &other_statement, &signature).unwrap(),
+            signer.address()
+        );
+        let other_domain = Eip712Domain {
+            chain_id: 5,
+            ..domain.clone()
+        };
+        assert_ne!(
+            recover_signer(&other_domain, &statement, &signature).unwrap(),
+            signer.address()
+        );
+
+        let mut invalid_signature = signature;
+        invalid_signature[64] = 26;
+        assert_eq!(
+            recover_signer(&domain, &statement, &invalid_signature),
+            Err(Error::InvalidSignature)
+        );
+    }
+}
@@ -1,8 +1,14 @@
 //! An example of ZkSnarks math for demonstration purposes, not intended for production use
 
-use crate::polynomial::Polynomial;
-use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use crate::{error::VerificationError, polynomial::Polynomial};
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use encodings::{ProofScheme, SecretScalar, SecurityLevel};
 use ff::Field;
+use subtle::ConstantTimeEq;
+use tracing::{instrument, trace};
+use zeroize::Zeroize;
 
 /// Collection of the prover's calculated curve points. These curve points
 /// are calculated by multiplying the polynomial coefficients by the verifier's
@@ -17,15 +23,32 @@ pub struct ProverTranscript {
     // Evaluation of the non-public roots of the prover's polynomial at the
     // verifier's challenge points
     hx_eval: G1Affine,
+    // Degree of the polynomial the prover evaluated
+    degree: usize,
+    // Leading coefficient of the prover's polynomial multiplied by the blinding scalar
+    leading_coefficient: Scalar,
+    // Evaluation of the prover's polynomial without its leading term at the verifier's degree
+    // bound challenge points
+    lower_eval: G1Affine,
 }
 
 impl ProverTranscript {
     // Create a new proof transcript
-    pub(crate) fn new(px_eval: G1Affine, px_powers_eval: G1Affine, hx_eval: G1Affine) -> Self {
+    pub(crate) fn new(
+        px_eval: G1Affine,
+        px_powers_eval: G1Affine,
+        hx_eval: G1Affine,
+        degree: usize,
+        leading_coefficient: Scalar,
+        lower_eval: G1Affine,
+    ) -> Self {
         Self {
             px_eval,
             px_powers_eval,
             hx_eval,
+            degree,
+            leading_coefficient,
+            lower_eval,
         }
     }
 
@@ -41,6 +64,21 @@ impl ProverTranscript {
     pub fn get_proof_values(&self) -> (G1Affine, G1Affine, G1Affine) {
         (self.px_eval, self.px_powers_eval, self.hx_eval)
     }
+
+    /// Get the degree of the polynomial the prover claims to have evaluated
+    pub fn get_degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Get the values binding the proof to the degree of the prover's polynomial
+    ///
+    /// # Returns
+    /// A tuple of the form (b*c_d, [`G1*b*alpha*(p(s) - c_d*s^d)`](G1Affine)) where c_d is the
+    /// leading coefficient of the polynomial, b the prover's blinding scalar and alpha the
+    /// verifier's secret degree bound scalar
+    pub fn get_degree_values(&self) -> (Scalar, G1Affine) {
+        (self.leading_coefficient, self.lower_eval)
+    }
 }
 
 /// Verifier's transcript providing a secret scalar raised to powers equal to the degree of the
@@ -65,26 +103,53 @@ pub struct VerifierTranscript {
     // challenge points through the pairing operation. This point is calculated using the
     // extension field of the BLS12-381 curve.
     power_verification_key: G2Affine,
+    // Degree of the target polynomial the encrypted powers were created for
+    degree: usize,
+    // Encrypted powers below the target degree multiplied by a secret degree bound scalar. The
+    // highest power is left out, so the prover can only evaluate polynomials of lower degree at
+    // these points.
+    degree_bound_powers: Vec<G1Projective>,
+    // A BLS12-381 (prime subgroup) point over the extension field multiplied by the secret degree
+    // bound scalar, used to check the prover's polynomial uses the highest power
+    degree_verification_key: G2Affine,
 }
 
 impl VerifierTranscript {
     /// Create a verifier transcript from the prover's polynomial degree and public roots
+    #[instrument(name = "setup", level = "debug", skip_all, fields(degree = target_polynomial.degree()))]
     pub fn new(target_polynomial: &Polynomial) -> Self {
+        // The secret scalars are overwritten with zeros when they go out of scope, as anyone who
+        // learns them can forge proofs for any polynomial
         let mut rng = rand::thread_rng();
-        let shift = Scalar::random(&mut rng);
-        let scalar = Scalar::random(&mut rng);
+        let shift = SecretScalar::new(Scalar::random(&mut rng));
+        let scalar = SecretScalar::new(Scalar::random(&mut rng));
         let g2 = G2Projective::generator();
-        let (encrypted_powers, shifted_powers) =
-            Self::calculate_encrypted_powers(&scalar, &shift, target_polynomial.degree());
+        let (encrypted_powers, shifted_powers) = Self::calculate_encrypted_powers(
+            scalar.get_scalar(),
+            shift.get_scalar(),
+            target_polynomial.degree(),
+        );
         let public_root_verification_key =
-            G2Affine::from(g2 * target_polynomial.eval_public_polynomial(&scalar));
-        let power_verification_key = G2Affine::from(g2 * shift);
+            G2Affine::from(g2 * target_polynomial.eval_public_polynomial(scalar.get_scalar()));
+        let power_verification_key = G2Affine::from(g2 * shift.get_scalar());
+
+        // Commit to the degree by handing out the powers below it under another secret scalar
+        let degree = target_polynomial.degree();
+        let degree_bound = SecretScalar::new(Scalar::random(&mut rng));
+        let degree_bound_powers = encrypted_powers[..degree]
+            .iter()
+            .map(|power| power * degree_bound.get_scalar())
+            .collect();
+        let degree_verification_key = G2Affine::from(g2 * degree_bound.get_scalar());
 
         Self {
             encrypted_powers,
             shifted_powers,
             public_root_verification_key,
             power_verification_key,
+            degree,
+            degree_bound_powers,
+            degree_verification_key,
         }
     }
 
@@ -103,7 +168,11 @@ impl VerifierTranscript {
             encrypted_powers.push(g1 * power);
             shifted_powers.push(g1 * (shift * power));
         }
-        println!("encrypted_powers: {:?}", encrypted_powers);
+        power.zeroize();
+        trace!(
+            powers = encrypted_powers.len(),
+            "calculated encrypted powers"
+        );
         (encrypted_powers, shifted_powers)
     }
 
@@ -120,6 +189,23 @@ impl VerifierTranscript {
         (&self.encrypted_powers, &self.shifted_powers)
     }
 
+    /// Get the degree of the target polynomial the verifier's powers were created for
+    pub fn get_degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Get the encrypted powers below the target degree multiplied by the secret degree bound
+    /// scalar, which the prover evaluates its polynomial without the leading term at
+    pub fn get_degree_bound_powers(&self) -> &Vec<G1Projective> {
+        &self.degree_bound_powers
+    }
+
+    /// Get the verification key multiplied by the secret degree bound scalar, used to check the
+    /// prover's polynomial has the target degree
+    pub fn get_degree_verification_key(&self) -> &G2Affine {
+        &self.degree_verification_key
+    }
+
     /// Get verification keys used in the pairing operation used to complete non-interactive
     /// verification of the proof
     ///
@@ -144,14 +230,27 @@ impl VerifierTranscript {
     /// This operation is roughly equivalent to the following:
     /// pair(G1*p(s), G2) == pair(G1*h(s), G2*t(s))
     /// pair(G1*p(s_shifted), G2) == pair(G1*p(s), G2*shift)
+    /// pair(G1*alpha*(p(s) - c_d*s^d), G2) == pair(G1*(p(s) - c_d*s^d), G2*alpha)
     ///
     /// The underlying mechanics of the pairing operation are complicated but their
     /// main useful feature is that they allow for already encrypted values to be
     /// compared directly (and homomorphically) allowing for non-interactive verification
     /// to happen without leaking sensitive secrets.
-    pub fn verify_proof(&self, proof: &ProverTranscript) -> bool {
-        // Get the prover's reported values
+    ///
+    /// The last check binds the proof to the target degree d: the verifier only hands out the
+    /// powers below s^d multiplied by alpha, so the prover can only pass it if the leading
+    /// coefficient c_d it reports, which must be nonzero, is the one its evaluation p(s) uses.
+    /// A prover with a polynomial of lower degree can't use the highest power and is rejected.
+    ///
+    /// Rejected proofs return the reason, e.g. [`VerificationError::PairingCheck`] for a
+    /// polynomial without the public roots or [`VerificationError::ShiftCheck`] for evaluations
+    /// that don't use the verifier's powers.
+    #[instrument(name = "verify", level = "debug", skip_all)]
+    pub fn verify_proof(&self, proof: &ProverTranscript) -> Result<(), VerificationError> {
+        self.check_proof_values(proof)?;
         let (px_eval, px_powers_eval, hx_eval) = proof.get_proof_values();
+        let (_, lower_eval) = proof.get_degree_values();
+        let px_lower = self.lower_terms(proof);
 
         // Perform the pairing operations to verify the prover's reported evaluations
         // against the verifier's challenge values
@@ -160,10 +259,153 @@ impl VerifierTranscript {
         let pairing_px_shifted = bls12_381::pairing(&px_powers_eval, &g2);
         let pairing_hx_tx = bls12_381::pairing(&hx_eval, &self.public_root_verification_key);
         let pairing_px_shift = bls12_381::pairing(&px_eval, &self.power_verification_key);
-        (pairing_px == pairing_hx_tx) && (pairing_px_shifted == pairing_px_shift)
+        let pairing_lower = bls12_381::pairing(&lower_eval, &g2);
+        let pairing_px_lower = bls12_381::pairing(&px_lower, &self.degree_verification_key);
+
+        // Compare the pairings in constant time and always compute every check, so how long
+        // verification takes doesn't depend on which check failed
+        let roots_hold = bool::from(pairing_px.ct_eq(&pairing_hx_tx));
+        let powers_hold = bool::from(pairing_px_shifted.ct_eq(&pairing_px_shift));
+        let degree_holds = bool::from(pairing_lower.ct_eq(&pairing_px_lower));
+        let verified = roots_hold && powers_hold && degree_holds;
+
+        // Recompute the checks in product form with a single final exponentiation each, so an
+        // arithmetic regression in either path trips the assertion in debug builds
+        debug_assert_eq!(
+            verified,
+            [
+                (px_eval, hx_eval, self.public_root_verification_key),
+                (px_powers_eval, px_eval, self.power_verification_key),
+                (lower_eval, px_lower, self.degree_verification_key),
+            ]
+            .iter()
+            .all(|(lhs, rhs, key)| multi_miller_loop(&[
+                (lhs, &G2Prepared::from(g2)),
+                (&-rhs, &G2Prepared::from(*key)),
+            ])
+            .final_exponentiation()
+                == Gt::identity()),
+            "pairing checks disagree with their product form"
+        );
+        match (roots_hold, powers_hold, degree_holds) {
+            (false, _, _) => Err(VerificationError::PairingCheck),
+            (true, false, _) => Err(VerificationError::ShiftCheck),
+            (true, true, false) => Err(VerificationError::DegreeCheck),
+            (true, true, true) => Ok(()),
+        }
+    }
+
+    /// Verify many proofs against this transcript at once using 4 pairings in total instead of 6
+    /// per proof.
+    ///
+    /// Each proof's three pairing checks are weighted by fresh random scalars r_i, u_i and w_i
+    /// and summed, so by bilinearity all proofs verify together when
+    /// pair(G1*sum(r_i*p_i(s) + u_i*p_i(s_shifted) - w_i*l_i), G2) ==
+    /// pair(G1*sum(r_i*h_i(s)), G2*t(s)) * pair(G1*sum(u_i*p_i(s)), G2*shift) *
+    /// pair(G1*sum(-w_i*(p_i(s) - c_i*s^d)), G2*alpha)
+    /// where l_i is the evaluation of the i-th polynomial without its leading term c_i*s^d at the
+    /// degree bound powers.
+    ///
+    /// A batch containing an invalid proof only passes if the random weights happen to cancel
+    /// its error, which happens with negligible probability since the prover can't predict them.
+    #[instrument(name = "verify", level = "debug", skip_all, fields(proofs = proofs.len()))]
+    pub fn verify_batch(&self, proofs: &[ProverTranscript]) -> bool {
+        if proofs
+            .iter()
+            .any(|proof| self.check_proof_values(proof).is_err())
+        {
+            return false;
+        }
+        let mut rng = rand::thread_rng();
+        let mut lhs = G1Projective::identity();
+        let mut hx_sum = G1Projective::identity();
+        let mut px_sum = G1Projective::identity();
+        let mut lower_sum = G1Projective::identity();
+        for proof in proofs {
+            let (px_eval, px_powers_eval, hx_eval) = proof.get_proof_values();
+            let (_, lower_eval) = proof.get_degree_values();
+            let (r, u, w) = (
+                Scalar::random(&mut rng),
+                Scalar::random(&mut rng),
+                Scalar::random(&mut rng),
+            );
+            lhs += px_eval * r + px_powers_eval * u - lower_eval * w;
+            hx_sum += hx_eval * r;
+            px_sum += px_eval * u;
+            lower_sum += G1Projective::from(self.lower_terms(proof)) * w;
+        }
+
+        // Move the right hand side over so a single final exponentiation checks the product
+        let (lhs, hx_sum, px_sum, lower_sum) = (
+            G1Affine::from(lhs),
+            G1Affine::from(-hx_sum),
+            G1Affine::from(-px_sum),
+            G1Affine::from(lower_sum),
+        );
+        let verified = multi_miller_loop(&[
+            (&lhs, &G2Prepared::from(G2Affine::generator())),
+            (
+                &hx_sum,
+                &G2Prepared::from(self.public_root_verification_key),
+            ),
+            (&px_sum, &G2Prepared::from(self.power_verification_key)),
+            (&lower_sum, &G2Prepared::from(self.degree_verification_key)),
+        ])
+        .final_exponentiation()
+            == Gt::identity();
+
+        // Except with negligible probability the batch verifies exactly when every proof does
+        debug_assert_eq!(
+            verified,
+            proofs.iter().all(|proof| self.verify_proof(proof).is_ok()),
+            "batch verification disagrees with verifying proofs one by one"
+        );
+        verified
+    }
+
+    // Check the values of a proof that don't need a pairing: the degree it claims, its points and
+    // its leading coefficient
+    fn check_proof_values(&self, proof: &ProverTranscript) -> Result<(), VerificationError> {
+        // Check the proof is for the degree the powers were created for
+        if proof.get_degree() != self.degree {
+            return Err(VerificationError::DegreeMismatch(
+                self.degree,
+                proof.get_degree(),
+            ));
+        }
+
+        // The identity passes the pairing checks for any polynomial, since every pairing with it
+        // is one, so it is rejected along with invalid points.
+        let (px_eval, px_powers_eval, hx_eval) = proof.get_proof_values();
+        let (leading_coefficient, lower_eval) = proof.get_degree_values();
+        let malformed = [px_eval, px_powers_eval, hx_eval].iter().any(|point| {
+            bool::from(point.is_identity() | !point.is_on_curve() | !point.is_torsion_free())
+        }) || !bool::from(lower_eval.is_on_curve() & lower_eval.is_torsion_free());
+        if malformed {
+            return Err(VerificationError::MalformedPoint);
+        }
+
+        // A zero leading coefficient means the polynomial has a lower degree than the target
+        if bool::from(leading_coefficient.is_zero()) {
+            return Err(VerificationError::DegreeCheck);
+        }
+        Ok(())
+    }
+
+    // Remove the leading term the prover reports from its evaluation, G1*(p(s) - c_d*s^d)
+    fn lower_terms(&self, proof: &ProverTranscript) -> G1Affine {
+        let (px_eval, _, _) = proof.get_proof_values();
+        let (leading_coefficient, _) = proof.get_degree_values();
+        G1Affine::from(px_eval - self.encrypted_powers[self.degree] * leading_coefficient)
     }
 }
 
+// The verifier's encrypted powers come without a proof of their structure and the proof isn't
+// zero knowledge, which is fine for showing the math but not for untrusted counterparties
+impl ProofScheme for ProverTranscript {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Educational;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,7 +489,144 @@ mod tests {
         let prover_response_alt = polynomial_alt.generate_response(&verifier_transcript);
 
         // Ensure only the correct polynomial verifies correctly
-        assert!(verifier_transcript.verify_proof(&prover_response));
-        assert!(!verifier_transcript.verify_proof(&prover_response_alt));
+        assert_eq!(verifier_transcript.verify_proof(&prover_response), Ok(()));
+        assert_eq!(
+            verifier_transcript.verify_proof(&prover_response_alt),
+            Err(VerificationError::PairingCheck)
+        );
+
+        // Evaluations mixed from two proofs don't use the same powers, and the identity or a
+        // proof claiming another degree is rejected before any pairing
+        let (px, _, hx) = prover_response.get_proof_values();
+        let (_, px_shift, _) = prover_response_alt.get_proof_values();
+        let (lead, lower) = prover_response.get_degree_values();
+        assert_eq!(
+            verifier_transcript
+                .verify_proof(&ProverTranscript::new(px, px_shift, hx, 5, lead, lower)),
+            Err(VerificationError::ShiftCheck)
+        );
+        let identity = G1Affine::identity();
+        assert_eq!(
+            verifier_transcript.verify_proof(&ProverTranscript::new(
+                identity, identity, hx, 5, lead, lower
+            )),
+            Err(VerificationError::MalformedPoint)
+        );
+        assert_eq!(
+            verifier_transcript
+                .verify_proof(&ProverTranscript::new(px, px_shift, hx, 4, lead, lower)),
+            Err(VerificationError::DegreeMismatch(5, 4))
+        );
+    }
+
+    #[test]
+    fn test_lower_degree_polynomials_are_rejected() {
+        // p(x) = (x + 2)(3x + 6)(2x + 4)(x + 8) and q(x) = (x + 2)(3x + 6)(x + 5) share the public
+        // roots, so q(x) passes both pairing checks against powers created for p(x)
+        let roots = [(1, 2), (3, 6), (2, 4), (1, 8)].map(|root| Root::try_from(root).unwrap());
+        let lower_roots = [(1, 2), (3, 6), (1, 5)].map(|root| Root::try_from(root).unwrap());
+        let polynomial = Polynomial::new(roots.to_vec(), 2).unwrap();
+        let lower_polynomial = Polynomial::new(lower_roots.to_vec(), 2).unwrap();
+        let verifier_transcript = VerifierTranscript::new(&polynomial);
+        assert_eq!(verifier_transcript.get_degree(), 4);
+        assert_eq!(verifier_transcript.get_degree_bound_powers().len(), 4);
+        assert!(verifier_transcript
+            .verify_proof(&polynomial.generate_response(&verifier_transcript))
+            .is_ok());
+
+        // Answering honestly reveals the lower degree
+        let lower_response = lower_polynomial.generate_response(&verifier_transcript);
+        assert_eq!(
+            verifier_transcript.verify_proof(&lower_response),
+            Err(VerificationError::DegreeMismatch(4, 3))
+        );
+
+        // Claiming the target degree leaves the leading coefficient zero, and making one up
+        // doesn't match the highest power the evaluation uses
+        let (px, px_shift, hx) = lower_response.get_proof_values();
+        let (lead, lower) = lower_response.get_degree_values();
+        assert_eq!(lead, Scalar::zero());
+        let claimed = ProverTranscript::new(px, px_shift, hx, 4, lead, lower);
+        assert_eq!(
+            verifier_transcript.verify_proof(&claimed),
+            Err(VerificationError::DegreeCheck)
+        );
+        let forged = ProverTranscript::new(px, px_shift, hx, 4, Scalar::one(), lower);
+        assert_eq!(
+            verifier_transcript.verify_proof(&forged),
+            Err(VerificationError::DegreeCheck)
+        );
+        assert!(!verifier_transcript.verify_batch(&[forged]));
+    }
+
+    #[test]
+    fn test_batches_verify_only_if_every_proof_does() {
+        let roots = vec![
+            Root::try_from((1, 2)).unwrap(),
+            Root::try_from((3, 6)).unwrap(),
+            Root::try_from((2, 4)).unwrap(),
+        ];
+        let roots_alt = vec![
+            Root::try_from((1, 2)).unwrap(),
+            Root::try_from((1, 5)).unwrap(),
+            Root::try_from((1, 3)).unwrap(),
+        ];
+        let polynomial = Polynomial::new(roots, 2).unwrap();
+        let polynomial_alt = Polynomial::new(roots_alt, 2).unwrap();
+        let verifier_transcript = VerifierTranscript::new(&polynomial);
+
+        // Every prover evaluates the same polynomial at the shared challenge points
+        let proofs: Vec<ProverTranscript> = (0..4)
+            .map(|_| polynomial.generate_response(&verifier_transcript))
+            .collect();
+        assert!(verifier_transcript.verify_batch(&proofs));
+        assert!(verifier_transcript.verify_batch(&[]));
+
+        // A single invalid proof fails the whole batch
+        let mut with_invalid = proofs.clone();
+        with_invalid.push(polynomial_alt.generate_response(&verifier_transcript));
+        assert!(!verifier_transcript.verify_batch(&with_invalid));
+
+        // Swapping values between valid proofs doesn't pass either
+        let (px, _, hx) = proofs[0].get_proof_values();
+        let (_, px_shift, _) = proofs[1].get_proof_values();
+        let (lead, lower) = proofs[0].get_degree_values();
+        let mixed = ProverTranscript::new(px, px_shift, hx, proofs[0].get_degree(), lead, lower);
+        assert!(!verifier_transcript.verify_batch(&[proofs[2].clone(), mixed]));
+    }
+
+    // Wall clock ratios are flaky on loaded machines and debug builds recompute the pairings
+    // in a `debug_assert_eq!`, so the check only runs when asked for:
+    // cargo test --release -p zksnarks-example -- --ignored test_rejecting_a_proof_takes_as_long
+    #[test]
+    #[ignore = "timing sensitive, run with --release and --ignored"]
+    fn test_rejecting_a_proof_takes_as_long_as_accepting_one() {
+        let roots = [(1, 2), (3, 6), (2, 4)].map(|root| Root::try_from(root).unwrap());
+        let roots_alt = [(1, 2), (1, 5), (1, 3)].map(|root| Root::try_from(root).unwrap());
+        let polynomial = Polynomial::new(roots.to_vec(), 2).unwrap();
+        let polynomial_alt = Polynomial::new(roots_alt.to_vec(), 2).unwrap();
+        let verifier_transcript = VerifierTranscript::new(&polynomial);
+
+        // Take the fastest of a few runs to filter out scheduling noise
+        let fastest_verification = |proof: &ProverTranscript| {
+            (0..3)
+                .map(|_| {
+                    let start = std::time::Instant::now();
+                    let verified = verifier_transcript.verify_proof(proof).is_ok();
+                    (start.elapsed(), verified)
+                })
+                .min()
+                .unwrap()
+        };
+        let (accepted, accepted_ok) =
+            fastest_verification(&polynomial.generate_response(&verifier_transcript));
+        let (rejected, rejected_ok) =
+            fastest_verification(&polynomial_alt.generate_response(&verifier_transcript));
+        assert!(accepted_ok && !rejected_ok);
+        let ratio = rejected.as_secs_f64() / accepted.as_secs_f64();
+        assert!(
+            (0.5..2.0).contains(&ratio),
+            "rejection took {rejected:?} and acceptance {accepted:?}"
+        );
     }
 }
@@ -8,4 +8,117 @@ pub enum Error {
     InvalidPublicRoots(usize),
     /// No public roots set
     NoPublicRoots,
+    /// Polynomial degree exceeds the maximum degree supported by the setup
+    DegreeExceedsSetup(usize, usize),
+    /// No data was provided to commit to
+    NoData,
+    /// Fewer distinct samples were provided than are needed to recover the data
+    InsufficientSamples(usize, usize),
+    /// Requested sample lies outside of the erasure coded data
+    SampleOutOfRange(usize),
+    /// Challenge response isn't accepted by the public polynomial
+    RejectedTranscript(i64),
+    /// The same challenge was answered more than once
+    RepeatedChallenge(i64),
+    /// Fewer distinct transcripts were provided than are needed to extract the polynomial
+    InsufficientTranscripts(usize, usize),
+    /// Bytes are not a valid EVM precompile encoding of a BN254 value
+    InvalidEvmEncoding,
+    /// Bytes are not a valid secp256k1 private key
+    InvalidSigningKey,
+    /// Signature is malformed or can't be created or recovered
+    InvalidSignature,
+    /// String is not a CIDv1 of a raw sha2-256 block
+    InvalidCid(String),
+    /// Content retrieved from or stored on an IPFS node doesn't match its CID
+    CidMismatch(String),
+    /// Block is larger than the maximum block size
+    BlockTooLarge(usize),
+    /// Request to an IPFS node failed
+    IpfsRequest(String),
+    /// Coefficient file can't be read or doesn't hold a whole number of coefficients
+    InvalidWitnessFile(String),
+    /// Coefficient index lies outside of the polynomial
+    CoefficientOutOfRange(usize),
+    /// Coefficient is not a canonical encoding of a field element
+    InvalidCoefficient(usize),
+    /// Repeated challenge protocols need at least one challenge
+    NoChallenges,
+    /// Challenges must be drawn from a range of at least one value
+    InvalidChallengeRange(i64),
+    /// Polynomial doesn't take the claimed value at the evaluation point
+    EvaluationMismatch,
+    /// Constraint system has no constraints
+    NoConstraints,
+    /// Assignment doesn't hold one value per variable (expected, actual)
+    AssignmentLength(usize, usize),
+    /// Assignment doesn't satisfy every constraint of the circuit
+    UnsatisfiedCircuit,
+    /// No domain of roots of unity of at least this size exists in the scalar field
+    DomainTooLarge(usize),
+    /// Number of values doesn't match the size of the domain (expected, actual)
+    DomainSizeMismatch(usize, usize),
+    /// Domain point appears more than once
+    RepeatedDomainPoint(usize),
+    /// Bytes are not a compressed point of the BLS12-381 G1 prime order subgroup
+    InvalidPointEncoding,
+    /// Submission is longer than the verifier accepts (length, maximum)
+    SubmissionTooLarge(usize, usize),
+    /// Reference string degree exceeds the verifier's limit (degree, maximum)
+    SetupTooLarge(usize, usize),
+    /// Submission holds more proofs than the verifier accepts in one batch (proofs, maximum)
+    BatchTooLarge(usize, usize),
+    /// Submission has a malformed or truncated record starting at this byte offset
+    MalformedSubmission(usize),
+    /// API key has no verification quota
+    UnknownApiKey(String),
+    /// API key has used up its verification quota
+    QuotaExceeded(String),
+    /// Calibration profile can't be read, written or parsed
+    InvalidCalibration(String),
+    /// Delegation mask is shorter than the polynomial it blinds (mask, coefficients)
+    MaskTooShort(usize, usize),
+    /// Helper's response to a delegation request is incorrect
+    DelegationMismatch,
+    /// Beacon round can't be fetched
+    BeaconRequest(String),
+    /// Beacon round can't be parsed
+    InvalidBeaconRound(String),
+    /// Beacon round's signature doesn't verify under the beacon's public key
+    InvalidBeaconSignature(u64),
+    /// Ceremony contribution at the index doesn't extend the reference string before it
+    InvalidContribution(usize),
+    /// Argmax needs at least one value
+    EmptyArgmax,
+    /// Merkle tree has no leaf at the index
+    LeafOutOfRange(usize),
+    /// Encoded reference string has the wrong length for its degree or doesn't start at the
+    /// generator (length)
+    MalformedSetup(usize),
+    /// Factor a*x + b of a root has a leading coefficient of zero, so it has no root
+    ZeroLeadingCoefficient,
+    /// Polynomial division by the zero polynomial
+    ZeroDivisor,
+    /// Polynomial isn't divisible by the public polynomial t(x)
+    NonzeroRemainder,
+    /// Sample's opening doesn't match the committed data (index)
+    InvalidSample(usize),
+}
+
+/// Reason a verifier rejects a proof of knowledge of a polynomial
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VerificationError {
+    /// First pairing check p(s) = t(s) * h(s) fails, so the polynomial doesn't have the public
+    /// roots
+    PairingCheck,
+    /// Second pairing check p(shift * s) = shift * p(s) fails, so the evaluations don't use the
+    /// verifier's encrypted powers
+    ShiftCheck,
+    /// Proof value is the identity or isn't a point of the prime order subgroup
+    MalformedPoint,
+    /// Proof is for a polynomial of another degree than the verifier's target (expected, actual)
+    DegreeMismatch(usize, usize),
+    /// Third pairing check fails or the leading coefficient is zero, so the prover's polynomial
+    /// doesn't use the highest encrypted power and has a lower degree than the target
+    DegreeCheck,
 }
@@ -0,0 +1,190 @@
+//! Encodings of BN254 values in the input format of the EVM precompiles
+
+use crate::error::Error;
+use ark_bn254::{Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::CurveGroup;
+use ark_ff::{BigInteger, PrimeField, Zero};
+
+// The EVM exposes BN254 arithmetic through three precompiled contracts defined in EIP-196 and
+// EIP-197. Their inputs are plain concatenations of 32 byte big endian integers:
+//
+// * A scalar is a single 32 byte integer.
+// * A G1 point is its affine coordinates `x || y` (64 bytes). The point at infinity is 64 zero
+// bytes.
+// * A G2 point has coordinates in the extension field Fq2 whose elements are `a*i + b`. Each
+// coordinate is written as `a || b`, imaginary part first, so a point is `x_a || x_b || y_a || y_b`
+// (128 bytes). Getting this order wrong is a very common source of failing on-chain verifiers.
+//
+// * ecAdd (0x06) takes two G1 points (128 bytes)
+// * ecMul (0x07) takes a G1 point and a scalar (96 bytes)
+// * ecPairing (0x08) takes any number of G1 and G2 pairs (192 bytes each) and returns 1 if the
+// product of their pairings is the identity
+
+/// Length of an encoded scalar or base field element
+pub const EVM_WORD_LEN: usize = 32;
+
+/// Length of an encoded G1 point
+pub const EVM_G1_LEN: usize = 2 * EVM_WORD_LEN;
+
+/// Length of an encoded G2 point
+pub const EVM_G2_LEN: usize = 4 * EVM_WORD_LEN;
+
+// Write a prime field element as a 32 byte big endian integer
+fn encode_field<F: PrimeField>(value: &F) -> [u8; EVM_WORD_LEN] {
+    let mut bytes = [0; EVM_WORD_LEN];
+    let be_bytes = value.into_bigint().to_bytes_be();
+    bytes[EVM_WORD_LEN - be_bytes.len()..].copy_from_slice(&be_bytes);
+    bytes
+}
+
+// Read a base field element from a 32 byte big endian integer, rejecting non-canonical values
+fn decode_base_field(bytes: &[u8]) -> Result<Fq, Error> {
+    let value = Fq::from_be_bytes_mod_order(bytes);
+    if encode_field(&value) != bytes {
+        return Err(Error::InvalidEvmEncoding);
+    }
+    Ok(value)
+}
+
+/// Encode a scalar as a 32 byte big endian integer
+pub fn encode_evm_scalar(scalar: &Fr) -> [u8; EVM_WORD_LEN] {
+    encode_field(scalar)
+}
+
+/// Encode a G1 point as `x || y`
+pub fn encode_evm_g1(point: &G1Projective) -> [u8; EVM_G1_LEN] {
+    let mut bytes = [0; EVM_G1_LEN];
+    let affine = point.into_affine();
+    if !affine.infinity {
+        bytes[..EVM_WORD_LEN].copy_from_slice(&encode_field(&affine.x));
+        bytes[EVM_WORD_LEN..].copy_from_slice(&encode_field(&affine.y));
+    }
+    bytes
+}
+
+/// Encode a G2 point as `x_imaginary || x_real || y_imaginary || y_real`
+pub fn encode_evm_g2(point: &G2Projective) -> [u8; EVM_G2_LEN] {
+    let mut bytes = [0; EVM_G2_LEN];
+    let affine = point.into_affine();
+    if !affine.infinity {
+        let words = [affine.x.c1, affine.x.c0, affine.y.c1, affine.y.c0];
+        for (chunk, word) in bytes.chunks_mut(EVM_WORD_LEN).zip(words.iter()) {
+            chunk.copy_from_slice(&encode_field(word));
+        }
+    }
+    bytes
+}
+
+/// Decode a G1 point, checking that it lies on the curve
+pub fn decode_evm_g1(bytes: &[u8]) -> Result<G1Projective, Error> {
+    if bytes.len() != EVM_G1_LEN {
+        return Err(Error::InvalidEvmEncoding);
+    }
+    let x = decode_base_field(&bytes[..EVM_WORD_LEN])?;
+    let y = decode_base_field(&bytes[EVM_WORD_LEN..])?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Projective::zero());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::InvalidEvmEncoding);
+    }
+    Ok(point.into())
+}
+
+/// Decode a G2 point, checking that it lies on the curve and in the prime order subgroup
+pub fn decode_evm_g2(bytes: &[u8]) -> Result<G2Projective, Error> {
+    if bytes.len() != EVM_G2_LEN {
+        return Err(Error::InvalidEvmEncoding);
+    }
+    let words = bytes
+        .chunks(EVM_WORD_LEN)
+        .map(decode_base_field)
+        .collect::<Result<Vec<Fq>, Error>>()?;
+    if words.iter().all(|word| word.is_zero()) {
+        return Ok(G2Projective::zero());
+    }
+    let point = G2Affine::new_unchecked(Fq2::new(words[1], words[0]), Fq2::new(words[3], words[2]));
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::InvalidEvmEncoding);
+    }
+    Ok(point.into())
+}
+
+/// Encode the input of the ecAdd precompile
+pub fn encode_ec_add_input(a: &G1Projective, b: &G1Projective) -> Vec<u8> {
+    [encode_evm_g1(a).as_slice(), encode_evm_g1(b).as_slice()].concat()
+}
+
+/// Encode the input of the ecMul precompile
+pub fn encode_ec_mul_input(point: &G1Projective, scalar: &Fr) -> Vec<u8> {
+    [
+        encode_evm_g1(point).as_slice(),
+        encode_evm_scalar(scalar).as_slice(),
+    ]
+    .concat()
+}
+
+/// Encode the input of the ecPairing precompile checking that the pairings of all pairs combine
+/// to the identity
+pub fn encode_pairing_input(pairs: &[(G1Projective, G2Projective)]) -> Vec<u8> {
+    pairs
+        .iter()
+        .flat_map(|(p, q)| [encode_evm_g1(p).as_slice(), encode_evm_g2(q).as_slice()].concat())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::Group;
+
+    fn hex_word(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_generators_match_eip_197_encoding() {
+        let g1 = encode_evm_g1(&G1Projective::generator());
+        assert_eq!(g1[31], 1);
+        assert_eq!(g1[63], 2);
+
+        // The G2 generator listed in EIP-197, imaginary parts first
+        let g2 = encode_evm_g2(&G2Projective::generator());
+        let expected = [
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+            "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+            "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+        ]
+        .iter()
+        .flat_map(|word| hex_word(word))
+        .collect::<Vec<u8>>();
+        assert_eq!(g2.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_points_round_trip_and_invalid_points_are_rejected() {
+        let p = G1Projective::generator() * Fr::from(12345u64);
+        let q = G2Projective::generator() * Fr::from(678u64);
+        assert_eq!(decode_evm_g1(&encode_evm_g1(&p)).unwrap(), p);
+        assert_eq!(decode_evm_g2(&encode_evm_g2(&q)).unwrap(), q);
+        assert_eq!(
+            decode_evm_g1(&[0; EVM_G1_LEN]).unwrap(),
+            G1Projective::zero()
+        );
+
+        // (1, 3) is not on the curve y^2 = x^3 + 3
+        let mut invalid = [0; EVM_G1_LEN];
+        invalid[31] = 1;
+        invalid[63] = 3;
+        assert_eq!(decode_evm_g1(&invalid), Err(Error::InvalidEvmEncoding));
+        assert_eq!(
+            encode_pairing_input(&[(p, q), (p, q)]).len(),
+            2 * (EVM_G1_LEN + EVM_G2_LEN)
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Knowledge extractor recovering a prover's polynomial from accepting challenge responses
+
+use crate::{error::Error, kzg::interpolate, UnencryptedChallengeResponse, UnencryptedPolynomial};
+use bls12_381::Scalar;
+
+// Special soundness for the polynomial protocols works like it does for Schnorr proofs, except
+// that more than two transcripts are needed. In the unencrypted protocol the verifier sends a
+// challenge `x` and the prover answers with `p(x)` and `h(x)`. A polynomial of degree `d` is fixed
+// by its values at `d + 1` distinct points, so an extractor that rewinds the prover and collects
+// `d + 1` accepting answers to different challenges can interpolate both `p(x)` and `h(x)`. This
+// is "(d + 1)-special soundness"; two transcripts are enough only for polynomials of degree one.
+//
+// A prover who answers every challenge consistently therefore "knows" a polynomial in a very
+// concrete sense. The encrypted protocol only ever reveals `G1*p(s)` so the values can't be
+// interpolated directly. There, knowledge rests on the knowledge of exponent assumption, which
+// is what the check against the shifted powers enforces.
+
+/// Polynomials `p(x)` and `h(x)` recovered from a prover's answers, with coefficients in
+/// ascending order of degree
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExtractedPolynomial {
+    // Coefficients of the prover's polynomial p(x)
+    coefficients: Vec<Scalar>,
+    // Coefficients of the hidden polynomial h(x) = p(x) / t(x)
+    hidden_coefficients: Vec<Scalar>,
+}
+
+impl ExtractedPolynomial {
+    /// Get the coefficients of the prover's polynomial p(x) in ascending order of degree
+    pub fn get_coefficients(&self) -> &[Scalar] {
+        &self.coefficients
+    }
+
+    /// Get the coefficients of the hidden polynomial h(x) in ascending order of degree
+    pub fn get_hidden_coefficients(&self) -> &[Scalar] {
+        &self.hidden_coefficients
+    }
+}
+
+/// Recover the polynomial of degree `degree` a prover used to answer challenges in the
+/// unencrypted protocol. Every transcript must be accepted by the public polynomial and at least
+/// `degree + 1` transcripts with distinct challenges are required.
+pub fn extract_polynomial(
+    degree: usize,
+    public_polynomial: &UnencryptedPolynomial,
+    transcripts: &[(i64, UnencryptedChallengeResponse)],
+) -> Result<ExtractedPolynomial, Error> {
+    let mut p_points = Vec::with_capacity(transcripts.len());
+    let mut h_points = Vec::with_capacity(transcripts.len());
+    for (i, (x, response)) in transcripts.iter().enumerate() {
+        if !response.verify(*x, public_polynomial) {
+            return Err(Error::RejectedTranscript(*x));
+        }
+        if transcripts[..i].iter().any(|(other, _)| other == x) {
+            return Err(Error::RepeatedChallenge(*x));
+        }
+        let (px, hx) = response.get_values();
+        p_points.push((to_scalar(*x), to_scalar(px)));
+        h_points.push((to_scalar(*x), to_scalar(hx)));
+    }
+    if transcripts.len() <= degree {
+        return Err(Error::InsufficientTranscripts(
+            transcripts.len(),
+            degree + 1,
+        ));
+    }
+    p_points.truncate(degree + 1);
+    h_points.truncate(degree + 1);
+
+    Ok(ExtractedPolynomial {
+        coefficients: trim(interpolate(&p_points)),
+        hidden_coefficients: trim(interpolate(&h_points)),
+    })
+}
+
+// Map a signed integer into the scalar field
+fn to_scalar(value: i64) -> Scalar {
+    let scalar = Scalar::from(value.unsigned_abs());
+    if value < 0 {
+        -scalar
+    } else {
+        scalar
+    }
+}
+
+// Remove zero coefficients above the polynomial's actual degree
+fn trim(mut coefficients: Vec<Scalar>) -> Vec<Scalar> {
+    while coefficients.len() > 1 && coefficients.last() == Some(&Scalar::zero()) {
+        coefficients.pop();
+    }
+    coefficients
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleRoot;
+
+    fn test_polynomial() -> UnencryptedPolynomial {
+        // p(x) = (x + 2)(3x + 6)(2x + 4) = 6x^3 + 36x^2 + 72x + 48 with t(x) = (x + 2)(3x + 6)
+        let roots = vec![
+            SimpleRoot::new(1, 2).unwrap(),
+            SimpleRoot::new(3, 6).unwrap(),
+            SimpleRoot::new(2, 4).unwrap(),
+        ];
+        UnencryptedPolynomial::new(roots).set_public_roots(2)
+    }
+
+    #[test]
+    fn test_extractor_recovers_polynomial_from_rewound_prover() {
+        let polynomial = test_polynomial();
+        let public_polynomial = polynomial.get_public_polynomial().unwrap();
+        let transcripts: Vec<(i64, UnencryptedChallengeResponse)> = [-3, 5, 40, 100]
+            .iter()
+            .map(|&x| (x, polynomial.answer_challenge(x)))
+            .collect();
+
+        let extracted = extract_polynomial(3, &public_polynomial, &transcripts).unwrap();
+        let expected: Vec<Scalar> = [48u64, 72, 36, 6]
+            .iter()
+            .map(|&c| Scalar::from(c))
+            .collect();
+        assert_eq!(extracted.get_coefficients(), &expected[..]);
+        assert_eq!(
+            extracted.get_hidden_coefficients(),
+            &[Scalar::from(4u64), Scalar::from(2u64)]
+        );
+    }
+
+    #[test]
+    fn test_extractor_needs_enough_distinct_accepting_transcripts() {
+        let polynomial = test_polynomial();
+        let public_polynomial = polynomial.get_public_polynomial().unwrap();
+        let transcripts: Vec<(i64, UnencryptedChallengeResponse)> = [1, 2, 3, 2]
+            .iter()
+            .map(|&x| (x, polynomial.answer_challenge(x)))
+            .collect();
+
+        assert_eq!(
+            extract_polynomial(3, &public_polynomial, &transcripts[..3]),
+            Err(Error::InsufficientTranscripts(3, 4))
+        );
+        assert_eq!(
+            extract_polynomial(3, &public_polynomial, &transcripts),
+            Err(Error::RepeatedChallenge(2))
+        );
+        let rejected = [(4, UnencryptedChallengeResponse::new(1, 1))];
+        assert_eq!(
+            extract_polynomial(0, &public_polynomial, &rejected),
+            Err(Error::RejectedTranscript(4))
+        );
+    }
+}
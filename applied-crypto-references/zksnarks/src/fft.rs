@@ -0,0 +1,195 @@
+//! Radix-2 fast Fourier transform over the BLS12-381 scalar field and fast polynomial
+//! multiplication
+
+use crate::{domain::EvaluationDomain, error::Error};
+use bls12_381::Scalar;
+use std::iter::successors;
+
+// Multiplying two polynomials of degree n term by term costs n^2 field multiplications. Over a
+// field with roots of unity of high power of two order, such as the BLS12-381 scalar field with
+// roots of unity of order up to 2^32, the number theoretic version of the fast Fourier transform
+// (FFT) does it in O(n log n):
+//
+// 1. Both polynomials are evaluated at the points of a domain of roots of unity large enough to
+// hold their product. The FFT computes all evaluations at once by splitting a polynomial into its
+// even and odd coefficients, p(x) = p_even(x^2) + x*p_odd(x^2), and reusing the evaluations of
+// the halves on the domain of squares, which is half as large.
+// 2. The evaluations are multiplied pointwise, giving the evaluations of the product.
+// 3. The inverse FFT interpolates the product's coefficients. It is the same transform with the
+// inverse root of unity, scaled by 1/n, and the transform with the inverse root only reorders the
+// outputs of the transform with the root itself.
+//
+// The transform is done in place, iteratively from the smallest butterflies up after reordering
+// the values by bit reversed index. Every level costs n/2 multiplications, so the three
+// transforms cost about 3n log2(2n) multiplications for factors of n coefficients. The constant
+// is larger than for the term by term product, which is why small products are still multiplied
+// term by term.
+
+// Number of coefficients of the smaller factor from which FFT multiplication is faster, see the
+// polynomial_mul benchmark
+pub(crate) const FFT_THRESHOLD: usize = 64;
+
+// Reorder values by bit reversed index and run the butterflies of a radix-2 transform with a
+// primitive root of unity of order `values.len()`
+fn radix2_transform(values: &mut [Scalar], generator: Scalar) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    // Primitive roots of unity of order 2, 4, .., n, found by squaring the generator
+    let mut roots = vec![generator];
+    for _ in 1..log_n {
+        let root = roots[roots.len() - 1].square();
+        roots.push(root);
+    }
+
+    let mut half = 1;
+    for root in roots.iter().rev() {
+        let twiddles: Vec<Scalar> = successors(Some(Scalar::one()), |w| Some(w * root))
+            .take(half)
+            .collect();
+        for chunk in values.chunks_mut(2 * half) {
+            let (low, high) = chunk.split_at_mut(half);
+            for ((even, odd), twiddle) in low.iter_mut().zip(high.iter_mut()).zip(&twiddles) {
+                let product = *odd * twiddle;
+                *odd = *even - product;
+                *even += product;
+            }
+        }
+        half *= 2;
+    }
+}
+
+/// Evaluate a polynomial given by coefficients in ascending order of degree at every point of a
+/// domain, in the order of [`EvaluationDomain::elements`]
+pub fn fft(domain: &EvaluationDomain, coefficients: &[Scalar]) -> Result<Vec<Scalar>, Error> {
+    if coefficients.len() > domain.size() {
+        return Err(Error::DomainSizeMismatch(domain.size(), coefficients.len()));
+    }
+    let mut values = coefficients.to_vec();
+    values.resize(domain.size(), Scalar::zero());
+    radix2_transform(&mut values, domain.generator());
+    Ok(values)
+}
+
+/// Interpolate the coefficients in ascending order of degree of the polynomial taking
+/// `evaluations` on the points of a domain
+pub fn ifft(domain: &EvaluationDomain, evaluations: &[Scalar]) -> Result<Vec<Scalar>, Error> {
+    if evaluations.len() != domain.size() {
+        return Err(Error::DomainSizeMismatch(domain.size(), evaluations.len()));
+    }
+    // Transforming with w^-1 gives the evaluations at w^-i = w^(n-i), which are those at w^i with
+    // every index but the first reversed
+    let mut values = evaluations.to_vec();
+    radix2_transform(&mut values, domain.generator());
+    values[1..].reverse();
+    let size_inverse = Scalar::from(domain.size() as u64).invert().unwrap();
+    for value in values.iter_mut() {
+        *value *= size_inverse;
+    }
+    Ok(values)
+}
+
+/// Multiply two polynomials given by coefficients in ascending order of degree term by term
+pub fn naive_polynomial_mul(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![Scalar::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += x * y;
+        }
+    }
+    product
+}
+
+/// Multiply two polynomials given by coefficients in ascending order of degree with the FFT
+pub fn fft_polynomial_mul(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let len = a.len() + b.len() - 1;
+    let domain =
+        EvaluationDomain::new(len).expect("products of at most 2^32 coefficients fit the field");
+    let a_evaluations = fft(&domain, a).expect("factors are shorter than their product");
+    let b_evaluations = fft(&domain, b).expect("factors are shorter than their product");
+    let evaluations: Vec<Scalar> = a_evaluations
+        .iter()
+        .zip(b_evaluations.iter())
+        .map(|(x, y)| x * y)
+        .collect();
+    let mut product = ifft(&domain, &evaluations).expect("evaluations fill the domain");
+    product.truncate(len);
+    product
+}
+
+/// Multiply two polynomials given by coefficients in ascending order of degree, with the FFT if
+/// both have at least [`FFT_THRESHOLD`] coefficients and term by term otherwise
+pub fn polynomial_mul(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
+    if a.len().min(b.len()) < FFT_THRESHOLD {
+        return naive_polynomial_mul(a, b);
+    }
+    fft_polynomial_mul(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kzg::evaluate, polynomial::expand_roots, Bls12_381};
+    use ff::Field;
+
+    #[test]
+    fn test_fft_evaluates_on_the_domain_and_inverts() {
+        let mut rng = rand::thread_rng();
+        let domain = EvaluationDomain::new(16).unwrap();
+        let coefficients: Vec<Scalar> = (0..11).map(|_| Scalar::random(&mut rng)).collect();
+        let evaluations = fft(&domain, &coefficients).unwrap();
+        for (point, evaluation) in domain.elements().iter().zip(evaluations.iter()) {
+            assert_eq!(evaluate(&coefficients, point), *evaluation);
+        }
+
+        // The inverse recovers the coefficients padded to the domain size
+        let mut padded = coefficients.clone();
+        padded.resize(16, Scalar::zero());
+        assert_eq!(ifft(&domain, &evaluations).unwrap(), padded);
+        assert_eq!(
+            fft(&domain, &[Scalar::one(); 17]),
+            Err(Error::DomainSizeMismatch(16, 17))
+        );
+    }
+
+    #[test]
+    fn test_fft_multiplication_matches_naive_multiplication() {
+        let mut rng = rand::thread_rng();
+        for (a_len, b_len) in [(1, 1), (3, 5), (64, 64), (100, 70), (2, 200)] {
+            let a: Vec<Scalar> = (0..a_len).map(|_| Scalar::random(&mut rng)).collect();
+            let b: Vec<Scalar> = (0..b_len).map(|_| Scalar::random(&mut rng)).collect();
+            let expected = naive_polynomial_mul(&a, &b);
+            assert_eq!(expected.len(), a_len + b_len - 1);
+            assert_eq!(fft_polynomial_mul(&a, &b), expected);
+            assert_eq!(polynomial_mul(&a, &b), expected);
+        }
+        assert!(fft_polynomial_mul(&[], &[Scalar::one()]).is_empty());
+
+        // Expanding many roots splits them and multiplies the halves with the FFT
+        let roots: Vec<(Scalar, Scalar)> = (0..3 * FFT_THRESHOLD)
+            .map(|_| (Scalar::random(&mut rng), Scalar::random(&mut rng)))
+            .collect();
+        let coefficients = expand_roots::<Bls12_381>(roots.iter().copied());
+        assert_eq!(coefficients.len(), roots.len() + 1);
+        let x = Scalar::random(&mut rng);
+        let product = roots
+            .iter()
+            .fold(Scalar::one(), |acc, (a, b)| acc * (a * x + b));
+        assert_eq!(evaluate(&coefficients, &x), product);
+    }
+}
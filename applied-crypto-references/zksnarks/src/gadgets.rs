@@ -0,0 +1,340 @@
+//! Fixed-point arithmetic gadgets for quantized inference circuits
+
+use crate::{scalar_from_i64, Bls12_381, ConstraintSystem, Error, LinearCombination, Variable};
+use bls12_381::Scalar;
+use encodings::FixedPoint;
+
+// Quantized models compute on fixed-point integers v = round(x * s) for a public scale s, see the
+// data mapping of the encodings crate. Proving an inference means expressing that arithmetic in
+// whatever the proof system understands, so the gadgets below are written once against a small
+// backend of signed integer operations:
+//
+// - Addition of two fixed-point values on the same scale is integer addition.
+// - The product of two values carries the scale s^2, so it is divided by s, rounding towards
+// negative infinity, to return to the scale s.
+// - Comparisons return 1 if the first value is smaller and 0 otherwise, and selections pick
+// between two values with such a bit, which gives ReLU as max(v, 0) and argmax as a running
+// maximum.
+//
+// An R1CS has no notion of order or division, so the constraint system backend builds them from
+// bit decompositions, which is where almost all of the constraints go:
+//
+// 1. a < b is decided by decomposing a - b + 2^k into k + 1 bits, which needs |a - b| < 2^k. The
+// top bit is set exactly when a >= b.
+// 2. floor(a / s) is a witness q with a remainder r such that q*s + r = a, where r is shown to lie
+// within [0, s) by decomposing both r and s - 1 - r, and q is shown to lie within [-2^k, 2^k) by
+// decomposing q + 2^k. Since the bounds are far below the field modulus, the equation can't wrap
+// around and q and r are unique.
+//
+// The bounds come from the magnitude of the format, so operands of comparisons and products must
+// lie within it, while the range check of the quotient also makes a product whose rescaled value
+// leaves the magnitude unsatisfiable. Sums aren't range checked and should be kept within the
+// magnitude by the model. Backends with native signed integers, such as the Aleo integration,
+// implement the operations directly.
+
+/// Signed integer operations the fixed-point gadgets are built from
+pub trait ArithmeticBackend {
+    /// Value held by the backend, e.g. a linear combination of circuit variables
+    type Value: Clone;
+
+    /// Get a constant value
+    fn constant(&mut self, value: i64) -> Self::Value;
+
+    /// Add two values
+    fn add(&mut self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+
+    /// Subtract a value from another
+    fn sub(&mut self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+
+    /// Multiply two values
+    fn mul(&mut self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+
+    /// Divide a value by a positive constant rounding towards negative infinity, where the
+    /// quotient lies within [-2^bits, 2^bits)
+    fn div_floor(&mut self, a: &Self::Value, divisor: u64, bits: u32) -> Self::Value;
+
+    /// Get 1 if `a < b` and 0 otherwise, where |a - b| < 2^bits
+    fn less_than(&mut self, a: &Self::Value, b: &Self::Value, bits: u32) -> Self::Value;
+
+    /// Get `a` if the bit is 1 and `b` if it is 0
+    fn select(&mut self, bit: &Self::Value, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// Fixed-point addition, multiplication, comparison, ReLU and argmax on values of a format
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FixedPointGadgets {
+    // Format of the values
+    format: FixedPoint,
+    // Number of bits holding the largest magnitude of the format
+    magnitude_bits: u32,
+}
+
+impl FixedPointGadgets {
+    /// Create gadgets for values quantized with `format`
+    pub fn new(format: FixedPoint) -> Self {
+        Self {
+            format,
+            magnitude_bits: u64::BITS - format.get_max_magnitude().leading_zeros(),
+        }
+    }
+
+    /// Get the format of the values
+    pub fn get_format(&self) -> &FixedPoint {
+        &self.format
+    }
+
+    /// Add two values
+    pub fn add<B: ArithmeticBackend>(
+        &self,
+        backend: &mut B,
+        a: &B::Value,
+        b: &B::Value,
+    ) -> B::Value {
+        backend.add(a, b)
+    }
+
+    /// Multiply two values and rescale the product to the scale of the format
+    pub fn mul<B: ArithmeticBackend>(
+        &self,
+        backend: &mut B,
+        a: &B::Value,
+        b: &B::Value,
+    ) -> B::Value {
+        let product = backend.mul(a, b);
+        backend.div_floor(&product, self.format.get_scale(), self.magnitude_bits)
+    }
+
+    /// Get 1 if `a < b` and 0 otherwise
+    pub fn less_than<B: ArithmeticBackend>(
+        &self,
+        backend: &mut B,
+        a: &B::Value,
+        b: &B::Value,
+    ) -> B::Value {
+        // The difference of two values within the magnitude needs one more bit
+        backend.less_than(a, b, self.magnitude_bits + 1)
+    }
+
+    /// Get the larger of a value and zero
+    pub fn relu<B: ArithmeticBackend>(&self, backend: &mut B, a: &B::Value) -> B::Value {
+        let zero = backend.constant(0);
+        let negative = self.less_than(backend, a, &zero);
+        backend.select(&negative, &zero, a)
+    }
+
+    /// Get the index and value of the largest value, preferring the first one on ties
+    pub fn argmax<B: ArithmeticBackend>(
+        &self,
+        backend: &mut B,
+        values: &[B::Value],
+    ) -> Result<(B::Value, B::Value), Error> {
+        let (first, rest) = values.split_first().ok_or(Error::EmptyArgmax)?;
+        let mut index = backend.constant(0);
+        let mut max = first.clone();
+        for (i, value) in rest.iter().enumerate() {
+            let larger = self.less_than(backend, &max, value);
+            let i = backend.constant(i as i64 + 1);
+            index = backend.select(&larger, &i, &index);
+            max = backend.select(&larger, value, &max);
+        }
+        Ok((index, max))
+    }
+}
+
+// Get the signed integer a scalar encodes, assuming it lies within i128
+fn signed_value(scalar: &Scalar) -> i128 {
+    let low = |scalar: &Scalar| {
+        let bytes = scalar.to_bytes();
+        let is_small = bytes[16..].iter().all(|byte| *byte == 0);
+        let low = u128::from_le_bytes(bytes[..16].try_into().expect("scalars are 32 bytes"));
+        is_small.then_some(low as i128)
+    };
+    match (low(scalar), low(&-scalar)) {
+        (Some(value), _) => value,
+        (None, Some(value)) => -value,
+        // Values outside i128 only arise from invalid witnesses, which fail the range checks
+        (None, None) => i128::MIN,
+    }
+}
+
+impl ConstraintSystem {
+    // Decompose a linear combination into `bits` boolean witnesses, constraining it to lie
+    // within [0, 2^bits)
+    fn decompose_bits(&mut self, value: &LinearCombination, bits: u32) -> Vec<Variable> {
+        let witness = signed_value(&value.evaluate(self.get_assignment())) as u128;
+        let mut sum = LinearCombination::default();
+        let mut bit_variables = Vec::with_capacity(bits as usize);
+        let mut power = Scalar::one();
+        for i in 0..bits {
+            let bit = self.alloc_witness(Scalar::from(((witness >> i) & 1) as u64));
+            self.enforce(
+                bit,
+                LinearCombination::constant(Scalar::one()) - bit,
+                LinearCombination::default(),
+            );
+            sum = sum + (bit, power);
+            power = power.double();
+            bit_variables.push(bit);
+        }
+        self.enforce(sum, Variable::ONE, value.clone());
+        bit_variables
+    }
+}
+
+impl ArithmeticBackend for ConstraintSystem {
+    type Value = LinearCombination;
+
+    fn constant(&mut self, value: i64) -> LinearCombination {
+        LinearCombination::constant(scalar_from_i64::<Bls12_381>(value))
+    }
+
+    fn add(&mut self, a: &LinearCombination, b: &LinearCombination) -> LinearCombination {
+        a.clone() + b.clone()
+    }
+
+    fn sub(&mut self, a: &LinearCombination, b: &LinearCombination) -> LinearCombination {
+        a.clone() - b.clone()
+    }
+
+    fn mul(&mut self, a: &LinearCombination, b: &LinearCombination) -> LinearCombination {
+        ConstraintSystem::mul(self, a.clone(), b.clone()).into()
+    }
+
+    fn div_floor(&mut self, a: &LinearCombination, divisor: u64, bits: u32) -> LinearCombination {
+        let value = signed_value(&a.evaluate(self.get_assignment()));
+        let (quotient, remainder) = (
+            value.div_euclid(divisor as i128),
+            value.rem_euclid(divisor as i128),
+        );
+        let quotient = self.alloc_witness(scalar_from_i128(quotient));
+        let remainder = self.alloc_witness(Scalar::from(remainder as u64));
+        self.enforce(
+            LinearCombination::from(remainder) + (quotient, Scalar::from(divisor)),
+            Variable::ONE,
+            a.clone(),
+        );
+
+        // 0 <= r <= s - 1 and -2^bits <= q < 2^bits
+        let remainder_bits = u64::BITS - (divisor - 1).leading_zeros();
+        self.decompose_bits(&remainder.into(), remainder_bits);
+        let headroom = LinearCombination::constant(Scalar::from(divisor - 1)) - remainder;
+        self.decompose_bits(&headroom, remainder_bits);
+        let offset = LinearCombination::from(quotient) + (Variable::ONE, power_of_two(bits));
+        self.decompose_bits(&offset, bits + 1);
+        quotient.into()
+    }
+
+    fn less_than(
+        &mut self,
+        a: &LinearCombination,
+        b: &LinearCombination,
+        bits: u32,
+    ) -> LinearCombination {
+        let offset = a.clone() - b.clone() + (Variable::ONE, power_of_two(bits));
+        let at_least = self.decompose_bits(&offset, bits + 1)[bits as usize];
+        LinearCombination::constant(Scalar::one()) - at_least
+    }
+
+    fn select(
+        &mut self,
+        bit: &LinearCombination,
+        a: &LinearCombination,
+        b: &LinearCombination,
+    ) -> LinearCombination {
+        // bit * (a - b) + b
+        let difference = ConstraintSystem::mul(self, bit.clone(), a.clone() - b.clone());
+        LinearCombination::from(difference) + b.clone()
+    }
+}
+
+// Map a signed integer into the scalar field
+fn scalar_from_i128(value: i128) -> Scalar {
+    let magnitude = value.unsigned_abs();
+    let scalar =
+        Scalar::from((magnitude >> 64) as u64) * power_of_two(64) + Scalar::from(magnitude as u64);
+    if value < 0 {
+        -scalar
+    } else {
+        scalar
+    }
+}
+
+// Get 2^exponent as a scalar
+fn power_of_two(exponent: u32) -> Scalar {
+    (0..exponent).fold(Scalar::one(), |power, _| power.double())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Evaluate a gadget output on the assignment of a constraint system
+    fn evaluate(cs: &ConstraintSystem, value: &LinearCombination) -> i128 {
+        signed_value(&value.evaluate(cs.get_assignment()))
+    }
+
+    #[test]
+    fn test_gadgets_compute_fixed_point_arithmetic() {
+        let format = FixedPoint::with_bits(8, 16).unwrap();
+        let gadgets = FixedPointGadgets::new(format);
+        let mut cs = ConstraintSystem::new();
+        let quantized = [1.5, -2.25, 0.75, -0.5].map(|value| format.quantize(value).unwrap());
+        let values: Vec<LinearCombination> = quantized
+            .iter()
+            .map(|value| {
+                cs.alloc_witness(scalar_from_i64::<Bls12_381>(*value))
+                    .into()
+            })
+            .collect();
+
+        let sum = gadgets.add(&mut cs, &values[0], &values[1]);
+        assert_eq!(evaluate(&cs, &sum), format.quantize(-0.75).unwrap() as i128);
+        let product = gadgets.mul(&mut cs, &values[0], &values[1]);
+        assert_eq!(
+            evaluate(&cs, &product),
+            format.quantize(-3.375).unwrap() as i128
+        );
+        // -0.5 times the smallest positive value rounds down to minus the smallest value
+        let smallest = cs.alloc_witness(Scalar::one()).into();
+        let product = gadgets.mul(&mut cs, &values[3], &smallest);
+        assert_eq!(evaluate(&cs, &product), -1);
+
+        let smaller = gadgets.less_than(&mut cs, &values[1], &values[0]);
+        let larger = gadgets.less_than(&mut cs, &values[0], &values[1]);
+        let equal = gadgets.less_than(&mut cs, &values[0], &values[0]);
+        assert_eq!(
+            [smaller, larger, equal].map(|bit| evaluate(&cs, &bit)),
+            [1, 0, 0]
+        );
+        let relu = [&values[0], &values[1]].map(|value| gadgets.relu(&mut cs, value));
+        assert_eq!(
+            relu.map(|value| evaluate(&cs, &value)),
+            [quantized[0] as i128, 0]
+        );
+        let (index, max) = gadgets.argmax(&mut cs, &values).unwrap();
+        assert_eq!((evaluate(&cs, &index), evaluate(&cs, &max)), (0, 384));
+        let (index, _) = gadgets.argmax(&mut cs, &values[1..]).unwrap();
+        assert_eq!(evaluate(&cs, &index), 1);
+        assert!(cs.is_satisfied());
+        assert_eq!(gadgets.argmax(&mut cs, &[]), Err(Error::EmptyArgmax));
+    }
+
+    #[test]
+    fn test_out_of_range_products_and_comparisons_are_unsatisfiable() {
+        let format = FixedPoint::with_bits(4, 4).unwrap();
+        let gadgets = FixedPointGadgets::new(format);
+
+        // 15.5 * 15.5 doesn't fit into 8 bits of magnitude after rescaling
+        let mut cs = ConstraintSystem::new();
+        let value: LinearCombination = cs.alloc_witness(Scalar::from(248)).into();
+        gadgets.mul(&mut cs, &value, &value);
+        assert!(!cs.is_satisfied());
+
+        // Comparing values outside the magnitude
+        let mut cs = ConstraintSystem::new();
+        let a: LinearCombination = cs.alloc_witness(Scalar::from(300)).into();
+        let b: LinearCombination = cs.alloc_witness(-Scalar::from(300)).into();
+        gadgets.less_than(&mut cs, &b, &a);
+        assert!(!cs.is_satisfied());
+    }
+}
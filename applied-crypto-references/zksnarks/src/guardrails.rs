@@ -0,0 +1,297 @@
+//! Limits enforced before any expensive verification work to protect verifiers from maliciously
+//! large submissions
+
+use crate::{
+    error::Error,
+    kzg::{EvaluationProof, EvaluationStatement, KzgCommitment, KzgSetup, KZG_POINT_LEN},
+};
+use bls12_381::Scalar;
+use std::collections::HashMap;
+
+// Checking a KZG evaluation costs two pairings, and loading a reference string costs memory linear
+// in its degree. A verifier exposed to the public can be made to burn time and memory simply by
+// being sent huge batches, huge proofs or proofs it has to decode before noticing they are
+// garbage. Submissions are therefore checked in order of increasing cost and rejected at the first
+// failed check:
+//
+// 1. Length: the raw submission length is compared against the maximum before a single byte is
+// parsed.
+// 2. Batch size: the number of records follows from the length alone, so oversized batches are
+// rejected before decoding.
+// 3. Structure: every record must decode into canonical scalars and points of the prime order
+// subgroup. This costs a few scalar multiplications per record, far less than the pairings.
+// 4. Only then are the pairing checks run.
+//
+// A service verifying proofs for many clients additionally gives every API key its own limits and
+// a budget of proofs, charged only once a submission passes the cheap checks.
+
+/// Length of an encoded evaluation record: commitment, point, value and proof
+pub const EVALUATION_RECORD_LEN: usize = KZG_POINT_LEN + 32 + 32 + KZG_POINT_LEN;
+
+/// Limits a verifier enforces before doing any expensive work
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VerifierLimits {
+    /// Maximum length in bytes of a submission
+    pub max_submission_len: usize,
+    /// Maximum degree of the reference string a verifier loads
+    pub max_setup_degree: usize,
+    /// Maximum number of proofs in a single submission
+    pub max_batch_size: usize,
+}
+
+impl Default for VerifierLimits {
+    fn default() -> Self {
+        Self {
+            max_submission_len: 64 * EVALUATION_RECORD_LEN,
+            max_setup_degree: 1 << 16,
+            max_batch_size: 64,
+        }
+    }
+}
+
+impl VerifierLimits {
+    /// Check a reference string is small enough to verify with
+    pub fn check_setup(&self, setup: &KzgSetup) -> Result<(), Error> {
+        if setup.max_degree() > self.max_setup_degree {
+            return Err(Error::SetupTooLarge(
+                setup.max_degree(),
+                self.max_setup_degree,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Decode a reference string, refusing to allocate one larger than the verifier's limit
+    pub fn load_setup(&self, bytes: &[u8]) -> Result<KzgSetup, Error> {
+        KzgSetup::from_bytes(bytes, self.max_setup_degree)
+    }
+
+    /// Run the cheap checks on a submission and decode its evaluation records
+    pub fn prevalidate(
+        &self,
+        submission: &[u8],
+    ) -> Result<Vec<(EvaluationStatement, EvaluationProof)>, Error> {
+        if submission.len() > self.max_submission_len {
+            return Err(Error::SubmissionTooLarge(
+                submission.len(),
+                self.max_submission_len,
+            ));
+        }
+        let num_records = submission.len() / EVALUATION_RECORD_LEN;
+        if num_records > self.max_batch_size {
+            return Err(Error::BatchTooLarge(num_records, self.max_batch_size));
+        }
+        if !submission.len().is_multiple_of(EVALUATION_RECORD_LEN) {
+            return Err(Error::MalformedSubmission(
+                num_records * EVALUATION_RECORD_LEN,
+            ));
+        }
+        submission
+            .chunks(EVALUATION_RECORD_LEN)
+            .enumerate()
+            .map(|(index, record)| {
+                decode_record(record)
+                    .ok_or(Error::MalformedSubmission(index * EVALUATION_RECORD_LEN))
+            })
+            .collect()
+    }
+}
+
+// Decode a record of exactly `EVALUATION_RECORD_LEN` bytes
+fn decode_record(record: &[u8]) -> Option<(EvaluationStatement, EvaluationProof)> {
+    let (commitment, rest) = record.split_at(KZG_POINT_LEN);
+    let (point, rest) = rest.split_at(32);
+    let (value, proof) = rest.split_at(32);
+    let statement = EvaluationStatement {
+        commitment: KzgCommitment::from_bytes(commitment.try_into().ok()?).ok()?,
+        point: Option::from(Scalar::from_bytes(point.try_into().ok()?))?,
+        value: Option::from(Scalar::from_bytes(value.try_into().ok()?))?,
+    };
+    let proof = EvaluationProof::from_bytes(proof.try_into().ok()?).ok()?;
+    Some((statement, proof))
+}
+
+/// Encode evaluation statements and their proofs into a submission
+pub fn encode_submission(evaluations: &[(EvaluationStatement, EvaluationProof)]) -> Vec<u8> {
+    let mut submission = Vec::with_capacity(evaluations.len() * EVALUATION_RECORD_LEN);
+    for (statement, proof) in evaluations {
+        submission.extend_from_slice(&statement.commitment.to_bytes());
+        submission.extend_from_slice(&statement.point.to_bytes());
+        submission.extend_from_slice(&statement.value.to_bytes());
+        submission.extend_from_slice(&proof.to_bytes());
+    }
+    submission
+}
+
+/// Check the limits and then verify every evaluation in a submission
+pub fn verify_submission(
+    setup: &KzgSetup,
+    limits: &VerifierLimits,
+    submission: &[u8],
+) -> Result<bool, Error> {
+    limits.check_setup(setup)?;
+    Ok(limits
+        .prevalidate(submission)?
+        .iter()
+        .all(|(statement, proof)| setup.verify_evaluation(statement, proof)))
+}
+
+/// Limits and proof budget of a single API key
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VerifierQuota {
+    /// Limits applied to the key's submissions
+    pub limits: VerifierLimits,
+    /// Total number of proofs the key may submit
+    pub max_proofs: u64,
+}
+
+/// Per API key quotas of a verification service
+#[derive(Clone, Debug, Default)]
+pub struct QuotaRegistry {
+    // Quota of each API key and the number of proofs it has used
+    quotas: HashMap<String, (VerifierQuota, u64)>,
+}
+
+impl QuotaRegistry {
+    /// Create a registry without any API keys
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the quota of an API key, keeping the number of proofs it has already used
+    pub fn set_quota(&mut self, api_key: &str, quota: VerifierQuota) {
+        let used = self.quotas.get(api_key).map_or(0, |(_, used)| *used);
+        self.quotas.insert(api_key.to_string(), (quota, used));
+    }
+
+    /// Get the number of proofs an API key may still submit
+    pub fn remaining(&self, api_key: &str) -> Result<u64, Error> {
+        let (quota, used) = self
+            .quotas
+            .get(api_key)
+            .ok_or(Error::UnknownApiKey(api_key.to_string()))?;
+        Ok(quota.max_proofs.saturating_sub(*used))
+    }
+
+    /// Verify a submission under an API key's limits, charging its proofs to the key's budget once
+    /// the submission passes the cheap checks
+    pub fn verify_submission(
+        &mut self,
+        api_key: &str,
+        setup: &KzgSetup,
+        submission: &[u8],
+    ) -> Result<bool, Error> {
+        let (quota, used) = self
+            .quotas
+            .get_mut(api_key)
+            .ok_or(Error::UnknownApiKey(api_key.to_string()))?;
+        quota.limits.check_setup(setup)?;
+        let evaluations = quota.limits.prevalidate(submission)?;
+        let num_proofs = evaluations.len() as u64;
+        if *used + num_proofs > quota.max_proofs {
+            return Err(Error::QuotaExceeded(api_key.to_string()));
+        }
+        *used += num_proofs;
+        Ok(evaluations
+            .iter()
+            .all(|(statement, proof)| setup.verify_evaluation(statement, proof)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Prove evaluations of f(x) = 3 + 2x + x^2 at the points 1, .., n
+    fn evaluations(setup: &KzgSetup, n: u64) -> Vec<(EvaluationStatement, EvaluationProof)> {
+        let coefficients = vec![Scalar::from(3u64), Scalar::from(2u64), Scalar::one()];
+        let commitment = setup.commit(&coefficients).unwrap();
+        (1..=n)
+            .map(|x| {
+                let opening = setup.open(&coefficients, &Scalar::from(x)).unwrap();
+                opening.into_evaluation(commitment, Scalar::from(x))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_oversized_and_malformed_submissions_are_rejected_before_verification() {
+        let setup = KzgSetup::new(4);
+        let limits = VerifierLimits {
+            max_submission_len: 4 * EVALUATION_RECORD_LEN,
+            max_setup_degree: 4,
+            max_batch_size: 3,
+        };
+        let submission = encode_submission(&evaluations(&setup, 3));
+        assert_eq!(verify_submission(&setup, &limits, &submission), Ok(true));
+
+        let oversized = vec![0; 5 * EVALUATION_RECORD_LEN];
+        assert_eq!(
+            limits.prevalidate(&oversized),
+            Err(Error::SubmissionTooLarge(
+                5 * EVALUATION_RECORD_LEN,
+                4 * EVALUATION_RECORD_LEN
+            ))
+        );
+        assert_eq!(
+            limits.prevalidate(&oversized[..4 * EVALUATION_RECORD_LEN]),
+            Err(Error::BatchTooLarge(4, 3))
+        );
+        assert_eq!(
+            limits.prevalidate(&submission[..EVALUATION_RECORD_LEN + 1]),
+            Err(Error::MalformedSubmission(EVALUATION_RECORD_LEN))
+        );
+        let mut corrupted = submission.clone();
+        corrupted[EVALUATION_RECORD_LEN + KZG_POINT_LEN + 31] = 0xff;
+        assert_eq!(
+            limits.prevalidate(&corrupted),
+            Err(Error::MalformedSubmission(EVALUATION_RECORD_LEN))
+        );
+        assert_eq!(
+            verify_submission(&KzgSetup::new(5), &limits, &submission),
+            Err(Error::SetupTooLarge(5, 4))
+        );
+        assert_eq!(limits.load_setup(&setup.to_bytes()), Ok(setup));
+        assert_eq!(
+            limits.load_setup(&KzgSetup::new(5).to_bytes()),
+            Err(Error::SetupTooLarge(5, 4))
+        );
+    }
+
+    #[test]
+    fn test_api_keys_are_limited_to_their_quota() {
+        let setup = KzgSetup::new(4);
+        let mut registry = QuotaRegistry::new();
+        registry.set_quota(
+            "client",
+            VerifierQuota {
+                limits: VerifierLimits::default(),
+                max_proofs: 5,
+            },
+        );
+        let submission = encode_submission(&evaluations(&setup, 3));
+        assert_eq!(
+            registry.verify_submission("client", &setup, &submission),
+            Ok(true)
+        );
+        assert_eq!(registry.remaining("client"), Ok(2));
+        assert_eq!(
+            registry.verify_submission("client", &setup, &submission),
+            Err(Error::QuotaExceeded("client".to_string()))
+        );
+        assert_eq!(
+            registry.verify_submission("stranger", &setup, &submission),
+            Err(Error::UnknownApiKey("stranger".to_string()))
+        );
+
+        // Raising the quota keeps the proofs already used
+        registry.set_quota(
+            "client",
+            VerifierQuota {
+                limits: VerifierLimits::default(),
+                max_proofs: 6,
+            },
+        );
+        assert_eq!(registry.remaining("client"), Ok(3));
+    }
+}
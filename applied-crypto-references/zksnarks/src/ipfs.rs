@@ -0,0 +1,324 @@
+//! Async IPFS client pinning and retrieving content addressed artifacts such as model weights and
+//! KZG reference strings
+
+use crate::error::Error;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+// IPFS names content by a hash of the content itself, a content identifier (CID). A client that
+// recomputes the CID of everything it downloads doesn't have to trust the node it talks to: a
+// node returning different bytes is caught immediately. That makes IPFS a good distribution
+// backend for large artifacts whose commitments are published elsewhere, as long as the client
+// actually performs the check.
+//
+// Files added through the usual `ipfs add` are split into a tree of blocks whose CID depends on
+// the chunking parameters, which makes it awkward to recompute. This client instead stores
+// artifacts as raw blocks of at most 1 MiB through the block API, each named by a CIDv1 with the
+// `raw` codec and a sha2-256 multihash, and describes an artifact by the list of its block CIDs.
+// Anyone holding the artifact can compute this manifest offline, so a registry only needs to
+// record the manifest to let clients verify everything they fetch.
+
+/// Largest block the client stores. IPFS nodes refuse to exchange blocks much larger than this.
+pub const MAX_BLOCK_LEN: usize = 1 << 20;
+
+// CIDv1 prefix: version 1, raw codec (0x55), sha2-256 multihash (0x12) of 32 bytes
+const CID_PREFIX: [u8; 4] = [0x01, 0x55, 0x12, 0x20];
+
+// Lowercase RFC 4648 base32 alphabet used by the default CIDv1 string encoding
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+// Multibase prefix of lowercase base32
+const BASE32_MULTIBASE_PREFIX: char = 'b';
+
+/// CIDv1 of a raw block hashed with sha2-256
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Cid {
+    // sha2-256 digest of the block
+    digest: [u8; 32],
+}
+
+impl Cid {
+    /// Compute the CID of a raw block
+    pub fn for_block(block: &[u8]) -> Self {
+        Self {
+            digest: Sha256::digest(block).into(),
+        }
+    }
+
+    /// Check that a block matches this CID
+    pub fn verify(&self, block: &[u8]) -> bool {
+        Self::for_block(block) == *self
+    }
+
+    /// Get the binary representation of the CID
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [CID_PREFIX.as_slice(), self.digest.as_slice()].concat()
+    }
+
+    /// Parse a base32 encoded CIDv1 of a raw sha2-256 block
+    pub fn parse(cid: &str) -> Result<Self, Error> {
+        let encoded = cid
+            .strip_prefix(BASE32_MULTIBASE_PREFIX)
+            .ok_or_else(|| Error::InvalidCid(cid.to_string()))?;
+        let bytes = decode_base32(encoded).ok_or_else(|| Error::InvalidCid(cid.to_string()))?;
+        if bytes.len() != CID_PREFIX.len() + 32 || bytes[..CID_PREFIX.len()] != CID_PREFIX {
+            return Err(Error::InvalidCid(cid.to_string()));
+        }
+        let mut digest = [0; 32];
+        digest.copy_from_slice(&bytes[CID_PREFIX.len()..]);
+        Ok(Self { digest })
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            BASE32_MULTIBASE_PREFIX,
+            encode_base32(&self.to_bytes())
+        )
+    }
+}
+
+// Encode bytes as unpadded lowercase base32
+fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    encoded
+}
+
+// Decode unpadded lowercase base32, returning None for characters outside the alphabet
+fn decode_base32(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in encoded.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|a| *a == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// List of the blocks an artifact is stored as, which is all a registry needs to record
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArtifactManifest {
+    // CIDs of the artifact's blocks in order
+    blocks: Vec<Cid>,
+    // Length of the artifact in bytes
+    len: usize,
+}
+
+impl ArtifactManifest {
+    /// Compute the manifest of an artifact without contacting an IPFS node
+    pub fn for_artifact(artifact: &[u8]) -> Self {
+        Self {
+            blocks: artifact.chunks(MAX_BLOCK_LEN).map(Cid::for_block).collect(),
+            len: artifact.len(),
+        }
+    }
+
+    /// Get the CIDs of the artifact's blocks in order
+    pub fn get_blocks(&self) -> &[Cid] {
+        &self.blocks
+    }
+
+    /// Get the length of the artifact in bytes
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+}
+
+// Response of the block/put endpoint
+#[derive(Deserialize)]
+struct BlockPutResponse {
+    #[serde(rename = "Key")]
+    key: String,
+}
+
+/// Client for the RPC API of an IPFS node (such as Kubo) which verifies all content it handles
+#[derive(Clone, Debug)]
+pub struct IpfsClient {
+    http: reqwest::Client,
+    // Base url of the node's RPC API, e.g. http://127.0.0.1:5001
+    api_url: String,
+}
+
+impl IpfsClient {
+    /// Create a client for the node RPC API at `api_url`
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // Send a request to an RPC endpoint, returning the response body
+    async fn post(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+        form: Option<reqwest::multipart::Form>,
+    ) -> Result<reqwest::Response, Error> {
+        let mut request = self
+            .http
+            .post(format!("{}/api/v0/{}", self.api_url, endpoint))
+            .query(query);
+        if let Some(form) = form {
+            request = request.multipart(form);
+        }
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::IpfsRequest(e.to_string()))
+    }
+
+    /// Store and pin a block, checking the node names it with the expected CID
+    pub async fn put_block(&self, block: &[u8]) -> Result<Cid, Error> {
+        if block.len() > MAX_BLOCK_LEN {
+            return Err(Error::BlockTooLarge(block.len()));
+        }
+        let expected = Cid::for_block(block);
+        let form = reqwest::multipart::Form::new()
+            .part("file", reqwest::multipart::Part::bytes(block.to_vec()));
+        let response: BlockPutResponse = self
+            .post(
+                "block/put",
+                &[
+                    ("cid-codec", "raw"),
+                    ("mhtype", "sha2-256"),
+                    ("pin", "true"),
+                ],
+                Some(form),
+            )
+            .await?
+            .json()
+            .await
+            .map_err(|e| Error::IpfsRequest(e.to_string()))?;
+        if Cid::parse(&response.key)? != expected {
+            return Err(Error::CidMismatch(expected.to_string()));
+        }
+        Ok(expected)
+    }
+
+    /// Retrieve a block, checking it matches its CID
+    pub async fn get_block(&self, cid: &Cid) -> Result<Vec<u8>, Error> {
+        let block = self
+            .post("block/get", &[("arg", &cid.to_string())], None)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| Error::IpfsRequest(e.to_string()))?;
+        if !cid.verify(&block) {
+            return Err(Error::CidMismatch(cid.to_string()));
+        }
+        Ok(block.to_vec())
+    }
+
+    /// Pin an existing block so the node keeps it
+    pub async fn pin(&self, cid: &Cid) -> Result<(), Error> {
+        self.post("pin/add", &[("arg", &cid.to_string())], None)
+            .await
+            .map(|_| ())
+    }
+
+    /// Store and pin an artifact of any size, returning its manifest
+    pub async fn put_artifact(&self, artifact: &[u8]) -> Result<ArtifactManifest, Error> {
+        for block in artifact.chunks(MAX_BLOCK_LEN) {
+            self.put_block(block).await?;
+        }
+        Ok(ArtifactManifest::for_artifact(artifact))
+    }
+
+    /// Retrieve an artifact, checking every block against the manifest
+    pub async fn get_artifact(&self, manifest: &ArtifactManifest) -> Result<Vec<u8>, Error> {
+        let mut artifact = Vec::with_capacity(manifest.len);
+        for cid in manifest.blocks.iter() {
+            artifact.extend(self.get_block(cid).await?);
+        }
+        if artifact.len() != manifest.len {
+            return Err(Error::CidMismatch(format!("{} bytes", manifest.len)));
+        }
+        Ok(artifact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    // Serve a single HTTP response with the given body and return the node's url
+    async fn serve_once(body: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request).await.unwrap();
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).await.unwrap();
+            stream.write_all(&body).await.unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn test_cid_matches_ipfs_and_round_trips() {
+        // `ipfs block put --cid-codec raw` of "hello world"
+        let cid = Cid::for_block(b"hello world");
+        assert_eq!(
+            cid.to_string(),
+            "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e"
+        );
+        assert_eq!(Cid::parse(&cid.to_string()).unwrap(), cid);
+        assert!(matches!(
+            Cid::parse("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"),
+            Err(Error::InvalidCid(_))
+        ));
+
+        let artifact = vec![7u8; MAX_BLOCK_LEN + 1];
+        let manifest = ArtifactManifest::for_artifact(&artifact);
+        assert_eq!(manifest.get_blocks().len(), 2);
+        assert_eq!(manifest.get_blocks()[1], Cid::for_block(&[7]));
+    }
+
+    #[tokio::test]
+    async fn test_client_rejects_blocks_not_matching_their_cid() {
+        let cid = Cid::for_block(b"model weights");
+        let client = IpfsClient::new(&serve_once(b"model weights".to_vec()).await);
+        assert_eq!(client.get_block(&cid).await.unwrap(), b"model weights");
+
+        // A node serving different bytes is caught
+        let client = IpfsClient::new(&serve_once(b"tampered weights".to_vec()).await);
+        assert_eq!(
+            client.get_block(&cid).await,
+            Err(Error::CidMismatch(cid.to_string()))
+        );
+    }
+}
@@ -0,0 +1,616 @@
+//! KZG polynomial commitments over the BLS12-381 curve
+
+use crate::{error::Error, witness_file::CoefficientSource};
+use bls12_381::{
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use encodings::{CommitAndProve, PointEncoding, ProofScheme, SecretScalar, SecurityLevel};
+use ff::Field;
+use std::ops::Add;
+use tracing::instrument;
+use zeroize::Zeroize;
+
+// The KZG (Kate-Zaverucha-Goldberg) scheme commits to a polynomial f(x) with a single curve point
+// and later proves the value of f(z) at any point z with another single curve point.
+//
+// 1. Setup: A secret scalar tau is chosen and the reference string <G1, G1*tau, .., G1*tau^n>
+// and G2*tau is published. tau itself must be destroyed after the setup.
+// 2. Commit: The committer evaluates f at tau "in the exponent" by multiplying the coefficients
+// of f with the reference string points giving C = G1*f(tau).
+// 3. Open: To prove f(z) = y, the committer computes the quotient q(x) = (f(x) - y) / (x - z).
+// This division only has no remainder if f(z) really is y. The proof is G1*q(tau).
+// 4. Verify: The verifier checks pair(C - G1*y, G2) == pair(G1*q(tau), G2*tau - G2*z) which
+// holds exactly when f(tau) - y = q(tau) * (tau - z).
+
+/// Commitment to a polynomial created with a [`KzgSetup`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KzgCommitment {
+    // Evaluation of the committed polynomial at the secret setup scalar
+    pub(crate) point: G1Affine,
+}
+
+/// Length of a compressed G1 point encoding a commitment or proof
+pub const KZG_POINT_LEN: usize = 48;
+
+impl KzgCommitment {
+    /// Get the curve point representing the commitment
+    pub fn get_point(&self) -> G1Affine {
+        self.point
+    }
+
+    /// Encode the commitment as a compressed curve point
+    pub fn to_bytes(&self) -> [u8; KZG_POINT_LEN] {
+        self.point.to_compressed()
+    }
+
+    /// Decode a commitment, checking the point lies in the prime order subgroup
+    pub fn from_bytes(bytes: &[u8; KZG_POINT_LEN]) -> Result<Self, Error> {
+        decode_point(bytes).map(|point| Self { point })
+    }
+}
+
+// Decode a compressed G1 point, checking it lies on the curve and in the prime order subgroup
+fn decode_point(bytes: &[u8; KZG_POINT_LEN]) -> Result<G1Affine, Error> {
+    Option::from(G1Affine::from_compressed(bytes)).ok_or(Error::InvalidPointEncoding)
+}
+
+/// Commitments are additively homomorphic: the sum of two commitments commits to the sum of the
+/// committed polynomials
+impl Add for KzgCommitment {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            point: (G1Projective::from(self.point) + other.point).into(),
+        }
+    }
+}
+
+/// Claimed evaluation of a committed polynomial along with a proof of its correctness
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KzgOpening {
+    // Value of the polynomial at the opened point
+    pub(crate) value: Scalar,
+    // Commitment to the quotient polynomial (f(x) - f(z)) / (x - z)
+    pub(crate) proof: G1Affine,
+}
+
+impl KzgOpening {
+    /// Get the claimed value of the polynomial at the opened point
+    pub fn get_value(&self) -> Scalar {
+        self.value
+    }
+}
+
+/// Public statement that a committed polynomial evaluates to `value` at `point`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EvaluationStatement {
+    /// Commitment to the polynomial
+    pub commitment: KzgCommitment,
+    /// Public evaluation point
+    pub point: Scalar,
+    /// Claimed value of the polynomial at the point
+    pub value: Scalar,
+}
+
+/// Proof of an [`EvaluationStatement`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EvaluationProof {
+    // Commitment to the quotient polynomial (f(x) - y) / (x - z)
+    quotient: G1Affine,
+}
+
+impl EvaluationProof {
+    /// Encode the proof as a compressed curve point
+    pub fn to_bytes(&self) -> [u8; KZG_POINT_LEN] {
+        self.quotient.to_compressed()
+    }
+
+    /// Decode a proof, checking the point lies in the prime order subgroup
+    pub fn from_bytes(bytes: &[u8; KZG_POINT_LEN]) -> Result<Self, Error> {
+        decode_point(bytes).map(|quotient| Self { quotient })
+    }
+
+    // Get the commitment to the quotient polynomial
+    pub(crate) fn get_quotient(&self) -> G1Affine {
+        self.quotient
+    }
+}
+
+impl KzgOpening {
+    /// Split the opening of a commitment at a point into the statement it proves and its proof
+    pub fn into_evaluation(
+        self,
+        commitment: KzgCommitment,
+        point: Scalar,
+    ) -> (EvaluationStatement, EvaluationProof) {
+        (
+            EvaluationStatement {
+                commitment,
+                point,
+                value: self.value,
+            },
+            EvaluationProof {
+                quotient: self.proof,
+            },
+        )
+    }
+}
+
+// WIRE FORMAT
+// Reference strings are distributed as the degree `n` as 4 little endian bytes, the n + 1
+// compressed G1 powers and the compressed G2*tau. Decoding is bounded by the largest degree the
+// reader is willing to load: the degree is checked against the bound and the total length against
+// the degree before anything is allocated, so a forged header can't make the reader allocate
+// more than the bound allows.
+
+/// Length of a compressed G2 point in an encoded reference string
+pub const KZG_G2_POINT_LEN: usize = 96;
+
+// Length of the degree header of an encoded reference string
+const SETUP_HEADER_LEN: usize = 4;
+
+/// Structured reference string for KZG commitments to polynomials up to a maximum degree
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KzgSetup {
+    // Powers of the secret setup scalar multiplied by the G1 generator
+    pub(crate) powers: Vec<G1Projective>,
+    // Secret setup scalar multiplied by the G2 generator
+    pub(crate) tau_g2: G2Affine,
+}
+
+impl KzgSetup {
+    /// Create a reference string supporting polynomials up to `max_degree` using a random secret
+    /// scalar which is overwritten with zeros once the reference string is computed
+    #[instrument(name = "setup", level = "debug", skip_all, fields(max_degree))]
+    pub fn new(max_degree: usize) -> Self {
+        let tau = SecretScalar::new(Scalar::random(&mut rand::thread_rng()));
+        Self::from_secret(tau.get_scalar(), max_degree)
+    }
+
+    // Create a reference string from a known secret scalar, erasing the powers of the secret
+    pub(crate) fn from_secret(tau: &Scalar, max_degree: usize) -> Self {
+        let g1 = G1Projective::generator();
+        let mut power = Scalar::one();
+        let mut powers = Vec::with_capacity(max_degree + 1);
+        for _ in 0..=max_degree {
+            powers.push(g1 * power);
+            power *= tau;
+        }
+        power.zeroize();
+        Self {
+            powers,
+            tau_g2: G2Affine::from(G2Projective::generator() * tau),
+        }
+    }
+
+    // Multiply the secret by `contribution`, scaling every power tau^i by contribution^i
+    pub(crate) fn rerandomize(&self, contribution: &Scalar) -> Self {
+        let mut factor = Scalar::one();
+        let powers = self
+            .powers
+            .iter()
+            .map(|power| {
+                let power = power * factor;
+                factor *= contribution;
+                power
+            })
+            .collect();
+        factor.zeroize();
+        Self {
+            powers,
+            tau_g2: (G2Projective::from(self.tau_g2) * contribution).into(),
+        }
+    }
+
+    /// Maximum degree of polynomial this reference string can commit to
+    pub fn max_degree(&self) -> usize {
+        self.powers.len() - 1
+    }
+
+    /// Encode the reference string in its wire format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            SETUP_HEADER_LEN + self.powers.len() * KZG_POINT_LEN + KZG_G2_POINT_LEN,
+        );
+        bytes.extend((self.max_degree() as u32).to_le_bytes());
+        bytes.extend(G1Projective::encode_batch(&self.powers));
+        bytes.extend(self.tau_g2.to_compressed());
+        bytes
+    }
+
+    /// Decode a reference string of degree at most `max_degree`, checking every point lies in
+    /// its prime order subgroup and the first power is the G1 generator
+    pub fn from_bytes(bytes: &[u8], max_degree: usize) -> Result<Self, Error> {
+        let header = bytes
+            .get(..SETUP_HEADER_LEN)
+            .ok_or(Error::MalformedSetup(bytes.len()))?;
+        let degree = u32::from_le_bytes(header.try_into().expect("header is 4 bytes")) as usize;
+        if degree > max_degree {
+            return Err(Error::SetupTooLarge(degree, max_degree));
+        }
+        let powers_len = (degree + 1) * KZG_POINT_LEN;
+        if bytes.len() != SETUP_HEADER_LEN + powers_len + KZG_G2_POINT_LEN {
+            return Err(Error::MalformedSetup(bytes.len()));
+        }
+
+        let (powers, tau_g2) = bytes[SETUP_HEADER_LEN..].split_at(powers_len);
+        let powers = powers
+            .chunks(KZG_POINT_LEN)
+            .map(|power| {
+                decode_point(power.try_into().expect("chunk is a point")).map(G1Projective::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if powers[0] != G1Projective::generator() {
+            return Err(Error::MalformedSetup(bytes.len()));
+        }
+        let tau_g2 = Option::from(G2Affine::from_compressed(
+            tau_g2.try_into().expect("remainder is a G2 point"),
+        ))
+        .ok_or(Error::InvalidPointEncoding)?;
+        Ok(Self { powers, tau_g2 })
+    }
+
+    /// Commit to a polynomial given by its coefficients in ascending order of degree
+    #[instrument(name = "prove", level = "debug", skip_all, fields(coefficients = coefficients.len()))]
+    pub fn commit(&self, coefficients: &[Scalar]) -> Result<KzgCommitment, Error> {
+        self.commit_source(coefficients)
+    }
+
+    /// Commit to a polynomial reading its coefficients one at a time
+    pub fn commit_source<S: CoefficientSource + ?Sized>(
+        &self,
+        coefficients: &S,
+    ) -> Result<KzgCommitment, Error> {
+        self.check_degree(coefficients.num_coefficients())?;
+        let mut point = G1Projective::identity();
+        for (i, power) in self.powers[..coefficients.num_coefficients()]
+            .iter()
+            .enumerate()
+        {
+            point += power * coefficients.coefficient(i)?;
+        }
+        Ok(KzgCommitment {
+            point: point.into(),
+        })
+    }
+
+    /// Evaluate a polynomial at a point and prove the evaluation is correct
+    #[instrument(name = "prove", level = "debug", skip_all, fields(coefficients = coefficients.len()))]
+    pub fn open(&self, coefficients: &[Scalar], point: &Scalar) -> Result<KzgOpening, Error> {
+        self.open_source(coefficients, point)
+    }
+
+    /// Evaluate a polynomial at a point and prove the evaluation is correct, reading the
+    /// coefficients one at a time from the highest degree down
+    pub fn open_source<S: CoefficientSource + ?Sized>(
+        &self,
+        coefficients: &S,
+        point: &Scalar,
+    ) -> Result<KzgOpening, Error> {
+        let len = coefficients.num_coefficients();
+        self.check_degree(len)?;
+
+        // Synthetic division yields the quotient's coefficients from the highest degree down, so
+        // they are committed to as they are produced instead of being collected first. The final
+        // Horner step gives the evaluation.
+        let mut carry = Scalar::zero();
+        let mut proof = G1Projective::identity();
+        for i in (1..len).rev() {
+            carry = coefficients.coefficient(i)? + carry * point;
+            proof += self.powers[i - 1] * carry;
+        }
+        let value = match len {
+            0 => Scalar::zero(),
+            _ => coefficients.coefficient(0)? + carry * point,
+        };
+        Ok(KzgOpening {
+            value,
+            proof: proof.into(),
+        })
+    }
+
+    /// Prove a public evaluation statement about a polynomial, failing if the polynomial doesn't
+    /// take the claimed value at the point
+    pub fn prove_evaluation(
+        &self,
+        coefficients: &[Scalar],
+        statement: &EvaluationStatement,
+    ) -> Result<EvaluationProof, Error> {
+        let opening = self.open(coefficients, &statement.point)?;
+        if opening.value != statement.value {
+            return Err(Error::EvaluationMismatch);
+        }
+        Ok(EvaluationProof {
+            quotient: opening.proof,
+        })
+    }
+
+    /// Verify a public evaluation statement
+    #[instrument(name = "verify", level = "debug", skip_all)]
+    pub fn verify_evaluation(
+        &self,
+        statement: &EvaluationStatement,
+        proof: &EvaluationProof,
+    ) -> bool {
+        let (commitment_key, point_key) = self.statement_keys(statement);
+        let lhs = bls12_381::pairing(&commitment_key, &G2Affine::generator());
+        let rhs = bls12_381::pairing(&proof.quotient, &point_key);
+        let verified = lhs == rhs;
+
+        // Recompute the check in product form as a debug build cross-check of the pairing math
+        debug_assert_eq!(
+            verified,
+            multi_miller_loop(&[
+                (&commitment_key, &G2Prepared::from(G2Affine::generator())),
+                (&-proof.quotient, &G2Prepared::from(point_key)),
+            ])
+            .final_exponentiation()
+                == Gt::identity(),
+            "pairing check disagrees with its product form"
+        );
+        verified
+    }
+
+    // Compute the inputs of the verification pairings which only depend on the statement, i.e.
+    // C - G1*y and G2*tau - G2*z
+    pub(crate) fn statement_keys(&self, statement: &EvaluationStatement) -> (G1Affine, G2Affine) {
+        let g1 = G1Projective::generator();
+        let g2 = G2Projective::generator();
+        (
+            G1Affine::from(G1Projective::from(statement.commitment.point) - g1 * statement.value),
+            G2Affine::from(G2Projective::from(self.tau_g2) - g2 * statement.point),
+        )
+    }
+
+    // Check a polynomial with `num_coefficients` coefficients fits the reference string
+    fn check_degree(&self, num_coefficients: usize) -> Result<(), Error> {
+        if num_coefficients > self.powers.len() {
+            return Err(Error::DegreeExceedsSetup(
+                num_coefficients - 1,
+                self.max_degree(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Verify that a committed polynomial evaluates to the opening's value at a point
+    #[instrument(name = "verify", level = "debug", skip_all)]
+    pub fn verify_opening(
+        &self,
+        commitment: &KzgCommitment,
+        point: &Scalar,
+        opening: &KzgOpening,
+    ) -> bool {
+        let (statement, proof) = opening.into_evaluation(*commitment, *point);
+        self.verify_evaluation(&statement, &proof)
+    }
+}
+
+/// Commit-and-prove backend committing to polynomials and proving their evaluations. KZG proofs
+/// are bound to their statement without a transcript, so the context is ignored.
+impl CommitAndProve for KzgSetup {
+    /// Coefficients of the polynomial in ascending order of degree
+    type Witness = Vec<Scalar>;
+    type Commitment = KzgCommitment;
+    /// Coefficients of the committed polynomial
+    type Opening = Vec<Scalar>;
+    /// Evaluation point and the claimed value of the polynomial at it
+    type Statement = (Scalar, Scalar);
+    type Proof = EvaluationProof;
+    type Error = Error;
+
+    fn commit(&self, witness: &Vec<Scalar>) -> Result<(KzgCommitment, Vec<Scalar>), Error> {
+        Ok((KzgSetup::commit(self, witness)?, witness.clone()))
+    }
+
+    fn prove(
+        &self,
+        commitment: &KzgCommitment,
+        opening: &Vec<Scalar>,
+        statement: &(Scalar, Scalar),
+        _context: &[u8],
+    ) -> Result<EvaluationProof, Error> {
+        let statement = EvaluationStatement {
+            commitment: *commitment,
+            point: statement.0,
+            value: statement.1,
+        };
+        self.prove_evaluation(opening, &statement)
+    }
+
+    fn verify(
+        &self,
+        commitment: &KzgCommitment,
+        statement: &(Scalar, Scalar),
+        proof: &EvaluationProof,
+        _context: &[u8],
+    ) -> Result<(), Error> {
+        let statement = EvaluationStatement {
+            commitment: *commitment,
+            point: statement.0,
+            value: statement.1,
+        };
+        match self.verify_evaluation(&statement, proof) {
+            true => Ok(()),
+            false => Err(Error::EvaluationMismatch),
+        }
+    }
+}
+
+// Evaluate a polynomial given by coefficients in ascending order of degree using Horner's rule
+pub(crate) fn evaluate(coefficients: &[Scalar], x: &Scalar) -> Scalar {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Scalar::zero(), |acc, c| acc * x + c)
+}
+
+// Divide f(x) - f(z) by (x - z) using synthetic division, returning the quotient's coefficients
+// in ascending order of degree
+pub(crate) fn divide_by_linear(coefficients: &[Scalar], z: &Scalar) -> Vec<Scalar> {
+    if coefficients.len() < 2 {
+        return Vec::new();
+    }
+    let mut quotient = vec![Scalar::zero(); coefficients.len() - 1];
+    let mut carry = Scalar::zero();
+    for i in (1..coefficients.len()).rev() {
+        carry = coefficients[i] + carry * z;
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
+
+// Find the coefficients of the unique polynomial of degree < n passing through n points with
+// distinct x values using Lagrange interpolation
+pub(crate) fn interpolate(points: &[(Scalar, Scalar)]) -> Vec<Scalar> {
+    // Vanishing polynomial z(x) = (x - x_0)(x - x_1)..(x - x_(n-1))
+    let mut vanishing = vec![Scalar::one()];
+    for (x, _) in points {
+        let mut next = vec![Scalar::zero(); vanishing.len() + 1];
+        for (i, c) in vanishing.iter().enumerate() {
+            next[i] -= c * x;
+            next[i + 1] += c;
+        }
+        vanishing = next;
+    }
+
+    // Sum y_i * L_i(x) where L_i(x) = (z(x) / (x - x_i)) / prod_(j != i)(x_i - x_j)
+    let mut coefficients = vec![Scalar::zero(); points.len()];
+    for (i, (x_i, y_i)) in points.iter().enumerate() {
+        let numerator = divide_by_linear(&vanishing, x_i);
+        let denominator = points
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(Scalar::one(), |acc, (_, (x_j, _))| acc * (x_i - x_j));
+        let scale = y_i * denominator.invert().unwrap();
+        for (c, n) in coefficients.iter_mut().zip(numerator.iter()) {
+            *c += scale * n;
+        }
+    }
+    coefficients
+}
+
+// Sound as long as nobody knows the secret, which `KzgSetup::new` leaves to the caller to ensure,
+// e.g. by applying contributions from a ceremony or beacon
+impl ProofScheme for KzgSetup {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kzg_opening_verifies_for_correct_evaluation() {
+        // f(x) = 3 + 2x + x^2
+        let coefficients = vec![Scalar::from(3u64), Scalar::from(2u64), Scalar::one()];
+        let setup = KzgSetup::new(4);
+        let commitment = setup.commit(&coefficients).unwrap();
+        let point = Scalar::from(5u64);
+        let opening = setup.open(&coefficients, &point).unwrap();
+
+        assert_eq!(opening.get_value(), Scalar::from(38u64));
+        assert!(setup.verify_opening(&commitment, &point, &opening));
+
+        // The same opening doesn't verify at a different point or with a different value
+        assert!(!setup.verify_opening(&commitment, &Scalar::from(6u64), &opening));
+        let forged_opening = KzgOpening {
+            value: Scalar::from(39u64),
+            proof: opening.proof,
+        };
+        assert!(!setup.verify_opening(&commitment, &point, &forged_opening));
+    }
+
+    #[test]
+    fn test_commit_and_prove_reuses_one_commitment_for_several_evaluations() {
+        let setup = KzgSetup::new(4);
+        // f(x) = 3 + 2x + x^2
+        let witness = vec![Scalar::from(3u64), Scalar::from(2u64), Scalar::one()];
+        let (commitment, opening) = CommitAndProve::commit(&setup, &witness).unwrap();
+        for (point, value) in [(5u64, 38u64), (0, 3)] {
+            let statement = (Scalar::from(point), Scalar::from(value));
+            let proof = setup.prove(&commitment, &opening, &statement, b"").unwrap();
+            assert_eq!(setup.verify(&commitment, &statement, &proof, b""), Ok(()));
+        }
+        let false_statement = (Scalar::from(5u64), Scalar::from(39u64));
+        assert_eq!(
+            setup.prove(&commitment, &opening, &false_statement, b""),
+            Err(Error::EvaluationMismatch)
+        );
+    }
+
+    #[test]
+    fn test_public_evaluation_statements_prove_only_the_claimed_value() {
+        // f(x) = 3 + 2x + x^2
+        let coefficients = vec![Scalar::from(3u64), Scalar::from(2u64), Scalar::one()];
+        let setup = KzgSetup::new(4);
+        let statement = EvaluationStatement {
+            commitment: setup.commit(&coefficients).unwrap(),
+            point: Scalar::from(5u64),
+            value: Scalar::from(38u64),
+        };
+        let proof = setup.prove_evaluation(&coefficients, &statement).unwrap();
+        assert!(setup.verify_evaluation(&statement, &proof));
+
+        // The prover refuses false statements and the proof doesn't carry over to other values
+        let false_statement = EvaluationStatement {
+            value: Scalar::from(39u64),
+            ..statement
+        };
+        assert_eq!(
+            setup.prove_evaluation(&coefficients, &false_statement),
+            Err(Error::EvaluationMismatch)
+        );
+        assert!(!setup.verify_evaluation(&false_statement, &proof));
+    }
+
+    #[test]
+    fn test_setup_decoding_is_bounded_by_the_maximum_degree() {
+        let setup = KzgSetup::new(8);
+        let bytes = setup.to_bytes();
+        assert_eq!(bytes.len(), 4 + 9 * KZG_POINT_LEN + KZG_G2_POINT_LEN);
+        assert_eq!(KzgSetup::from_bytes(&bytes, 8), Ok(setup.clone()));
+        assert_eq!(
+            KzgSetup::from_bytes(&bytes, 7).err(),
+            Some(Error::SetupTooLarge(8, 7))
+        );
+
+        // A header claiming a huge degree is rejected before its points are read
+        let mut forged = bytes.clone();
+        forged[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            KzgSetup::from_bytes(&forged, 1 << 16).err(),
+            Some(Error::SetupTooLarge(u32::MAX as usize, 1 << 16))
+        );
+        for malformed in [&bytes[..3], &bytes[..bytes.len() - 1]] {
+            assert_eq!(
+                KzgSetup::from_bytes(malformed, 8).err(),
+                Some(Error::MalformedSetup(malformed.len()))
+            );
+        }
+
+        // Every power has to be a valid point and the string has to start at the generator
+        let mut invalid = bytes.clone();
+        invalid[4 + KZG_POINT_LEN + 5] ^= 1;
+        assert!(KzgSetup::from_bytes(&invalid, 8).is_err());
+        let mut shifted = setup.clone();
+        shifted.powers.remove(0);
+        assert_eq!(
+            KzgSetup::from_bytes(&shifted.to_bytes(), 8).err(),
+            Some(Error::MalformedSetup(
+                4 + 8 * KZG_POINT_LEN + KZG_G2_POINT_LEN
+            ))
+        );
+    }
+
+    #[test]
+    fn test_kzg_rejects_polynomials_larger_than_setup() {
+        let setup = KzgSetup::new(1);
+        assert_eq!(
+            setup.commit(&[Scalar::one(); 3]).err(),
+            Some(Error::DegreeExceedsSetup(2, 1))
+        );
+    }
+}
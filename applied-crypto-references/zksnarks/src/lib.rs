@@ -1,13 +1,87 @@
-#![feature(associated_type_defaults)]
-
+mod amortized;
+mod beacon;
+mod ceremony;
+mod cost_estimator;
+mod curve_backend;
+mod data_availability;
+mod delegated;
+mod dense_polynomial;
+mod domain;
+mod eip712;
 mod encrypted_zksnark;
 mod error;
+mod evm;
+mod extractor;
+mod fft;
+mod gadgets;
+mod guardrails;
+mod ipfs;
+mod kzg;
+mod merkle;
+mod msm;
+mod pairing;
+mod pairing_snark;
 mod polynomial;
+mod polynomial_relations;
+mod qap;
+mod r1cs;
+mod solidity;
+mod tutorials;
 mod unencrypted_zksnark;
+mod witness_file;
 
 pub use crate::{
+    amortized::{statement_digest, AmortizedVerifier, DEFAULT_STATEMENT_CACHE_LEN},
+    beacon::{BeaconChain, BeaconClient, BeaconRandomness, BeaconRound, BeaconScheme},
+    ceremony::{verify_contribution, Ceremony, Contribution, ContributionProof},
+    cost_estimator::{CalibrationProfile, CostEstimator},
+    curve_backend::{scalar_from_i64, CurveBackend, Ristretto},
+    data_availability::{
+        DataAvailabilityCommitment, DataAvailabilitySampler, DataSample, ErasureCodedData,
+    },
+    delegated::{
+        DelegationMask, DelegationRequest, DelegationResponse, PendingCommitment, PendingOpening,
+    },
+    dense_polynomial::DensePolynomial,
+    domain::{barycentric_weights, EvaluationDomain},
+    eip712::{
+        eth_address, recover_signer, Eip712Domain, ProofStatement, StatementSigner, TypedStatement,
+        ADDRESS_LEN, ETH_SIGNATURE_LEN,
+    },
     encrypted_zksnark::{ProverTranscript, VerifierTranscript},
-    error::Error,
+    error::{Error, VerificationError},
+    evm::{
+        decode_evm_g1, decode_evm_g2, encode_ec_add_input, encode_ec_mul_input, encode_evm_g1,
+        encode_evm_g2, encode_evm_scalar, encode_pairing_input, EVM_G1_LEN, EVM_G2_LEN,
+        EVM_WORD_LEN,
+    },
+    extractor::{extract_polynomial, ExtractedPolynomial},
+    fft::{fft, fft_polynomial_mul, ifft, naive_polynomial_mul, polynomial_mul},
+    gadgets::{ArithmeticBackend, FixedPointGadgets},
+    guardrails::{
+        encode_submission, verify_submission, QuotaRegistry, VerifierLimits, VerifierQuota,
+        EVALUATION_RECORD_LEN,
+    },
+    ipfs::{ArtifactManifest, Cid, IpfsClient, MAX_BLOCK_LEN},
+    kzg::{
+        EvaluationProof, EvaluationStatement, KzgCommitment, KzgOpening, KzgSetup, KZG_G2_POINT_LEN,
+        KZG_POINT_LEN,
+    },
+    merkle::{MerklePath, MerkleTree},
+    msm::{multiscalar_mul, naive_multiscalar_mul},
+    pairing::{Bls12_381, Bn254, PairingGroup},
+    pairing_snark::{coefficients_from_roots, PairingProverTranscript, PairingVerifierTranscript},
     polynomial::{Polynomial, Root, SimpleRoot, UnencryptedPolynomial},
-    unencrypted_zksnark::UnencryptedChallengeResponse,
+    polynomial_relations::{verify_sum, ProductProof},
+    qap::Qap,
+    r1cs::{ConstraintSystem, LinearCombination, R1csMatrices, Variable},
+    solidity::{
+        encode_verifier_calldata, generate_solidity_verifier, verify_proof_selector,
+        VERIFY_PROOF_SIGNATURE,
+    },
+    tutorials::zksnark_tutorial,
+    unencrypted_zksnark::{
+        RepeatedChallengeVerifier, SoundnessReport, UnencryptedChallengeResponse,
+    },
+    witness_file::{write_coefficients, CoefficientSource, MappedCoefficients, COEFFICIENT_LEN},
 };
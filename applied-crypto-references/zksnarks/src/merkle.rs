@@ -0,0 +1,291 @@
+//! Poseidon Merkle trees over BLS12-381 scalars with inclusion proofs checkable in circuits
+
+use crate::{ConstraintSystem, Error, LinearCombination, Variable};
+use bls12_381::Scalar;
+use encodings::{pack_bytes, Digest, HashBackend, PoseidonParameters, POSEIDON_WIDTH};
+
+// A Merkle tree commits to a list of field elements, e.g. the quantized weights of a model or a
+// batch of inference inputs, with a single root. Its nodes are hashed with the Poseidon backend
+// of the encodings crate, so a circuit can recompute a path with a few thousand constraints:
+//
+// 1. Every leaf is hashed as the pair (leaf, 0) under a leaf domain separator, and the hashed
+// leaves are padded with hashed zero leaves up to a power of two. Hashing leaves separately from
+// inner nodes keeps an inner node from being passed off as a leaf.
+// 2. Every inner node is the hash of its two children under a node domain separator, up to the
+// root.
+// 3. An inclusion proof is the position of the leaf and the sibling of every node on the way up.
+// The verifier hashes the leaf with the siblings, putting the running node on the left or the
+// right by the bits of the position, and compares the result with the root.
+//
+// The inclusion gadget does the same in a constraint system. The bits of the position and the
+// siblings are private witnesses, so the circuit shows that some leaf of the tree has the given
+// value without revealing which, and each bit costs a multiplication choosing the order of the
+// children. Poseidon itself is recomputed with the backend's own parameters: the S-box x^5 costs
+// three multiplications, while round constants and the MDS matrix are free in the linear
+// combinations.
+
+// Domain separator for hashing leaves
+const LEAF_DOMAIN_SEP: &[u8] = b"MERKLE_LEAF";
+
+// Domain separator for hashing inner nodes
+const NODE_DOMAIN_SEP: &[u8] = b"MERKLE_NODE";
+
+// Hash two field elements with the Poseidon backend
+fn hash_pair(domain: &[u8], left: &Scalar, right: &Scalar) -> Scalar {
+    let digest = HashBackend::Poseidon.hash_pair(domain, &left.to_bytes(), &right.to_bytes());
+    Scalar::from_bytes(&digest).expect("Poseidon digests are field elements")
+}
+
+// Hash a leaf of the tree
+fn hash_leaf(leaf: &Scalar) -> Scalar {
+    hash_pair(LEAF_DOMAIN_SEP, leaf, &Scalar::zero())
+}
+
+// Hash the children of an inner node
+fn hash_node(left: &Scalar, right: &Scalar) -> Scalar {
+    hash_pair(NODE_DOMAIN_SEP, left, right)
+}
+
+/// Merkle tree over field elements hashed with Poseidon
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MerkleTree {
+    // Levels of the tree from the hashed leaves up to the root
+    levels: Vec<Vec<Scalar>>,
+    // Number of leaves before padding
+    num_leaves: usize,
+}
+
+impl MerkleTree {
+    /// Build a tree over the leaves
+    pub fn new(leaves: &[Scalar]) -> Result<Self, Error> {
+        if leaves.is_empty() {
+            return Err(Error::NoData);
+        }
+        let mut level: Vec<Scalar> = leaves.iter().map(hash_leaf).collect();
+        level.resize(leaves.len().next_power_of_two(), hash_leaf(&Scalar::zero()));
+        let mut levels = vec![level];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let parents = level
+                .chunks(2)
+                .map(|children| hash_node(&children[0], &children[1]))
+                .collect();
+            levels.push(parents);
+        }
+        Ok(Self {
+            levels,
+            num_leaves: leaves.len(),
+        })
+    }
+
+    /// Get the root committing to the leaves
+    pub fn root(&self) -> Scalar {
+        self.levels[self.depth()][0]
+    }
+
+    /// Get the number of levels above the leaves
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Get the number of leaves before padding
+    pub fn num_leaves(&self) -> usize {
+        self.num_leaves
+    }
+
+    /// Prove that the leaf at `index` is in the tree
+    pub fn prove(&self, index: usize) -> Result<MerklePath, Error> {
+        if index >= self.num_leaves {
+            return Err(Error::LeafOutOfRange(index));
+        }
+        let siblings = self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+            .collect();
+        Ok(MerklePath { index, siblings })
+    }
+}
+
+/// Inclusion proof of a leaf of a Merkle tree
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MerklePath {
+    // Position of the leaf
+    index: usize,
+    // Sibling of every node from the leaf up to the root
+    siblings: Vec<Scalar>,
+}
+
+impl MerklePath {
+    /// Get the position of the leaf
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the sibling of every node from the leaf up to the root
+    pub fn get_siblings(&self) -> &[Scalar] {
+        &self.siblings
+    }
+
+    /// Compute the root of the tree the path leads to from a leaf
+    pub fn compute_root(&self, leaf: &Scalar) -> Scalar {
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(hash_leaf(leaf), |node, (level, sibling)| {
+                match (self.index >> level) & 1 {
+                    0 => hash_node(&node, sibling),
+                    _ => hash_node(sibling, &node),
+                }
+            })
+    }
+
+    /// Verify that the leaf is in the tree with the root
+    pub fn verify(&self, root: &Scalar, leaf: &Scalar) -> bool {
+        self.index >> self.siblings.len() == 0 && self.compute_root(leaf) == *root
+    }
+
+    /// Constrain `leaf` to be in the tree with `root`, with the position of the leaf and the
+    /// siblings of the path as private witnesses
+    pub fn enforce_inclusion(
+        &self,
+        cs: &mut ConstraintSystem,
+        leaf: impl Into<LinearCombination>,
+        root: impl Into<LinearCombination>,
+    ) {
+        let one = LinearCombination::constant(Scalar::one());
+        let mut node = hash_gadget(
+            cs,
+            LEAF_DOMAIN_SEP,
+            leaf.into(),
+            LinearCombination::default(),
+        );
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            let is_right = cs.alloc_witness(Scalar::from(((self.index >> level) & 1) as u64));
+            cs.enforce(
+                is_right,
+                one.clone() - is_right,
+                LinearCombination::default(),
+            );
+            let sibling = cs.alloc_witness(*sibling);
+
+            // The left child is the sibling if the node is on the right, and the right child is
+            // the other of the two
+            let swap = cs.mul(is_right, LinearCombination::from(sibling) - node.clone());
+            let left = node.clone() + swap;
+            let right = LinearCombination::from(sibling) - swap;
+            node = hash_gadget(cs, NODE_DOMAIN_SEP, left, right);
+        }
+        cs.enforce(node, Variable::ONE, root);
+    }
+}
+
+// Recompute a pair hash of the Poseidon backend in a constraint system
+fn hash_gadget(
+    cs: &mut ConstraintSystem,
+    domain: &[u8],
+    left: LinearCombination,
+    right: LinearCombination,
+) -> LinearCombination {
+    let mut elements: Vec<LinearCombination> = pack_bytes(domain)
+        .into_iter()
+        .map(LinearCombination::constant)
+        .collect();
+    elements.extend([left, right]);
+
+    // Pair hashes start from a capacity element of 1
+    let mut state = [
+        LinearCombination::constant(Scalar::one()),
+        LinearCombination::default(),
+        LinearCombination::default(),
+    ];
+    for block in elements.chunks(POSEIDON_WIDTH - 1) {
+        for (lane, element) in state[1..].iter_mut().zip(block) {
+            *lane = lane.clone() + element.clone();
+        }
+        permutation_gadget(cs, &mut state);
+    }
+    state[1].clone()
+}
+
+// Apply the Poseidon permutation to a state of linear combinations
+fn permutation_gadget(cs: &mut ConstraintSystem, state: &mut [LinearCombination; POSEIDON_WIDTH]) {
+    let parameters = PoseidonParameters::get();
+    for (round, constants) in parameters.round_constants().iter().enumerate() {
+        for (lane, constant) in state.iter_mut().zip(constants) {
+            *lane = lane.clone() + (Variable::ONE, *constant);
+        }
+        let sboxed = match parameters.is_full_round(round) {
+            true => POSEIDON_WIDTH,
+            false => 1,
+        };
+        for lane in state[..sboxed].iter_mut() {
+            // x^5 as x^2, x^4 and x^4 * x
+            let square = cs.mul(lane.clone(), lane.clone());
+            let fourth = cs.mul(square, square);
+            *lane = cs.mul(fourth, lane.clone()).into();
+        }
+        *state = parameters.mds().map(|row| {
+            row.iter()
+                .zip(state.iter())
+                .fold(
+                    LinearCombination::default(),
+                    |mixed, (coefficient, lane)| mixed + lane.clone() * *coefficient,
+                )
+                .compact()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u64) -> Vec<Scalar> {
+        (0..count).map(|i| Scalar::from(i * i + 7)).collect()
+    }
+
+    #[test]
+    fn test_inclusion_proofs_verify_against_the_root() {
+        let leaves = leaves(5);
+        let tree = MerkleTree::new(&leaves).unwrap();
+        assert_eq!((tree.depth(), tree.num_leaves()), (3, 5));
+        for (index, leaf) in leaves.iter().enumerate() {
+            let path = tree.prove(index).unwrap();
+            assert_eq!(path.get_index(), index);
+            assert!(path.verify(&tree.root(), leaf));
+            assert!(!path.verify(&tree.root(), &(leaf + Scalar::one())));
+        }
+
+        // Paths don't verify for another position or tree
+        let path = tree.prove(1).unwrap();
+        let moved = MerklePath {
+            index: 3,
+            siblings: path.get_siblings().to_vec(),
+        };
+        assert!(!moved.verify(&tree.root(), &leaves[1]));
+        let other_tree = MerkleTree::new(&leaves[..4]).unwrap();
+        assert_ne!(other_tree.root(), tree.root());
+        assert!(!path.verify(&other_tree.root(), &leaves[1]));
+        assert_eq!(tree.prove(5), Err(Error::LeafOutOfRange(5)));
+        assert_eq!(MerkleTree::new(&[]), Err(Error::NoData));
+    }
+
+    #[test]
+    fn test_inclusion_gadget_is_satisfied_only_by_leaves_of_the_tree() {
+        let leaves = leaves(4);
+        let tree = MerkleTree::new(&leaves).unwrap();
+        let path = tree.prove(2).unwrap();
+        let circuit = |leaf: Scalar| {
+            let mut cs = ConstraintSystem::new();
+            let root = cs.alloc_input(tree.root());
+            let leaf = cs.alloc_witness(leaf);
+            path.enforce_inclusion(&mut cs, leaf, root);
+            cs
+        };
+
+        let cs = circuit(leaves[2]);
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.get_public_inputs().len(), 1);
+        assert!(!circuit(leaves[1]).is_satisfied());
+    }
+}
@@ -0,0 +1,113 @@
+//! Multi-scalar multiplication of BLS12-381 G1 points with Pippenger's bucket method
+
+use bls12_381::{G1Projective, Scalar};
+
+// Evaluating a polynomial at encrypted powers means computing c_1*P_1 + .. + c_n*P_n. Doing every
+// scalar multiplication separately costs about 255 doublings and 128 additions per term. Pippenger's
+// method instead splits every scalar into windows of w bits and, one window at a time:
+//
+// 1. Adds every point into the bucket of its scalar's digit in the window, one addition per term.
+// 2. Sums the buckets weighted by their digit with a running sum, about 2^(w+1) additions.
+// 3. Doubles the accumulated result w times before moving on to the next lower window.
+//
+// With a window of about log2(n) bits this takes roughly 255/w * (n + 2^(w+1)) additions in total
+// rather than 383n, which pays off from a few dozen terms on. Below that the naive sum is used.
+
+// Number of bits in a scalar
+const SCALAR_BITS: usize = 255;
+
+// Number of terms below which the naive sum of scalar multiplications is faster
+const PIPPENGER_THRESHOLD: usize = 32;
+
+/// Compute the sum of `scalars[i] * points[i]` by multiplying every point separately, ignoring
+/// terms without a counterpart
+pub fn naive_multiscalar_mul(points: &[G1Projective], scalars: &[Scalar]) -> G1Projective {
+    points
+        .iter()
+        .zip(scalars.iter())
+        .map(|(point, scalar)| point * scalar)
+        .sum()
+}
+
+/// Compute the sum of `scalars[i] * points[i]` with Pippenger's method, ignoring terms without a
+/// counterpart
+pub fn multiscalar_mul(points: &[G1Projective], scalars: &[Scalar]) -> G1Projective {
+    let len = points.len().min(scalars.len());
+    if len < PIPPENGER_THRESHOLD {
+        return naive_multiscalar_mul(points, scalars);
+    }
+    let window_bits = window_bits(len);
+    let scalars: Vec<[u8; 32]> = scalars[..len].iter().map(Scalar::to_bytes).collect();
+    let mut result = G1Projective::identity();
+    for window in (0..SCALAR_BITS.div_ceil(window_bits)).rev() {
+        for _ in 0..window_bits {
+            result = result.double();
+        }
+        // Bucket i holds the sum of the points whose digit in this window is i + 1
+        let mut buckets = vec![G1Projective::identity(); (1 << window_bits) - 1];
+        for (point, scalar) in points.iter().zip(scalars.iter()) {
+            let digit = window_digit(scalar, window * window_bits, window_bits);
+            if digit > 0 {
+                buckets[digit - 1] += point;
+            }
+        }
+        // The running sum adds bucket i into the total i + 1 times
+        let mut running_sum = G1Projective::identity();
+        for bucket in buckets.iter().rev() {
+            running_sum += bucket;
+            result += running_sum;
+        }
+    }
+    result
+}
+
+// Window size minimizing the number of additions for `len` terms
+fn window_bits(len: usize) -> usize {
+    (usize::BITS - len.leading_zeros()).saturating_sub(2).max(4) as usize
+}
+
+// Read `bits` bits of a little endian scalar starting at bit `offset`
+fn window_digit(scalar: &[u8; 32], offset: usize, bits: usize) -> usize {
+    (offset..(offset + bits).min(SCALAR_BITS))
+        .filter(|bit| scalar[bit / 8] >> (bit % 8) & 1 == 1)
+        .map(|bit| 1 << (bit - offset))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+
+    #[test]
+    fn test_pippenger_matches_the_naive_sum() {
+        let mut rng = rand::thread_rng();
+        for len in [0, 1, 31, 32, 100, 513] {
+            let points: Vec<_> = (0..len)
+                .map(|_| G1Projective::generator() * Scalar::random(&mut rng))
+                .collect();
+            let scalars: Vec<_> = (0..len).map(|_| Scalar::random(&mut rng)).collect();
+            assert_eq!(
+                multiscalar_mul(&points, &scalars),
+                naive_multiscalar_mul(&points, &scalars)
+            );
+        }
+    }
+
+    #[test]
+    fn test_pippenger_handles_extreme_scalars_and_mismatched_lengths() {
+        let points: Vec<_> = (1..=64u64)
+            .map(|i| G1Projective::generator() * Scalar::from(i))
+            .collect();
+        let mut scalars = vec![-Scalar::one(), Scalar::zero(), Scalar::one()];
+        scalars.resize(points.len() + 10, -Scalar::from(2u64));
+        assert_eq!(
+            multiscalar_mul(&points, &scalars),
+            naive_multiscalar_mul(&points, &scalars)
+        );
+        assert_eq!(
+            multiscalar_mul(&points[..40], &scalars),
+            naive_multiscalar_mul(&points[..40], &scalars[..40])
+        );
+    }
+}
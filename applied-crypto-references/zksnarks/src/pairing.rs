@@ -0,0 +1,191 @@
+//! Abstraction over the pairing-friendly curves the snark examples can be run over
+
+use ark_ec::{pairing::Pairing, Group};
+use ark_ff::Zero;
+use ark_std::UniformRand;
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Gt};
+use ff::Field;
+use rand::{CryptoRng, RngCore};
+use std::{
+    fmt::Debug,
+    ops::{Add, Mul, Neg},
+};
+
+use crate::{fft::polynomial_mul, msm::multiscalar_mul, CurveBackend};
+
+// The encrypted polynomial proof and KZG commitments only need a few things from a curve: two
+// groups G1 and G2 of the same prime order, a target group GT and a bilinear map
+// `pair(a*P, b*Q) = pair(P, Q)*(a*b)` from G1 x G2 into GT. Any curve providing these can run the
+// same verification equations, but different ecosystems settled on different curves:
+//
+// * BLS12-381 targets roughly 128 bits of security and is used by Zcash, Ethereum consensus and
+// most of the examples in this crate.
+// * BN254 (also known as alt_bn128) has a smaller ~100 bit security level after improvements in
+// discrete log attacks, but it is the only pairing curve the EVM supports through the precompiles
+// at addresses 0x06 (addition), 0x07 (scalar multiplication) and 0x08 (pairing check). Proofs
+// that should be verified in an Ethereum contract have to be created over BN254.
+//
+// Target groups are written additively in both libraries, so a product of pairings being the
+// identity in GT is written here as a sum of pairings being the identity.
+
+/// Pairing-friendly curve whose points form the first source group G1, with a second source group
+/// G2 and a target group GT sharing its scalar field
+pub trait PairingGroup: CurveBackend {
+    /// Point in the second source group
+    type G2: Copy
+        + Debug
+        + Eq
+        + Add<Output = Self::G2>
+        + Neg<Output = Self::G2>
+        + Mul<Self::Scalar, Output = Self::G2>;
+
+    /// Element of the target group
+    type Gt: Copy + Debug + Eq + Add<Output = Self::Gt>;
+
+    /// Generator of G2
+    fn g2_generator() -> Self::G2;
+
+    /// Identity of the target group
+    fn gt_identity() -> Self::Gt;
+
+    /// Compute the pairing of a G1 and a G2 point
+    fn pairing(p: &Self::Point, q: &Self::G2) -> Self::Gt;
+
+    /// Check that the pairings of all pairs combine to the identity of the target group. This is
+    /// the check performed by the EVM pairing precompile.
+    fn pairing_check(pairs: &[(Self::Point, Self::G2)]) -> bool {
+        pairs
+            .iter()
+            .fold(Self::gt_identity(), |acc, (p, q)| acc + Self::pairing(p, q))
+            == Self::gt_identity()
+    }
+}
+
+/// The BLS12-381 curve
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bls12_381;
+
+impl CurveBackend for Bls12_381 {
+    const NAME: &'static str = "BLS12-381";
+
+    type Scalar = bls12_381::Scalar;
+    type Point = G1Projective;
+
+    fn generator() -> Self::Point {
+        G1Projective::generator()
+    }
+
+    fn identity() -> Self::Point {
+        G1Projective::identity()
+    }
+
+    fn random_scalar<R: CryptoRng + RngCore>(rng: &mut R) -> Self::Scalar {
+        bls12_381::Scalar::random(rng)
+    }
+
+    fn invert_scalar(scalar: &Self::Scalar) -> Option<Self::Scalar> {
+        scalar.invert().into()
+    }
+
+    fn multiscalar_mul(points: &[Self::Point], scalars: &[Self::Scalar]) -> Self::Point {
+        multiscalar_mul(points, scalars)
+    }
+
+    fn polynomial_mul(a: &[Self::Scalar], b: &[Self::Scalar]) -> Vec<Self::Scalar> {
+        polynomial_mul(a, b)
+    }
+}
+
+impl PairingGroup for Bls12_381 {
+    type G2 = G2Projective;
+    type Gt = Gt;
+
+    fn g2_generator() -> Self::G2 {
+        G2Projective::generator()
+    }
+
+    fn gt_identity() -> Self::Gt {
+        Gt::identity()
+    }
+
+    fn pairing(p: &Self::Point, q: &Self::G2) -> Self::Gt {
+        bls12_381::pairing(&G1Affine::from(p), &G2Affine::from(q))
+    }
+}
+
+/// The BN254 (alt_bn128) curve supported by the EVM precompiles
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bn254;
+
+impl CurveBackend for Bn254 {
+    const NAME: &'static str = "BN254";
+
+    type Scalar = ark_bn254::Fr;
+    type Point = ark_bn254::G1Projective;
+
+    fn generator() -> Self::Point {
+        ark_bn254::G1Projective::generator()
+    }
+
+    fn identity() -> Self::Point {
+        ark_bn254::G1Projective::zero()
+    }
+
+    fn random_scalar<R: CryptoRng + RngCore>(rng: &mut R) -> Self::Scalar {
+        ark_bn254::Fr::rand(rng)
+    }
+
+    fn invert_scalar(scalar: &Self::Scalar) -> Option<Self::Scalar> {
+        ark_ff::Field::inverse(scalar)
+    }
+}
+
+impl PairingGroup for Bn254 {
+    type G2 = ark_bn254::G2Projective;
+    type Gt = ark_ec::pairing::PairingOutput<ark_bn254::Bn254>;
+
+    fn g2_generator() -> Self::G2 {
+        ark_bn254::G2Projective::generator()
+    }
+
+    fn gt_identity() -> Self::Gt {
+        // The default target field element is zero rather than the multiplicative identity
+        Self::Gt::zero()
+    }
+
+    fn pairing(p: &Self::Point, q: &Self::G2) -> Self::Gt {
+        ark_bn254::Bn254::pairing(p, q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scalar_from_i64;
+
+    fn check_bilinearity<E: PairingGroup>() {
+        let mut rng = rand::thread_rng();
+        let a = E::random_scalar(&mut rng);
+        let b = E::random_scalar(&mut rng);
+        let p = E::generator();
+        let q = E::g2_generator();
+        assert_eq!(
+            E::pairing(&(p * a), &(q * b)),
+            E::pairing(&(p * (a * b)), &q)
+        );
+
+        // pair(a*P, b*Q) + pair(-a*b*P, Q) is the identity
+        assert!(E::pairing_check(&[(p * a, q * b), (-(p * (a * b)), q)]));
+        assert!(!E::pairing_check(&[(p * a, q * b), (-(p * a), q)]));
+    }
+
+    #[test]
+    fn test_pairings_are_bilinear_on_all_curves() {
+        check_bilinearity::<Bls12_381>();
+        check_bilinearity::<Bn254>();
+        assert_eq!(
+            scalar_from_i64::<Bn254>(-5) + ark_bn254::Fr::from(5u64),
+            ark_bn254::Fr::from(0u64)
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! The encrypted polynomial proof from the zksnark example over any [`PairingGroup`]
+
+use crate::{
+    curve_backend::{scalar_from_i64, CurveBackend},
+    error::Error,
+    pairing::PairingGroup,
+    polynomial::expand_roots,
+};
+use encodings::{Profile, ProofScheme, SecurityLevel};
+
+// This is the same protocol as the `VerifierTranscript` and `ProverTranscript` example, written
+// against the `PairingGroup` trait instead of BLS12-381 directly so the proof can also be created
+// over BN254 and checked by the EVM pairing precompile. Polynomials are given by their
+// coefficients in ascending order of degree instead of by their roots. It doesn't bind the proof
+// to the degree of the polynomial, so only its two original pairing checks fit the precompile.
+//
+// Every verification equation `pair(A, B) == pair(C, D)` is rewritten as the pairing check
+// `pair(A, B) + pair(-C, D) == identity`, which is the form the precompile accepts.
+//
+// Both equations are also satisfied by a proof made of identity points, i.e. the zero polynomial,
+// which is divisible by any public polynomial. Verifiers with a strictly validating security
+// profile reject such proofs.
+
+/// Prover's evaluations of their polynomial at the verifier's encrypted powers
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PairingProverTranscript<E: PairingGroup> {
+    // Evaluation of the prover's polynomial at the encrypted challenge powers
+    px_eval: E::Point,
+    // Evaluation of the prover's polynomial at the shifted encrypted challenge powers
+    px_powers_eval: E::Point,
+    // Evaluation of the hidden polynomial at the encrypted challenge powers
+    hx_eval: E::Point,
+}
+
+impl<E: PairingGroup> PairingProverTranscript<E> {
+    /// Get the prover's evaluations as a tuple of the form (p(s), p(s_shifted), h(s))
+    pub fn get_proof_values(&self) -> (E::Point, E::Point, E::Point) {
+        (self.px_eval, self.px_powers_eval, self.hx_eval)
+    }
+}
+
+/// Verifier's encrypted powers of a secret challenge scalar and the keys used to check the
+/// prover's evaluations
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PairingVerifierTranscript<E: PairingGroup> {
+    // G1*s^i for i in 0..=degree
+    encrypted_powers: Vec<E::Point>,
+    // G1*shift*s^i for i in 0..=degree
+    shifted_powers: Vec<E::Point>,
+    // G2*t(s) where t(x) is the public polynomial
+    public_root_verification_key: E::G2,
+    // G2*shift
+    power_verification_key: E::G2,
+    // Security profile deciding whether degenerate proofs are rejected
+    profile: Profile,
+}
+
+impl<E: PairingGroup> PairingVerifierTranscript<E> {
+    /// Create a verifier transcript for a prover claiming to know a polynomial of `degree` which
+    /// is divisible by the public polynomial `t(x)`
+    pub fn new(public_coefficients: &[E::Scalar], degree: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        let scalar = E::random_scalar(&mut rng);
+        let shift = E::random_scalar(&mut rng);
+        Self::from_secrets(public_coefficients, degree, &scalar, &shift)
+    }
+
+    // Create a verifier transcript from known secret scalars
+    pub(crate) fn from_secrets(
+        public_coefficients: &[E::Scalar],
+        degree: usize,
+        scalar: &E::Scalar,
+        shift: &E::Scalar,
+    ) -> Self {
+        let g1 = E::generator();
+        let mut power = E::Scalar::from(1);
+        let mut encrypted_powers = Vec::with_capacity(degree + 1);
+        let mut shifted_powers = Vec::with_capacity(degree + 1);
+        for _ in 0..=degree {
+            encrypted_powers.push(g1 * power);
+            shifted_powers.push(g1 * (*shift * power));
+            power = power * *scalar;
+        }
+        Self {
+            encrypted_powers,
+            shifted_powers,
+            public_root_verification_key: E::g2_generator()
+                * evaluate::<E>(public_coefficients, scalar),
+            power_verification_key: E::g2_generator() * *shift,
+            profile: Profile::default(),
+        }
+    }
+
+    /// Verify proofs under a security profile other than the default
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Get the verification keys as a tuple of the form (G2*t(s), G2*shift)
+    pub fn get_verification_keys(&self) -> (E::G2, E::G2) {
+        (
+            self.public_root_verification_key,
+            self.power_verification_key,
+        )
+    }
+
+    /// Evaluate the prover's polynomial `p(x)` and hidden polynomial `h(x) = p(x) / t(x)` at the
+    /// encrypted powers, blinding both with the same random scalar
+    pub fn generate_response(
+        &self,
+        coefficients: &[E::Scalar],
+        hidden_coefficients: &[E::Scalar],
+    ) -> Result<PairingProverTranscript<E>, Error> {
+        if coefficients.len() > self.encrypted_powers.len() {
+            return Err(Error::DegreeExceedsSetup(
+                coefficients.len() - 1,
+                self.encrypted_powers.len() - 1,
+            ));
+        }
+        let blinding = E::random_scalar(&mut rand::thread_rng());
+        let blinded_eval = |powers: &[E::Point], coefficients: &[E::Scalar]| {
+            let scalars: Vec<E::Scalar> = coefficients.iter().map(|c| *c * blinding).collect();
+            E::multiscalar_mul(powers, &scalars)
+        };
+        Ok(PairingProverTranscript {
+            px_eval: blinded_eval(&self.encrypted_powers, coefficients),
+            px_powers_eval: blinded_eval(&self.shifted_powers, coefficients),
+            hx_eval: blinded_eval(&self.encrypted_powers, hidden_coefficients),
+        })
+    }
+
+    /// Get the two pairing checks the proof must pass:
+    /// pair(G1*p(s), G2) + pair(-G1*h(s), G2*t(s)) == identity
+    /// pair(G1*p(s_shifted), G2) + pair(-G1*p(s), G2*shift) == identity
+    pub fn pairing_checks(
+        &self,
+        proof: &PairingProverTranscript<E>,
+    ) -> [[(E::Point, E::G2); 2]; 2] {
+        let g2 = E::g2_generator();
+        [
+            [
+                (proof.px_eval, g2),
+                (-proof.hx_eval, self.public_root_verification_key),
+            ],
+            [
+                (proof.px_powers_eval, g2),
+                (-proof.px_eval, self.power_verification_key),
+            ],
+        ]
+    }
+
+    /// Verify the prover's evaluations using the pairing checks
+    pub fn verify_proof(&self, proof: &PairingProverTranscript<E>) -> bool {
+        let identity = E::identity();
+        if self.profile.strict_validation()
+            && [proof.px_eval, proof.px_powers_eval, proof.hx_eval].contains(&identity)
+        {
+            return false;
+        }
+        let verified = self
+            .pairing_checks(proof)
+            .iter()
+            .all(|pairs| E::pairing_check(pairs));
+
+        // Recompute the checks as equalities of separate pairings without negated points, as a
+        // debug build cross-check of the pairing check form
+        let g2 = E::g2_generator();
+        debug_assert_eq!(
+            verified,
+            E::pairing(&proof.px_eval, &g2)
+                == E::pairing(&proof.hx_eval, &self.public_root_verification_key)
+                && E::pairing(&proof.px_powers_eval, &g2)
+                    == E::pairing(&proof.px_eval, &self.power_verification_key),
+            "pairing checks disagree with their equality form"
+        );
+        verified
+    }
+}
+
+/// Expand roots `a*x + b` into polynomial coefficients in ascending order of degree
+pub fn coefficients_from_roots<C: CurveBackend>(roots: &[(i64, i64)]) -> Vec<C::Scalar> {
+    expand_roots::<C>(
+        roots
+            .iter()
+            .map(|(a, b)| (scalar_from_i64::<C>(*a), scalar_from_i64::<C>(*b))),
+    )
+}
+
+// Evaluate a polynomial given by coefficients in ascending order of degree using Horner's rule
+fn evaluate<E: PairingGroup>(coefficients: &[E::Scalar], x: &E::Scalar) -> E::Scalar {
+    coefficients
+        .iter()
+        .rev()
+        .fold(E::Scalar::from(0), |acc, c| acc * *x + *c)
+}
+
+// Same protocol as the encrypted example, only over a generic pairing group
+impl<E: PairingGroup> ProofScheme for PairingProverTranscript<E> {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Educational;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pairing::{Bls12_381, Bn254};
+
+    fn check_proof<E: PairingGroup>() {
+        // p(x) = (x + 2)(3x + 6)(2x + 4)(x + 8) with public roots (x + 2)(3x + 6)
+        let roots = [(1, 2), (3, 6), (2, 4), (1, 8)];
+        let public_coefficients = coefficients_from_roots::<E>(&roots[..2]);
+        let coefficients = coefficients_from_roots::<E>(&roots);
+        let hidden_coefficients = coefficients_from_roots::<E>(&roots[2..]);
+        let verifier = PairingVerifierTranscript::<E>::new(&public_coefficients, 4);
+
+        let proof = verifier
+            .generate_response(&coefficients, &hidden_coefficients)
+            .unwrap();
+        assert!(verifier.verify_proof(&proof));
+
+        // A polynomial not divisible by t(x) fails
+        let alt_coefficients = coefficients_from_roots::<E>(&[(1, 2), (1, 5), (2, 4), (1, 8)]);
+        let alt_proof = verifier
+            .generate_response(&alt_coefficients, &hidden_coefficients)
+            .unwrap();
+        assert!(!verifier.verify_proof(&alt_proof));
+
+        // The all identity proof of the zero polynomial is only accepted by lenient verifiers
+        let identity = E::identity();
+        let identity_proof = PairingProverTranscript::<E> {
+            px_eval: identity,
+            px_powers_eval: identity,
+            hx_eval: identity,
+        };
+        assert!(!verifier.verify_proof(&identity_proof));
+        assert!(verifier
+            .with_profile(Profile::Interactive)
+            .verify_proof(&identity_proof));
+    }
+
+    #[test]
+    fn test_pairing_proof_verifies_on_bls12_381_and_bn254() {
+        check_proof::<Bls12_381>();
+        check_proof::<Bn254>();
+    }
+
+    #[test]
+    fn test_coefficients_from_roots_and_degree_limit() {
+        // (x + 2)(3x + 6) = 3x^2 + 12x + 12
+        let coefficients = coefficients_from_roots::<Bn254>(&[(1, 2), (3, 6)]);
+        let expected: Vec<ark_bn254::Fr> = [12u64, 12, 3].iter().map(|&c| c.into()).collect();
+        assert_eq!(coefficients, expected);
+
+        let verifier = PairingVerifierTranscript::<Bn254>::new(&coefficients, 1);
+        assert_eq!(
+            verifier.generate_response(&coefficients, &[]).err(),
+            Some(Error::DegreeExceedsSetup(2, 1))
+        );
+    }
+}
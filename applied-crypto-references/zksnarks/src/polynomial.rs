@@ -1,46 +1,67 @@
 //! Implementation of Polynomials used for ZkSnarks
 
 use crate::{
+    curve_backend::{scalar_from_i64, CurveBackend},
+    dense_polynomial::DensePolynomial,
     encrypted_zksnark::{ProverTranscript, VerifierTranscript},
     error::Error,
+    fft::FFT_THRESHOLD,
+    pairing::Bls12_381,
     unencrypted_zksnark::UnencryptedChallengeResponse,
 };
 use bls12_381::{G1Projective, Scalar};
-use ff::Field;
+use encodings::{BatchNormalize, SecretScalar};
+use tracing::instrument;
 
-/// Root with coefficients in the 381-bit prime field used by curve BLS12-381
+// A root is given as a factor `a*x + b` of the polynomial. Over the integers such a factor only
+// has an integer root when `a` divides `b`, which is why the unencrypted polynomials below are
+// limited to those. The encrypted polynomials live in a prime field where every nonzero `a` has
+// an inverse, so any factor can be used:
+//
+// 1. The factor is stored as `a*(x + a^-1*b)`, i.e. its leading coefficient and the negated root
+// `a^-1*b`, which is a field element even when `b/a` isn't an integer.
+// 2. Roots are combined by expanding the monic factors `x + a^-1*b` and scaling the result by the
+// product of the leading coefficients, which gives the same coefficients as multiplying out the
+// factors `a*x + b` directly.
+//
+// Only `a = 0` is rejected, since the factor is then a constant without a root.
+
+/// Root with coefficients in the scalar field of a curve, the 381-bit prime field used by curve
+/// BLS12-381 unless another backend is chosen
 #[derive(Clone)]
-pub struct Root {
-    pub a: Scalar,
-    pub b: Scalar,
+pub struct Root<C: CurveBackend = Bls12_381> {
+    // Leading coefficient a of the factor a*x + b
+    a: C::Scalar,
+    // Negated root a^-1*b of the factor, which is then a*(x + a^-1*b)
+    offset: C::Scalar,
 }
 
-impl Root {
+impl<C: CurveBackend> Root<C> {
+    /// Create the root of the factor `a*x + b`, which has to have a nonzero `a`
+    pub fn new(a: C::Scalar, b: C::Scalar) -> Result<Self, Error> {
+        let a_inverse = C::invert_scalar(&a).ok_or(Error::ZeroLeadingCoefficient)?;
+        Ok(Self {
+            a,
+            offset: a_inverse * b,
+        })
+    }
+
+    /// Get the field element `-a^-1*b` the factor vanishes at
+    pub fn get_root(&self) -> C::Scalar {
+        -self.offset
+    }
+
     /// Evaluate the root at a given scalar
-    pub fn eval(&self, x: &Scalar) -> Scalar {
-        x * self.a + self.b
+    pub fn eval(&self, x: &C::Scalar) -> C::Scalar {
+        self.a * (*x + self.offset)
     }
 }
 
-impl TryFrom<(i64, i64)> for Root {
+impl<C: CurveBackend> TryFrom<(i64, i64)> for Root<C> {
     type Error = Error;
 
     fn try_from((a, b): (i64, i64)) -> Result<Self, Self::Error> {
-        if b % a == 0 {
-            let mut a_prime = Scalar::from(a.unsigned_abs());
-            let mut b_prime = Scalar::from(b.unsigned_abs());
-            if a < 0 {
-                a_prime = -a_prime;
-            }
-            if b < 0 {
-                b_prime = -b_prime;
-            }
-            return Ok(Self {
-                a: a_prime,
-                b: b_prime,
-            });
-        }
-        Err(Error::OutsideIntegerField(a, b))
+        Self::new(scalar_from_i64::<C>(a), scalar_from_i64::<C>(b))
     }
 }
 
@@ -68,61 +89,119 @@ impl SimpleRoot {
     }
 }
 
-/// Polynomial with coefficients in the 381-bit prime field used by curve BLS12-381
+/// Polynomial with coefficients in the scalar field of a curve, the 381-bit prime field used by
+/// curve BLS12-381 unless another backend is chosen
 #[derive(Clone)]
-pub struct Polynomial {
-    // Polynomial roots (a, b) such that a*x + b is a factor of the polynomial
-    roots: Vec<Root>,
-    // Polynomial coefficients
-    coefficients: Vec<Scalar>,
+pub struct Polynomial<C: CurveBackend = Bls12_381> {
+    // Public roots (a, b) such that a*x + b is a factor of the public polynomial t(x)
+    public_roots: Vec<Root<C>>,
+    // Polynomial coefficients in ascending order of degree
+    coefficients: Vec<C::Scalar>,
     // Hidden polynomial coefficients (defined by h(x) = p(x)/t(x))
-    hidden_coefficients: Vec<Scalar>,
-    // Number of public roots
-    num_public_roots: usize,
+    hidden_coefficients: Vec<C::Scalar>,
 }
 
-impl Polynomial {
+impl<C: CurveBackend> Polynomial<C> {
     /// Create a new polynomial from a list of roots
-    pub fn new(roots: Vec<Root>, num_public_roots: usize) -> Result<Self, Error> {
+    pub fn new(roots: Vec<Root<C>>, num_public_roots: usize) -> Result<Self, Error> {
         if num_public_roots == 0 || num_public_roots == roots.len() {
             return Err(Error::InvalidPublicRoots(num_public_roots));
         }
         let coefficients = Self::combine_roots(&roots[..]);
-        let hidden_coefficients = Self::combine_roots(&roots[num_public_roots..]);
+        Self::from_coefficients(coefficients, roots[..num_public_roots].to_vec())
+    }
+
+    /// Create a new polynomial from its coefficients in ascending order of degree and the public
+    /// roots it must be divisible by. The hidden polynomial `h(x) = p(x)/t(x)` is found by
+    /// dividing by the public polynomial, which fails if it leaves a remainder.
+    pub fn from_coefficients(
+        coefficients: Vec<C::Scalar>,
+        public_roots: Vec<Root<C>>,
+    ) -> Result<Self, Error> {
+        let polynomial = DensePolynomial::<C>::from_coefficients(coefficients);
+        if public_roots.is_empty() || public_roots.len() >= polynomial.degree() {
+            return Err(Error::InvalidPublicRoots(public_roots.len()));
+        }
+        let public_polynomial =
+            DensePolynomial::from_coefficients(Self::combine_roots(&public_roots[..]));
+        let (hidden, remainder) = polynomial.div_rem(&public_polynomial)?;
+        if !remainder.is_zero() {
+            return Err(Error::NonzeroRemainder);
+        }
         Ok(Self {
-            roots,
-            coefficients,
-            hidden_coefficients,
-            num_public_roots,
+            public_roots,
+            coefficients: polynomial.get_coefficients().to_vec(),
+            hidden_coefficients: hidden.get_coefficients().to_vec(),
         })
     }
 
-    // Combine polynomial roots into coefficients
-    fn combine_roots(roots: &[Root]) -> Vec<Scalar> {
-        let mut coefficients = Vec::new();
-        for root in roots.iter() {
-            if coefficients.is_empty() {
-                coefficients.push(root.a);
-                coefficients.push(root.b);
-            } else {
-                let mut new_coefficients = Vec::new();
-                new_coefficients.push(coefficients[0] * root.a);
-                for i in 1..coefficients.len() {
-                    new_coefficients.push(coefficients[i - 1] * root.b + coefficients[i] * root.a);
-                }
-                new_coefficients.push(coefficients[coefficients.len() - 1] * root.b);
-                coefficients = new_coefficients;
-            }
-        }
-        coefficients.reverse();
-        coefficients
+    // Combine polynomial roots into coefficients by expanding the monic factors and scaling by
+    // the product of the leading coefficients
+    fn combine_roots(roots: &[Root<C>]) -> Vec<C::Scalar> {
+        let scale = roots
+            .iter()
+            .fold(C::Scalar::from(1), |acc, root| acc * root.a);
+        expand_roots::<C>(roots.iter().map(|root| (C::Scalar::from(1), root.offset)))
+            .into_iter()
+            .map(|coefficient| coefficient * scale)
+            .collect()
     }
 
     /// Degree of the polynomial
     pub fn degree(&self) -> usize {
-        self.roots.len()
+        self.coefficients.len() - 1
     }
 
+    /// Get the coefficients of the polynomial `p(x)` in ascending order of degree
+    pub fn get_coefficients(&self) -> &[C::Scalar] {
+        &self.coefficients
+    }
+
+    /// Get the coefficients of the hidden polynomial `h(x) = p(x)/t(x)` in ascending order of
+    /// degree
+    pub fn get_hidden_coefficients(&self) -> &[C::Scalar] {
+        &self.hidden_coefficients
+    }
+
+    /// Evaluate `p(x)` and `h(x)` at encrypted powers `<G*s^0, G*s^1, .., G*s^n>` of a secret
+    /// scalar `s`, blinding both evaluations with `blinding_scalar`
+    ///
+    /// # Returns
+    /// Tuple of the form (G*b*p(s), G*b*h(s)) for the blinding scalar `b`
+    pub fn eval_at_powers(
+        &self,
+        powers: &[C::Point],
+        blinding_scalar: &C::Scalar,
+    ) -> (C::Point, C::Point) {
+        (
+            self.eval(powers, &self.coefficients, blinding_scalar),
+            self.eval(powers, &self.hidden_coefficients, blinding_scalar),
+        )
+    }
+
+    // To evaluate the polynomial, scalar polynomial coefficients and a blinding scalar `b
+    // are multiplied by the curve points PS_1, PS_2, .., PS_n representing repeated
+    // addition of each curve point. The curve points are then summed together to complete
+    // the polynomial evaluation, which a multi-scalar multiplication does at once
+    fn eval(
+        &self,
+        powers: &[C::Point],
+        coefficients: &[C::Scalar],
+        blinding_scalar: &C::Scalar,
+    ) -> C::Point {
+        let scalars: Vec<C::Scalar> = coefficients.iter().map(|c| *c * *blinding_scalar).collect();
+        C::multiscalar_mul(powers, &scalars)
+    }
+
+    /// Evaluate public polynomial t(s) at given scalar s
+    pub fn eval_public_polynomial(&self, scalar: &C::Scalar) -> C::Scalar {
+        self.public_roots
+            .iter()
+            .fold(C::Scalar::from(1), |acc, root| acc * root.eval(scalar))
+    }
+}
+
+impl Polynomial<Bls12_381> {
     /// Take the [`verifier_transcript`](VerifierTranscript) and evaluate the polynomial
     /// at the encrypted and shifted powers of the secret scalar.
     ///
@@ -136,48 +215,74 @@ impl Polynomial {
     /// ['ProverTranscript'] containing the polynomial evaluation at the encrypted and shifted
     /// powers done by multiplying the coefficients of the polynomial by the challenge values
     /// (i.e. <a1*P1, a2*P2, .., an*Pn>
+    #[instrument(name = "prove", level = "debug", skip_all, fields(degree = self.degree()))]
     pub fn generate_response(&self, verifier_transcript: &VerifierTranscript) -> ProverTranscript {
-        // Generate random scalar in order to encrypt the evaluation of the polynomial
-        let b = Scalar::random(&mut rand::thread_rng());
+        // Generate random scalar in order to encrypt the evaluation of the polynomial, which is
+        // overwritten with zeros once the response is computed
+        let b = SecretScalar::new(Bls12_381::random_scalar(&mut rand::thread_rng()));
         let (encrypted_powers, shifted_powers) = verifier_transcript.get_encrypted_powers();
 
         // Evaluate p(s) = t(s) * h(s) at the encrypted scalars sent by the verifier
-        let px_eval = self.eval(encrypted_powers, &self.coefficients, &b).into();
+        let px_eval = self.eval(encrypted_powers, &self.coefficients, b.get_scalar());
 
         // Evaluate p(s) = t(s) * h(s) at the encrypted scalars sent by the verifier
-        let hx_eval = self
-            .eval(encrypted_powers, &self.hidden_coefficients, &b)
-            .into();
+        let hx_eval = self.eval(encrypted_powers, &self.hidden_coefficients, b.get_scalar());
 
         // Evaluate p(s*shift) = t(s*shift) * h(s*shift) at the encrypted & shifted scalars sent by the verifier
-        let px_shift_eval = self.eval(shifted_powers, &self.coefficients, &b).into();
-        ProverTranscript::new(px_eval, px_shift_eval, hx_eval)
-    }
+        let px_shift_eval = self.eval(shifted_powers, &self.coefficients, b.get_scalar());
 
-    // To evaluate the polynomial, scalar polynomial coefficients and a blinding scalar `b
-    // are multiplied by the curve points PS_1, PS_2, .., PS_n representing repeated
-    // addition of each curve point. The curve points are then summed together to complete
-    // the polynomial evaluation
-    fn eval(
-        &self,
-        powers: &[G1Projective],
-        coefficients: &[Scalar],
-        blinding_scalar: &Scalar,
-    ) -> G1Projective {
-        powers
-            .iter()
-            .zip(coefficients.iter())
-            .map(|(p, c)| p * (c * blinding_scalar))
-            .sum()
+        // Split off the leading term of the target degree and evaluate the rest at the degree
+        // bound powers, which only exist below the target degree
+        let degree = verifier_transcript.get_degree();
+        let leading_coefficient = self
+            .coefficients
+            .get(degree)
+            .map_or(Scalar::zero(), |c| c * b.get_scalar());
+        let lower_eval = self.eval(
+            verifier_transcript.get_degree_bound_powers(),
+            &self.coefficients,
+            b.get_scalar(),
+        );
+
+        // Normalize the four evaluations to affine coordinates with a single inversion
+        let evaluations =
+            G1Projective::normalize_batch(&[px_eval, px_shift_eval, hx_eval, lower_eval]);
+        ProverTranscript::new(
+            evaluations[0],
+            evaluations[1],
+            evaluations[2],
+            self.degree(),
+            leading_coefficient,
+            evaluations[3],
+        )
     }
+}
 
-    /// Evaluate public polynomial t(s) at given scalar s
-    pub fn eval_public_polynomial(&self, scalar: &Scalar) -> Scalar {
-        self.roots[0..self.num_public_roots]
-            .to_vec()
-            .iter()
-            .fold(Scalar::one(), |acc, root| acc * root.eval(scalar))
+// Expand roots `(a, b)` of factors `a*x + b` into polynomial coefficients in ascending order of
+// degree. Multiplying the factors in one by one takes quadratic time, so longer lists are split in
+// halves whose expansions are multiplied with the curve's polynomial multiplication, which is
+// subquadratic for curves with an FFT.
+pub(crate) fn expand_roots<C: CurveBackend>(
+    roots: impl IntoIterator<Item = (C::Scalar, C::Scalar)>,
+) -> Vec<C::Scalar> {
+    let roots: Vec<_> = roots.into_iter().collect();
+    if roots.len() > FFT_THRESHOLD {
+        let (low, high) = roots.split_at(roots.len() / 2);
+        return C::polynomial_mul(
+            &expand_roots::<C>(low.iter().copied()),
+            &expand_roots::<C>(high.iter().copied()),
+        );
     }
+    let mut coefficients = vec![C::Scalar::from(1)];
+    for (a, b) in roots {
+        let mut next = vec![C::Scalar::from(0); coefficients.len() + 1];
+        for (i, c) in coefficients.iter().enumerate() {
+            next[i] = next[i] + *c * b;
+            next[i + 1] = next[i + 1] + *c * a;
+        }
+        coefficients = next;
+    }
+    coefficients
 }
 
 /// Polynomial with coefficients restricted to integers within the field of 8-bit signed integers
@@ -237,6 +342,8 @@ impl UnencryptedPolynomial {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Bn254, Ristretto, VerificationError};
+    use bls12_381::Scalar;
 
     #[test]
     fn test_polynomial_simple_roots_must_divide() {
@@ -247,11 +354,117 @@ mod tests {
     }
 
     #[test]
-    fn test_polynomial_roots_must_divide() {
+    fn test_polynomial_roots_need_a_nonzero_leading_coefficient() {
         assert_eq!(
-            Root::try_from((2i64, 1i64)).err().unwrap(),
-            Error::OutsideIntegerField(2, 1)
+            Root::<Bls12_381>::try_from((0i64, 1i64)).err().unwrap(),
+            Error::ZeroLeadingCoefficient
+        );
+
+        // Rational roots are field elements, e.g. 2x + 1 vanishes at -1/2 and -3x + 2 at 2/3
+        let half = Root::<Bls12_381>::try_from((2i64, 1i64)).unwrap();
+        assert_eq!(half.eval(&half.get_root()), Scalar::zero());
+        assert_eq!(half.get_root() * Scalar::from(2), -Scalar::one());
+        let two_thirds = Root::<Bls12_381>::try_from((-3i64, 2i64)).unwrap();
+        assert_eq!(two_thirds.get_root() * Scalar::from(3), Scalar::from(2));
+        assert_eq!(two_thirds.eval(&Scalar::from(5)), -Scalar::from(13));
+    }
+
+    #[test]
+    fn test_proofs_verify_for_rational_and_negative_roots() {
+        // p(x) = (2x + 1)(-3x + 2)(5x - 7)(x + 4) with public roots (2x + 1)(-3x + 2)
+        let roots = [(2, 1), (-3, 2), (5, -7), (1, 4)].map(|root| Root::try_from(root).unwrap());
+        let polynomial = Polynomial::new(roots.to_vec(), 2).unwrap();
+
+        // The combined coefficients match multiplying out the factors directly
+        let expected: Vec<Scalar> = [-56i64, -2, 191, -73, -30]
+            .iter()
+            .map(|&c| scalar_from_i64::<Bls12_381>(c))
+            .collect();
+        assert_eq!(polynomial.get_coefficients(), expected);
+        let expected_hidden: Vec<Scalar> = [-28i64, 13, 5]
+            .iter()
+            .map(|&c| scalar_from_i64::<Bls12_381>(c))
+            .collect();
+        assert_eq!(polynomial.get_hidden_coefficients(), expected_hidden);
+
+        let verifier_transcript = VerifierTranscript::new(&polynomial);
+        assert!(verifier_transcript
+            .verify_proof(&polynomial.generate_response(&verifier_transcript))
+            .is_ok());
+
+        // A polynomial with different rational public roots doesn't verify
+        let other_roots =
+            [(2, 3), (-3, 2), (5, -7), (1, 4)].map(|root| Root::try_from(root).unwrap());
+        let other = Polynomial::new(other_roots.to_vec(), 2).unwrap();
+        assert_eq!(
+            verifier_transcript.verify_proof(&other.generate_response(&verifier_transcript)),
+            Err(VerificationError::PairingCheck)
+        );
+    }
+
+    #[test]
+    fn test_polynomial_from_coefficients_divides_by_public_roots() {
+        // p(x) = 6x^3 + 36x^2 + 72x + 48 is divisible by t(x) = (x + 2)(3x + 6)
+        let coefficients: Vec<Scalar> = [48, 72, 36, 6].into_iter().map(Scalar::from).collect();
+        let public_roots = [(1, 2), (3, 6)].map(|root| Root::try_from(root).unwrap());
+        let polynomial =
+            Polynomial::from_coefficients(coefficients.clone(), public_roots.to_vec()).unwrap();
+        assert_eq!(
+            polynomial.get_hidden_coefficients(),
+            [Scalar::from(4), Scalar::from(2)]
+        );
+        let verifier_transcript = VerifierTranscript::new(&polynomial);
+        assert!(verifier_transcript
+            .verify_proof(&polynomial.generate_response(&verifier_transcript))
+            .is_ok());
+
+        // Changing the constant term leaves a remainder, so the public roots aren't roots of p(x)
+        let mut shifted = coefficients.clone();
+        shifted[0] += Scalar::one();
+        assert_eq!(
+            Polynomial::from_coefficients(shifted, public_roots.to_vec()).err(),
+            Some(Error::NonzeroRemainder)
         );
+        assert_eq!(
+            Polynomial::<Bls12_381>::from_coefficients(coefficients, Vec::new()).err(),
+            Some(Error::InvalidPublicRoots(0))
+        );
+    }
+
+    fn check_eval_at_powers<C: CurveBackend>() {
+        // p(x) = (x + 2)(3x + 6)(2x + 4) with public roots (x + 2)(3x + 6)
+        let roots = [(1, 2), (3, 6), (2, 4)].map(|root| Root::<C>::try_from(root).unwrap());
+        let polynomial = Polynomial::<C>::new(roots.to_vec(), 2).unwrap();
+        assert_eq!(polynomial.get_coefficients().len(), 4);
+        assert_eq!(polynomial.get_hidden_coefficients().len(), 2);
+
+        let mut rng = rand::thread_rng();
+        let (secret, blinding) = (C::random_scalar(&mut rng), C::random_scalar(&mut rng));
+        let mut powers = vec![C::generator()];
+        for i in 0..polynomial.degree() {
+            powers.push(powers[i] * secret);
+        }
+        let evaluate = |coefficients: &[C::Scalar]| {
+            coefficients
+                .iter()
+                .rev()
+                .fold(C::Scalar::from(0), |acc, c| acc * secret + *c)
+        };
+        let (px_eval, hx_eval) = polynomial.eval_at_powers(&powers, &blinding);
+        let (px, hx) = (
+            evaluate(polynomial.get_coefficients()),
+            evaluate(polynomial.get_hidden_coefficients()),
+        );
+        assert_eq!(px_eval, C::generator() * (blinding * px));
+        assert_eq!(hx_eval, C::generator() * (blinding * hx));
+        assert_eq!(px, polynomial.eval_public_polynomial(&secret) * hx);
+    }
+
+    #[test]
+    fn test_polynomial_evaluates_at_encrypted_powers_on_all_backends() {
+        check_eval_at_powers::<Ristretto>();
+        check_eval_at_powers::<Bls12_381>();
+        check_eval_at_powers::<Bn254>();
     }
 
     #[test]
@@ -0,0 +1,160 @@
+//! Proofs of addition and multiplication relations between KZG committed polynomials
+
+use crate::{
+    error::Error,
+    kzg::{KzgCommitment, KzgOpening, KzgSetup},
+};
+use bls12_381::Scalar;
+use encodings::{ProofScheme, SecurityLevel};
+use sha2::{Digest, Sha256};
+
+// Circuits are easier to build from pieces which are committed to separately and then tied
+// together by relations between the committed polynomials:
+//
+// * Addition: KZG commitments are additively homomorphic, G1*p1(tau) + G1*p2(tau) =
+// G1*(p1 + p2)(tau), so p3 = p1 + p2 holds exactly when C3 = C1 + C2 and needs no proof at all.
+// * Multiplication: commitments can't be multiplied, so p3 = p1 * p2 is checked at a single random
+// point z instead. If p3 != p1 * p2, then p3 - p1 * p2 is a non-zero polynomial of degree at most
+// 2d with at most 2d roots, and a random z is one of them with probability at most 2d/|F| by the
+// Schwartz-Zippel lemma. The prover opens all three polynomials at z and the verifier checks
+// p1(z) * p2(z) = p3(z).
+//
+// The point z is derived by hashing the three commitments, so the prover is bound to its
+// polynomials before learning where they will be checked.
+
+// Domain separator for deriving the evaluation point of a product proof
+const PRODUCT_RELATION_DOMAIN_SEP: &[u8] = b"KZG_PRODUCT_RELATION";
+
+// Derive the evaluation point of a relation from the commitments involved
+fn relation_point(commitments: &[KzgCommitment]) -> Scalar {
+    let mut wide = [0; 64];
+    for (counter, half) in wide.chunks_mut(32).enumerate() {
+        let mut hasher = Sha256::new();
+        hasher.update(PRODUCT_RELATION_DOMAIN_SEP);
+        hasher.update([counter as u8]);
+        for commitment in commitments {
+            hasher.update(commitment.get_point().to_compressed());
+        }
+        half.copy_from_slice(&hasher.finalize());
+    }
+    Scalar::from_bytes_wide(&wide)
+}
+
+/// Check that the polynomial committed in `sum` is the sum of the polynomials committed in
+/// `first` and `second`
+pub fn verify_sum(first: &KzgCommitment, second: &KzgCommitment, sum: &KzgCommitment) -> bool {
+    *first + *second == *sum
+}
+
+/// Proof that the polynomial committed in `product` is the product of the polynomials committed
+/// in `first` and `second`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProductProof {
+    // Openings of the first, second and product polynomials at the relation point
+    openings: [KzgOpening; 3],
+}
+
+impl ProductProof {
+    /// Prove that `product = first * second` for polynomials given by their coefficients in
+    /// ascending order of degree
+    pub fn generate_proof(
+        setup: &KzgSetup,
+        first: &[Scalar],
+        second: &[Scalar],
+        product: &[Scalar],
+    ) -> Result<Self, Error> {
+        let commitments = [
+            setup.commit(first)?,
+            setup.commit(second)?,
+            setup.commit(product)?,
+        ];
+        let point = relation_point(&commitments);
+        Ok(Self {
+            openings: [
+                setup.open(first, &point)?,
+                setup.open(second, &point)?,
+                setup.open(product, &point)?,
+            ],
+        })
+    }
+
+    /// Verify the committed polynomials satisfy `product = first * second`
+    pub fn verify_proof(
+        &self,
+        setup: &KzgSetup,
+        first: &KzgCommitment,
+        second: &KzgCommitment,
+        product: &KzgCommitment,
+    ) -> bool {
+        let commitments = [*first, *second, *product];
+        let point = relation_point(&commitments);
+        let [first_opening, second_opening, product_opening] = &self.openings;
+        commitments
+            .iter()
+            .zip(self.openings.iter())
+            .all(|(commitment, opening)| setup.verify_opening(commitment, &point, opening))
+            && first_opening.get_value() * second_opening.get_value() == product_opening.get_value()
+    }
+}
+
+impl ProofScheme for ProductProof {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Experimental;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Multiply two polynomials given by their coefficients in ascending order of degree
+    fn multiply(first: &[Scalar], second: &[Scalar]) -> Vec<Scalar> {
+        let mut product = vec![Scalar::zero(); first.len() + second.len() - 1];
+        for (i, a) in first.iter().enumerate() {
+            for (j, b) in second.iter().enumerate() {
+                product[i + j] += a * b;
+            }
+        }
+        product
+    }
+
+    fn to_scalars(coefficients: &[u64]) -> Vec<Scalar> {
+        coefficients.iter().map(|c| Scalar::from(*c)).collect()
+    }
+
+    #[test]
+    fn test_separately_committed_polynomials_compose() {
+        let setup = KzgSetup::new(8);
+        let first = to_scalars(&[1, 2, 3]);
+        let second = to_scalars(&[4, 0, 5, 6]);
+        let sum = to_scalars(&[5, 2, 8, 6]);
+        let product = multiply(&first, &second);
+        let commitments = [&first, &second, &sum, &product].map(|p| setup.commit(p).unwrap());
+
+        assert!(verify_sum(
+            &commitments[0],
+            &commitments[1],
+            &commitments[2]
+        ));
+        assert!(!verify_sum(
+            &commitments[0],
+            &commitments[1],
+            &commitments[3]
+        ));
+
+        let proof = ProductProof::generate_proof(&setup, &first, &second, &product).unwrap();
+        assert!(proof.verify_proof(&setup, &commitments[0], &commitments[1], &commitments[3]));
+        assert!(!proof.verify_proof(&setup, &commitments[1], &commitments[0], &commitments[3]));
+    }
+
+    #[test]
+    fn test_product_proof_rejects_wrong_products() {
+        let setup = KzgSetup::new(8);
+        let first = to_scalars(&[1, 2, 3]);
+        let second = to_scalars(&[4, 0, 5, 6]);
+        let mut wrong_product = multiply(&first, &second);
+        wrong_product[2] += Scalar::one();
+
+        let proof = ProductProof::generate_proof(&setup, &first, &second, &wrong_product).unwrap();
+        let commitments = [&first, &second, &wrong_product].map(|p| setup.commit(p).unwrap());
+        assert!(!proof.verify_proof(&setup, &commitments[0], &commitments[1], &commitments[2]));
+    }
+}
@@ -0,0 +1,194 @@
+//! Conversion of rank-1 constraint systems into quadratic arithmetic programs
+
+use crate::{
+    error::Error,
+    kzg::interpolate,
+    r1cs::{ConstraintSystem, R1csMatrices},
+};
+use bls12_381::Scalar;
+use ff::Field;
+
+// A quadratic arithmetic program (QAP) turns the m constraints of an R1CS into a single polynomial
+// divisibility check, which is the statement the polynomial proofs in this crate prove:
+//
+// 1. Each constraint i is assigned the point x = i + 1.
+// 2. For every variable j, the polynomial A_j(x) interpolates column j of the matrix A, i.e.
+// A_j(i + 1) = A[i][j], and likewise for B_j(x) and C_j(x).
+// 3. For an assignment z, A(x) = sum z_j*A_j(x) evaluates to <A[i], z> at x = i + 1, so
+// p(x) = A(x)*B(x) - C(x) is zero at every constraint point exactly when every constraint holds.
+// 4. A polynomial is zero at 1, .., m exactly when it is divisible by the target polynomial
+// t(x) = (x - 1)(x - 2)..(x - m), so the assignment satisfies the circuit exactly when
+// p(x) = h(x)*t(x) for some polynomial h(x).
+//
+// The prover of the encrypted polynomial proof can then prove it knows p(x) divisible by t(x).
+// Note that the encrypted proof alone doesn't show that p(x) was built from A, B and C, which full
+// QAP based snarks such as Pinocchio and Groth16 additionally check.
+
+/// Quadratic arithmetic program with one polynomial per variable for each R1CS matrix
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Qap {
+    // Polynomials A_j(x), B_j(x) and C_j(x) interpolating each variable's column
+    a: Vec<Vec<Scalar>>,
+    b: Vec<Vec<Scalar>>,
+    c: Vec<Vec<Scalar>>,
+    // Target polynomial t(x) = (x - 1)..(x - m)
+    target: Vec<Scalar>,
+}
+
+impl Qap {
+    /// Convert the constraints of a constraint system into a QAP
+    pub fn from_r1cs(constraint_system: &ConstraintSystem) -> Result<Self, Error> {
+        Self::from_matrices(&constraint_system.matrices())
+    }
+
+    /// Convert R1CS matrices into a QAP
+    pub fn from_matrices(matrices: &R1csMatrices) -> Result<Self, Error> {
+        let num_constraints = matrices.a.len();
+        if num_constraints == 0 {
+            return Err(Error::NoConstraints);
+        }
+        let num_variables = matrices.a[0].len();
+        let domain: Vec<Scalar> = (1..=num_constraints as u64).map(Scalar::from).collect();
+        let columns = |matrix: &Vec<Vec<Scalar>>| -> Vec<Vec<Scalar>> {
+            (0..num_variables)
+                .map(|j| {
+                    let points: Vec<(Scalar, Scalar)> = domain
+                        .iter()
+                        .zip(matrix.iter())
+                        .map(|(x, row)| (*x, row[j]))
+                        .collect();
+                    interpolate(&points)
+                })
+                .collect()
+        };
+        let target = domain.iter().fold(vec![Scalar::one()], |acc, x| {
+            multiply(&acc, &[-x, Scalar::one()])
+        });
+        Ok(Self {
+            a: columns(&matrices.a),
+            b: columns(&matrices.b),
+            c: columns(&matrices.c),
+            target,
+        })
+    }
+
+    /// Get the target polynomial t(x) in ascending order of degree
+    pub fn target_polynomial(&self) -> &[Scalar] {
+        &self.target
+    }
+
+    /// Compute p(x) = A(x)*B(x) - C(x) for an assignment and the quotient h(x) = p(x) / t(x),
+    /// failing if the assignment doesn't satisfy the circuit
+    pub fn witness_polynomial(
+        &self,
+        assignment: &[Scalar],
+    ) -> Result<(Vec<Scalar>, Vec<Scalar>), Error> {
+        if assignment.len() != self.a.len() {
+            return Err(Error::AssignmentLength(self.a.len(), assignment.len()));
+        }
+        let combine = |polynomials: &Vec<Vec<Scalar>>| {
+            let mut combined = vec![Scalar::zero(); self.target.len() - 1];
+            for (polynomial, z) in polynomials.iter().zip(assignment.iter()) {
+                for (c, p) in combined.iter_mut().zip(polynomial.iter()) {
+                    *c += p * z;
+                }
+            }
+            combined
+        };
+        let mut witness = multiply(&combine(&self.a), &combine(&self.b));
+        for (w, c) in witness.iter_mut().zip(combine(&self.c).iter()) {
+            *w -= c;
+        }
+        let (quotient, remainder) = divide(&witness, &self.target);
+        if remainder.iter().any(|r| !bool::from(r.is_zero())) {
+            return Err(Error::UnsatisfiedCircuit);
+        }
+        Ok((witness, quotient))
+    }
+}
+
+// Multiply two polynomials given by their coefficients in ascending order of degree
+fn multiply(first: &[Scalar], second: &[Scalar]) -> Vec<Scalar> {
+    let mut product = vec![Scalar::zero(); first.len() + second.len() - 1];
+    for (i, a) in first.iter().enumerate() {
+        for (j, b) in second.iter().enumerate() {
+            product[i + j] += a * b;
+        }
+    }
+    product
+}
+
+// Divide a polynomial by a monic polynomial using long division, returning the quotient and
+// remainder
+fn divide(numerator: &[Scalar], denominator: &[Scalar]) -> (Vec<Scalar>, Vec<Scalar>) {
+    let mut remainder = numerator.to_vec();
+    if numerator.len() < denominator.len() {
+        return (Vec::new(), remainder);
+    }
+    let shift = numerator.len() - denominator.len();
+    let mut quotient = vec![Scalar::zero(); shift + 1];
+    for i in (0..=shift).rev() {
+        let factor = remainder[i + denominator.len() - 1];
+        quotient[i] = factor;
+        for (j, d) in denominator.iter().enumerate() {
+            remainder[i + j] -= factor * d;
+        }
+    }
+    remainder.truncate(denominator.len() - 1);
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pairing::Bls12_381,
+        pairing_snark::PairingVerifierTranscript,
+        r1cs::{LinearCombination, Variable},
+    };
+
+    // Build a circuit proving knowledge of x such that x^3 + x + 5 = out
+    fn cubic_circuit(x: u64, out: u64) -> ConstraintSystem {
+        let mut cs = ConstraintSystem::new();
+        let out = cs.alloc_input(Scalar::from(out));
+        let x = cs.alloc_witness(Scalar::from(x));
+        let x_squared = cs.mul(x, x);
+        let x_cubed = cs.mul(x_squared, x);
+        let sum = cs.add(
+            x_cubed,
+            LinearCombination::from(x) + (Variable::ONE, Scalar::from(5)),
+        );
+        cs.enforce(sum, Variable::ONE, out);
+        cs
+    }
+
+    #[test]
+    fn test_satisfied_circuit_proves_with_the_encrypted_proof() {
+        let cs = cubic_circuit(3, 35);
+        let qap = Qap::from_r1cs(&cs).unwrap();
+        let (witness, quotient) = qap.witness_polynomial(cs.get_assignment()).unwrap();
+        assert_eq!(
+            witness,
+            multiply(qap.target_polynomial(), &quotient)[..witness.len()]
+        );
+
+        let verifier =
+            PairingVerifierTranscript::<Bls12_381>::new(qap.target_polynomial(), witness.len() - 1);
+        let proof = verifier.generate_response(&witness, &quotient).unwrap();
+        assert!(verifier.verify_proof(&proof));
+    }
+
+    #[test]
+    fn test_unsatisfied_circuit_has_no_quotient() {
+        let cs = cubic_circuit(4, 35);
+        let qap = Qap::from_r1cs(&cs).unwrap();
+        assert_eq!(
+            qap.witness_polynomial(cs.get_assignment()),
+            Err(Error::UnsatisfiedCircuit)
+        );
+        assert_eq!(
+            qap.witness_polynomial(&[Scalar::one()]),
+            Err(Error::AssignmentLength(6, 1))
+        );
+    }
+}
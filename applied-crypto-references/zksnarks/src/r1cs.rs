@@ -0,0 +1,331 @@
+//! Rank-1 constraint systems built from addition and multiplication gates
+
+use bls12_381::Scalar;
+use std::ops::{Add, Mul, Sub};
+
+// The polynomial examples prove knowledge of a polynomial with given roots, which isn't a
+// statement anyone needs to prove. Real statements are computations, and the first step towards
+// proving them is flattening the computation into a rank-1 constraint system (R1CS):
+//
+// 1. Every value in the computation becomes a variable. The assignment vector z holds the value
+// of every variable, starting with the constant 1 followed by public inputs and private witnesses
+// in the order they were allocated.
+// 2. Every constraint has the form <a, z> * <b, z> = <c, z> where a, b and c are vectors of
+// coefficients, i.e. a product of two linear combinations of variables equals a third one.
+// 3. Stacking the coefficient vectors of all constraints gives the matrices A, B and C, and an
+// assignment satisfies the circuit when (A*z) o (B*z) = C*z with o the entrywise product.
+//
+// Multiplication gates map directly to one constraint. Additions are free inside linear
+// combinations, but an addition gate producing a new variable costs a constraint (a + b) * 1 = c.
+
+/// Variable of a constraint system
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Variable(usize);
+
+impl Variable {
+    /// Variable always assigned the constant 1
+    pub const ONE: Variable = Variable(0);
+
+    /// Get the position of the variable in the assignment vector
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Sum of variables multiplied by constant coefficients
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LinearCombination {
+    terms: Vec<(Variable, Scalar)>,
+}
+
+impl LinearCombination {
+    /// Linear combination of a single constant
+    pub fn constant(value: Scalar) -> Self {
+        Self {
+            terms: vec![(Variable::ONE, value)],
+        }
+    }
+
+    /// Evaluate the linear combination for an assignment
+    pub fn evaluate(&self, assignment: &[Scalar]) -> Scalar {
+        self.terms
+            .iter()
+            .fold(Scalar::zero(), |acc, (variable, coefficient)| {
+                acc + assignment[variable.0] * coefficient
+            })
+    }
+
+    // Merge the terms of each variable, which keeps linear combinations built up over many gates
+    // from growing with every gate
+    pub(crate) fn compact(self) -> Self {
+        let mut coefficients = std::collections::BTreeMap::new();
+        for (variable, coefficient) in self.terms {
+            *coefficients.entry(variable).or_insert_with(Scalar::zero) += coefficient;
+        }
+        Self {
+            terms: coefficients.into_iter().collect(),
+        }
+    }
+
+    // Write the coefficients into a dense row of a matrix with one column per variable
+    fn to_row(&self, num_variables: usize) -> Vec<Scalar> {
+        let mut row = vec![Scalar::zero(); num_variables];
+        for (variable, coefficient) in self.terms.iter() {
+            row[variable.0] += coefficient;
+        }
+        row
+    }
+}
+
+impl From<Variable> for LinearCombination {
+    fn from(variable: Variable) -> Self {
+        Self {
+            terms: vec![(variable, Scalar::one())],
+        }
+    }
+}
+
+impl Add<(Variable, Scalar)> for LinearCombination {
+    type Output = Self;
+
+    fn add(mut self, term: (Variable, Scalar)) -> Self {
+        self.terms.push(term);
+        self
+    }
+}
+
+impl Add<Variable> for LinearCombination {
+    type Output = Self;
+
+    fn add(self, variable: Variable) -> Self {
+        self + (variable, Scalar::one())
+    }
+}
+
+impl Add for LinearCombination {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.terms.extend(other.terms);
+        self
+    }
+}
+
+impl Sub for LinearCombination {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + other * -Scalar::one()
+    }
+}
+
+impl Sub<Variable> for LinearCombination {
+    type Output = Self;
+
+    fn sub(self, variable: Variable) -> Self {
+        self + (variable, -Scalar::one())
+    }
+}
+
+impl Mul<Scalar> for LinearCombination {
+    type Output = Self;
+
+    fn mul(mut self, scalar: Scalar) -> Self {
+        for (_, coefficient) in self.terms.iter_mut() {
+            *coefficient *= scalar;
+        }
+        self
+    }
+}
+
+/// Constraint matrices A, B and C with one row per constraint and one column per variable
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct R1csMatrices {
+    /// Coefficients of the left factor of each constraint
+    pub a: Vec<Vec<Scalar>>,
+    /// Coefficients of the right factor of each constraint
+    pub b: Vec<Vec<Scalar>>,
+    /// Coefficients of the product of each constraint
+    pub c: Vec<Vec<Scalar>>,
+}
+
+/// Builder of a rank-1 constraint system together with an assignment of its variables
+#[derive(Clone, Debug)]
+pub struct ConstraintSystem {
+    // Value of every variable, starting with the constant 1
+    assignment: Vec<Scalar>,
+    // Whether each variable is a public input
+    public: Vec<bool>,
+    // Constraints <a, z> * <b, z> = <c, z>
+    constraints: Vec<(LinearCombination, LinearCombination, LinearCombination)>,
+}
+
+impl Default for ConstraintSystem {
+    fn default() -> Self {
+        Self {
+            assignment: vec![Scalar::one()],
+            public: vec![true],
+            constraints: Vec::new(),
+        }
+    }
+}
+
+impl ConstraintSystem {
+    /// Create an empty constraint system holding only the constant 1
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn alloc(&mut self, value: Scalar, public: bool) -> Variable {
+        self.assignment.push(value);
+        self.public.push(public);
+        Variable(self.assignment.len() - 1)
+    }
+
+    /// Allocate a public input variable
+    pub fn alloc_input(&mut self, value: Scalar) -> Variable {
+        self.alloc(value, true)
+    }
+
+    /// Allocate a private witness variable
+    pub fn alloc_witness(&mut self, value: Scalar) -> Variable {
+        self.alloc(value, false)
+    }
+
+    /// Add the constraint `a * b = c`
+    pub fn enforce(
+        &mut self,
+        a: impl Into<LinearCombination>,
+        b: impl Into<LinearCombination>,
+        c: impl Into<LinearCombination>,
+    ) {
+        self.constraints.push((a.into(), b.into(), c.into()));
+    }
+
+    /// Multiplication gate allocating a witness holding the product of two linear combinations
+    pub fn mul(
+        &mut self,
+        a: impl Into<LinearCombination>,
+        b: impl Into<LinearCombination>,
+    ) -> Variable {
+        let (a, b) = (a.into(), b.into());
+        let product =
+            self.alloc_witness(a.evaluate(&self.assignment) * b.evaluate(&self.assignment));
+        self.enforce(a, b, product);
+        product
+    }
+
+    /// Addition gate allocating a witness holding the sum of two linear combinations
+    pub fn add(
+        &mut self,
+        a: impl Into<LinearCombination>,
+        b: impl Into<LinearCombination>,
+    ) -> Variable {
+        let (a, b) = (a.into(), b.into());
+        let sum = self.alloc_witness(a.evaluate(&self.assignment) + b.evaluate(&self.assignment));
+        let mut terms = a.terms;
+        terms.extend(b.terms);
+        self.enforce(LinearCombination { terms }, Variable::ONE, sum);
+        sum
+    }
+
+    /// Get the number of constraints
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Get the number of variables including the constant 1
+    pub fn num_variables(&self) -> usize {
+        self.assignment.len()
+    }
+
+    /// Get the assignment vector z
+    pub fn get_assignment(&self) -> &[Scalar] {
+        &self.assignment
+    }
+
+    /// Get the variables which are public inputs, excluding the constant 1
+    pub fn get_public_inputs(&self) -> Vec<Variable> {
+        (1..self.public.len())
+            .filter(|i| self.public[*i])
+            .map(Variable)
+            .collect()
+    }
+
+    /// Check whether the assignment satisfies every constraint
+    pub fn is_satisfied(&self) -> bool {
+        self.constraints.iter().all(|(a, b, c)| {
+            a.evaluate(&self.assignment) * b.evaluate(&self.assignment)
+                == c.evaluate(&self.assignment)
+        })
+    }
+
+    /// Export the constraints as dense A, B and C matrices
+    pub fn matrices(&self) -> R1csMatrices {
+        let num_variables = self.num_variables();
+        let mut matrices = R1csMatrices {
+            a: Vec::with_capacity(self.num_constraints()),
+            b: Vec::with_capacity(self.num_constraints()),
+            c: Vec::with_capacity(self.num_constraints()),
+        };
+        for (a, b, c) in self.constraints.iter() {
+            matrices.a.push(a.to_row(num_variables));
+            matrices.b.push(b.to_row(num_variables));
+            matrices.c.push(c.to_row(num_variables));
+        }
+        matrices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Build a circuit proving knowledge of x such that x^3 + x + 5 = out
+    fn cubic_circuit(x: u64, out: u64) -> (ConstraintSystem, Variable) {
+        let mut cs = ConstraintSystem::new();
+        let out = cs.alloc_input(Scalar::from(out));
+        let x = cs.alloc_witness(Scalar::from(x));
+        let x_squared = cs.mul(x, x);
+        let x_cubed = cs.mul(x_squared, x);
+        let sum = cs.add(
+            x_cubed,
+            LinearCombination::from(x) + (Variable::ONE, Scalar::from(5)),
+        );
+        cs.enforce(sum, Variable::ONE, out);
+        (cs, out)
+    }
+
+    #[test]
+    fn test_cubic_circuit_exports_satisfied_matrices() {
+        let (cs, out) = cubic_circuit(3, 35);
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 4);
+        assert_eq!(cs.num_variables(), 6);
+        assert_eq!(cs.get_public_inputs(), vec![out]);
+
+        // (A*z) o (B*z) = C*z for every row
+        let matrices = cs.matrices();
+        let dot = |row: &Vec<Scalar>| {
+            row.iter()
+                .zip(cs.get_assignment())
+                .fold(Scalar::zero(), |acc, (c, z)| acc + c * z)
+        };
+        for i in 0..cs.num_constraints() {
+            assert_eq!(
+                dot(&matrices.a[i]) * dot(&matrices.b[i]),
+                dot(&matrices.c[i])
+            );
+        }
+        // The first constraint is x * x = x_squared
+        assert_eq!(matrices.a[0][2], Scalar::one());
+        assert_eq!(matrices.b[0][2], Scalar::one());
+        assert_eq!(matrices.c[0][3], Scalar::one());
+    }
+
+    #[test]
+    fn test_wrong_witness_doesnt_satisfy_circuit() {
+        let (cs, _) = cubic_circuit(4, 35);
+        assert!(!cs.is_satisfied());
+    }
+}
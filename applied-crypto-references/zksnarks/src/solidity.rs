@@ -0,0 +1,193 @@
+//! Generator for Solidity contracts verifying the polynomial proof on EVM chains
+
+use crate::{
+    evm::{encode_evm_g1, encode_evm_g2, EVM_WORD_LEN},
+    pairing::{Bn254, PairingGroup},
+    pairing_snark::{PairingProverTranscript, PairingVerifierTranscript},
+};
+use sha3::{Digest, Keccak256};
+
+// The generated contract checks the same two equations as `PairingVerifierTranscript::verify_proof`
+// using the ecPairing precompile at address 0x08:
+//
+// pair(G1*p(s), G2) + pair(-G1*h(s), G2*t(s)) == identity
+// pair(G1*p(s_shifted), G2) + pair(-G1*p(s), G2*shift) == identity
+//
+// The verification keys G2*t(s) and G2*shift are embedded into the contract as constants, while
+// the prover's three G1 points are passed as calldata. The contract negates the points itself
+// rather than accepting negated points from the caller so that a proof can only be submitted in
+// one form. Only BN254 has a pairing precompile available on every EVM chain, so contracts are
+// only generated for BN254 proofs.
+//
+// Whoever created the verifier transcript knows the secret scalars behind the keys and could
+// forge proofs. Contracts should only be deployed for transcripts from a trusted setup.
+
+/// Solidity signature of the generated verification function
+pub const VERIFY_PROOF_SIGNATURE: &str = "verifyProof(uint256[2],uint256[2],uint256[2])";
+
+// Format a 32 byte big endian word as a Solidity hex literal
+fn word_literal(word: &[u8]) -> String {
+    let hex: String = word.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", hex)
+}
+
+// Format an encoded G2 point as a Solidity uint256[4] array literal
+fn g2_literal(point: &<Bn254 as PairingGroup>::G2) -> String {
+    let words: Vec<String> = encode_evm_g2(point)
+        .chunks(EVM_WORD_LEN)
+        .map(word_literal)
+        .collect();
+    format!(
+        "[\n            uint256({}),\n            {},\n            {},\n            {}\n        ]",
+        words[0], words[1], words[2], words[3]
+    )
+}
+
+/// Generate a Solidity contract verifying proofs against a BN254 verifier transcript
+pub fn generate_solidity_verifier(
+    contract_name: &str,
+    verifier: &PairingVerifierTranscript<Bn254>,
+) -> String {
+    let (public_root_key, power_key) = verifier.get_verification_keys();
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+/// @notice Verifies polynomial proofs over BN254 using the ecPairing precompile.
+/// Generated by zksnarks-example, do not edit by hand.
+contract {name} {{
+    // Modulus of the BN254 base field
+    uint256 internal constant Q =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    function g2Generator() internal pure returns (uint256[4] memory) {{
+        return {g2};
+    }}
+
+    // G2*t(s) where t(x) is the public polynomial
+    function publicRootKey() internal pure returns (uint256[4] memory) {{
+        return {public_root_key};
+    }}
+
+    // G2*shift
+    function powerKey() internal pure returns (uint256[4] memory) {{
+        return {power_key};
+    }}
+
+    function negate(uint256[2] memory p) internal pure returns (uint256[2] memory) {{
+        if (p[0] == 0 && p[1] == 0) {{
+            return p;
+        }}
+        return [p[0], Q - (p[1] % Q)];
+    }}
+
+    // Check pair(a, b) + pair(c, d) == identity
+    function pairingCheck(
+        uint256[2] memory a,
+        uint256[4] memory b,
+        uint256[2] memory c,
+        uint256[4] memory d
+    ) internal view returns (bool) {{
+        uint256[12] memory input = [
+            a[0], a[1], b[0], b[1], b[2], b[3],
+            c[0], c[1], d[0], d[1], d[2], d[3]
+        ];
+        uint256[1] memory out;
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x08, input, 384, out, 32)
+        }}
+        return success && out[0] == 1;
+    }}
+
+    /// @param px Prover's evaluation G1*p(s)
+    /// @param pxShifted Prover's evaluation G1*p(s_shifted)
+    /// @param hx Prover's evaluation G1*h(s)
+    function verifyProof(
+        uint256[2] calldata px,
+        uint256[2] calldata pxShifted,
+        uint256[2] calldata hx
+    ) external view returns (bool) {{
+        return pairingCheck(px, g2Generator(), negate(hx), publicRootKey())
+            && pairingCheck(pxShifted, g2Generator(), negate(px), powerKey());
+    }}
+}}
+"#,
+        name = contract_name,
+        g2 = g2_literal(&Bn254::g2_generator()),
+        public_root_key = g2_literal(&public_root_key),
+        power_key = g2_literal(&power_key),
+    )
+}
+
+/// Get the 4 byte function selector of the generated verification function
+pub fn verify_proof_selector() -> [u8; 4] {
+    let hash = Keccak256::digest(VERIFY_PROOF_SIGNATURE.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Encode calldata calling the generated contract's `verifyProof` function with a proof
+pub fn encode_verifier_calldata(proof: &PairingProverTranscript<Bn254>) -> Vec<u8> {
+    let (px_eval, px_powers_eval, hx_eval) = proof.get_proof_values();
+    [
+        verify_proof_selector().as_slice(),
+        encode_evm_g1(&px_eval).as_slice(),
+        encode_evm_g1(&px_powers_eval).as_slice(),
+        encode_evm_g1(&hx_eval).as_slice(),
+    ]
+    .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        evm::{decode_evm_g1, encode_pairing_input, EVM_G1_LEN},
+        pairing_snark::coefficients_from_roots,
+    };
+
+    #[test]
+    fn test_calldata_matches_abi_layout_and_passes_pairing_checks() {
+        let roots = [(1, 2), (3, 6), (2, 4)];
+        let public_coefficients = coefficients_from_roots::<Bn254>(&roots[..2]);
+        let verifier = PairingVerifierTranscript::<Bn254>::new(&public_coefficients, 3);
+        let proof = verifier
+            .generate_response(
+                &coefficients_from_roots::<Bn254>(&roots),
+                &coefficients_from_roots::<Bn254>(&roots[2..]),
+            )
+            .unwrap();
+
+        // Static uint256[2] arguments are encoded inline after the selector
+        let calldata = encode_verifier_calldata(&proof);
+        assert_eq!(calldata.len(), 4 + 3 * EVM_G1_LEN);
+        assert_eq!(calldata[..4], verify_proof_selector());
+        let points: Vec<_> = calldata[4..]
+            .chunks(EVM_G1_LEN)
+            .map(|chunk| decode_evm_g1(chunk).unwrap())
+            .collect();
+        assert_eq!((points[0], points[1], points[2]), proof.get_proof_values());
+
+        // The precompile inputs the contract assembles hold the same pairs the Rust verifier checks
+        for pairs in verifier.pairing_checks(&proof).iter() {
+            assert_eq!(encode_pairing_input(pairs).len(), 384);
+            assert!(Bn254::pairing_check(pairs));
+        }
+    }
+
+    #[test]
+    fn test_generated_contract_embeds_verification_keys() {
+        let public_coefficients = coefficients_from_roots::<Bn254>(&[(1, 2)]);
+        let verifier = PairingVerifierTranscript::<Bn254>::new(&public_coefficients, 2);
+        let contract = generate_solidity_verifier("PolynomialProofVerifier", &verifier);
+
+        assert!(contract.contains("contract PolynomialProofVerifier {"));
+        let (public_root_key, power_key) = verifier.get_verification_keys();
+        for key in [public_root_key, power_key] {
+            let first_word = word_literal(&encode_evm_g2(&key)[..EVM_WORD_LEN]);
+            assert!(contract.contains(&first_word));
+        }
+        // The selector of verifyProof(uint256[2],uint256[2],uint256[2])
+        assert_eq!(verify_proof_selector(), [0x1f, 0x1c, 0xda, 0xa2]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Step by step walk-through of the encrypted polynomial zkSNARK printing its intermediate values
+
+use crate::{Polynomial, Root, VerifierTranscript};
+use bls12_381::{G1Affine, G2Affine};
+
+// Print the first bytes of a compressed BLS12-381 G1 point, which is enough to tell points apart
+fn short_point(point: &G1Affine) -> String {
+    format!("{}..", hex::encode(&point.to_compressed()[..8]))
+}
+
+pub fn zksnark_tutorial() {
+    // A zkSNARK lets a prover convince a verifier that they know a polynomial p(x) with certain
+    // public roots, without revealing the rest of the polynomial. The verifier knows the public
+    // part t(x) of the polynomial, the prover claims that p(x) = t(x) * h(x) for some hidden
+    // polynomial h(x). If the claim holds, it holds at every point, so the verifier checks it at a
+    // single secret point s which it only hands to the prover in encrypted form.
+    //
+    // This tutorial runs each step of the protocol with the library types and prints the values
+    // exchanged along the way.
+
+    // STEP 1: ROOT SELECTION
+    // The prover picks the roots of their polynomial. Each root is a factor a*x + b, here
+    // p(x) = (x + 2)(3x + 6)(2x + 4). The first two factors are public, so t(x) = (x + 2)(3x + 6)
+    // and the hidden polynomial is h(x) = 2x + 4.
+    let roots: Vec<Root> = [(1i64, 2i64), (3, 6), (2, 4)]
+        .into_iter()
+        .map(|root| Root::try_from(root).expect("every factor has a nonzero leading coefficient"))
+        .collect();
+    let polynomial = Polynomial::new(roots, 2).expect("some but not all roots are public");
+
+    println!();
+    println!("This tutorial walks through a zkSNARK proving knowledge of a polynomial p(x) = t(x) * h(x)");
+    println!("whose factors t(x) are public while h(x) stays hidden.");
+    println!();
+    println!("Step 1: Root selection");
+    println!("The prover's polynomial has the factors (x + 2), (3x + 6) and (2x + 4).");
+    println!(
+        "The first two are public: t(x) = (x + 2)(3x + 6). The last one is hidden: h(x) = 2x + 4."
+    );
+
+    // STEP 2: COEFFICIENT COMBINATION
+    // Multiplying the factors out gives the coefficients of p(x) and h(x) in ascending order of
+    // degree. Coefficients are elements of the scalar field of BLS12-381, so they are printed as
+    // field elements in hexadecimal.
+    println!();
+    println!("Step 2: Coefficient combination");
+    println!("Multiplying out the factors gives p(x) = 6x^3 + 36x^2 + 72x + 48 with hexadecimal coefficients:");
+    for (degree, coefficient) in polynomial.get_coefficients().iter().enumerate() {
+        println!("  x^{degree}: {coefficient:?}");
+    }
+    println!("and h(x) = 2x + 4 with coefficients:");
+    for (degree, coefficient) in polynomial.get_hidden_coefficients().iter().enumerate() {
+        println!("  x^{degree}: {coefficient:?}");
+    }
+
+    // STEP 3: ENCRYPTED POWER GENERATION
+    // The verifier picks a secret point s and a secret shift, and hands out the powers of s
+    // "encrypted" as curve points G1*s^i together with the shifted powers G1*shift*s^i. Nobody can
+    // recover s from them, but anyone can evaluate a polynomial at s by a linear combination of
+    // the points. The verifier keeps G2*t(s) and G2*shift as verification keys.
+    let verifier = VerifierTranscript::new(&polynomial);
+    let (encrypted_powers, shifted_powers) = verifier.get_encrypted_powers();
+
+    println!();
+    println!("Step 3: Encrypted power generation");
+    println!("The verifier picks a secret point s and a secret shift and publishes:");
+    for (i, (power, shifted)) in encrypted_powers.iter().zip(shifted_powers).enumerate() {
+        println!(
+            "  G1*s^{i}: {}    G1*shift*s^{i}: {}",
+            short_point(&G1Affine::from(power)),
+            short_point(&G1Affine::from(shifted))
+        );
+    }
+
+    // STEP 4: PROVING
+    // The prover evaluates p(x) and h(x) at the encrypted powers, and p(x) once more at the
+    // shifted powers, which shows the evaluations were made from the published powers. All three
+    // are multiplied by the same random scalar so the proof reveals nothing about the
+    // coefficients.
+    let proof = polynomial.generate_response(&verifier);
+    let (px_eval, px_shifted_eval, hx_eval) = proof.get_proof_values();
+
+    println!();
+    println!("Step 4: Proving");
+    println!("The prover evaluates its polynomials at the encrypted powers and blinds them with a random b:");
+    println!("  G1*b*p(s):       {}", short_point(&px_eval));
+    println!("  G1*b*shift*p(s): {}", short_point(&px_shifted_eval));
+    println!("  G1*b*h(s):       {}", short_point(&hx_eval));
+
+    // STEP 5: PAIRING VERIFICATION
+    // A pairing multiplies the hidden scalars of a G1 and a G2 point, so the verifier can check
+    // p(s) = t(s) * h(s) and that the shifted evaluation is p(s) times the shift without ever
+    // learning s, p(s) or h(s).
+    let (public_root_key, shift_key) = verifier.get_verification_keys();
+    let g2 = G2Affine::generator();
+    let roots_hold =
+        bls12_381::pairing(&px_eval, &g2) == bls12_381::pairing(&hx_eval, public_root_key);
+    let powers_hold =
+        bls12_381::pairing(&px_shifted_eval, &g2) == bls12_381::pairing(&px_eval, shift_key);
+
+    // A prover who doesn't know a polynomial with the public roots can't pass the first check.
+    // Here the prover swaps the public factor (x + 2) for (x + 1).
+    let cheating_roots: Vec<Root> = [(1i64, 1i64), (3, 6), (2, 4)]
+        .into_iter()
+        .map(|root| Root::try_from(root).expect("every factor has a nonzero leading coefficient"))
+        .collect();
+    let cheating_polynomial =
+        Polynomial::new(cheating_roots, 2).expect("some but not all roots are public");
+    let cheating_proof = cheating_polynomial.generate_response(&verifier);
+
+    println!();
+    println!("Step 5: Pairing verification");
+    println!(
+        "The verifier checks both relations with pairings against its keys G2*t(s) and G2*shift:"
+    );
+    println!("  pair(G1*b*p(s), G2) == pair(G1*b*h(s), G2*t(s)):           {roots_hold}");
+    println!("  pair(G1*b*shift*p(s), G2) == pair(G1*b*p(s), G2*shift):    {powers_hold}");
+    println!("Verification result: {:?}", verifier.verify_proof(&proof));
+    println!();
+    println!(
+        "A prover using (x + 1) instead of the public factor (x + 2) produces a proof for which"
+    );
+    println!("p(s) != t(s) * h(s), so the first pairing check fails:");
+    println!(
+        "Verification result: {:?}",
+        verifier.verify_proof(&cheating_proof)
+    );
+    println!();
+    println!(
+        "The encrypted powers in this tutorial come from a verifier that knows s. Real deployments"
+    );
+    println!(
+        "generate them in a trusted setup ceremony so that nobody learns s, which makes the proof"
+    );
+    println!("verifiable by anyone holding the verification keys.");
+}
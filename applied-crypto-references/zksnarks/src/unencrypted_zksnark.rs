@@ -1,6 +1,28 @@
 //! Simple demonstration of the basic polynomial math behind ZkSnarks
 
-use crate::polynomial::UnencryptedPolynomial;
+use crate::{error::Error, polynomial::UnencryptedPolynomial};
+use encodings::{
+    ChallengeRecord, ChallengeSource, EntropyLedger, Profile, ProofScheme, SecurityLevel,
+};
+use rand::Rng;
+
+// A single challenge gives a weak guarantee. If a prover really answers from fixed polynomials
+// p(x) and h(x) of degree at most d and p(x) != h(x)*t(x), the difference p(x) - h(x)*t(x) is a
+// non-zero polynomial with at most d roots. A challenge drawn uniformly from n integers hits one
+// of these roots with probability at most d/n, which for small challenge ranges is large. Asking
+// k independent challenges and requiring every answer to verify multiplies the error to (d/n)^k.
+//
+// This bound only holds for provers committed to fixed polynomials, which is exactly what the
+// unencrypted protocol can't enforce: a prover can compute h(x) after seeing x, passing whenever
+// t(x) happens to divide its p(x), or simply pick any h and answer p = h*t(x). Closing that gap is
+// what the encrypted powers and shifted evaluations of the encrypted protocol are for.
+//
+// The challenge ranges used here are tiny, so the (d/n)^k bound only holds against a live
+// verifier. Were the challenges derived by Fiat-Shamir instead, a prover could grind every round
+// separately in about n/d attempts, which the verifier's entropy ledger flags.
+
+// Label the verifier's challenges are recorded under in entropy ledgers
+const UNENCRYPTED_PROTOCOL_LABEL: &str = "UNENCRYPTED_POLYNOMIAL";
 
 /// Unencrypted challenge response pair
 #[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
@@ -17,12 +39,138 @@ impl UnencryptedChallengeResponse {
         Self { px, hx }
     }
 
+    /// Get the prover's answers as a tuple of the form (p(x), h(x))
+    pub fn get_values(&self) -> (i64, i64) {
+        (self.px, self.hx)
+    }
+
     /// Verify the challenge values provided by the prover match!
     pub fn verify(&self, x: i64, polynomial: &UnencryptedPolynomial) -> bool {
         self.px == self.hx * polynomial.eval(x)
     }
 }
 
+/// Soundness error of a repeated challenge protocol against a prover answering from fixed
+/// polynomials
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SoundnessReport {
+    /// Number of independent challenges which must all verify
+    pub rounds: usize,
+    /// Number of distinct values each challenge is drawn from
+    pub challenge_range: i64,
+    /// Degree of the prover's polynomial
+    pub degree: usize,
+}
+
+impl SoundnessReport {
+    /// Probability of a cheating prover passing a single round, at most d/n
+    pub fn round_error(&self) -> f64 {
+        (self.degree as f64 / self.challenge_range as f64).min(1.0)
+    }
+
+    /// Probability of a cheating prover passing every round, at most (d/n)^k
+    pub fn total_error(&self) -> f64 {
+        self.round_error().powi(self.rounds as i32)
+    }
+
+    /// Bits of security given by the total soundness error
+    pub fn security_bits(&self) -> f64 {
+        -self.total_error().log2()
+    }
+}
+
+/// Verifier of the unencrypted protocol asking several independent challenges
+#[derive(Clone)]
+pub struct RepeatedChallengeVerifier {
+    // Public polynomial t(x)
+    public_polynomial: UnencryptedPolynomial,
+    // Degree of the prover's polynomial p(x)
+    degree: usize,
+    // Challenges drawn uniformly from [0, challenge_range)
+    challenges: Vec<i64>,
+    challenge_range: i64,
+}
+
+impl RepeatedChallengeVerifier {
+    /// Draw `rounds` independent challenges from [0, challenge_range) for a prover claiming a
+    /// polynomial of `degree` divisible by the public polynomial
+    pub fn new(
+        public_polynomial: UnencryptedPolynomial,
+        degree: usize,
+        rounds: usize,
+        challenge_range: i64,
+    ) -> Result<Self, Error> {
+        let mut rng = rand::thread_rng();
+        let challenges = (0..rounds)
+            .map(|_| rng.gen_range(0..challenge_range.max(1)))
+            .collect();
+        Self::from_challenges(public_polynomial, degree, challenges, challenge_range)
+    }
+
+    // Create a verifier asking known challenges
+    pub(crate) fn from_challenges(
+        public_polynomial: UnencryptedPolynomial,
+        degree: usize,
+        challenges: Vec<i64>,
+        challenge_range: i64,
+    ) -> Result<Self, Error> {
+        if challenges.is_empty() {
+            return Err(Error::NoChallenges);
+        }
+        if challenge_range < 1 {
+            return Err(Error::InvalidChallengeRange(challenge_range));
+        }
+        Ok(Self {
+            public_polynomial,
+            degree,
+            challenges,
+            challenge_range,
+        })
+    }
+
+    /// Get the challenges the prover must answer
+    pub fn get_challenges(&self) -> &[i64] {
+        &self.challenges
+    }
+
+    /// Verify the prover answered every challenge correctly
+    pub fn verify_responses(&self, responses: &[UnencryptedChallengeResponse]) -> bool {
+        responses.len() == self.challenges.len()
+            && responses
+                .iter()
+                .zip(self.challenges.iter())
+                .all(|(response, &x)| response.verify(x, &self.public_polynomial))
+    }
+
+    /// Get the soundness error the repeated challenges give
+    pub fn soundness_report(&self) -> SoundnessReport {
+        SoundnessReport {
+            rounds: self.challenges.len(),
+            challenge_range: self.challenge_range,
+            degree: self.degree,
+        }
+    }
+
+    /// Account for the verifier's challenges as if they were derived from `source`, checked
+    /// against the soundness target of `profile`
+    pub fn entropy_ledger(&self, source: ChallengeSource, profile: Profile) -> EntropyLedger {
+        let record = ChallengeRecord {
+            entropy_bits: (self.challenge_range as f64).log2(),
+            soundness_bits: -self.soundness_report().round_error().log2(),
+        };
+        let mut ledger = EntropyLedger::new(source, profile);
+        for _ in &self.challenges {
+            ledger.record(UNENCRYPTED_PROTOCOL_LABEL, record);
+        }
+        ledger
+    }
+}
+
+// Provers can compute h(x) after seeing the challenge, so the protocol is unsound
+impl ProofScheme for UnencryptedChallengeResponse {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Educational;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,4 +198,94 @@ mod tests {
         assert!(polynomial_response_two.verify(challenge_two, &challenge_polynomial));
         assert!(polynomial_response_three.verify(challenge_three, &challenge_polynomial));
     }
+
+    #[test]
+    fn test_repeated_challenges_catch_provers_passing_single_challenges() {
+        let roots = vec![
+            SimpleRoot::new(1, 2).unwrap(),
+            SimpleRoot::new(3, 6).unwrap(),
+            SimpleRoot::new(2, 4).unwrap(),
+        ];
+        let polynomial = UnencryptedPolynomial::new(roots).set_public_roots(2);
+        let public_polynomial = polynomial.get_public_polynomial().unwrap();
+        let verifier = |challenges: Vec<i64>| {
+            RepeatedChallengeVerifier::from_challenges(
+                public_polynomial.clone(),
+                3,
+                challenges,
+                100,
+            )
+            .unwrap()
+        };
+
+        // The honest prover answers every challenge
+        let honest_verifier = verifier(vec![1, 4, 9, 50]);
+        let honest_responses: Vec<_> = honest_verifier
+            .get_challenges()
+            .iter()
+            .map(|x| polynomial.answer_challenge(*x))
+            .collect();
+        assert!(honest_verifier.verify_responses(&honest_responses));
+
+        // A cheater with p(x) = (x + 2)(x + 5)(2x + 4), which t(x) = 3(x + 2)^2 doesn't divide,
+        // computes h(x) = p(x) / t(x) after seeing x and passes whenever 3 divides 2(x + 5)
+        let cheater = UnencryptedPolynomial::new(vec![
+            SimpleRoot::new(1, 2).unwrap(),
+            SimpleRoot::new(1, 5).unwrap(),
+            SimpleRoot::new(2, 4).unwrap(),
+        ]);
+        let cheat = |x: &i64| {
+            let px = cheater.eval(*x);
+            UnencryptedChallengeResponse::new(px, px / public_polynomial.eval(*x))
+        };
+        let passing = (0..100).filter(|x| cheat(x).verify(*x, &public_polynomial));
+        assert_eq!(passing.count(), 33);
+        assert!(verifier(vec![4]).verify_responses(&[cheat(&4)]));
+        assert!(!verifier(vec![4, 7, 9]).verify_responses(&[cheat(&4), cheat(&7), cheat(&9)]));
+
+        // A prover committed to fixed polynomials would only pass 3 in 100 challenges, which is why
+        // the adaptive cheater above is out of scope of the report
+        let report = verifier(vec![1, 4, 9, 50]).soundness_report();
+        assert_eq!(report.round_error(), 0.03);
+        assert!((report.total_error() - 0.03f64.powi(4)).abs() < 1e-15);
+        assert!(report.security_bits() > 20.0 && report.security_bits() < 21.0);
+        assert_eq!(
+            RepeatedChallengeVerifier::new(public_polynomial.clone(), 3, 0, 100).err(),
+            Some(Error::NoChallenges)
+        );
+    }
+
+    #[test]
+    fn test_tiny_challenge_ranges_are_flagged_as_grindable() {
+        let polynomial = UnencryptedPolynomial::new(vec![
+            SimpleRoot::new(1, 2).unwrap(),
+            SimpleRoot::new(3, 6).unwrap(),
+            SimpleRoot::new(2, 4).unwrap(),
+        ])
+        .set_public_roots(2);
+        let verifier = RepeatedChallengeVerifier::new(
+            polynomial.get_public_polynomial().unwrap(),
+            4,
+            20,
+            1 << 12,
+        )
+        .unwrap();
+
+        // 20 rounds which each fail a cheater with probability 1 - 2^-10 give 200 bits against a
+        // live verifier, but grinding them one at a time takes only 20 * 2^10 attempts
+        let interactive =
+            verifier.entropy_ledger(ChallengeSource::Interactive, Profile::Standard128);
+        assert_eq!(
+            interactive.challenge_bits(UNENCRYPTED_PROTOCOL_LABEL),
+            240.0
+        );
+        assert_eq!(interactive.effective_soundness_bits(), Some(200.0));
+        assert!(interactive.grindable().is_empty());
+
+        let fiat_shamir =
+            verifier.entropy_ledger(ChallengeSource::FiatShamir, Profile::Standard128);
+        let report = fiat_shamir.grindable()[0];
+        assert_eq!(report.challenges, 20);
+        assert!((report.effective_soundness_bits - (10.0 + 20f64.log2())).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,157 @@
+//! Coefficient sources allowing provers to read polynomials lazily from memory-mapped files
+
+use crate::error::Error;
+use bls12_381::Scalar;
+use memmap2::Mmap;
+use std::{fs::File, io::Write, path::Path};
+
+// Provers in this crate take polynomials as a `&[Scalar]` which must be held in memory in full.
+// For very large circuits the witness polynomials alone may not fit into memory, even though the
+// prover only ever looks at one coefficient at a time: committing multiplies each coefficient with
+// its reference string point, and opening divides by (x - z) with a single pass from the highest
+// coefficient down.
+//
+// A `CoefficientSource` hands out coefficients by index instead. Coefficient files store each
+// coefficient as its canonical 32 byte little endian encoding, so a memory-mapped file can serve
+// any coefficient without parsing the rest and the operating system pages the file in and out as
+// the prover walks through it. Coefficients are validated as they are read, since a file is not
+// guaranteed to hold canonical field elements.
+
+/// Length of an encoded coefficient
+pub const COEFFICIENT_LEN: usize = 32;
+
+/// Polynomial whose coefficients, in ascending order of degree, can be read one at a time
+pub trait CoefficientSource {
+    /// Number of coefficients
+    fn num_coefficients(&self) -> usize;
+
+    /// Read the coefficient at an index
+    fn coefficient(&self, index: usize) -> Result<Scalar, Error>;
+}
+
+impl CoefficientSource for [Scalar] {
+    fn num_coefficients(&self) -> usize {
+        self.len()
+    }
+
+    fn coefficient(&self, index: usize) -> Result<Scalar, Error> {
+        self.get(index)
+            .copied()
+            .ok_or(Error::CoefficientOutOfRange(index))
+    }
+}
+
+/// Coefficients read on demand from a memory-mapped coefficient file
+#[derive(Debug)]
+pub struct MappedCoefficients {
+    mmap: Mmap,
+}
+
+impl MappedCoefficients {
+    /// Map a coefficient file into memory without reading it
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path).map_err(|e| Error::InvalidWitnessFile(e.to_string()))?;
+        // SAFETY: The mapping is only read from. Coefficient files must not be modified while they
+        // are mapped, which is the same requirement as for any prover input.
+        let mmap =
+            unsafe { Mmap::map(&file) }.map_err(|e| Error::InvalidWitnessFile(e.to_string()))?;
+        if mmap.len() % COEFFICIENT_LEN != 0 {
+            return Err(Error::InvalidWitnessFile(format!(
+                "length {} is not a multiple of {}",
+                mmap.len(),
+                COEFFICIENT_LEN
+            )));
+        }
+        Ok(Self { mmap })
+    }
+}
+
+impl CoefficientSource for MappedCoefficients {
+    fn num_coefficients(&self) -> usize {
+        self.mmap.len() / COEFFICIENT_LEN
+    }
+
+    fn coefficient(&self, index: usize) -> Result<Scalar, Error> {
+        let start = index * COEFFICIENT_LEN;
+        let bytes = self
+            .mmap
+            .get(start..start + COEFFICIENT_LEN)
+            .ok_or(Error::CoefficientOutOfRange(index))?;
+        let mut encoded = [0; COEFFICIENT_LEN];
+        encoded.copy_from_slice(bytes);
+        Option::from(Scalar::from_bytes(&encoded)).ok_or(Error::InvalidCoefficient(index))
+    }
+}
+
+/// Write coefficients in ascending order of degree in the coefficient file format
+pub fn write_coefficients<W: Write, I: IntoIterator<Item = Scalar>>(
+    writer: &mut W,
+    coefficients: I,
+) -> Result<(), Error> {
+    for coefficient in coefficients {
+        writer
+            .write_all(&coefficient.to_bytes())
+            .map_err(|e| Error::InvalidWitnessFile(e.to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kzg::KzgSetup;
+    use std::io::BufWriter;
+
+    // Write coefficients to a fresh file in the temporary directory
+    fn write_test_file(name: &str, coefficients: &[Scalar]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let mut writer = BufWriter::new(File::create(&path).unwrap());
+        write_coefficients(&mut writer, coefficients.iter().copied()).unwrap();
+        writer.flush().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_mapped_coefficients_commit_and_open_like_in_memory_ones() {
+        let coefficients: Vec<Scalar> = (1..=16u64).map(|i| Scalar::from(i * i)).collect();
+        let path = write_test_file("mapped-coefficients", &coefficients);
+        let mapped = MappedCoefficients::open(&path).unwrap();
+        assert_eq!(mapped.num_coefficients(), 16);
+
+        let setup = KzgSetup::new(16);
+        let commitment = setup.commit_source(&mapped).unwrap();
+        assert_eq!(commitment, setup.commit(&coefficients).unwrap());
+        let point = Scalar::from(9u64);
+        let opening = setup.open_source(&mapped, &point).unwrap();
+        assert_eq!(opening, setup.open(&coefficients, &point).unwrap());
+        assert!(setup.verify_opening(&commitment, &point, &opening));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_malformed_coefficient_files_are_rejected() {
+        let path = write_test_file("malformed-coefficients", &[Scalar::one()]);
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+
+        // The field modulus isn't a canonical encoding of any coefficient
+        let mut modulus = (-Scalar::one()).to_bytes();
+        modulus[0] += 1;
+        file.write_all(&modulus).unwrap();
+        let mapped = MappedCoefficients::open(&path).unwrap();
+        assert_eq!(mapped.coefficient(0), Ok(Scalar::one()));
+        assert_eq!(mapped.coefficient(1), Err(Error::InvalidCoefficient(1)));
+        assert_eq!(mapped.coefficient(2), Err(Error::CoefficientOutOfRange(2)));
+        drop(mapped);
+
+        // Files holding a partial coefficient can't be mapped
+        file.write_all(&[0; 5]).unwrap();
+        assert!(matches!(
+            MappedCoefficients::open(&path),
+            Err(Error::InvalidWitnessFile(_))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+//! Single entry point to the stable API of the zk-counterparty workspace
+
+// The workspace is split into crates by the math they implement: `encodings` for commitments,
+// byte encodings and security policies, `merlin-example` for sigma protocols over Ristretto and
+// `zksnarks-example` for pairing based proofs. Their names and module layouts still change as the
+// project grows, so downstream users depend on this crate instead and import from modules grouped
+// by what they are used for:
+//
+// 1. `commitments`: Pedersen and polynomial commitments and the fixed point data they hold.
+// 2. `proofs`: the proofs a prover creates and the transcripts they are created with.
+// 3. `envelopes`: wire formats of proofs and the certificates and logs exchanged about them.
+// 4. `verifier`: verifiers of pairing based proofs and the reasons they reject proofs.
+// 5. `policy`: security levels and profiles deciding which proofs may be used.
+//
+// The `prelude` imports the traits and types most protocols need at once. Sigma protocols are
+// behind the `sigma` feature and pairing based proofs behind the `snark` feature, both enabled
+// by default, so users of only one of them don't build the other's dependencies.
+
+/// Scalars and points of the curves proofs are made over
+pub mod curves {
+    pub use encodings::SecretScalar;
+
+    /// Ristretto group over Curve25519, used by the sigma protocols
+    #[cfg(feature = "sigma")]
+    pub mod ristretto {
+        pub use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+    }
+
+    /// Pairing friendly curve BLS12-381, used by the pairing based proofs
+    #[cfg(feature = "snark")]
+    pub mod bls12_381 {
+        pub use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+    }
+}
+
+/// Commitments to values, vectors and polynomials
+pub mod commitments {
+    pub use encodings::{
+        Bls12G1, CommitmentGroup, CommitmentLayout, FeatureSchema, FeatureSpec, FixedPoint,
+        PedersenCommitment, QuantizedVector, Ristretto,
+    };
+
+    #[cfg(feature = "sigma")]
+    pub use merlin_example::{BlindingProof, PedersenGenerators};
+
+    #[cfg(feature = "snark")]
+    pub use zksnarks_example::{KzgCommitment, KzgOpening, KzgSetup};
+}
+
+/// Proofs and the transcripts they are created with
+pub mod proofs {
+    #[cfg(feature = "sigma")]
+    pub use merlin::Transcript;
+
+    #[cfg(feature = "sigma")]
+    pub use merlin_example::{
+        AggregatedSchnorrProof, ChallengeOracle, DleqProof, FixedRangeProof, ProfiledTranscript,
+        RangeProof, SchnorrProof, SetMembershipProof, SigmaProof, SigmaProtocol,
+        SimpleSchnorrProof, SUPPORTED_RANGE_BITS,
+    };
+
+    #[cfg(feature = "sigma")]
+    pub use merlin_example::{
+        DkgBroadcast, DkgParticipant, EscrowCommitment, KeyShare, PartialThresholdResponse,
+        ThresholdKeyShare, ThresholdPublicKey, ThresholdSigner,
+    };
+
+    #[cfg(feature = "snark")]
+    pub use zksnarks_example::{
+        EvaluationProof, EvaluationStatement, PairingProverTranscript, Polynomial,
+        ProverTranscript, Root,
+    };
+}
+
+/// Wire formats of proofs and the certificates and logs exchanged about them
+pub mod envelopes {
+    pub use encodings::{Endianness, PointEncoding, ScalarEncoding};
+
+    #[cfg(feature = "post-quantum")]
+    pub use encodings::{ArchiveSignature, ArchiveSigningKey, ArchiveVerifyingKey};
+
+    #[cfg(feature = "sigma")]
+    pub use merlin_example::{
+        AuditLog, AuditTranscript, RecomputableProof, RecomputationCertificate,
+        VerificationReceipt, CERTIFICATE_LEN, SCHNORR_PROOF_LEN,
+    };
+
+    #[cfg(feature = "snark")]
+    pub use zksnarks_example::{KZG_G2_POINT_LEN, KZG_POINT_LEN};
+}
+
+/// Verifiers of pairing based proofs and the reasons they reject proofs
+#[cfg(feature = "snark")]
+pub mod verifier {
+    pub use zksnarks_example::{
+        AmortizedVerifier, PairingVerifierTranscript, VerificationError, VerifierLimits,
+        VerifierTranscript,
+    };
+}
+
+/// Security levels and profiles deciding which proofs may be used
+pub mod policy {
+    pub use encodings::{Profile, ProofScheme, SecurityLevel, SecurityPolicy};
+}
+
+/// Traits and types most protocols need
+pub mod prelude {
+    pub use crate::{
+        commitments::CommitmentGroup,
+        envelopes::{PointEncoding, ScalarEncoding},
+        policy::{Profile, ProofScheme, SecurityLevel, SecurityPolicy},
+    };
+
+    #[cfg(feature = "sigma")]
+    pub use crate::{
+        commitments::PedersenGenerators,
+        envelopes::RecomputableProof,
+        proofs::{ChallengeOracle, SchnorrProof, Transcript},
+    };
+
+    #[cfg(feature = "snark")]
+    pub use crate::{
+        proofs::Polynomial,
+        verifier::{VerificationError, VerifierTranscript},
+    };
+}
+
+pub use encodings::Error as EncodingError;
+
+#[cfg(feature = "sigma")]
+pub use merlin_example::Error as SigmaError;
+
+#[cfg(feature = "snark")]
+pub use zksnarks_example::Error as SnarkError;
+
+#[cfg(all(test, any(feature = "sigma", feature = "snark")))]
+mod tests {
+    use super::prelude::*;
+
+    #[cfg(feature = "sigma")]
+    #[test]
+    fn test_sigma_proofs_are_usable_through_the_facade() {
+        use super::{curves::ristretto::Scalar, proofs::FixedRangeProof};
+
+        let private_key = Scalar::random(&mut rand::rngs::OsRng);
+        let proof =
+            SchnorrProof::generate_proof(&private_key, &mut SchnorrProof::create_new_transcript());
+        let public_key = private_key * curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+        assert!(proof
+            .verify_proof(&public_key, &mut SchnorrProof::create_new_transcript())
+            .is_ok());
+        assert!(SecurityPolicy::default()
+            .check_scheme::<SchnorrProof>()
+            .is_ok());
+
+        let generators = PedersenGenerators::default();
+        let blinding = Scalar::random(&mut rand::rngs::OsRng);
+        let commitment = generators.commit(&Scalar::from(42u64), &blinding);
+        let range_proof = FixedRangeProof::<8>::generate_proof(
+            42,
+            &blinding,
+            &generators,
+            &mut Transcript::new(b"facade"),
+        )
+        .unwrap();
+        assert!(range_proof
+            .verify_proof(&commitment, &generators, &mut Transcript::new(b"facade"))
+            .is_ok());
+    }
+
+    #[cfg(feature = "snark")]
+    #[test]
+    fn test_snark_verification_errors_are_usable_through_the_facade() {
+        use super::proofs::{ProverTranscript, Root};
+
+        let roots = [(1, 2), (3, 6), (2, 4)].map(|root| Root::try_from(root).unwrap());
+        let polynomial = Polynomial::new(roots.to_vec(), 2).unwrap();
+        let verifier = VerifierTranscript::new(&polynomial);
+        assert!(verifier
+            .verify_proof(&polynomial.generate_response(&verifier))
+            .is_ok());
+
+        // The encrypted zkSNARK is educational, so the default policy refuses it
+        assert!(SecurityPolicy::default()
+            .check_scheme::<ProverTranscript>()
+            .is_err());
+        let other_roots = [(1, 2), (1, 5), (1, 3)].map(|root| Root::try_from(root).unwrap());
+        let other = Polynomial::new(other_roots.to_vec(), 2).unwrap();
+        assert_eq!(
+            verifier.verify_proof(&other.generate_response(&verifier)),
+            Err(VerificationError::PairingCheck)
+        );
+    }
+}